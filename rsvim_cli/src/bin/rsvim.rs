@@ -54,6 +54,9 @@ fn main() -> IoResult<()> {
     // Initialize user config.
     event_loop.init_config()?;
 
+    // Initialize command-line history.
+    event_loop.init_history()?;
+
     // Finish initialize terminal.
     event_loop.init_tui()?;
 
@@ -67,6 +70,9 @@ fn main() -> IoResult<()> {
     // Run loop.
     event_loop.run().await?;
 
+    // Save command-line history.
+    event_loop.save_history()?;
+
     // Shutdown terminal raw mode.
     event_loop.shutdown_tui()
   })
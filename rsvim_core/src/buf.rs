@@ -1,27 +1,40 @@
 //! Vim buffers.
 
 use crate::prelude::*;
+use crate::state::autocmd::{AutoCmdEvent, FiredEvent};
+use crate::terminal::Terminal;
 
 use opt::*;
+use selection::Selection;
 use text::Text;
 
 use path_absolutize::Absolutize;
 use ropey::{Rope, RopeBuilder};
 use std::collections::BTreeMap;
 use std::fs::Metadata;
-use std::io::Read;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::sync::atomic::{AtomicI32, Ordering};
 use std::time::Instant;
 use tracing::trace;
 
+pub mod filetype;
 pub mod opt;
+pub mod selection;
 pub mod text;
+pub mod text_object;
 pub mod unicode;
 
+#[cfg(test)]
+mod filetype_tests;
 #[cfg(test)]
 mod opt_tests;
 #[cfg(test)]
+mod selection_tests;
+#[cfg(test)]
+mod text_object_tests;
+#[cfg(test)]
 mod text_tests;
 #[cfg(test)]
 mod unicode_tests;
@@ -55,6 +68,16 @@ pub struct Buffer {
   absolute_filename: Option<PathBuf>,
   metadata: Option<Metadata>,
   last_sync_time: Option<Instant>,
+
+  // The `changedtick` of the text when it is read from or written to the file.
+  saved_changedtick: usize,
+
+  // The visual selection, and the last visual selection (i.e. `gv`, the `'<` and `'>` marks).
+  selection: Option<Selection>,
+  last_selection: Option<Selection>,
+
+  // The terminal emulator of the terminal buffer, i.e. `:terminal`.
+  terminal: Option<Terminal>,
 }
 
 arc_mutex_ptr!(Buffer);
@@ -79,6 +102,10 @@ impl Buffer {
       absolute_filename,
       metadata,
       last_sync_time,
+      saved_changedtick: 0,
+      selection: None,
+      last_selection: None,
+      terminal: None,
     }
   }
 
@@ -133,8 +160,153 @@ impl Buffer {
   pub fn set_last_sync_time(&mut self, last_sync_time: Option<Instant>) {
     self.last_sync_time = last_sync_time;
   }
+
+  /// Whether the text is changed after it is read from or written to the file, i.e. the
+  /// 'modified' option. The terminal buffer is never modified.
+  pub fn is_modified(&self) -> bool {
+    !self.is_terminal() && self.text.changedtick() != self.saved_changedtick
+  }
+
+  /// The name shown in messages, i.e. the file name, or `[No Name]` for the unnamed buffer.
+  pub fn name(&self) -> String {
+    match &self.filename {
+      Some(filename) => filename.to_string_lossy().to_string(),
+      None => "[No Name]".to_string(),
+    }
+  }
+
+  /// The file type detected from the file name, i.e. `rust` for `*.rs`. It is empty if the
+  /// buffer is unnamed or the file type is unknown.
+  pub fn filetype(&self) -> &'static str {
+    match &self.filename {
+      Some(filename) if !self.is_terminal() => filetype::detect(filename),
+      _ => "",
+    }
+  }
+
+  /// Write the text to the file, the buffer is not modified after it.
+  ///
+  /// # Returns
+  ///
+  /// It returns the written bytes if successful, otherwise it returns the error.
+  ///
+  /// # Panics
+  ///
+  /// If the buffer is unnamed.
+  ///
+  /// NOTE: This is a primitive API.
+  pub fn write(&mut self) -> IoResult<usize> {
+    debug_assert!(self.absolute_filename.is_some());
+    let filename = self.absolute_filename.clone().unwrap();
+    let mut fp = std::fs::File::create(&filename)?;
+    let mut bytes = 0_usize;
+    for chunk in self.text.rope().chunks() {
+      fp.write_all(chunk.as_bytes())?;
+      bytes += chunk.len();
+    }
+    fp.flush()?;
+    trace!("Write {} bytes to file {:?}", bytes, filename);
+
+    self.metadata = fp.metadata().ok();
+    self.last_sync_time = Some(Instant::now());
+    self.saved_changedtick = self.text.changedtick();
+    Ok(bytes)
+  }
+
+  /// The visual selection, it is `None` if not in visual mode.
+  pub fn selection(&self) -> &Option<Selection> {
+    &self.selection
+  }
+
+  pub fn selection_mut(&mut self) -> &mut Option<Selection> {
+    &mut self.selection
+  }
+
+  /// Start the visual selection.
+  pub fn set_selection(&mut self, selection: Option<Selection>) {
+    self.selection = selection;
+  }
+
+  /// Stop the visual selection, and save it as the last selection.
+  pub fn take_selection(&mut self) -> Option<Selection> {
+    let selection = self.selection.take();
+    if selection.is_some() {
+      self.last_selection = selection;
+    }
+    selection
+  }
+
+  /// The last visual selection, i.e. `gv`.
+  pub fn last_selection(&self) -> &Option<Selection> {
+    &self.last_selection
+  }
+
+  /// Get the `(line_idx, char_idx)` of the mark, only the `'<` and `'>` marks are supported now.
+  pub fn mark(&self, name: char) -> Option<(usize, usize)> {
+    match (name, self.last_selection.as_ref()) {
+      ('<', Some(selection)) => Some(selection.start()),
+      ('>', Some(selection)) => Some(selection.end()),
+      _ => None,
+    }
+  }
 }
 
+// Terminal {
+
+impl Buffer {
+  /// The terminal emulator, if it is a terminal buffer.
+  pub fn terminal(&self) -> &Option<Terminal> {
+    &self.terminal
+  }
+
+  pub fn terminal_mut(&mut self) -> &mut Option<Terminal> {
+    &mut self.terminal
+  }
+
+  /// Whether it is a terminal buffer.
+  pub fn is_terminal(&self) -> bool {
+    self.terminal.is_some()
+  }
+
+  /// Whether it is a terminal buffer, and the program is still running.
+  pub fn is_terminal_running(&self) -> bool {
+    self
+      .terminal
+      .as_ref()
+      .map(|terminal| terminal.is_running())
+      .unwrap_or(false)
+  }
+
+  /// Feed the program output to the terminal, the text is synced with the terminal screen.
+  ///
+  /// Returns the terminal cursor `(line_idx, char_idx)`, or `None` if it is not a terminal buffer.
+  pub fn terminal_feed(&mut self, bytes: &[u8]) -> Option<(usize, usize)> {
+    let terminal = self.terminal.as_mut()?;
+    Some(terminal.feed(bytes, &mut self.text))
+  }
+
+  /// Resize the terminal to the window size, the text is synced with the terminal screen.
+  ///
+  /// Returns the terminal cursor `(line_idx, char_idx)`, or `None` if it is not a terminal buffer.
+  pub fn terminal_resize(&mut self, size: U16Size) -> Option<(usize, usize)> {
+    let terminal = self.terminal.as_mut()?;
+    Some(terminal.resize(size, &mut self.text))
+  }
+
+  /// Mark the program of the terminal as exited with the `exit_code`.
+  ///
+  /// Returns the last line `(line_idx, char_idx)`, or `None` if it is not a terminal buffer.
+  pub fn terminal_exit(
+    &mut self,
+    exit_code: Option<i32>,
+  ) -> Option<(usize, usize)> {
+    let terminal = self.terminal.as_mut()?;
+    Some(terminal.exit(exit_code, &mut self.text))
+  }
+}
+
+// Terminal }
+
 #[derive(Debug, Clone)]
 /// The manager for all normal (file) buffers.
 ///
@@ -148,6 +320,9 @@ pub struct BuffersManager {
 
   // Global-local options for buffers.
   global_local_options: BufferLocalOptions,
+
+  // The buffer events (i.e. reading the file), they wait to be moved to the autocommands.
+  fired: Vec<FiredEvent>,
 }
 
 arc_mutex_ptr!(BuffersManager);
@@ -167,6 +342,7 @@ impl BuffersManager {
       global_local_options: BufferLocalOptionsBuilder::default()
         .build()
         .unwrap(),
+      fired: vec![],
     }
   }
 
@@ -214,29 +390,41 @@ impl BuffersManager {
       }
     };
 
-    let buf = if existed {
-      match self.edit_file(canvas_size, filename, &abs_filename) {
-        Ok(buf) => buf,
+    let buf = Buffer::_new(
+      *self.global_local_options(),
+      canvas_size,
+      Rope::new(),
+      Some(filename.to_path_buf()),
+      Some(abs_filename.clone()),
+      None,
+      None,
+    );
+    let buf_id = buf.id();
+    let buf = Buffer::to_arc(buf);
+    self.buffers.insert(buf_id, buf.clone());
+    self
+      .buffers_by_path
+      .insert(Some(abs_filename.clone()), buf.clone());
+
+    // The file is read after the buffer is created, between `BufReadPre` and `BufReadPost`.
+    if existed {
+      self.fire(AutoCmdEvent::BufReadPre, buf_id);
+      match self.read_file(filename) {
+        Ok((rope, metadata)) => {
+          let mut buf = lock!(buf);
+          buf.text = Text::new(*self.global_local_options(), canvas_size, rope);
+          buf.metadata = Some(metadata);
+          buf.last_sync_time = Some(Instant::now());
+        }
         Err(e) => {
+          // The buffer is not created, and neither are its events.
+          self.remove(&buf_id);
+          self.fired.retain(|fired| fired.buffer_id != Some(buf_id));
           return Err(e);
         }
       }
-    } else {
-      Buffer::_new(
-        *self.global_local_options(),
-        canvas_size,
-        Rope::new(),
-        Some(filename.to_path_buf()),
-        Some(abs_filename.clone()),
-        None,
-        None,
-      )
-    };
-
-    let buf_id = buf.id();
-    let buf = Buffer::to_arc(buf);
-    self.buffers.insert(buf_id, buf.clone());
-    self.buffers_by_path.insert(Some(abs_filename), buf);
+      self.fire(AutoCmdEvent::BufReadPost, buf_id);
+    }
     Ok(buf_id)
   }
 
@@ -272,6 +460,33 @@ impl BuffersManager {
     buf_id
   }
 
+  /// Create a terminal buffer for the `terminal`, the buffer name is the terminal name (i.e.
+  /// `term://1234:bash`), and it is not associated with any file.
+  ///
+  /// NOTE: This is a primitive API.
+  pub fn new_terminal_buffer(
+    &mut self,
+    canvas_size: U16Size,
+    terminal: Terminal,
+  ) -> BufferId {
+    let name = Some(PathBuf::from(terminal.name()));
+    let mut buf = Buffer::_new(
+      *self.global_local_options(),
+      canvas_size,
+      Rope::new(),
+      name.clone(),
+      name.clone(),
+      None,
+      None,
+    );
+    buf.terminal = Some(terminal);
+    let buf_id = buf.id();
+    let buf = Buffer::to_arc(buf);
+    self.buffers.insert(buf_id, buf.clone());
+    self.buffers_by_path.insert(name, buf);
+    buf_id
+  }
+
   #[cfg(debug_assertions)]
   /// NOTE: This API should only be used for testing.
   pub fn _add_buffer(&mut self, buf: BufferArc) -> BufferId {
@@ -307,13 +522,8 @@ impl BuffersManager {
     }
   }
 
-  // Implementation for [new_buffer_edit_file](new_buffer_edit_file).
-  fn edit_file(
-    &self,
-    canvas_size: U16Size,
-    filename: &Path,
-    absolute_filename: &Path,
-  ) -> IoResult<Buffer> {
+  // Read the file contents and metadata, see [`BuffersManager::new_file_buffer`].
+  fn read_file(&self, filename: &Path) -> IoResult<(Rope, Metadata)> {
     match std::fs::File::open(filename) {
       Ok(fp) => {
         let metadata = match fp.metadata() {
@@ -340,15 +550,7 @@ impl BuffersManager {
         );
         debug_assert!(bytes == buf.len());
 
-        Ok(Buffer::_new(
-          *self.global_local_options(),
-          canvas_size,
-          self.to_rope(&buf, buf.len()),
-          Some(filename.to_path_buf()),
-          Some(absolute_filename.to_path_buf()),
-          Some(metadata),
-          Some(Instant::now()),
-        ))
+        Ok((self.to_rope(&buf, buf.len()), metadata))
       }
       Err(e) => {
         trace!("Failed to open file {:?}:{:?}", filename, e);
@@ -370,8 +572,13 @@ impl BuffersManager {
     self.buffers.len()
   }
 
+  /// Remove the buffer, along with its file name.
   pub fn remove(&mut self, id: &BufferId) -> Option<BufferArc> {
-    self.buffers.remove(id)
+    let buf = self.buffers.remove(id)?;
+    self
+      .buffers_by_path
+      .retain(|_, other| !Arc::ptr_eq(other, &buf));
+    Some(buf)
   }
 
   pub fn get(&self, id: &BufferId) -> Option<&BufferArc> {
@@ -394,6 +601,18 @@ impl BuffersManager {
     self.buffers.iter()
   }
 
+  /// Find the buffer by the file name, `None` is the unnamed buffer.
+  pub fn find_by_filename(&self, filename: Option<&Path>) -> Option<BufferId> {
+    let abs_filename = match filename {
+      Some(filename) => Some(filename.absolutize().ok()?.to_path_buf()),
+      None => None,
+    };
+    self
+      .buffers_by_path
+      .get(&abs_filename)
+      .map(|buffer| lock!(buffer).id())
+  }
+
   pub fn first_key_value(&self) -> Option<(&BufferId, &BufferArc)> {
     self.buffers.first_key_value()
   }
@@ -404,6 +623,22 @@ impl BuffersManager {
 }
 // BTreeMap }
 
+// Events {
+
+impl BuffersManager {
+  /// Fire the buffer event.
+  pub fn fire(&mut self, event: AutoCmdEvent, buffer_id: BufferId) {
+    self.fired.push(FiredEvent::new(event, Some(buffer_id), ""));
+  }
+
+  /// Take the fired buffer events in the firing order.
+  pub fn take_fired(&mut self) -> Vec<FiredEvent> {
+    std::mem::take(&mut self.fired)
+  }
+}
+
+// Events }
+
 impl Default for BuffersManager {
   fn default() -> Self {
     BuffersManager::new()
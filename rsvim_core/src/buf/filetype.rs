@@ -0,0 +1,75 @@
+//! Detect the file type from the file name, i.e. `rust` for `*.rs`.
+//!
+//! See: <https://vimhelp.org/filetype.txt.html>.
+
+use std::path::Path;
+
+// The file types of the file name extensions.
+const EXTENSIONS: [(&str, &str); 38] = [
+  ("c", "c"),
+  ("h", "c"),
+  ("cc", "cpp"),
+  ("cpp", "cpp"),
+  ("cxx", "cpp"),
+  ("hpp", "cpp"),
+  ("cs", "cs"),
+  ("css", "css"),
+  ("go", "go"),
+  ("htm", "html"),
+  ("html", "html"),
+  ("java", "java"),
+  ("js", "javascript"),
+  ("cjs", "javascript"),
+  ("mjs", "javascript"),
+  ("jsx", "javascriptreact"),
+  ("json", "json"),
+  ("kt", "kotlin"),
+  ("lua", "lua"),
+  ("md", "markdown"),
+  ("markdown", "markdown"),
+  ("php", "php"),
+  ("py", "python"),
+  ("rb", "ruby"),
+  ("rs", "rust"),
+  ("scala", "scala"),
+  ("sh", "sh"),
+  ("bash", "sh"),
+  ("sql", "sql"),
+  ("swift", "swift"),
+  ("toml", "toml"),
+  ("ts", "typescript"),
+  ("mts", "typescript"),
+  ("tsx", "typescriptreact"),
+  ("txt", "text"),
+  ("vim", "vim"),
+  ("yaml", "yaml"),
+  ("yml", "yaml"),
+];
+
+// The file types of the whole file names.
+const FILENAMES: [(&str, &str); 4] = [
+  ("Makefile", "make"),
+  ("makefile", "make"),
+  ("Dockerfile", "dockerfile"),
+  ("CMakeLists.txt", "cmake"),
+];
+
+/// Detect the file type of the file name, returns the empty string if it is unknown.
+pub fn detect(filename: &Path) -> &'static str {
+  let name = filename
+    .file_name()
+    .map(|name| name.to_string_lossy())
+    .unwrap_or_default();
+  if let Some((_, filetype)) = FILENAMES.iter().find(|(n, _)| *n == name) {
+    return filetype;
+  }
+  let extension = filename
+    .extension()
+    .map(|extension| extension.to_string_lossy().to_lowercase())
+    .unwrap_or_default();
+  EXTENSIONS
+    .iter()
+    .find(|(e, _)| *e == extension)
+    .map(|(_, filetype)| *filetype)
+    .unwrap_or("")
+}
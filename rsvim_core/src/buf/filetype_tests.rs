@@ -0,0 +1,14 @@
+use super::filetype::*;
+
+use std::path::Path;
+
+#[test]
+fn detect1() {
+  assert_eq!(detect(Path::new("src/main.rs")), "rust");
+  assert_eq!(detect(Path::new("/tmp/index.TS")), "typescript");
+  assert_eq!(detect(Path::new("Makefile")), "make");
+  assert_eq!(detect(Path::new("a/CMakeLists.txt")), "cmake");
+  assert_eq!(detect(Path::new("notes.txt")), "text");
+  assert_eq!(detect(Path::new("README")), "");
+  assert_eq!(detect(Path::new("a.unknown")), "");
+}
@@ -7,14 +7,18 @@ use derive_builder::Builder;
 // Re-export
 pub use file_encoding::*;
 pub use file_format::*;
+pub use is_keyword::*;
 
 pub mod file_encoding;
 pub mod file_format;
+pub mod is_keyword;
 
 #[cfg(test)]
 mod file_encoding_tests;
 #[cfg(test)]
 mod file_format_tests;
+#[cfg(test)]
+mod is_keyword_tests;
 
 #[derive(Debug, Copy, Clone, Builder)]
 /// Local buffer options.
@@ -27,6 +31,9 @@ pub struct BufferLocalOptions {
 
   #[builder(default = defaults::buf::FILE_FORMAT)]
   file_format: FileFormatOption,
+
+  #[builder(default = defaults::buf::IS_KEYWORD)]
+  is_keyword: IsKeywordOption,
 }
 
 impl BufferLocalOptions {
@@ -63,6 +70,17 @@ impl BufferLocalOptions {
     self.file_format = value;
   }
 
+  /// Buffer 'is-keyword' option.
+  ///
+  /// See: <https://vimhelp.org/options.txt.html#%27iskeyword%27>.
+  pub fn is_keyword(&self) -> IsKeywordOption {
+    self.is_keyword
+  }
+
+  pub fn set_is_keyword(&mut self, value: IsKeywordOption) {
+    self.is_keyword = value;
+  }
+
   /// Get 'end-of-line' based on 'file-format' option.
   pub fn end_of_line(&self) -> EndOfLineOption {
     self.file_format.into()
@@ -75,6 +75,29 @@ impl Display for EndOfLineOption {
   }
 }
 
+impl EndOfLineOption {
+  /// Convert all the line breaks (`<CR><NL>`, `<NL>` or `<CR>`) in the text to this end-of-line,
+  /// i.e. the pasted text uses the line breaks of the buffer.
+  pub fn normalize(&self, text: &str) -> String {
+    let eol = self.to_string();
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+      match c {
+        '\r' => {
+          if chars.peek() == Some(&'\n') {
+            chars.next();
+          }
+          result.push_str(&eol);
+        }
+        '\n' => result.push_str(&eol),
+        _ => result.push(c),
+      }
+    }
+    result
+  }
+}
+
 impl TryFrom<&str> for EndOfLineOption {
   type Error = String;
 
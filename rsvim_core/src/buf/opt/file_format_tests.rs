@@ -5,3 +5,12 @@ fn display1() {
   let actual1 = format!("{}", FileFormatOption::Dos);
   assert_eq!(actual1, "dos");
 }
+
+#[test]
+fn normalize1() {
+  let text = "a\r\nb\nc\rd";
+  assert_eq!(EndOfLineOption::LF.normalize(text), "a\nb\nc\nd");
+  assert_eq!(EndOfLineOption::CRLF.normalize(text), "a\r\nb\r\nc\r\nd");
+  assert_eq!(EndOfLineOption::CR.normalize(text), "a\rb\rc\rd");
+  assert_eq!(EndOfLineOption::LF.normalize(""), "");
+}
@@ -0,0 +1,185 @@
+//! The "is-keyword" option for Vim buffer.
+
+use std::fmt::Display;
+use std::string::ToString;
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+/// The chars that are keyword chars, i.e. the word chars of `w`, `b`, `iw`, etc.
+///
+/// The option is a comma-separated list of parts, each part can be:
+///
+/// - `@`: All the alphabetic chars.
+/// - A single char, i.e. `_`, or its decimal number, i.e. `95`.
+/// - A range of chars, i.e. `a-z` or `48-57`.
+/// - A part starts with `^` excludes the chars, i.e. `^a-c`. A single `^` is the char itself.
+///
+/// It only decides the chars `0..=255`, the other chars are decided by their unicode classes, see
+/// [`char_class`](crate::buf::unicode::char_class).
+///
+/// See: <https://vimhelp.org/options.txt.html#%27iskeyword%27>.
+pub struct IsKeywordOption {
+  // The chars `0..=255` explicitly included, a bit for each char.
+  chars: [u64; 4],
+  // The `@` part, i.e. all the alphabetic chars `0..=255`.
+  alpha: bool,
+}
+
+impl IsKeywordOption {
+  /// Vim's default option `@,48-57,_,192-255`.
+  pub const fn vim_default() -> Self {
+    let mut value = Self {
+      chars: [0; 4],
+      alpha: true,
+    };
+    value = value._with_range(48, 57);
+    value = value._with_range(95, 95);
+    value._with_range(192, 255)
+  }
+
+  const fn _with_range(mut self, start: u32, end: u32) -> Self {
+    let mut c = start;
+    while c <= end {
+      self.chars[(c / 64) as usize] |= 1 << (c % 64);
+      c += 1;
+    }
+    self
+  }
+
+  fn _set(&mut self, c: u32, value: bool) {
+    if value {
+      self.chars[(c / 64) as usize] |= 1 << (c % 64);
+    } else {
+      self.chars[(c / 64) as usize] &= !(1 << (c % 64));
+    }
+  }
+
+  fn _get(&self, c: u32) -> bool {
+    self.chars[(c / 64) as usize] & (1 << (c % 64)) != 0
+  }
+
+  /// Whether the char `0..=255` is a keyword char, the other chars always return `false`.
+  pub fn contains(&self, c: char) -> bool {
+    let code = c as u32;
+    code < 256 && (self._get(code) || (self.alpha && c.is_alphabetic()))
+  }
+}
+
+// Parse the char or its decimal number.
+fn _parse_char(value: &str) -> Option<u32> {
+  if !value.is_empty() && value.chars().all(|c| c.is_ascii_digit()) {
+    return value.parse::<u32>().ok();
+  }
+  let mut chars = value.chars();
+  match (chars.next(), chars.next()) {
+    (Some(c), None) => Some(c as u32),
+    _ => None,
+  }
+}
+
+impl TryFrom<&str> for IsKeywordOption {
+  type Error = String;
+
+  fn try_from(value: &str) -> Result<Self, Self::Error> {
+    let mut result = Self {
+      chars: [0; 4],
+      alpha: false,
+    };
+
+    for part in value.split(',').filter(|part| !part.is_empty()) {
+      let (include, part) = match part.strip_prefix('^') {
+        Some(rest) if !rest.is_empty() => (false, rest),
+        _ => (true, part),
+      };
+
+      if part == "@" {
+        if include {
+          result.alpha = true;
+        } else {
+          // Materialize the alphabetic chars before excluding them.
+          result.alpha = false;
+          for code in 0..256_u32 {
+            if char::from_u32(code).is_some_and(|c| c.is_alphabetic()) {
+              result._set(code, false);
+            }
+          }
+        }
+        continue;
+      }
+
+      // The `-` after the first char is a range, i.e. `a-z`, `48-57`, `@-@`.
+      let first_len = part.chars().next().unwrap().len_utf8();
+      let (start, end) = match part[first_len..].find('-') {
+        Some(i) => {
+          let dash = first_len + i;
+          (_parse_char(&part[..dash]), _parse_char(&part[dash + 1..]))
+        }
+        None => {
+          let c = _parse_char(part);
+          (c, c)
+        }
+      };
+      let (start, end) = match (start, end) {
+        (Some(start), Some(end)) if start <= end && end < 256 => (start, end),
+        _ => return Err(format!("Invalid iskeyword part: {part}")),
+      };
+
+      if !include && result.alpha {
+        // Materialize the alphabetic chars before excluding any of them.
+        let excluded = (start..=end)
+          .any(|code| char::from_u32(code).is_some_and(|c| c.is_alphabetic()));
+        if excluded {
+          result.alpha = false;
+          for code in 0..256_u32 {
+            if char::from_u32(code).is_some_and(|c| c.is_alphabetic()) {
+              result._set(code, true);
+            }
+          }
+        }
+      }
+      for code in start..=end {
+        result._set(code, include);
+      }
+    }
+
+    Ok(result)
+  }
+}
+
+impl Display for IsKeywordOption {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let format_char = |code: u32| {
+      let c = char::from_u32(code).unwrap();
+      if c.is_ascii_graphic()
+        && !c.is_ascii_digit()
+        && !matches!(c, ',' | '^' | '-' | '@')
+      {
+        c.to_string()
+      } else {
+        code.to_string()
+      }
+    };
+
+    let mut parts = vec![];
+    if self.alpha {
+      parts.push("@".to_string());
+    }
+    let mut code = 0_u32;
+    while code < 256 {
+      if !self._get(code) {
+        code += 1;
+        continue;
+      }
+      let start = code;
+      while code + 1 < 256 && self._get(code + 1) {
+        code += 1;
+      }
+      if start == code {
+        parts.push(format_char(start));
+      } else {
+        parts.push(format!("{start}-{code}"));
+      }
+      code += 1;
+    }
+    write!(f, "{}", parts.join(","))
+  }
+}
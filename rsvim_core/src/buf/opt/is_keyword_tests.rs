@@ -0,0 +1,45 @@
+use super::is_keyword::*;
+
+#[test]
+fn default1() {
+  let value = IsKeywordOption::vim_default();
+  assert_eq!(value.to_string(), "@,48-57,_,192-255");
+  assert_eq!(IsKeywordOption::try_from("@,48-57,_,192-255"), Ok(value));
+
+  assert!(value.contains('a'));
+  assert!(value.contains('Z'));
+  assert!(value.contains('0'));
+  assert!(value.contains('_'));
+  assert!(value.contains('é'));
+  assert!(!value.contains('-'));
+  assert!(!value.contains(' '));
+  // Only decides the chars `0..=255`.
+  assert!(!value.contains('你'));
+}
+
+#[test]
+fn parse1() {
+  let value = IsKeywordOption::try_from("a-c,-,@-@,35").unwrap();
+  assert!(value.contains('b'));
+  assert!(value.contains('-'));
+  assert!(value.contains('@'));
+  assert!(value.contains('#'));
+  assert!(!value.contains('d'));
+  assert_eq!(value.to_string(), "#,45,64,97-99");
+
+  // Exclude.
+  let value = IsKeywordOption::try_from("@,^a-c,^").unwrap();
+  assert!(!value.contains('b'));
+  assert!(value.contains('d'));
+  assert!(value.contains('^'));
+
+  let value = IsKeywordOption::try_from("48-57,^50").unwrap();
+  assert!(value.contains('1'));
+  assert!(!value.contains('2'));
+
+  assert!(IsKeywordOption::try_from("").is_ok());
+  assert!(IsKeywordOption::try_from("z-a").is_err());
+  assert!(IsKeywordOption::try_from("1-").is_err());
+  assert!(IsKeywordOption::try_from("0-300").is_err());
+  assert!(IsKeywordOption::try_from("ab").is_err());
+}
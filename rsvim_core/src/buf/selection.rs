@@ -0,0 +1,219 @@
+//! Visual selection of buffer.
+//!
+//! See: <https://vimhelp.org/visual.txt.html>.
+
+use crate::buf::text::Text;
+
+use std::ops::{Range, RangeInclusive};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// Selection type.
+pub enum SelectionKind {
+  /// Charwise, i.e. `v`.
+  Char,
+  /// Linewise, i.e. `V`.
+  Line,
+  /// Blockwise, i.e. `<C-V>`.
+  Block,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// Visual selection, it is anchored on the buffer text with positions `(line_idx, char_idx)`.
+///
+/// The `anchor` is where the selection starts, the `cursor` is where the cursor is, and it moves
+/// with the cursor. Both ends are inclusive.
+pub struct Selection {
+  kind: SelectionKind,
+  anchor: (usize, usize),
+  cursor: (usize, usize),
+}
+
+impl Selection {
+  pub fn new(
+    kind: SelectionKind,
+    anchor: (usize, usize),
+    cursor: (usize, usize),
+  ) -> Self {
+    Self {
+      kind,
+      anchor,
+      cursor,
+    }
+  }
+
+  pub fn kind(&self) -> SelectionKind {
+    self.kind
+  }
+
+  pub fn set_kind(&mut self, kind: SelectionKind) {
+    self.kind = kind;
+  }
+
+  /// The `(line_idx, char_idx)` where the selection starts.
+  pub fn anchor(&self) -> (usize, usize) {
+    self.anchor
+  }
+
+  /// The `(line_idx, char_idx)` of the cursor.
+  pub fn cursor(&self) -> (usize, usize) {
+    self.cursor
+  }
+
+  pub fn set_cursor(&mut self, cursor: (usize, usize)) {
+    self.cursor = cursor;
+  }
+
+  /// Swap the anchor and the cursor, i.e. `o` in visual mode.
+  pub fn swap(&mut self) {
+    std::mem::swap(&mut self.anchor, &mut self.cursor);
+  }
+
+  /// Clamp both ends into the `text`, since the selected lines or chars can be deleted after the
+  /// selection is saved, i.e. `gv` after editing.
+  pub fn clamp(&self, text: &Text) -> Self {
+    let clamp = |(line_idx, char_idx): (usize, usize)| {
+      let line_idx =
+        std::cmp::min(line_idx, text.rope().len_lines().saturating_sub(1));
+      let char_idx = match text.last_char_on_line_no_eol(line_idx) {
+        Some(last_char) => std::cmp::min(char_idx, last_char),
+        None => 0,
+      };
+      (line_idx, char_idx)
+    };
+    Self::new(self.kind, clamp(self.anchor), clamp(self.cursor))
+  }
+
+  /// The first position of the selection, i.e. the `'<` mark.
+  pub fn start(&self) -> (usize, usize) {
+    std::cmp::min(self.anchor, self.cursor)
+  }
+
+  /// The last position of the selection, i.e. the `'>` mark.
+  pub fn end(&self) -> (usize, usize) {
+    std::cmp::max(self.anchor, self.cursor)
+  }
+
+  /// The selected lines.
+  pub fn lines(&self) -> RangeInclusive<usize> {
+    self.start().0..=self.end().0
+  }
+
+  /// The display columns `[left, right)` of blockwise selection.
+  pub fn block_columns(&self, text: &Text) -> Range<usize> {
+    let columns = |(line_idx, char_idx): (usize, usize)| {
+      let len_chars = text.rope().line(line_idx).len_chars();
+      let left = text.width_before(line_idx, char_idx);
+      let right = if char_idx < len_chars {
+        text.width_until(line_idx, char_idx)
+      } else {
+        left + 1
+      };
+      // Empty char (i.e. line break) still occupies 1 column.
+      (left, std::cmp::max(right, left + 1))
+    };
+    let (anchor_left, anchor_right) = columns(self.anchor);
+    let (cursor_left, cursor_right) = columns(self.cursor);
+    std::cmp::min(anchor_left, cursor_left)
+      ..std::cmp::max(anchor_right, cursor_right)
+  }
+
+  /// Whether the char at `(line_idx, char_idx)` is selected.
+  pub fn contains(
+    &self,
+    text: &Text,
+    line_idx: usize,
+    char_idx: usize,
+  ) -> bool {
+    if !self.lines().contains(&line_idx) {
+      return false;
+    }
+    match self.kind {
+      SelectionKind::Char => {
+        let pos = (line_idx, char_idx);
+        self.start() <= pos && pos <= self.end()
+      }
+      SelectionKind::Line => true,
+      SelectionKind::Block => {
+        let columns = self.block_columns(text);
+        let left = text.width_before(line_idx, char_idx);
+        let right = text.width_until(line_idx, char_idx);
+        left < columns.end && right > columns.start
+      }
+    }
+  }
+
+  /// The selected chars in absolute char indexes, i.e. the chars from [`Rope::line_to_char`].
+  ///
+  /// Charwise and linewise selection is a single range, blockwise selection is a range for each
+  /// line, the line breaks are not included in blockwise selection.
+  ///
+  /// [`Rope::line_to_char`]: ropey::Rope::line_to_char
+  pub fn char_ranges(&self, text: &Text) -> Vec<Range<usize>> {
+    let rope = text.rope();
+    let len_chars = rope.len_chars();
+    let absolute = |(line_idx, char_idx): (usize, usize)| {
+      std::cmp::min(rope.line_to_char(line_idx) + char_idx, len_chars)
+    };
+
+    match self.kind {
+      SelectionKind::Char => {
+        let start = absolute(self.start());
+        let end = std::cmp::min(absolute(self.end()) + 1, len_chars);
+        vec![Range { start, end }]
+      }
+      SelectionKind::Line => {
+        let start = rope.line_to_char(self.start().0);
+        let end = if self.end().0 + 1 < rope.len_lines() {
+          rope.line_to_char(self.end().0 + 1)
+        } else {
+          len_chars
+        };
+        vec![Range { start, end }]
+      }
+      SelectionKind::Block => {
+        let columns = self.block_columns(text);
+        self
+          .lines()
+          .filter(|line_idx| *line_idx < rope.len_lines())
+          .map(|line_idx| {
+            let line_start = rope.line_to_char(line_idx);
+            let len_chars = text
+              .last_char_on_line_no_eol(line_idx)
+              .map(|c| c + 1)
+              .unwrap_or(0);
+            let selected: Vec<usize> = (0..len_chars)
+              .filter(|char_idx| {
+                let left = text.width_before(line_idx, *char_idx);
+                let right = text.width_until(line_idx, *char_idx);
+                left < columns.end && right > columns.start
+              })
+              .collect();
+            match (selected.first(), selected.last()) {
+              (Some(first), Some(last)) => {
+                line_start + first..line_start + last + 1
+              }
+              _ => line_start..line_start,
+            }
+          })
+          .collect()
+      }
+    }
+  }
+
+  /// The selected text, blockwise selection joins lines with line break.
+  pub fn text(&self, text: &Text) -> String {
+    let rope = text.rope();
+    let ranges = self.char_ranges(text);
+    match self.kind {
+      SelectionKind::Block => ranges
+        .into_iter()
+        .map(|range| rope.slice(range).to_string())
+        .collect::<Vec<_>>()
+        .join("\n"),
+      _ => ranges
+        .into_iter()
+        .map(|range| rope.slice(range).to_string())
+        .collect(),
+    }
+  }
+}
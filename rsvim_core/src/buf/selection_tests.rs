@@ -0,0 +1,97 @@
+use super::selection::*;
+
+use crate::buf::opt::BufferLocalOptionsBuilder;
+use crate::buf::text::Text;
+use crate::coord::U16Size;
+use crate::test::log::init as test_log_init;
+
+use ropey::Rope;
+
+fn make_text(payload: &str) -> Text {
+  let opt = BufferLocalOptionsBuilder::default().build().unwrap();
+  Text::new(opt, U16Size::new(10, 10), Rope::from_str(payload))
+}
+
+#[test]
+fn start_end1() {
+  test_log_init();
+  let mut selection = Selection::new(SelectionKind::Char, (2, 3), (0, 5));
+  assert_eq!(selection.start(), (0, 5));
+  assert_eq!(selection.end(), (2, 3));
+  assert_eq!(selection.lines(), 0..=2);
+
+  selection.swap();
+  assert_eq!(selection.anchor(), (0, 5));
+  assert_eq!(selection.cursor(), (2, 3));
+  assert_eq!(selection.start(), (0, 5));
+  assert_eq!(selection.end(), (2, 3));
+}
+
+#[test]
+fn charwise1() {
+  test_log_init();
+  let text = make_text("hello\nworld\nfoo\n");
+  let selection = Selection::new(SelectionKind::Char, (1, 2), (0, 3));
+  assert_eq!(selection.char_ranges(&text), vec![3..9]);
+  assert_eq!(selection.text(&text), "lo\nwor");
+  assert!(selection.contains(&text, 0, 3));
+  assert!(selection.contains(&text, 1, 0));
+  assert!(selection.contains(&text, 1, 2));
+  assert!(!selection.contains(&text, 0, 2));
+  assert!(!selection.contains(&text, 1, 3));
+  assert!(!selection.contains(&text, 2, 0));
+}
+
+#[test]
+fn linewise1() {
+  test_log_init();
+  let text = make_text("hello\nworld\nfoo\n");
+  let selection = Selection::new(SelectionKind::Line, (0, 3), (1, 0));
+  assert_eq!(selection.char_ranges(&text), vec![0..12]);
+  assert_eq!(selection.text(&text), "hello\nworld\n");
+  assert!(selection.contains(&text, 1, 4));
+  assert!(!selection.contains(&text, 2, 0));
+
+  let selection = Selection::new(SelectionKind::Line, (2, 0), (2, 0));
+  assert_eq!(selection.text(&text), "foo\n");
+}
+
+#[test]
+fn blockwise1() {
+  test_log_init();
+  let text = make_text("hello\nab\nworld\n");
+  let selection = Selection::new(SelectionKind::Block, (0, 1), (2, 3));
+  assert_eq!(selection.block_columns(&text), 1..4);
+  assert_eq!(selection.char_ranges(&text), vec![1..4, 7..8, 10..13]);
+  assert_eq!(selection.text(&text), "ell\nb\norl");
+  assert!(selection.contains(&text, 1, 1));
+  assert!(!selection.contains(&text, 1, 0));
+  assert!(!selection.contains(&text, 2, 4));
+}
+
+#[test]
+fn blockwise2() {
+  test_log_init();
+  let text = make_text("a\tb\nabcdefghij\n");
+  // The tab occupies columns `[1, 9)`.
+  let selection = Selection::new(SelectionKind::Block, (1, 3), (1, 4));
+  assert_eq!(selection.block_columns(&text), 3..5);
+  let selection = Selection::new(SelectionKind::Block, (0, 1), (1, 3));
+  assert_eq!(selection.block_columns(&text), 1..9);
+  assert_eq!(selection.text(&text), "\t\nbcdefghi");
+}
+
+#[test]
+fn clamp1() {
+  test_log_init();
+  let text = make_text("hello\nab\n");
+  let selection = Selection::new(SelectionKind::Char, (0, 4), (5, 3));
+  let selection = selection.clamp(&text);
+  assert_eq!(selection.anchor(), (0, 4));
+  assert_eq!(selection.cursor(), (2, 0));
+
+  let selection = Selection::new(SelectionKind::Line, (1, 9), (1, 0));
+  let selection = selection.clamp(&text);
+  assert_eq!(selection.anchor(), (1, 1));
+  assert_eq!(selection.kind(), SelectionKind::Line);
+}
@@ -3,6 +3,7 @@
 use crate::buf::opt::BufferLocalOptions;
 use crate::buf::unicode;
 use crate::prelude::*;
+use crate::state::autocmd::next_event_seq;
 
 // Re-export
 pub use cidx::ColumnIndex;
@@ -12,6 +13,7 @@ use compact_str::{CompactString, ToCompactString};
 use lru::LruCache;
 use ropey::{Rope, RopeSlice};
 use std::cell::RefCell;
+use std::ops::Range;
 
 pub mod cidx;
 
@@ -24,6 +26,11 @@ pub struct Text {
   rope: Rope,
   cached_lines_width: RefCell<LruCache<usize, ColumnIndex, RandomState>>,
   options: BufferLocalOptions,
+  // The count of changes, i.e. `b:changedtick`.
+  changedtick: usize,
+  // The event sequence numbers of the changes that are not taken yet, the changes without other
+  // events between them are recorded once.
+  changes: Vec<u64>,
 }
 
 arc_mutex_ptr!(Text);
@@ -47,6 +54,8 @@ impl Text {
         RandomState::new(),
       )),
       options: opts,
+      changedtick: 0,
+      changes: vec![],
     }
   }
 }
@@ -90,9 +99,26 @@ impl Text {
   // operations to correctly reset internal cached display width.
   // and hide these details.
   fn rope_mut(&mut self) -> &mut Rope {
+    self.changedtick += 1;
+    let seq = next_event_seq();
+    match self.changes.last_mut() {
+      Some(last) if *last + 1 == seq => *last = seq,
+      _ => self.changes.push(seq),
+    }
     &mut self.rope
   }
 
+  /// The count of changes, it increases on every change of the text, i.e. `b:changedtick`.
+  pub fn changedtick(&self) -> usize {
+    self.changedtick
+  }
+
+  /// Take the event sequence numbers of the changes, they fire the `TextChanged` events, see
+  /// [`AutoCmds::fire_changes`](crate::state::autocmd::AutoCmds::fire_changes).
+  pub fn take_changes(&mut self) -> Vec<u64> {
+    std::mem::take(&mut self.changes)
+  }
+
   /// Similar with [`Rope::get_line`], but collect and clone a normal string with limited length,
   /// for performance reason when the line is too long to clone.
   pub fn clone_line(
@@ -222,6 +248,72 @@ impl Text {
 }
 // Rope }
 
+// Lines {
+impl Text {
+  /// Get the count of lines, the empty line after the eol at the end of text is not counted, i.e.
+  /// Vim's `line('$')`. An empty text has 1 empty line.
+  pub fn lines_count(&self) -> usize {
+    let len_lines = self.rope.len_lines();
+    if len_lines > 1
+      && self.rope.line_to_char(len_lines - 1) >= self.rope.len_chars()
+    {
+      len_lines - 1
+    } else {
+      len_lines
+    }
+  }
+
+  /// Get the count of chars on line, without the eol.
+  ///
+  /// It returns `0` if line not exists or line is empty.
+  pub fn line_len_chars_no_eol(&self, line_idx: usize) -> usize {
+    self
+      .last_char_on_line_no_eol(line_idx)
+      .map(|last_char| last_char + 1)
+      .unwrap_or(0)
+  }
+
+  /// Whether the position `(line_idx, char_idx)` is on a line, the char can be the eol (or the
+  /// end of the last line).
+  pub fn has_position(&self, (line_idx, char_idx): (usize, usize)) -> bool {
+    line_idx < self.lines_count()
+      && char_idx <= self.line_len_chars_no_eol(line_idx)
+  }
+
+  // The absolute char index of the position.
+  fn _absolute_char_idx(&self, (line_idx, char_idx): (usize, usize)) -> usize {
+    self.rope.line_to_char(line_idx) + char_idx
+  }
+
+  /// Get the lines in the range of line indexes, without the eols.
+  ///
+  /// # Panics
+  /// If the range doesn't exist, see [`Text::lines_count`].
+  pub fn get_lines(&self, range: Range<usize>) -> Vec<String> {
+    debug_assert!(range.start <= range.end);
+    debug_assert!(range.end <= self.lines_count());
+    range
+      .map(|line_idx| {
+        let len_chars = self.line_len_chars_no_eol(line_idx);
+        self.rope.line(line_idx).chars().take(len_chars).collect()
+      })
+      .collect()
+  }
+
+  /// Get the text between the positions `(line_idx, char_idx)`, the end is exclusive.
+  ///
+  /// # Panics
+  /// If the positions don't exist, see [`Text::has_position`].
+  pub fn get_text(&self, start: (usize, usize), end: (usize, usize)) -> String {
+    debug_assert!(self.has_position(start));
+    debug_assert!(self.has_position(end));
+    debug_assert!(start <= end);
+    let range = self._absolute_char_idx(start)..self._absolute_char_idx(end);
+    self.rope.slice(range).to_string()
+  }
+}
+// Lines }
+
 // Options {
 impl Text {
   pub fn options(&self) -> &BufferLocalOptions {
@@ -229,6 +321,10 @@ impl Text {
   }
 
   pub fn set_options(&mut self, options: &BufferLocalOptions) {
+    // The display width of `\t` depends on 'tab-stop' option.
+    if self.options.tab_stop() != options.tab_stop() {
+      self.clear_cached_lines();
+    }
     self.options = *options;
   }
 }
@@ -391,9 +487,8 @@ impl Text {
     self.cached_lines_width.borrow_mut().clear()
   }
 
-  #[allow(dead_code)]
-  /// Resize cache.
-  fn resize_cached_lines(&self, canvas_size: U16Size) {
+  /// Resize cache, i.e. the terminal is resized.
+  pub fn resize_cached_lines(&self, canvas_size: U16Size) {
     let new_cache_size = _cached_size(canvas_size);
     let mut cached_width = self.cached_lines_width.borrow_mut();
     if new_cache_size > cached_width.cap() {
@@ -663,6 +758,90 @@ impl Text {
     Some((cursor_line_idx_after_deleted, cursor_char_idx_after_deleted))
   }
 
+  /// Replace the absolute char `range` (i.e. the chars from [`Rope::line_to_char`]) with text
+  /// payload, the payload can be empty, i.e. only delete the range.
+  ///
+  /// # Returns
+  /// It returns the absolute char index after the inserted payload.
+  ///
+  /// # Panics
+  /// It panics if the range doesn't exist.
+  pub fn replace_range(&mut self, range: Range<usize>, payload: &str) -> usize {
+    debug_assert!(range.start <= range.end);
+    debug_assert!(range.end <= self.rope.len_chars());
+
+    let start_line_idx = self.rope.char_to_line(range.start);
+    if !range.is_empty() {
+      self.rope_mut().remove(range.clone());
+    }
+    if !payload.is_empty() {
+      self.rope_mut().insert(range.start, payload);
+    }
+    self
+      .retain_cached_lines(|line_idx, _column_idx| *line_idx < start_line_idx);
+
+    // Append eol at file end if it doesn't exist.
+    self.append_eol_at_end_if_not_exist();
+
+    range.start + payload.chars().count()
+  }
+
+  /// Replace the lines in the range of line indexes with the `lines` (without the eols), i.e. the
+  /// empty `lines` deletes the range, the empty range `n..n` inserts the `lines` before line `n`.
+  ///
+  /// # Panics
+  /// If the range doesn't exist, see [`Text::lines_count`].
+  pub fn set_lines(&mut self, range: Range<usize>, lines: &[String]) {
+    debug_assert!(range.start <= range.end);
+    debug_assert!(range.end <= self.lines_count());
+
+    let eol = self.options().end_of_line().to_compact_string();
+    let start = self.rope.line_to_char(range.start);
+    let end = self.rope.line_to_char(range.end);
+    let mut payload = String::new();
+    if !lines.is_empty() {
+      // Append after the last line that doesn't have the eol.
+      if start > 0
+        && start == self.rope.len_chars()
+        && !self._is_eol_on_whole_text(start - 1)
+      {
+        payload.push_str(&eol);
+      }
+      for line in lines {
+        payload.push_str(line);
+        payload.push_str(&eol);
+      }
+    }
+    self.replace_range(start..end, &payload);
+  }
+
+  /// Replace the text between the positions `(line_idx, char_idx)` with text payload, the end is
+  /// exclusive, i.e. the empty payload deletes the text.
+  ///
+  /// # Returns
+  /// It returns the position `(line_idx,char_idx)` after the inserted payload.
+  ///
+  /// # Panics
+  /// If the positions don't exist, see [`Text::has_position`].
+  pub fn set_text(
+    &mut self,
+    start: (usize, usize),
+    end: (usize, usize),
+    payload: &str,
+  ) -> (usize, usize) {
+    debug_assert!(self.has_position(start));
+    debug_assert!(self.has_position(end));
+    debug_assert!(start <= end);
+
+    let range = self._absolute_char_idx(start)..self._absolute_char_idx(end);
+    let absolute_char_idx = self.replace_range(range, payload);
+    let line_idx = self.rope.char_to_line(absolute_char_idx);
+    (
+      line_idx,
+      absolute_char_idx - self.rope.line_to_char(line_idx),
+    )
+  }
+
   /// Clear all text payload in current content.
   pub fn clear(&mut self) {
     self.rope_mut().remove(0..);
@@ -0,0 +1,678 @@
+//! Text objects, i.e. `iw`, `a(`, `it`, etc.
+//!
+//! A text object selects a piece of text around the cursor, it is used after an operator (i.e.
+//! `diw`) or in visual mode (i.e. `vi(`). The `i` (inner) objects select the text without white
+//! spaces or surroundings, the `a` objects include them.
+//!
+//! All the text objects are registered by their key in [`TextObjects`], more text objects can be
+//! registered by plugins, or a syntax tree in the future.
+//!
+//! See: <https://vimhelp.org/motion.txt.html#text-objects>.
+
+use crate::buf::selection::{Selection, SelectionKind};
+use crate::buf::text::Text;
+use crate::buf::unicode::{BLANK_CLASS, char_class};
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// The text object.
+pub trait TextObject: Debug + Send + Sync {
+  /// Select the text object around the `cursor` `(line_idx, char_idx)`, the `inner` is `i`
+  /// (`true`) or `a` (`false`), the `count` is the count of objects or the nested levels.
+  ///
+  /// It returns `None` if the text object is not found.
+  fn select(
+    &self,
+    text: &Text,
+    cursor: (usize, usize),
+    count: usize,
+    inner: bool,
+  ) -> Option<Selection>;
+}
+
+/// The text object reference.
+pub type TextObjectArc = Arc<dyn TextObject>;
+
+#[derive(Debug, Clone)]
+/// The text objects registry, it maps the key after `i`/`a` to the text object.
+pub struct TextObjects {
+  objects: HashMap<char, TextObjectArc>,
+}
+
+impl Default for TextObjects {
+  /// Create the registry with all the builtin text objects.
+  fn default() -> Self {
+    let mut objects = Self {
+      objects: HashMap::new(),
+    };
+    objects.register('w', Arc::new(WordObject::new(false)));
+    objects.register('W', Arc::new(WordObject::new(true)));
+    objects.register('s', Arc::new(SentenceObject::default()));
+    objects.register('p', Arc::new(ParagraphObject::default()));
+    for quote in ['"', '\'', '`'] {
+      objects.register(quote, Arc::new(QuoteObject::new(quote)));
+    }
+    let brackets = [
+      ('(', ')', ['(', ')', 'b']),
+      ('{', '}', ['{', '}', 'B']),
+      ('[', ']', ['[', ']', '[']),
+      ('<', '>', ['<', '>', '<']),
+    ];
+    for (open, close, keys) in brackets {
+      let object: TextObjectArc = Arc::new(BracketObject::new(open, close));
+      for key in keys {
+        objects.register(key, object.clone());
+      }
+    }
+    objects.register('t', Arc::new(TagObject::default()));
+    objects
+  }
+}
+
+impl TextObjects {
+  /// Register the text object with `key`, the old one is replaced and returned.
+  pub fn register(
+    &mut self,
+    key: char,
+    object: TextObjectArc,
+  ) -> Option<TextObjectArc> {
+    self.objects.insert(key, object)
+  }
+
+  /// Unregister the text object with `key`.
+  pub fn unregister(&mut self, key: char) -> Option<TextObjectArc> {
+    self.objects.remove(&key)
+  }
+
+  pub fn get(&self, key: char) -> Option<TextObjectArc> {
+    self.objects.get(&key).cloned()
+  }
+
+  pub fn contains(&self, key: char) -> bool {
+    self.objects.contains_key(&key)
+  }
+}
+
+// Position {
+
+// The absolute char index of the `(line_idx, char_idx)`.
+fn _absolute(text: &Text, (line_idx, char_idx): (usize, usize)) -> usize {
+  text.rope().line_to_char(line_idx) + char_idx
+}
+
+// The `(line_idx, char_idx)` of the absolute char index.
+fn _position(text: &Text, absolute: usize) -> (usize, usize) {
+  let line_idx = text.rope().char_to_line(absolute);
+  (line_idx, absolute - text.rope().line_to_char(line_idx))
+}
+
+// Charwise selection of the absolute char `range`, returns `None` if it is empty.
+fn _char_selection(text: &Text, range: Range<usize>) -> Option<Selection> {
+  if range.start >= range.end {
+    return None;
+  }
+  Some(Selection::new(
+    SelectionKind::Char,
+    _position(text, range.start),
+    _position(text, range.end - 1),
+  ))
+}
+
+// The chars on the line, without the eol.
+fn _line_chars(text: &Text, line_idx: usize) -> Vec<char> {
+  let len_chars = text
+    .last_char_on_line_no_eol(line_idx)
+    .map(|c| c + 1)
+    .unwrap_or(0);
+  text.rope().line(line_idx).chars().take(len_chars).collect()
+}
+
+// The lines in text, the empty last line after the eol at the end of text is not included.
+fn _len_lines(text: &Text) -> usize {
+  let rope = text.rope();
+  let len_lines = rope.len_lines();
+  if len_lines > 0 && rope.line_to_char(len_lines - 1) >= rope.len_chars() {
+    len_lines - 1
+  } else {
+    len_lines
+  }
+}
+
+// Position }
+
+// Runs {
+
+/// Select the runs of items in `0..len` around item `at`, the continuous items with the same class
+/// are a run, the class [`BLANK_CLASS`] is the white spaces.
+///
+/// - The inner object is `count` runs, i.e. the white spaces are also counted.
+/// - The `a` object is `count` runs with the trailing white spaces, or the leading white spaces if
+///   there are no trailing ones. If it starts from the white spaces, they are included and the run
+///   after them is selected.
+///
+/// It is shared by words (items are chars), sentences (items are sentences and the white spaces
+/// between them) and paragraphs (items are lines).
+///
+/// # Returns
+/// It returns the items range.
+pub fn select_runs<F>(
+  len: usize,
+  at: usize,
+  count: usize,
+  inner: bool,
+  class: F,
+) -> Range<usize>
+where
+  F: Fn(usize) -> usize,
+{
+  let run_start = |i: usize| {
+    let c = class(i);
+    let mut j = i;
+    while j > 0 && class(j - 1) == c {
+      j -= 1;
+    }
+    j
+  };
+  let run_end = |i: usize| {
+    let c = class(i);
+    let mut j = i;
+    while j < len && class(j) == c {
+      j += 1;
+    }
+    j
+  };
+
+  let mut start = run_start(at);
+  let mut end = start;
+  let mut trailing = false;
+  for _ in 0..std::cmp::max(count, 1) {
+    if end >= len {
+      break;
+    }
+    if inner {
+      end = run_end(end);
+    } else if class(end) == BLANK_CLASS {
+      end = run_end(end);
+      if end < len {
+        end = run_end(end);
+      }
+      trailing = false;
+    } else {
+      end = run_end(end);
+      trailing = end < len && class(end) == BLANK_CLASS;
+      if trailing {
+        end = run_end(end);
+      }
+    }
+  }
+
+  if !inner
+    && !trailing
+    && class(at) != BLANK_CLASS
+    && start > 0
+    && class(start - 1) == BLANK_CLASS
+  {
+    start = run_start(start - 1);
+  }
+  start..end
+}
+
+// Runs }
+
+// Word {
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The word (`iw`, `aw`) and WORD (`iW`, `aW`) text objects, they don't cross lines.
+pub struct WordObject {
+  big_word: bool,
+}
+
+impl WordObject {
+  pub fn new(big_word: bool) -> Self {
+    Self { big_word }
+  }
+}
+
+impl TextObject for WordObject {
+  fn select(
+    &self,
+    text: &Text,
+    cursor: (usize, usize),
+    count: usize,
+    inner: bool,
+  ) -> Option<Selection> {
+    let (line_idx, char_idx) = cursor;
+    let chars = _line_chars(text, line_idx);
+    if char_idx >= chars.len() {
+      return None;
+    }
+    let range = select_runs(chars.len(), char_idx, count, inner, |i| {
+      char_class(text.options(), chars[i], self.big_word)
+    });
+    let line_start = _absolute(text, (line_idx, 0));
+    _char_selection(text, line_start + range.start..line_start + range.end)
+  }
+}
+
+// Word }
+
+// Sentence {
+
+/// The sentences in the paragraph chars, a sentence ends with `.`, `!` or `?`, followed by any
+/// `)`, `]`, `"` and `'`, then a white space or the end of paragraph.
+pub fn sentences(chars: &[char]) -> Vec<Range<usize>> {
+  let mut sentences = vec![];
+  let mut i = 0;
+  while i < chars.len() {
+    while i < chars.len() && chars[i].is_whitespace() {
+      i += 1;
+    }
+    if i >= chars.len() {
+      break;
+    }
+    let start = i;
+    loop {
+      if i >= chars.len() {
+        break;
+      }
+      if matches!(chars[i], '.' | '!' | '?') {
+        let mut j = i + 1;
+        while j < chars.len() && matches!(chars[j], ')' | ']' | '"' | '\'') {
+          j += 1;
+        }
+        if j >= chars.len() || chars[j].is_whitespace() {
+          i = j;
+          break;
+        }
+      }
+      i += 1;
+    }
+    sentences.push(start..i);
+  }
+  sentences
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+/// The sentence text objects `is` and `as`, the sentences are in a paragraph, i.e. an empty line
+/// also ends the sentence.
+pub struct SentenceObject {}
+
+impl TextObject for SentenceObject {
+  fn select(
+    &self,
+    text: &Text,
+    cursor: (usize, usize),
+    count: usize,
+    inner: bool,
+  ) -> Option<Selection> {
+    let is_blank = |line_idx: usize| {
+      _line_chars(text, line_idx)
+        .iter()
+        .all(|c| c.is_whitespace())
+    };
+    let len_lines = _len_lines(text);
+    if cursor.0 >= len_lines || is_blank(cursor.0) {
+      return None;
+    }
+
+    // The paragraph around cursor, without the last eol.
+    let mut first_line = cursor.0;
+    while first_line > 0 && !is_blank(first_line - 1) {
+      first_line -= 1;
+    }
+    let mut last_line = cursor.0;
+    while last_line + 1 < len_lines && !is_blank(last_line + 1) {
+      last_line += 1;
+    }
+    let paragraph_start = _absolute(text, (first_line, 0));
+    let paragraph_end =
+      _absolute(text, (last_line, _line_chars(text, last_line).len()));
+    let chars: Vec<char> = text
+      .rope()
+      .slice(paragraph_start..paragraph_end)
+      .chars()
+      .collect();
+
+    // The items are the sentences and the white spaces between them.
+    let mut items: Vec<(Range<usize>, usize)> = vec![];
+    for (i, sentence) in sentences(&chars).into_iter().enumerate() {
+      let last = items.last().map(|(range, _)| range.end).unwrap_or(0);
+      if sentence.start > last {
+        items.push((last..sentence.start, BLANK_CLASS));
+      }
+      items.push((sentence, i + 1));
+    }
+    let last = items.last().map(|(range, _)| range.end).unwrap_or(0);
+    if chars.len() > last {
+      items.push((last..chars.len(), BLANK_CLASS));
+    }
+
+    let at = _absolute(text, cursor).saturating_sub(paragraph_start);
+    let at = items
+      .iter()
+      .position(|(range, _)| range.contains(&at))
+      .unwrap_or(items.len().saturating_sub(1));
+    let range = select_runs(items.len(), at, count, inner, |i| items[i].1);
+    _char_selection(
+      text,
+      paragraph_start + items[range.start].0.start
+        ..paragraph_start + items[range.end - 1].0.end,
+    )
+  }
+}
+
+// Sentence }
+
+// Paragraph {
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+/// The paragraph text objects `ip` and `ap`, they're linewise. The blank lines are the paragraph
+/// boundaries, and they're also counted as a paragraph in `ip`.
+pub struct ParagraphObject {}
+
+impl TextObject for ParagraphObject {
+  fn select(
+    &self,
+    text: &Text,
+    cursor: (usize, usize),
+    count: usize,
+    inner: bool,
+  ) -> Option<Selection> {
+    let len_lines = _len_lines(text);
+    if cursor.0 >= len_lines {
+      return None;
+    }
+    let range = select_runs(len_lines, cursor.0, count, inner, |line_idx| {
+      if _line_chars(text, line_idx)
+        .iter()
+        .all(|c| c.is_whitespace())
+      {
+        BLANK_CLASS
+      } else {
+        1
+      }
+    });
+    Some(Selection::new(
+      SelectionKind::Line,
+      (range.start, 0),
+      (range.end - 1, 0),
+    ))
+  }
+}
+
+// Paragraph }
+
+// Quote {
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The quote text objects, i.e. `i"`, `a'`, `` i` ``, they don't cross lines. The quote escaped by
+/// `\` is skipped.
+///
+/// The quotes are paired from the start of line, if the cursor is not inside a quoted string, the
+/// first quoted string after the cursor is selected. The `a` object includes the trailing white
+/// spaces, or the leading white spaces if there are no trailing ones.
+pub struct QuoteObject {
+  quote: char,
+}
+
+impl QuoteObject {
+  pub fn new(quote: char) -> Self {
+    Self { quote }
+  }
+}
+
+impl TextObject for QuoteObject {
+  fn select(
+    &self,
+    text: &Text,
+    cursor: (usize, usize),
+    _count: usize,
+    inner: bool,
+  ) -> Option<Selection> {
+    let (line_idx, char_idx) = cursor;
+    let chars = _line_chars(text, line_idx);
+
+    let mut quotes = vec![];
+    let mut escaped = false;
+    for (i, c) in chars.iter().enumerate() {
+      if escaped {
+        escaped = false;
+      } else if *c == '\\' {
+        escaped = true;
+      } else if *c == self.quote {
+        quotes.push(i);
+      }
+    }
+    let (open, close) = quotes
+      .chunks_exact(2)
+      .map(|pair| (pair[0], pair[1]))
+      .find(|(_, close)| char_idx <= *close)?;
+
+    let (start, end) = if inner {
+      (open + 1, close)
+    } else {
+      let mut end = close + 1;
+      while end < chars.len()
+        && char_class(text.options(), chars[end], true) == BLANK_CLASS
+      {
+        end += 1;
+      }
+      let mut start = open;
+      if end == close + 1 {
+        while start > 0
+          && char_class(text.options(), chars[start - 1], true) == BLANK_CLASS
+        {
+          start -= 1;
+        }
+      }
+      (start, end)
+    };
+    let line_start = _absolute(text, (line_idx, 0));
+    _char_selection(text, line_start + start..line_start + end)
+  }
+}
+
+// Quote }
+
+// Bracket {
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The bracket text objects, i.e. `i(`, `a{`, `i[`, `a<`, they can cross lines and are nested,
+/// the `count` selects the count-th enclosing brackets.
+///
+/// For the inner object, if the open bracket is at the end of line, and the close bracket is
+/// after the indent of its line, the lines between them are selected, i.e. `di{` keeps the
+/// brackets on their own lines.
+pub struct BracketObject {
+  open: char,
+  close: char,
+}
+
+impl BracketObject {
+  pub fn new(open: char, close: char) -> Self {
+    Self { open, close }
+  }
+
+  // Find the unmatched open bracket before `at` (exclusive).
+  fn _find_open(&self, text: &Text, at: usize) -> Option<usize> {
+    let rope = text.rope();
+    let mut depth = 0_usize;
+    let mut i = at;
+    while i > 0 {
+      i -= 1;
+      let c = rope.char(i);
+      if c == self.close {
+        depth += 1;
+      } else if c == self.open {
+        if depth == 0 {
+          return Some(i);
+        }
+        depth -= 1;
+      }
+    }
+    None
+  }
+
+  // Find the matched close bracket of the open bracket at `open`.
+  fn _find_close(&self, text: &Text, open: usize) -> Option<usize> {
+    let rope = text.rope();
+    let mut depth = 0_usize;
+    for i in open + 1..rope.len_chars() {
+      let c = rope.char(i);
+      if c == self.open {
+        depth += 1;
+      } else if c == self.close {
+        if depth == 0 {
+          return Some(i);
+        }
+        depth -= 1;
+      }
+    }
+    None
+  }
+}
+
+impl TextObject for BracketObject {
+  fn select(
+    &self,
+    text: &Text,
+    cursor: (usize, usize),
+    count: usize,
+    inner: bool,
+  ) -> Option<Selection> {
+    let rope = text.rope();
+    let at = _absolute(text, cursor);
+    if at >= rope.len_chars() {
+      return None;
+    }
+
+    // The cursor on a bracket selects it.
+    let mut open = match rope.char(at) {
+      c if c == self.open => at,
+      c if c == self.close => self._find_open(text, at)?,
+      _ => self._find_open(text, at)?,
+    };
+    for _ in 1..std::cmp::max(count, 1) {
+      open = self._find_open(text, open)?;
+    }
+    let close = self._find_close(text, open)?;
+
+    if !inner {
+      return _char_selection(text, open..close + 1);
+    }
+
+    let (open_line, open_char) = _position(text, open);
+    let (close_line, close_char) = _position(text, close);
+    let mut start = open + 1;
+    let mut end = close;
+    if open_line < close_line {
+      if open_char + 1 == _line_chars(text, open_line).len() {
+        start = _absolute(text, (open_line + 1, 0));
+      }
+      let indent = _line_chars(text, close_line)
+        .iter()
+        .take(close_char)
+        .all(|c| char_class(text.options(), *c, true) == BLANK_CLASS);
+      if indent && start < _absolute(text, (close_line, 0)) {
+        end = _absolute(text, (close_line, 0));
+      }
+    }
+    _char_selection(text, start..end)
+  }
+}
+
+// Bracket }
+
+// Tag {
+
+// The matched tag pair, the `open` is `<a>` and the `close` is `</a>` in absolute char indexes.
+#[derive(Debug, Clone)]
+struct _TagPair {
+  open: Range<usize>,
+  close: Range<usize>,
+}
+
+// Parse the tags in chars, the self-closing tags (i.e. `<br/>`), comments and declarations are
+// skipped, the unmatched tags are discarded.
+fn _tag_pairs(chars: &[char]) -> Vec<_TagPair> {
+  let is_name =
+    |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | ':' | '.');
+  let mut stack: Vec<(String, Range<usize>)> = vec![];
+  let mut pairs = vec![];
+  let mut i = 0;
+  while i < chars.len() {
+    if chars[i] != '<' {
+      i += 1;
+      continue;
+    }
+    let start = i;
+    let closing = chars.get(i + 1) == Some(&'/');
+    let mut j = if closing { i + 2 } else { i + 1 };
+    let name_start = j;
+    while j < chars.len() && is_name(chars[j]) {
+      j += 1;
+    }
+    if j == name_start {
+      i += 1;
+      continue;
+    }
+    let name: String = chars[name_start..j].iter().collect();
+    while j < chars.len() && chars[j] != '>' && chars[j] != '<' {
+      j += 1;
+    }
+    if j >= chars.len() || chars[j] != '>' {
+      i = j;
+      continue;
+    }
+    let end = j + 1;
+    if closing {
+      if let Some(k) = stack.iter().rposition(|(open, _)| *open == name) {
+        let (_, open) = stack.remove(k);
+        stack.truncate(k);
+        pairs.push(_TagPair {
+          open,
+          close: start..end,
+        });
+      }
+    } else if chars[j - 1] != '/' {
+      stack.push((name, start..end));
+    }
+    i = end;
+  }
+  pairs
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+/// The XML/HTML tag text objects `it` and `at`, they can cross lines and are nested, the `count`
+/// selects the count-th enclosing tags.
+pub struct TagObject {}
+
+impl TextObject for TagObject {
+  fn select(
+    &self,
+    text: &Text,
+    cursor: (usize, usize),
+    count: usize,
+    inner: bool,
+  ) -> Option<Selection> {
+    let chars: Vec<char> = text.rope().chars().collect();
+    let at = _absolute(text, cursor);
+    let mut enclosing: Vec<_TagPair> = _tag_pairs(&chars)
+      .into_iter()
+      .filter(|pair| pair.open.start <= at && at < pair.close.end)
+      .collect();
+    // The innermost tags first.
+    enclosing.sort_by_key(|pair| std::cmp::Reverse(pair.open.start));
+    let pair = enclosing.get(std::cmp::max(count, 1) - 1)?;
+    if inner {
+      _char_selection(text, pair.open.end..pair.close.start)
+    } else {
+      _char_selection(text, pair.open.start..pair.close.end)
+    }
+  }
+}
+
+// Tag }
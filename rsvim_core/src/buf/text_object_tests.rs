@@ -0,0 +1,230 @@
+use super::text_object::*;
+
+use crate::buf::opt::BufferLocalOptionsBuilder;
+use crate::buf::selection::{Selection, SelectionKind};
+use crate::buf::text::Text;
+use crate::coord::U16Size;
+use crate::test::log::init as test_log_init;
+
+use ropey::Rope;
+use std::sync::Arc;
+
+fn make_text(payload: &str) -> Text {
+  let opt = BufferLocalOptionsBuilder::default().build().unwrap();
+  Text::new(opt, U16Size::new(10, 10), Rope::from_str(payload))
+}
+
+fn select(
+  text: &Text,
+  key: char,
+  cursor: (usize, usize),
+  count: usize,
+  inner: bool,
+) -> Option<Selection> {
+  TextObjects::default()
+    .get(key)
+    .unwrap()
+    .select(text, cursor, count, inner)
+}
+
+fn chars(start: (usize, usize), end: (usize, usize)) -> Option<Selection> {
+  Some(Selection::new(SelectionKind::Char, start, end))
+}
+
+fn lines(start: usize, end: usize) -> Option<Selection> {
+  Some(Selection::new(SelectionKind::Line, (start, 0), (end, 0)))
+}
+
+#[test]
+fn word1() {
+  test_log_init();
+  let text = make_text("foo bar baz\n\n");
+
+  assert_eq!(select(&text, 'w', (0, 5), 1, true), chars((0, 4), (0, 6)));
+  assert_eq!(select(&text, 'w', (0, 5), 1, false), chars((0, 4), (0, 7)));
+  // No trailing white spaces.
+  assert_eq!(select(&text, 'w', (0, 9), 1, false), chars((0, 7), (0, 10)));
+  // On white spaces.
+  assert_eq!(select(&text, 'w', (0, 3), 1, true), chars((0, 3), (0, 3)));
+  assert_eq!(select(&text, 'w', (0, 3), 1, false), chars((0, 3), (0, 6)));
+  // Counts.
+  assert_eq!(select(&text, 'w', (0, 0), 3, true), chars((0, 0), (0, 6)));
+  assert_eq!(select(&text, 'w', (0, 0), 2, false), chars((0, 0), (0, 7)));
+  assert_eq!(select(&text, 'w', (0, 0), 10, true), chars((0, 0), (0, 10)));
+  // Empty line.
+  assert_eq!(select(&text, 'w', (1, 0), 1, true), None);
+}
+
+#[test]
+fn word2() {
+  test_log_init();
+  let text = make_text("foo.bar(baz) 你好\n");
+
+  assert_eq!(select(&text, 'w', (0, 1), 1, true), chars((0, 0), (0, 2)));
+  assert_eq!(select(&text, 'w', (0, 3), 1, true), chars((0, 3), (0, 3)));
+  assert_eq!(select(&text, 'W', (0, 1), 1, true), chars((0, 0), (0, 11)));
+  assert_eq!(select(&text, 'W', (0, 1), 1, false), chars((0, 0), (0, 12)));
+  assert_eq!(
+    select(&text, 'w', (0, 14), 1, true),
+    chars((0, 13), (0, 14))
+  );
+}
+
+#[test]
+fn sentence1() {
+  test_log_init();
+  let text = make_text("Hello world. This is it!  Next one?\n");
+
+  assert_eq!(
+    select(&text, 's', (0, 15), 1, true),
+    chars((0, 13), (0, 23))
+  );
+  assert_eq!(
+    select(&text, 's', (0, 15), 1, false),
+    chars((0, 13), (0, 25))
+  );
+  // No trailing white spaces.
+  assert_eq!(
+    select(&text, 's', (0, 28), 1, false),
+    chars((0, 24), (0, 34))
+  );
+  // On white spaces.
+  assert_eq!(
+    select(&text, 's', (0, 12), 1, true),
+    chars((0, 12), (0, 12))
+  );
+  // Counts.
+  assert_eq!(select(&text, 's', (0, 0), 2, false), chars((0, 0), (0, 25)));
+}
+
+#[test]
+fn sentence2() {
+  test_log_init();
+  let text = make_text("One.\nTwo\nthree.\n\nFour.\n");
+
+  assert_eq!(select(&text, 's', (1, 1), 1, true), chars((1, 0), (2, 5)));
+  assert_eq!(select(&text, 's', (0, 0), 1, false), chars((0, 0), (0, 4)));
+  // The empty line ends the paragraph.
+  assert_eq!(select(&text, 's', (4, 0), 1, true), chars((4, 0), (4, 4)));
+  assert_eq!(select(&text, 's', (3, 0), 1, true), None);
+}
+
+#[test]
+fn paragraph1() {
+  test_log_init();
+  let text = make_text("a\nb\n\n  \nc\n");
+
+  assert_eq!(select(&text, 'p', (0, 0), 1, true), lines(0, 1));
+  assert_eq!(select(&text, 'p', (0, 0), 1, false), lines(0, 3));
+  assert_eq!(select(&text, 'p', (2, 0), 1, true), lines(2, 3));
+  assert_eq!(select(&text, 'p', (2, 0), 1, false), lines(2, 4));
+  // No trailing blank lines.
+  assert_eq!(select(&text, 'p', (4, 0), 1, false), lines(2, 4));
+  // Counts.
+  assert_eq!(select(&text, 'p', (0, 0), 2, true), lines(0, 3));
+  assert_eq!(select(&text, 'p', (5, 0), 1, true), None);
+}
+
+#[test]
+fn quote1() {
+  test_log_init();
+  let text = make_text("say \"hi \\\"there\\\"\" and 'x'\n");
+
+  assert_eq!(select(&text, '"', (0, 6), 1, true), chars((0, 5), (0, 16)));
+  assert_eq!(select(&text, '"', (0, 6), 1, false), chars((0, 4), (0, 18)));
+  // On the quote.
+  assert_eq!(select(&text, '"', (0, 17), 1, true), chars((0, 5), (0, 16)));
+  // After cursor.
+  assert_eq!(select(&text, '"', (0, 0), 1, true), chars((0, 5), (0, 16)));
+  assert_eq!(select(&text, '"', (0, 20), 1, true), None);
+  assert_eq!(
+    select(&text, '\'', (0, 0), 1, true),
+    chars((0, 24), (0, 24))
+  );
+  // No trailing white spaces.
+  assert_eq!(
+    select(&text, '\'', (0, 24), 1, false),
+    chars((0, 22), (0, 25))
+  );
+  assert_eq!(select(&text, '`', (0, 0), 1, true), None);
+}
+
+#[test]
+fn bracket1() {
+  test_log_init();
+  let text = make_text("f(a, (b), c)\n");
+
+  assert_eq!(select(&text, '(', (0, 6), 1, true), chars((0, 6), (0, 6)));
+  assert_eq!(select(&text, ')', (0, 6), 1, false), chars((0, 5), (0, 7)));
+  assert_eq!(select(&text, 'b', (0, 6), 2, true), chars((0, 2), (0, 10)));
+  // On the bracket.
+  assert_eq!(select(&text, '(', (0, 1), 1, false), chars((0, 1), (0, 11)));
+  assert_eq!(select(&text, '(', (0, 11), 1, true), chars((0, 2), (0, 10)));
+  assert_eq!(select(&text, '(', (0, 0), 1, true), None);
+  assert_eq!(select(&text, '(', (0, 6), 3, true), None);
+  assert_eq!(select(&text, '{', (0, 6), 1, true), None);
+}
+
+#[test]
+fn bracket2() {
+  test_log_init();
+  let text = make_text("fn {\n  foo\n}\n");
+
+  // The lines between brackets.
+  assert_eq!(select(&text, '{', (1, 2), 1, true), chars((1, 0), (1, 5)));
+  assert_eq!(select(&text, 'B', (1, 2), 1, false), chars((0, 3), (2, 0)));
+
+  let text = make_text("{a\n  [b]\n}\n");
+  assert_eq!(select(&text, '[', (1, 3), 1, true), chars((1, 3), (1, 3)));
+  assert_eq!(select(&text, '{', (1, 3), 1, true), chars((0, 1), (1, 5)));
+}
+
+#[test]
+fn tag1() {
+  test_log_init();
+  let text = make_text("<div><p>hi <b>x</b></p><br/></div>\n");
+
+  assert_eq!(select(&text, 't', (0, 9), 1, true), chars((0, 8), (0, 18)));
+  assert_eq!(select(&text, 't', (0, 9), 1, false), chars((0, 5), (0, 22)));
+  assert_eq!(select(&text, 't', (0, 9), 2, true), chars((0, 5), (0, 27)));
+  assert_eq!(
+    select(&text, 't', (0, 14), 1, true),
+    chars((0, 14), (0, 14))
+  );
+  // On the tag.
+  assert_eq!(select(&text, 't', (0, 1), 1, false), chars((0, 0), (0, 33)));
+  assert_eq!(select(&text, 't', (0, 9), 3, true), None);
+}
+
+#[derive(Debug)]
+struct LineObject {}
+
+impl TextObject for LineObject {
+  fn select(
+    &self,
+    _text: &Text,
+    cursor: (usize, usize),
+    _count: usize,
+    _inner: bool,
+  ) -> Option<Selection> {
+    Some(Selection::new(SelectionKind::Line, cursor, cursor))
+  }
+}
+
+#[test]
+fn registry1() {
+  test_log_init();
+  let mut objects = TextObjects::default();
+  assert!(objects.contains('b'));
+  assert!(!objects.contains('l'));
+
+  assert!(objects.register('l', Arc::new(LineObject {})).is_none());
+  let text = make_text("hello\n");
+  assert_eq!(
+    objects.get('l').unwrap().select(&text, (0, 2), 1, true),
+    Some(Selection::new(SelectionKind::Line, (0, 2), (0, 2)))
+  );
+
+  assert!(objects.unregister('l').is_some());
+  assert!(objects.get('l').is_none());
+}
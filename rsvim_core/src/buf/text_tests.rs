@@ -321,3 +321,119 @@ fn last_char1_mac() {
     assert_eq!(text.rope().line(1).char(4), 'd');
   }
 }
+
+#[test]
+fn changedtick1() {
+  test_log_init();
+
+  let terminal_size = U16Size::new(10, 10);
+  let opt = BufferLocalOptionsBuilder::default().build().unwrap();
+  let rope = Rope::from_str("hello\nworld\n");
+  let mut text = Text::new(opt, terminal_size, rope);
+  assert_eq!(text.changedtick(), 0);
+
+  // Reading doesn't change it.
+  let _ = text.last_char_on_line(0);
+  assert_eq!(text.changedtick(), 0);
+
+  text.insert_at(0, 0, "a".into());
+  let changedtick = text.changedtick();
+  assert!(changedtick > 0);
+  text.delete_at(0, 0, 1);
+  assert!(text.changedtick() > changedtick);
+
+  // The changes are taken once.
+  let changes = text.take_changes();
+  assert!(!changes.is_empty());
+  assert!(changes.is_sorted());
+  assert!(text.take_changes().is_empty());
+  let _ = text.last_char_on_line(0);
+  assert!(text.take_changes().is_empty());
+}
+
+#[test]
+fn lines1() {
+  test_log_init();
+
+  let terminal_size = U16Size::new(10, 10);
+  let opt = BufferLocalOptionsBuilder::default().build().unwrap();
+
+  let text = Text::new(opt, terminal_size, Rope::from_str("hello\nworld\n"));
+  assert_eq!(text.lines_count(), 2);
+  assert_eq!(text.line_len_chars_no_eol(0), 5);
+  assert_eq!(text.get_lines(0..2), vec!["hello", "world"]);
+  assert_eq!(text.get_lines(1..1), Vec::<String>::new());
+  assert!(text.has_position((1, 5)));
+  assert!(!text.has_position((1, 6)));
+  assert!(!text.has_position((2, 0)));
+  assert_eq!(text.get_text((0, 3), (1, 2)), "lo\nwo");
+
+  let text = Text::new(opt, terminal_size, Rope::from_str("hello"));
+  assert_eq!(text.lines_count(), 1);
+  assert_eq!(text.get_lines(0..1), vec!["hello"]);
+
+  let text = Text::new(opt, terminal_size, Rope::new());
+  assert_eq!(text.lines_count(), 1);
+  assert_eq!(text.get_lines(0..1), vec![""]);
+  assert!(text.has_position((0, 0)));
+}
+
+#[test]
+fn set_lines1() {
+  test_log_init();
+
+  let terminal_size = U16Size::new(10, 10);
+  let opt = BufferLocalOptionsBuilder::default().build().unwrap();
+  let lines = |lines: &[&str]| -> Vec<String> {
+    lines.iter().map(|line| line.to_string()).collect()
+  };
+
+  let mut text = Text::new(opt, terminal_size, Rope::from_str("a\nb\nc\n"));
+  // Replace
+  text.set_lines(1..2, &lines(&["x", "y"]));
+  assert_eq!(text.rope().to_string(), "a\nx\ny\nc\n");
+  // Insert
+  text.set_lines(0..0, &lines(&["0"]));
+  assert_eq!(text.rope().to_string(), "0\na\nx\ny\nc\n");
+  // Append
+  text.set_lines(5..5, &lines(&["d"]));
+  assert_eq!(text.rope().to_string(), "0\na\nx\ny\nc\nd\n");
+  // Delete
+  text.set_lines(1..4, &[]);
+  assert_eq!(text.rope().to_string(), "0\nc\nd\n");
+  text.set_lines(0..3, &[]);
+  assert_eq!(text.rope().to_string(), "\n");
+  assert_eq!(text.lines_count(), 1);
+
+  // The last line doesn't have the eol.
+  let mut text = Text::new(opt, terminal_size, Rope::from_str("a"));
+  text.set_lines(1..1, &lines(&["b"]));
+  assert_eq!(text.rope().to_string(), "a\nb\n");
+
+  let mut text = Text::new(opt, terminal_size, Rope::new());
+  text.set_lines(0..1, &lines(&["a", "b"]));
+  assert_eq!(text.rope().to_string(), "a\nb\n");
+}
+
+#[test]
+fn set_text1() {
+  test_log_init();
+
+  let terminal_size = U16Size::new(10, 10);
+  let opt = BufferLocalOptionsBuilder::default().build().unwrap();
+
+  let mut text =
+    Text::new(opt, terminal_size, Rope::from_str("hello\nworld\n"));
+  let changedtick = text.changedtick();
+  assert_eq!(text.set_text((0, 2), (1, 2), "y, wo"), (0, 7));
+  assert_eq!(text.rope().to_string(), "hey, world\n");
+  assert!(text.changedtick() > changedtick);
+
+  // Insert
+  assert_eq!(text.set_text((0, 10), (0, 10), "!\nbye"), (1, 3));
+  assert_eq!(text.rope().to_string(), "hey, world!\nbye\n");
+
+  // Delete
+  assert_eq!(text.set_text((0, 3), (1, 0), ""), (0, 3));
+  assert_eq!(text.rope().to_string(), "heybye\n");
+}
@@ -89,3 +89,135 @@ pub fn str_symbols(opt: &BufferLocalOptions, s: &str) -> CompactString {
     },
   )
 }
+
+/// The char class of white spaces, i.e. space, tab and unicode spaces.
+pub const BLANK_CLASS: usize = 0;
+
+/// The char class of punctuations, i.e. the non-blank chars that are not keyword chars.
+pub const PUNCTUATION_CLASS: usize = 1;
+
+/// The char class of keyword chars, see [`IsKeywordOption`](crate::buf::opt::IsKeywordOption).
+pub const KEYWORD_CLASS: usize = 2;
+
+/// The char class of emojis.
+pub const EMOJI_CLASS: usize = 3;
+
+// The unicode classes of the chars `>= 256`, the `(first, last, class)` ranges are sorted and not
+// overlapped. The scripts (i.e. CJK ideographs, Hiragana, Katakana, Hangul) are classes by their
+// first char. The chars not in the table are keyword chars.
+//
+// See: `utf_class` in Vim's `mbyte.c`.
+const UNICODE_CLASSES: [(u32, u32, usize); 70] = [
+  (0x037e, 0x037e, PUNCTUATION_CLASS), // Greek question mark
+  (0x0387, 0x0387, PUNCTUATION_CLASS), // Greek ano teleia
+  (0x055a, 0x055f, PUNCTUATION_CLASS), // Armenian punctuation
+  (0x0589, 0x0589, PUNCTUATION_CLASS), // Armenian full stop
+  (0x05be, 0x05be, PUNCTUATION_CLASS),
+  (0x05c0, 0x05c0, PUNCTUATION_CLASS),
+  (0x05c3, 0x05c3, PUNCTUATION_CLASS),
+  (0x05f3, 0x05f4, PUNCTUATION_CLASS),
+  (0x060c, 0x060c, PUNCTUATION_CLASS),
+  (0x061b, 0x061b, PUNCTUATION_CLASS),
+  (0x061f, 0x061f, PUNCTUATION_CLASS),
+  (0x066a, 0x066d, PUNCTUATION_CLASS),
+  (0x06d4, 0x06d4, PUNCTUATION_CLASS),
+  (0x0700, 0x070d, PUNCTUATION_CLASS), // Syriac punctuation
+  (0x0964, 0x0965, PUNCTUATION_CLASS),
+  (0x0970, 0x0970, PUNCTUATION_CLASS),
+  (0x0df4, 0x0df4, PUNCTUATION_CLASS),
+  (0x0e4f, 0x0e4f, PUNCTUATION_CLASS),
+  (0x0e5a, 0x0e5b, PUNCTUATION_CLASS),
+  (0x0f04, 0x0f12, PUNCTUATION_CLASS),
+  (0x0f3a, 0x0f3d, PUNCTUATION_CLASS),
+  (0x0f85, 0x0f85, PUNCTUATION_CLASS),
+  (0x104a, 0x104f, PUNCTUATION_CLASS), // Myanmar punctuation
+  (0x10fb, 0x10fb, PUNCTUATION_CLASS), // Georgian punctuation
+  (0x1361, 0x1368, PUNCTUATION_CLASS), // Ethiopic punctuation
+  (0x166d, 0x166e, PUNCTUATION_CLASS), // Canadian Syl. punctuation
+  (0x1680, 0x1680, BLANK_CLASS),
+  (0x169b, 0x169c, PUNCTUATION_CLASS),
+  (0x16eb, 0x16ed, PUNCTUATION_CLASS),
+  (0x1735, 0x1736, PUNCTUATION_CLASS),
+  (0x17d4, 0x17dc, PUNCTUATION_CLASS), // Khmer punctuation
+  (0x1800, 0x180a, PUNCTUATION_CLASS), // Mongolian punctuation
+  (0x2000, 0x200b, BLANK_CLASS),       // spaces
+  (0x200c, 0x2027, PUNCTUATION_CLASS), // punctuation and symbols
+  (0x2028, 0x2029, BLANK_CLASS),
+  (0x202a, 0x202e, PUNCTUATION_CLASS), // punctuation and symbols
+  (0x202f, 0x202f, BLANK_CLASS),
+  (0x2030, 0x205e, PUNCTUATION_CLASS), // punctuation and symbols
+  (0x205f, 0x205f, BLANK_CLASS),
+  (0x2060, 0x206f, PUNCTUATION_CLASS), // punctuation and symbols
+  (0x2070, 0x207f, 0x2070),            // superscript
+  (0x2080, 0x2094, 0x2080),            // subscript
+  (0x20a0, 0x27ff, PUNCTUATION_CLASS), // all kinds of symbols
+  (0x2800, 0x28ff, 0x2800),            // braille
+  (0x2900, 0x2998, PUNCTUATION_CLASS), // arrows, brackets, etc.
+  (0x29d8, 0x29db, PUNCTUATION_CLASS),
+  (0x29fc, 0x29fd, PUNCTUATION_CLASS),
+  (0x2e00, 0x2e7f, PUNCTUATION_CLASS), // supplemental punctuation
+  (0x3000, 0x3000, BLANK_CLASS),       // ideographic space
+  (0x3001, 0x3020, PUNCTUATION_CLASS), // ideographic punctuation
+  (0x3030, 0x3030, PUNCTUATION_CLASS),
+  (0x303d, 0x303d, PUNCTUATION_CLASS),
+  (0x3040, 0x309f, 0x3040), // Hiragana
+  (0x30a0, 0x30ff, 0x30a0), // Katakana
+  (0x3300, 0x9fff, 0x4e00), // CJK ideographs
+  (0xac00, 0xd7a3, 0xac00), // Hangul syllables
+  (0xf900, 0xfaff, 0x4e00), // CJK ideographs
+  (0xfd3e, 0xfd3f, PUNCTUATION_CLASS),
+  (0xfe30, 0xfe6b, PUNCTUATION_CLASS), // punctuation forms
+  (0xff00, 0xff0f, PUNCTUATION_CLASS), // half/fullwidth ASCII
+  (0xff1a, 0xff20, PUNCTUATION_CLASS), // half/fullwidth ASCII
+  (0xff3b, 0xff40, PUNCTUATION_CLASS), // half/fullwidth ASCII
+  (0xff5b, 0xff65, PUNCTUATION_CLASS), // half/fullwidth ASCII
+  (0x1d000, 0x1d24f, PUNCTUATION_CLASS), // musical notation
+  (0x1d400, 0x1d7ff, 0x1d400),         // math alphanumerics
+  (0x1f000, 0x1faff, EMOJI_CLASS),     // emojis and pictographs
+  (0x20000, 0x2a6df, 0x4e00),          // CJK ideographs
+  (0x2a700, 0x2b81f, 0x4e00),          // CJK ideographs
+  (0x2b820, 0x2ebef, 0x4e00),          // CJK ideographs
+  (0x2f800, 0x2fa1f, 0x4e00),          // CJK ideographs
+];
+
+/// Get the char class for word motions and text objects, i.e. `w`, `b`, `iw`. The continuous
+/// chars with the same class are a word.
+///
+/// - For the chars `0..=255`, they're [`BLANK_CLASS`] (space, tab, no-break space),
+///   [`KEYWORD_CLASS`] (the 'iskeyword' option) or [`PUNCTUATION_CLASS`].
+/// - For other chars, they're classified by unicode, i.e. the CJK ideographs, Hiragana, Katakana
+///   are different classes, so they're different words.
+/// - For WORD (`big_word`), all the non-blank chars are [`KEYWORD_CLASS`].
+///
+/// NOTE: The line break is not handled here, the callers should treat it as a boundary.
+pub fn char_class(opt: &BufferLocalOptions, c: char, big_word: bool) -> usize {
+  let code = c as u32;
+  let class = if code < 0x100 {
+    if c == ' ' || c == '\t' || c == '\0' || code == 0xa0 {
+      BLANK_CLASS
+    } else if opt.is_keyword().contains(c) {
+      KEYWORD_CLASS
+    } else {
+      PUNCTUATION_CLASS
+    }
+  } else {
+    match UNICODE_CLASSES.binary_search_by(|(first, last, _)| {
+      if *last < code {
+        std::cmp::Ordering::Less
+      } else if *first > code {
+        std::cmp::Ordering::Greater
+      } else {
+        std::cmp::Ordering::Equal
+      }
+    }) {
+      Ok(i) => UNICODE_CLASSES[i].2,
+      Err(_) => KEYWORD_CLASS,
+    }
+  };
+
+  if big_word && class != BLANK_CLASS {
+    KEYWORD_CLASS
+  } else {
+    class
+  }
+}
@@ -1,6 +1,8 @@
 use super::unicode::*;
 
-use crate::buf::opt::{BufferLocalOptionsBuilder, FileFormatOption};
+use crate::buf::opt::{
+  BufferLocalOptionsBuilder, FileFormatOption, IsKeywordOption,
+};
 use crate::defaults::ascii::AsciiControlCodeFormatter;
 use crate::test::log::init as test_log_init;
 
@@ -192,3 +194,41 @@ fn nerdfont_characters_test1() {
     info!("i:{i},c:{c:?}, unicode_width:{w1:?}, icu:{w2:?}({w2_name})");
   }
 }
+
+#[test]
+fn char_class1() {
+  test_log_init();
+  let opt = BufferLocalOptionsBuilder::default().build().unwrap();
+
+  assert_eq!(char_class(&opt, ' ', false), BLANK_CLASS);
+  assert_eq!(char_class(&opt, '\t', false), BLANK_CLASS);
+  assert_eq!(char_class(&opt, 'a', false), KEYWORD_CLASS);
+  assert_eq!(char_class(&opt, '_', false), KEYWORD_CLASS);
+  assert_eq!(char_class(&opt, '9', false), KEYWORD_CLASS);
+  assert_eq!(char_class(&opt, 'é', false), KEYWORD_CLASS);
+  assert_eq!(char_class(&opt, '.', false), PUNCTUATION_CLASS);
+  assert_eq!(char_class(&opt, '.', true), KEYWORD_CLASS);
+  assert_eq!(char_class(&opt, ' ', true), BLANK_CLASS);
+
+  // Unicode.
+  assert_eq!(char_class(&opt, '\u{3000}', false), BLANK_CLASS);
+  assert_eq!(char_class(&opt, '，', false), PUNCTUATION_CLASS);
+  assert_eq!(char_class(&opt, '。', false), PUNCTUATION_CLASS);
+  assert_eq!(char_class(&opt, '你', false), char_class(&opt, '好', false));
+  assert_ne!(char_class(&opt, '你', false), KEYWORD_CLASS);
+  assert_ne!(char_class(&opt, 'の', false), char_class(&opt, 'カ', false));
+  assert_ne!(char_class(&opt, '你', false), char_class(&opt, '한', false));
+  assert_eq!(char_class(&opt, '😀', false), EMOJI_CLASS);
+  assert_eq!(char_class(&opt, 'λ', false), KEYWORD_CLASS);
+  assert_eq!(char_class(&opt, '你', true), KEYWORD_CLASS);
+}
+
+#[test]
+fn char_class2() {
+  test_log_init();
+  let mut opt = BufferLocalOptionsBuilder::default().build().unwrap();
+  opt.set_is_keyword(IsKeywordOption::try_from("@,48-57,_,-").unwrap());
+
+  assert_eq!(char_class(&opt, '-', false), KEYWORD_CLASS);
+  assert_eq!(char_class(&opt, '.', false), PUNCTUATION_CLASS);
+}
@@ -2,14 +2,41 @@
 
 use crate::buf::opt::BufferLocalOptionsBuilder;
 use crate::buf::text::Text;
+use crate::buf::text_object::TextObjects;
+use crate::content::history::History;
+use crate::content::message::Messages;
+use crate::content::register::Registers;
+use crate::content::wildmenu::Wildmenu;
 use crate::prelude::*;
 
+use compact_str::CompactString;
 use ropey::Rope;
 
+pub mod history;
+pub mod message;
+pub mod register;
+pub mod wildmenu;
+
+#[cfg(test)]
+mod history_tests;
+#[cfg(test)]
+mod message_tests;
+#[cfg(test)]
+mod register_tests;
+#[cfg(test)]
+mod wildmenu_tests;
+
 #[derive(Debug)]
 /// Temporary contents except buffers.
 pub struct TextContents {
   command_line_content: Text,
+  command_line_history: History,
+  command_line_wildmenu: Wildmenu,
+  messages: Messages,
+  registers: Registers,
+  text_objects: TextObjects,
+  showcmd: CompactString,
+  recording: Option<char>,
 }
 
 arc_mutex_ptr!(TextContents);
@@ -24,9 +51,21 @@ impl TextContents {
         canvas_size,
         Rope::new(),
       ),
+      command_line_history: History::default(),
+      command_line_wildmenu: Wildmenu::default(),
+      messages: Messages::default(),
+      registers: Registers::default(),
+      text_objects: TextObjects::default(),
+      showcmd: CompactString::default(),
+      recording: None,
     }
   }
 
+  /// Resize the caches of the command-line content, i.e. the terminal is resized.
+  pub fn resize(&mut self, canvas_size: U16Size) {
+    self.command_line_content.resize_cached_lines(canvas_size);
+  }
+
   pub fn command_line_content(&self) -> &Text {
     &self.command_line_content
   }
@@ -34,4 +73,62 @@ impl TextContents {
   pub fn command_line_content_mut(&mut self) -> &mut Text {
     &mut self.command_line_content
   }
+
+  pub fn command_line_history(&self) -> &History {
+    &self.command_line_history
+  }
+
+  pub fn command_line_history_mut(&mut self) -> &mut History {
+    &mut self.command_line_history
+  }
+
+  pub fn command_line_wildmenu(&self) -> &Wildmenu {
+    &self.command_line_wildmenu
+  }
+
+  pub fn command_line_wildmenu_mut(&mut self) -> &mut Wildmenu {
+    &mut self.command_line_wildmenu
+  }
+
+  pub fn messages(&self) -> &Messages {
+    &self.messages
+  }
+
+  pub fn messages_mut(&mut self) -> &mut Messages {
+    &mut self.messages
+  }
+
+  pub fn registers(&self) -> &Registers {
+    &self.registers
+  }
+
+  pub fn registers_mut(&mut self) -> &mut Registers {
+    &mut self.registers
+  }
+
+  pub fn text_objects(&self) -> &TextObjects {
+    &self.text_objects
+  }
+
+  pub fn text_objects_mut(&mut self) -> &mut TextObjects {
+    &mut self.text_objects
+  }
+
+  /// The pending keys of an incomplete command, shown in command-line with the `showcmd` option.
+  pub fn showcmd(&self) -> &str {
+    &self.showcmd
+  }
+
+  pub fn set_showcmd(&mut self, showcmd: &str) {
+    self.showcmd = CompactString::new(showcmd);
+  }
+
+  /// The register of the macro being recorded, shown in command-line as `recording @q`.
+  pub fn recording(&self) -> Option<char> {
+    self.recording
+  }
+
+  pub fn set_recording(&mut self, recording: Option<char>) {
+    self.recording = recording;
+  }
 }
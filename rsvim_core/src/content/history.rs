@@ -0,0 +1,432 @@
+//! Command-line history.
+//!
+//! There're separate history rings for ex commands (`:`), search forward (`/`), search backward
+//! (`?`) and input prompts (`@`). See: <https://vimhelp.org/cmdline.txt.html#cmdline-history>.
+//!
+//! The history is saved to the [`HISTORY_FILE_NAME`] file under the data home directory when the
+//! editor exits, and loaded when the editor starts. Multiple editor instances can exit at the same
+//! time, thus the history file is locked while saving, and the entries inside the file (written by
+//! other instances) are merged with current entries by their timestamps.
+
+use crate::defaults;
+use crate::prelude::*;
+
+use compact_str::{CompactString, ToCompactString};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// History file name under the data home directory.
+pub const HISTORY_FILE_NAME: &str = "history";
+
+/// Lock file name under the data home directory, it is locked while saving the history file.
+pub const HISTORY_LOCK_FILE_NAME: &str = "history.lock";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// History type.
+pub enum HistoryKind {
+  /// Ex commands, i.e. `:`.
+  Cmd,
+  /// Search forward patterns, i.e. `/`.
+  SearchForward,
+  /// Search backward patterns, i.e. `?`.
+  SearchBackward,
+  /// Input prompts, i.e. `@`.
+  Input,
+}
+
+impl HistoryKind {
+  /// All history types.
+  pub const ALL: [HistoryKind; 4] = [
+    HistoryKind::Cmd,
+    HistoryKind::SearchForward,
+    HistoryKind::SearchBackward,
+    HistoryKind::Input,
+  ];
+
+  fn index(&self) -> usize {
+    match self {
+      HistoryKind::Cmd => 0,
+      HistoryKind::SearchForward => 1,
+      HistoryKind::SearchBackward => 2,
+      HistoryKind::Input => 3,
+    }
+  }
+
+  /// The char that identifies the history type, i.e. `:`, `/`, `?` and `@`.
+  pub fn as_char(&self) -> char {
+    match self {
+      HistoryKind::Cmd => ':',
+      HistoryKind::SearchForward => '/',
+      HistoryKind::SearchBackward => '?',
+      HistoryKind::Input => '@',
+    }
+  }
+
+  pub fn from_char(c: char) -> Option<Self> {
+    HistoryKind::ALL
+      .into_iter()
+      .find(|kind| kind.as_char() == c)
+  }
+
+  /// The history name shows in `:history`.
+  pub fn name(&self) -> &'static str {
+    match self {
+      HistoryKind::Cmd => "cmd",
+      HistoryKind::SearchForward => "search forward",
+      HistoryKind::SearchBackward => "search backward",
+      HistoryKind::Input => "input",
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// History entry.
+pub struct HistoryEntry {
+  text: CompactString,
+  /// Unix timestamp in milliseconds, when the entry is added.
+  timestamp: u64,
+}
+
+impl HistoryEntry {
+  pub fn new(text: CompactString, timestamp: u64) -> Self {
+    Self { text, timestamp }
+  }
+
+  pub fn text(&self) -> &CompactString {
+    &self.text
+  }
+
+  pub fn timestamp(&self) -> u64 {
+    self.timestamp
+  }
+}
+
+#[derive(Debug, Clone)]
+/// The state of browsing history with `<Up>`/`<Down>`.
+struct HistoryNavigator {
+  kind: HistoryKind,
+  /// The text typed before browsing history, only entries start with it are recalled.
+  prefix: CompactString,
+  /// Current index in the history ring, it equals to the ring length when it is not on any entry.
+  index: usize,
+}
+
+fn _now() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap()
+    .as_millis() as u64
+}
+
+fn _escape(text: &str) -> String {
+  let mut result = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '\\' => result.push_str("\\\\"),
+      '\n' => result.push_str("\\n"),
+      '\r' => result.push_str("\\r"),
+      '\t' => result.push_str("\\t"),
+      _ => result.push(c),
+    }
+  }
+  result
+}
+
+fn _unescape(text: &str) -> CompactString {
+  let mut result = CompactString::with_capacity(text.len());
+  let mut chars = text.chars();
+  while let Some(c) = chars.next() {
+    if c == '\\' {
+      match chars.next() {
+        Some('n') => result.push('\n'),
+        Some('r') => result.push('\r'),
+        Some('t') => result.push('\t'),
+        Some(next) => result.push(next),
+        None => result.push(c),
+      }
+    } else {
+      result.push(c);
+    }
+  }
+  result
+}
+
+/// Max time to wait for the lock file.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A lock file that is older than this is left by a crashed editor instance.
+const LOCK_STALE: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+/// The exclusive lock of the history file, it is released when dropped.
+///
+/// The lock file is atomically created with [`OpenOptions::create_new`], and removed when it is
+/// released.
+struct HistoryLock {
+  path: PathBuf,
+}
+
+impl HistoryLock {
+  fn acquire(path: &Path) -> IoResult<Self> {
+    let start = Instant::now();
+    loop {
+      match OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(_) => {
+          return Ok(Self {
+            path: path.to_path_buf(),
+          });
+        }
+        Err(e) if e.kind() == IoErrKind::AlreadyExists => {
+          let stale = std::fs::metadata(path)
+            .and_then(|m| m.modified())
+            .is_ok_and(|t| t.elapsed().is_ok_and(|d| d > LOCK_STALE));
+          if stale {
+            let _ = std::fs::remove_file(path);
+            continue;
+          }
+          if start.elapsed() > LOCK_TIMEOUT {
+            return Err(e);
+          }
+          std::thread::sleep(Duration::from_millis(10));
+        }
+        Err(e) => return Err(e),
+      }
+    }
+  }
+}
+
+impl Drop for HistoryLock {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_file(&self.path);
+  }
+}
+
+#[derive(Debug, Clone)]
+/// Command-line history.
+pub struct History {
+  rings: [VecDeque<HistoryEntry>; 4],
+  max_size: usize,
+  navigator: Option<HistoryNavigator>,
+}
+
+impl History {
+  /// Make new history, each history ring keeps at most `max_size` entries.
+  pub fn new(max_size: usize) -> Self {
+    Self {
+      rings: Default::default(),
+      max_size,
+      navigator: None,
+    }
+  }
+
+  /// Max entries of each history ring, i.e. the 'history' option.
+  pub fn max_size(&self) -> usize {
+    self.max_size
+  }
+
+  /// Set max entries of each history ring, the oldest entries are removed if exceeded.
+  pub fn set_max_size(&mut self, max_size: usize) {
+    self.max_size = max_size;
+    for ring in self.rings.iter_mut() {
+      while ring.len() > max_size {
+        ring.pop_front();
+      }
+    }
+    self.navigator = None;
+  }
+
+  /// All entries of the history ring, from oldest to newest.
+  pub fn entries(&self, kind: HistoryKind) -> &VecDeque<HistoryEntry> {
+    &self.rings[kind.index()]
+  }
+
+  /// Add new entry to the history ring. Empty text is ignored, and the same old entry is moved to
+  /// newest.
+  pub fn add(&mut self, kind: HistoryKind, text: &str) {
+    self.add_with_timestamp(kind, text, _now());
+  }
+
+  fn add_with_timestamp(
+    &mut self,
+    kind: HistoryKind,
+    text: &str,
+    timestamp: u64,
+  ) {
+    self.navigator = None;
+    if text.trim().is_empty() || self.max_size == 0 {
+      return;
+    }
+    let ring = &mut self.rings[kind.index()];
+    ring.retain(|e| e.text != text);
+    ring.push_back(HistoryEntry::new(text.to_compact_string(), timestamp));
+    while ring.len() > self.max_size {
+      ring.pop_front();
+    }
+  }
+
+  /// Stop browsing history, the next [`History::previous`] starts from the newest entry again.
+  pub fn reset_navigation(&mut self) {
+    self.navigator = None;
+  }
+
+  /// Recall the older entry that starts with `typed` (i.e. `<Up>` in command-line).
+  ///
+  /// The `typed` text is only used when starting browsing, i.e. the first `<Up>`, the prefix is
+  /// kept until [`History::reset_navigation`] or a new entry is added. The command-line resets it
+  /// whenever its content is edited, thus the edited content becomes the new prefix.
+  ///
+  /// Returns `None` if there's no older entry.
+  pub fn previous(
+    &mut self,
+    kind: HistoryKind,
+    typed: &str,
+  ) -> Option<CompactString> {
+    let ring = &self.rings[kind.index()];
+    let navigator = match &mut self.navigator {
+      Some(navigator) if navigator.kind == kind => navigator,
+      _ => self.navigator.insert(HistoryNavigator {
+        kind,
+        prefix: typed.to_compact_string(),
+        index: ring.len(),
+      }),
+    };
+
+    let found = (0..navigator.index)
+      .rev()
+      .find(|i| ring[*i].text.starts_with(navigator.prefix.as_str()))?;
+    navigator.index = found;
+    Some(ring[found].text.clone())
+  }
+
+  /// Recall the newer entry that starts with the prefix (i.e. `<Down>` in command-line). When
+  /// there's no newer entry, it goes back to the originally typed text.
+  ///
+  /// Returns `None` if it is not browsing history, or already back to the typed text.
+  pub fn next(&mut self, kind: HistoryKind) -> Option<CompactString> {
+    let ring = &self.rings[kind.index()];
+    let navigator = match &mut self.navigator {
+      Some(navigator) if navigator.kind == kind => navigator,
+      _ => return None,
+    };
+    if navigator.index >= ring.len() {
+      return None;
+    }
+
+    match (navigator.index + 1..ring.len())
+      .find(|i| ring[*i].text.starts_with(navigator.prefix.as_str()))
+    {
+      Some(found) => {
+        navigator.index = found;
+        Some(ring[found].text.clone())
+      }
+      None => {
+        navigator.index = ring.len();
+        Some(navigator.prefix.clone())
+      }
+    }
+  }
+
+  /// Merge entries (i.e. loaded from the history file) by timestamps, the newer one wins if there
+  /// are duplicated entries.
+  fn merge(&mut self, kind: HistoryKind, entries: Vec<HistoryEntry>) {
+    let ring = &mut self.rings[kind.index()];
+    let mut merged: Vec<HistoryEntry> = ring.drain(..).collect();
+    for entry in entries {
+      match merged.iter_mut().find(|e| e.text == entry.text) {
+        Some(e) => e.timestamp = e.timestamp.max(entry.timestamp),
+        None => merged.push(entry),
+      }
+    }
+    // Stable sort keeps the original order for the same timestamps.
+    merged.sort_by_key(|e| e.timestamp);
+    let skip = merged.len().saturating_sub(self.max_size);
+    ring.extend(merged.into_iter().skip(skip));
+    self.navigator = None;
+  }
+
+  /// Parse the history file content, invalid lines are ignored.
+  fn parse(reader: impl BufRead) -> Vec<(HistoryKind, HistoryEntry)> {
+    let mut result = vec![];
+    for line in reader.lines().map_while(Result::ok) {
+      let mut fields = line.splitn(3, '\t');
+      let (Some(kind), Some(timestamp), Some(text)) =
+        (fields.next(), fields.next(), fields.next())
+      else {
+        continue;
+      };
+      let mut kind_chars = kind.chars();
+      let kind = match (kind_chars.next(), kind_chars.next()) {
+        (Some(c), None) => HistoryKind::from_char(c),
+        _ => None,
+      };
+      if let (Some(kind), Ok(timestamp)) = (kind, timestamp.parse::<u64>()) {
+        result.push((kind, HistoryEntry::new(_unescape(text), timestamp)));
+      }
+    }
+    result
+  }
+
+  fn merge_from_file(&mut self, path: &Path) -> IoResult<()> {
+    let file = match File::open(path) {
+      Ok(file) => file,
+      Err(e) if e.kind() == IoErrKind::NotFound => return Ok(()),
+      Err(e) => return Err(e),
+    };
+    let mut entries: HashMap<HistoryKind, Vec<HistoryEntry>> = HashMap::new();
+    for (kind, entry) in Self::parse(BufReader::new(file)) {
+      entries.entry(kind).or_default().push(entry);
+    }
+    for (kind, entries) in entries {
+      self.merge(kind, entries);
+    }
+    Ok(())
+  }
+
+  /// Load history from the history file under `dir`, it's fine if the file doesn't exist.
+  pub fn load(&mut self, dir: &Path) -> IoResult<()> {
+    self.merge_from_file(&dir.join(HISTORY_FILE_NAME))
+  }
+
+  /// Save history to the history file under `dir`.
+  ///
+  /// The history file is exclusively locked (by the [`HISTORY_LOCK_FILE_NAME`] file) while saving, the entries written by other editor
+  /// instances are merged first. The file is written to a temporary file, then renamed to the
+  /// history file, thus it will never be half-written.
+  pub fn save(&mut self, dir: &Path) -> IoResult<()> {
+    std::fs::create_dir_all(dir)?;
+    let _lock = HistoryLock::acquire(&dir.join(HISTORY_LOCK_FILE_NAME))?;
+
+    let path = dir.join(HISTORY_FILE_NAME);
+    self.merge_from_file(&path)?;
+
+    let tmp_path =
+      dir.join(format!("{}.{}.tmp", HISTORY_FILE_NAME, std::process::id()));
+    {
+      let mut writer = BufWriter::new(File::create(&tmp_path)?);
+      for kind in HistoryKind::ALL {
+        for entry in self.entries(kind) {
+          writeln!(
+            writer,
+            "{}\t{}\t{}",
+            kind.as_char(),
+            entry.timestamp,
+            _escape(&entry.text)
+          )?;
+        }
+      }
+      writer.flush()?;
+    }
+    std::fs::rename(&tmp_path, &path)?;
+
+    Ok(())
+  }
+}
+
+impl Default for History {
+  fn default() -> Self {
+    History::new(defaults::global::HISTORY as usize)
+  }
+}
@@ -0,0 +1,160 @@
+use super::history::*;
+
+use crate::test::log::init as test_log_init;
+
+use compact_str::CompactString;
+
+fn texts(history: &History, kind: HistoryKind) -> Vec<&str> {
+  history
+    .entries(kind)
+    .iter()
+    .map(|e| e.text().as_str())
+    .collect()
+}
+
+#[test]
+fn kind1() {
+  for kind in HistoryKind::ALL {
+    assert_eq!(HistoryKind::from_char(kind.as_char()), Some(kind));
+  }
+  assert_eq!(HistoryKind::from_char('x'), None);
+}
+
+#[test]
+fn add1() {
+  test_log_init();
+  let mut history = History::new(3);
+  history.add(HistoryKind::Cmd, "a");
+  history.add(HistoryKind::Cmd, "b");
+  history.add(HistoryKind::Cmd, "");
+  history.add(HistoryKind::Cmd, "  ");
+  history.add(HistoryKind::SearchForward, "x");
+  assert_eq!(texts(&history, HistoryKind::Cmd), vec!["a", "b"]);
+  assert_eq!(texts(&history, HistoryKind::SearchForward), vec!["x"]);
+  assert!(history.entries(HistoryKind::SearchBackward).is_empty());
+
+  // Duplicated entry moves to newest.
+  history.add(HistoryKind::Cmd, "a");
+  assert_eq!(texts(&history, HistoryKind::Cmd), vec!["b", "a"]);
+
+  // Oldest entries are removed.
+  history.add(HistoryKind::Cmd, "c");
+  history.add(HistoryKind::Cmd, "d");
+  assert_eq!(texts(&history, HistoryKind::Cmd), vec!["a", "c", "d"]);
+
+  history.set_max_size(1);
+  assert_eq!(texts(&history, HistoryKind::Cmd), vec!["d"]);
+
+  history.set_max_size(0);
+  history.add(HistoryKind::Cmd, "e");
+  assert!(history.entries(HistoryKind::Cmd).is_empty());
+}
+
+#[test]
+fn navigate1() {
+  test_log_init();
+  let mut history = History::new(10);
+  for text in ["set ts=4", "js 1", "set nowrap", "history"] {
+    history.add(HistoryKind::Cmd, text);
+  }
+
+  // Without prefix.
+  assert_eq!(history.next(HistoryKind::Cmd), None);
+  assert_eq!(
+    history.previous(HistoryKind::Cmd, ""),
+    Some(CompactString::new("history"))
+  );
+  assert_eq!(
+    history.previous(HistoryKind::Cmd, "history"),
+    Some(CompactString::new("set nowrap"))
+  );
+  assert_eq!(
+    history.next(HistoryKind::Cmd),
+    Some(CompactString::new("history"))
+  );
+  assert_eq!(history.next(HistoryKind::Cmd), Some(CompactString::new("")));
+  assert_eq!(history.next(HistoryKind::Cmd), None);
+
+  // With prefix.
+  history.reset_navigation();
+  assert_eq!(
+    history.previous(HistoryKind::Cmd, "se"),
+    Some(CompactString::new("set nowrap"))
+  );
+  assert_eq!(
+    history.previous(HistoryKind::Cmd, "set nowrap"),
+    Some(CompactString::new("set ts=4"))
+  );
+  assert_eq!(history.previous(HistoryKind::Cmd, "set ts=4"), None);
+  assert_eq!(
+    history.next(HistoryKind::Cmd),
+    Some(CompactString::new("set nowrap"))
+  );
+  assert_eq!(
+    history.next(HistoryKind::Cmd),
+    Some(CompactString::new("se"))
+  );
+
+  // Other history types.
+  assert_eq!(history.previous(HistoryKind::SearchForward, ""), None);
+  assert_eq!(history.next(HistoryKind::SearchForward), None);
+}
+
+#[test]
+fn save_load1() {
+  test_log_init();
+  let dir = assert_fs::TempDir::new().unwrap();
+
+  let mut history1 = History::new(3);
+  history1.add(HistoryKind::Cmd, "set ts=4");
+  history1.add(HistoryKind::Cmd, "js \"a\tb\\c\"");
+  history1.add(HistoryKind::SearchBackward, "foo\nbar");
+  history1.save(dir.path()).unwrap();
+
+  let mut history2 = History::new(3);
+  history2.load(dir.path()).unwrap();
+  assert_eq!(
+    texts(&history2, HistoryKind::Cmd),
+    vec!["set ts=4", "js \"a\tb\\c\""]
+  );
+  assert_eq!(
+    texts(&history2, HistoryKind::SearchBackward),
+    vec!["foo\nbar"]
+  );
+
+  // Another instance saves later, the entries are merged.
+  std::thread::sleep(std::time::Duration::from_millis(5));
+  let mut history3 = History::new(3);
+  history3.add(HistoryKind::Cmd, "set nowrap");
+  history3.add(HistoryKind::Cmd, "set ts=4");
+  history3.save(dir.path()).unwrap();
+
+  let mut history4 = History::new(3);
+  history4.load(dir.path()).unwrap();
+  assert_eq!(
+    texts(&history4, HistoryKind::Cmd),
+    vec!["js \"a\tb\\c\"", "set nowrap", "set ts=4"]
+  );
+  assert_eq!(
+    texts(&history4, HistoryKind::SearchBackward),
+    vec!["foo\nbar"]
+  );
+
+  // The old instance saves at last, its older entries don't override the newer ones.
+  history1.save(dir.path()).unwrap();
+  let mut history5 = History::new(3);
+  history5.load(dir.path()).unwrap();
+  assert_eq!(
+    texts(&history5, HistoryKind::Cmd),
+    vec!["js \"a\tb\\c\"", "set nowrap", "set ts=4"]
+  );
+}
+
+#[test]
+fn load_missing_file1() {
+  test_log_init();
+  let dir = assert_fs::TempDir::new().unwrap();
+  let mut history = History::new(3);
+  assert!(history.load(&dir.path().join("not_exist")).is_ok());
+  assert!(history.entries(HistoryKind::Cmd).is_empty());
+}
@@ -0,0 +1,191 @@
+//! Messages, i.e. the info/warning/error messages shown in the command-line.
+//!
+//! A message is shown in the command-line row, if the shown messages are taller than one line,
+//! a pager takes over the bottom rows of the screen and scrolls them like `more`. The messages are
+//! also saved in a bounded history, which can be shown again with the `:messages` ex command.
+//!
+//! See: <https://vimhelp.org/message.txt.html>.
+
+use crate::defaults;
+
+use compact_str::CompactString;
+use std::collections::VecDeque;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// Message level.
+pub enum MessageLevel {
+  Info,
+  Warn,
+  Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A message, it can contain multiple lines.
+pub struct Message {
+  level: MessageLevel,
+  text: CompactString,
+}
+
+impl Message {
+  pub fn new(level: MessageLevel, text: &str) -> Self {
+    Self {
+      level,
+      text: CompactString::new(text),
+    }
+  }
+
+  pub fn level(&self) -> MessageLevel {
+    self.level
+  }
+
+  pub fn text(&self) -> &str {
+    &self.text
+  }
+
+  /// Lines of the message, without the trailing line break.
+  pub fn lines(&self) -> impl Iterator<Item = &str> {
+    self.text.trim_end_matches(['\r', '\n']).lines()
+  }
+}
+
+#[derive(Debug, Clone)]
+/// The shown messages and the `:messages` history.
+pub struct Messages {
+  // The `:messages` history, from oldest to newest.
+  history: VecDeque<Message>,
+  max_size: usize,
+
+  // The messages shown in the command-line (or the pager).
+  shown: Vec<Message>,
+
+  // The first shown line of the pager, `None` if the pager is not started.
+  pager_top: Option<usize>,
+}
+
+impl Default for Messages {
+  fn default() -> Self {
+    Messages::new(defaults::global::MESSAGE_HISTORY as usize)
+  }
+}
+
+impl Messages {
+  pub fn new(max_size: usize) -> Self {
+    Self {
+      history: VecDeque::new(),
+      max_size,
+      shown: vec![],
+      pager_top: None,
+    }
+  }
+
+  /// Max entries of the history.
+  pub fn max_size(&self) -> usize {
+    self.max_size
+  }
+
+  /// Set max entries of the history, the oldest entries are removed if it is exceeded.
+  pub fn set_max_size(&mut self, max_size: usize) {
+    self.max_size = max_size;
+    while self.history.len() > self.max_size {
+      self.history.pop_front();
+    }
+  }
+
+  /// Show a message, and save it to the history. Empty message is ignored.
+  pub fn add(&mut self, level: MessageLevel, text: &str) {
+    if text.is_empty() {
+      return;
+    }
+    if self.max_size > 0 {
+      if self.history.len() >= self.max_size {
+        self.history.pop_front();
+      }
+      self.history.push_back(Message::new(level, text));
+    }
+    self.echo(level, text);
+  }
+
+  /// Show a message, without saving it to the history, i.e. the output of ex commands. Empty
+  /// message is ignored.
+  pub fn echo(&mut self, level: MessageLevel, text: &str) {
+    if text.is_empty() {
+      return;
+    }
+    self.shown.push(Message::new(level, text));
+  }
+
+  pub fn history(&self) -> &VecDeque<Message> {
+    &self.history
+  }
+
+  /// Clear the history, i.e. `:messages clear`.
+  pub fn clear_history(&mut self) {
+    self.history.clear();
+  }
+
+  pub fn shown(&self) -> &Vec<Message> {
+    &self.shown
+  }
+
+  /// All lines of the shown messages, with their levels.
+  pub fn shown_lines(&self) -> Vec<(MessageLevel, &str)> {
+    self
+      .shown
+      .iter()
+      .flat_map(|m| m.lines().map(move |line| (m.level(), line)))
+      .collect()
+  }
+
+  /// Hide the non-error messages shown after the first `len` shown messages, i.e. the messages of
+  /// the `<silent>` mappings. They're still saved in the history.
+  pub fn hide_shown_since(&mut self, len: usize) {
+    let mut i = 0_usize;
+    self.shown.retain(|m| {
+      i += 1;
+      i <= len || m.level() == MessageLevel::Error
+    });
+  }
+
+  /// Clear the shown messages, and stop the pager.
+  pub fn clear_shown(&mut self) {
+    self.shown.clear();
+    self.pager_top = None;
+  }
+
+  /// Whether the shown messages are taller than one line, and the pager is not started yet.
+  pub fn needs_pager(&self) -> bool {
+    self.pager_top.is_none() && self.shown_lines().len() > 1
+  }
+
+  /// The first shown line of the pager, `None` if the pager is not started.
+  pub fn pager_top(&self) -> Option<usize> {
+    self.pager_top
+  }
+
+  /// Start the pager from the first line.
+  pub fn start_pager(&mut self) {
+    self.pager_top = Some(0);
+  }
+
+  /// Scroll the pager by `n` lines (negative scrolls up), `height` is the lines shown in the pager.
+  /// It stops at the first line and the last page.
+  pub fn scroll_pager(&mut self, n: isize, height: usize) {
+    if let Some(top) = self.pager_top {
+      let max_top = self.shown_lines().len().saturating_sub(height);
+      let top = if n >= 0 {
+        top.saturating_add(n as usize)
+      } else {
+        top.saturating_sub(n.unsigned_abs())
+      };
+      self.pager_top = Some(top.min(max_top));
+    }
+  }
+
+  /// Whether the pager shows the last line, `height` is the lines shown in the pager.
+  pub fn pager_at_end(&self, height: usize) -> bool {
+    match self.pager_top {
+      Some(top) => top + height >= self.shown_lines().len(),
+      None => true,
+    }
+  }
+}
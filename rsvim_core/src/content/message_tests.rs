@@ -0,0 +1,86 @@
+use super::message::*;
+
+use crate::test::log::init as test_log_init;
+
+fn texts(messages: &Messages) -> Vec<&str> {
+  messages.history().iter().map(|m| m.text()).collect()
+}
+
+#[test]
+fn add1() {
+  test_log_init();
+  let mut messages = Messages::new(3);
+  messages.add(MessageLevel::Info, "a");
+  messages.add(MessageLevel::Warn, "b");
+  messages.add(MessageLevel::Error, "");
+  assert_eq!(texts(&messages), vec!["a", "b"]);
+  assert_eq!(messages.shown().len(), 2);
+
+  messages.add(MessageLevel::Error, "c");
+  messages.add(MessageLevel::Error, "d");
+  assert_eq!(texts(&messages), vec!["b", "c", "d"]);
+  assert_eq!(messages.history()[0].level(), MessageLevel::Warn);
+
+  messages.set_max_size(1);
+  assert_eq!(texts(&messages), vec!["d"]);
+  messages.clear_history();
+  assert!(messages.history().is_empty());
+  assert_eq!(messages.shown().len(), 4);
+}
+
+#[test]
+fn echo1() {
+  test_log_init();
+  let mut messages = Messages::new(3);
+  messages.echo(MessageLevel::Info, "a");
+  assert!(messages.history().is_empty());
+  assert_eq!(messages.shown().len(), 1);
+  assert!(!messages.needs_pager());
+
+  messages.add(MessageLevel::Error, "b\nc\n");
+  assert_eq!(
+    messages.shown_lines(),
+    vec![
+      (MessageLevel::Info, "a"),
+      (MessageLevel::Error, "b"),
+      (MessageLevel::Error, "c")
+    ]
+  );
+  assert!(messages.needs_pager());
+
+  messages.clear_shown();
+  assert!(messages.shown().is_empty());
+  assert!(!messages.needs_pager());
+}
+
+#[test]
+fn pager1() {
+  test_log_init();
+  let mut messages = Messages::new(10);
+  for i in 0..10 {
+    messages.add(MessageLevel::Info, &format!("{i}"));
+  }
+  assert!(messages.needs_pager());
+  assert_eq!(messages.pager_top(), None);
+
+  messages.start_pager();
+  assert!(!messages.needs_pager());
+  assert_eq!(messages.pager_top(), Some(0));
+  assert!(!messages.pager_at_end(4));
+
+  messages.scroll_pager(4, 4);
+  assert_eq!(messages.pager_top(), Some(4));
+  messages.scroll_pager(isize::MAX, 4);
+  assert_eq!(messages.pager_top(), Some(6));
+  assert!(messages.pager_at_end(4));
+
+  messages.scroll_pager(-1, 4);
+  assert_eq!(messages.pager_top(), Some(5));
+  messages.scroll_pager(isize::MIN, 4);
+  assert_eq!(messages.pager_top(), Some(0));
+
+  // All lines fit in the pager.
+  assert!(messages.pager_at_end(10));
+  messages.clear_shown();
+  assert_eq!(messages.pager_top(), None);
+}
@@ -0,0 +1,127 @@
+//! Registers.
+//!
+//! See: <https://vimhelp.org/change.txt.html#registers>.
+
+use crate::prelude::*;
+
+use compact_str::CompactString;
+
+/// The unnamed register.
+pub const UNNAMED_REGISTER: char = '"';
+
+/// The small delete register.
+pub const SMALL_DELETE_REGISTER: char = '-';
+
+/// The black hole register, writing to it does nothing, reading from it returns nothing.
+pub const BLACK_HOLE_REGISTER: char = '_';
+
+#[derive(Debug, Clone, Default)]
+/// Registers that store text, i.e. the unnamed register `"`, the numbered registers `0`-`9`, the
+/// named registers `a`-`z` and the small delete register `-`.
+///
+/// NOTE: The read-only registers (`:`, `/`, `%`, etc) are not stored here, they're resolved from
+/// the command-line history and the current buffer when being read.
+pub struct Registers {
+  values: HashMap<char, CompactString>,
+}
+
+impl Registers {
+  /// Whether the register `name` is writable.
+  pub fn is_writable(name: char) -> bool {
+    name == UNNAMED_REGISTER
+      || name == SMALL_DELETE_REGISTER
+      || name == BLACK_HOLE_REGISTER
+      || name.is_ascii_digit()
+      || name.is_ascii_alphabetic()
+  }
+
+  /// Get the text of register `name`, the uppercase named registers are the same as lowercase.
+  pub fn get(&self, name: char) -> Option<&CompactString> {
+    self.values.get(&name.to_ascii_lowercase())
+  }
+
+  /// Set the text of register `name`, the uppercase named registers (`A`-`Z`) append the text to
+  /// the lowercase register.
+  ///
+  /// Returns `false` if the register is not writable.
+  pub fn set(&mut self, name: char, text: &str) -> bool {
+    if !Self::is_writable(name) {
+      return false;
+    }
+    if name == BLACK_HOLE_REGISTER {
+      return true;
+    }
+    if name.is_ascii_uppercase() {
+      self
+        .values
+        .entry(name.to_ascii_lowercase())
+        .or_default()
+        .push_str(text);
+    } else {
+      self.values.insert(name, CompactString::new(text));
+    }
+    true
+  }
+
+  /// Yank text, i.e. the text is saved to the unnamed register `"` and the register `0`.
+  pub fn yank(&mut self, text: &str) {
+    self.set(UNNAMED_REGISTER, text);
+    self.set('0', text);
+  }
+
+  /// Delete text, i.e. the text is saved to the unnamed register `"`, and:
+  ///
+  /// - If it is a small delete (less than one line), it is saved to the small delete register
+  ///   `-`.
+  /// - Otherwise the numbered registers are shifted, i.e. `1` to `2`, `2` to `3`, etc, and it is
+  ///   saved to the register `1`.
+  pub fn delete(&mut self, text: &str, small: bool) {
+    self.set(UNNAMED_REGISTER, text);
+    if small {
+      self.set(SMALL_DELETE_REGISTER, text);
+    } else {
+      for i in (1..9).rev() {
+        let from = char::from_digit(i, 10).unwrap();
+        let to = char::from_digit(i + 1, 10).unwrap();
+        if let Some(value) = self.values.remove(&from) {
+          self.values.insert(to, value);
+        }
+      }
+      self.set('1', text);
+    }
+  }
+
+  /// Yank text to the register `name` given by `"x`, or the default registers if `None`, see
+  /// [`yank`](Self::yank).
+  ///
+  /// The named register also sets the unnamed register `"`, but not the register `0`. The black
+  /// hole register `_` does nothing.
+  pub fn yank_to(&mut self, name: Option<char>, text: &str) {
+    match name {
+      None | Some(UNNAMED_REGISTER) => self.yank(text),
+      Some(name) => self._set_named(name, text),
+    }
+  }
+
+  /// Delete text to the register `name` given by `"x`, or the default registers if `None`, see
+  /// [`delete`](Self::delete).
+  ///
+  /// The named register also sets the unnamed register `"`, but the numbered registers are not
+  /// shifted. The black hole register `_` does nothing.
+  pub fn delete_to(&mut self, name: Option<char>, text: &str, small: bool) {
+    match name {
+      None | Some(UNNAMED_REGISTER) => self.delete(text, small),
+      Some(name) => self._set_named(name, text),
+    }
+  }
+
+  fn _set_named(&mut self, name: char, text: &str) {
+    if name == BLACK_HOLE_REGISTER || !self.set(name, text) {
+      return;
+    }
+    // The unnamed register points to the whole register, i.e. after appending to `A`.
+    if let Some(value) = self.get(name).cloned() {
+      self.values.insert(UNNAMED_REGISTER, value);
+    }
+  }
+}
@@ -0,0 +1,89 @@
+use super::register::*;
+
+use crate::test::log::init as test_log_init;
+
+#[test]
+fn set1() {
+  test_log_init();
+  let mut registers = Registers::default();
+  assert!(registers.get('a').is_none());
+  assert!(registers.set('a', "hello"));
+  assert_eq!(registers.get('a').unwrap(), "hello");
+  assert_eq!(registers.get('A').unwrap(), "hello");
+
+  // Uppercase appends.
+  assert!(registers.set('A', " world"));
+  assert_eq!(registers.get('a').unwrap(), "hello world");
+  assert!(registers.set('B', "b"));
+  assert_eq!(registers.get('b').unwrap(), "b");
+
+  assert!(registers.set(UNNAMED_REGISTER, "x"));
+  assert_eq!(registers.get(UNNAMED_REGISTER).unwrap(), "x");
+  assert!(registers.set('0', "y"));
+  assert_eq!(registers.get('0').unwrap(), "y");
+}
+
+#[test]
+fn set2() {
+  test_log_init();
+  let mut registers = Registers::default();
+  // Black hole register.
+  assert!(registers.set(BLACK_HOLE_REGISTER, "a"));
+  assert!(registers.get(BLACK_HOLE_REGISTER).is_none());
+
+  // Read-only registers.
+  assert!(!registers.set(':', "a"));
+  assert!(!registers.set('%', "a"));
+  assert!(registers.get(':').is_none());
+}
+
+#[test]
+fn yank_delete1() {
+  test_log_init();
+  let mut registers = Registers::default();
+  registers.yank("a");
+  assert_eq!(registers.get(UNNAMED_REGISTER).unwrap(), "a");
+  assert_eq!(registers.get('0').unwrap(), "a");
+
+  registers.delete("b", true);
+  assert_eq!(registers.get(UNNAMED_REGISTER).unwrap(), "b");
+  assert_eq!(registers.get(SMALL_DELETE_REGISTER).unwrap(), "b");
+  assert!(registers.get('1').is_none());
+
+  registers.delete("c\n", false);
+  registers.delete("d\n", false);
+  assert_eq!(registers.get(UNNAMED_REGISTER).unwrap(), "d\n");
+  assert_eq!(registers.get('1').unwrap(), "d\n");
+  assert_eq!(registers.get('2').unwrap(), "c\n");
+  assert_eq!(registers.get('0').unwrap(), "a");
+  assert_eq!(registers.get(SMALL_DELETE_REGISTER).unwrap(), "b");
+}
+
+#[test]
+fn yank_delete_to1() {
+  test_log_init();
+  let mut registers = Registers::default();
+  registers.yank_to(None, "a");
+  assert_eq!(registers.get('0').unwrap(), "a");
+
+  registers.yank_to(Some('x'), "b");
+  assert_eq!(registers.get('x').unwrap(), "b");
+  assert_eq!(registers.get(UNNAMED_REGISTER).unwrap(), "b");
+  assert_eq!(registers.get('0').unwrap(), "a");
+
+  registers.yank_to(Some('X'), "c");
+  assert_eq!(registers.get('x').unwrap(), "bc");
+  assert_eq!(registers.get(UNNAMED_REGISTER).unwrap(), "bc");
+
+  registers.delete_to(Some('y'), "d\n", false);
+  assert_eq!(registers.get('y').unwrap(), "d\n");
+  assert_eq!(registers.get(UNNAMED_REGISTER).unwrap(), "d\n");
+  assert!(registers.get('1').is_none());
+
+  registers.delete_to(Some(BLACK_HOLE_REGISTER), "e\n", false);
+  assert_eq!(registers.get(UNNAMED_REGISTER).unwrap(), "d\n");
+  assert!(registers.get('1').is_none());
+
+  registers.delete_to(None, "f\n", false);
+  assert_eq!(registers.get('1').unwrap(), "f\n");
+}
@@ -0,0 +1,151 @@
+//! Wildmenu, i.e. the completion matches of command-line.
+
+use crate::excommand::complete::CompletionItem;
+use crate::js::JsFutureId;
+
+use compact_str::CompactString;
+
+#[derive(Debug, Clone, Default)]
+/// Wildmenu, it holds the completion matches of the command-line and which one is selected.
+///
+/// The selection cycles through all the matches and then the original text typed by user, i.e.
+/// the `None` selection.
+pub struct Wildmenu {
+  items: Vec<CompletionItem>,
+  selected: Option<usize>,
+  // The char index where the completed text starts in the command-line.
+  start_char_idx: usize,
+  // The original text typed by user, from `start_char_idx` to cursor.
+  original: CompactString,
+  // Whether the matches are shown.
+  visible: bool,
+  // Whether the matches are shown in a vertical popup menu, instead of a horizontal bar.
+  popup: bool,
+  // How many `<Tab>` have been pressed since the completion starts.
+  tab_count: usize,
+  // The completion request waiting for js completer.
+  pending: Option<JsFutureId>,
+}
+
+impl Wildmenu {
+  /// Whether the completion is in progress, i.e. it has matches or it is waiting for the js
+  /// completer.
+  pub fn is_active(&self) -> bool {
+    !self.items.is_empty() || self.pending.is_some()
+  }
+
+  pub fn items(&self) -> &Vec<CompletionItem> {
+    &self.items
+  }
+
+  pub fn selected(&self) -> Option<usize> {
+    self.selected
+  }
+
+  pub fn start_char_idx(&self) -> usize {
+    self.start_char_idx
+  }
+
+  pub fn original(&self) -> &CompactString {
+    &self.original
+  }
+
+  pub fn set_original(&mut self, original: CompactString) {
+    self.original = original;
+  }
+
+  pub fn visible(&self) -> bool {
+    self.visible && !self.items.is_empty()
+  }
+
+  pub fn set_visible(&mut self, visible: bool) {
+    self.visible = visible;
+  }
+
+  pub fn popup(&self) -> bool {
+    self.popup
+  }
+
+  pub fn set_popup(&mut self, popup: bool) {
+    self.popup = popup;
+  }
+
+  pub fn tab_count(&self) -> usize {
+    self.tab_count
+  }
+
+  pub fn increase_tab_count(&mut self) {
+    self.tab_count += 1;
+  }
+
+  pub fn pending(&self) -> Option<JsFutureId> {
+    self.pending
+  }
+
+  /// Start waiting for the js completer, the old matches are cleared.
+  pub fn start_pending(
+    &mut self,
+    future_id: JsFutureId,
+    start_char_idx: usize,
+    original: CompactString,
+  ) {
+    self.clear();
+    self.pending = Some(future_id);
+    self.start_char_idx = start_char_idx;
+    self.original = original;
+  }
+
+  /// Start a new completion with the matches.
+  pub fn start(
+    &mut self,
+    items: Vec<CompletionItem>,
+    start_char_idx: usize,
+    original: CompactString,
+  ) {
+    self.clear();
+    self.items = items;
+    self.start_char_idx = start_char_idx;
+    self.original = original;
+  }
+
+  /// Fill the matches for the pending js completion request, i.e. the `start_char_idx` and
+  /// `original` text are kept.
+  pub fn resolve_pending(&mut self, items: Vec<CompletionItem>) {
+    self.pending = None;
+    self.items = items;
+    self.selected = None;
+  }
+
+  /// Select the next (or previous if `forward` is `false`) match.
+  ///
+  /// Returns the text of the selected match, or the original text when the selection cycles back
+  /// to it.
+  pub fn select_next(&mut self, forward: bool) -> CompactString {
+    let n = self.items.len();
+    if n == 0 {
+      return self.original.clone();
+    }
+    self.selected = match (self.selected, forward) {
+      (None, true) => Some(0),
+      (None, false) => Some(n - 1),
+      (Some(i), true) if i + 1 < n => Some(i + 1),
+      (Some(i), false) if i > 0 => Some(i - 1),
+      (Some(_), _) => None,
+    };
+    match self.selected {
+      Some(i) => self.items[i].word().clone(),
+      None => self.original.clone(),
+    }
+  }
+
+  /// Stop the completion and remove all the matches.
+  pub fn clear(&mut self) {
+    self.items.clear();
+    self.selected = None;
+    self.start_char_idx = 0;
+    self.original = CompactString::new("");
+    self.visible = false;
+    self.tab_count = 0;
+    self.pending = None;
+  }
+}
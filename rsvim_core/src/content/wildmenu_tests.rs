@@ -0,0 +1,74 @@
+use super::wildmenu::*;
+
+use crate::excommand::complete::CompletionItem;
+use crate::test::log::init as test_log_init;
+
+use compact_str::CompactString;
+
+fn items(words: &[&str]) -> Vec<CompletionItem> {
+  words.iter().map(|w| CompletionItem::from(*w)).collect()
+}
+
+#[test]
+fn select_next1() {
+  test_log_init();
+  let mut wildmenu = Wildmenu::default();
+  assert!(!wildmenu.is_active());
+  wildmenu.start(items(&["set", "setglobal"]), 0, CompactString::new("se"));
+  assert!(wildmenu.is_active());
+  assert_eq!(wildmenu.selected(), None);
+
+  assert_eq!(wildmenu.select_next(true), "set");
+  assert_eq!(wildmenu.selected(), Some(0));
+  assert_eq!(wildmenu.select_next(true), "setglobal");
+  assert_eq!(wildmenu.selected(), Some(1));
+  // Cycles back to the original text.
+  assert_eq!(wildmenu.select_next(true), "se");
+  assert_eq!(wildmenu.selected(), None);
+  assert_eq!(wildmenu.select_next(true), "set");
+}
+
+#[test]
+fn select_next2() {
+  test_log_init();
+  let mut wildmenu = Wildmenu::default();
+  wildmenu.start(items(&["a", "b", "c"]), 3, CompactString::new(""));
+  assert_eq!(wildmenu.select_next(false), "c");
+  assert_eq!(wildmenu.select_next(false), "b");
+  assert_eq!(wildmenu.select_next(false), "a");
+  assert_eq!(wildmenu.select_next(false), "");
+  assert_eq!(wildmenu.selected(), None);
+  assert_eq!(wildmenu.start_char_idx(), 3);
+}
+
+#[test]
+fn visible1() {
+  test_log_init();
+  let mut wildmenu = Wildmenu::default();
+  wildmenu.set_visible(true);
+  // No matches, nothing to show.
+  assert!(!wildmenu.visible());
+  wildmenu.start(items(&["a", "b"]), 0, CompactString::new(""));
+  assert!(!wildmenu.visible());
+  wildmenu.set_visible(true);
+  assert!(wildmenu.visible());
+  wildmenu.clear();
+  assert!(!wildmenu.visible());
+  assert!(!wildmenu.is_active());
+}
+
+#[test]
+fn pending1() {
+  test_log_init();
+  let mut wildmenu = Wildmenu::default();
+  wildmenu.start_pending(7, 4, CompactString::new("fo"));
+  assert!(wildmenu.is_active());
+  assert_eq!(wildmenu.pending(), Some(7));
+  assert!(wildmenu.items().is_empty());
+
+  wildmenu.resolve_pending(items(&["foo", "foobar"]));
+  assert_eq!(wildmenu.pending(), None);
+  assert_eq!(wildmenu.items().len(), 2);
+  assert_eq!(wildmenu.start_char_idx(), 4);
+  assert_eq!(wildmenu.original(), "fo");
+}
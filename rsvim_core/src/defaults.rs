@@ -2,6 +2,7 @@
 
 pub mod ascii;
 pub mod buf;
+pub mod global;
 pub mod win;
 
 #[cfg(test)]
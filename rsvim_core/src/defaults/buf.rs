@@ -11,3 +11,5 @@ pub const FILE_FORMAT: FileFormatOption = FileFormatOption::Dos;
 
 #[cfg(not(target_os = "windows"))]
 pub const FILE_FORMAT: FileFormatOption = FileFormatOption::Unix;
+
+pub const IS_KEYWORD: IsKeywordOption = IsKeywordOption::vim_default();
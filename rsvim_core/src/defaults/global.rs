@@ -0,0 +1,27 @@
+//! Vim editor's global default options.
+//!
+//! See: [`crate::state::opt::GlobalOptions`].
+
+pub const HISTORY: u16 = 10000_u16;
+
+pub const MESSAGE_HISTORY: u16 = 500_u16;
+
+pub const WILD_MODE: &str = "full";
+
+pub const WILD_IGNORE: &str = "";
+
+pub const WILD_OPTIONS: &str = "";
+
+pub const TIMEOUT: bool = true;
+
+pub const TIMEOUT_LEN: u32 = 1000_u32;
+
+pub const SHOW_CMD: bool = true;
+
+pub const UPDATE_TIME: u32 = 4000_u32;
+
+pub const KEY_MODEL: &str = "";
+
+pub const SELECT_MODE: &str = "";
+
+pub const MOUSE: &str = "";
@@ -1,27 +1,37 @@
 //! Event loop.
 
-use crate::buf::{BuffersManager, BuffersManagerArc};
+use crate::buf::{Buffer, BufferId, BuffersManager, BuffersManagerArc};
 use crate::cli::CliOpt;
+use crate::content::message::MessageLevel;
 use crate::content::{TextContents, TextContentsArc};
 use crate::evloop::msg::WorkerToMasterMessage;
 use crate::js::msg::{
   self as jsmsg, EventLoopToJsRuntimeMessage, JsRuntimeToEventLoopMessage,
 };
-use crate::js::{JsRuntime, JsRuntimeOptions, SnapshotData};
+use crate::js::{JsRuntime, JsRuntimeOptions, SnapshotData, next_future_id};
 use crate::prelude::*;
-use crate::state::fsm::{Stateful, StatefulDataAccess, StatefulValue};
-use crate::state::{State, StateArc};
+use crate::state::autocmd::{AutoCmdEvent, FiredEvent};
+use crate::state::fsm::{
+  MessagePagerStateful, NormalStateful, Stateful, StatefulDataAccess,
+  StatefulValue, mapping, mouse,
+};
+use crate::state::keymap::{KeymapTarget, MAX_MAP_DEPTH};
+use crate::state::mode::Mode;
+use crate::state::ops::Operation;
+use crate::state::ops::{cursor_ops, motion_ops};
+use crate::state::{State, StateArc, keys};
 use crate::ui::canvas::{Canvas, CanvasArc, Shader, ShaderCommand};
 use crate::ui::tree::*;
 use crate::ui::widget::command_line::CommandLine;
 use crate::ui::widget::cursor::Cursor;
 use crate::ui::widget::window::Window;
 
-use crossterm::event::{Event, EventStream};
+use compact_str::ToCompactString;
+use crossterm::event::{Event, EventStream, KeyEventKind};
 use crossterm::{self, queue};
 use futures::StreamExt;
 use std::path::Path;
-use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 // use heed::types::U16;
 use std::io::Write;
 use std::io::{BufWriter, Stdout};
@@ -68,6 +78,13 @@ pub struct EventLoop {
 
   /// Finite-state machine for editing state.
   pub stateful_machine: StatefulValue,
+  /// The deadline of the pending keys of an incomplete command, see the `timeout` and
+  /// `timeoutlen` options.
+  pub pending_deadline: Option<tokio::time::Instant>,
+  /// The deadline of the `CursorHold` event, see the `updatetime` option.
+  pub cursor_hold_deadline: Option<tokio::time::Instant>,
+  /// Whether the mouse events are captured, see the `mouse` option.
+  pub mouse_capture: bool,
 
   /// Vim buffers.
   pub buffers: BuffersManagerArc,
@@ -182,7 +199,9 @@ impl EventLoop {
       .as_millis();
 
     // State
-    let state = State::to_arc(State::new(jsrt_tick_dispatcher.clone()));
+    let mut state = State::new(jsrt_tick_dispatcher.clone());
+    state.set_worker_send_to_master(Some(wkr_to_mstr.clone()));
+    let state = State::to_arc(state);
     let stateful_machine = StatefulValue::default();
 
     // Js Runtime
@@ -208,6 +227,9 @@ impl EventLoop {
       tree,
       state,
       stateful_machine,
+      pending_deadline: None,
+      cursor_hold_deadline: None,
+      mouse_capture: false,
       buffers: buffers_manager,
       contents: text_contents,
       writer: BufWriter::new(std::io::stdout()),
@@ -227,10 +249,40 @@ impl EventLoop {
   /// Initialize user config file.
   pub fn init_config(&mut self) -> IoResult<()> {
     if let Some(config_entry) = PATH_CONFIG.config_entry() {
-      self
+      // The error is already reported to the message area, and shown when the TUI starts.
+      if let Err(e) = self
         .js_runtime
         .execute_module(config_entry.to_str().unwrap(), None)
-        .unwrap();
+      {
+        error!("Failed to initialize user config:{:?}", e);
+      }
+    }
+    Ok(())
+  }
+
+  /// Initialize command-line history from the history file under data home directory.
+  ///
+  /// NOTE: This should be called after the user config is initialized, the user config may change
+  /// the 'history' option.
+  pub fn init_history(&mut self) -> IoResult<()> {
+    let history_size = lock!(self.state).global_options().history();
+    let mut contents = lock!(self.contents);
+    let history = contents.command_line_history_mut();
+    history.set_max_size(history_size as usize);
+    if let Err(e) = history.load(PATH_CONFIG.data_home().as_path()) {
+      error!("Failed to load command-line history:{:?}", e);
+    }
+    Ok(())
+  }
+
+  /// Save command-line history to the history file under data home directory.
+  pub fn save_history(&mut self) -> IoResult<()> {
+    let history_size = lock!(self.state).global_options().history();
+    let mut contents = lock!(self.contents);
+    let history = contents.command_line_history_mut();
+    history.set_max_size(history_size as usize);
+    if let Err(e) = history.save(PATH_CONFIG.data_home().as_path()) {
+      error!("Failed to save command-line history:{:?}", e);
     }
     Ok(())
   }
@@ -271,6 +323,16 @@ impl EventLoop {
       crossterm::cursor::MoveTo(cursor.pos().x(), cursor.pos().y())
     )?;
 
+    // Enable bracketed paste, the pasted text comes as a whole instead of typed keys.
+    let mut shader = Shader::new();
+    shader.push(ShaderCommand::EventEnableBracketedPaste(
+      crossterm::event::EnableBracketedPaste,
+    ));
+    self.queue_shader(shader)?;
+
+    self.dispatch_autocmds();
+    self.start_message_pager();
+    self.update_mouse_capture()?;
     self.render()?;
 
     Ok(())
@@ -371,27 +433,61 @@ impl EventLoop {
       Some(Ok(event)) => {
         trace!("Polled terminal event ok: {:?}", event);
 
-        let data_access = StatefulDataAccess::new(
-          self.state.clone(),
-          self.tree.clone(),
-          self.buffers.clone(),
-          self.contents.clone(),
-          event,
-        );
+        // Record the typed key into the macro, the keys played by macros are not recorded.
+        if let Event::Key(key_event) = &event {
+          if key_event.kind == KeyEventKind::Press {
+            lock!(self.state).macros_mut().push_key(*key_event);
+          }
+        }
 
-        // Handle by state machine
-        let stateful = self.stateful_machine;
-        let next_stateful = stateful.handle(data_access);
-        {
-          let mut state = lock!(self.state);
-          state.update_state_machine(&next_stateful);
+        // The resize is handled centrally, it doesn't go through the state machine.
+        if let Event::Resize(columns, rows) = event {
+          self.resize(U16Size::new(columns, rows));
+          return;
         }
-        self.stateful_machine = next_stateful;
 
-        // Exit loop and quit.
-        if let StatefulValue::QuitState(_) = next_stateful {
-          self.cancellation_token.cancel();
+        // The focus changes only fire the events.
+        if let Event::FocusGained | Event::FocusLost = event {
+          let event = if event == Event::FocusGained {
+            AutoCmdEvent::FocusGained
+          } else {
+            AutoCmdEvent::FocusLost
+          };
+          lock!(self.state).autocmds_mut().fire(event, None, "");
+          return;
         }
+
+        // Handle by state machine, the typed keys go through the key mappings.
+        let data_access = self.data_access(event.clone());
+        let stateful = self.stateful_machine;
+        let next_stateful = match event {
+          Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+            mapping::feed_key(&data_access, stateful, key_event)
+          }
+          Event::Mouse(mouse_event) => {
+            let next_stateful =
+              mouse::handle(&data_access, stateful, mouse_event);
+            lock!(self.state).update_state_machine(&next_stateful);
+            next_stateful
+          }
+          // The pending keys are resolved before the pasted text, which bypasses the mappings.
+          Event::Paste(_) => {
+            let stateful = if lock!(self.state).keymap_pending().is_empty() {
+              stateful
+            } else {
+              mapping::flush(&data_access, stateful)
+            };
+            let next_stateful = stateful.handle(data_access);
+            lock!(self.state).update_state_machine(&next_stateful);
+            next_stateful
+          }
+          _ => {
+            let next_stateful = stateful.handle(data_access);
+            lock!(self.state).update_state_machine(&next_stateful);
+            next_stateful
+          }
+        };
+        self.update_stateful_machine(next_stateful);
       }
       Some(Err(e)) => {
         error!("Polled terminal event error: {:?}", e);
@@ -404,11 +500,424 @@ impl EventLoop {
     }
   }
 
+  fn data_access(&self, event: Event) -> StatefulDataAccess {
+    StatefulDataAccess::new(
+      self.state.clone(),
+      self.tree.clone(),
+      self.buffers.clone(),
+      self.contents.clone(),
+      event,
+    )
+  }
+
+  /// Save the state machine after an event, and quit if it is the quit state.
+  fn update_stateful_machine(&mut self, next_stateful: StatefulValue) {
+    self.stateful_machine = next_stateful;
+    self.reset_pending_deadline();
+
+    // Exit loop and quit.
+    if let StatefulValue::QuitState(_) = next_stateful {
+      self.fire_changes();
+      lock!(self.state).autocmds_mut().fire(
+        AutoCmdEvent::VimLeavePre,
+        None,
+        "",
+      );
+      self.cancellation_token.cancel();
+    }
+  }
+
+  /// Start (or restart) the timer when there're pending keys after the key, the pending keys
+  /// are dropped if the next key doesn't arrive before the deadline.
+  fn reset_pending_deadline(&mut self) {
+    let (timeout, timeout_len) = {
+      let state = lock!(self.state);
+      let options = state.global_options();
+      (options.timeout(), options.timeout_len())
+    };
+    let pending = !self.stateful_machine.showcmd().is_empty()
+      || !lock!(self.state).keymap_pending().is_empty();
+    self.pending_deadline = if timeout && pending {
+      Some(
+        tokio::time::Instant::now() + Duration::from_millis(timeout_len as u64),
+      )
+    } else {
+      None
+    };
+  }
+
+  /// Drop the pending keys when the timer expires. The keys that are a prefix of some mappings
+  /// are resolved first, i.e. the shorter mapping is executed or the keys are not mapped.
+  fn process_pending_timeout(&mut self) {
+    trace!("Pending keys timeout");
+    self.pending_deadline = None;
+    if !lock!(self.state).keymap_pending().is_empty() {
+      let data_access = self.data_access(Event::FocusGained);
+      let next_stateful = mapping::flush(&data_access, self.stateful_machine);
+      self.update_stateful_machine(next_stateful);
+      return;
+    }
+    if let Some(next_stateful) = self.stateful_machine.handle_timeout() {
+      lock!(self.state).update_state_machine(&next_stateful);
+      self.stateful_machine = next_stateful;
+    }
+  }
+
+  /// Start (or restart) the timer of the `CursorHold` event after an input, the event is fired
+  /// when no key is typed for 'updatetime' milliseconds in normal mode.
+  fn reset_cursor_hold_deadline(&mut self) {
+    let update_time = lock!(self.state).global_options().update_time();
+    let hold = {
+      let state = lock!(self.state);
+      state.mode() == Mode::Normal
+        && state.autocmds().has_listener(AutoCmdEvent::CursorHold)
+    };
+    self.cursor_hold_deadline = if hold
+      && matches!(self.stateful_machine, StatefulValue::NormalMode(_))
+    {
+      Some(
+        tokio::time::Instant::now() + Duration::from_millis(update_time as u64),
+      )
+    } else {
+      None
+    };
+  }
+
+  /// Fire the `CursorHold` event when the timer expires, it is fired once until the next key.
+  fn process_cursor_hold_timeout(&mut self) {
+    trace!("Cursor hold timeout");
+    self.cursor_hold_deadline = None;
+    lock!(self.state)
+      .autocmds_mut()
+      .fire(AutoCmdEvent::CursorHold, None, "");
+  }
+
+  /// Move the events fired by the buffers (i.e. reading or writing the file), the widget tree
+  /// (i.e. the cursor moves) and the texts to the autocommands, see
+  /// [`AutoCmds::fire_changes`](crate::state::autocmd::AutoCmds::fire_changes).
+  fn fire_changes(&mut self) {
+    let mut changes = lock!(self.buffers).take_fired();
+    changes.extend(lock!(self.tree).take_fired());
+    for (buffer_id, buffer) in lock!(self.buffers).iter() {
+      let mut buffer = lock!(buffer);
+      let text_changes = buffer.text_mut().take_changes();
+      // The text of the terminal buffer is changed by the program, not by the user.
+      if buffer.is_terminal() {
+        continue;
+      }
+      changes.extend(text_changes.into_iter().map(|seq| FiredEvent {
+        seq,
+        event: AutoCmdEvent::TextChanged,
+        buffer_id: Some(*buffer_id),
+        data: Default::default(),
+      }));
+    }
+    // The command-line content fires no events.
+    lock!(self.contents)
+      .command_line_content_mut()
+      .take_changes();
+    lock!(self.state).autocmds_mut().fire_changes(changes);
+  }
+
+  // The buffer of current window.
+  fn current_buffer_id(&self) -> Option<BufferId> {
+    let buffer = lock!(self.tree).current_window()?.buffer().upgrade()?;
+    Some(lock!(buffer).id())
+  }
+
+  /// Dispatch the fired events to the autocommands after an input, the events fired by other
+  /// parts of the editor (i.e. the cursor moves) are collected first. Each callback runs in the js
+  /// runtime before the next one is sent, and the exception it throws is reported without
+  /// stopping the other callbacks.
+  fn dispatch_autocmds(&mut self) {
+    self.fire_changes();
+    let current_buffer_id = self.current_buffer_id();
+    let fired = lock!(self.state).autocmds_mut().take_fired();
+
+    for fired in fired {
+      let buffer_id = fired.buffer_id.or(current_buffer_id);
+      let buffer =
+        buffer_id.and_then(|id| lock!(self.buffers).get(&id).cloned());
+      let (file, filetype) = match buffer {
+        Some(buffer) => {
+          let buffer = lock!(buffer);
+          let file = buffer
+            .absolute_filename()
+            .as_ref()
+            .map(|file| file.to_string_lossy().to_compact_string())
+            .unwrap_or_default();
+          (file, buffer.filetype())
+        }
+        None => (Default::default(), ""),
+      };
+      let autocmds = lock!(self.state)
+        .autocmds_mut()
+        .matched(&fired, buffer_id, &file, filetype);
+      for autocmd in autocmds {
+        trace!("Dispatch {} to autocmd:{:?}", fired.event, autocmd.id());
+        let req = jsmsg::AutoCmdReq {
+          future_id: next_future_id(),
+          autocmd_id: autocmd.id(),
+          event: fired.event.name().to_compact_string(),
+          group: autocmd.group().cloned(),
+          buffer_id,
+          file: file.clone(),
+          matched: fired.target(&file).to_compact_string(),
+          data: fired.data.clone(),
+          once: autocmd.once(),
+        };
+        match self
+          .mstr_to_jsrt
+          .try_send(EventLoopToJsRuntimeMessage::AutoCmdReq(req))
+        {
+          Ok(_) => self.js_runtime.tick_event_loop(),
+          Err(e) => error!("Failed to dispatch autocmd:{:?}", e),
+        }
+      }
+    }
+  }
+
+  /// Run the js callback of the mapping, then resolve the keys after it. The keys returned by an
+  /// `<expr>` mapping are handled before the keys typed after the mapping, and they can be mapped
+  /// to another callback, it runs until no callback is waiting.
+  fn run_keymap_callbacks(&mut self) {
+    let mut depth = 0_usize;
+    loop {
+      let Some(keymap) = lock!(self.state).take_keymap_callback() else {
+        break;
+      };
+      let KeymapTarget::Callback(callback_id) = keymap.target() else {
+        unreachable!();
+      };
+
+      depth += 1;
+      if depth > MAX_MAP_DEPTH {
+        lock!(self.contents)
+          .messages_mut()
+          .add(MessageLevel::Error, "E223: Recursive mapping");
+        let mut state = lock!(self.state);
+        state.macros_mut().fail();
+        state.keymap_pending_mut().clear();
+        break;
+      }
+
+      trace!("Run keymap callback:{:?}", callback_id);
+      let req =
+        jsmsg::KeymapReq::new(next_future_id(), *callback_id, keymap.expr());
+      match self
+        .mstr_to_jsrt
+        .try_send(EventLoopToJsRuntimeMessage::KeymapReq(req))
+      {
+        Ok(_) => self.js_runtime.tick_event_loop(),
+        Err(e) => error!("Failed to run keymap callback:{:?}", e),
+      }
+
+      let expr_keys = lock!(self.state)
+        .take_keymap_expr_keys()
+        .map(|keys| keys::parse(&keys))
+        .unwrap_or_default();
+      let data_access = self.data_access(Event::FocusGained);
+      let next_stateful = mapping::resume(
+        &data_access,
+        self.stateful_machine,
+        &keymap,
+        expr_keys,
+      );
+      self.update_stateful_machine(next_stateful);
+    }
+  }
+
+  /// Update the pending keys shown in command-line, i.e. the `showcmd` option.
+  fn update_showcmd(&mut self) {
+    let show_cmd = lock!(self.state).global_options().show_cmd();
+    let showcmd = if show_cmd {
+      let keymap_pending =
+        keys::to_notations(lock!(self.state).keymap_pending());
+      format!("{}{}", self.stateful_machine.showcmd(), keymap_pending)
+    } else {
+      String::new()
+    };
+    let mut contents = lock!(self.contents);
+    if contents.showcmd() != showcmd {
+      contents.set_showcmd(&showcmd);
+    }
+  }
+
+  /// Update the register of the macro being recorded, shown in command-line.
+  fn update_recording(&mut self) {
+    let recording = lock!(self.state).macros().recording();
+    lock!(self.contents).set_recording(recording);
+  }
+
   async fn process_worker_notify(
     &mut self,
     msg: Option<WorkerToMasterMessage>,
   ) {
     trace!("Received {:?} message from workers", msg);
+    if let Some(msg) = msg {
+      match msg {
+        WorkerToMasterMessage::TerminalOutput(output) => {
+          self.sync_terminal(output.buffer_id, |buffer| {
+            buffer.terminal_feed(&output.bytes)
+          });
+        }
+        WorkerToMasterMessage::TerminalExited(exited) => {
+          self.sync_terminal(exited.buffer_id, |buffer| {
+            buffer.terminal_exit(exited.exit_code)
+          });
+          // The program is gone, there's nothing to type into.
+          if let StatefulValue::TerminalMode(_) = self.stateful_machine {
+            let next_stateful =
+              StatefulValue::NormalMode(NormalStateful::default());
+            lock!(self.state).update_state_machine(&next_stateful);
+            self.stateful_machine = next_stateful;
+          }
+        }
+      }
+    }
+  }
+
+  /// Re-layout the canvas, the widget tree and the viewports when the terminal is resized, the
+  /// terminals shown in the windows are resized to the windows.
+  fn resize(&mut self, size: U16Size) {
+    if lock!(self.canvas).size() == size {
+      return;
+    }
+    trace!("Resize terminal:{:?}", size);
+    lock!(self.state)
+      .autocmds_mut()
+      .fire(AutoCmdEvent::VimResized, None, "");
+
+    // The terminal is cleared, then all the cells are printed again.
+    lock!(self.canvas).resize(size);
+    let mut shader = Shader::new();
+    shader.push(ShaderCommand::TerminalClear(crossterm::terminal::Clear(
+      crossterm::terminal::ClearType::All,
+    )));
+    if let Err(e) = self.queue_shader(shader) {
+      error!("Failed to clear terminal:{:?}", e);
+    }
+
+    let mut tree = lock!(self.tree);
+    tree.resize(size);
+
+    let buffers = lock!(self.buffers);
+    for buffer in buffers.values() {
+      lock!(buffer).text().resize_cached_lines(size);
+    }
+    let insert_mode = matches!(
+      self.stateful_machine,
+      StatefulValue::InsertMode(_) | StatefulValue::ReplaceMode(_)
+    );
+    let terminal_mode =
+      matches!(self.stateful_machine, StatefulValue::TerminalMode(_));
+    let current_window_id = tree.current_window_id();
+    for window_id in tree.window_ids().clone() {
+      let window = tree.window(window_id).unwrap();
+      let content_shape = *window.content().actual_shape();
+      let buffer = match window.buffer().upgrade() {
+        Some(buffer) => buffer,
+        None => continue,
+      };
+      // There's no viewport for the empty window, i.e. the terminal is too small.
+      if content_shape.width() == 0 || content_shape.height() == 0 {
+        continue;
+      }
+      let mut buffer = lock!(buffer);
+      let terminal_cursor = buffer.terminal_resize(U16Size::new(
+        content_shape.width(),
+        content_shape.height(),
+      ));
+      let text = buffer.text();
+      cursor_ops::update_viewport_after_resized(
+        &mut tree,
+        window_id,
+        text,
+        insert_mode,
+      );
+
+      // The window follows the terminal cursor in terminal mode.
+      if let Some((line_idx, char_idx)) = terminal_cursor {
+        if terminal_mode && current_window_id == Some(window_id) {
+          cursor_ops::cursor_move(
+            &mut tree,
+            window_id,
+            text,
+            Operation::CursorMoveTo((char_idx, line_idx)),
+            true,
+          );
+        }
+      }
+    }
+
+    let mut contents = lock!(self.contents);
+    contents.resize(size);
+    let cmdline_id = tree
+      .command_line()
+      .filter(|cmdline| cmdline.content().actual_shape().width() > 0)
+      .map(|cmdline| cmdline.id());
+    if let Some(cmdline_id) = cmdline_id {
+      cursor_ops::update_viewport_after_resized(
+        &mut tree,
+        cmdline_id,
+        contents.command_line_content(),
+        true,
+      );
+    }
+  }
+
+  /// Sync the terminal buffer with the terminal by `f`, i.e. feed the program output or mark the
+  /// program as exited. If the terminal buffer is shown in current window, the window follows the
+  /// terminal cursor in terminal mode.
+  fn sync_terminal(
+    &mut self,
+    buffer_id: BufferId,
+    f: impl FnOnce(&mut Buffer) -> Option<(usize, usize)>,
+  ) {
+    let buffer = match lock!(self.buffers).get(&buffer_id) {
+      Some(buffer) => buffer.clone(),
+      None => return,
+    };
+
+    let mut tree = lock!(self.tree);
+    let current_window = tree.current_window().and_then(|window| {
+      let window_buffer = window.buffer().upgrade()?;
+      if Arc::ptr_eq(&window_buffer, &buffer) {
+        let cursor_viewport = window.cursor_viewport();
+        Some((
+          window.id(),
+          (cursor_viewport.line_idx(), cursor_viewport.char_idx()),
+        ))
+      } else {
+        None
+      }
+    });
+
+    let mut buffer = lock!(buffer);
+    let terminal_cursor = f(&mut buffer);
+
+    if let (Some(terminal_cursor), Some((window_id, window_cursor))) =
+      (terminal_cursor, current_window)
+    {
+      let text = buffer.text();
+      let terminal_mode =
+        matches!(self.stateful_machine, StatefulValue::TerminalMode(_));
+      let (line_idx, char_idx) = if terminal_mode {
+        terminal_cursor
+      } else {
+        motion_ops::clamp_target(text, window_cursor)
+      };
+      cursor_ops::_update_viewport_after_text_changed(
+        &mut tree, window_id, text,
+      );
+      cursor_ops::cursor_move(
+        &mut tree,
+        window_id,
+        text,
+        Operation::CursorMoveTo((char_idx, line_idx)),
+        terminal_mode,
+      );
+    }
   }
 
   async fn process_js_runtime_request(
@@ -463,10 +972,13 @@ impl EventLoop {
   pub async fn run(&mut self) -> IoResult<()> {
     let mut reader = EventStream::new();
     loop {
+      let pending_deadline = self.pending_deadline;
+      let cursor_hold_deadline = self.cursor_hold_deadline;
       tokio::select! {
         // Receive keyboard/mouse events
         event = reader.next() => {
           self.process_event(event).await;
+          self.reset_cursor_hold_deadline();
         }
         // Receive notification from workers => master
         worker_msg = self.mstr_from_wkr.recv() => {
@@ -479,6 +991,18 @@ impl EventLoop {
         js_resp = self.jsrt_tick_queue.recv() => {
             self.process_js_runtime_response(js_resp).await;
         }
+        // The pending keys timeout
+        _ = tokio::time::sleep_until(
+          pending_deadline.unwrap_or_else(tokio::time::Instant::now)
+        ), if pending_deadline.is_some() => {
+          self.process_pending_timeout();
+        }
+        // The cursor hold timeout
+        _ = tokio::time::sleep_until(
+          cursor_hold_deadline.unwrap_or_else(tokio::time::Instant::now)
+        ), if cursor_hold_deadline.is_some() => {
+          self.process_cursor_hold_timeout();
+        }
         // Receive cancellation notify
         _ = self.cancellation_token.cancelled() => {
           self.process_cancellation_notify().await;
@@ -487,6 +1011,25 @@ impl EventLoop {
         }
       }
 
+      // Run the js callbacks of the mappings, before the next key.
+      self.run_keymap_callbacks();
+
+      // Call the autocommands of the fired events.
+      self.dispatch_autocmds();
+
+      // Switch to the stateful machine requested by plugins.
+      self.start_requested_stateful();
+
+      // Show the messages that are taller than one line in the pager.
+      self.start_message_pager();
+
+      // Show the pending keys, and the recording macro.
+      self.update_showcmd();
+      self.update_recording();
+
+      // Capture the mouse events if the 'mouse' option enables current mode.
+      self.update_mouse_capture()?;
+
       // Update terminal
       self.render()?;
     }
@@ -494,6 +1037,71 @@ impl EventLoop {
     Ok(())
   }
 
+  /// Switch to the stateful machine requested by plugins, i.e. the selection of a snippet
+  /// placeholder opens in select mode.
+  fn start_requested_stateful(&mut self) {
+    let requested = lock!(self.state).take_requested_stateful();
+    if let Some(next_stateful) = requested {
+      if matches!(
+        self.stateful_machine,
+        StatefulValue::MessagePagerState(_)
+          | StatefulValue::ConfirmState(_)
+          | StatefulValue::QuitState(_)
+      ) {
+        return;
+      }
+      lock!(self.state).update_state_machine(&next_stateful);
+      self.stateful_machine = next_stateful;
+    }
+  }
+
+  /// Start the message pager if the shown messages are taller than one line, the pager takes over
+  /// the keys until it is closed.
+  fn start_message_pager(&mut self) {
+    if matches!(
+      self.stateful_machine,
+      StatefulValue::MessagePagerState(_)
+        | StatefulValue::ConfirmState(_)
+        | StatefulValue::QuitState(_)
+    ) {
+      return;
+    }
+
+    let mut contents = lock!(self.contents);
+    if contents.messages().needs_pager() {
+      contents.messages_mut().start_pager();
+      let mode = lock!(self.state).mode();
+      let next_stateful =
+        StatefulValue::MessagePagerState(MessagePagerStateful::new(mode));
+      lock!(self.state).update_state_machine(&next_stateful);
+      self.stateful_machine = next_stateful;
+    }
+  }
+
+  /// Enable or disable the mouse capture when the 'mouse' option (or current mode) changes, the
+  /// terminal handles the mouse (i.e. selects the text) when it is not captured.
+  fn update_mouse_capture(&mut self) -> IoResult<()> {
+    let mouse_capture = mouse::is_enabled(
+      lock!(self.state).global_options(),
+      &self.stateful_machine,
+    );
+    if mouse_capture != self.mouse_capture {
+      self.mouse_capture = mouse_capture;
+      let mut shader = Shader::new();
+      shader.push(if mouse_capture {
+        ShaderCommand::EventEnableMouseCapture(
+          crossterm::event::EnableMouseCapture,
+        )
+      } else {
+        ShaderCommand::EventDisableMouseCapture(
+          crossterm::event::DisableMouseCapture,
+        )
+      });
+      self.queue_shader(shader)?;
+    }
+    Ok(())
+  }
+
   fn render(&mut self) -> IoResult<()> {
     // Draw UI components to the canvas.
     lock!(self.tree).draw(self.canvas.clone());
@@ -1,11 +1,45 @@
 //! Messages used inside [`EventLoop`](crate::evloop::EventLoop).
 
+use crate::buf::BufferId;
+
 // Worker to Master message {
 
 #[derive(Debug)]
 /// Message.
 pub enum WorkerToMasterMessage {
   // BufferLoadedBytes(BufferLoadedBytes),
+  TerminalOutput(TerminalOutput),
+  TerminalExited(TerminalExited),
+}
+
+#[derive(Debug)]
+/// The output bytes of the program running in a terminal buffer.
+pub struct TerminalOutput {
+  pub buffer_id: BufferId,
+  pub bytes: Vec<u8>,
+}
+
+impl TerminalOutput {
+  pub fn new(buffer_id: BufferId, bytes: Vec<u8>) -> Self {
+    Self { buffer_id, bytes }
+  }
+}
+
+#[derive(Debug)]
+/// The program running in a terminal buffer has exited.
+pub struct TerminalExited {
+  pub buffer_id: BufferId,
+  /// The exit code, or `None` if the program is killed by signal.
+  pub exit_code: Option<i32>,
+}
+
+impl TerminalExited {
+  pub fn new(buffer_id: BufferId, exit_code: Option<i32>) -> Self {
+    Self {
+      buffer_id,
+      exit_code,
+    }
+  }
 }
 
 // Worker to Master message }
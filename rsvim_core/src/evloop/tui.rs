@@ -3,8 +3,8 @@
 use crate::prelude::*;
 
 use crossterm::event::{
-  DisableFocusChange, DisableMouseCapture, EnableFocusChange,
-  EnableMouseCapture,
+  DisableBracketedPaste, DisableFocusChange, DisableMouseCapture,
+  EnableFocusChange,
 };
 use crossterm::{self, execute};
 use std::io::Write;
@@ -20,7 +20,6 @@ pub fn initialize_raw_mode() -> IoResult<()> {
     out,
     crossterm::terminal::EnterAlternateScreen,
     crossterm::terminal::Clear(crossterm::terminal::ClearType::All),
-    EnableMouseCapture,
     EnableFocusChange,
   )?;
   Ok(())
@@ -32,6 +31,7 @@ pub fn shutdown_raw_mode() -> IoResult<()> {
   execute!(
     out,
     DisableMouseCapture,
+    DisableBracketedPaste,
     DisableFocusChange,
     crossterm::terminal::LeaveAlternateScreen,
   )?;
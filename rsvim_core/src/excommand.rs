@@ -1,51 +1,556 @@
 //! Ex-commands.
 
+use crate::opt::OptionTarget;
 use crate::prelude::*;
+use complete::CompletionKind;
+use range::{ExRange, LineSpecifier};
 
-use compact_str::CompactString;
+use compact_str::{CompactString, ToCompactString};
+use std::collections::BTreeMap;
+use std::ops::Bound;
 
-#[derive(Debug)]
-/// Ex-command definition.
+pub mod complete;
+pub mod history;
+pub mod map;
+pub mod messages;
+pub mod quit;
+pub mod range;
+pub mod set;
+pub mod terminal;
+
+#[cfg(test)]
+mod complete_tests;
+#[cfg(test)]
+mod history_tests;
+#[cfg(test)]
+mod map_tests;
+#[cfg(test)]
+mod messages_tests;
+#[cfg(test)]
+mod quit_tests;
+#[cfg(test)]
+mod range_tests;
+#[cfg(test)]
+mod set_tests;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The number of arguments of a user ex command, i.e. `-nargs`.
+pub enum ExCommandNargs {
+  /// No arguments, `0`.
+  Zero,
+  /// Exactly one argument, it includes the whitespaces, `1`.
+  One,
+  /// Any number of arguments, `*`.
+  Any,
+  /// Zero or one argument, it includes the whitespaces, `?`.
+  ZeroOrOne,
+  /// One or more arguments, `+`.
+  OneOrMore,
+}
+
+impl ExCommandNargs {
+  pub fn parse(value: &str) -> Option<Self> {
+    match value {
+      "0" => Some(ExCommandNargs::Zero),
+      "1" => Some(ExCommandNargs::One),
+      "*" => Some(ExCommandNargs::Any),
+      "?" => Some(ExCommandNargs::ZeroOrOne),
+      "+" => Some(ExCommandNargs::OneOrMore),
+      _ => None,
+    }
+  }
+
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      ExCommandNargs::Zero => "0",
+      ExCommandNargs::One => "1",
+      ExCommandNargs::Any => "*",
+      ExCommandNargs::ZeroOrOne => "?",
+      ExCommandNargs::OneOrMore => "+",
+    }
+  }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The range of a user ex command, i.e. `-range` and `-count`.
+pub enum ExCommandRange {
+  /// No range is allowed.
+  None,
+  /// A range of lines, defaults to the cursor line, `-range`.
+  Line,
+  /// A range of lines, defaults to the whole file, `-range=%`.
+  File,
+  /// A count in the line number position or as the first argument, defaults to `N`,
+  /// `-count=N`.
+  Count(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The options of a user ex command.
+pub struct ExCommandOptions {
+  pub nargs: ExCommandNargs,
+  pub range: ExCommandRange,
+  /// The command accepts `!`, i.e. `:Name!`.
+  pub bang: bool,
+  /// The completion of the arguments, the [`CompletionKind::Custom`] is completed by the js
+  /// completer.
+  pub complete: Option<CompletionKind>,
+  pub desc: CompactString,
+}
+
+impl Default for ExCommandOptions {
+  fn default() -> Self {
+    Self {
+      nargs: ExCommandNargs::Zero,
+      range: ExCommandRange::None,
+      bang: false,
+      complete: None,
+      desc: CompactString::new(""),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+/// User ex command definition, it is backed by a js callback.
 pub struct ExCommand {
-  _name: CompactString,
-  // backend: JsCallbackFunction,
+  name: CompactString,
+  callback_id: i32,
+  options: ExCommandOptions,
 }
 
 impl ExCommand {
-  pub fn new(name: CompactString) -> Self {
-    Self { _name: name }
+  pub fn new(
+    name: CompactString,
+    callback_id: i32,
+    options: ExCommandOptions,
+  ) -> Self {
+    Self {
+      name,
+      callback_id,
+      options,
+    }
+  }
+
+  pub fn name(&self) -> &CompactString {
+    &self.name
+  }
+
+  /// The ID of the js callback.
+  pub fn callback_id(&self) -> i32 {
+    self.callback_id
+  }
+
+  pub fn options(&self) -> &ExCommandOptions {
+    &self.options
+  }
+
+  /// Check the parsed command-line against the options, and resolve the range with the cursor
+  /// line index `current`, the last line index `last`, and the line index of the marks.
+  pub fn prepare(
+    &self,
+    cmdline: &ExCommandLine,
+    current: usize,
+    last: usize,
+    mark: impl Fn(char) -> Option<usize>,
+  ) -> ExCommandResult<ExCommandArgs> {
+    let options = &self.options;
+    if cmdline.bang && !options.bang {
+      return Err(ExCommandErr::NoBangAllowed);
+    }
+
+    let mut args = cmdline.args.clone();
+    let (range, count) = match (options.range, cmdline.range) {
+      (ExCommandRange::None, Some(_)) => {
+        return Err(ExCommandErr::NoRangeAllowed);
+      }
+      (ExCommandRange::None, None) => (None, None),
+      (ExCommandRange::Line, None) => (Some((current, current)), None),
+      (ExCommandRange::File, None) => (Some((0, last)), None),
+      (ExCommandRange::Line | ExCommandRange::File, Some(range)) => {
+        let lines = range.resolve(current, last, mark)?;
+        (Some((*lines.start(), *lines.end())), None)
+      }
+      (ExCommandRange::Count(n), range) => {
+        let count = match range {
+          // The count is not a line, i.e. `:100Name` in a short file.
+          Some(range) => match range.end.line {
+            LineSpecifier::Number(n) => {
+              (n as isize + range.end.offset).max(0) as usize
+            }
+            _ => *range.resolve(current, last, mark)?.end() + 1,
+          },
+          None => {
+            let digits = args
+              .find(|c: char| !c.is_ascii_digit())
+              .unwrap_or(args.len());
+            let rest = &args[digits..];
+            if digits > 0 && (rest.is_empty() || rest.starts_with(' ')) {
+              let count = args[..digits].parse().unwrap_or(n);
+              args = rest.trim_start().to_compact_string();
+              count
+            } else {
+              n
+            }
+          }
+        };
+        (None, Some(count))
+      }
+    };
+
+    let fargs = match options.nargs {
+      ExCommandNargs::Zero if !args.is_empty() => {
+        return Err(ExCommandErr::TrailingCharacters(args.to_string()));
+      }
+      ExCommandNargs::One | ExCommandNargs::OneOrMore if args.is_empty() => {
+        return Err(ExCommandErr::ArgumentRequired);
+      }
+      ExCommandNargs::Zero => vec![],
+      ExCommandNargs::One | ExCommandNargs::ZeroOrOne if args.is_empty() => {
+        vec![]
+      }
+      ExCommandNargs::One | ExCommandNargs::ZeroOrOne => vec![args.clone()],
+      ExCommandNargs::Any | ExCommandNargs::OneOrMore => split_args(&args),
+    };
+
+    Ok(ExCommandArgs {
+      name: self.name.clone(),
+      args,
+      fargs,
+      range,
+      bang: cmdline.bang,
+      count,
+    })
   }
 }
 
-#[derive(Debug)]
-pub struct ExCommandsManager {
-  _ex_commands: HashMap<CompactString, ExCommand>,
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The parsed user ex command-line, i.e. `:[range]Name[!] [args]`.
+pub struct ExCommandLine {
+  pub range: Option<ExRange>,
+  /// The typed command name, it can be an abbreviation.
+  pub name: CompactString,
+  pub bang: bool,
+  pub args: CompactString,
 }
 
-arc_mutex_ptr!(ExCommandsManager);
+impl ExCommandLine {
+  /// Parse the command-line content, returns `None` if it is not a user ex command, i.e. the name
+  /// doesn't start with an uppercase letter.
+  pub fn parse(source: &str) -> Option<Self> {
+    let source =
+      source.trim_start_matches(|c: char| c == ':' || c.is_whitespace());
+    let (range, source) = ExRange::parse(source);
+    let source = source.trim_start();
+    let name_end = source
+      .find(|c: char| !c.is_ascii_alphanumeric())
+      .unwrap_or(source.len());
+    let (name, rest) = source.split_at(name_end);
+    if !name.starts_with(|c: char| c.is_ascii_uppercase()) {
+      return None;
+    }
+    let (bang, rest) = match rest.strip_prefix('!') {
+      Some(rest) => (true, rest),
+      None => (false, rest),
+    };
+    // The arguments are separated from the name by whitespaces, i.e. `:Name-x` is invalid.
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+      return None;
+    }
+    Some(Self {
+      range,
+      name: name.to_compact_string(),
+      bang,
+      args: rest.trim().to_compact_string(),
+    })
+  }
+}
 
-impl Default for ExCommandsManager {
-  fn default() -> Self {
-    ExCommandsManager::new()
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The arguments passed to the js callback of a user ex command.
+pub struct ExCommandArgs {
+  /// The full command name.
+  pub name: CompactString,
+  /// The arguments as a string, without the count.
+  pub args: CompactString,
+  /// The arguments split by whitespaces (or the whole arguments if `-nargs` is `1` or `?`).
+  pub fargs: Vec<CompactString>,
+  /// The 0-based line indexes of the range, both are inclusive. It is `None` if the command
+  /// doesn't accept a range. The js callback gets the 1-based line numbers.
+  pub range: Option<(usize, usize)>,
+  pub bang: bool,
+  /// The count, it is `None` if the command doesn't accept a count.
+  pub count: Option<usize>,
+}
+
+/// Split the arguments by whitespaces, i.e. the `:set` arguments and the `fargs` of the user ex
+/// commands. A whitespace escaped by backslash (`\ `) is a part of the argument, and `\\` is a
+/// backslash. Other backslashes are kept.
+pub fn split_args(args: &str) -> Vec<CompactString> {
+  let mut result = vec![];
+  let mut current = CompactString::new("");
+  let mut chars = args.chars();
+  while let Some(c) = chars.next() {
+    if c == '\\' {
+      match chars.next() {
+        Some(next) if next.is_whitespace() || next == '\\' => {
+          current.push(next)
+        }
+        Some(next) => {
+          current.push(c);
+          current.push(next);
+        }
+        None => current.push(c),
+      }
+    } else if c.is_whitespace() {
+      if !current.is_empty() {
+        result.push(current);
+        current = CompactString::new("");
+      }
+    } else {
+      current.push(c);
+    }
   }
+  if !current.is_empty() {
+    result.push(current);
+  }
+  result
+}
+
+/// Whether the `name` is a valid user ex command name, i.e. it starts with an uppercase letter, and
+/// only contains letters and digits.
+pub fn is_valid_name(name: &str) -> bool {
+  name.starts_with(|c: char| c.is_ascii_uppercase())
+    && name.chars().all(|c| c.is_ascii_alphanumeric())
 }
 
+#[derive(Debug, Clone, Default)]
+/// User ex commands.
+pub struct ExCommandsManager {
+  ex_commands: BTreeMap<CompactString, ExCommand>,
+}
+
+arc_mutex_ptr!(ExCommandsManager);
+
 impl ExCommandsManager {
   pub fn new() -> Self {
-    Self {
-      _ex_commands: HashMap::new(),
+    Self::default()
+  }
+
+  /// Insert the command, returns the old command of the same name.
+  pub fn insert(&mut self, cmd: ExCommand) -> Option<ExCommand> {
+    self.ex_commands.insert(cmd.name.clone(), cmd)
+  }
+
+  pub fn remove(&mut self, name: &str) -> Option<ExCommand> {
+    self.ex_commands.remove(name)
+  }
+
+  pub fn get(&self, name: &str) -> Option<&ExCommand> {
+    self.ex_commands.get(name)
+  }
+
+  /// Find the command by the typed name, it can be an abbreviation if it is unambiguous.
+  pub fn find(&self, name: &str) -> ExCommandResult<&ExCommand> {
+    if let Some(cmd) = self.ex_commands.get(name) {
+      return Ok(cmd);
+    }
+    let mut matched = self
+      .ex_commands
+      .range::<str, _>((Bound::Included(name), Bound::Unbounded))
+      .take_while(|(n, _)| n.starts_with(name))
+      .map(|(_, cmd)| cmd);
+    match (matched.next(), matched.next()) {
+      (Some(cmd), None) => Ok(cmd),
+      (Some(_), Some(_)) => {
+        Err(ExCommandErr::AmbiguousCommand(name.to_string()))
+      }
+      _ => Err(ExCommandErr::NotAnEditorCommand(name.to_string())),
     }
   }
 
-  pub fn insert(&mut self, _cmd: ExCommand) -> Option<ExCommand> {
-    None
+  /// The commands in the order of names.
+  pub fn list(&self) -> impl Iterator<Item = &ExCommand> {
+    self.ex_commands.values()
   }
 
-  pub fn remove(&mut self, _name: CompactString) -> Option<ExCommand> {
-    None
+  /// The command names in order.
+  pub fn names(&self) -> Vec<CompactString> {
+    self.ex_commands.keys().cloned().collect()
   }
 
-  pub fn get(&self, _command_line_content: CompactString) -> Option<ExCommand> {
-    None
+  /// Whether the js callback is used by any command.
+  pub fn has_callback(&self, callback_id: i32) -> bool {
+    self
+      .ex_commands
+      .values()
+      .any(|cmd| cmd.callback_id == callback_id)
+  }
+}
+
+/// Whether the `word` is an abbreviation of the ex command `name`, i.e. `word` is a prefix of `name`
+/// and has at least `min_len` chars. For example `:se[t]` is `is_abbrev_of("se", "set", 2)`.
+pub fn is_abbrev_of(word: &str, name: &str, min_len: usize) -> bool {
+  word.len() >= min_len && name.starts_with(word)
+}
+
+/// Full names of all the builtin ex-commands.
+pub const BUILTIN_EX_COMMANDS: [&str; 44] = [
+  "cmap",
+  "cnoremap",
+  "confirm",
+  "cunmap",
+  "exit",
+  "history",
+  "imap",
+  "inoremap",
+  "iunmap",
+  "map",
+  "messages",
+  "nmap",
+  "nnoremap",
+  "noremap",
+  "normal",
+  "nunmap",
+  "omap",
+  "onoremap",
+  "ounmap",
+  "qall",
+  "quit",
+  "quitall",
+  "set",
+  "setglobal",
+  "setlocal",
+  "smap",
+  "snoremap",
+  "sunmap",
+  "terminal",
+  "tmap",
+  "tnoremap",
+  "tunmap",
+  "unmap",
+  "vmap",
+  "vnoremap",
+  "vunmap",
+  "wq",
+  "wqall",
+  "write",
+  "xall",
+  "xit",
+  "xmap",
+  "xnoremap",
+  "xunmap",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Builtin ex-commands, they're implemented in rust and executed by the editor directly, i.e. they
+/// will not be sent to js runtime.
+pub enum BuiltinExCommand {
+  /// `:se[t]`, `:setl[ocal]` and `:setg[lobal]`, with the arguments.
+  Set(OptionTarget, CompactString),
+
+  /// `:his[tory]`, with the arguments.
+  History(CompactString),
+
+  /// `:mes[sages]`, with the arguments.
+  Messages(CompactString),
+
+  /// `:[range]norm[al][!]`, executes the keys (in [key notation](crate::state::keys)) in normal
+  /// mode, on each line of the range, or at the cursor if there's no range.
+  Normal(Option<ExRange>, bool, CompactString),
+
+  /// `:ter[minal]`, with the command to run in the terminal, or the shell if it is empty.
+  Terminal(CompactString),
+
+  /// `:map`, `:noremap`, `:unmap` and their mode variants (i.e. `:nnoremap`), with the modes,
+  /// the kind and the arguments. The `:map!` family has the `!` modes.
+  Map(CompactString, map::MapKind, CompactString),
+
+  /// `:w[rite][!]`, writes current buffer to its file.
+  Write,
+
+  /// `:q[uit][!]`, `:qa[ll][!]`, `:wq[!]`, `:x[it][!]`, `:wqa[ll][!]` and their aliases, with the
+  /// kind, the bang, and whether it is prefixed by `:conf[irm]`, i.e. it asks to save the modified
+  /// buffers instead of refusing to quit.
+  Quit(quit::QuitKind, bool, bool),
+}
+
+impl BuiltinExCommand {
+  /// Parse the command-line content into a builtin ex-command, returns `None` if it is not a
+  /// builtin ex-command.
+  pub fn parse(source: &str) -> Option<Self> {
+    let source =
+      source.trim_start_matches(|c: char| c == ':' || c.is_whitespace());
+    let (range, source) = ExRange::parse(source);
+    let source = source.trim_start();
+    let name_end = source
+      .find(|c: char| !c.is_ascii_alphabetic())
+      .unwrap_or(source.len());
+    let (name, args) = source.split_at(name_end);
+
+    // The keys of `:normal` keep the trailing whitespaces, i.e. `:norm A `.
+    if is_abbrev_of(name, "normal", 4) {
+      let (bang, args) = match args.strip_prefix('!') {
+        Some(args) => (true, args),
+        None => (false, args),
+      };
+      let keys = args.trim_start().to_compact_string();
+      return Some(BuiltinExCommand::Normal(range, bang, keys));
+    }
+
+    // Other builtin ex-commands don't accept a range.
+    if range.is_some() {
+      return None;
+    }
+    let args = args.trim().to_compact_string();
+
+    if is_abbrev_of(name, "set", 2) {
+      Some(BuiltinExCommand::Set(OptionTarget::LocalAndGlobal, args))
+    } else if is_abbrev_of(name, "setlocal", 4) {
+      Some(BuiltinExCommand::Set(OptionTarget::Local, args))
+    } else if is_abbrev_of(name, "setglobal", 4) {
+      Some(BuiltinExCommand::Set(OptionTarget::Global, args))
+    } else if is_abbrev_of(name, "history", 3) {
+      Some(BuiltinExCommand::History(args))
+    } else if is_abbrev_of(name, "messages", 3) {
+      Some(BuiltinExCommand::Messages(args))
+    } else if is_abbrev_of(name, "terminal", 3) {
+      Some(BuiltinExCommand::Terminal(args))
+    } else if is_abbrev_of(name, "write", 1) {
+      // The file name argument is not supported yet.
+      match args.strip_prefix('!').unwrap_or(args.as_str()).trim() {
+        "" => Some(BuiltinExCommand::Write),
+        _ => None,
+      }
+    } else if let Some(kind) = quit::parse_name(name) {
+      match args.strip_prefix('!') {
+        Some(args) if args.trim().is_empty() => {
+          Some(BuiltinExCommand::Quit(kind, true, false))
+        }
+        None if args.is_empty() => {
+          Some(BuiltinExCommand::Quit(kind, false, false))
+        }
+        _ => None,
+      }
+    } else if is_abbrev_of(name, "confirm", 4) {
+      // Only the quit commands ask for confirmation.
+      match BuiltinExCommand::parse(&args) {
+        Some(BuiltinExCommand::Quit(kind, bang, _)) => {
+          Some(BuiltinExCommand::Quit(kind, bang, true))
+        }
+        _ => None,
+      }
+    } else if let Some((modes, kind)) = map::parse_name(name) {
+      // The `:map!`, `:noremap!` and `:unmap!` are for insert and command-line mode.
+      match args.strip_prefix('!') {
+        Some(args) if modes.is_empty() => Some(BuiltinExCommand::Map(
+          "!".into(),
+          kind,
+          args.trim_start().to_compact_string(),
+        )),
+        _ => Some(BuiltinExCommand::Map(modes, kind, args)),
+      }
+    } else {
+      None
+    }
   }
 }
@@ -0,0 +1,528 @@
+//! Command-line completion.
+//!
+//! The completion is context-aware, i.e. it completes ex command names, or the arguments of the ex
+//! command (option names/values for `:set`, file paths for `:edit`, buffer names for `:buffer`,
+//! etc). For other ex commands (i.e. user commands), the completion is provided by the js
+//! completer registered with `Rsvim.cmd.setCompleter`.
+//!
+//! See: <https://vimhelp.org/cmdline.txt.html#cmdline-completion>.
+
+use crate::excommand::{BUILTIN_EX_COMMANDS, is_abbrev_of};
+use crate::opt::{self, OPTIONS, OptionDef, OptionKind, OptionValue};
+
+use compact_str::{CompactString, ToCompactString};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A completion match.
+pub struct CompletionItem {
+  /// The text inserted to the command-line.
+  word: CompactString,
+  /// The text shows in the wildmenu, for example file name without its parent directory.
+  abbr: CompactString,
+}
+
+impl CompletionItem {
+  pub fn new(word: CompactString, abbr: CompactString) -> Self {
+    Self { word, abbr }
+  }
+
+  pub fn word(&self) -> &CompactString {
+    &self.word
+  }
+
+  pub fn abbr(&self) -> &CompactString {
+    &self.abbr
+  }
+}
+
+impl From<&str> for CompletionItem {
+  fn from(value: &str) -> Self {
+    Self::new(value.to_compact_string(), value.to_compact_string())
+  }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// What to complete.
+pub enum CompletionKind {
+  /// Ex command names.
+  Command,
+  /// Option names, for `:set`.
+  Option,
+  /// Option values, for `:set {option}=`, with the option name.
+  OptionValue(&'static str),
+  /// File and directory paths, for `:edit`.
+  File,
+  /// Directory paths, for `:cd`.
+  Directory,
+  /// Buffer names, for `:buffer`.
+  Buffer,
+  /// History names, for `:history`.
+  History,
+  /// The arguments of other ex commands, they're completed by js completers.
+  Custom,
+}
+
+impl CompletionKind {
+  /// Parse the completion kind of a user ex command, i.e. `file` for `-complete=file`. The
+  /// `custom` kind is completed by the js completer.
+  pub fn parse(name: &str) -> Option<Self> {
+    match name {
+      "command" => Some(CompletionKind::Command),
+      "option" => Some(CompletionKind::Option),
+      "file" => Some(CompletionKind::File),
+      "dir" => Some(CompletionKind::Directory),
+      "buffer" => Some(CompletionKind::Buffer),
+      "history" => Some(CompletionKind::History),
+      "custom" => Some(CompletionKind::Custom),
+      _ => None,
+    }
+  }
+
+  /// The name of the completion kind, it is parsed by [`CompletionKind::parse`].
+  pub fn name(&self) -> &'static str {
+    match self {
+      CompletionKind::Command => "command",
+      CompletionKind::Option | CompletionKind::OptionValue(_) => "option",
+      CompletionKind::File => "file",
+      CompletionKind::Directory => "dir",
+      CompletionKind::Buffer => "buffer",
+      CompletionKind::History => "history",
+      CompletionKind::Custom => "custom",
+    }
+  }
+}
+
+/// The argument completion kinds of builtin ex commands, i.e. `(name, min abbreviation length,
+/// kind)`.
+const ARGUMENT_KINDS: [(&str, usize, CompletionKind); 14] = [
+  ("set", 2, CompletionKind::Option),
+  ("setlocal", 4, CompletionKind::Option),
+  ("setglobal", 4, CompletionKind::Option),
+  ("history", 3, CompletionKind::History),
+  ("edit", 1, CompletionKind::File),
+  ("write", 1, CompletionKind::File),
+  ("read", 1, CompletionKind::File),
+  ("split", 2, CompletionKind::File),
+  ("vsplit", 2, CompletionKind::File),
+  ("source", 2, CompletionKind::File),
+  ("cd", 2, CompletionKind::Directory),
+  ("lcd", 2, CompletionKind::Directory),
+  ("buffer", 1, CompletionKind::Buffer),
+  ("sbuffer", 2, CompletionKind::Buffer),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The completion context, parsed from the command-line content before cursor.
+pub struct CompletionContext {
+  /// What to complete.
+  pub kind: CompletionKind,
+  /// The ex command name, it is empty when completing command names.
+  pub command: CompactString,
+  /// The char index in the command-line where the completed text starts, the text from here to
+  /// the cursor is replaced by the completion match.
+  pub start_char_idx: usize,
+  /// The text being completed, i.e. from `start_char_idx` to the cursor.
+  pub lead: CompactString,
+}
+
+/// Find the start char index of the last argument, the whitespace escaped by backslash (`\ `) is
+/// a part of the argument.
+fn _last_arg_start(chars: &[char], from: usize) -> usize {
+  let mut start = from;
+  let mut i = from;
+  while i < chars.len() {
+    if chars[i] == '\\' {
+      i += 2;
+      continue;
+    }
+    if chars[i].is_whitespace() {
+      start = i + 1;
+    }
+    i += 1;
+  }
+  start.min(chars.len())
+}
+
+/// Parse the completion context from the command-line content before cursor.
+pub fn parse_context(line: &str) -> CompletionContext {
+  let chars: Vec<char> = line.chars().collect();
+  let mut i = 0_usize;
+  while i < chars.len() && (chars[i] == ':' || chars[i].is_whitespace()) {
+    i += 1;
+  }
+  let name_start = i;
+  while i < chars.len() && chars[i].is_ascii_alphabetic() {
+    i += 1;
+  }
+  let name: CompactString = chars[name_start..i].iter().collect();
+
+  // Still typing the command name.
+  if i == chars.len() {
+    return CompletionContext {
+      kind: CompletionKind::Command,
+      command: CompactString::new(""),
+      start_char_idx: name_start,
+      lead: name,
+    };
+  }
+
+  // Skip the `!` right after command name, i.e. `:edit!`.
+  if chars[i] == '!' {
+    i += 1;
+  }
+  let start = _last_arg_start(&chars, i);
+  let lead: CompactString = chars[start..].iter().collect();
+
+  let kind = ARGUMENT_KINDS
+    .iter()
+    .find(|(full, min_len, _)| is_abbrev_of(&name, full, *min_len))
+    .map(|(_, _, kind)| *kind)
+    .unwrap_or(CompletionKind::Custom);
+
+  if kind == CompletionKind::Option {
+    // Complete option value after `=`, `:`, `+=`, `^=` or `-=`.
+    if let Some(pos) = lead.find(['=', ':']) {
+      let option_name = lead[..pos].trim_end_matches(['+', '^', '-']);
+      if let Some(def) = opt::find_option(option_name) {
+        let value_start = start + lead[..pos + 1].chars().count();
+        return CompletionContext {
+          kind: CompletionKind::OptionValue(def.name()),
+          command: name,
+          start_char_idx: value_start,
+          lead: CompactString::new(&lead[pos + 1..]),
+        };
+      }
+    }
+  }
+
+  CompletionContext {
+    kind,
+    command: name,
+    start_char_idx: start,
+    lead,
+  }
+}
+
+/// Complete ex command names, both builtin commands and the `custom` (user) commands.
+pub fn complete_commands(
+  lead: &str,
+  custom: &[CompactString],
+) -> Vec<CompletionItem> {
+  let mut result: Vec<CompletionItem> = BUILTIN_EX_COMMANDS
+    .iter()
+    .copied()
+    .chain(custom.iter().map(|name| name.as_str()))
+    .filter(|name| name.starts_with(lead))
+    .map(CompletionItem::from)
+    .collect();
+  result.sort_by(|a, b| a.word.cmp(&b.word));
+  result.dedup();
+  result
+}
+
+/// Complete option names, `no{option}` and `inv{option}` are completed with boolean options.
+pub fn complete_options(lead: &str) -> Vec<CompletionItem> {
+  let mut result = vec![];
+  for (prefix, only_boolean) in [("", false), ("no", true), ("inv", true)] {
+    let Some(lead) = lead.strip_prefix(prefix) else {
+      continue;
+    };
+    for def in OPTIONS.iter() {
+      if only_boolean && def.kind() != OptionKind::Boolean {
+        continue;
+      }
+      if def.name().starts_with(lead) {
+        result.push(CompletionItem::from(
+          format!("{prefix}{}", def.name()).as_str(),
+        ));
+      }
+    }
+  }
+  result.sort_by(|a, b| a.word.cmp(&b.word));
+  result.dedup();
+  result
+}
+
+/// Complete option values. When `lead` is empty, the `current` value is the first match.
+pub fn complete_option_values(
+  def: &OptionDef,
+  lead: &str,
+  current: &OptionValue,
+) -> Vec<CompletionItem> {
+  let mut result = vec![];
+  if lead.is_empty() && def.kind() != OptionKind::Boolean {
+    let current = current.to_compact_string();
+    if !current.is_empty() {
+      result.push(CompletionItem::from(current.as_str()));
+    }
+  }
+  for value in def.values() {
+    if value.starts_with(lead) && !result.iter().any(|r| r.word == *value) {
+      result.push(CompletionItem::from(*value));
+    }
+  }
+  result
+}
+
+/// Complete history names.
+pub fn complete_history_names(lead: &str) -> Vec<CompletionItem> {
+  ["all", "cmd", "input", "search"]
+    .into_iter()
+    .filter(|name| name.starts_with(lead))
+    .map(CompletionItem::from)
+    .collect()
+}
+
+/// Complete buffer names, i.e. any buffer name that contains `lead`.
+pub fn complete_buffers(
+  lead: &str,
+  names: &[CompactString],
+) -> Vec<CompletionItem> {
+  let lead = _unescape(lead);
+  names
+    .iter()
+    .filter(|name| name.contains(lead.as_str()))
+    .map(|name| {
+      CompletionItem::new(_escape(name).to_compact_string(), name.clone())
+    })
+    .collect()
+}
+
+fn _escape(s: &str) -> String {
+  let mut result = String::with_capacity(s.len());
+  for c in s.chars() {
+    if c.is_whitespace() || c == '\\' {
+      result.push('\\');
+    }
+    result.push(c);
+  }
+  result
+}
+
+fn _unescape(s: &str) -> String {
+  let mut result = String::with_capacity(s.len());
+  let mut chars = s.chars();
+  while let Some(c) = chars.next() {
+    if c == '\\' {
+      match chars.next() {
+        Some(next) => result.push(next),
+        None => result.push(c),
+      }
+    } else {
+      result.push(c);
+    }
+  }
+  result
+}
+
+/// Expand the leading `~` to home directory, and `$VAR`/`${VAR}` to environment variables.
+/// Unknown environment variables are kept as they are.
+pub fn expand_path(path: &str, home: Option<&Path>) -> String {
+  let mut result = String::with_capacity(path.len());
+  let mut rest = path;
+
+  if let Some(home) = home {
+    if rest == "~" || rest.starts_with("~/") {
+      result.push_str(&home.to_string_lossy());
+      rest = &rest[1..];
+    }
+  }
+
+  let mut chars = rest.char_indices().peekable();
+  while let Some((i, c)) = chars.next() {
+    if c != '$' {
+      result.push(c);
+      continue;
+    }
+    let (name, end) = if rest[i + 1..].starts_with('{') {
+      match rest[i + 2..].find('}') {
+        Some(close) => (&rest[i + 2..i + 2 + close], i + 3 + close),
+        None => ("", i + 1),
+      }
+    } else {
+      let len = rest[i + 1..]
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len() - i - 1);
+      (&rest[i + 1..i + 1 + len], i + 1 + len)
+    };
+    match std::env::var(name) {
+      Ok(value) if !name.is_empty() => {
+        result.push_str(&value);
+        while chars.peek().is_some_and(|(j, _)| *j < end) {
+          chars.next();
+        }
+      }
+      _ => result.push(c),
+    }
+  }
+  result
+}
+
+/// Whether the `name` matches the glob `pattern`, `*` matches any chars, `?` matches a single
+/// char.
+pub fn glob_matches(pattern: &str, name: &str) -> bool {
+  let pattern: Vec<char> = pattern.chars().collect();
+  let name: Vec<char> = name.chars().collect();
+  let (mut p, mut n) = (0_usize, 0_usize);
+  let mut backtrack: Option<(usize, usize)> = None;
+  while n < name.len() {
+    if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+      p += 1;
+      n += 1;
+    } else if p < pattern.len() && pattern[p] == '*' {
+      backtrack = Some((p, n));
+      p += 1;
+    } else if let Some((bp, bn)) = backtrack {
+      p = bp + 1;
+      n = bn + 1;
+      backtrack = Some((bp, bn + 1));
+    } else {
+      return false;
+    }
+  }
+  while p < pattern.len() && pattern[p] == '*' {
+    p += 1;
+  }
+  p == pattern.len()
+}
+
+/// Whether the file `name` is ignored by the comma-separated 'wildignore' patterns.
+pub fn is_wild_ignored(name: &str, wild_ignore: &str) -> bool {
+  let name = name.trim_end_matches('/');
+  wild_ignore
+    .split(',')
+    .filter(|pattern| !pattern.is_empty())
+    .any(|pattern| glob_matches(pattern.trim_end_matches('/'), name))
+}
+
+/// Complete file paths relative to `cwd`, the leading `~` and environment variables are expanded
+/// when reading the file system, but the completed text keeps the typed directory part.
+///
+/// The directories end with `/`, and the hidden files are only completed when the file name part
+/// starts with `.`.
+pub fn complete_files(
+  lead: &str,
+  cwd: &Path,
+  home: Option<&Path>,
+  wild_ignore: &str,
+  directories_only: bool,
+) -> Vec<CompletionItem> {
+  let lead = _unescape(lead);
+  let (dir_part, file_part) = match lead.rfind('/') {
+    Some(pos) => (&lead[..pos + 1], &lead[pos + 1..]),
+    None => ("", lead.as_str()),
+  };
+
+  let dir = if dir_part.is_empty() {
+    cwd.to_path_buf()
+  } else {
+    let expanded = PathBuf::from(expand_path(dir_part, home));
+    if expanded.is_absolute() {
+      expanded
+    } else {
+      cwd.join(expanded)
+    }
+  };
+
+  let Ok(entries) = std::fs::read_dir(&dir) else {
+    return vec![];
+  };
+
+  let mut result = vec![];
+  for entry in entries.flatten() {
+    let name = entry.file_name().to_string_lossy().to_compact_string();
+    if !name.starts_with(file_part) {
+      continue;
+    }
+    if name.starts_with('.') && !file_part.starts_with('.') {
+      continue;
+    }
+    let is_dir = entry.path().is_dir();
+    if directories_only && !is_dir {
+      continue;
+    }
+    if is_wild_ignored(&name, wild_ignore) {
+      continue;
+    }
+    let abbr = if is_dir {
+      format!("{name}/").to_compact_string()
+    } else {
+      name
+    };
+    let word = _escape(&format!("{dir_part}{abbr}")).to_compact_string();
+    result.push(CompletionItem::new(word, abbr));
+  }
+  result.sort_by(|a, b| a.word.cmp(&b.word));
+  result
+}
+
+/// The longest common prefix of all the completion matches.
+pub fn longest_common_prefix(items: &[CompletionItem]) -> CompactString {
+  let Some(first) = items.first() else {
+    return CompactString::new("");
+  };
+  let mut len = first.word.len();
+  for item in items.iter().skip(1) {
+    len = first
+      .word
+      .char_indices()
+      .zip(item.word.chars())
+      .take_while(|((i, a), b)| *i < len && a == b)
+      .map(|((i, a), _)| i + a.len_utf8())
+      .last()
+      .unwrap_or(0);
+  }
+  CompactString::new(&first.word[..len])
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+/// A part of the 'wildmode' option, i.e. what a `<Tab>` does.
+pub struct WildModePart {
+  /// Complete till the longest common string.
+  pub longest: bool,
+  /// Complete the next full match.
+  pub full: bool,
+  /// Show all the matches in the wildmenu.
+  pub list: bool,
+}
+
+/// Parse the 'wildmode' option, it is a comma-separated list of at most 4 parts, the first part is
+/// used for the first `<Tab>`, the second part for the second `<Tab>`, and so on. The last part is
+/// used for all the following `<Tab>`.
+///
+/// Returns `None` if the option value is invalid.
+pub fn parse_wild_mode(value: &str) -> Option<Vec<WildModePart>> {
+  let mut result = vec![];
+  for part in value.split(',') {
+    let mut mode = WildModePart::default();
+    let mut items = part.split(':');
+    match items.next()? {
+      "full" => {
+        mode.full = true;
+        mode.list = true;
+      }
+      "longest" => mode.longest = true,
+      "list" => mode.list = true,
+      _ => return None,
+    }
+    match items.next() {
+      None => {}
+      // `longest:full` only starts the wildmenu, doesn't select the match.
+      Some("full") if mode.longest => mode.list = true,
+      Some("full") if mode.list && !mode.full => mode.full = true,
+      Some("longest") if mode.list && !mode.full => mode.longest = true,
+      Some("list") if mode.longest => mode.list = true,
+      Some(_) => return None,
+    }
+    if items.next().is_some() {
+      return None;
+    }
+    result.push(mode);
+  }
+  if result.is_empty() || result.len() > 4 {
+    None
+  } else {
+    Some(result)
+  }
+}
@@ -0,0 +1,307 @@
+use super::complete::*;
+
+use crate::opt::{OptionValue, find_option};
+use crate::test::log::init as test_log_init;
+
+use assert_fs::prelude::*;
+use compact_str::CompactString;
+use std::path::Path;
+
+fn words(items: &[CompletionItem]) -> Vec<&str> {
+  items.iter().map(|i| i.word().as_str()).collect()
+}
+
+#[test]
+fn parse_context1() {
+  test_log_init();
+  let ctx = parse_context("se");
+  assert_eq!(ctx.kind, CompletionKind::Command);
+  assert_eq!(ctx.start_char_idx, 0);
+  assert_eq!(ctx.lead, "se");
+
+  let ctx = parse_context("  :his");
+  assert_eq!(ctx.kind, CompletionKind::Command);
+  assert_eq!(ctx.start_char_idx, 3);
+  assert_eq!(ctx.lead, "his");
+
+  let ctx = parse_context("");
+  assert_eq!(ctx.kind, CompletionKind::Command);
+  assert_eq!(ctx.lead, "");
+}
+
+#[test]
+fn parse_context2() {
+  test_log_init();
+  let ctx = parse_context("set wrap ts");
+  assert_eq!(ctx.kind, CompletionKind::Option);
+  assert_eq!(ctx.command, "set");
+  assert_eq!(ctx.start_char_idx, 9);
+  assert_eq!(ctx.lead, "ts");
+
+  let ctx = parse_context("setl ");
+  assert_eq!(ctx.kind, CompletionKind::Option);
+  assert_eq!(ctx.start_char_idx, 5);
+  assert_eq!(ctx.lead, "");
+
+  let ctx = parse_context("set ff=u");
+  assert_eq!(ctx.kind, CompletionKind::OptionValue("fileformat"));
+  assert_eq!(ctx.start_char_idx, 7);
+  assert_eq!(ctx.lead, "u");
+
+  let ctx = parse_context("set wim+=");
+  assert_eq!(ctx.kind, CompletionKind::OptionValue("wildmode"));
+  assert_eq!(ctx.start_char_idx, 9);
+  assert_eq!(ctx.lead, "");
+
+  // Unknown option.
+  let ctx = parse_context("set xyz=a");
+  assert_eq!(ctx.kind, CompletionKind::Option);
+  assert_eq!(ctx.lead, "xyz=a");
+}
+
+#[test]
+fn parse_context3() {
+  test_log_init();
+  let ctx = parse_context("e src/ma");
+  assert_eq!(ctx.kind, CompletionKind::File);
+  assert_eq!(ctx.start_char_idx, 2);
+  assert_eq!(ctx.lead, "src/ma");
+
+  let ctx = parse_context("edit! my\\ fi");
+  assert_eq!(ctx.kind, CompletionKind::File);
+  assert_eq!(ctx.start_char_idx, 6);
+  assert_eq!(ctx.lead, "my\\ fi");
+
+  let ctx = parse_context("cd ~/");
+  assert_eq!(ctx.kind, CompletionKind::Directory);
+
+  let ctx = parse_context("b main");
+  assert_eq!(ctx.kind, CompletionKind::Buffer);
+  assert_eq!(ctx.lead, "main");
+
+  let ctx = parse_context("his s");
+  assert_eq!(ctx.kind, CompletionKind::History);
+
+  let ctx = parse_context("Color gr");
+  assert_eq!(ctx.kind, CompletionKind::Custom);
+  assert_eq!(ctx.command, "Color");
+  assert_eq!(ctx.start_char_idx, 6);
+  assert_eq!(ctx.lead, "gr");
+}
+
+#[test]
+fn complete_commands1() {
+  test_log_init();
+  assert_eq!(
+    words(&complete_commands("se", &[])),
+    vec!["set", "setglobal", "setlocal"]
+  );
+  assert_eq!(words(&complete_commands("h", &[])), vec!["history"]);
+  assert_eq!(
+    words(&complete_commands(
+      "s",
+      &[CompactString::new("Sort"), CompactString::new("sum")]
+    )),
+    vec![
+      "set",
+      "setglobal",
+      "setlocal",
+      "smap",
+      "snoremap",
+      "sum",
+      "sunmap"
+    ]
+  );
+  assert_eq!(
+    words(&complete_commands("q", &[])),
+    vec!["qall", "quit", "quitall"]
+  );
+  assert!(complete_commands("z", &[]).is_empty());
+}
+
+#[test]
+fn complete_options1() {
+  test_log_init();
+  assert_eq!(
+    words(&complete_options("wi")),
+    vec!["wildignore", "wildmode", "wildoptions"]
+  );
+  assert_eq!(words(&complete_options("nowr")), vec!["nowrap"]);
+  assert_eq!(words(&complete_options("invli")), vec!["invlinebreak"]);
+  // Number options don't have `no` prefix.
+  assert!(complete_options("notab").is_empty());
+}
+
+#[test]
+fn complete_option_values1() {
+  test_log_init();
+  let def = find_option("fileformat").unwrap();
+  let current = OptionValue::String(CompactString::new("unix"));
+  assert_eq!(
+    words(&complete_option_values(def, "", &current)),
+    vec!["unix", "dos", "mac"]
+  );
+  assert_eq!(
+    words(&complete_option_values(def, "d", &current)),
+    vec!["dos"]
+  );
+
+  let def = find_option("tabstop").unwrap();
+  let current = OptionValue::Number(8);
+  assert_eq!(words(&complete_option_values(def, "", &current)), vec!["8"]);
+  assert!(complete_option_values(def, "1", &current).is_empty());
+}
+
+#[test]
+fn complete_history_names1() {
+  test_log_init();
+  assert_eq!(
+    words(&complete_history_names("")),
+    vec!["all", "cmd", "input", "search"]
+  );
+  assert_eq!(words(&complete_history_names("s")), vec!["search"]);
+}
+
+#[test]
+fn complete_buffers1() {
+  test_log_init();
+  let names = vec![
+    CompactString::new("src/main.rs"),
+    CompactString::new("src/lib.rs"),
+    CompactString::new("my file.txt"),
+  ];
+  assert_eq!(
+    words(&complete_buffers("main", &names)),
+    vec!["src/main.rs"]
+  );
+  assert_eq!(
+    words(&complete_buffers("src", &names)),
+    vec!["src/main.rs", "src/lib.rs"]
+  );
+  let items = complete_buffers("my\\ f", &names);
+  assert_eq!(words(&items), vec!["my\\ file.txt"]);
+  assert_eq!(items[0].abbr(), "my file.txt");
+}
+
+#[test]
+fn glob_matches1() {
+  test_log_init();
+  assert!(glob_matches("*.o", "main.o"));
+  assert!(!glob_matches("*.o", "main.rs"));
+  assert!(glob_matches("?.txt", "a.txt"));
+  assert!(!glob_matches("?.txt", "ab.txt"));
+  assert!(glob_matches("*", ""));
+  assert!(glob_matches("a*b*c", "aXXbYYc"));
+  assert!(!glob_matches("a*b*c", "aXXbYY"));
+  assert!(is_wild_ignored("target/", "*.o,target"));
+  assert!(!is_wild_ignored("src", "*.o,target"));
+  assert!(!is_wild_ignored("src", ""));
+}
+
+#[test]
+fn expand_path1() {
+  test_log_init();
+  let home = Path::new("/home/user");
+  assert_eq!(expand_path("~/src", Some(home)), "/home/user/src");
+  assert_eq!(expand_path("~", Some(home)), "/home/user");
+  assert_eq!(expand_path("a~/src", Some(home)), "a~/src");
+  assert_eq!(expand_path("~/src", None), "~/src");
+  assert_eq!(
+    expand_path("$RSVIM_COMPLETE_TESTS_NOT_EXIST/a", None),
+    "$RSVIM_COMPLETE_TESTS_NOT_EXIST/a"
+  );
+  if let Ok(path) = std::env::var("PATH") {
+    assert_eq!(expand_path("$PATH/a", None), format!("{path}/a"));
+    assert_eq!(expand_path("${PATH}/a", None), format!("{path}/a"));
+  }
+}
+
+#[test]
+fn complete_files1() {
+  test_log_init();
+  let dir = assert_fs::TempDir::new().unwrap();
+  dir.child("main.rs").touch().unwrap();
+  dir.child("main.o").touch().unwrap();
+  dir.child("my file.txt").touch().unwrap();
+  dir.child(".hidden").touch().unwrap();
+  dir.child("src/lib.rs").touch().unwrap();
+
+  let items = complete_files("m", dir.path(), None, "", false);
+  assert_eq!(words(&items), vec!["main.o", "main.rs", "my\\ file.txt"]);
+  assert_eq!(items[2].abbr(), "my file.txt");
+
+  let items = complete_files("m", dir.path(), None, "*.o", false);
+  assert_eq!(words(&items), vec!["main.rs", "my\\ file.txt"]);
+
+  let items = complete_files("", dir.path(), None, "", true);
+  assert_eq!(words(&items), vec!["src/"]);
+
+  let items = complete_files(".", dir.path(), None, "", false);
+  assert_eq!(words(&items), vec![".hidden"]);
+
+  let items = complete_files("src/l", dir.path(), None, "", false);
+  assert_eq!(words(&items), vec!["src/lib.rs"]);
+  assert_eq!(items[0].abbr(), "lib.rs");
+
+  // Home directory.
+  let items =
+    complete_files("~/src/", Path::new("/"), Some(dir.path()), "", false);
+  assert_eq!(words(&items), vec!["~/src/lib.rs"]);
+}
+
+#[test]
+fn longest_common_prefix1() {
+  test_log_init();
+  let items: Vec<CompletionItem> = ["setglobal", "setlocal", "set"]
+    .iter()
+    .map(|w| CompletionItem::from(*w))
+    .collect();
+  assert_eq!(longest_common_prefix(&items), "set");
+  let items: Vec<CompletionItem> = ["中文a", "中文b"]
+    .iter()
+    .map(|w| CompletionItem::from(*w))
+    .collect();
+  assert_eq!(longest_common_prefix(&items), "中文");
+  assert_eq!(longest_common_prefix(&[]), "");
+}
+
+#[test]
+fn parse_wild_mode1() {
+  test_log_init();
+  let full = WildModePart {
+    longest: false,
+    full: true,
+    list: true,
+  };
+  let longest = WildModePart {
+    longest: true,
+    full: false,
+    list: false,
+  };
+  let longest_list = WildModePart {
+    longest: true,
+    full: false,
+    list: true,
+  };
+  let list = WildModePart {
+    longest: false,
+    full: false,
+    list: true,
+  };
+  let list_full = WildModePart {
+    longest: false,
+    full: true,
+    list: true,
+  };
+  assert_eq!(parse_wild_mode("full"), Some(vec![full]));
+  assert_eq!(parse_wild_mode("longest,full"), Some(vec![longest, full]));
+  assert_eq!(parse_wild_mode("longest:full"), Some(vec![longest_list]));
+  assert_eq!(parse_wild_mode("longest:list"), Some(vec![longest_list]));
+  assert_eq!(parse_wild_mode("list:full"), Some(vec![list_full]));
+  assert_eq!(parse_wild_mode("list:longest"), Some(vec![longest_list]));
+  assert_eq!(parse_wild_mode("list"), Some(vec![list]));
+  assert_eq!(parse_wild_mode(""), None);
+  assert_eq!(parse_wild_mode("full:list"), None);
+  assert_eq!(parse_wild_mode("foo"), None);
+  assert_eq!(parse_wild_mode("full,full,full,full,full"), None);
+}
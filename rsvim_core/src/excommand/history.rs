@@ -0,0 +1,40 @@
+//! The `:history` ex command.
+//!
+//! See: <https://vimhelp.org/cmdline.txt.html#%3Ahistory>.
+
+use crate::content::history::{History, HistoryKind};
+use crate::prelude::*;
+
+/// Parse the `:history` argument into history types, i.e. `cmd` (or `:`), `search` (both `/` and
+/// `?`), `/`, `?`, `input` (or `@`) and `all`. Without argument it is `cmd`.
+pub fn parse_arg(arg: &str) -> ExCommandResult<Vec<HistoryKind>> {
+  match arg.trim() {
+    "" | ":" | "cmd" => Ok(vec![HistoryKind::Cmd]),
+    "/" => Ok(vec![HistoryKind::SearchForward]),
+    "?" => Ok(vec![HistoryKind::SearchBackward]),
+    "search" => Ok(vec![
+      HistoryKind::SearchForward,
+      HistoryKind::SearchBackward,
+    ]),
+    "@" | "input" => Ok(vec![HistoryKind::Input]),
+    "all" => Ok(HistoryKind::ALL.to_vec()),
+    arg => Err(ExCommandErr::TrailingCharacters(arg.to_string())),
+  }
+}
+
+/// Execute `:history` with the argument, returns the output messages.
+///
+/// Each history type starts with a title line, then entries from oldest to newest with their
+/// index, the newest entry is marked with `>`.
+pub fn execute(history: &History, arg: &str) -> ExCommandResult<Vec<String>> {
+  let mut messages = vec![];
+  for kind in parse_arg(arg)? {
+    messages.push(format!("      #  {} history", kind.name()));
+    let entries = history.entries(kind);
+    for (i, entry) in entries.iter().enumerate() {
+      let mark = if i + 1 == entries.len() { '>' } else { ' ' };
+      messages.push(format!("{}{:>5}  {}", mark, i + 1, entry.text()));
+    }
+  }
+  Ok(messages)
+}
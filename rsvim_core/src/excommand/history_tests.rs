@@ -0,0 +1,52 @@
+use super::history::*;
+
+use crate::content::history::{History, HistoryKind};
+use crate::prelude::*;
+
+#[test]
+fn parse_arg1() {
+  assert_eq!(parse_arg(""), Ok(vec![HistoryKind::Cmd]));
+  assert_eq!(parse_arg(":"), Ok(vec![HistoryKind::Cmd]));
+  assert_eq!(parse_arg("cmd"), Ok(vec![HistoryKind::Cmd]));
+  assert_eq!(parse_arg("/"), Ok(vec![HistoryKind::SearchForward]));
+  assert_eq!(parse_arg("?"), Ok(vec![HistoryKind::SearchBackward]));
+  assert_eq!(
+    parse_arg("search"),
+    Ok(vec![
+      HistoryKind::SearchForward,
+      HistoryKind::SearchBackward
+    ])
+  );
+  assert_eq!(parse_arg("input"), Ok(vec![HistoryKind::Input]));
+  assert_eq!(parse_arg("all"), Ok(HistoryKind::ALL.to_vec()));
+  assert_eq!(
+    parse_arg("foo"),
+    Err(ExCommandErr::TrailingCharacters("foo".to_string()))
+  );
+}
+
+#[test]
+fn execute1() {
+  let mut history = History::new(10);
+  history.add(HistoryKind::Cmd, "set ts=4");
+  history.add(HistoryKind::Cmd, "history");
+  history.add(HistoryKind::SearchForward, "foo");
+
+  assert_eq!(
+    execute(&history, ""),
+    Ok(vec![
+      "      #  cmd history".to_string(),
+      "      1  set ts=4".to_string(),
+      ">     2  history".to_string(),
+    ])
+  );
+  assert_eq!(
+    execute(&history, "search"),
+    Ok(vec![
+      "      #  search forward history".to_string(),
+      ">     1  foo".to_string(),
+      "      #  search backward history".to_string(),
+    ])
+  );
+  assert!(execute(&history, "bar").is_err());
+}
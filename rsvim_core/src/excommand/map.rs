@@ -0,0 +1,196 @@
+//! The `:map`, `:noremap` and `:unmap` ex commands, and their mode variants, i.e. `:nnoremap`.
+//!
+//! See: <https://vimhelp.org/map.txt.html#%3Amap-commands>.
+
+use crate::buf::BufferId;
+use crate::excommand::is_abbrev_of;
+use crate::prelude::*;
+use crate::state::keymap::{
+  Keymap, KeymapOptions, KeymapTarget, Keymaps, mode_char, parse_modes,
+};
+use crate::state::keys;
+use crate::state::mode::Mode;
+
+use compact_str::CompactString;
+
+// The modes in the order of listing.
+const LIST_MODES: [Mode; 7] = [
+  Mode::Normal,
+  Mode::Visual,
+  Mode::Select,
+  Mode::OperatorPending,
+  Mode::Insert,
+  Mode::CommandLineEx,
+  Mode::Terminal,
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The kind of the mapping commands.
+pub enum MapKind {
+  /// `:map`, the right-hand side is remapped.
+  Map,
+  /// `:noremap`, the right-hand side is not remapped.
+  Noremap,
+  /// `:unmap`.
+  Unmap,
+}
+
+/// Parse the command name into the modes (in [`parse_modes`] format) and the kind, i.e.
+/// `nn[oremap]` is `("n", Noremap)`. The modes are empty for `:map`, `:noremap` and `:unmap`.
+///
+/// Returns `None` if it is not a mapping command.
+pub fn parse_name(name: &str) -> Option<(CompactString, MapKind)> {
+  if is_abbrev_of(name, "map", 3) {
+    return Some(("".into(), MapKind::Map));
+  }
+  if is_abbrev_of(name, "noremap", 2) {
+    return Some(("".into(), MapKind::Noremap));
+  }
+  if is_abbrev_of(name, "unmap", 3) {
+    return Some(("".into(), MapKind::Unmap));
+  }
+
+  let mode = name.chars().next()?;
+  if !"nvxsoict".contains(mode) {
+    return None;
+  }
+  let rest = &name[1..];
+  let kind = if is_abbrev_of(rest, "map", 1) {
+    MapKind::Map
+  } else if is_abbrev_of(rest, "noremap", 1) {
+    MapKind::Noremap
+  } else if is_abbrev_of(rest, "unmap", 2) {
+    MapKind::Unmap
+  } else {
+    return None;
+  };
+  Some((mode.to_string().into(), kind))
+}
+
+#[derive(Debug, Default)]
+// The special arguments, i.e. `<buffer>`, `<silent>`, `<unique>`.
+struct MapArgs<'a> {
+  buffer: bool,
+  silent: bool,
+  unique: bool,
+  lhs: &'a str,
+  rhs: &'a str,
+}
+
+fn _parse_args(args: &str) -> ExCommandResult<MapArgs<'_>> {
+  let mut result = MapArgs::default();
+  let mut rest = args.trim_start();
+  loop {
+    let special = ["<buffer>", "<silent>", "<unique>", "<expr>"]
+      .into_iter()
+      .find(|s| {
+        rest
+          .get(..s.len())
+          .is_some_and(|p| p.eq_ignore_ascii_case(s))
+      });
+    let Some(special) = special else {
+      break;
+    };
+    match special {
+      "<buffer>" => result.buffer = true,
+      "<silent>" => result.silent = true,
+      "<unique>" => result.unique = true,
+      // The `<expr>` needs a js callback, see `Rsvim.keymap.set`.
+      _ => return Err(ExCommandErr::InvalidArgument(special.to_string())),
+    }
+    rest = rest[special.len()..].trim_start();
+  }
+
+  let lhs_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+  result.lhs = &rest[..lhs_end];
+  result.rhs = rest[lhs_end..].trim();
+  Ok(result)
+}
+
+// The line of a mapping in the list, i.e. `n  x           * dl`.
+fn _list_line(mode: Mode, keymap: &Keymap) -> String {
+  let flags = format!(
+    "{}{}",
+    if keymap.noremap() { "*" } else { " " },
+    if keymap.buffer().is_some() { "@" } else { " " },
+  );
+  let mut line = format!(
+    "{:<3}{:<12} {}{}",
+    mode_char(mode),
+    keys::to_notations(keymap.lhs()),
+    flags,
+    keymap.rhs_notation()
+  );
+  if !keymap.desc().is_empty() {
+    line.push_str(&format!("  {}", keymap.desc()));
+  }
+  line
+}
+
+/// Execute the mapping command with the `modes` (from [`parse_name`]) and the arguments, the
+/// `buffer` is current buffer. Returns the output messages.
+///
+/// - `:map` lists the mappings, `:map {lhs}` lists the mappings starting with `{lhs}`.
+/// - `:map {lhs} {rhs}` sets the mapping, `<Nop>` is no keys.
+/// - `:unmap {lhs}` deletes the mapping.
+pub fn execute(
+  keymaps: &mut Keymaps,
+  buffer: BufferId,
+  modes: &str,
+  kind: MapKind,
+  args: &str,
+) -> ExCommandResult<Vec<String>> {
+  let modes = parse_modes(modes)
+    .ok_or_else(|| ExCommandErr::InvalidArgument(modes.to_string()))?;
+  let args = _parse_args(args)?;
+  let current_buffer = buffer;
+  let buffer = if args.buffer { Some(buffer) } else { None };
+  let lhs = keymaps.parse_lhs(args.lhs);
+
+  match kind {
+    MapKind::Unmap => {
+      if lhs.is_empty() {
+        return Err(ExCommandErr::InvalidArgument(String::new()));
+      }
+      if keymaps.del(&modes, &lhs, buffer).is_empty() {
+        return Err(ExCommandErr::NoSuchMapping);
+      }
+      Ok(vec![])
+    }
+    _ if args.rhs.is_empty() => {
+      let mut lines = vec![];
+      for mode in LIST_MODES.iter().filter(|mode| modes.contains(mode)) {
+        // The buffer-local mappings are listed first, only them with `<buffer>`.
+        for keymap in keymaps.list(*mode, Some(current_buffer)) {
+          if keymap.lhs().starts_with(&lhs)
+            && (!args.buffer || keymap.buffer().is_some())
+          {
+            lines.push(_list_line(*mode, keymap));
+          }
+        }
+      }
+      if lines.is_empty() {
+        lines.push("No mapping found".to_string());
+      }
+      Ok(lines)
+    }
+    _ => {
+      if args.unique
+        && modes
+          .iter()
+          .any(|mode| keymaps.get(*mode, &lhs, buffer).is_some())
+      {
+        return Err(ExCommandErr::MappingAlreadyExists(args.lhs.to_string()));
+      }
+      let rhs = keymaps.parse_rhs(args.rhs);
+      let options = KeymapOptions {
+        noremap: kind == MapKind::Noremap,
+        silent: args.silent,
+        buffer,
+        ..Default::default()
+      };
+      keymaps.set(&modes, Keymap::new(lhs, KeymapTarget::Keys(rhs), options));
+      Ok(vec![])
+    }
+  }
+}
@@ -0,0 +1,90 @@
+use super::map::*;
+
+use crate::prelude::*;
+use crate::state::keymap::{KeymapTarget, Keymaps, parse_modes};
+use crate::state::keys;
+use crate::state::mode::Mode;
+
+use compact_str::CompactString;
+
+#[test]
+fn parse_name1() {
+  let name =
+    |modes: &str, kind: MapKind| Some((CompactString::new(modes), kind));
+  assert_eq!(parse_name("map"), name("", MapKind::Map));
+  assert_eq!(parse_name("no"), name("", MapKind::Noremap));
+  assert_eq!(parse_name("noremap"), name("", MapKind::Noremap));
+  assert_eq!(parse_name("unm"), name("", MapKind::Unmap));
+  assert_eq!(parse_name("nm"), name("n", MapKind::Map));
+  assert_eq!(parse_name("nn"), name("n", MapKind::Noremap));
+  assert_eq!(parse_name("vnoremap"), name("v", MapKind::Noremap));
+  assert_eq!(parse_name("xu"), name("x", MapKind::Unmap));
+  assert_eq!(parse_name("tmap"), name("t", MapKind::Map));
+  assert_eq!(parse_name("ma"), None);
+  assert_eq!(parse_name("un"), None);
+  assert_eq!(parse_name("nu"), None);
+  assert_eq!(parse_name("zmap"), None);
+  assert_eq!(parse_name("nmaps"), None);
+}
+
+#[test]
+fn execute1() {
+  let mut keymaps = Keymaps::default();
+
+  let output = execute(&mut keymaps, 1, "n", MapKind::Noremap, "Q  dd");
+  assert_eq!(output, Ok(vec![]));
+  let keymap = keymaps.get(Mode::Normal, &keys::parse("Q"), None).unwrap();
+  assert!(keymap.noremap());
+  assert_eq!(keymap.rhs_notation(), "dd");
+  assert!(keymaps.get(Mode::Visual, &keys::parse("Q"), None).is_none());
+
+  let output = execute(&mut keymaps, 1, "n", MapKind::Map, "Q");
+  assert_eq!(output.unwrap(), vec!["n  Q            * dd".to_string()]);
+
+  let output = execute(&mut keymaps, 1, "", MapKind::Unmap, "Q");
+  assert_eq!(output, Ok(vec![]));
+  assert!(keymaps.get(Mode::Normal, &keys::parse("Q"), None).is_none());
+  let output = execute(&mut keymaps, 1, "", MapKind::Unmap, "Q");
+  assert_eq!(output, Err(ExCommandErr::NoSuchMapping));
+
+  let output = execute(&mut keymaps, 1, "n", MapKind::Map, "Q");
+  assert_eq!(output.unwrap(), vec!["No mapping found".to_string()]);
+}
+
+#[test]
+fn execute_args1() {
+  let mut keymaps = Keymaps::default();
+
+  let output =
+    execute(&mut keymaps, 3, "n", MapKind::Map, "<buffer> <silent> Q x");
+  assert_eq!(output, Ok(vec![]));
+  assert!(keymaps.get(Mode::Normal, &keys::parse("Q"), None).is_none());
+  let keymap = keymaps
+    .get(Mode::Normal, &keys::parse("Q"), Some(3))
+    .unwrap();
+  assert!(keymap.silent());
+  assert!(!keymap.noremap());
+
+  // Only the buffer-local mappings are listed with `<buffer>`.
+  let output = execute(&mut keymaps, 3, "n", MapKind::Map, "<buffer>");
+  assert_eq!(output.unwrap(), vec!["n  Q             @x".to_string()]);
+
+  let output = execute(&mut keymaps, 3, "n", MapKind::Map, "<unique> x d");
+  assert_eq!(
+    output,
+    Err(ExCommandErr::MappingAlreadyExists("x".to_string()))
+  );
+  let output = execute(&mut keymaps, 3, "n", MapKind::Map, "<expr> Q x");
+  assert_eq!(
+    output,
+    Err(ExCommandErr::InvalidArgument("<expr>".to_string()))
+  );
+
+  // The `<Nop>` is no keys.
+  let output = execute(&mut keymaps, 3, "nx", MapKind::Noremap, "x <Nop>");
+  assert_eq!(output, Ok(vec![]));
+  for mode in parse_modes("nx").unwrap().iter() {
+    let keymap = keymaps.get(*mode, &keys::parse("x"), None).unwrap();
+    assert_eq!(keymap.target(), &KeymapTarget::Keys(vec![]));
+  }
+}
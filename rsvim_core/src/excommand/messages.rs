@@ -0,0 +1,23 @@
+//! The `:messages` ex command.
+//!
+//! See: <https://vimhelp.org/message.txt.html#%3Amessages>.
+
+use crate::content::message::{Message, Messages};
+use crate::prelude::*;
+
+/// Execute `:messages` with the argument, returns the messages in history from oldest to newest.
+///
+/// With the `clear` argument, it clears the history and returns nothing.
+pub fn execute(
+  messages: &mut Messages,
+  arg: &str,
+) -> ExCommandResult<Vec<Message>> {
+  match arg.trim() {
+    "" => Ok(messages.history().iter().cloned().collect()),
+    "clear" => {
+      messages.clear_history();
+      Ok(vec![])
+    }
+    arg => Err(ExCommandErr::TrailingCharacters(arg.to_string())),
+  }
+}
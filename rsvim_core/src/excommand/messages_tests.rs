@@ -0,0 +1,28 @@
+use super::messages::*;
+
+use crate::content::message::{MessageLevel, Messages};
+use crate::prelude::*;
+
+#[test]
+fn execute1() {
+  let mut messages = Messages::new(10);
+  assert_eq!(execute(&mut messages, ""), Ok(vec![]));
+
+  messages.add(MessageLevel::Error, "E1: a");
+  messages.add(MessageLevel::Info, "b");
+  let actual = execute(&mut messages, "").unwrap();
+  assert_eq!(actual.len(), 2);
+  assert_eq!(actual[0].level(), MessageLevel::Error);
+  assert_eq!(actual[0].text(), "E1: a");
+  assert_eq!(actual[1].level(), MessageLevel::Info);
+  assert_eq!(actual[1].text(), "b");
+
+  assert_eq!(
+    execute(&mut messages, "foo"),
+    Err(ExCommandErr::TrailingCharacters("foo".to_string()))
+  );
+  assert_eq!(messages.history().len(), 2);
+
+  assert_eq!(execute(&mut messages, " clear "), Ok(vec![]));
+  assert!(messages.history().is_empty());
+}
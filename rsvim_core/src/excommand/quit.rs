@@ -0,0 +1,236 @@
+//! The `:write`, `:quit`, `:qall`, `:wq`, `:xit` and `:wqall` ex commands.
+//!
+//! See: <https://vimhelp.org/editing.txt.html#write-quit>.
+
+use crate::buf::{BufferId, BuffersManager};
+use crate::excommand::is_abbrev_of;
+use crate::prelude::*;
+use crate::results::{ExCommandErr, ExCommandResult};
+use crate::state::autocmd::AutoCmdEvent;
+use crate::ui::tree::*;
+use crate::ui::widget::window::WindowNode;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// The kind of the quit commands.
+pub enum QuitKind {
+  /// `:q[uit]`, closes current window, or quits the editor if it is the last window.
+  Quit,
+  /// `:qa[ll]` and `:quita[ll]`, quits the editor.
+  QuitAll,
+  /// `:x[it]` and `:exi[t]`, writes current buffer if it is modified, then `:quit`.
+  Exit,
+  /// `:wq`, writes current buffer, then `:quit`.
+  WriteQuit,
+  /// `:wqa[ll]` and `:xa[ll]`, writes all the modified buffers, then `:qall`.
+  WriteQuitAll,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The result of the quit commands.
+pub enum QuitAction {
+  /// Current window is closed.
+  CloseWindow,
+  /// The editor should quit.
+  QuitEditor,
+}
+
+impl QuitKind {
+  /// The kind after the buffers are written, i.e. `:wq` is `:quit` after current buffer is
+  /// written. It doesn't write the buffers again.
+  pub fn without_write(&self) -> QuitKind {
+    match self {
+      QuitKind::Exit | QuitKind::WriteQuit => QuitKind::Quit,
+      QuitKind::WriteQuitAll => QuitKind::QuitAll,
+      QuitKind::Quit | QuitKind::QuitAll => *self,
+    }
+  }
+}
+
+/// Parse the command name into the kind, returns `None` if it is not a quit command.
+pub fn parse_name(name: &str) -> Option<QuitKind> {
+  if is_abbrev_of(name, "quit", 1) {
+    Some(QuitKind::Quit)
+  } else if is_abbrev_of(name, "qall", 2) || is_abbrev_of(name, "quitall", 5) {
+    Some(QuitKind::QuitAll)
+  } else if is_abbrev_of(name, "xit", 1) || is_abbrev_of(name, "exit", 3) {
+    Some(QuitKind::Exit)
+  } else if name == "wq" {
+    Some(QuitKind::WriteQuit)
+  } else if is_abbrev_of(name, "wqall", 3) || is_abbrev_of(name, "xall", 2) {
+    Some(QuitKind::WriteQuitAll)
+  } else {
+    None
+  }
+}
+
+fn _current_buffer_id(tree: &Tree) -> BufferId {
+  let buffer = tree.current_window().unwrap().buffer().upgrade().unwrap();
+  lock!(buffer).id()
+}
+
+/// Whether the command closes current window instead of quitting the editor, i.e. `:quit` when
+/// there're other windows.
+pub fn closes_window(tree: &Tree, kind: QuitKind) -> bool {
+  matches!(kind, QuitKind::Quit | QuitKind::Exit | QuitKind::WriteQuit)
+    && tree.window_ids().len() > 1
+}
+
+/// The modified buffers that stop the command, current buffer comes first. Closing a window is
+/// only stopped by current buffer, when it is not shown in other windows.
+pub fn modified_buffers(
+  tree: &Tree,
+  buffers: &BuffersManager,
+  kind: QuitKind,
+) -> Vec<BufferId> {
+  let current_buffer_id = _current_buffer_id(tree);
+  let is_modified = |buffer_id: &BufferId| {
+    buffers
+      .get(buffer_id)
+      .map(|buffer| lock!(buffer).is_modified())
+      .unwrap_or(false)
+  };
+
+  if closes_window(tree, kind) {
+    let current_window_id = tree.current_window_id();
+    let shown_elsewhere = tree
+      .window_ids()
+      .iter()
+      .filter(|window_id| Some(**window_id) != current_window_id)
+      .filter_map(|window_id| tree.window(*window_id))
+      .filter_map(|window| window.buffer().upgrade())
+      .any(|buffer| lock!(buffer).id() == current_buffer_id);
+    return if !shown_elsewhere && is_modified(&current_buffer_id) {
+      vec![current_buffer_id]
+    } else {
+      vec![]
+    };
+  }
+
+  let mut result: Vec<BufferId> = vec![];
+  if is_modified(&current_buffer_id) {
+    result.push(current_buffer_id);
+  }
+  result.extend(
+    buffers
+      .keys()
+      .filter(|buffer_id| **buffer_id != current_buffer_id)
+      .filter(|buffer_id| is_modified(buffer_id)),
+  );
+  result
+}
+
+/// Write the buffer to its file, the `BufWritePre` and `BufWritePost` events are fired around it.
+///
+/// Returns the written bytes.
+pub fn write_buffer(
+  buffers: &mut BuffersManager,
+  buffer_id: BufferId,
+) -> ExCommandResult<usize> {
+  let buffer = buffers.get(&buffer_id).unwrap().clone();
+  let mut buffer = lock!(buffer);
+  if buffer.filename().is_none() {
+    return Err(ExCommandErr::NoFileName);
+  }
+  buffers.fire(AutoCmdEvent::BufWritePre, buffer_id);
+  let bytes = buffer
+    .write()
+    .map_err(|_e| ExCommandErr::CannotWrite(buffer.name()))?;
+  buffers.fire(AutoCmdEvent::BufWritePost, buffer_id);
+  Ok(bytes)
+}
+
+/// Write current buffer to its file, i.e. `:write`.
+///
+/// Returns the written message.
+pub fn write(
+  tree: &Tree,
+  buffers: &mut BuffersManager,
+) -> ExCommandResult<String> {
+  let buffer_id = _current_buffer_id(tree);
+  let bytes = write_buffer(buffers, buffer_id)?;
+  let name = lock!(buffers.get(&buffer_id).unwrap()).name();
+  Ok(format!("\"{name}\" {bytes}B written"))
+}
+
+/// Close current window, the cursor moves to the next current window.
+///
+/// # Panics
+///
+/// If it is the last window.
+pub fn close_window(tree: &mut Tree) {
+  debug_assert!(tree.window_ids().len() > 1);
+  let window_id = tree.current_window_id().unwrap();
+  let cursor = match tree.current_window_mut().unwrap().remove_cursor() {
+    Some(WindowNode::Cursor(cursor)) => Some(cursor),
+    _ => None,
+  };
+  tree.remove(window_id);
+
+  let current_window = tree.current_window_mut().unwrap();
+  if let Some(cursor) = cursor {
+    let cursor_viewport = current_window.cursor_viewport();
+    current_window.insert_cursor(cursor);
+    current_window.move_cursor_to(
+      cursor_viewport.column_idx() as isize,
+      cursor_viewport.row_idx() as isize,
+    );
+  }
+}
+
+/// Execute the quit command, the command is refused if there's any modified buffer, unless
+/// `bang` is given, i.e. `:quit!`.
+pub fn execute(
+  tree: &mut Tree,
+  buffers: &mut BuffersManager,
+  kind: QuitKind,
+  bang: bool,
+) -> ExCommandResult<QuitAction> {
+  let current_buffer_id = _current_buffer_id(tree);
+
+  // Write the buffers first.
+  match kind {
+    QuitKind::Exit => {
+      let buffer = buffers.get(&current_buffer_id).unwrap();
+      if lock!(buffer).is_modified() {
+        write_buffer(buffers, current_buffer_id)?;
+      }
+    }
+    QuitKind::WriteQuit => {
+      write_buffer(buffers, current_buffer_id)?;
+    }
+    QuitKind::WriteQuitAll => {
+      let modified: Vec<BufferId> = buffers
+        .iter()
+        .filter(|(_, buffer)| lock!(buffer).is_modified())
+        .map(|(buffer_id, _)| *buffer_id)
+        .collect();
+      for buffer_id in modified {
+        write_buffer(buffers, buffer_id).map_err(|e| match e {
+          ExCommandErr::NoFileName => {
+            ExCommandErr::NoFileNameForBuffer(buffer_id)
+          }
+          e => e,
+        })?;
+      }
+    }
+    QuitKind::Quit | QuitKind::QuitAll => {}
+  }
+
+  if !bang {
+    if let Some(buffer_id) = modified_buffers(tree, buffers, kind).first() {
+      return Err(if *buffer_id == current_buffer_id {
+        ExCommandErr::NoWriteSinceLastChange(*buffer_id)
+      } else {
+        let name = lock!(buffers.get(buffer_id).unwrap()).name();
+        ExCommandErr::NoWriteSinceLastChangeForBuffer(*buffer_id, name)
+      });
+    }
+  }
+
+  if closes_window(tree, kind) {
+    close_window(tree);
+    Ok(QuitAction::CloseWindow)
+  } else {
+    Ok(QuitAction::QuitEditor)
+  }
+}
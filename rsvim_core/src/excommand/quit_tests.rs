@@ -0,0 +1,235 @@
+use super::quit::*;
+
+use crate::buf::opt::BufferLocalOptionsBuilder;
+use crate::buf::{Buffer, BufferArc, BuffersManagerArc};
+use crate::prelude::*;
+use crate::state::autocmd::AutoCmdEvent;
+use crate::test::buf::{make_buffer_from_lines, make_buffers_manager};
+use crate::test::log::init as test_log_init;
+use crate::test::tree::make_tree_with_buffers;
+use crate::ui::tree::*;
+use crate::ui::widget::window::{Window, WindowLocalOptionsBuilder};
+
+use ropey::Rope;
+use std::path::Path;
+use std::sync::Arc;
+
+// The buffers are shown in the windows, the first buffer is in current window.
+fn make_tree(bufs: Vec<BufferArc>) -> (TreeArc, BuffersManagerArc) {
+  let terminal_size = U16Size::new(10, 10);
+  let buf_opts = BufferLocalOptionsBuilder::default().build().unwrap();
+  let win_opts = WindowLocalOptionsBuilder::default().build().unwrap();
+  let bufs_manager = make_buffers_manager(buf_opts, bufs.clone());
+  let tree =
+    make_tree_with_buffers(terminal_size, win_opts, bufs_manager.clone());
+  (tree, bufs_manager)
+}
+
+fn make_file_buffer(path: &Path, text: &str) -> BufferArc {
+  let buf_opts = BufferLocalOptionsBuilder::default().build().unwrap();
+  let buf = Buffer::_new(
+    buf_opts,
+    U16Size::new(10, 10),
+    Rope::from_str(text),
+    Some(path.to_path_buf()),
+    Some(path.to_path_buf()),
+    None,
+    None,
+  );
+  Buffer::to_arc(buf)
+}
+
+fn make_unnamed_buffer(lines: Vec<&str>) -> BufferArc {
+  let buf_opts = BufferLocalOptionsBuilder::default().build().unwrap();
+  make_buffer_from_lines(U16Size::new(10, 10), buf_opts, lines)
+}
+
+fn modify(buf: &BufferArc) {
+  lock!(buf).text_mut().insert_at(0, 0, "x".into());
+}
+
+fn add_window(tree: &TreeArc, buf: &BufferArc) -> TreeNodeId {
+  let mut tree = lock!(tree);
+  let shape = IRect::new((0, 0), (10, 5));
+  let window =
+    Window::new(tree.global_local_options(), shape, Arc::downgrade(buf));
+  let window_id = window.id();
+  let root_id = tree.root_id();
+  tree.bounded_insert(root_id, TreeNode::Window(window));
+  window_id
+}
+
+#[test]
+fn parse_name1() {
+  assert_eq!(parse_name("q"), Some(QuitKind::Quit));
+  assert_eq!(parse_name("quit"), Some(QuitKind::Quit));
+  assert_eq!(parse_name("qa"), Some(QuitKind::QuitAll));
+  assert_eq!(parse_name("quita"), Some(QuitKind::QuitAll));
+  assert_eq!(parse_name("x"), Some(QuitKind::Exit));
+  assert_eq!(parse_name("exi"), Some(QuitKind::Exit));
+  assert_eq!(parse_name("wqa"), Some(QuitKind::WriteQuitAll));
+  assert_eq!(parse_name("xa"), Some(QuitKind::WriteQuitAll));
+  assert_eq!(parse_name("wq"), Some(QuitKind::WriteQuit));
+  assert_eq!(parse_name("ex"), None);
+  assert_eq!(parse_name("w"), None);
+  assert_eq!(parse_name("quits"), None);
+}
+
+#[test]
+fn quit1() {
+  test_log_init();
+  let buf = make_unnamed_buffer(vec!["hello\n"]);
+  let (tree, bufs) = make_tree(vec![buf.clone()]);
+  let mut tree = lock!(tree);
+  let mut bufs = lock!(bufs);
+
+  assert!(!lock!(buf).is_modified());
+  assert_eq!(
+    execute(&mut tree, &mut bufs, QuitKind::Quit, false),
+    Ok(QuitAction::QuitEditor)
+  );
+
+  modify(&buf);
+  assert!(lock!(buf).is_modified());
+  let buf_id = lock!(buf).id();
+  for kind in [QuitKind::Quit, QuitKind::QuitAll] {
+    assert_eq!(
+      execute(&mut tree, &mut bufs, kind, false),
+      Err(ExCommandErr::NoWriteSinceLastChange(buf_id))
+    );
+    assert_eq!(
+      execute(&mut tree, &mut bufs, kind, true),
+      Ok(QuitAction::QuitEditor)
+    );
+  }
+
+  // The unnamed buffer cannot be written.
+  assert_eq!(
+    execute(&mut tree, &mut bufs, QuitKind::Exit, false),
+    Err(ExCommandErr::NoFileName)
+  );
+  assert_eq!(
+    execute(&mut tree, &mut bufs, QuitKind::WriteQuit, true),
+    Err(ExCommandErr::NoFileName)
+  );
+  assert_eq!(
+    execute(&mut tree, &mut bufs, QuitKind::WriteQuitAll, false),
+    Err(ExCommandErr::NoFileNameForBuffer(buf_id))
+  );
+}
+
+#[test]
+fn quit_other_buffer1() {
+  test_log_init();
+  let dir = assert_fs::TempDir::new().unwrap();
+  let path = dir.path().join("other.txt");
+  let buf1 = make_unnamed_buffer(vec!["hello\n"]);
+  let buf2 = make_file_buffer(&path, "world\n");
+  let (tree, bufs) = make_tree(vec![buf1.clone(), buf2.clone()]);
+  let mut tree = lock!(tree);
+  let mut bufs = lock!(bufs);
+
+  modify(&buf2);
+  let buf2_id = lock!(buf2).id();
+  assert_eq!(
+    modified_buffers(&tree, &bufs, QuitKind::Quit),
+    vec![buf2_id]
+  );
+  assert_eq!(
+    execute(&mut tree, &mut bufs, QuitKind::Quit, false),
+    Err(ExCommandErr::NoWriteSinceLastChangeForBuffer(
+      buf2_id,
+      path.to_string_lossy().to_string()
+    ))
+  );
+
+  // Current buffer comes first.
+  modify(&buf1);
+  let buf1_id = lock!(buf1).id();
+  assert_eq!(
+    modified_buffers(&tree, &bufs, QuitKind::QuitAll),
+    vec![buf1_id, buf2_id]
+  );
+}
+
+#[test]
+fn write1() {
+  test_log_init();
+  let dir = assert_fs::TempDir::new().unwrap();
+  let path1 = dir.path().join("a.txt");
+  let path2 = dir.path().join("b.txt");
+  let buf1 = make_file_buffer(&path1, "hello\n");
+  let buf2 = make_file_buffer(&path2, "world\n");
+  let (tree, bufs) = make_tree(vec![buf1.clone(), buf2.clone()]);
+  let mut tree = lock!(tree);
+  let mut bufs = lock!(bufs);
+
+  // Only current buffer is written, the other buffer is still modified.
+  modify(&buf1);
+  modify(&buf2);
+  let buf2_id = lock!(buf2).id();
+  assert_eq!(
+    execute(&mut tree, &mut bufs, QuitKind::Exit, false),
+    Err(ExCommandErr::NoWriteSinceLastChangeForBuffer(
+      buf2_id,
+      path2.to_string_lossy().to_string()
+    ))
+  );
+  assert!(!lock!(buf1).is_modified());
+  assert_eq!(std::fs::read_to_string(&path1).unwrap(), "xhello\n");
+  assert!(!path2.exists());
+
+  assert_eq!(
+    execute(&mut tree, &mut bufs, QuitKind::WriteQuitAll, false),
+    Ok(QuitAction::QuitEditor)
+  );
+  assert!(!lock!(buf2).is_modified());
+  assert_eq!(std::fs::read_to_string(&path2).unwrap(), "xworld\n");
+
+  // The write events are fired around each written buffer.
+  let buf1_id = lock!(buf1).id();
+  assert_eq!(
+    bufs
+      .take_fired()
+      .iter()
+      .map(|fired| (fired.event, fired.buffer_id.unwrap()))
+      .collect::<Vec<_>>(),
+    vec![
+      (AutoCmdEvent::BufWritePre, buf1_id),
+      (AutoCmdEvent::BufWritePost, buf1_id),
+      (AutoCmdEvent::BufWritePre, buf2_id),
+      (AutoCmdEvent::BufWritePost, buf2_id),
+    ]
+  );
+}
+
+#[test]
+fn close_window1() {
+  test_log_init();
+  let buf = make_unnamed_buffer(vec!["hello\n"]);
+  let (tree_arc, bufs) = make_tree(vec![buf.clone()]);
+  let window_id = add_window(&tree_arc, &buf);
+  let mut tree = lock!(tree_arc);
+  let mut bufs = lock!(bufs);
+  let current_window_id = tree.current_window_id().unwrap();
+  assert_ne!(current_window_id, window_id);
+
+  // The buffer is shown in the other window.
+  modify(&buf);
+  assert!(closes_window(&tree, QuitKind::Quit));
+  assert!(!closes_window(&tree, QuitKind::QuitAll));
+  assert_eq!(
+    execute(&mut tree, &mut bufs, QuitKind::Quit, false),
+    Ok(QuitAction::CloseWindow)
+  );
+  assert_eq!(tree.window_ids().len(), 1);
+  assert_eq!(tree.current_window_id(), Some(window_id));
+  assert!(tree.current_window().unwrap().cursor_id().is_some());
+
+  // The last window.
+  assert!(!closes_window(&tree, QuitKind::Quit));
+  assert_eq!(
+    execute(&mut tree, &mut bufs, QuitKind::Quit, false),
+    Err(ExCommandErr::NoWriteSinceLastChange(lock!(buf).id()))
+  );
+}
@@ -0,0 +1,171 @@
+//! Ex command ranges, i.e. the lines before the command name, `:3,5`, `:%`, `:'<,'>`, `:.,$-1`.
+//!
+//! See: <https://vimhelp.org/cmdline.txt.html#cmdline-ranges>.
+
+use crate::prelude::*;
+
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The line of an address.
+pub enum LineSpecifier {
+  /// The line number (1-based), i.e. `3`.
+  Number(usize),
+
+  /// The cursor line, i.e. `.`.
+  Current,
+
+  /// The last line, i.e. `$`.
+  Last,
+
+  /// The line of the mark, i.e. `'<`.
+  Mark(char),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// A line address with the offset, i.e. `.+2`, `$-1`.
+pub struct Address {
+  pub line: LineSpecifier,
+  pub offset: isize,
+}
+
+impl Address {
+  pub fn new(line: LineSpecifier, offset: isize) -> Self {
+    Self { line, offset }
+  }
+
+  // Parse an address, returns `None` if there's no address.
+  fn _parse(source: &str) -> (Option<Self>, &str) {
+    let digits =
+      |s: &str| s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+
+    let mut rest = source;
+    let line = match rest.chars().next() {
+      Some('.') => {
+        rest = &rest[1..];
+        Some(LineSpecifier::Current)
+      }
+      Some('$') => {
+        rest = &rest[1..];
+        Some(LineSpecifier::Last)
+      }
+      Some('\'') => match rest[1..].chars().next() {
+        Some(c) => {
+          rest = &rest[1 + c.len_utf8()..];
+          Some(LineSpecifier::Mark(c))
+        }
+        None => return (None, source),
+      },
+      Some(c) if c.is_ascii_digit() => {
+        let end = digits(rest);
+        let n = rest[..end].parse::<usize>().unwrap_or(usize::MAX);
+        rest = &rest[end..];
+        Some(LineSpecifier::Number(n))
+      }
+      _ => None,
+    };
+
+    // The offsets, i.e. `+2`, `-`, `++`.
+    let mut offset = 0_isize;
+    let mut has_offset = false;
+    while let Some(sign @ ('+' | '-')) = rest.chars().next() {
+      has_offset = true;
+      rest = &rest[1..];
+      let end = digits(rest);
+      let n = if end == 0 {
+        1
+      } else {
+        rest[..end].parse::<isize>().unwrap_or(isize::MAX)
+      };
+      rest = &rest[end..];
+      offset = if sign == '+' {
+        offset.saturating_add(n)
+      } else {
+        offset.saturating_sub(n)
+      };
+    }
+
+    match (line, has_offset) {
+      (Some(line), _) => (Some(Self::new(line, offset)), rest),
+      // The offset without line is relative to the cursor line.
+      (None, true) => (Some(Self::new(LineSpecifier::Current, offset)), rest),
+      (None, false) => (None, source),
+    }
+  }
+
+  // Resolve the address to the line index.
+  fn _resolve(
+    &self,
+    current: usize,
+    last: usize,
+    mark: &impl Fn(char) -> Option<usize>,
+  ) -> ExCommandResult<usize> {
+    let line_idx = match self.line {
+      LineSpecifier::Number(n) => n.saturating_sub(1),
+      LineSpecifier::Current => current,
+      LineSpecifier::Last => last,
+      LineSpecifier::Mark(c) => mark(c).ok_or(ExCommandErr::MarkNotSet)?,
+    };
+    let line_idx = line_idx as isize + self.offset;
+    if line_idx < 0 || line_idx as usize > last {
+      return Err(ExCommandErr::InvalidRange);
+    }
+    Ok(line_idx as usize)
+  }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The range of lines, both ends are inclusive.
+pub struct ExRange {
+  pub start: Address,
+  pub end: Address,
+}
+
+impl ExRange {
+  pub fn new(start: Address, end: Address) -> Self {
+    Self { start, end }
+  }
+
+  /// Parse the range at the start of the command-line (without the `:`), returns the range (or
+  /// `None` if there's no range) and the rest of the command-line.
+  pub fn parse(source: &str) -> (Option<Self>, &str) {
+    if let Some(rest) = source.strip_prefix('%') {
+      let range = Self::new(
+        Address::new(LineSpecifier::Number(1), 0),
+        Address::new(LineSpecifier::Last, 0),
+      );
+      return (Some(range), rest);
+    }
+
+    let (start, rest) = Address::_parse(source);
+    let start = match start {
+      Some(start) => start,
+      None => return (None, source),
+    };
+    match rest.strip_prefix([',', ';']) {
+      Some(rest) => match Address::_parse(rest) {
+        (Some(end), rest) => (Some(Self::new(start, end)), rest),
+        // The missing end is the cursor line, i.e. `:3,`.
+        (None, rest) => (
+          Some(Self::new(start, Address::new(LineSpecifier::Current, 0))),
+          rest,
+        ),
+      },
+      None => (Some(Self::new(start, start)), rest),
+    }
+  }
+
+  /// Resolve the range to line indexes, with the cursor line index `current`, the last line index
+  /// `last`, and the line index of the marks. The backwards range is swapped, i.e. `:5,3` is
+  /// `:3,5`.
+  pub fn resolve(
+    &self,
+    current: usize,
+    last: usize,
+    mark: impl Fn(char) -> Option<usize>,
+  ) -> ExCommandResult<RangeInclusive<usize>> {
+    let start = self.start._resolve(current, last, &mark)?;
+    let end = self.end._resolve(current, last, &mark)?;
+    Ok(std::cmp::min(start, end)..=std::cmp::max(start, end))
+  }
+}
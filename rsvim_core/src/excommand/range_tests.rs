@@ -0,0 +1,64 @@
+use super::range::*;
+
+use crate::prelude::*;
+use crate::test::log::init as test_log_init;
+
+fn marks(c: char) -> Option<usize> {
+  match c {
+    '<' => Some(1),
+    '>' => Some(3),
+    _ => None,
+  }
+}
+
+#[test]
+fn parse1() {
+  test_log_init();
+  let (range, rest) = ExRange::parse("norm x");
+  assert_eq!(range, None);
+  assert_eq!(rest, "norm x");
+
+  let (range, rest) = ExRange::parse("%norm x");
+  let range = range.unwrap();
+  assert_eq!(range.start, Address::new(LineSpecifier::Number(1), 0));
+  assert_eq!(range.end, Address::new(LineSpecifier::Last, 0));
+  assert_eq!(rest, "norm x");
+
+  let (range, rest) = ExRange::parse("'<,'>norm");
+  let range = range.unwrap();
+  assert_eq!(range.start, Address::new(LineSpecifier::Mark('<'), 0));
+  assert_eq!(range.end, Address::new(LineSpecifier::Mark('>'), 0));
+  assert_eq!(rest, "norm");
+
+  let (range, rest) = ExRange::parse(".+2;$-1 norm");
+  let range = range.unwrap();
+  assert_eq!(range.start, Address::new(LineSpecifier::Current, 2));
+  assert_eq!(range.end, Address::new(LineSpecifier::Last, -1));
+  assert_eq!(rest, " norm");
+
+  let (range, rest) = ExRange::parse("3norm");
+  let range = range.unwrap();
+  assert_eq!(range.start, range.end);
+  assert_eq!(range.start, Address::new(LineSpecifier::Number(3), 0));
+  assert_eq!(rest, "norm");
+
+  let (range, _) = ExRange::parse("--,5");
+  let range = range.unwrap();
+  assert_eq!(range.start, Address::new(LineSpecifier::Current, -2));
+  assert_eq!(range.end, Address::new(LineSpecifier::Number(5), 0));
+}
+
+#[test]
+fn resolve1() {
+  test_log_init();
+  let resolve =
+    |source: &str| ExRange::parse(source).0.unwrap().resolve(2, 9, marks);
+  assert_eq!(resolve("%"), Ok(0..=9));
+  assert_eq!(resolve("'<,'>"), Ok(1..=3));
+  assert_eq!(resolve(".,$-1"), Ok(2..=8));
+  assert_eq!(resolve("5,3"), Ok(2..=4));
+  assert_eq!(resolve("+3"), Ok(5..=5));
+  assert_eq!(resolve("11"), Err(ExCommandErr::InvalidRange));
+  assert_eq!(resolve(".-3"), Err(ExCommandErr::InvalidRange));
+  assert_eq!(resolve("'a"), Err(ExCommandErr::MarkNotSet));
+}
@@ -0,0 +1,269 @@
+//! The `:set`, `:setlocal` and `:setglobal` ex commands.
+//!
+//! See: <https://vimhelp.org/options.txt.html#%3Aset>.
+
+use crate::buf::BuffersManager;
+use crate::excommand::split_args;
+use crate::opt::{
+  self, OPTIONS, OptionDef, OptionKind, OptionTarget, OptionValue,
+};
+use crate::prelude::*;
+use crate::state::opt::GlobalOptions;
+use crate::ui::tree::Tree;
+
+use compact_str::{CompactString, ToCompactString};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The operation of a single `:set` argument.
+pub enum SetOp {
+  /// `:set {option}`, turn on a boolean option, or show a number/string option.
+  Enable,
+  /// `:set no{option}`, turn off a boolean option.
+  Disable,
+  /// `:set {option}!` or `:set inv{option}`, invert a boolean option.
+  Toggle,
+  /// `:set {option}?`, show the option.
+  Show,
+  /// `:set {option}&`, reset the option to its default value.
+  Reset,
+  /// `:set {option}={value}` or `:set {option}:{value}`.
+  Assign(CompactString),
+  /// `:set {option}+={value}`, add to a number option, or append to a string option (add the
+  /// items to a list option).
+  Append(CompactString),
+  /// `:set {option}^={value}`, multiply a number option, or prepend to a string option (add the
+  /// items to the front of a list option).
+  Prepend(CompactString),
+  /// `:set {option}-={value}`, subtract from a number option, or remove from a string option
+  /// (remove the items from a list option).
+  Remove(CompactString),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A single parsed `:set` argument.
+pub enum SetArg {
+  /// `:set all`, show all options.
+  All,
+  /// `:set {option}...`.
+  Option(CompactString, SetOp),
+}
+
+/// Parse a single `:set` argument.
+pub fn parse_arg(arg: &str) -> OptionResult<SetArg> {
+  if arg == "all" {
+    return Ok(SetArg::All);
+  }
+
+  let name_end = arg
+    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+    .unwrap_or(arg.len());
+  let (name, rest) = arg.split_at(name_end);
+  let name = name.to_compact_string();
+
+  let op = if rest.is_empty() {
+    // Try `no{option}` and `inv{option}` only when the whole word is not an option.
+    if opt::find_option(&name).is_none() {
+      if let Some(stripped) = name.strip_prefix("no") {
+        return Ok(SetArg::Option(
+          stripped.to_compact_string(),
+          SetOp::Disable,
+        ));
+      }
+      if let Some(stripped) = name.strip_prefix("inv") {
+        return Ok(SetArg::Option(stripped.to_compact_string(), SetOp::Toggle));
+      }
+    }
+    SetOp::Enable
+  } else if rest == "?" {
+    SetOp::Show
+  } else if rest == "!" {
+    SetOp::Toggle
+  } else if rest == "&" || rest == "&vim" {
+    SetOp::Reset
+  } else if let Some(value) = rest.strip_prefix("+=") {
+    SetOp::Append(value.to_compact_string())
+  } else if let Some(value) = rest.strip_prefix("^=") {
+    SetOp::Prepend(value.to_compact_string())
+  } else if let Some(value) = rest.strip_prefix("-=") {
+    SetOp::Remove(value.to_compact_string())
+  } else if let Some(value) =
+    rest.strip_prefix('=').or_else(|| rest.strip_prefix(':'))
+  {
+    SetOp::Assign(value.to_compact_string())
+  } else {
+    // Invalid trailing chars, for example `:set wrap#`.
+    return Err(OptionErr::InvalidArgument(arg.to_string()));
+  };
+
+  Ok(SetArg::Option(name, op))
+}
+
+/// Report the error with the whole `:set` argument, instead of only the value part.
+fn _with_arg(e: OptionErr, arg: &str) -> OptionErr {
+  match e {
+    OptionErr::NumberRequired(_) => OptionErr::NumberRequired(arg.to_string()),
+    _ => OptionErr::InvalidArgument(arg.to_string()),
+  }
+}
+
+// The items of a comma-separated list option, the empty items are skipped.
+fn _list_items(value: &str) -> Vec<&str> {
+  value.split(',').filter(|item| !item.is_empty()).collect()
+}
+
+/// Calculate the new option value for `+=`, `^=` and `-=` operations.
+fn _operate(
+  def: &OptionDef,
+  arg: &str,
+  old: &OptionValue,
+  op: &SetOp,
+) -> OptionResult<OptionValue> {
+  let (value, operand) = match op {
+    SetOp::Append(v) | SetOp::Prepend(v) | SetOp::Remove(v) => {
+      (def.parse(v)?, v)
+    }
+    _ => unreachable!(),
+  };
+  let result = match (old, &value) {
+    (OptionValue::Number(a), OptionValue::Number(b)) => match op {
+      SetOp::Append(_) => a.checked_add(*b),
+      SetOp::Prepend(_) => a.checked_mul(*b),
+      SetOp::Remove(_) => a.checked_sub(*b),
+      _ => unreachable!(),
+    }
+    .map(OptionValue::Number),
+    // The items are added and removed as a whole, the duplicated items are skipped.
+    (OptionValue::String(a), OptionValue::String(_)) if def.is_list() => {
+      let mut items = _list_items(a);
+      let operands = _list_items(operand);
+      match op {
+        SetOp::Append(_) => {
+          for item in operands {
+            if !items.contains(&item) {
+              items.push(item);
+            }
+          }
+        }
+        SetOp::Prepend(_) => {
+          let mut prepended = vec![];
+          for item in operands {
+            if !items.contains(&item) && !prepended.contains(&item) {
+              prepended.push(item);
+            }
+          }
+          prepended.extend(items);
+          items = prepended;
+        }
+        SetOp::Remove(_) => items.retain(|item| !operands.contains(item)),
+        _ => unreachable!(),
+      }
+      Some(OptionValue::String(items.join(",").to_compact_string()))
+    }
+    (OptionValue::String(a), OptionValue::String(_)) => {
+      let s = match op {
+        SetOp::Append(_) => format!("{a}{operand}"),
+        SetOp::Prepend(_) => format!("{operand}{a}"),
+        SetOp::Remove(_) => a.replacen(operand.as_str(), "", 1),
+        _ => unreachable!(),
+      };
+      Some(OptionValue::String(s.to_compact_string()))
+    }
+    _ => None,
+  };
+  result.ok_or_else(|| OptionErr::InvalidArgument(arg.to_string()))
+}
+
+/// Execute `:set` (and `:setlocal`, `:setglobal`) with the arguments.
+///
+/// # Returns
+///
+/// It returns the output messages if all arguments are applied successfully, i.e. options that
+/// are asked to show. Otherwise it returns the error of the first failed argument, the arguments
+/// before it are still applied.
+pub fn execute(
+  tree: &mut Tree,
+  buffers: &mut BuffersManager,
+  global_options: &mut GlobalOptions,
+  target: OptionTarget,
+  args: &str,
+) -> OptionResult<Vec<String>> {
+  let args = split_args(args);
+
+  // Without arguments, show all options that differ from their default values.
+  if args.is_empty() {
+    let mut messages = vec!["--- Options ---".to_string()];
+    for def in OPTIONS.iter() {
+      let value =
+        opt::get_option_value(tree, buffers, global_options, def, target);
+      if value != def.default_value() {
+        messages.push(def.format(&value));
+      }
+    }
+    return Ok(messages);
+  }
+
+  let mut messages = vec![];
+  for arg in args.iter() {
+    let (name, op) = match parse_arg(arg)? {
+      SetArg::All => {
+        messages.push("--- Options ---".to_string());
+        for def in OPTIONS.iter() {
+          let value =
+            opt::get_option_value(tree, buffers, global_options, def, target);
+          messages.push(def.format(&value));
+        }
+        continue;
+      }
+      SetArg::Option(name, op) => (name, op),
+    };
+
+    let def = match opt::find_option(&name) {
+      Some(def) => def,
+      None => return Err(OptionErr::UnknownOption(arg.to_string())),
+    };
+    let old = opt::get_option_value(tree, buffers, global_options, def, target);
+
+    let new = match &op {
+      SetOp::Show => None,
+      SetOp::Enable => match def.kind() {
+        OptionKind::Boolean => Some(OptionValue::Boolean(true)),
+        _ => None,
+      },
+      SetOp::Disable | SetOp::Toggle => match def.kind() {
+        OptionKind::Boolean => Some(OptionValue::Boolean(
+          matches!(op, SetOp::Toggle) && !old.as_bool(),
+        )),
+        _ => return Err(OptionErr::InvalidArgument(arg.to_string())),
+      },
+      SetOp::Reset => Some(def.default_value()),
+      SetOp::Assign(v) => match def.kind() {
+        OptionKind::Boolean => {
+          return Err(OptionErr::InvalidArgument(arg.to_string()));
+        }
+        _ => Some(def.parse(v).map_err(|e| _with_arg(e, arg))?),
+      },
+      SetOp::Append(_) | SetOp::Prepend(_) | SetOp::Remove(_) => {
+        match def.kind() {
+          OptionKind::Boolean => {
+            return Err(OptionErr::InvalidArgument(arg.to_string()));
+          }
+          _ => {
+            Some(_operate(def, arg, &old, &op).map_err(|e| _with_arg(e, arg))?)
+          }
+        }
+      }
+    };
+
+    match new {
+      Some(new) => {
+        if !def.validate(&new) {
+          return Err(OptionErr::InvalidArgument(arg.to_string()));
+        }
+        opt::set_option_value(tree, buffers, global_options, def, target, &new);
+      }
+      None => messages.push(def.format(&old)),
+    }
+  }
+
+  Ok(messages)
+}
@@ -0,0 +1,355 @@
+use super::set::*;
+
+use crate::buf::opt::BufferLocalOptionsBuilder;
+use crate::buf::{BufferArc, BuffersManagerArc};
+use crate::defaults;
+use crate::opt::OptionTarget;
+use crate::prelude::*;
+use crate::state::opt::GlobalOptionsBuilder;
+use crate::test::buf::{make_buffer_from_lines, make_buffers_manager};
+use crate::test::log::init as test_log_init;
+use crate::test::tree::make_tree_with_buffers;
+use crate::ui::tree::TreeArc;
+use crate::ui::widget::window::WindowLocalOptionsBuilder;
+
+use compact_str::CompactString;
+
+fn make_tree(lines: Vec<&str>) -> (TreeArc, BuffersManagerArc, BufferArc) {
+  let terminal_size = U16Size::new(10, 10);
+  let buf_opts = BufferLocalOptionsBuilder::default().build().unwrap();
+  let win_opts = WindowLocalOptionsBuilder::default().build().unwrap();
+  let buf = make_buffer_from_lines(terminal_size, buf_opts, lines);
+  let bufs = make_buffers_manager(buf_opts, vec![buf.clone()]);
+  let tree = make_tree_with_buffers(terminal_size, win_opts, bufs.clone());
+  (tree, bufs, buf)
+}
+
+#[test]
+fn parse_arg1() {
+  let opt =
+    |name: &str, op: SetOp| SetArg::Option(CompactString::new(name), op);
+  assert_eq!(parse_arg("all"), Ok(SetArg::All));
+  assert_eq!(parse_arg("wrap"), Ok(opt("wrap", SetOp::Enable)));
+  assert_eq!(parse_arg("nowrap"), Ok(opt("wrap", SetOp::Disable)));
+  assert_eq!(parse_arg("invwrap"), Ok(opt("wrap", SetOp::Toggle)));
+  assert_eq!(parse_arg("wrap!"), Ok(opt("wrap", SetOp::Toggle)));
+  assert_eq!(parse_arg("wrap?"), Ok(opt("wrap", SetOp::Show)));
+  assert_eq!(parse_arg("wrap&"), Ok(opt("wrap", SetOp::Reset)));
+  assert_eq!(
+    parse_arg("ts=4"),
+    Ok(opt("ts", SetOp::Assign(CompactString::new("4"))))
+  );
+  assert_eq!(
+    parse_arg("ts:4"),
+    Ok(opt("ts", SetOp::Assign(CompactString::new("4"))))
+  );
+  assert_eq!(
+    parse_arg("ts+=4"),
+    Ok(opt("ts", SetOp::Append(CompactString::new("4"))))
+  );
+  assert_eq!(
+    parse_arg("ts^=4"),
+    Ok(opt("ts", SetOp::Prepend(CompactString::new("4"))))
+  );
+  assert_eq!(
+    parse_arg("ts-=4"),
+    Ok(opt("ts", SetOp::Remove(CompactString::new("4"))))
+  );
+  assert_eq!(
+    parse_arg("wrap#"),
+    Err(OptionErr::InvalidArgument("wrap#".to_string()))
+  );
+}
+
+#[test]
+fn execute1() {
+  test_log_init();
+  let (tree, bufs, buf) = make_tree(vec!["Hello\tWorld\n"]);
+  let mut tree = lock!(tree);
+  let mut bufs = lock!(bufs);
+  let mut options = GlobalOptionsBuilder::default().build().unwrap();
+
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "ts=4 nowrap so=3",
+  );
+  assert_eq!(actual, Ok(vec![]));
+  assert_eq!(lock!(buf).options().tab_stop(), 4);
+  assert_eq!(bufs.global_local_options().tab_stop(), 4);
+  assert!(!tree.current_window().unwrap().options().wrap());
+  assert!(!tree.global_local_options().wrap());
+  assert_eq!(tree.current_window().unwrap().options().scroll_off(), 3);
+
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "ts? wrap",
+  );
+  assert_eq!(actual, Ok(vec!["tabstop=4".to_string()]));
+  assert!(tree.current_window().unwrap().options().wrap());
+
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "ts+=2",
+  );
+  assert_eq!(actual, Ok(vec![]));
+  assert_eq!(lock!(buf).options().tab_stop(), 6);
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "ts^=2",
+  );
+  assert_eq!(actual, Ok(vec![]));
+  assert_eq!(lock!(buf).options().tab_stop(), 12);
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "ts-=2",
+  );
+  assert_eq!(actual, Ok(vec![]));
+  assert_eq!(lock!(buf).options().tab_stop(), 10);
+
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "",
+  );
+  assert_eq!(
+    actual,
+    Ok(vec![
+      "--- Options ---".to_string(),
+      "tabstop=10".to_string(),
+      "scrolloff=3".to_string()
+    ])
+  );
+
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "ts& so&",
+  );
+  assert_eq!(actual, Ok(vec![]));
+  assert_eq!(lock!(buf).options().tab_stop(), defaults::buf::TAB_STOP);
+  assert_eq!(
+    tree.current_window().unwrap().options().scroll_off(),
+    defaults::win::SCROLL_OFF
+  );
+}
+
+#[test]
+fn execute2() {
+  test_log_init();
+  let (tree, bufs, buf) = make_tree(vec!["Hello\n"]);
+  let mut tree = lock!(tree);
+  let mut bufs = lock!(bufs);
+  let mut options = GlobalOptionsBuilder::default().build().unwrap();
+
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::Local,
+    "ts=2",
+  );
+  assert_eq!(actual, Ok(vec![]));
+  assert_eq!(lock!(buf).options().tab_stop(), 2);
+  assert_eq!(
+    bufs.global_local_options().tab_stop(),
+    defaults::buf::TAB_STOP
+  );
+
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::Global,
+    "ts=3",
+  );
+  assert_eq!(actual, Ok(vec![]));
+  assert_eq!(lock!(buf).options().tab_stop(), 2);
+  assert_eq!(bufs.global_local_options().tab_stop(), 3);
+
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::Global,
+    "ts?",
+  );
+  assert_eq!(actual, Ok(vec!["tabstop=3".to_string()]));
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::Local,
+    "ts?",
+  );
+  assert_eq!(actual, Ok(vec!["tabstop=2".to_string()]));
+}
+
+#[test]
+fn execute_list1() {
+  test_log_init();
+  let (tree, bufs, buf) = make_tree(vec!["Hello\n"]);
+  let mut tree = lock!(tree);
+  let mut bufs = lock!(bufs);
+  let mut options = GlobalOptionsBuilder::default().build().unwrap();
+
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "isk+=-",
+  );
+  assert_eq!(actual, Ok(vec![]));
+  assert_eq!(
+    lock!(buf).options().is_keyword().to_string(),
+    "@,45,48-57,_,192-255"
+  );
+  // The `5` is not an item, the `48-57` is kept.
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "isk-=5",
+  );
+  assert_eq!(actual, Ok(vec![]));
+  assert_eq!(
+    lock!(buf).options().is_keyword().to_string(),
+    "@,45,48-57,_,192-255"
+  );
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "isk-=45",
+  );
+  assert_eq!(actual, Ok(vec![]));
+  assert_eq!(
+    lock!(buf).options().is_keyword().to_string(),
+    "@,48-57,_,192-255"
+  );
+
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "wig+=*.o wig+=*.a,*.o wig^=*.b",
+  );
+  assert_eq!(actual, Ok(vec![]));
+  assert_eq!(options.wild_ignore(), "*.b,*.o,*.a");
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "wig-=*.o",
+  );
+  assert_eq!(actual, Ok(vec![]));
+  assert_eq!(options.wild_ignore(), "*.b,*.a");
+
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "slm=mouse slm+=key slm+=mouse slm-=mouse",
+  );
+  assert_eq!(actual, Ok(vec![]));
+  assert_eq!(options.select_mode(), "key");
+}
+
+#[test]
+fn execute_failed1() {
+  test_log_init();
+  let (tree, bufs, buf) = make_tree(vec!["Hello\n"]);
+  let mut tree = lock!(tree);
+  let mut bufs = lock!(bufs);
+  let mut options = GlobalOptionsBuilder::default().build().unwrap();
+
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "foo",
+  );
+  assert_eq!(actual, Err(OptionErr::UnknownOption("foo".to_string())));
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "ts=abc",
+  );
+  assert_eq!(actual, Err(OptionErr::NumberRequired("ts=abc".to_string())));
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "ts=0",
+  );
+  assert_eq!(actual, Err(OptionErr::InvalidArgument("ts=0".to_string())));
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "wrap=1",
+  );
+  assert_eq!(
+    actual,
+    Err(OptionErr::InvalidArgument("wrap=1".to_string()))
+  );
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "nots",
+  );
+  assert_eq!(actual, Err(OptionErr::InvalidArgument("nots".to_string())));
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "ff=windows",
+  );
+  assert_eq!(
+    actual,
+    Err(OptionErr::InvalidArgument("ff=windows".to_string()))
+  );
+
+  // Arguments before the failed one are still applied.
+  let actual = execute(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    OptionTarget::LocalAndGlobal,
+    "ts=4 foo",
+  );
+  assert!(actual.is_err());
+  assert_eq!(lock!(buf).options().tab_stop(), 4);
+}
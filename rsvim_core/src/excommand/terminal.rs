@@ -0,0 +1,61 @@
+//! The `:terminal` ex command.
+//!
+//! See: <https://vimhelp.org/terminal.txt.html#%3Aterminal>.
+
+use crate::buf::{BufferId, BuffersManager};
+use crate::evloop::msg::WorkerToMasterMessage;
+use crate::prelude::*;
+use crate::results::{ExCommandErr, ExCommandResult};
+use crate::terminal::Terminal;
+use crate::ui::tree::*;
+
+use std::sync::Arc;
+use tokio::sync::mpsc::Sender;
+
+/// Execute `:ter[minal] [command]`, runs the `command` (or the shell) in a new terminal buffer, and
+/// shows the buffer in current window. The size of the terminal is the size of current window.
+///
+/// Returns the ID of the terminal buffer.
+pub fn execute(
+  tree: &mut Tree,
+  buffers: &mut BuffersManager,
+  worker_send_to_master: Option<Sender<WorkerToMasterMessage>>,
+  command: &str,
+) -> ExCommandResult<BufferId> {
+  let canvas_size = {
+    let root = tree.node(tree.root_id()).unwrap();
+    let shape = root.actual_shape();
+    U16Size::new(shape.width(), shape.height())
+  };
+  let current_window = tree.current_window_mut().unwrap();
+  let size = {
+    let shape = current_window.content().actual_shape();
+    U16Size::new(shape.width(), shape.height())
+  };
+
+  let terminal = Terminal::spawn(command, size)
+    .map_err(|e| ExCommandErr::ProcessFailedToStart(e.to_string()))?;
+  let buffer_id = buffers.new_terminal_buffer(canvas_size, terminal);
+  let buffer = buffers.get(&buffer_id).unwrap().clone();
+  let buffer = lock!(buffer);
+  if let Some(worker_send_to_master) = worker_send_to_master {
+    let started = buffer
+      .terminal()
+      .as_ref()
+      .unwrap()
+      .start_reader(buffer_id, worker_send_to_master);
+    if let Err(e) = started {
+      // The program is killed along with the buffer.
+      buffers.remove(&buffer_id);
+      return Err(ExCommandErr::ProcessFailedToStart(e.to_string()));
+    }
+  }
+
+  let current_window_id = tree.current_window_id().unwrap();
+  tree.set_window_buffer(
+    current_window_id,
+    Arc::downgrade(buffers.get(&buffer_id).unwrap()),
+    buffer.text(),
+  );
+  Ok(buffer_id)
+}
@@ -0,0 +1,350 @@
+use super::excommand::range::*;
+use super::excommand::*;
+
+use crate::opt::OptionTarget;
+
+use compact_str::CompactString;
+
+#[test]
+fn is_abbrev_of1() {
+  assert!(is_abbrev_of("se", "set", 2));
+  assert!(is_abbrev_of("set", "set", 2));
+  assert!(!is_abbrev_of("s", "set", 2));
+  assert!(!is_abbrev_of("sets", "set", 2));
+}
+
+#[test]
+fn builtin_parse1() {
+  assert_eq!(
+    BuiltinExCommand::parse("set ts=4"),
+    Some(BuiltinExCommand::Set(
+      OptionTarget::LocalAndGlobal,
+      CompactString::new("ts=4")
+    ))
+  );
+  assert_eq!(
+    BuiltinExCommand::parse("se"),
+    Some(BuiltinExCommand::Set(
+      OptionTarget::LocalAndGlobal,
+      CompactString::new("")
+    ))
+  );
+  assert_eq!(
+    BuiltinExCommand::parse("setl nowrap"),
+    Some(BuiltinExCommand::Set(
+      OptionTarget::Local,
+      CompactString::new("nowrap")
+    ))
+  );
+  assert_eq!(
+    BuiltinExCommand::parse("setglobal   so=2 "),
+    Some(BuiltinExCommand::Set(
+      OptionTarget::Global,
+      CompactString::new("so=2")
+    ))
+  );
+  assert_eq!(
+    BuiltinExCommand::parse("his cmd"),
+    Some(BuiltinExCommand::History(CompactString::new("cmd")))
+  );
+  assert_eq!(
+    BuiltinExCommand::parse("history"),
+    Some(BuiltinExCommand::History(CompactString::new("")))
+  );
+  assert_eq!(
+    BuiltinExCommand::parse("mes clear"),
+    Some(BuiltinExCommand::Messages(CompactString::new("clear")))
+  );
+  assert_eq!(
+    BuiltinExCommand::parse("messages"),
+    Some(BuiltinExCommand::Messages(CompactString::new("")))
+  );
+  assert_eq!(
+    BuiltinExCommand::parse("ter"),
+    Some(BuiltinExCommand::Terminal(CompactString::new("")))
+  );
+  assert_eq!(
+    BuiltinExCommand::parse("terminal ls -la "),
+    Some(BuiltinExCommand::Terminal(CompactString::new("ls -la")))
+  );
+  assert_eq!(BuiltinExCommand::parse("hi"), None);
+  assert_eq!(BuiltinExCommand::parse("me"), None);
+  assert_eq!(BuiltinExCommand::parse("te"), None);
+  assert_eq!(BuiltinExCommand::parse("s"), None);
+  assert_eq!(BuiltinExCommand::parse("setx"), None);
+  assert_eq!(BuiltinExCommand::parse("js console.log(1)"), None);
+}
+
+#[test]
+fn builtin_parse_normal1() {
+  assert_eq!(
+    BuiltinExCommand::parse("norm A;"),
+    Some(BuiltinExCommand::Normal(
+      None,
+      false,
+      CompactString::new("A;")
+    ))
+  );
+  assert_eq!(
+    BuiltinExCommand::parse("normal!  dw "),
+    Some(BuiltinExCommand::Normal(
+      None,
+      true,
+      CompactString::new("dw ")
+    ))
+  );
+  let range = ExRange::new(
+    Address::new(LineSpecifier::Number(2), 0),
+    Address::new(LineSpecifier::Last, -1),
+  );
+  assert_eq!(
+    BuiltinExCommand::parse(":2,$-1 norm x"),
+    Some(BuiltinExCommand::Normal(
+      Some(range),
+      false,
+      CompactString::new("x")
+    ))
+  );
+  assert_eq!(BuiltinExCommand::parse("%set"), None);
+}
+
+#[test]
+fn builtin_parse_map1() {
+  use super::excommand::map::MapKind;
+
+  assert_eq!(
+    BuiltinExCommand::parse("nor x dl"),
+    Some(BuiltinExCommand::Map(
+      CompactString::new(""),
+      MapKind::Noremap,
+      CompactString::new("x dl")
+    ))
+  );
+  assert_eq!(
+    BuiltinExCommand::parse("nmap <silent> Q @q"),
+    Some(BuiltinExCommand::Map(
+      CompactString::new("n"),
+      MapKind::Map,
+      CompactString::new("<silent> Q @q")
+    ))
+  );
+  assert_eq!(
+    BuiltinExCommand::parse("map! jk <Esc>"),
+    Some(BuiltinExCommand::Map(
+      CompactString::new("!"),
+      MapKind::Map,
+      CompactString::new("jk <Esc>")
+    ))
+  );
+  assert_eq!(
+    BuiltinExCommand::parse("xu x"),
+    Some(BuiltinExCommand::Map(
+      CompactString::new("x"),
+      MapKind::Unmap,
+      CompactString::new("x")
+    ))
+  );
+}
+
+#[test]
+fn builtin_parse_quit1() {
+  use super::excommand::quit::QuitKind;
+
+  assert_eq!(
+    BuiltinExCommand::parse("q"),
+    Some(BuiltinExCommand::Quit(QuitKind::Quit, false, false))
+  );
+  assert_eq!(
+    BuiltinExCommand::parse("qa!"),
+    Some(BuiltinExCommand::Quit(QuitKind::QuitAll, true, false))
+  );
+  assert_eq!(
+    BuiltinExCommand::parse("x"),
+    Some(BuiltinExCommand::Quit(QuitKind::Exit, false, false))
+  );
+  assert_eq!(
+    BuiltinExCommand::parse("conf wqa"),
+    Some(BuiltinExCommand::Quit(QuitKind::WriteQuitAll, false, true))
+  );
+  assert_eq!(
+    BuiltinExCommand::parse("wq!"),
+    Some(BuiltinExCommand::Quit(QuitKind::WriteQuit, true, false))
+  );
+  assert_eq!(BuiltinExCommand::parse("w"), Some(BuiltinExCommand::Write));
+  assert_eq!(
+    BuiltinExCommand::parse("write!"),
+    Some(BuiltinExCommand::Write)
+  );
+  assert_eq!(BuiltinExCommand::parse("w foo"), None);
+  assert_eq!(BuiltinExCommand::parse("q foo"), None);
+  assert_eq!(BuiltinExCommand::parse("confirm set"), None);
+  assert_eq!(BuiltinExCommand::parse("1q"), None);
+}
+
+#[test]
+fn user_parse1() {
+  let cmdline = ExCommandLine::parse(":Foo a  b ").unwrap();
+  assert_eq!(cmdline.range, None);
+  assert_eq!(cmdline.name, "Foo");
+  assert!(!cmdline.bang);
+  assert_eq!(cmdline.args, "a  b");
+
+  let cmdline = ExCommandLine::parse("%Foo2!").unwrap();
+  assert!(cmdline.range.is_some());
+  assert_eq!(cmdline.name, "Foo2");
+  assert!(cmdline.bang);
+  assert_eq!(cmdline.args, "");
+
+  assert_eq!(ExCommandLine::parse("foo"), None);
+  assert_eq!(ExCommandLine::parse("Foo-x"), None);
+  assert_eq!(ExCommandLine::parse("3"), None);
+}
+
+#[test]
+fn split_args1() {
+  assert_eq!(split_args(" a b\tc "), vec!["a", "b", "c"]);
+  assert_eq!(split_args(r"a\ b c\d"), vec!["a b", r"c\d"]);
+  assert_eq!(split_args(r"a\\b \x"), vec![r"a\b", r"\x"]);
+  assert!(split_args("").is_empty());
+  assert!(split_args("   ").is_empty());
+}
+
+#[test]
+fn user_prepare1() {
+  let prepare = |options: ExCommandOptions, source: &str| {
+    let cmd = ExCommand::new(CompactString::new("Foo"), 1, options);
+    let cmdline = ExCommandLine::parse(source).unwrap();
+    cmd.prepare(&cmdline, 2, 9, |c| (c == 'a').then_some(5))
+  };
+
+  let args = prepare(
+    ExCommandOptions {
+      nargs: ExCommandNargs::Any,
+      ..Default::default()
+    },
+    "Foo a b",
+  )
+  .unwrap();
+  assert_eq!(args.name, "Foo");
+  assert_eq!(args.args, "a b");
+  assert_eq!(args.fargs, vec!["a", "b"]);
+  assert_eq!(args.range, None);
+  assert_eq!(args.count, None);
+  assert!(!args.bang);
+
+  // The whole arguments is one argument.
+  let args = prepare(
+    ExCommandOptions {
+      nargs: ExCommandNargs::One,
+      bang: true,
+      ..Default::default()
+    },
+    "Fo! a b",
+  )
+  .unwrap();
+  assert_eq!(args.fargs, vec!["a b"]);
+  assert!(args.bang);
+
+  assert_eq!(
+    prepare(ExCommandOptions::default(), "Foo a"),
+    Err(ExCommandErr::TrailingCharacters("a".to_string()))
+  );
+  assert_eq!(
+    prepare(ExCommandOptions::default(), "Foo!"),
+    Err(ExCommandErr::NoBangAllowed)
+  );
+  assert_eq!(
+    prepare(ExCommandOptions::default(), "1Foo"),
+    Err(ExCommandErr::NoRangeAllowed)
+  );
+  assert_eq!(
+    prepare(
+      ExCommandOptions {
+        nargs: ExCommandNargs::OneOrMore,
+        ..Default::default()
+      },
+      "Foo"
+    ),
+    Err(ExCommandErr::ArgumentRequired)
+  );
+}
+
+#[test]
+fn user_prepare_range1() {
+  let prepare = |range: ExCommandRange, source: &str| {
+    let options = ExCommandOptions {
+      nargs: ExCommandNargs::Any,
+      range,
+      ..Default::default()
+    };
+    let cmd = ExCommand::new(CompactString::new("Foo"), 1, options);
+    let cmdline = ExCommandLine::parse(source).unwrap();
+    cmd
+      .prepare(&cmdline, 2, 9, |c| (c == 'a').then_some(5))
+      .map(|args| (args.range, args.count, args.args))
+  };
+
+  assert_eq!(
+    prepare(ExCommandRange::Line, "Foo"),
+    Ok((Some((2, 2)), None, "".into()))
+  );
+  assert_eq!(
+    prepare(ExCommandRange::Line, "'a,$Foo"),
+    Ok((Some((5, 9)), None, "".into()))
+  );
+  assert_eq!(
+    prepare(ExCommandRange::File, "Foo"),
+    Ok((Some((0, 9)), None, "".into()))
+  );
+  assert_eq!(
+    prepare(ExCommandRange::File, "20Foo"),
+    Err(ExCommandErr::InvalidRange)
+  );
+
+  // The count is in the line number position, or the first argument.
+  assert_eq!(
+    prepare(ExCommandRange::Count(0), "Foo x"),
+    Ok((None, Some(0), "x".into()))
+  );
+  assert_eq!(
+    prepare(ExCommandRange::Count(0), "20Foo x"),
+    Ok((None, Some(20), "x".into()))
+  );
+  assert_eq!(
+    prepare(ExCommandRange::Count(0), "Foo 3 x"),
+    Ok((None, Some(3), "x".into()))
+  );
+  assert_eq!(
+    prepare(ExCommandRange::Count(0), "Foo 3x"),
+    Ok((None, Some(0), "3x".into()))
+  );
+}
+
+#[test]
+fn user_find1() {
+  let mut ex_commands = ExCommandsManager::new();
+  for (i, name) in ["Foo", "FooBar", "Format"].into_iter().enumerate() {
+    ex_commands.insert(ExCommand::new(
+      CompactString::new(name),
+      i as i32,
+      ExCommandOptions::default(),
+    ));
+  }
+  assert_eq!(ex_commands.names(), vec!["Foo", "FooBar", "Format"]);
+  assert_eq!(ex_commands.find("Foo").unwrap().name(), "Foo");
+  assert_eq!(ex_commands.find("FooB").unwrap().name(), "FooBar");
+  assert_eq!(ex_commands.find("Form").unwrap().name(), "Format");
+  assert_eq!(
+    ex_commands.find("Fo").unwrap_err(),
+    ExCommandErr::AmbiguousCommand("Fo".to_string())
+  );
+  assert_eq!(
+    ex_commands.find("Bar").unwrap_err(),
+    ExCommandErr::NotAnEditorCommand("Bar".to_string())
+  );
+
+  assert!(ex_commands.has_callback(1));
+  assert!(ex_commands.remove("FooBar").is_some());
+  assert!(!ex_commands.has_callback(1));
+  assert!(ex_commands.find("FooB").is_err());
+}
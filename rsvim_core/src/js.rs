@@ -3,6 +3,12 @@
 use crate::buf::BuffersManagerArc;
 use crate::cli::CliOpt;
 use crate::content::TextContentsArc;
+use crate::content::message::MessageLevel;
+use crate::js::binding::global_rsvim::autocmd::AutoCmdFuture;
+use crate::js::binding::global_rsvim::cmd::{
+  CompletionFuture, ExCommandFuture, RepeatFuture,
+};
+use crate::js::binding::global_rsvim::keymap::KeymapFuture;
 use crate::js::err::JsError;
 use crate::js::exception::ExceptionState;
 use crate::js::hook::module_resolve_cb;
@@ -17,6 +23,7 @@ use crate::prelude::*;
 use crate::state::StateArc;
 use crate::ui::tree::TreeArc;
 
+use compact_str::CompactString;
 use std::rc::Rc;
 use std::sync::Once;
 use std::sync::atomic::{AtomicI32, Ordering};
@@ -303,6 +310,16 @@ pub struct JsRuntimeState {
   // pub interrupt_handle: LoopInterruptHandle,
   /// Holds JS pending futures scheduled by the event-loop.
   pub pending_futures: HashMap<JsFutureId, Box<dyn JsFuture>>,
+  /// Completers for the arguments of ex commands, registered by `Rsvim.cmd.setCompleter`.
+  pub completers: HashMap<CompactString, Rc<v8::Global<v8::Function>>>,
+  /// The repeatable action repeated by `.`, registered by `Rsvim.cmd.setRepeat`.
+  pub repeat_action: Option<Rc<v8::Global<v8::Function>>>,
+  /// The callbacks of the mappings, registered by `Rsvim.keymap.set`.
+  pub keymap_callbacks: HashMap<i32, Rc<v8::Global<v8::Function>>>,
+  /// The callbacks of the autocommands, registered by `Rsvim.autocmd.create`.
+  pub autocmd_callbacks: HashMap<i32, Rc<v8::Global<v8::Function>>>,
+  /// The callbacks of the user ex commands, registered by `Rsvim.cmd.create`.
+  pub ex_command_callbacks: HashMap<i32, Rc<v8::Global<v8::Function>>>,
   /// Indicates the start time of the process.
   pub startup_moment: Instant,
   /// Specifies the timestamp which the current process began in Unix time.
@@ -453,6 +470,11 @@ impl JsRuntime {
       timeout_handles: HashSet::new(),
       // interrupt_handle: event_loop.interrupt_handle(),
       pending_futures: HashMap::new(),
+      completers: HashMap::new(),
+      repeat_action: None,
+      keymap_callbacks: HashMap::new(),
+      autocmd_callbacks: HashMap::new(),
+      ex_command_callbacks: HashMap::new(),
       // timeout_queue: BTreeMap::new(),
       startup_moment,
       time_origin,
@@ -528,6 +550,11 @@ impl JsRuntime {
       timeout_handles: HashSet::new(),
       // interrupt_handle: event_loop.interrupt_handle(),
       pending_futures: HashMap::new(),
+      completers: HashMap::new(),
+      repeat_action: None,
+      keymap_callbacks: HashMap::new(),
+      autocmd_callbacks: HashMap::new(),
+      ex_command_callbacks: HashMap::new(),
       // timeout_queue: BTreeMap::new(),
       startup_moment,
       time_origin,
@@ -650,8 +677,7 @@ impl JsRuntime {
         let exception = tc_scope.exception().unwrap();
         let _exception = JsError::from_v8_exception(tc_scope, exception, None);
         let e = format!("User config not found: {filename:?}");
-        error!(e);
-        eprintln!("{e}");
+        report_error(tc_scope, &e);
         anyhow::bail!(e);
       }
     };
@@ -666,8 +692,7 @@ impl JsRuntime {
       let e = format!(
         "Failed to instantiate user config module {filename:?}: {exception:?}"
       );
-      error!(e);
-      eprintln!("{e}");
+      report_error(tc_scope, &e);
       anyhow::bail!(e);
     }
 
@@ -688,8 +713,7 @@ impl JsRuntime {
       let e = format!(
         "Failed to evaluate user config module {filename:?}: {exception:?}"
       );
-      error!(e);
-      eprintln!("{e}");
+      report_error(tc_scope, &e);
       anyhow::bail!(e);
     }
 
@@ -773,7 +797,28 @@ impl JsRuntime {
           }
           EventLoopToJsRuntimeMessage::ExCommandReq(req) => {
             trace!("Receive ExCommandReq:{req:?}");
-            debug_assert!(!state.pending_futures.contains_key(&req.future_id));
+            let cb = state.ex_command_callbacks.get(&req.callback_id).cloned();
+            futures.push(Box::new(ExCommandFuture { req, cb }));
+          }
+          EventLoopToJsRuntimeMessage::CompletionReq(req) => {
+            trace!("Receive CompletionReq:{req:?}");
+            let cb = state.completers.get(&req.name).cloned();
+            futures.push(Box::new(CompletionFuture { req, cb }));
+          }
+          EventLoopToJsRuntimeMessage::RepeatReq(req) => {
+            trace!("Receive RepeatReq:{req:?}");
+            let cb = state.repeat_action.clone();
+            futures.push(Box::new(RepeatFuture { req, cb }));
+          }
+          EventLoopToJsRuntimeMessage::KeymapReq(req) => {
+            trace!("Receive KeymapReq:{req:?}");
+            let cb = state.keymap_callbacks.get(&req.callback_id).cloned();
+            futures.push(Box::new(KeymapFuture { req, cb }));
+          }
+          EventLoopToJsRuntimeMessage::AutoCmdReq(req) => {
+            trace!("Receive AutoCmdReq:{req:?}");
+            let cb = state.autocmd_callbacks.get(&req.autocmd_id).cloned();
+            futures.push(Box::new(AutoCmdFuture { req, cb }));
           }
         }
       }
@@ -784,9 +829,7 @@ impl JsRuntime {
     for mut fut in futures {
       fut.run(scope);
       if let Some(error) = check_exceptions(scope) {
-        // FIXME: Cannot simply report error and exit process, because this is inside the editor.
-        error!("Js runtime timeout error:{error:?}");
-        eprintln!("Js runtime timeout error:{error:?}");
+        report_error(scope, &format!("{error:?}"));
       }
       run_next_tick_callbacks(scope);
     }
@@ -865,9 +908,7 @@ impl JsRuntime {
         assert!(tc_scope.has_caught());
         let exception = tc_scope.exception().unwrap();
         let exception = JsError::from_v8_exception(tc_scope, exception, None);
-        // FIXME: Cannot simply report error and exit process, because this is inside the editor.
-        error!("{exception:?}");
-        eprintln!("{exception:?}");
+        report_error(tc_scope, &format!("{exception:?}"));
         continue;
       }
 
@@ -888,9 +929,7 @@ impl JsRuntime {
         drop(state);
 
         if let Some(error) = check_exceptions(tc_scope) {
-          // FIXME: Cannot simply report error and exit process, because this is inside the editor.
-          error!("{error:?}");
-          eprintln!("{error:?}");
+          report_error(tc_scope, &format!("{error:?}"));
           continue;
         }
       }
@@ -1006,6 +1045,25 @@ fn run_next_tick_callbacks(scope: &mut v8::HandleScope) {
   tc_scope.perform_microtask_checkpoint();
 }
 
+/// Report the error to the command-line message area, and save it in the `:messages` history.
+///
+/// NOTE: Unlike other js runtimes, we cannot simply report the error and exit the process, because
+/// the js runtime is the configuration layer inside the editor. The error is shown to user, and
+/// the js runtime continues running.
+pub fn report_error(scope: &mut v8::HandleScope, e: &str) {
+  error!("{e}");
+  let state_rc = JsRuntime::state(scope);
+  let (editing_state, contents) = {
+    let state = state_rc.borrow();
+    (state.editing_state.clone(), state.contents.clone())
+  };
+  let max_size = lock!(editing_state).global_options().message_history();
+  let mut contents = lock!(contents);
+  let messages = contents.messages_mut();
+  messages.set_max_size(max_size as usize);
+  messages.add(MessageLevel::Error, e);
+}
+
 // Returns an error if an uncaught exception or unhandled rejection has been captured.
 pub fn check_exceptions(scope: &mut v8::HandleScope) -> Option<JsError> {
   let state_rc = JsRuntime::state(scope);
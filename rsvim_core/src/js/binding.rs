@@ -79,22 +79,162 @@ pub fn create_new_context<'s>(
     );
   }
 
+  // For `Rsvim.autocmd`
+  {
+    set_function_to(
+      scope,
+      vim,
+      "autocmd_create",
+      global_rsvim::autocmd::create,
+    );
+    set_function_to(scope, vim, "autocmd_del", global_rsvim::autocmd::del);
+    set_function_to(
+      scope,
+      vim,
+      "autocmd_create_group",
+      global_rsvim::autocmd::create_group,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "autocmd_del_group",
+      global_rsvim::autocmd::del_group,
+    );
+    set_function_to(scope, vim, "autocmd_clear", global_rsvim::autocmd::clear);
+    set_function_to(scope, vim, "autocmd_list", global_rsvim::autocmd::list);
+  }
+
+  // For `Rsvim.buf`
+  {
+    set_function_to(scope, vim, "buf_current", global_rsvim::buf::current);
+    set_function_to(scope, vim, "buf_list", global_rsvim::buf::list);
+    set_function_to(scope, vim, "buf_create", global_rsvim::buf::create);
+    set_function_to(scope, vim, "buf_info", global_rsvim::buf::info);
+    set_function_to(scope, vim, "buf_get_lines", global_rsvim::buf::get_lines);
+    set_function_to(scope, vim, "buf_set_lines", global_rsvim::buf::set_lines);
+    set_function_to(scope, vim, "buf_get_text", global_rsvim::buf::get_text);
+    set_function_to(scope, vim, "buf_set_text", global_rsvim::buf::set_text);
+    set_function_to(scope, vim, "buf_insert", global_rsvim::buf::insert);
+    set_function_to(scope, vim, "buf_delete", global_rsvim::buf::delete);
+    set_function_to(
+      scope,
+      vim,
+      "buf_get_option",
+      global_rsvim::buf::get_option,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "buf_set_option",
+      global_rsvim::buf::set_option,
+    );
+    set_function_to(scope, vim, "buf_select", global_rsvim::buf::select);
+  }
+
+  // For `Rsvim.cmd`
+  {
+    set_function_to(scope, vim, "cmd_create", global_rsvim::cmd::create);
+    set_function_to(scope, vim, "cmd_del", global_rsvim::cmd::del);
+    set_function_to(scope, vim, "cmd_list", global_rsvim::cmd::list);
+    set_function_to(
+      scope,
+      vim,
+      "cmd_set_completer",
+      global_rsvim::cmd::set_completer,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "cmd_set_repeat",
+      global_rsvim::cmd::set_repeat,
+    );
+  }
+
+  // For `Rsvim.keymap`
+  {
+    set_function_to(scope, vim, "keymap_set", global_rsvim::keymap::set);
+    set_function_to(scope, vim, "keymap_del", global_rsvim::keymap::del);
+    set_function_to(scope, vim, "keymap_list", global_rsvim::keymap::list);
+    set_function_to(
+      scope,
+      vim,
+      "keymap_get_leader",
+      global_rsvim::keymap::get_leader,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "keymap_set_leader",
+      global_rsvim::keymap::set_leader,
+    );
+  }
+
   // For `Rsvim.opt`
   {
-    set_function_to(scope, vim, "opt_get_wrap", global_rsvim::opt::get_wrap);
-    set_function_to(scope, vim, "opt_set_wrap", global_rsvim::opt::set_wrap);
+    set_function_to(scope, vim, "opt_get", global_rsvim::opt::get);
+    set_function_to(scope, vim, "opt_set", global_rsvim::opt::set);
+  }
+
+  // For `Rsvim.win`
+  {
+    set_function_to(scope, vim, "win_current", global_rsvim::win::current);
+    set_function_to(scope, vim, "win_list", global_rsvim::win::list);
+    set_function_to(
+      scope,
+      vim,
+      "win_get_cursor",
+      global_rsvim::win::get_cursor,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "win_set_cursor",
+      global_rsvim::win::set_cursor,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "win_get_scroll",
+      global_rsvim::win::get_scroll,
+    );
     set_function_to(
       scope,
       vim,
-      "opt_get_line_break",
-      global_rsvim::opt::get_line_break,
+      "win_set_scroll",
+      global_rsvim::win::set_scroll,
     );
+    set_function_to(scope, vim, "win_get_size", global_rsvim::win::get_size);
     set_function_to(
       scope,
       vim,
-      "opt_set_line_break",
-      global_rsvim::opt::set_line_break,
+      "win_get_position",
+      global_rsvim::win::get_position,
     );
+    set_function_to(
+      scope,
+      vim,
+      "win_get_option",
+      global_rsvim::win::get_option,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "win_set_option",
+      global_rsvim::win::set_option,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "win_get_buffer",
+      global_rsvim::win::get_buffer,
+    );
+    set_function_to(
+      scope,
+      vim,
+      "win_set_buffer",
+      global_rsvim::win::set_buffer,
+    );
+    set_function_to(scope, vim, "win_focus", global_rsvim::win::focus);
   }
 
   // Expose low-level functions to JavaScript.
@@ -127,11 +267,7 @@ pub fn create_new_context<'s>(
 //   drop(state);
 //
 //   if let Some(error) = check_exceptions(scope) {
-//     // FIXME: We cannot simply exit the process like other js runtimes, because js runtime inside the
-//     // editor is a configuration layer. The only thing we should do is popup an error message to
-//     // command line, and let js runtime continue running.
-//     error!("{:?}", error);
-//     eprintln!("{:?}", error);
+//     report_error(scope, &format!("{error:?}"));
 //   }
 // }
 
@@ -260,3 +396,10 @@ pub fn throw_type_error(scope: &mut v8::HandleScope, message: &str) {
   let exception = v8::Exception::type_error(scope, message);
   scope.throw_exception(exception);
 }
+
+/// Useful utility to throw v8 range errors.
+pub fn throw_range_error(scope: &mut v8::HandleScope, message: &str) {
+  let message = v8::String::new(scope, message).unwrap();
+  let exception = v8::Exception::range_error(scope, message);
+  scope.throw_exception(exception);
+}
@@ -1,3 +1,190 @@
 //! APIs for `Rsvim` namespace.
 
+use crate::buf::BufferId;
+use crate::js::JsRuntime;
+use crate::js::binding::{throw_range_error, throw_type_error};
+use crate::opt::{
+  OptionDef, OptionKind, OptionScope, OptionValue, find_option,
+};
+use crate::prelude::*;
+use crate::state::mode::Mode;
+
+use compact_str::{CompactString, ToCompactString};
+
+pub mod autocmd;
+pub mod buf;
+pub mod cmd;
+pub mod keymap;
 pub mod opt;
+pub mod win;
+
+// Resolve the `buffer` argument of the `api`: `-1` is global, `0` is current buffer, otherwise
+// the buffer ID. It throws if the buffer is not found.
+fn _buffer_id(
+  scope: &mut v8::HandleScope,
+  api: &str,
+  buffer: i32,
+) -> Result<Option<BufferId>, ()> {
+  if buffer < 0 {
+    return Ok(None);
+  }
+  let state_rc = JsRuntime::state(scope);
+  let (tree, buffers) = {
+    let state = state_rc.borrow();
+    (state.tree.clone(), state.buffers.clone())
+  };
+  let buffer_id = if buffer == 0 {
+    let tree = lock!(tree);
+    tree
+      .current_window()
+      .and_then(|window| window.buffer().upgrade())
+      .map(|buffer| lock!(buffer).id())
+  } else if lock!(buffers).contains_key(&buffer) {
+    Some(buffer)
+  } else {
+    None
+  };
+  match buffer_id {
+    Some(buffer_id) => Ok(Some(buffer_id)),
+    None => {
+      throw_type_error(
+        scope,
+        &format!("\"{api}\" buffer {buffer} is not found"),
+      );
+      Err(())
+    }
+  }
+}
+
+// Find the option by its name or short alias, the name is case-insensitive so the camel case
+// names (i.e. `tabStop`) also work. The option must be in the `scope` if it is not `None`. It
+// throws if the option is not found.
+fn _option_def(
+  scope: &mut v8::HandleScope,
+  api: &str,
+  name: &str,
+  option_scope: Option<OptionScope>,
+) -> Result<&'static OptionDef, ()> {
+  match find_option(&name.to_lowercase()) {
+    Some(def)
+      if option_scope.is_none() || option_scope == Some(def.scope()) =>
+    {
+      Ok(def)
+    }
+    Some(def) => {
+      throw_type_error(
+        scope,
+        &format!(
+          "\"{api}\" option {:?} is a {} option, not a {} option",
+          def.name(),
+          def.scope(),
+          option_scope.unwrap()
+        ),
+      );
+      Err(())
+    }
+    None => {
+      throw_type_error(
+        scope,
+        &format!("\"{api}\" option {name:?} is not found"),
+      );
+      Err(())
+    }
+  }
+}
+
+// Convert the js value to the value of the option. It throws a `TypeError` if the value type
+// doesn't match the option type, or a `RangeError` if the option doesn't accept the value.
+fn _from_js_value(
+  scope: &mut v8::HandleScope,
+  api: &str,
+  def: &OptionDef,
+  value: v8::Local<v8::Value>,
+) -> Result<OptionValue, ()> {
+  let option_value = match def.kind() {
+    OptionKind::Boolean if value.is_boolean() => {
+      Some(OptionValue::Boolean(value.boolean_value(scope)))
+    }
+    OptionKind::Number if value.is_number() => {
+      let n = value.number_value(scope).unwrap();
+      if n.fract() == 0.0 && n.abs() <= i64::MAX as f64 {
+        Some(OptionValue::Number(n as i64))
+      } else {
+        None
+      }
+    }
+    OptionKind::String if value.is_string() => Some(OptionValue::String(
+      value.to_rust_string_lossy(scope).to_compact_string(),
+    )),
+    _ => None,
+  };
+  let display = value.to_rust_string_lossy(scope);
+  match option_value {
+    Some(option_value) if def.validate(&option_value) => Ok(option_value),
+    Some(_) => {
+      throw_range_error(
+        scope,
+        &format!(
+          "\"{api}\" option {:?} doesn't accept the value {display:?}",
+          def.name()
+        ),
+      );
+      Err(())
+    }
+    None => {
+      let type_name = value.type_of(scope).to_rust_string_lossy(scope);
+      let kind = match def.kind() {
+        OptionKind::Number => "an integer".to_string(),
+        kind => format!("a {kind}"),
+      };
+      throw_type_error(
+        scope,
+        &format!(
+          "\"{api}\" option {:?} must be {kind}, but found {display} ({type_name})",
+          def.name()
+        ),
+      );
+      Err(())
+    }
+  }
+}
+
+/// Whether the cursor can be on the eol of a line in current mode, i.e. the insert mode.
+pub fn include_eol(scope: &mut v8::HandleScope) -> bool {
+  let state_rc = JsRuntime::state(scope);
+  let editing_state = state_rc.borrow().editing_state.clone();
+  let mode = lock!(editing_state).mode();
+  matches!(
+    mode,
+    Mode::Insert | Mode::Replace | Mode::VirtualReplace | Mode::Terminal
+  )
+}
+
+/// Collect the strings of a js array.
+pub fn strings(
+  scope: &mut v8::HandleScope,
+  value: v8::Local<v8::Value>,
+) -> Vec<CompactString> {
+  let mut result = vec![];
+  if let Ok(array) = v8::Local::<v8::Array>::try_from(value) {
+    for i in 0..array.length() {
+      if let Some(item) = array.get_index(scope, i) {
+        result.push(item.to_rust_string_lossy(scope).to_compact_string());
+      }
+    }
+  }
+  result
+}
+
+/// Make a js array of the strings.
+pub fn string_array<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  items: Vec<&str>,
+) -> v8::Local<'s, v8::Array> {
+  let array = v8::Array::new(scope, items.len() as i32);
+  for (i, item) in items.into_iter().enumerate() {
+    let item = v8::String::new(scope, item).unwrap();
+    array.set_index(scope, i as u32, item.into());
+  }
+  array
+}
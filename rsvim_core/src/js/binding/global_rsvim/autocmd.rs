@@ -0,0 +1,361 @@
+//! APIs for `Rsvim.autocmd` namespace.
+
+use crate::js::binding::global_rsvim::{_buffer_id, string_array, strings};
+use crate::js::binding::throw_type_error;
+use crate::js::msg::AutoCmdReq;
+use crate::js::{JsFuture, JsRuntime, next_future_id};
+use crate::prelude::*;
+use crate::state::autocmd::{AutoCmd, AutoCmdEvent, AutoCmdOptions};
+
+use compact_str::{CompactString, ToCompactString};
+use std::rc::Rc;
+use tracing::trace;
+
+/// Call the js callback of an autocommand with the event info
+/// `{id, event, group, buffer, file, match, data}`.
+pub struct AutoCmdFuture {
+  pub req: AutoCmdReq,
+  pub cb: Option<Rc<v8::Global<v8::Function>>>,
+}
+
+impl AutoCmdFuture {
+  fn _info<'s>(
+    &self,
+    scope: &mut v8::HandleScope<'s>,
+  ) -> v8::Local<'s, v8::Object> {
+    let req = &self.req;
+    let object = v8::Object::new(scope);
+    let values: [(&str, v8::Local<v8::Value>); 7] = [
+      ("id", v8::Integer::new(scope, req.autocmd_id).into()),
+      ("event", v8::String::new(scope, &req.event).unwrap().into()),
+      (
+        "group",
+        match &req.group {
+          Some(group) => v8::String::new(scope, group).unwrap().into(),
+          None => v8::null(scope).into(),
+        },
+      ),
+      (
+        "buffer",
+        match req.buffer_id {
+          Some(buffer_id) => v8::Integer::new(scope, buffer_id).into(),
+          None => v8::null(scope).into(),
+        },
+      ),
+      ("file", v8::String::new(scope, &req.file).unwrap().into()),
+      (
+        "match",
+        v8::String::new(scope, &req.matched).unwrap().into(),
+      ),
+      ("data", v8::String::new(scope, &req.data).unwrap().into()),
+    ];
+    for (key, value) in values {
+      let key = v8::String::new(scope, key).unwrap();
+      object.set(scope, key.into(), value);
+    }
+    object
+  }
+}
+
+impl JsFuture for AutoCmdFuture {
+  fn run(&mut self, scope: &mut v8::HandleScope) {
+    trace!("autocmd callback:{:?}", self.req.future_id);
+    if self.req.once {
+      _cleanup_callbacks(scope);
+    }
+    let Some(cb) = &self.cb else {
+      return;
+    };
+    let undefined = v8::undefined(scope).into();
+    let callback = v8::Local::new(scope, (**cb).clone());
+    let info = self._info(scope).into();
+
+    let tc_scope = &mut v8::TryCatch::new(scope);
+    callback.call(tc_scope, undefined, &[info]);
+
+    // Report if callback threw an exception, the other autocommands still run.
+    if tc_scope.has_caught() {
+      let exception = tc_scope.exception().unwrap();
+      let exception = v8::Global::new(tc_scope, exception);
+      let state = JsRuntime::state(tc_scope);
+      state.borrow_mut().exceptions.capture_exception(exception);
+    }
+  }
+}
+
+// Remove the js callbacks that are not used by any autocommand.
+fn _cleanup_callbacks(scope: &mut v8::HandleScope) {
+  let state_rc = JsRuntime::state(scope);
+  let mut state = state_rc.borrow_mut();
+  let editing_state = state.editing_state.clone();
+  let editing_state = lock!(editing_state);
+  state
+    .autocmd_callbacks
+    .retain(|id, _| editing_state.autocmds().has_callback(*id));
+}
+
+// Parse the event name, the empty name is `None` (any event). It throws if the name is invalid.
+fn _event(
+  scope: &mut v8::HandleScope,
+  api: &str,
+  name: &str,
+) -> Result<Option<AutoCmdEvent>, ()> {
+  if name.is_empty() {
+    return Ok(None);
+  }
+  match AutoCmdEvent::parse(name) {
+    Some(event) => Ok(Some(event)),
+    None => {
+      throw_type_error(
+        scope,
+        &format!("\"Rsvim.autocmd.{api}\" event {name:?} is invalid"),
+      );
+      Err(())
+    }
+  }
+}
+
+// Check the group name, the empty name is `None` (no group). It throws if the group is not found.
+fn _group(
+  scope: &mut v8::HandleScope,
+  api: &str,
+  name: &str,
+) -> Result<Option<CompactString>, ()> {
+  if name.is_empty() {
+    return Ok(None);
+  }
+  let state_rc = JsRuntime::state(scope);
+  let editing_state = state_rc.borrow().editing_state.clone();
+  if lock!(editing_state).autocmds().has_group(name) {
+    Ok(Some(name.to_compact_string()))
+  } else {
+    throw_type_error(
+      scope,
+      &format!("\"Rsvim.autocmd.{api}\" group {name:?} is not found"),
+    );
+    Err(())
+  }
+}
+
+/// Create an autocommand. The arguments are `(events, callback, patterns, filetypes, buffer,
+/// group, once, desc)`, the `buffer` is `-1` for global, `0` for current buffer, the empty `group`
+/// is no group. Returns the autocommand ID.
+pub fn create(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 8);
+  let names = strings(scope, args.get(0));
+  let callback = v8::Local::<v8::Function>::try_from(args.get(1)).unwrap();
+  let patterns = strings(scope, args.get(2));
+  let filetypes = strings(scope, args.get(3));
+  let buffer = args.get(4).int32_value(scope).unwrap_or(-1);
+  let group = args.get(5).to_rust_string_lossy(scope);
+  let once = args.get(6).to_boolean(scope).boolean_value(scope);
+  let desc = args.get(7).to_rust_string_lossy(scope).to_compact_string();
+  trace!("autocmd_create: {:?} {:?}", names, patterns);
+
+  let mut events = vec![];
+  for name in names.iter() {
+    match _event(scope, "create", name) {
+      Ok(Some(event)) => {
+        if !events.contains(&event) {
+          events.push(event);
+        }
+      }
+      Ok(None) => {
+        throw_type_error(scope, "\"Rsvim.autocmd.create\" event is empty");
+        return;
+      }
+      Err(_) => return,
+    }
+  }
+  let Ok(buffer) = _buffer_id(scope, "Rsvim.autocmd.create", buffer) else {
+    return;
+  };
+  let Ok(group) = _group(scope, "create", &group) else {
+    return;
+  };
+
+  let id = next_future_id();
+  let state_rc = JsRuntime::state(scope);
+  let callback = Rc::new(v8::Global::new(scope, callback));
+  let editing_state = {
+    let mut state = state_rc.borrow_mut();
+    state.autocmd_callbacks.insert(id, callback);
+    state.editing_state.clone()
+  };
+  let options = AutoCmdOptions {
+    patterns,
+    filetypes,
+    buffer,
+    group,
+    once,
+    desc,
+  };
+  lock!(editing_state)
+    .autocmds_mut()
+    .add(AutoCmd::new(id, events, options));
+  rv.set(v8::Integer::new(scope, id).into());
+}
+
+/// Delete an autocommand by its ID.
+pub fn del(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let id = args.get(0).int32_value(scope).unwrap_or(0);
+  trace!("autocmd_del: {:?}", id);
+  let state_rc = JsRuntime::state(scope);
+  let editing_state = state_rc.borrow().editing_state.clone();
+  let deleted = lock!(editing_state).autocmds_mut().del(id);
+  if !deleted {
+    throw_type_error(
+      scope,
+      &format!("\"Rsvim.autocmd.del\" autocmd {id} is not found"),
+    );
+    return;
+  }
+  _cleanup_callbacks(scope);
+}
+
+/// Create a group if it doesn't exist. The arguments are `(name, clear)`, with `clear` the
+/// autocommands in the existing group are deleted.
+pub fn create_group(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let name = args.get(0).to_rust_string_lossy(scope);
+  let clear = args.get(1).to_boolean(scope).boolean_value(scope);
+  trace!("autocmd_create_group: {:?} {:?}", name, clear);
+  let state_rc = JsRuntime::state(scope);
+  let editing_state = state_rc.borrow().editing_state.clone();
+  lock!(editing_state)
+    .autocmds_mut()
+    .create_group(&name, clear);
+  _cleanup_callbacks(scope);
+}
+
+/// Delete a group and its autocommands.
+pub fn del_group(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let name = args.get(0).to_rust_string_lossy(scope);
+  trace!("autocmd_del_group: {:?}", name);
+  let state_rc = JsRuntime::state(scope);
+  let editing_state = state_rc.borrow().editing_state.clone();
+  let deleted = lock!(editing_state).autocmds_mut().del_group(&name);
+  if !deleted {
+    throw_type_error(
+      scope,
+      &format!("\"Rsvim.autocmd.delGroup\" group {name:?} is not found"),
+    );
+    return;
+  }
+  _cleanup_callbacks(scope);
+}
+
+/// Delete the event from the autocommands in the group. The arguments are `(event, group)`, the
+/// empty string is any event or any group.
+pub fn clear(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let event = args.get(0).to_rust_string_lossy(scope);
+  let group = args.get(1).to_rust_string_lossy(scope);
+  trace!("autocmd_clear: {:?} {:?}", event, group);
+  let Ok(event) = _event(scope, "clear", &event) else {
+    return;
+  };
+  let Ok(group) = _group(scope, "clear", &group) else {
+    return;
+  };
+  let state_rc = JsRuntime::state(scope);
+  let editing_state = state_rc.borrow().editing_state.clone();
+  lock!(editing_state)
+    .autocmds_mut()
+    .clear(event, group.as_deref());
+  _cleanup_callbacks(scope);
+}
+
+/// List the autocommands of the event in the group. The arguments are `(event, group)`, the empty
+/// string is any event or any group. Returns an array of
+/// `{id, events, patterns, filetypes, buffer, group, once, desc}`.
+pub fn list(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let event = args.get(0).to_rust_string_lossy(scope);
+  let group = args.get(1).to_rust_string_lossy(scope);
+  trace!("autocmd_list: {:?} {:?}", event, group);
+  let Ok(event) = _event(scope, "list", &event) else {
+    return;
+  };
+  let Ok(group) = _group(scope, "list", &group) else {
+    return;
+  };
+
+  let items: Vec<AutoCmd> = {
+    let state_rc = JsRuntime::state(scope);
+    let editing_state = state_rc.borrow().editing_state.clone();
+    let editing_state = lock!(editing_state);
+    editing_state
+      .autocmds()
+      .list(event, group.as_deref())
+      .into_iter()
+      .cloned()
+      .collect()
+  };
+
+  let array = v8::Array::new(scope, items.len() as i32);
+  for (i, autocmd) in items.iter().enumerate() {
+    let object = v8::Object::new(scope);
+    let options = autocmd.options();
+    let events = autocmd.events().iter().map(|e| e.name()).collect();
+    let patterns = options.patterns.iter().map(|p| p.as_str()).collect();
+    let filetypes = options.filetypes.iter().map(|f| f.as_str()).collect();
+    let values: [(&str, v8::Local<v8::Value>); 8] = [
+      ("id", v8::Integer::new(scope, autocmd.id()).into()),
+      ("events", string_array(scope, events).into()),
+      ("patterns", string_array(scope, patterns).into()),
+      ("filetypes", string_array(scope, filetypes).into()),
+      (
+        "buffer",
+        match options.buffer {
+          Some(buffer) => v8::Integer::new(scope, buffer).into(),
+          None => v8::null(scope).into(),
+        },
+      ),
+      (
+        "group",
+        match &options.group {
+          Some(group) => v8::String::new(scope, group).unwrap().into(),
+          None => v8::null(scope).into(),
+        },
+      ),
+      ("once", v8::Boolean::new(scope, options.once).into()),
+      (
+        "desc",
+        v8::String::new(scope, &options.desc).unwrap().into(),
+      ),
+    ];
+    for (key, value) in values {
+      let key = v8::String::new(scope, key).unwrap();
+      object.set(scope, key.into(), value);
+    }
+    array.set_index(scope, i as u32, object.into());
+  }
+  rv.set(array.into());
+}
@@ -0,0 +1,562 @@
+//! APIs for `Rsvim.buf` namespace.
+//!
+//! NOTE: There's no undo yet, the changes made by the APIs cannot be undone.
+
+use crate::buf::text::Text;
+use crate::buf::{BufferArc, BufferId};
+use crate::js::JsRuntime;
+use crate::js::binding::global_rsvim::opt::{fire_option_set, to_js_value};
+use crate::js::binding::global_rsvim::{
+  _buffer_id, _from_js_value, _option_def, include_eol, string_array, strings,
+};
+use crate::js::binding::{
+  throw_exception, throw_range_error, throw_type_error,
+};
+use crate::opt::{OptionAccessor, OptionScope, set_buffer_option_value};
+use crate::prelude::*;
+use crate::state::fsm::{SelectStateful, StatefulValue};
+use crate::state::ops::cursor_ops;
+use crate::ui::tree::Inodeable;
+
+use std::path::Path;
+use tracing::trace;
+
+// Get the non-negative integer argument `name` of the `api`. It throws if the value is negative,
+// not an integer, or out of range.
+fn _integer(
+  scope: &mut v8::HandleScope,
+  api: &str,
+  name: &str,
+  value: v8::Local<v8::Value>,
+) -> Result<usize, ()> {
+  let n = value.number_value(scope).unwrap_or(f64::NAN);
+  if n.fract() == 0.0 && n >= 0.0 && n <= i32::MAX as f64 {
+    return Ok(n as usize);
+  }
+  let display = value.to_rust_string_lossy(scope);
+  throw_range_error(
+    scope,
+    &format!(
+      "\"{api}\" {name} must be a non-negative integer, but found {display}"
+    ),
+  );
+  Err(())
+}
+
+// Get the buffer by the `id` argument of the `api`, `0` is current buffer. It throws if the id is
+// invalid, or the buffer is not found.
+fn _buffer(
+  scope: &mut v8::HandleScope,
+  api: &str,
+  id: v8::Local<v8::Value>,
+) -> Result<BufferArc, ()> {
+  let id = _integer(scope, api, "id", id)?;
+  let Ok(Some(id)) = _buffer_id(scope, api, id as BufferId) else {
+    return Err(());
+  };
+  let state_rc = JsRuntime::state(scope);
+  let buffers = state_rc.borrow().buffers.clone();
+  let buffer = lock!(buffers).get(&id).unwrap().clone();
+  Ok(buffer)
+}
+
+// Get the non-negative integer arguments (the line or char indexes) since the `start`. It throws
+// if any of them is invalid.
+fn _integers<const N: usize>(
+  scope: &mut v8::HandleScope,
+  api: &str,
+  args: &v8::FunctionCallbackArguments,
+  start: i32,
+) -> Result<[usize; N], ()> {
+  let mut result = [0_usize; N];
+  for (i, n) in result.iter_mut().enumerate() {
+    *n = _integer(scope, api, "position", args.get(start + i as i32))?;
+  }
+  Ok(result)
+}
+
+// Check the `position` is on a line of the text. It throws if it is out of range.
+fn _position(
+  scope: &mut v8::HandleScope,
+  api: &str,
+  text: &Text,
+  position: (usize, usize),
+) -> Result<(), ()> {
+  if text.has_position(position) {
+    Ok(())
+  } else {
+    throw_range_error(
+      scope,
+      &format!(
+        "\"{api}\" position [{}, {}] is out of range",
+        position.0, position.1
+      ),
+    );
+    Err(())
+  }
+}
+
+// Check the line range `[start, end)` is in the text. It throws if it is out of range.
+fn _line_range(
+  scope: &mut v8::HandleScope,
+  api: &str,
+  text: &Text,
+  start: usize,
+  end: usize,
+) -> Result<(), ()> {
+  if start <= end && end <= text.lines_count() {
+    Ok(())
+  } else {
+    throw_range_error(
+      scope,
+      &format!(
+        "\"{api}\" lines [{start}, {end}) is out of range, the buffer has {} lines",
+        text.lines_count()
+      ),
+    );
+    Err(())
+  }
+}
+
+// Edit the text of the buffer with `f`, then update the viewports of the windows that show the
+// buffer. The terminal buffer cannot be edited. It returns the result of `f`.
+fn _edit<T, F>(
+  scope: &mut v8::HandleScope,
+  api: &str,
+  buffer: &BufferArc,
+  f: F,
+) -> Result<T, ()>
+where
+  F: FnOnce(&mut v8::HandleScope, &mut Text) -> Result<T, ()>,
+{
+  let include_eol = include_eol(scope);
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let mut tree = lock!(tree);
+  let window_ids = tree.buffer_window_ids(buffer);
+  let mut buffer = lock!(buffer);
+  if buffer.is_terminal() {
+    let id = buffer.id();
+    throw_type_error(
+      scope,
+      &format!("\"{api}\" buffer {id} is a terminal buffer"),
+    );
+    return Err(());
+  }
+  let result = f(scope, buffer.text_mut())?;
+  cursor_ops::update_viewports_after_text_changed(
+    &mut tree,
+    &window_ids,
+    buffer.text(),
+    include_eol,
+  );
+  Ok(result)
+}
+
+// Make a js array of the position `[line, char]`.
+fn _position_array<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  (line_idx, char_idx): (usize, usize),
+) -> v8::Local<'s, v8::Array> {
+  let array = v8::Array::new(scope, 2);
+  let line_idx = v8::Integer::new(scope, line_idx as i32);
+  let char_idx = v8::Integer::new(scope, char_idx as i32);
+  array.set_index(scope, 0, line_idx.into());
+  array.set_index(scope, 1, char_idx.into());
+  array
+}
+
+/// Get current buffer ID, i.e. the buffer of current window.
+pub fn current(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  if let Ok(Some(id)) = _buffer_id(scope, "Rsvim.buf.current", 0) {
+    rv.set(v8::Integer::new(scope, id).into());
+  }
+}
+
+/// List all the buffer IDs.
+pub fn list(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let buffers = state_rc.borrow().buffers.clone();
+  let ids: Vec<BufferId> = lock!(buffers).keys().copied().collect();
+  trace!("buf_list: {:?}", ids);
+  let array = v8::Array::new(scope, ids.len() as i32);
+  for (i, id) in ids.into_iter().enumerate() {
+    let id = v8::Integer::new(scope, id);
+    array.set_index(scope, i as u32, id.into());
+  }
+  rv.set(array.into());
+}
+
+/// Create a buffer for the file name, or an unnamed buffer if the file name is empty. The file is
+/// read if it exists. If the buffer of the file already exists, it returns the existing buffer.
+/// Returns the buffer ID.
+pub fn create(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let filename = args.get(0).to_rust_string_lossy(scope);
+  trace!("buf_create: {:?}", filename);
+  let filename = if filename.is_empty() {
+    None
+  } else {
+    Some(Path::new(&filename))
+  };
+
+  let state_rc = JsRuntime::state(scope);
+  let (tree, buffers) = {
+    let state = state_rc.borrow();
+    (state.tree.clone(), state.buffers.clone())
+  };
+  let canvas_size = {
+    let tree = lock!(tree);
+    let shape = tree.node(tree.root_id()).unwrap().actual_shape();
+    U16Size::new(shape.width(), shape.height())
+  };
+  let mut buffers = lock!(buffers);
+  let id = match (buffers.find_by_filename(filename), filename) {
+    (Some(id), Some(_)) => id,
+    (Some(_), None) => {
+      throw_type_error(
+        scope,
+        "\"Rsvim.buf.create\" the unnamed buffer already exists",
+      );
+      return;
+    }
+    (None, Some(filename)) => {
+      match buffers.new_file_buffer(canvas_size, filename) {
+        Ok(id) => id,
+        Err(e) => {
+          throw_exception(scope, &AnyErr::from(e));
+          return;
+        }
+      }
+    }
+    (None, None) => buffers.new_empty_buffer(canvas_size),
+  };
+  rv.set(v8::Integer::new(scope, id).into());
+}
+
+/// Get the info of the buffer by the `field`: `name` (the file name, empty for the unnamed
+/// buffer), `filetype`, `changedtick`, `modified` or `lineCount`.
+pub fn info(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let field = args.get(1).to_rust_string_lossy(scope);
+  let api = format!("Rsvim.buf.{field}");
+  let Ok(buffer) = _buffer(scope, &api, args.get(0)) else {
+    return;
+  };
+  let buffer = lock!(buffer);
+  trace!("buf_info: {:?} {:?}", buffer.id(), field);
+  let value: v8::Local<v8::Value> = match field.as_str() {
+    "name" => {
+      let name = buffer
+        .filename()
+        .as_ref()
+        .map(|filename| filename.to_string_lossy().to_string())
+        .unwrap_or_default();
+      v8::String::new(scope, &name).unwrap().into()
+    }
+    "filetype" => v8::String::new(scope, buffer.filetype()).unwrap().into(),
+    "changedtick" => {
+      v8::Number::new(scope, buffer.text().changedtick() as f64).into()
+    }
+    "modified" => v8::Boolean::new(scope, buffer.is_modified()).into(),
+    "lineCount" => {
+      v8::Integer::new(scope, buffer.text().lines_count() as i32).into()
+    }
+    _ => unreachable!(),
+  };
+  rv.set(value);
+}
+
+/// Get the lines of the buffer. The arguments are `(id, start, end)`, the range is `[start, end)`
+/// and `end` is the line count if it is negative. Returns the lines without the eols.
+pub fn get_lines(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 3);
+  let api = "Rsvim.buf.getLines";
+  let Ok(buffer) = _buffer(scope, api, args.get(0)) else {
+    return;
+  };
+  let Ok([start]) = _integers::<1>(scope, api, &args, 1) else {
+    return;
+  };
+  let end = args.get(2).int32_value(scope).unwrap_or(-1);
+  let buffer = lock!(buffer);
+  let text = buffer.text();
+  let end = if end < 0 {
+    text.lines_count()
+  } else {
+    end as usize
+  };
+  trace!("buf_get_lines: {:?} {:?}-{:?}", buffer.id(), start, end);
+  if _line_range(scope, api, text, start, end).is_err() {
+    return;
+  }
+  let lines = text.get_lines(start..end);
+  let lines = string_array(scope, lines.iter().map(|l| l.as_str()).collect());
+  rv.set(lines.into());
+}
+
+/// Replace the lines of the buffer. The arguments are `(id, start, end, lines)`, the range is
+/// `[start, end)` and `end` is the line count if it is negative.
+pub fn set_lines(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 4);
+  let api = "Rsvim.buf.setLines";
+  let Ok(buffer) = _buffer(scope, api, args.get(0)) else {
+    return;
+  };
+  let Ok([start]) = _integers::<1>(scope, api, &args, 1) else {
+    return;
+  };
+  let end = args.get(2).int32_value(scope).unwrap_or(-1);
+  let lines: Vec<String> = strings(scope, args.get(3))
+    .into_iter()
+    .map(|line| line.to_string())
+    .collect();
+  trace!("buf_set_lines: {:?}-{:?} {:?}", start, end, lines);
+  let _ = _edit(scope, api, &buffer, |scope, text| {
+    let end = if end < 0 {
+      text.lines_count()
+    } else {
+      end as usize
+    };
+    _line_range(scope, api, text, start, end)?;
+    text.set_lines(start..end, &lines);
+    Ok(())
+  });
+}
+
+/// Get the text of the buffer. The arguments are `(id, startLine, startChar, endLine, endChar)`,
+/// the end is exclusive.
+pub fn get_text(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 5);
+  let api = "Rsvim.buf.getText";
+  let Ok(buffer) = _buffer(scope, api, args.get(0)) else {
+    return;
+  };
+  let Ok([start_line, start_char, end_line, end_char]) =
+    _integers::<4>(scope, api, &args, 1)
+  else {
+    return;
+  };
+  let (start, end) = ((start_line, start_char), (end_line, end_char));
+  trace!("buf_get_text: {:?}-{:?}", start, end);
+  let buffer = lock!(buffer);
+  let text = buffer.text();
+  if _position(scope, api, text, start).is_err()
+    || _position(scope, api, text, end.max(start)).is_err()
+  {
+    return;
+  }
+  let payload = text.get_text(start, end.max(start));
+  rv.set(v8::String::new(scope, &payload).unwrap().into());
+}
+
+/// Replace the text of the buffer. The arguments are `(id, startLine, startChar, endLine,
+/// endChar, text)`, the end is exclusive. Returns the position `[line, char]` after the inserted
+/// text.
+pub fn set_text(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 6);
+  let api = "Rsvim.buf.setText";
+  let Ok(buffer) = _buffer(scope, api, args.get(0)) else {
+    return;
+  };
+  let Ok([start_line, start_char, end_line, end_char]) =
+    _integers::<4>(scope, api, &args, 1)
+  else {
+    return;
+  };
+  let (start, end) = ((start_line, start_char), (end_line, end_char));
+  let payload = args.get(5).to_rust_string_lossy(scope);
+  trace!("buf_set_text: {:?}-{:?} {:?}", start, end, payload);
+  let position = _edit(scope, api, &buffer, |scope, text| {
+    _position(scope, api, text, start)?;
+    _position(scope, api, text, end.max(start))?;
+    Ok(text.set_text(start, end.max(start), &payload))
+  });
+  if let Ok(position) = position {
+    rv.set(_position_array(scope, position).into());
+  }
+}
+
+/// Insert the text into the buffer. The arguments are `(id, line, char, text)`. Returns the
+/// position `[line, char]` after the inserted text.
+pub fn insert(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 4);
+  let api = "Rsvim.buf.insert";
+  let Ok(buffer) = _buffer(scope, api, args.get(0)) else {
+    return;
+  };
+  let Ok([line_idx, char_idx]) = _integers::<2>(scope, api, &args, 1) else {
+    return;
+  };
+  let payload = args.get(3).to_rust_string_lossy(scope);
+  trace!("buf_insert: {:?} {:?}", (line_idx, char_idx), payload);
+  let position = _edit(scope, api, &buffer, |scope, text| {
+    _position(scope, api, text, (line_idx, char_idx))?;
+    if payload.is_empty() {
+      Ok((line_idx, char_idx))
+    } else {
+      Ok(text.insert_at(line_idx, char_idx, payload.into()))
+    }
+  });
+  if let Ok(position) = position {
+    rv.set(_position_array(scope, position).into());
+  }
+}
+
+/// Delete the chars in the buffer. The arguments are `(id, line, char, n)`, it deletes `n` chars
+/// to the right if `n>0`, or to the left if `n<0`. Returns the position `[line, char]` after the
+/// deleted chars.
+pub fn delete(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 4);
+  let api = "Rsvim.buf.delete";
+  let Ok(buffer) = _buffer(scope, api, args.get(0)) else {
+    return;
+  };
+  let Ok([line_idx, char_idx]) = _integers::<2>(scope, api, &args, 1) else {
+    return;
+  };
+  let n = args.get(3).int32_value(scope).unwrap_or(0) as isize;
+  trace!("buf_delete: {:?} {:?}", (line_idx, char_idx), n);
+  let position = _edit(scope, api, &buffer, |scope, text| {
+    _position(scope, api, text, (line_idx, char_idx))?;
+    // The empty last line without the eol has nothing to delete to the right.
+    let len_chars = text.rope().line(line_idx).len_chars();
+    if n == 0 || char_idx >= len_chars {
+      return Ok((line_idx, char_idx));
+    }
+    Ok(
+      text
+        .delete_at(line_idx, char_idx, n)
+        .unwrap_or((line_idx, char_idx)),
+    )
+  });
+  if let Ok(position) = position {
+    rv.set(_position_array(scope, position).into());
+  }
+}
+
+/// Get the buffer-local option of the buffer. The arguments are `(id, name)`.
+pub fn get_option(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let api = "Rsvim.buf.getOption";
+  let Ok(buffer) = _buffer(scope, api, args.get(0)) else {
+    return;
+  };
+  let name = args.get(1).to_rust_string_lossy(scope);
+  trace!("buf_get_option: {:?}", name);
+  let Ok(def) = _option_def(scope, api, &name, Some(OptionScope::Buffer))
+  else {
+    return;
+  };
+  let OptionAccessor::Buffer { get, .. } = def.accessor() else {
+    unreachable!()
+  };
+  let value = get(lock!(buffer).options());
+  rv.set(to_js_value(scope, &value));
+}
+
+/// Set the buffer-local option of the buffer. The arguments are `(id, name, value)`.
+pub fn set_option(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 3);
+  let api = "Rsvim.buf.setOption";
+  let Ok(buffer) = _buffer(scope, api, args.get(0)) else {
+    return;
+  };
+  let name = args.get(1).to_rust_string_lossy(scope);
+  trace!("buf_set_option: {:?}", name);
+  let Ok(def) = _option_def(scope, api, &name, Some(OptionScope::Buffer))
+  else {
+    return;
+  };
+  let Ok(value) = _from_js_value(scope, api, def, args.get(2)) else {
+    return;
+  };
+  let OptionAccessor::Buffer { get, .. } = def.accessor() else {
+    unreachable!()
+  };
+  let old = get(lock!(buffer).options());
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  set_buffer_option_value(&mut lock!(tree), &buffer, def, &value);
+  if old != value {
+    fire_option_set(scope, def);
+  }
+}
+
+/// Select the text in current buffer and start select mode, i.e. the snippet placeholders. The
+/// arguments are the 0-based `(line, char)` of the start and the end, both are inclusive.
+pub fn select(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 4);
+  let Ok(position) = _integers::<4>(scope, "Rsvim.buf.select", &args, 0) else {
+    return;
+  };
+  let anchor = (position[0], position[1]);
+  let cursor = (position[2], position[3]);
+  trace!("select: {:?}-{:?}", anchor, cursor);
+
+  let state_rc = JsRuntime::state(scope);
+  let (tree, editing_state) = {
+    let state = state_rc.borrow();
+    (state.tree.clone(), state.editing_state.clone())
+  };
+  SelectStateful::select_text(&mut lock!(tree), anchor, cursor);
+
+  let mut editing_state = lock!(editing_state);
+  // The text inserted before selecting is a finished change, i.e. expanding a snippet in insert
+  // mode.
+  editing_state.dot_repeat_mut().finish();
+  editing_state
+    .request_stateful(StatefulValue::SelectMode(SelectStateful::default()));
+}
@@ -0,0 +1,510 @@
+//! APIs for `Rsvim.cmd` namespace.
+
+use crate::content::message::MessageLevel;
+use crate::excommand::complete::{CompletionItem, CompletionKind};
+use crate::excommand::{
+  ExCommand, ExCommandNargs, ExCommandOptions, ExCommandRange, is_valid_name,
+};
+use crate::js::binding::global_rsvim::string_array;
+use crate::js::binding::throw_type_error;
+use crate::js::msg::{CompletionReq, ExCommandReq, RepeatReq};
+use crate::js::{JsFuture, JsRuntime, next_future_id};
+use crate::prelude::*;
+use crate::state::ops::cmdline_ops;
+use crate::state::repeat::RepeatableChange;
+
+use compact_str::ToCompactString;
+use std::rc::Rc;
+use tracing::trace;
+
+/// Call the js completer for the completion request, and fill the returned matches to the
+/// command-line wildmenu.
+pub struct CompletionFuture {
+  pub req: CompletionReq,
+  pub cb: Option<Rc<v8::Global<v8::Function>>>,
+}
+
+impl CompletionFuture {
+  fn _call(&self, scope: &mut v8::HandleScope) -> Vec<CompletionItem> {
+    let Some(cb) = &self.cb else {
+      return vec![];
+    };
+    let undefined = v8::undefined(scope).into();
+    let callback = v8::Local::new(scope, (**cb).clone());
+    let args: Vec<v8::Local<v8::Value>> = vec![
+      v8::String::new(scope, &self.req.arg_lead).unwrap().into(),
+      v8::String::new(scope, &self.req.cmdline).unwrap().into(),
+      v8::Integer::new(scope, self.req.cursor_pos as i32).into(),
+    ];
+
+    let tc_scope = &mut v8::TryCatch::new(scope);
+    let result = callback.call(tc_scope, undefined, &args);
+
+    // Report if callback threw an exception.
+    if tc_scope.has_caught() {
+      let exception = tc_scope.exception().unwrap();
+      let exception = v8::Global::new(tc_scope, exception);
+      let state = JsRuntime::state(tc_scope);
+      state.borrow_mut().exceptions.capture_exception(exception);
+      return vec![];
+    }
+
+    let mut items = vec![];
+    if let Some(matches) =
+      result.and_then(|r| v8::Local::<v8::Array>::try_from(r).ok())
+    {
+      for i in 0..matches.length() {
+        if let Some(m) = matches.get_index(tc_scope, i) {
+          let m = m.to_rust_string_lossy(tc_scope);
+          items.push(CompletionItem::from(m.as_str()));
+        }
+      }
+    }
+    items
+  }
+}
+
+impl JsFuture for CompletionFuture {
+  fn run(&mut self, scope: &mut v8::HandleScope) {
+    trace!("completion callback:{:?}", self.req.future_id);
+    let items = self._call(scope);
+
+    let state_rc = JsRuntime::state(scope);
+    let (tree, contents, editing_state) = {
+      let state = state_rc.borrow();
+      (
+        state.tree.clone(),
+        state.contents.clone(),
+        state.editing_state.clone(),
+      )
+    };
+    let global_options = lock!(editing_state).global_options().clone();
+    let mut tree = lock!(tree);
+    let mut contents = lock!(contents);
+    if let Some(cmdline_id) = tree.command_line_id() {
+      cmdline_ops::cmdline_complete_resolve(
+        &mut tree,
+        cmdline_id,
+        &mut contents,
+        &global_options,
+        self.req.future_id,
+        items,
+      );
+    }
+  }
+}
+
+// Check the number of the arguments. It throws if the number is wrong.
+fn _check_args(
+  scope: &mut v8::HandleScope,
+  args: &v8::FunctionCallbackArguments,
+  api: &str,
+  expect: i32,
+) -> Result<(), ()> {
+  if args.length() == expect {
+    return Ok(());
+  }
+  throw_type_error(
+    scope,
+    &format!(
+      "\"{api}\" expects {expect} arguments, but found {}",
+      args.length()
+    ),
+  );
+  Err(())
+}
+
+// Parse the function argument. It throws if the value is not a function.
+fn _function<'a>(
+  scope: &mut v8::HandleScope,
+  value: v8::Local<'a, v8::Value>,
+  api: &str,
+  name: &str,
+) -> Result<v8::Local<'a, v8::Function>, ()> {
+  if let Ok(f) = v8::Local::<v8::Function>::try_from(value) {
+    return Ok(f);
+  }
+  let display = value.to_rust_string_lossy(scope);
+  throw_type_error(
+    scope,
+    &format!("\"{api}\" {name} must be a function, but found {display}"),
+  );
+  Err(())
+}
+
+/// Set the completer for the arguments of an ex command, or remove it if the completer is
+/// `null`/`undefined`.
+pub fn set_completer(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  if _check_args(scope, &args, "Rsvim.cmd.setCompleter", 2).is_err() {
+    return;
+  }
+  let name = args.get(0).to_rust_string_lossy(scope).to_compact_string();
+  let state_rc = JsRuntime::state(scope);
+  match v8::Local::<v8::Function>::try_from(args.get(1)) {
+    Ok(completer) => {
+      trace!("set_completer:{:?}", name);
+      let completer = Rc::new(v8::Global::new(scope, completer));
+      state_rc.borrow_mut().completers.insert(name, completer);
+    }
+    Err(_) => {
+      trace!("remove completer:{:?}", name);
+      state_rc.borrow_mut().completers.remove(&name);
+    }
+  }
+}
+
+/// Call the repeatable action for `.`, with the count given to `.` (or `undefined`).
+pub struct RepeatFuture {
+  pub req: RepeatReq,
+  pub cb: Option<Rc<v8::Global<v8::Function>>>,
+}
+
+impl JsFuture for RepeatFuture {
+  fn run(&mut self, scope: &mut v8::HandleScope) {
+    trace!("repeat callback:{:?}", self.req.future_id);
+    let Some(cb) = &self.cb else {
+      return;
+    };
+    let undefined = v8::undefined(scope).into();
+    let callback = v8::Local::new(scope, (**cb).clone());
+    let count: v8::Local<v8::Value> = match self.req.count {
+      Some(count) => v8::Number::new(scope, count as f64).into(),
+      None => undefined,
+    };
+
+    let tc_scope = &mut v8::TryCatch::new(scope);
+    callback.call(tc_scope, undefined, &[count]);
+
+    // Report if callback threw an exception.
+    if tc_scope.has_caught() {
+      let exception = tc_scope.exception().unwrap();
+      let exception = v8::Global::new(tc_scope, exception);
+      let state = JsRuntime::state(tc_scope);
+      state.borrow_mut().exceptions.capture_exception(exception);
+    }
+  }
+}
+
+/// Set the repeatable action as the last change, it is called by `.`.
+pub fn set_repeat(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  if _check_args(scope, &args, "Rsvim.cmd.setRepeat", 1).is_err() {
+    return;
+  }
+  let Ok(action) =
+    _function(scope, args.get(0), "Rsvim.cmd.setRepeat", "action")
+  else {
+    return;
+  };
+  trace!("set_repeat");
+  let action = Rc::new(v8::Global::new(scope, action));
+  let state_rc = JsRuntime::state(scope);
+  let editing_state = {
+    let mut state = state_rc.borrow_mut();
+    state.repeat_action = Some(action);
+    state.editing_state.clone()
+  };
+  lock!(editing_state)
+    .dot_repeat_mut()
+    .set_last_change(RepeatableChange::js());
+}
+
+/// Call the js callback of a user ex command with the parsed arguments
+/// `{name, args, fargs, range, bang, count}`. The returned value (except `undefined` and `null`)
+/// is shown in the message area.
+pub struct ExCommandFuture {
+  pub req: ExCommandReq,
+  pub cb: Option<Rc<v8::Global<v8::Function>>>,
+}
+
+impl ExCommandFuture {
+  fn _args<'s>(
+    &self,
+    scope: &mut v8::HandleScope<'s>,
+  ) -> v8::Local<'s, v8::Object> {
+    let args = &self.req.args;
+    let object = v8::Object::new(scope);
+    let fargs = args.fargs.iter().map(|a| a.as_str()).collect();
+    let values: [(&str, v8::Local<v8::Value>); 6] = [
+      ("name", v8::String::new(scope, &args.name).unwrap().into()),
+      ("args", v8::String::new(scope, &args.args).unwrap().into()),
+      ("fargs", string_array(scope, fargs).into()),
+      (
+        "range",
+        // The 1-based line numbers, i.e. `:3,5Foo` is `[3, 5]`.
+        match args.range {
+          Some((start, end)) => {
+            let elements = [
+              v8::Integer::new(scope, start as i32 + 1).into(),
+              v8::Integer::new(scope, end as i32 + 1).into(),
+            ];
+            v8::Array::new_with_elements(scope, &elements).into()
+          }
+          None => v8::null(scope).into(),
+        },
+      ),
+      ("bang", v8::Boolean::new(scope, args.bang).into()),
+      (
+        "count",
+        match args.count {
+          Some(count) => v8::Number::new(scope, count as f64).into(),
+          None => v8::null(scope).into(),
+        },
+      ),
+    ];
+    for (key, value) in values {
+      let key = v8::String::new(scope, key).unwrap();
+      object.set(scope, key.into(), value);
+    }
+    object
+  }
+}
+
+impl JsFuture for ExCommandFuture {
+  fn run(&mut self, scope: &mut v8::HandleScope) {
+    trace!("ex command callback:{:?}", self.req.future_id);
+    let Some(cb) = &self.cb else {
+      return;
+    };
+    let undefined = v8::undefined(scope).into();
+    let callback = v8::Local::new(scope, (**cb).clone());
+    let args = self._args(scope).into();
+
+    let tc_scope = &mut v8::TryCatch::new(scope);
+    let result = callback.call(tc_scope, undefined, &[args]);
+
+    // Report if callback threw an exception.
+    if tc_scope.has_caught() {
+      let exception = tc_scope.exception().unwrap();
+      let exception = v8::Global::new(tc_scope, exception);
+      let state = JsRuntime::state(tc_scope);
+      state.borrow_mut().exceptions.capture_exception(exception);
+      return;
+    }
+
+    // The async callback reports its rejection as an unhandled rejection.
+    let text = match result {
+      Some(result)
+        if !result.is_null_or_undefined() && !result.is_promise() =>
+      {
+        result.to_rust_string_lossy(tc_scope)
+      }
+      _ => return,
+    };
+    let state_rc = JsRuntime::state(tc_scope);
+    let contents = state_rc.borrow().contents.clone();
+    lock!(contents)
+      .messages_mut()
+      .echo(MessageLevel::Info, &text);
+  }
+}
+
+// Remove the js callbacks that are not used by any user ex command.
+fn _cleanup_callbacks(scope: &mut v8::HandleScope) {
+  let state_rc = JsRuntime::state(scope);
+  let mut state = state_rc.borrow_mut();
+  let editing_state = state.editing_state.clone();
+  let editing_state = lock!(editing_state);
+  state
+    .ex_command_callbacks
+    .retain(|id, _| editing_state.ex_commands().has_callback(*id));
+}
+
+// Parse the `range` option: `true` is a range of lines that defaults to the cursor line, `"%"`
+// defaults to the whole file, a number is a count that defaults to it. It throws if the value is
+// invalid.
+fn _range(
+  scope: &mut v8::HandleScope,
+  value: v8::Local<v8::Value>,
+) -> Result<ExCommandRange, ()> {
+  if value.is_boolean() {
+    return Ok(if value.boolean_value(scope) {
+      ExCommandRange::Line
+    } else {
+      ExCommandRange::None
+    });
+  }
+  if value.is_number() {
+    let n = value.number_value(scope).unwrap();
+    if n.fract() == 0.0 && n >= 0.0 {
+      return Ok(ExCommandRange::Count(n as usize));
+    }
+  }
+  if value.is_string() && value.to_rust_string_lossy(scope) == "%" {
+    return Ok(ExCommandRange::File);
+  }
+  let display = value.to_rust_string_lossy(scope);
+  throw_type_error(
+    scope,
+    &format!(
+      "\"Rsvim.cmd.create\" range must be a boolean value, \"%\" or a non-negative integer, but found {display}"
+    ),
+  );
+  Err(())
+}
+
+/// Create a user ex command, or replace the existing one. The arguments are `(name, callback,
+/// nargs, range, bang, complete, desc)`, the empty `complete` is no completion.
+pub fn create(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  if _check_args(scope, &args, "Rsvim.cmd.create", 7).is_err() {
+    return;
+  }
+  let name = args.get(0).to_rust_string_lossy(scope).to_compact_string();
+  let Ok(callback) =
+    _function(scope, args.get(1), "Rsvim.cmd.create", "callback")
+  else {
+    return;
+  };
+  let nargs = args.get(2).to_rust_string_lossy(scope);
+  let bang = args.get(4).to_boolean(scope).boolean_value(scope);
+  let complete = args.get(5).to_rust_string_lossy(scope);
+  let desc = args.get(6).to_rust_string_lossy(scope).to_compact_string();
+  trace!("cmd_create: {:?} {:?}", name, nargs);
+
+  if !is_valid_name(&name) {
+    throw_type_error(
+      scope,
+      &format!(
+        "\"Rsvim.cmd.create\" name {name:?} must start with an uppercase letter, and only contain letters and digits"
+      ),
+    );
+    return;
+  }
+  let Some(nargs) = ExCommandNargs::parse(&nargs) else {
+    throw_type_error(
+      scope,
+      &format!("\"Rsvim.cmd.create\" nargs {nargs:?} is invalid"),
+    );
+    return;
+  };
+  let Ok(range) = _range(scope, args.get(3)) else {
+    return;
+  };
+  let complete = match complete.as_str() {
+    "" => None,
+    kind => match CompletionKind::parse(kind) {
+      Some(kind) => Some(kind),
+      None => {
+        throw_type_error(
+          scope,
+          &format!("\"Rsvim.cmd.create\" complete {kind:?} is invalid"),
+        );
+        return;
+      }
+    },
+  };
+
+  let id = next_future_id();
+  let state_rc = JsRuntime::state(scope);
+  let callback = Rc::new(v8::Global::new(scope, callback));
+  let editing_state = {
+    let mut state = state_rc.borrow_mut();
+    state.ex_command_callbacks.insert(id, callback);
+    state.editing_state.clone()
+  };
+  let options = ExCommandOptions {
+    nargs,
+    range,
+    bang,
+    complete,
+    desc,
+  };
+  lock!(editing_state)
+    .ex_commands_mut()
+    .insert(ExCommand::new(name, id, options));
+  _cleanup_callbacks(scope);
+}
+
+/// Delete a user ex command by its name, and its completer.
+pub fn del(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  if _check_args(scope, &args, "Rsvim.cmd.del", 1).is_err() {
+    return;
+  }
+  let name = args.get(0).to_rust_string_lossy(scope).to_compact_string();
+  trace!("cmd_del: {:?}", name);
+  let state_rc = JsRuntime::state(scope);
+  let editing_state = state_rc.borrow().editing_state.clone();
+  let deleted = lock!(editing_state).ex_commands_mut().remove(&name);
+  if deleted.is_none() {
+    throw_type_error(
+      scope,
+      &format!("\"Rsvim.cmd.del\" command {name:?} is not found"),
+    );
+    return;
+  }
+  state_rc.borrow_mut().completers.remove(&name);
+  _cleanup_callbacks(scope);
+}
+
+/// List the user ex commands in the order of names. Returns an array of
+/// `{name, nargs, range, bang, complete, desc}`.
+pub fn list(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  trace!("cmd_list");
+  let items: Vec<ExCommand> = {
+    let state_rc = JsRuntime::state(scope);
+    let editing_state = state_rc.borrow().editing_state.clone();
+    let editing_state = lock!(editing_state);
+    editing_state.ex_commands().list().cloned().collect()
+  };
+
+  let array = v8::Array::new(scope, items.len() as i32);
+  for (i, cmd) in items.iter().enumerate() {
+    let object = v8::Object::new(scope);
+    let options = cmd.options();
+    let values: [(&str, v8::Local<v8::Value>); 6] = [
+      ("name", v8::String::new(scope, cmd.name()).unwrap().into()),
+      (
+        "nargs",
+        v8::String::new(scope, options.nargs.as_str())
+          .unwrap()
+          .into(),
+      ),
+      (
+        "range",
+        match options.range {
+          ExCommandRange::None => v8::Boolean::new(scope, false).into(),
+          ExCommandRange::Line => v8::Boolean::new(scope, true).into(),
+          ExCommandRange::File => v8::String::new(scope, "%").unwrap().into(),
+          ExCommandRange::Count(n) => v8::Number::new(scope, n as f64).into(),
+        },
+      ),
+      ("bang", v8::Boolean::new(scope, options.bang).into()),
+      (
+        "complete",
+        match options.complete {
+          Some(kind) => v8::String::new(scope, kind.name()).unwrap().into(),
+          None => v8::null(scope).into(),
+        },
+      ),
+      (
+        "desc",
+        v8::String::new(scope, &options.desc).unwrap().into(),
+      ),
+    ];
+    for (key, value) in values {
+      let key = v8::String::new(scope, key).unwrap();
+      object.set(scope, key.into(), value);
+    }
+    array.set_index(scope, i as u32, object.into());
+  }
+  rv.set(array.into());
+}
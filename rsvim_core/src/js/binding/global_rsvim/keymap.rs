@@ -0,0 +1,291 @@
+//! APIs for `Rsvim.keymap` namespace.
+
+use crate::js::binding::global_rsvim::_buffer_id;
+use crate::js::binding::throw_type_error;
+use crate::js::msg::KeymapReq;
+use crate::js::{JsFuture, JsRuntime, next_future_id};
+use crate::prelude::*;
+use crate::state::keymap::{
+  Keymap, KeymapOptions, KeymapTarget, mode_char, parse_modes,
+};
+use crate::state::keys;
+use crate::state::mode::Mode;
+
+use compact_str::ToCompactString;
+use std::rc::Rc;
+use tracing::trace;
+
+// The modes in the order of listing.
+const LIST_MODES: [Mode; 7] = [
+  Mode::Normal,
+  Mode::Visual,
+  Mode::Select,
+  Mode::OperatorPending,
+  Mode::Insert,
+  Mode::CommandLineEx,
+  Mode::Terminal,
+];
+
+/// Call the js callback of a mapping. For an `<expr>` mapping, the returned string is fed as the
+/// keys, see [`mapping::resume`](crate::state::fsm::mapping::resume).
+pub struct KeymapFuture {
+  pub req: KeymapReq,
+  pub cb: Option<Rc<v8::Global<v8::Function>>>,
+}
+
+impl JsFuture for KeymapFuture {
+  fn run(&mut self, scope: &mut v8::HandleScope) {
+    trace!("keymap callback:{:?}", self.req.future_id);
+    let Some(cb) = &self.cb else {
+      return;
+    };
+    let undefined = v8::undefined(scope).into();
+    let callback = v8::Local::new(scope, (**cb).clone());
+
+    let tc_scope = &mut v8::TryCatch::new(scope);
+    let result = callback.call(tc_scope, undefined, &[]);
+
+    // Report if callback threw an exception.
+    if tc_scope.has_caught() {
+      let exception = tc_scope.exception().unwrap();
+      let exception = v8::Global::new(tc_scope, exception);
+      let state = JsRuntime::state(tc_scope);
+      state.borrow_mut().exceptions.capture_exception(exception);
+      return;
+    }
+
+    if !self.req.expr {
+      return;
+    }
+    let keys = match result {
+      Some(result) if result.is_string() => {
+        result.to_rust_string_lossy(tc_scope).to_compact_string()
+      }
+      _ => return,
+    };
+    // The event loop feeds the keys before the keys typed after the mapping.
+    let state_rc = JsRuntime::state(tc_scope);
+    let editing_state = state_rc.borrow().editing_state.clone();
+    lock!(editing_state).set_keymap_expr_keys(Some(keys));
+  }
+}
+
+// Remove the js callbacks that are not used by any mapping.
+fn _cleanup_callbacks(scope: &mut v8::HandleScope) {
+  let state_rc = JsRuntime::state(scope);
+  let mut state = state_rc.borrow_mut();
+  let editing_state = state.editing_state.clone();
+  let editing_state = lock!(editing_state);
+  state
+    .keymap_callbacks
+    .retain(|id, _| editing_state.keymaps().has_callback(*id));
+}
+
+/// Set a mapping. The arguments are `(modes, lhs, rhs, remap, silent, expr, buffer, desc)`, the
+/// `rhs` is the keys or a js callback. The `buffer` is `-1` for global, `0` for current buffer.
+pub fn set(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 8);
+  let modes = args.get(0).to_rust_string_lossy(scope);
+  let lhs = args.get(1).to_rust_string_lossy(scope);
+  let remap = args.get(3).to_boolean(scope).boolean_value(scope);
+  let silent = args.get(4).to_boolean(scope).boolean_value(scope);
+  let expr = args.get(5).to_boolean(scope).boolean_value(scope);
+  let buffer = args.get(6).int32_value(scope).unwrap_or(-1);
+  let desc = args.get(7).to_rust_string_lossy(scope).to_compact_string();
+  trace!("keymap_set: {:?} {:?}", modes, lhs);
+
+  let Some(modes) = parse_modes(&modes) else {
+    throw_type_error(
+      scope,
+      &format!("\"Rsvim.keymap.set\" modes {modes:?} is invalid"),
+    );
+    return;
+  };
+  let Ok(buffer) = _buffer_id(scope, "Rsvim.keymap.set", buffer) else {
+    return;
+  };
+
+  let state_rc = JsRuntime::state(scope);
+  let editing_state = state_rc.borrow().editing_state.clone();
+  let target = match v8::Local::<v8::Function>::try_from(args.get(2)) {
+    Ok(callback) => {
+      let id = next_future_id();
+      let callback = Rc::new(v8::Global::new(scope, callback));
+      state_rc.borrow_mut().keymap_callbacks.insert(id, callback);
+      KeymapTarget::Callback(id)
+    }
+    Err(_) => {
+      let rhs = args.get(2).to_rust_string_lossy(scope);
+      KeymapTarget::Keys(lock!(editing_state).keymaps().parse_rhs(&rhs))
+    }
+  };
+
+  {
+    let mut editing_state = lock!(editing_state);
+    let keymaps = editing_state.keymaps_mut();
+    let lhs = keymaps.parse_lhs(&lhs);
+    let options = KeymapOptions {
+      noremap: !remap,
+      silent,
+      expr,
+      buffer,
+      desc,
+    };
+    keymaps.set(&modes, Keymap::new(lhs, target, options));
+  }
+  _cleanup_callbacks(scope);
+}
+
+/// Delete a mapping. The arguments are `(modes, lhs, buffer)`.
+pub fn del(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 3);
+  let modes = args.get(0).to_rust_string_lossy(scope);
+  let lhs = args.get(1).to_rust_string_lossy(scope);
+  let buffer = args.get(2).int32_value(scope).unwrap_or(-1);
+  trace!("keymap_del: {:?} {:?}", modes, lhs);
+
+  let Some(modes) = parse_modes(&modes) else {
+    throw_type_error(
+      scope,
+      &format!("\"Rsvim.keymap.del\" modes {modes:?} is invalid"),
+    );
+    return;
+  };
+  let Ok(buffer) = _buffer_id(scope, "Rsvim.keymap.del", buffer) else {
+    return;
+  };
+
+  let state_rc = JsRuntime::state(scope);
+  let editing_state = state_rc.borrow().editing_state.clone();
+  let deleted = {
+    let mut editing_state = lock!(editing_state);
+    let keymaps = editing_state.keymaps_mut();
+    let lhs = keymaps.parse_lhs(&lhs);
+    keymaps.del(&modes, &lhs, buffer)
+  };
+  if deleted.is_empty() {
+    throw_type_error(
+      scope,
+      &format!("\"Rsvim.keymap.del\" mapping {lhs:?} is not found"),
+    );
+    return;
+  }
+  _cleanup_callbacks(scope);
+}
+
+/// List the mappings of the `modes`, including the buffer-local mappings of current buffer.
+/// Returns an array of `{mode, lhs, rhs, remap, silent, expr, buffer, desc, builtin}`.
+pub fn list(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let modes = args.get(0).to_rust_string_lossy(scope);
+  trace!("keymap_list: {:?}", modes);
+  let Some(modes) = parse_modes(&modes) else {
+    throw_type_error(
+      scope,
+      &format!("\"Rsvim.keymap.list\" modes {modes:?} is invalid"),
+    );
+    return;
+  };
+  let Ok(buffer) = _buffer_id(scope, "Rsvim.keymap.list", 0) else {
+    return;
+  };
+
+  // (mode, lhs, rhs, remap, silent, expr, buffer, desc, builtin)
+  let items = {
+    let state_rc = JsRuntime::state(scope);
+    let editing_state = state_rc.borrow().editing_state.clone();
+    let editing_state = lock!(editing_state);
+    LIST_MODES
+      .iter()
+      .filter(|mode| modes.contains(mode))
+      .flat_map(|mode| {
+        editing_state
+          .keymaps()
+          .list(*mode, buffer)
+          .into_iter()
+          .map(|keymap| {
+            (
+              mode_char(*mode).to_string(),
+              keys::to_notations(keymap.lhs()),
+              keymap.rhs_notation(),
+              !keymap.noremap(),
+              keymap.silent(),
+              keymap.expr(),
+              keymap.buffer(),
+              keymap.desc().to_string(),
+              keymap.is_builtin(),
+            )
+          })
+          .collect::<Vec<_>>()
+      })
+      .collect::<Vec<_>>()
+  };
+
+  let array = v8::Array::new(scope, items.len() as i32);
+  for (i, item) in items.into_iter().enumerate() {
+    let object = v8::Object::new(scope);
+    let (mode, lhs, rhs, remap, silent, expr, buffer, desc, builtin) = item;
+    let values: [(&str, v8::Local<v8::Value>); 9] = [
+      ("mode", v8::String::new(scope, &mode).unwrap().into()),
+      ("lhs", v8::String::new(scope, &lhs).unwrap().into()),
+      ("rhs", v8::String::new(scope, &rhs).unwrap().into()),
+      ("remap", v8::Boolean::new(scope, remap).into()),
+      ("silent", v8::Boolean::new(scope, silent).into()),
+      ("expr", v8::Boolean::new(scope, expr).into()),
+      (
+        "buffer",
+        match buffer {
+          Some(buffer) => v8::Integer::new(scope, buffer).into(),
+          None => v8::null(scope).into(),
+        },
+      ),
+      ("desc", v8::String::new(scope, &desc).unwrap().into()),
+      ("builtin", v8::Boolean::new(scope, builtin).into()),
+    ];
+    for (key, value) in values {
+      let key = v8::String::new(scope, key).unwrap();
+      object.set(scope, key.into(), value);
+    }
+    array.set_index(scope, i as u32, object.into());
+  }
+  rv.set(array.into());
+}
+
+/// Get the leader key, i.e. `<leader>` in the mappings.
+pub fn get_leader(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let editing_state = state_rc.borrow().editing_state.clone();
+  let leader = lock!(editing_state).keymaps().leader().to_string();
+  trace!("get_leader: {:?}", leader);
+  rv.set(v8::String::new(scope, &leader).unwrap().into());
+}
+
+/// Set the leader key, it is used by the mappings set after it.
+pub fn set_leader(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let leader = args.get(0).to_rust_string_lossy(scope);
+  trace!("set_leader: {:?}", leader);
+  let state_rc = JsRuntime::state(scope);
+  let editing_state = state_rc.borrow().editing_state.clone();
+  lock!(editing_state).keymaps_mut().set_leader(&leader);
+}
@@ -1,68 +1,129 @@
-//! APIs for `Rsvim.opt` namespace.
+//! APIs for `Rsvim.opt` and `Rsvim.o` namespaces.
 
 use crate::js::JsRuntime;
+use crate::js::binding::global_rsvim::{_from_js_value, _option_def};
+use crate::opt::{
+  OptionDef, OptionTarget, OptionValue, get_option_value, set_option_value,
+};
 use crate::prelude::*;
+use crate::state::autocmd::AutoCmdEvent;
 
 use tracing::trace;
 
-/// Get the _wrap_ option.
-/// See: <https://vimhelp.org/options.txt.html#%27wrap%27>
-/// Also known as _line-wrap_, see: <https://en.wikipedia.org/wiki/Line_wrap_and_word_wrap>.
-pub fn get_wrap(
-  scope: &mut v8::HandleScope,
-  _args: v8::FunctionCallbackArguments,
-  mut rv: v8::ReturnValue,
-) {
+/// Convert the option value to js value.
+pub fn to_js_value<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  value: &OptionValue,
+) -> v8::Local<'s, v8::Value> {
+  match value {
+    OptionValue::Boolean(b) => v8::Boolean::new(scope, *b).into(),
+    OptionValue::Number(n) => v8::Number::new(scope, *n as f64).into(),
+    OptionValue::String(s) => v8::String::new(scope, s).unwrap().into(),
+  }
+}
+
+/// Fire the `OptionSet` event of the option.
+pub fn fire_option_set(scope: &mut v8::HandleScope, def: &OptionDef) {
   let state_rc = JsRuntime::state(scope);
-  let tree = state_rc.borrow().tree.clone();
-  let tree = lock!(tree);
-  let value = tree.global_local_options().wrap();
-  trace!("get_wrap: {:?}", value);
-  rv.set_bool(value);
+  let editing_state = state_rc.borrow().editing_state.clone();
+  lock!(editing_state).autocmds_mut().fire(
+    AutoCmdEvent::OptionSet,
+    None,
+    def.name(),
+  );
 }
 
-/// Set the _wrap_ option.
-pub fn set_wrap(
+// Parse the `target` argument, empty string is `:set`, "global" is `:setglobal`. Returns the
+// target and the api name.
+fn _target(
   scope: &mut v8::HandleScope,
-  args: v8::FunctionCallbackArguments,
-  _: v8::ReturnValue,
-) {
-  assert!(args.length() == 1);
-  let value = args.get(0).to_boolean(scope).boolean_value(scope);
-  trace!("set_wrap: {:?}", value);
-  let state_rc = JsRuntime::state(scope);
-  let tree = state_rc.borrow().tree.clone();
-  let mut tree = lock!(tree);
-  tree.global_local_options_mut().set_wrap(value);
+  value: v8::Local<v8::Value>,
+) -> (OptionTarget, &'static str) {
+  match value.to_rust_string_lossy(scope).as_str() {
+    "global" => (OptionTarget::Global, "Rsvim.o"),
+    _ => (OptionTarget::LocalAndGlobal, "Rsvim.opt"),
+  }
 }
 
-/// Get the _line-break_ option.
-/// See: <https://vimhelp.org/options.txt.html#%27linebreak%27>
-/// Also known as _word-wrap_, see: <https://en.wikipedia.org/wiki/Line_wrap_and_word_wrap>.
-pub fn get_line_break(
+/// Get the option value. The arguments are `(name, target)`, the `target` is `"global"` for
+/// `Rsvim.o`, or empty for `Rsvim.opt`.
+pub fn get(
   scope: &mut v8::HandleScope,
-  _args: v8::FunctionCallbackArguments,
+  args: v8::FunctionCallbackArguments,
   mut rv: v8::ReturnValue,
 ) {
+  assert!(args.length() == 2);
+  let name = args.get(0).to_rust_string_lossy(scope);
+  let (target, api) = _target(scope, args.get(1));
+  trace!("opt_get: {:?} {:?}", name, target);
+  let Ok(def) = _option_def(scope, api, &name, None) else {
+    return;
+  };
   let state_rc = JsRuntime::state(scope);
-  let tree = state_rc.borrow().tree.clone();
-  let tree = lock!(tree);
-  let value = tree.global_local_options().line_break();
-  trace!("get_line_break: {:?}", value);
-  rv.set_bool(value);
+  let (tree, buffers, editing_state) = {
+    let state = state_rc.borrow();
+    (
+      state.tree.clone(),
+      state.buffers.clone(),
+      state.editing_state.clone(),
+    )
+  };
+  let value = get_option_value(
+    &lock!(tree),
+    &lock!(buffers),
+    lock!(editing_state).global_options(),
+    def,
+    target,
+  );
+  rv.set(to_js_value(scope, &value));
 }
 
-/// Set the _line-break_ option.
-pub fn set_line_break(
+/// Set the option value. The arguments are `(name, target, value)`, the `target` is `"global"`
+/// for `Rsvim.o`, or empty for `Rsvim.opt`.
+pub fn set(
   scope: &mut v8::HandleScope,
   args: v8::FunctionCallbackArguments,
   _: v8::ReturnValue,
 ) {
-  assert!(args.length() == 1);
-  let value = args.get(0).to_boolean(scope).boolean_value(scope);
-  trace!("set_line_break: {:?}", value);
+  assert!(args.length() == 3);
+  let name = args.get(0).to_rust_string_lossy(scope);
+  let (target, api) = _target(scope, args.get(1));
+  trace!("opt_set: {:?} {:?}", name, target);
+  let Ok(def) = _option_def(scope, api, &name, None) else {
+    return;
+  };
+  let Ok(value) = _from_js_value(scope, api, def, args.get(2)) else {
+    return;
+  };
   let state_rc = JsRuntime::state(scope);
-  let tree = state_rc.borrow().tree.clone();
-  let mut tree = lock!(tree);
-  tree.global_local_options_mut().set_line_break(value);
+  let (tree, buffers, editing_state) = {
+    let state = state_rc.borrow();
+    (
+      state.tree.clone(),
+      state.buffers.clone(),
+      state.editing_state.clone(),
+    )
+  };
+  let changed = {
+    let mut tree = lock!(tree);
+    let mut buffers = lock!(buffers);
+    let mut editing_state = lock!(editing_state);
+    // The `Rsvim.opt` writes both the local and global values, either of them can be changed.
+    let old = [target, OptionTarget::Global].map(|target| {
+      let global_options = editing_state.global_options();
+      get_option_value(&tree, &buffers, global_options, def, target)
+    });
+    set_option_value(
+      &mut tree,
+      &mut buffers,
+      editing_state.global_options_mut(),
+      def,
+      target,
+      &value,
+    );
+    old.iter().any(|old| *old != value)
+  };
+  if changed {
+    fire_option_set(scope, def);
+  }
 }
@@ -0,0 +1,437 @@
+//! APIs for `Rsvim.win` namespace.
+
+use crate::buf::BufferArc;
+use crate::js::JsRuntime;
+use crate::js::binding::global_rsvim::opt::{fire_option_set, to_js_value};
+use crate::js::binding::global_rsvim::{
+  _buffer_id, _from_js_value, _option_def, include_eol,
+};
+use crate::js::binding::{throw_range_error, throw_type_error};
+use crate::opt::{OptionAccessor, OptionScope, set_window_option_value};
+use crate::prelude::*;
+use crate::state::fsm::mouse::focus_window;
+use crate::state::ops::{Operation, cursor_ops};
+use crate::ui::tree::{Inodeable, Tree, TreeNodeId};
+
+use std::sync::Arc;
+use tracing::trace;
+
+// Get the window ID by the `id` argument of the `api`, `0` is current window. It throws if the
+// window is not found.
+fn _window_id(
+  scope: &mut v8::HandleScope,
+  api: &str,
+  id: v8::Local<v8::Value>,
+) -> Result<TreeNodeId, ()> {
+  let id = id.int32_value(scope).unwrap_or(-1);
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let window_id = {
+    let tree = lock!(tree);
+    if id == 0 {
+      tree.current_window_id()
+    } else {
+      tree.window_ids().get(&id).copied()
+    }
+  };
+  match window_id {
+    Some(window_id) => Ok(window_id),
+    None => {
+      throw_type_error(scope, &format!("\"{api}\" window {id} is not found"));
+      Err(())
+    }
+  }
+}
+
+// Get the buffer shown in the window.
+fn _window_buffer(tree: &Tree, window_id: TreeNodeId) -> BufferArc {
+  tree.window(window_id).unwrap().buffer().upgrade().unwrap()
+}
+
+// Check the line is in the buffer text. It throws if it is out of range.
+fn _line(
+  scope: &mut v8::HandleScope,
+  api: &str,
+  line_idx: usize,
+  lines_count: usize,
+) -> Result<(), ()> {
+  if line_idx < lines_count {
+    Ok(())
+  } else {
+    throw_range_error(
+      scope,
+      &format!(
+        "\"{api}\" line {line_idx} is out of range, the buffer has {lines_count} lines"
+      ),
+    );
+    Err(())
+  }
+}
+
+// Make a js array of the pair of integers.
+fn _pair_array<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  (a, b): (usize, usize),
+) -> v8::Local<'s, v8::Array> {
+  let array = v8::Array::new(scope, 2);
+  let a = v8::Integer::new(scope, a as i32);
+  let b = v8::Integer::new(scope, b as i32);
+  array.set_index(scope, 0, a.into());
+  array.set_index(scope, 1, b.into());
+  array
+}
+
+// Make a js object of the integer fields.
+fn _object<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  fields: &[(&str, isize)],
+) -> v8::Local<'s, v8::Object> {
+  let object = v8::Object::new(scope);
+  for (key, value) in fields {
+    let key = v8::String::new(scope, key).unwrap();
+    let value = v8::Integer::new(scope, *value as i32);
+    object.set(scope, key.into(), value.into());
+  }
+  object
+}
+
+/// Get current window ID, i.e. the window that has the cursor.
+pub fn current(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let window_id = lock!(tree).current_window_id();
+  match window_id {
+    Some(window_id) => rv.set(v8::Integer::new(scope, window_id).into()),
+    None => rv.set(v8::null(scope).into()),
+  }
+}
+
+/// List all the window IDs.
+pub fn list(
+  scope: &mut v8::HandleScope,
+  _args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let ids: Vec<TreeNodeId> = lock!(tree).window_ids().iter().copied().collect();
+  trace!("win_list: {:?}", ids);
+  let array = v8::Array::new(scope, ids.len() as i32);
+  for (i, id) in ids.into_iter().enumerate() {
+    let id = v8::Integer::new(scope, id);
+    array.set_index(scope, i as u32, id.into());
+  }
+  rv.set(array.into());
+}
+
+/// Get the cursor position of the window. Returns the position `[line, char]`.
+pub fn get_cursor(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let Ok(window_id) = _window_id(scope, "Rsvim.win.getCursor", args.get(0))
+  else {
+    return;
+  };
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let cursor_viewport =
+    lock!(tree).window(window_id).unwrap().cursor_viewport();
+  let position = (cursor_viewport.line_idx(), cursor_viewport.char_idx());
+  trace!("win_get_cursor: {:?} {:?}", window_id, position);
+  rv.set(_pair_array(scope, position).into());
+}
+
+/// Move the cursor of the window. The arguments are `(id, line, char)`, the char is clamped into
+/// the line. The window scrolls to keep the cursor visible.
+pub fn set_cursor(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 3);
+  let api = "Rsvim.win.setCursor";
+  let Ok(window_id) = _window_id(scope, api, args.get(0)) else {
+    return;
+  };
+  let line_idx = args.get(1).int32_value(scope).unwrap_or(0).max(0) as usize;
+  let char_idx = args.get(2).int32_value(scope).unwrap_or(0).max(0) as usize;
+  trace!("win_set_cursor: {:?} {:?}", window_id, (line_idx, char_idx));
+  let include_eol = include_eol(scope);
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let mut tree = lock!(tree);
+  let buffer = _window_buffer(&tree, window_id);
+  let buffer = lock!(buffer);
+  let text = buffer.text();
+  if _line(scope, api, line_idx, text.lines_count()).is_err() {
+    return;
+  }
+  let include_eol = include_eol && tree.current_window_id() == Some(window_id);
+  cursor_ops::cursor_move(
+    &mut tree,
+    window_id,
+    text,
+    Operation::CursorMoveTo((char_idx, line_idx)),
+    include_eol,
+  );
+}
+
+/// Get the scroll position of the window, i.e. the first line and the first column shown in the
+/// window. Returns the position `[line, column]`.
+pub fn get_scroll(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let Ok(window_id) = _window_id(scope, "Rsvim.win.getScroll", args.get(0))
+  else {
+    return;
+  };
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let viewport = lock!(tree).window(window_id).unwrap().viewport();
+  let position = (viewport.start_line_idx(), viewport.start_column_idx());
+  trace!("win_get_scroll: {:?} {:?}", window_id, position);
+  rv.set(_pair_array(scope, position).into());
+}
+
+/// Scroll the window. The arguments are `(id, line, column)`, the line is shown at the top of
+/// window and the column is shown at the left. The cursor moves into the window if it is not shown.
+pub fn set_scroll(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 3);
+  let api = "Rsvim.win.setScroll";
+  let Ok(window_id) = _window_id(scope, api, args.get(0)) else {
+    return;
+  };
+  let line_idx = args.get(1).int32_value(scope).unwrap_or(0).max(0) as usize;
+  let column_idx = args.get(2).int32_value(scope).unwrap_or(0).max(0) as usize;
+  trace!(
+    "win_set_scroll: {:?} {:?}",
+    window_id,
+    (line_idx, column_idx)
+  );
+  let include_eol = include_eol(scope);
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let mut tree = lock!(tree);
+  let buffer = _window_buffer(&tree, window_id);
+  let buffer = lock!(buffer);
+  let text = buffer.text();
+  if _line(scope, api, line_idx, text.lines_count()).is_err() {
+    return;
+  }
+  let include_eol = include_eol && tree.current_window_id() == Some(window_id);
+  cursor_ops::window_scroll(
+    &mut tree,
+    window_id,
+    text,
+    Operation::WindowScrollTo((column_idx, line_idx)),
+    include_eol,
+  );
+}
+
+/// Get the size of the window, i.e. the text area. Returns `{ width, height }`.
+pub fn get_size(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let Ok(window_id) = _window_id(scope, "Rsvim.win.getSize", args.get(0))
+  else {
+    return;
+  };
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let shape = *lock!(tree)
+    .window(window_id)
+    .unwrap()
+    .content()
+    .actual_shape();
+  trace!("win_get_size: {:?} {:?}", window_id, shape);
+  let fields = [
+    ("width", shape.width() as isize),
+    ("height", shape.height() as isize),
+  ];
+  rv.set(_object(scope, &fields).into());
+}
+
+/// Get the position of the window on the terminal, i.e. the top-left corner. Returns `{ row,
+/// column }`, both are 0-based.
+pub fn get_position(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let Ok(window_id) = _window_id(scope, "Rsvim.win.getPosition", args.get(0))
+  else {
+    return;
+  };
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let shape = *lock!(tree).window(window_id).unwrap().actual_shape();
+  trace!("win_get_position: {:?} {:?}", window_id, shape);
+  let fields = [
+    ("row", shape.min().y as isize),
+    ("column", shape.min().x as isize),
+  ];
+  rv.set(_object(scope, &fields).into());
+}
+
+/// Get the window-local option of the window. The arguments are `(id, name)`.
+pub fn get_option(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let api = "Rsvim.win.getOption";
+  let Ok(window_id) = _window_id(scope, api, args.get(0)) else {
+    return;
+  };
+  let name = args.get(1).to_rust_string_lossy(scope);
+  trace!("win_get_option: {:?} {:?}", window_id, name);
+  let Ok(def) = _option_def(scope, api, &name, Some(OptionScope::Window))
+  else {
+    return;
+  };
+  let OptionAccessor::Window { get, .. } = def.accessor() else {
+    unreachable!()
+  };
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let value = get(lock!(tree).window(window_id).unwrap().options());
+  rv.set(to_js_value(scope, &value));
+}
+
+/// Set the window-local option of the window. The arguments are `(id, name, value)`.
+pub fn set_option(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 3);
+  let api = "Rsvim.win.setOption";
+  let Ok(window_id) = _window_id(scope, api, args.get(0)) else {
+    return;
+  };
+  let name = args.get(1).to_rust_string_lossy(scope);
+  trace!("win_set_option: {:?} {:?}", window_id, name);
+  let Ok(def) = _option_def(scope, api, &name, Some(OptionScope::Window))
+  else {
+    return;
+  };
+  let Ok(value) = _from_js_value(scope, api, def, args.get(2)) else {
+    return;
+  };
+  let OptionAccessor::Window { get, .. } = def.accessor() else {
+    unreachable!()
+  };
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let changed = {
+    let mut tree = lock!(tree);
+    let old = get(tree.window(window_id).unwrap().options());
+    set_window_option_value(&mut tree, window_id, def, &value);
+    old != value
+  };
+  if changed {
+    fire_option_set(scope, def);
+  }
+}
+
+/// Get the buffer ID shown in the window.
+pub fn get_buffer(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  mut rv: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let Ok(window_id) = _window_id(scope, "Rsvim.win.getBuffer", args.get(0))
+  else {
+    return;
+  };
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  let buffer = _window_buffer(&lock!(tree), window_id);
+  let buffer_id = lock!(buffer).id();
+  rv.set(v8::Integer::new(scope, buffer_id).into());
+}
+
+/// Show the buffer in the window. The arguments are `(id, buffer)`, the cursor moves to the start
+/// of the buffer.
+pub fn set_buffer(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 2);
+  let api = "Rsvim.win.setBuffer";
+  let Ok(window_id) = _window_id(scope, api, args.get(0)) else {
+    return;
+  };
+  let buffer = args.get(1).int32_value(scope).unwrap_or(-1);
+  let Ok(Some(buffer_id)) = _buffer_id(scope, api, buffer.max(0)) else {
+    return;
+  };
+  trace!("win_set_buffer: {:?} {:?}", window_id, buffer_id);
+
+  let state_rc = JsRuntime::state(scope);
+  let (tree, buffers) = {
+    let state = state_rc.borrow();
+    (state.tree.clone(), state.buffers.clone())
+  };
+  let buffer = lock!(buffers).get(&buffer_id).unwrap().clone();
+  let mut tree = lock!(tree);
+  if tree
+    .window(window_id)
+    .unwrap()
+    .buffer()
+    .upgrade()
+    .is_some_and(|b| Arc::ptr_eq(&b, &buffer))
+  {
+    return;
+  }
+  tree.set_window_buffer(
+    window_id,
+    Arc::downgrade(&buffer),
+    lock!(buffer).text(),
+  );
+  let window = tree.window_mut(window_id).unwrap();
+  if window.cursor_id().is_some() {
+    let cursor_viewport = window.cursor_viewport();
+    window.move_cursor_to(
+      cursor_viewport.column_idx() as isize,
+      cursor_viewport.row_idx() as isize,
+    );
+  }
+}
+
+/// Focus the window, i.e. move the cursor into the window, it becomes the current window.
+pub fn focus(
+  scope: &mut v8::HandleScope,
+  args: v8::FunctionCallbackArguments,
+  _: v8::ReturnValue,
+) {
+  assert!(args.length() == 1);
+  let Ok(window_id) = _window_id(scope, "Rsvim.win.focus", args.get(0)) else {
+    return;
+  };
+  trace!("win_focus: {:?}", window_id);
+  let state_rc = JsRuntime::state(scope);
+  let tree = state_rc.borrow().tree.clone();
+  focus_window(&mut lock!(tree), window_id);
+}
@@ -5,7 +5,10 @@ use std::time::Duration;
 
 use compact_str::CompactString;
 
+use crate::buf::BufferId;
+use crate::excommand::ExCommandArgs;
 use crate::js::JsFutureId;
+use crate::state::autocmd::AutoCmdId;
 
 // The message JsRuntime send to EventLoop {
 
@@ -27,8 +30,22 @@ pub enum EventLoopToJsRuntimeMessage {
   /// Event loop notify Js runtime to shutdown this thread.
   TimeoutResp(TimeoutResp),
 
-  /// Event loop send EX command to js runtime to run.
+  /// Event loop ask js runtime to call the callback of a user EX command created by
+  /// `Rsvim.cmd.create`.
   ExCommandReq(ExCommandReq),
+
+  /// Event loop ask js runtime to complete the arguments of an EX command.
+  CompletionReq(CompletionReq),
+
+  /// Event loop ask js runtime to repeat the action registered by `Rsvim.cmd.setRepeat`.
+  RepeatReq(RepeatReq),
+
+  /// Event loop ask js runtime to call the callback of a mapping set by `Rsvim.keymap.set`.
+  KeymapReq(KeymapReq),
+
+  /// Event loop ask js runtime to call the callback of an autocommand set by
+  /// `Rsvim.autocmd.create`.
+  AutoCmdReq(AutoCmdReq),
 }
 
 // The message JsRuntime receive from EventLoop }
@@ -66,11 +83,106 @@ impl TimeoutReq {
 #[derive(Debug)]
 pub struct ExCommandReq {
   pub future_id: JsFutureId,
-  pub source: CompactString,
+  /// The ID of the callback.
+  pub callback_id: i32,
+  /// The parsed arguments of the command-line.
+  pub args: ExCommandArgs,
 }
 
 impl ExCommandReq {
-  pub fn new(future_id: JsFutureId, source: CompactString) -> Self {
-    ExCommandReq { future_id, source }
+  pub fn new(
+    future_id: JsFutureId,
+    callback_id: i32,
+    args: ExCommandArgs,
+  ) -> Self {
+    ExCommandReq {
+      future_id,
+      callback_id,
+      args,
+    }
+  }
+}
+
+#[derive(Debug)]
+pub struct CompletionReq {
+  pub future_id: JsFutureId,
+  /// The ex command name.
+  pub name: CompactString,
+  /// The leading portion of the argument being completed.
+  pub arg_lead: CompactString,
+  /// The whole command-line content.
+  pub cmdline: CompactString,
+  /// The cursor char index in command-line.
+  pub cursor_pos: usize,
+}
+
+impl CompletionReq {
+  pub fn new(
+    future_id: JsFutureId,
+    name: CompactString,
+    arg_lead: CompactString,
+    cmdline: CompactString,
+    cursor_pos: usize,
+  ) -> Self {
+    CompletionReq {
+      future_id,
+      name,
+      arg_lead,
+      cmdline,
+      cursor_pos,
+    }
   }
 }
+
+#[derive(Debug)]
+pub struct RepeatReq {
+  pub future_id: JsFutureId,
+  /// The count given to `.`.
+  pub count: Option<usize>,
+}
+
+impl RepeatReq {
+  pub fn new(future_id: JsFutureId, count: Option<usize>) -> Self {
+    RepeatReq { future_id, count }
+  }
+}
+
+#[derive(Debug)]
+pub struct KeymapReq {
+  pub future_id: JsFutureId,
+  /// The ID of the callback.
+  pub callback_id: i32,
+  /// The callback returns the keys to feed, i.e. the `<expr>` mapping.
+  pub expr: bool,
+}
+
+impl KeymapReq {
+  pub fn new(future_id: JsFutureId, callback_id: i32, expr: bool) -> Self {
+    KeymapReq {
+      future_id,
+      callback_id,
+      expr,
+    }
+  }
+}
+
+#[derive(Debug)]
+pub struct AutoCmdReq {
+  pub future_id: JsFutureId,
+  /// The ID of the autocommand, it is also the ID of the callback.
+  pub autocmd_id: AutoCmdId,
+  /// The event name.
+  pub event: CompactString,
+  /// The group of the autocommand.
+  pub group: Option<CompactString>,
+  /// The buffer of the event.
+  pub buffer_id: Option<BufferId>,
+  /// The file name of the buffer.
+  pub file: CompactString,
+  /// The string matched by the patterns.
+  pub matched: CompactString,
+  /// The event data, i.e. `n:i` for `ModeChanged`.
+  pub data: CompactString,
+  /// The autocommand runs once, the callback is removed after it.
+  pub once: bool,
+}
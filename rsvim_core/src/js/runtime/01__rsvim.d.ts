@@ -1,9 +1,192 @@
 export declare class Rsvim {
-    readonly opt: RsvimOpt;
+    readonly autocmd: RsvimAutocmd;
+    readonly buf: RsvimBuf;
+    readonly cmd: RsvimCmd;
+    readonly keymap: RsvimKeymap;
+    readonly opt: RsvimOptions;
+    readonly o: RsvimOptions;
+    readonly bo: RsvimBufOptions;
+    readonly win: RsvimWin;
+    readonly wo: RsvimWinOptions;
 }
-export declare class RsvimOpt {
-    get wrap(): boolean;
-    set wrap(value: boolean);
-    get lineBreak(): boolean;
-    set lineBreak(value: boolean);
+export interface RsvimAutocmdEventInfo {
+    id: number;
+    event: string;
+    group: string | null;
+    buffer: number | null;
+    file: string;
+    match: string;
+    data: string;
+}
+export type RsvimAutocmdCallback = (info: RsvimAutocmdEventInfo) => void;
+export interface RsvimAutocmdOptions {
+    pattern?: string | string[];
+    filetype?: string | string[];
+    buffer?: boolean | number;
+    group?: string;
+    once?: boolean;
+    desc?: string;
+}
+export interface RsvimAutocmdInfo {
+    id: number;
+    events: string[];
+    patterns: string[];
+    filetypes: string[];
+    buffer: number | null;
+    group: string | null;
+    once: boolean;
+    desc: string;
+}
+export declare class RsvimAutocmd {
+    create(event: string | string[], callback: RsvimAutocmdCallback, opts?: RsvimAutocmdOptions): number;
+    del(id: number): void;
+    createGroup(name: string, opts?: {
+        clear?: boolean;
+    }): string;
+    delGroup(name: string): void;
+    clear(opts?: {
+        event?: string;
+        group?: string;
+    }): void;
+    list(opts?: {
+        event?: string;
+        group?: string;
+    }): RsvimAutocmdInfo[];
+}
+export type RsvimBufPosition = [number, number];
+export declare class RsvimBuf {
+    current(): number;
+    list(): number[];
+    create(filename?: string): number;
+    name(id?: number): string;
+    filetype(id?: number): string;
+    changedtick(id?: number): number;
+    modified(id?: number): boolean;
+    lineCount(id?: number): number;
+    getLines(id: number, start: number, end?: number): string[];
+    setLines(id: number, start: number, end: number, lines: string[]): void;
+    getText(id: number, startLine: number, startChar: number, endLine: number, endChar: number): string;
+    setText(id: number, startLine: number, startChar: number, endLine: number, endChar: number, text: string): RsvimBufPosition;
+    insert(id: number, line: number, char: number, text: string): RsvimBufPosition;
+    delete(id: number, line: number, char: number, n: number): RsvimBufPosition;
+    getOption(id: number, name: string): boolean | number | string;
+    setOption(id: number, name: string, value: boolean | number | string): void;
+    select(startLine: number, startChar: number, endLine: number, endChar: number): void;
+}
+export interface RsvimCmdArgs {
+    name: string;
+    args: string;
+    fargs: string[];
+    /** The 1-based `[start, end]` line numbers of the range, both are inclusive, i.e. `:3,5Foo` is `[3, 5]`. It is `null` if the command doesn't accept a range. */
+    range: [number, number] | null;
+    bang: boolean;
+    count: number | null;
+}
+export type RsvimCmdCallback = (args: RsvimCmdArgs) => unknown;
+export interface RsvimCmdOptions {
+    nargs?: "0" | "1" | "*" | "?" | "+" | 0 | 1;
+    range?: boolean | "%" | number;
+    bang?: boolean;
+    complete?: string | RsvimCmdCompleter;
+    desc?: string;
+}
+export interface RsvimCmdInfo {
+    name: string;
+    nargs: string;
+    range: boolean | "%" | number;
+    bang: boolean;
+    complete: string | null;
+    desc: string;
+}
+export type RsvimCmdCompleter = (argLead: string, cmdLine: string, cursorPos: number) => string[];
+export type RsvimCmdRepeatAction = (count: number | undefined) => void;
+export declare class RsvimCmd {
+    create(name: string, callback: RsvimCmdCallback, opts?: RsvimCmdOptions): void;
+    del(name: string): void;
+    list(): RsvimCmdInfo[];
+    setCompleter(name: string, completer: RsvimCmdCompleter | null): void;
+    setRepeat(action: RsvimCmdRepeatAction): void;
+}
+export type RsvimKeymapCallback = () => string | void;
+export interface RsvimKeymapOptions {
+    remap?: boolean;
+    silent?: boolean;
+    expr?: boolean;
+    buffer?: boolean | number;
+    desc?: string;
+}
+export interface RsvimKeymapInfo {
+    mode: string;
+    lhs: string;
+    rhs: string;
+    remap: boolean;
+    silent: boolean;
+    expr: boolean;
+    buffer: number | null;
+    desc: string;
+    builtin: boolean;
+}
+export declare class RsvimKeymap {
+    get leader(): string;
+    set leader(value: string);
+    set(modes: string, lhs: string, rhs: string | RsvimKeymapCallback, opts?: RsvimKeymapOptions): void;
+    del(modes: string, lhs: string, opts?: {
+        buffer?: boolean | number;
+    }): void;
+    list(modes?: string): RsvimKeymapInfo[];
+}
+export interface RsvimBufferOptions {
+    tabStop: number;
+    fileEncoding: string;
+    fileFormat: string;
+    isKeyword: string;
+}
+export interface RsvimWindowOptions {
+    wrap: boolean;
+    lineBreak: boolean;
+    scrollOff: number;
+}
+export interface RsvimGlobalOptions {
+    history: number;
+    msgHistory: number;
+    wildMode: string;
+    wildIgnore: string;
+    wildOptions: string;
+    timeout: boolean;
+    timeoutLen: number;
+    showCmd: boolean;
+    updateTime: number;
+    keyModel: string;
+    selectMode: string;
+    mouse: string;
+}
+export type RsvimOptions = RsvimBufferOptions & RsvimWindowOptions & RsvimGlobalOptions;
+export type RsvimBufOptions = RsvimBufferOptions & {
+    [id: number]: RsvimBufferOptions;
+};
+export type RsvimWinOptions = RsvimWindowOptions & {
+    [id: number]: RsvimWindowOptions;
+};
+export interface RsvimWinSize {
+    width: number;
+    height: number;
+}
+export interface RsvimWinPosition {
+    row: number;
+    column: number;
+}
+export declare class RsvimWin {
+    current(): number | null;
+    list(): number[];
+    getCursor(id?: number): RsvimBufPosition;
+    setCursor(id: number, line: number, char: number): void;
+    getScroll(id?: number): [number, number];
+    setScroll(id: number, line: number, column: number): void;
+    getSize(id?: number): RsvimWinSize;
+    getPosition(id?: number): RsvimWinPosition;
+    getOption(id: number, name: string): boolean | number | string;
+    setOption(id: number, name: string, value: boolean | number | string): void;
+    getBuffer(id?: number): number;
+    setBuffer(id: number, buffer: number): void;
+    focus(id: number): void;
 }
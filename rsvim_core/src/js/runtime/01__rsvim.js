@@ -1,24 +1,394 @@
 export class Rsvim {
-    opt = new RsvimOpt();
+    autocmd = new RsvimAutocmd();
+    buf = new RsvimBuf();
+    cmd = new RsvimCmd();
+    keymap = new RsvimKeymap();
+    opt = options("");
+    o = options("global");
+    bo = bufOptions(0);
+    win = new RsvimWin();
+    wo = winOptions(0);
 }
-export class RsvimOpt {
-    get wrap() {
-        return __InternalRsvimGlobalObject.opt_get_wrap();
+function bufferArg(api, buffer) {
+    if (buffer === undefined || buffer === false) {
+        return -1;
     }
-    set wrap(value) {
-        if (typeof value !== "boolean") {
-            throw new Error(`"Rsvim.opt.wrap" must be a boolean value, but found ${value} (${typeof value})`);
+    if (buffer === true) {
+        return 0;
+    }
+    if (!Number.isInteger(buffer) || buffer <= 0) {
+        throw new Error(`"${api}" buffer must be a boolean value or a positive integer, but found ${buffer} (${typeof buffer})`);
+    }
+    return buffer;
+}
+function stringsArg(api, name, value) {
+    if (value === undefined) {
+        return [];
+    }
+    const values = Array.isArray(value) ? value : [value];
+    for (const v of values) {
+        if (typeof v !== "string" || v.length === 0) {
+            throw new Error(`"${api}" ${name} must be a non-empty string or an array of them, but found ${v} (${typeof v})`);
+        }
+    }
+    return values;
+}
+export class RsvimAutocmd {
+    create(event, callback, opts) {
+        const events = stringsArg("Rsvim.autocmd.create", "event", event);
+        if (events.length === 0) {
+            throw new Error(`"Rsvim.autocmd.create" event must not be empty`);
+        }
+        if (typeof callback !== "function") {
+            throw new Error(`"Rsvim.autocmd.create" callback must be a function, but found ${callback} (${typeof callback})`);
+        }
+        const o = opts ?? {};
+        if (typeof o !== "object") {
+            throw new Error(`"Rsvim.autocmd.create" opts must be an object, but found ${o} (${typeof o})`);
+        }
+        const patterns = stringsArg("Rsvim.autocmd.create", "pattern", o.pattern);
+        const filetypes = stringsArg("Rsvim.autocmd.create", "filetype", o.filetype);
+        const buffer = bufferArg("Rsvim.autocmd.create", o.buffer);
+        if (o.group !== undefined && typeof o.group !== "string") {
+            throw new Error(`"Rsvim.autocmd.create" group must be a string, but found ${o.group} (${typeof o.group})`);
+        }
+        return __InternalRsvimGlobalObject.autocmd_create(events, callback, patterns, filetypes, buffer, o.group ?? "", !!o.once, o.desc === undefined ? "" : String(o.desc));
+    }
+    del(id) {
+        if (!Number.isInteger(id)) {
+            throw new Error(`"Rsvim.autocmd.del" id must be an integer, but found ${id} (${typeof id})`);
+        }
+        __InternalRsvimGlobalObject.autocmd_del(id);
+    }
+    createGroup(name, opts) {
+        if (typeof name !== "string" || name.length === 0) {
+            throw new Error(`"Rsvim.autocmd.createGroup" name must be a non-empty string, but found ${name} (${typeof name})`);
+        }
+        const clear = (opts ?? {}).clear ?? true;
+        __InternalRsvimGlobalObject.autocmd_create_group(name, !!clear);
+        return name;
+    }
+    delGroup(name) {
+        if (typeof name !== "string") {
+            throw new Error(`"Rsvim.autocmd.delGroup" name must be a string, but found ${name} (${typeof name})`);
+        }
+        __InternalRsvimGlobalObject.autocmd_del_group(name);
+    }
+    clear(opts) {
+        const o = opts ?? {};
+        __InternalRsvimGlobalObject.autocmd_clear(o.event === undefined ? "" : String(o.event), o.group === undefined ? "" : String(o.group));
+    }
+    list(opts) {
+        const o = opts ?? {};
+        return __InternalRsvimGlobalObject.autocmd_list(o.event === undefined ? "" : String(o.event), o.group === undefined ? "" : String(o.group));
+    }
+}
+function bufIdArg(api, id) {
+    if (id === undefined) {
+        return 0;
+    }
+    if (!Number.isInteger(id) || id < 0) {
+        throw new Error(`"${api}" id must be a non-negative integer, but found ${id} (${typeof id})`);
+    }
+    return id;
+}
+function positionsArg(api, positions) {
+    for (const p of positions) {
+        if (!Number.isInteger(p) || p < 0) {
+            throw new Error(`"${api}" position must be a non-negative integer, but found ${p} (${typeof p})`);
+        }
+    }
+}
+function endLineArg(api, end) {
+    if (end === undefined) {
+        return -1;
+    }
+    if (!Number.isInteger(end)) {
+        throw new Error(`"${api}" end must be an integer, but found ${end} (${typeof end})`);
+    }
+    return end < 0 ? -1 : end;
+}
+export class RsvimBuf {
+    current() {
+        return __InternalRsvimGlobalObject.buf_current();
+    }
+    list() {
+        return __InternalRsvimGlobalObject.buf_list();
+    }
+    create(filename) {
+        const f = filename ?? "";
+        if (typeof f !== "string") {
+            throw new Error(`"Rsvim.buf.create" filename must be a string, but found ${f} (${typeof f})`);
+        }
+        return __InternalRsvimGlobalObject.buf_create(f);
+    }
+    name(id) {
+        return __InternalRsvimGlobalObject.buf_info(bufIdArg("Rsvim.buf.name", id), "name");
+    }
+    filetype(id) {
+        return __InternalRsvimGlobalObject.buf_info(bufIdArg("Rsvim.buf.filetype", id), "filetype");
+    }
+    changedtick(id) {
+        return __InternalRsvimGlobalObject.buf_info(bufIdArg("Rsvim.buf.changedtick", id), "changedtick");
+    }
+    modified(id) {
+        return __InternalRsvimGlobalObject.buf_info(bufIdArg("Rsvim.buf.modified", id), "modified");
+    }
+    lineCount(id) {
+        return __InternalRsvimGlobalObject.buf_info(bufIdArg("Rsvim.buf.lineCount", id), "lineCount");
+    }
+    getLines(id, start, end) {
+        positionsArg("Rsvim.buf.getLines", [start]);
+        return __InternalRsvimGlobalObject.buf_get_lines(bufIdArg("Rsvim.buf.getLines", id), start, endLineArg("Rsvim.buf.getLines", end));
+    }
+    setLines(id, start, end, lines) {
+        positionsArg("Rsvim.buf.setLines", [start]);
+        if (!Array.isArray(lines) || lines.some((l) => typeof l !== "string")) {
+            throw new Error(`"Rsvim.buf.setLines" lines must be an array of strings, but found ${lines} (${typeof lines})`);
+        }
+        __InternalRsvimGlobalObject.buf_set_lines(bufIdArg("Rsvim.buf.setLines", id), start, endLineArg("Rsvim.buf.setLines", end), lines);
+    }
+    getText(id, startLine, startChar, endLine, endChar) {
+        positionsArg("Rsvim.buf.getText", [
+            startLine,
+            startChar,
+            endLine,
+            endChar,
+        ]);
+        return __InternalRsvimGlobalObject.buf_get_text(bufIdArg("Rsvim.buf.getText", id), startLine, startChar, endLine, endChar);
+    }
+    setText(id, startLine, startChar, endLine, endChar, text) {
+        positionsArg("Rsvim.buf.setText", [
+            startLine,
+            startChar,
+            endLine,
+            endChar,
+        ]);
+        if (typeof text !== "string") {
+            throw new Error(`"Rsvim.buf.setText" text must be a string, but found ${text} (${typeof text})`);
         }
-        __InternalRsvimGlobalObject.opt_set_wrap(value);
+        return __InternalRsvimGlobalObject.buf_set_text(bufIdArg("Rsvim.buf.setText", id), startLine, startChar, endLine, endChar, text);
     }
-    get lineBreak() {
-        return __InternalRsvimGlobalObject.opt_get_line_break();
+    insert(id, line, char, text) {
+        positionsArg("Rsvim.buf.insert", [line, char]);
+        if (typeof text !== "string") {
+            throw new Error(`"Rsvim.buf.insert" text must be a string, but found ${text} (${typeof text})`);
+        }
+        return __InternalRsvimGlobalObject.buf_insert(bufIdArg("Rsvim.buf.insert", id), line, char, text);
+    }
+    delete(id, line, char, n) {
+        positionsArg("Rsvim.buf.delete", [line, char]);
+        if (!Number.isInteger(n)) {
+            throw new Error(`"Rsvim.buf.delete" n must be an integer, but found ${n} (${typeof n})`);
+        }
+        return __InternalRsvimGlobalObject.buf_delete(bufIdArg("Rsvim.buf.delete", id), line, char, n);
     }
-    set lineBreak(value) {
-        if (typeof value !== "boolean") {
-            throw new Error(`"Rsvim.opt.lineBreak" must be a boolean value, but found ${value} (${typeof value})`);
+    getOption(id, name) {
+        if (typeof name !== "string") {
+            throw new Error(`"Rsvim.buf.getOption" name must be a string, but found ${name} (${typeof name})`);
         }
-        __InternalRsvimGlobalObject.opt_set_line_break(value);
+        return __InternalRsvimGlobalObject.buf_get_option(bufIdArg("Rsvim.buf.getOption", id), name);
+    }
+    setOption(id, name, value) {
+        if (typeof name !== "string") {
+            throw new Error(`"Rsvim.buf.setOption" name must be a string, but found ${name} (${typeof name})`);
+        }
+        __InternalRsvimGlobalObject.buf_set_option(bufIdArg("Rsvim.buf.setOption", id), name, value);
+    }
+    select(startLine, startChar, endLine, endChar) {
+        for (const p of [startLine, startChar, endLine, endChar]) {
+            if (!Number.isInteger(p) || p < 0) {
+                throw new Error(`"Rsvim.buf.select" position must be a non-negative integer, but found ${p} (${typeof p})`);
+            }
+        }
+        __InternalRsvimGlobalObject.buf_select(startLine, startChar, endLine, endChar);
+    }
+}
+export class RsvimCmd {
+    create(name, callback, opts) {
+        if (typeof name !== "string") {
+            throw new Error(`"Rsvim.cmd.create" name must be a string, but found ${name} (${typeof name})`);
+        }
+        if (typeof callback !== "function") {
+            throw new Error(`"Rsvim.cmd.create" callback must be a function, but found ${callback} (${typeof callback})`);
+        }
+        const o = opts ?? {};
+        if (typeof o !== "object") {
+            throw new Error(`"Rsvim.cmd.create" opts must be an object, but found ${o} (${typeof o})`);
+        }
+        const complete = o.complete ?? "";
+        if (typeof complete !== "string" && typeof complete !== "function") {
+            throw new Error(`"Rsvim.cmd.create" complete must be a string or a function, but found ${complete} (${typeof complete})`);
+        }
+        __InternalRsvimGlobalObject.cmd_create(name, callback, String(o.nargs ?? "0"), o.range ?? false, !!o.bang, typeof complete === "function" ? "custom" : complete, o.desc === undefined ? "" : String(o.desc));
+        if (typeof complete === "function") {
+            this.setCompleter(name, complete);
+        }
+    }
+    del(name) {
+        if (typeof name !== "string") {
+            throw new Error(`"Rsvim.cmd.del" name must be a string, but found ${name} (${typeof name})`);
+        }
+        __InternalRsvimGlobalObject.cmd_del(name);
+    }
+    list() {
+        return __InternalRsvimGlobalObject.cmd_list();
+    }
+    setCompleter(name, completer) {
+        if (typeof name !== "string") {
+            throw new Error(`"Rsvim.cmd.setCompleter" name must be a string, but found ${name} (${typeof name})`);
+        }
+        if (completer !== null && typeof completer !== "function") {
+            throw new Error(`"Rsvim.cmd.setCompleter" completer must be a function or null, but found ${completer} (${typeof completer})`);
+        }
+        __InternalRsvimGlobalObject.cmd_set_completer(name, completer);
+    }
+    setRepeat(action) {
+        if (typeof action !== "function") {
+            throw new Error(`"Rsvim.cmd.setRepeat" action must be a function, but found ${action} (${typeof action})`);
+        }
+        __InternalRsvimGlobalObject.cmd_set_repeat(action);
+    }
+}
+export class RsvimKeymap {
+    get leader() {
+        return __InternalRsvimGlobalObject.keymap_get_leader();
+    }
+    set leader(value) {
+        if (typeof value !== "string" || value.length === 0) {
+            throw new Error(`"Rsvim.keymap.leader" must be a non-empty string, but found ${value} (${typeof value})`);
+        }
+        __InternalRsvimGlobalObject.keymap_set_leader(value);
+    }
+    set(modes, lhs, rhs, opts) {
+        if (typeof modes !== "string") {
+            throw new Error(`"Rsvim.keymap.set" modes must be a string, but found ${modes} (${typeof modes})`);
+        }
+        if (typeof lhs !== "string" || lhs.length === 0) {
+            throw new Error(`"Rsvim.keymap.set" lhs must be a non-empty string, but found ${lhs} (${typeof lhs})`);
+        }
+        if (typeof rhs !== "string" && typeof rhs !== "function") {
+            throw new Error(`"Rsvim.keymap.set" rhs must be a string or a function, but found ${rhs} (${typeof rhs})`);
+        }
+        const o = opts ?? {};
+        if (typeof o !== "object") {
+            throw new Error(`"Rsvim.keymap.set" opts must be an object, but found ${o} (${typeof o})`);
+        }
+        const buffer = bufferArg("Rsvim.keymap.set", o.buffer);
+        __InternalRsvimGlobalObject.keymap_set(modes, lhs, rhs, !!o.remap, !!o.silent, !!o.expr, buffer, o.desc === undefined ? "" : String(o.desc));
+    }
+    del(modes, lhs, opts) {
+        if (typeof modes !== "string") {
+            throw new Error(`"Rsvim.keymap.del" modes must be a string, but found ${modes} (${typeof modes})`);
+        }
+        if (typeof lhs !== "string" || lhs.length === 0) {
+            throw new Error(`"Rsvim.keymap.del" lhs must be a non-empty string, but found ${lhs} (${typeof lhs})`);
+        }
+        const buffer = bufferArg("Rsvim.keymap.del", (opts ?? {}).buffer);
+        __InternalRsvimGlobalObject.keymap_del(modes, lhs, buffer);
+    }
+    list(modes) {
+        const m = modes ?? "";
+        if (typeof m !== "string") {
+            throw new Error(`"Rsvim.keymap.list" modes must be a string, but found ${m} (${typeof m})`);
+        }
+        return __InternalRsvimGlobalObject.keymap_list(m);
+    }
+}
+function optionsProxy(get, set, byId) {
+    return new Proxy({}, {
+        get(_target, name) {
+            if (typeof name !== "string") {
+                return undefined;
+            }
+            if (byId !== undefined && /^\d+$/.test(name)) {
+                return byId(Number(name));
+            }
+            return get(name);
+        },
+        set(_target, name, value) {
+            if (typeof name !== "string") {
+                return false;
+            }
+            set(name, value);
+            return true;
+        },
+    });
+}
+function bufOptions(id) {
+    return optionsProxy((name) => __InternalRsvimGlobalObject.buf_get_option(id, name), (name, value) => {
+        __InternalRsvimGlobalObject.buf_set_option(id, name, value);
+    }, id === 0 ? bufOptions : undefined);
+}
+function winOptions(id) {
+    return optionsProxy((name) => __InternalRsvimGlobalObject.win_get_option(id, name), (name, value) => {
+        __InternalRsvimGlobalObject.win_set_option(id, name, value);
+    }, id === 0 ? winOptions : undefined);
+}
+function options(target) {
+    return optionsProxy((name) => __InternalRsvimGlobalObject.opt_get(name, target), (name, value) => {
+        __InternalRsvimGlobalObject.opt_set(name, target, value);
+    });
+}
+function winIdArg(api, id) {
+    if (id === undefined) {
+        return 0;
+    }
+    if (!Number.isInteger(id) || id < 0) {
+        throw new Error(`"${api}" id must be a non-negative integer, but found ${id} (${typeof id})`);
+    }
+    return id;
+}
+export class RsvimWin {
+    current() {
+        return __InternalRsvimGlobalObject.win_current();
+    }
+    list() {
+        return __InternalRsvimGlobalObject.win_list();
+    }
+    getCursor(id) {
+        return __InternalRsvimGlobalObject.win_get_cursor(winIdArg("Rsvim.win.getCursor", id));
+    }
+    setCursor(id, line, char) {
+        positionsArg("Rsvim.win.setCursor", [line, char]);
+        __InternalRsvimGlobalObject.win_set_cursor(winIdArg("Rsvim.win.setCursor", id), line, char);
+    }
+    getScroll(id) {
+        return __InternalRsvimGlobalObject.win_get_scroll(winIdArg("Rsvim.win.getScroll", id));
+    }
+    setScroll(id, line, column) {
+        positionsArg("Rsvim.win.setScroll", [line, column]);
+        __InternalRsvimGlobalObject.win_set_scroll(winIdArg("Rsvim.win.setScroll", id), line, column);
+    }
+    getSize(id) {
+        return __InternalRsvimGlobalObject.win_get_size(winIdArg("Rsvim.win.getSize", id));
+    }
+    getPosition(id) {
+        return __InternalRsvimGlobalObject.win_get_position(winIdArg("Rsvim.win.getPosition", id));
+    }
+    getOption(id, name) {
+        if (typeof name !== "string") {
+            throw new Error(`"Rsvim.win.getOption" name must be a string, but found ${name} (${typeof name})`);
+        }
+        return __InternalRsvimGlobalObject.win_get_option(winIdArg("Rsvim.win.getOption", id), name);
+    }
+    setOption(id, name, value) {
+        if (typeof name !== "string") {
+            throw new Error(`"Rsvim.win.setOption" name must be a string, but found ${name} (${typeof name})`);
+        }
+        __InternalRsvimGlobalObject.win_set_option(winIdArg("Rsvim.win.setOption", id), name, value);
+    }
+    getBuffer(id) {
+        return __InternalRsvimGlobalObject.win_get_buffer(winIdArg("Rsvim.win.getBuffer", id));
+    }
+    setBuffer(id, buffer) {
+        if (!Number.isInteger(buffer) || buffer <= 0) {
+            throw new Error(`"Rsvim.win.setBuffer" buffer must be a positive integer, but found ${buffer} (${typeof buffer})`);
+        }
+        __InternalRsvimGlobalObject.win_set_buffer(winIdArg("Rsvim.win.setBuffer", id), buffer);
+    }
+    focus(id) {
+        __InternalRsvimGlobalObject.win_focus(winIdArg("Rsvim.win.focus", id));
     }
 }
 (function (globalThis) {
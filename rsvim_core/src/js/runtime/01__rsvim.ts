@@ -19,131 +19,1787 @@
 /**
  * The `Rsvim` global object, it contains multiple sub fields:
  *
- * - `Rsvim.opt`: Global editor options.
+ * - `Rsvim.autocmd`: Autocommands.
+ * - `Rsvim.buf`: Buffers.
+ * - `Rsvim.cmd`: Ex commands.
+ * - `Rsvim.keymap`: Key mappings.
+ * - `Rsvim.opt`: Editor options, setting a local option sets both current window (or buffer)
+ *   and the global value, same as `:set`.
+ * - `Rsvim.o`: Global values of the editor options, same as `:setglobal`.
+ * - `Rsvim.bo`: Buffer-local options of current buffer, `Rsvim.bo[id]` for the buffer `id`.
+ * - `Rsvim.win`: Windows.
+ * - `Rsvim.wo`: Window-local options of current window, `Rsvim.wo[id]` for the window `id`.
+ *
+ * The option names are either the camel case of the Vim option names (i.e. `tabStop`), or the
+ * Vim option names and their short aliases (i.e. `tabstop` and `ts`). Setting an option validates
+ * the value, throws {@link !TypeError} if the option is not found or the value type is wrong,
+ * throws {@link !RangeError} if the option doesn't accept the value. The `OptionSet` event is
+ * fired when the value is changed.
  *
  * @example
  * ```javascript
  * // Create a alias to 'Rsvim'.
  * const vim = Rsvim;
+ *
+ * // Set options.
+ * Rsvim.opt.wrap = false;
+ * Rsvim.o.tabStop = 4;
+ * Rsvim.bo.fileFormat = "dos";
+ * Rsvim.wo[Rsvim.win.current()].scrollOff = 3;
  * ```
  *
  * @category Global Object
  * @hideconstructor
  */
 export class Rsvim {
-  readonly opt: RsvimOpt = new RsvimOpt();
+  readonly autocmd: RsvimAutocmd = new RsvimAutocmd();
+  readonly buf: RsvimBuf = new RsvimBuf();
+  readonly cmd: RsvimCmd = new RsvimCmd();
+  readonly keymap: RsvimKeymap = new RsvimKeymap();
+  readonly opt: RsvimOptions = options("");
+  readonly o: RsvimOptions = options("global");
+  readonly bo: RsvimBufOptions = bufOptions(0);
+  readonly win: RsvimWin = new RsvimWin();
+  readonly wo: RsvimWinOptions = winOptions(0);
+}
+
+// Convert the `buffer` option of the `api`: `-1` is global, `0` is current buffer.
+function bufferArg(api: string, buffer: boolean | number | undefined): number {
+  if (buffer === undefined || buffer === false) {
+    return -1;
+  }
+  if (buffer === true) {
+    return 0;
+  }
+  if (!Number.isInteger(buffer) || buffer <= 0) {
+    throw new Error(
+      `"${api}" buffer must be a boolean value or a positive integer, but found ${buffer} (${typeof buffer})`,
+    );
+  }
+  return buffer;
+}
+
+// Convert the string or the array of strings to an array, the strings must be non-empty.
+function stringsArg(api: string, name: string, value: unknown): string[] {
+  if (value === undefined) {
+    return [];
+  }
+  const values = Array.isArray(value) ? value : [value];
+  for (const v of values) {
+    if (typeof v !== "string" || v.length === 0) {
+      throw new Error(
+        `"${api}" ${name} must be a non-empty string or an array of them, but found ${v} (${typeof v})`,
+      );
+    }
+  }
+  return values;
+}
+
+/**
+ * The event info passed to the callback of an autocommand.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimAutocmdEventInfo {
+  /** The autocommand ID. */
+  id: number;
+  /** The event name, i.e. `BufEnter`. */
+  event: string;
+  /** The group of the autocommand, `null` if it is not in a group. */
+  group: string | null;
+  /** The buffer ID of the event. */
+  buffer: number | null;
+  /** The absolute file name of the buffer, empty for the unnamed buffer. */
+  file: string;
+  /** The string matched by the patterns, it is the file name, or the event data for `ModeChanged`, `CmdlineEnter`, `CmdlineLeave` and `OptionSet`. */
+  match: string;
+  /** The event data, i.e. `n:i` for `ModeChanged`, `:` for `CmdlineEnter`, `tabstop` for `OptionSet`. */
+  data: string;
+}
+
+/**
+ * The callback of an autocommand.
+ *
+ * @category Editor APIs
+ */
+export type RsvimAutocmdCallback = (info: RsvimAutocmdEventInfo) => void;
+
+/**
+ * The options of an autocommand.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimAutocmdOptions {
+  /** The file globs (`*` and `?`), a glob without `/` matches the file name tail. By default it matches everything. */
+  pattern?: string | string[];
+  /** The file types of the buffer, i.e. `rust`. By default it matches everything. */
+  filetype?: string | string[];
+  /** Local to current buffer if `true`, or to the buffer ID. By default `false`. */
+  buffer?: boolean | number;
+  /** The group name, the group must be created by {@link RsvimAutocmd.createGroup}. */
+  group?: string;
+  /** The autocommand is deleted after it runs once. By default `false`. */
+  once?: boolean;
+  /** The description. */
+  desc?: string;
+}
+
+/**
+ * An autocommand returned by {@link RsvimAutocmd.list}.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimAutocmdInfo {
+  id: number;
+  events: string[];
+  patterns: string[];
+  filetypes: string[];
+  /** The buffer ID of a buffer-local autocommand, `null` for a global autocommand. */
+  buffer: number | null;
+  group: string | null;
+  once: boolean;
+  desc: string;
 }
 
 /**
- * The `Rsvim.opt` global object for global editor options.
+ * The `Rsvim.autocmd` global object for autocommands, i.e. the callbacks of the editor events.
+ *
+ * The events are: `BufReadPre`, `BufReadPost`, `BufWritePre`, `BufWritePost`, `BufEnter`,
+ * `BufLeave`, `WinEnter`, `WinLeave`, `ModeChanged`, `InsertEnter`, `InsertLeave`, `TextChanged`,
+ * `TextChangedI`, `CursorMoved`, `CursorMovedI`, `CursorHold`, `VimResized`, `FocusGained`,
+ * `FocusLost`, `CmdlineEnter`, `CmdlineLeave`, `OptionSet` and `VimLeavePre`. The names are
+ * case-insensitive.
+ *
+ * The callbacks run after the editor handles the input that fires the events, in the order of the
+ * events. An exception thrown by a callback is reported in the message area, it doesn't stop the
+ * other callbacks.
  *
  * @example
  * ```javascript
- * // Create a alias to 'Rsvim.opt'.
- * const opt = Rsvim.opt;
+ * // Create a alias to 'Rsvim.autocmd'.
+ * const autocmd = Rsvim.autocmd;
  * ```
  *
  * @category Editor APIs
  * @hideconstructor
  */
-export class RsvimOpt {
+export class RsvimAutocmd {
   /**
-   * Get the _wrap_ option. This option is also known as [line wrap](https://en.wikipedia.org/wiki/Line_wrap_and_word_wrap).
+   * Create an autocommand.
    *
-   * Local to Window.
+   * @param {string | string[]} event - The event names.
+   * @param {RsvimAutocmdCallback} callback - The callback.
+   * @param {RsvimAutocmdOptions} opts - The options.
+   * @returns {number} The autocommand ID.
+   * @throws Throws {@link !Error} if any argument is invalid, or the buffer or the group is not found.
    *
-   * This option changes how text is displayed.
+   * @example
+   * ```javascript
+   * // Trim the trailing whitespaces before writing a rust file.
+   * Rsvim.autocmd.create("BufWritePre", (info) => { ... }, { pattern: "*.rs" });
+   * // Print the mode when leaving insert mode.
+   * Rsvim.autocmd.create("ModeChanged", (info) => { ... }, { pattern: "i:*" });
+   * ```
+   */
+  create(
+    event: string | string[],
+    callback: RsvimAutocmdCallback,
+    opts?: RsvimAutocmdOptions,
+  ): number {
+    const events = stringsArg("Rsvim.autocmd.create", "event", event);
+    if (events.length === 0) {
+      throw new Error(`"Rsvim.autocmd.create" event must not be empty`);
+    }
+    if (typeof callback !== "function") {
+      throw new Error(
+        `"Rsvim.autocmd.create" callback must be a function, but found ${callback} (${typeof callback})`,
+      );
+    }
+    const o = opts ?? {};
+    if (typeof o !== "object") {
+      throw new Error(
+        `"Rsvim.autocmd.create" opts must be an object, but found ${o} (${typeof o})`,
+      );
+    }
+    const patterns = stringsArg("Rsvim.autocmd.create", "pattern", o.pattern);
+    const filetypes = stringsArg(
+      "Rsvim.autocmd.create",
+      "filetype",
+      o.filetype,
+    );
+    const buffer = bufferArg("Rsvim.autocmd.create", o.buffer);
+    if (o.group !== undefined && typeof o.group !== "string") {
+      throw new Error(
+        `"Rsvim.autocmd.create" group must be a string, but found ${o.group} (${typeof o.group})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.autocmd_create(
+      events,
+      callback,
+      patterns,
+      filetypes,
+      buffer,
+      o.group ?? "",
+      !!o.once,
+      o.desc === undefined ? "" : String(o.desc),
+    );
+  }
+
+  /**
+   * Delete an autocommand.
    *
-   * When `true` (on), lines longer than the width of the window will wrap and
-   * displaying continues on the next line. When `false` (off) lines will not wrap
-   * and only part of long lines will be displayed. When the cursor is
-   * moved to a part that is not shown, the screen will scroll horizontally.
+   * @param {number} id - The autocommand ID returned by {@link create}.
+   * @throws Throws {@link !Error} if id is not an integer, or the autocommand is not found.
+   */
+  del(id: number): void {
+    if (!Number.isInteger(id)) {
+      throw new Error(
+        `"Rsvim.autocmd.del" id must be an integer, but found ${id} (${typeof id})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.autocmd_del(id);
+  }
+
+  /**
+   * Create a group if it doesn't exist, a plugin puts its autocommands in a group so they can be
+   * cleared at once.
    *
-   * The line will be broken in the middle of a word if necessary. See {@link lineBreak}
-   * to get the break at a word boundary.
+   * @param {string} name - The group name.
+   * @param {{ clear?: boolean }} opts - Delete the autocommands in the existing group if `clear` is `true`, by default `true`.
+   * @returns {string} The group name.
+   * @throws Throws {@link !Error} if name is not a non-empty string.
+   *
+   * @example
+   * ```javascript
+   * // Reloading the plugin doesn't duplicate its autocommands.
+   * const group = Rsvim.autocmd.createGroup("MyPlugin");
+   * Rsvim.autocmd.create("BufEnter", (info) => { ... }, { group });
+   * ```
+   */
+  createGroup(name: string, opts?: { clear?: boolean }): string {
+    if (typeof name !== "string" || name.length === 0) {
+      throw new Error(
+        `"Rsvim.autocmd.createGroup" name must be a non-empty string, but found ${name} (${typeof name})`,
+      );
+    }
+    const clear = (opts ?? {}).clear ?? true;
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.autocmd_create_group(name, !!clear);
+    return name;
+  }
+
+  /**
+   * Delete a group and its autocommands.
+   *
+   * @param {string} name - The group name.
+   * @throws Throws {@link !Error} if name is not a string, or the group is not found.
+   */
+  delGroup(name: string): void {
+    if (typeof name !== "string") {
+      throw new Error(
+        `"Rsvim.autocmd.delGroup" name must be a string, but found ${name} (${typeof name})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.autocmd_del_group(name);
+  }
+
+  /**
+   * Delete the event from the autocommands in the group, an autocommand is deleted if it listens to
+   * no event. By default it is any event and any group.
+   *
+   * @param {{ event?: string, group?: string }} opts - The event and the group.
+   * @throws Throws {@link !Error} if the event is invalid, or the group is not found.
+   */
+  clear(opts?: { event?: string; group?: string }): void {
+    const o = opts ?? {};
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.autocmd_clear(
+      o.event === undefined ? "" : String(o.event),
+      o.group === undefined ? "" : String(o.group),
+    );
+  }
+
+  /**
+   * List the autocommands of the event in the group. By default it is any event and any group.
+   *
+   * @param {{ event?: string, group?: string }} opts - The event and the group.
+   * @returns {RsvimAutocmdInfo[]}
+   * @throws Throws {@link !Error} if the event is invalid, or the group is not found.
+   */
+  list(opts?: { event?: string; group?: string }): RsvimAutocmdInfo[] {
+    const o = opts ?? {};
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.autocmd_list(
+      o.event === undefined ? "" : String(o.event),
+      o.group === undefined ? "" : String(o.group),
+    );
+  }
+}
+
+// Convert the buffer `id` argument of the `api`: `0` (or `undefined`) is current buffer.
+function bufIdArg(api: string, id: number | undefined): number {
+  if (id === undefined) {
+    return 0;
+  }
+  if (!Number.isInteger(id) || id < 0) {
+    throw new Error(
+      `"${api}" id must be a non-negative integer, but found ${id} (${typeof id})`,
+    );
+  }
+  return id;
+}
+
+// Check the positions (line or char indexes) of the `api` are non-negative integers.
+function positionsArg(api: string, positions: number[]): void {
+  for (const p of positions) {
+    if (!Number.isInteger(p) || p < 0) {
+      throw new Error(
+        `"${api}" position must be a non-negative integer, but found ${p} (${typeof p})`,
+      );
+    }
+  }
+}
+
+// Convert the `end` line of the `api`: a negative integer (or `undefined`) is the line count.
+function endLineArg(api: string, end: number | undefined): number {
+  if (end === undefined) {
+    return -1;
+  }
+  if (!Number.isInteger(end)) {
+    throw new Error(
+      `"${api}" end must be an integer, but found ${end} (${typeof end})`,
+    );
+  }
+  return end < 0 ? -1 : end;
+}
+
+/**
+ * The position `[line, char]` in a buffer, both are 0-based.
+ *
+ * @category Editor APIs
+ */
+export type RsvimBufPosition = [number, number];
+
+/**
+ * The `Rsvim.buf` global object for buffers.
+ *
+ * A buffer is identified by its ID, the ID `0` (or omitting it) is current buffer, i.e. the buffer
+ * of current window. The lines and chars are 0-based, the lines are without the eols. The end
+ * of a range is exclusive.
+ *
+ * All the changes to the text are the same with the changes made by editing, i.e. they increase
+ * the `changedtick`, mark the buffer as modified, and the windows of the buffer are updated. A
+ * terminal buffer cannot be changed. There's no undo yet, so the changes cannot be undone.
+ *
+ * @example
+ * ```javascript
+ * // Create a alias to 'Rsvim.buf'.
+ * const buf = Rsvim.buf;
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimBuf {
+  /**
+   * Get current buffer ID, i.e. the buffer of current window.
+   *
+   * @returns {number}
+   */
+  current(): number {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.buf_current();
+  }
+
+  /**
+   * List all the buffer IDs.
+   *
+   * @returns {number[]}
+   */
+  list(): number[] {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.buf_list();
+  }
+
+  /**
+   * Create a buffer for the file, the file is read if it exists. If the buffer of the file already
+   * exists, it returns the existing buffer. The buffer is not shown in any window.
+   *
+   * @param {string} filename - The file name, by default it is the empty string, i.e. an unnamed buffer.
+   * @returns {number} The buffer ID.
+   * @throws Throws {@link !Error} if filename is not a string, the file cannot be read, or the unnamed buffer already exists.
+   *
+   * @example
+   * ```javascript
+   * const id = Rsvim.buf.create("README.md");
+   * ```
+   */
+  create(filename?: string): number {
+    const f = filename ?? "";
+    if (typeof f !== "string") {
+      throw new Error(
+        `"Rsvim.buf.create" filename must be a string, but found ${f} (${typeof f})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.buf_create(f);
+  }
+
+  /**
+   * Get the file name of the buffer.
    *
+   * @param {number} id - The buffer ID, by default it is current buffer.
+   * @returns {string} The file name, empty for the unnamed buffer.
+   * @throws Throws {@link !Error} if id is invalid, or the buffer is not found.
+   */
+  name(id?: number): string {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.buf_info(
+      bufIdArg("Rsvim.buf.name", id),
+      "name",
+    );
+  }
+
+  /**
+   * Get the file type of the buffer, i.e. `rust`.
+   *
+   * @param {number} id - The buffer ID, by default it is current buffer.
+   * @returns {string} The file type, empty if it is not detected.
+   * @throws Throws {@link !Error} if id is invalid, or the buffer is not found.
+   */
+  filetype(id?: number): string {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.buf_info(
+      bufIdArg("Rsvim.buf.filetype", id),
+      "filetype",
+    );
+  }
+
+  /**
+   * Get the count of changes of the buffer, it increases on every change of the text, i.e. Vim's
+   * `b:changedtick`.
+   *
+   * @param {number} id - The buffer ID, by default it is current buffer.
+   * @returns {number}
+   * @throws Throws {@link !Error} if id is invalid, or the buffer is not found.
+   */
+  changedtick(id?: number): number {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.buf_info(
+      bufIdArg("Rsvim.buf.changedtick", id),
+      "changedtick",
+    );
+  }
+
+  /**
+   * Whether the buffer is modified since it is read or written.
+   *
+   * @param {number} id - The buffer ID, by default it is current buffer.
    * @returns {boolean}
+   * @throws Throws {@link !Error} if id is invalid, or the buffer is not found.
+   */
+  modified(id?: number): boolean {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.buf_info(
+      bufIdArg("Rsvim.buf.modified", id),
+      "modified",
+    );
+  }
+
+  /**
+   * Get the count of lines of the buffer, i.e. Vim's `line('$')`.
    *
-   * @defaultValue `true`
+   * @param {number} id - The buffer ID, by default it is current buffer.
+   * @returns {number}
+   * @throws Throws {@link !Error} if id is invalid, or the buffer is not found.
+   */
+  lineCount(id?: number): number {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.buf_info(
+      bufIdArg("Rsvim.buf.lineCount", id),
+      "lineCount",
+    );
+  }
+
+  /**
+   * Get the lines of the buffer.
+   *
+   * @param {number} id - The buffer ID, `0` is current buffer.
+   * @param {number} start - The start line.
+   * @param {number} end - The end line (exclusive), a negative integer is the line count. By default it is the line count.
+   * @returns {string[]} The lines without the eols.
+   * @throws Throws {@link !Error} if any argument is invalid, the buffer is not found, or the lines are out of range.
+   *
+   * @example
+   * ```javascript
+   * // All the lines of current buffer.
+   * const lines = Rsvim.buf.getLines(0, 0);
+   * ```
+   */
+  getLines(id: number, start: number, end?: number): string[] {
+    positionsArg("Rsvim.buf.getLines", [start]);
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.buf_get_lines(
+      bufIdArg("Rsvim.buf.getLines", id),
+      start,
+      endLineArg("Rsvim.buf.getLines", end),
+    );
+  }
+
+  /**
+   * Replace the lines of the buffer, i.e. the empty `lines` deletes the lines, the empty range
+   * `[n, n)` inserts the `lines` before line `n`.
+   *
+   * @param {number} id - The buffer ID, `0` is current buffer.
+   * @param {number} start - The start line.
+   * @param {number} end - The end line (exclusive), a negative integer is the line count.
+   * @param {string[]} lines - The new lines without the eols.
+   * @throws Throws {@link !Error} if any argument is invalid, the buffer is not found or is a terminal buffer, or the lines are out of range.
    *
    * @example
    * ```javascript
-   * // Get the 'wrap' option.
-   * const value = Rsvim.opt.wrap;
+   * // Append a line to current buffer.
+   * Rsvim.buf.setLines(0, -1, -1, ["// EOF"]);
+   * // Delete the first line.
+   * Rsvim.buf.setLines(0, 0, 1, []);
    * ```
    */
-  get wrap(): boolean {
+  setLines(id: number, start: number, end: number, lines: string[]): void {
+    positionsArg("Rsvim.buf.setLines", [start]);
+    if (!Array.isArray(lines) || lines.some((l) => typeof l !== "string")) {
+      throw new Error(
+        `"Rsvim.buf.setLines" lines must be an array of strings, but found ${lines} (${typeof lines})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.buf_set_lines(
+      bufIdArg("Rsvim.buf.setLines", id),
+      start,
+      endLineArg("Rsvim.buf.setLines", end),
+      lines,
+    );
+  }
+
+  /**
+   * Get the text between the positions of the buffer.
+   *
+   * @param {number} id - The buffer ID, `0` is current buffer.
+   * @param {number} startLine - The line of the start.
+   * @param {number} startChar - The char on the line of the start.
+   * @param {number} endLine - The line of the end.
+   * @param {number} endChar - The char on the line of the end (exclusive).
+   * @returns {string} The text, the line breaks are the eols of the buffer.
+   * @throws Throws {@link !Error} if any argument is invalid, the buffer is not found, or the positions are out of range.
+   */
+  getText(
+    id: number,
+    startLine: number,
+    startChar: number,
+    endLine: number,
+    endChar: number,
+  ): string {
+    positionsArg("Rsvim.buf.getText", [
+      startLine,
+      startChar,
+      endLine,
+      endChar,
+    ]);
     // @ts-ignore Ignore warning
-    return __InternalRsvimGlobalObject.opt_get_wrap();
+    return __InternalRsvimGlobalObject.buf_get_text(
+      bufIdArg("Rsvim.buf.getText", id),
+      startLine,
+      startChar,
+      endLine,
+      endChar,
+    );
   }
 
   /**
-   * Set the _wrap_ option.
+   * Replace the text between the positions of the buffer, i.e. the empty `text` deletes the text.
    *
-   * @param {boolean} value - The _wrap_ option.
-   * @throws Throws {@link !Error} if value is not a boolean value.
+   * @param {number} id - The buffer ID, `0` is current buffer.
+   * @param {number} startLine - The line of the start.
+   * @param {number} startChar - The char on the line of the start.
+   * @param {number} endLine - The line of the end.
+   * @param {number} endChar - The char on the line of the end (exclusive).
+   * @param {string} text - The new text.
+   * @returns {RsvimBufPosition} The position after the new text.
+   * @throws Throws {@link !Error} if any argument is invalid, the buffer is not found or is a terminal buffer, or the positions are out of range.
    *
    * @example
    * ```javascript
-   * // Set the 'wrap' option.
-   * Rsvim.opt.wrap = true;
+   * // Replace "hello" with "bye" on the first line `hello world`.
+   * Rsvim.buf.setText(0, 0, 0, 0, 5, "bye");
    * ```
    */
-  set wrap(value: boolean) {
-    if (typeof value !== "boolean") {
+  setText(
+    id: number,
+    startLine: number,
+    startChar: number,
+    endLine: number,
+    endChar: number,
+    text: string,
+  ): RsvimBufPosition {
+    positionsArg("Rsvim.buf.setText", [
+      startLine,
+      startChar,
+      endLine,
+      endChar,
+    ]);
+    if (typeof text !== "string") {
       throw new Error(
-        `"Rsvim.opt.wrap" must be a boolean value, but found ${value} (${typeof value})`,
+        `"Rsvim.buf.setText" text must be a string, but found ${text} (${typeof text})`,
       );
     }
     // @ts-ignore Ignore warning
-    __InternalRsvimGlobalObject.opt_set_wrap(value);
+    return __InternalRsvimGlobalObject.buf_set_text(
+      bufIdArg("Rsvim.buf.setText", id),
+      startLine,
+      startChar,
+      endLine,
+      endChar,
+      text,
+    );
   }
 
   /**
-   * Get the _line-break_ option. This options is also known as [word wrap](https://en.wikipedia.org/wiki/Line_wrap_and_word_wrap).
+   * Insert the text at the position of the buffer.
    *
-   * Local to Window.
+   * @param {number} id - The buffer ID, `0` is current buffer.
+   * @param {number} line - The line.
+   * @param {number} char - The char on the line, it can be the end of the line.
+   * @param {string} text - The text.
+   * @returns {RsvimBufPosition} The position after the inserted text.
+   * @throws Throws {@link !Error} if any argument is invalid, the buffer is not found or is a terminal buffer, or the position is out of range.
    *
-   * If `true` (on), Vim will wrap long lines by a word boundary rather than at the last character that fits on the screen.
-   * It only affects the way the file is displayed, not its contents.
+   * @example
+   * ```javascript
+   * // Insert a header to current buffer.
+   * Rsvim.buf.insert(0, 0, 0, "// Copyright\n");
+   * ```
+   */
+  insert(
+    id: number,
+    line: number,
+    char: number,
+    text: string,
+  ): RsvimBufPosition {
+    positionsArg("Rsvim.buf.insert", [line, char]);
+    if (typeof text !== "string") {
+      throw new Error(
+        `"Rsvim.buf.insert" text must be a string, but found ${text} (${typeof text})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.buf_insert(
+      bufIdArg("Rsvim.buf.insert", id),
+      line,
+      char,
+      text,
+    );
+  }
+
+  /**
+   * Delete the chars at the position of the buffer, an eol is 1 char.
    *
-   * This option is not used when the {@link wrap} option is `false`.
+   * @param {number} id - The buffer ID, `0` is current buffer.
+   * @param {number} line - The line.
+   * @param {number} char - The char on the line.
+   * @param {number} n - Delete `n` chars to the right if it is positive, or to the left if it is negative.
+   * @returns {RsvimBufPosition} The position after the chars are deleted.
+   * @throws Throws {@link !Error} if any argument is invalid, the buffer is not found or is a terminal buffer, or the position is out of range.
    *
-   * @returns {boolean}
+   * @example
+   * ```javascript
+   * // Join the first 2 lines, i.e. delete the eol of the first line.
+   * const n = Rsvim.buf.getLines(0, 0, 1)[0].length;
+   * Rsvim.buf.delete(0, 0, n, 1);
+   * ```
+   */
+  delete(
+    id: number,
+    line: number,
+    char: number,
+    n: number,
+  ): RsvimBufPosition {
+    positionsArg("Rsvim.buf.delete", [line, char]);
+    if (!Number.isInteger(n)) {
+      throw new Error(
+        `"Rsvim.buf.delete" n must be an integer, but found ${n} (${typeof n})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.buf_delete(
+      bufIdArg("Rsvim.buf.delete", id),
+      line,
+      char,
+      n,
+    );
+  }
+
+  /**
+   * Get the buffer-local option of the buffer, i.e. `tabstop`. The option name can be the short
+   * alias, i.e. `ts`.
    *
-   * @defaultValue `false`
+   * @param {number} id - The buffer ID, `0` is current buffer.
+   * @param {string} name - The option name.
+   * @returns {boolean | number | string} The option value.
+   * @throws Throws {@link !Error} if any argument is invalid, the buffer is not found, or the option is not a buffer-local option.
    *
    * @example
    * ```javascript
-   * // Get the 'lineBreak' option.
-   * const value = Rsvim.opt.lineBreak;
+   * const tabstop = Rsvim.buf.getOption(0, "tabstop");
    * ```
    */
-  get lineBreak(): boolean {
+  getOption(id: number, name: string): boolean | number | string {
+    if (typeof name !== "string") {
+      throw new Error(
+        `"Rsvim.buf.getOption" name must be a string, but found ${name} (${typeof name})`,
+      );
+    }
     // @ts-ignore Ignore warning
-    return __InternalRsvimGlobalObject.opt_get_line_break();
+    return __InternalRsvimGlobalObject.buf_get_option(
+      bufIdArg("Rsvim.buf.getOption", id),
+      name,
+    );
   }
 
   /**
-   * Set the _line-break_ option.
+   * Set the buffer-local option of the buffer, i.e. `:setlocal` in the buffer.
    *
-   * @param {boolean} value - The _line-break_ option.
-   * @throws Throws {@link !Error} if value is not a boolean value.
+   * @param {number} id - The buffer ID, `0` is current buffer.
+   * @param {string} name - The option name.
+   * @param {boolean | number | string} value - The option value.
+   * @throws Throws {@link !Error} if any argument is invalid, the buffer is not found, the option is not a buffer-local option, or the option doesn't accept the value.
    *
    * @example
    * ```javascript
-   * // Set the 'lineBreak' option.
-   * Rsvim.opt.lineBreak = true;
+   * Rsvim.buf.setOption(0, "expandtab", true);
    * ```
    */
-  set lineBreak(value: boolean) {
-    if (typeof value !== "boolean") {
+  setOption(
+    id: number,
+    name: string,
+    value: boolean | number | string,
+  ): void {
+    if (typeof name !== "string") {
       throw new Error(
-        `"Rsvim.opt.lineBreak" must be a boolean value, but found ${value} (${typeof value})`,
+        `"Rsvim.buf.setOption" name must be a string, but found ${name} (${typeof name})`,
       );
     }
     // @ts-ignore Ignore warning
-    __InternalRsvimGlobalObject.opt_set_line_break(value);
+    __InternalRsvimGlobalObject.buf_set_option(
+      bufIdArg("Rsvim.buf.setOption", id),
+      name,
+      value,
+    );
+  }
+
+  /**
+   * Select the text in current buffer and start select mode, i.e. the snippet placeholders. Then
+   * typing a printable char replaces the selected text, see the 'selectmode' option.
+   *
+   * The positions are 0-based, and both the start and the end are inclusive. They're clamped into
+   * the buffer text. The cursor is moved to the end.
+   *
+   * @param {number} startLine - The line of the start.
+   * @param {number} startChar - The char on the line of the start.
+   * @param {number} endLine - The line of the end.
+   * @param {number} endChar - The char on the line of the end.
+   * @throws Throws {@link !Error} if any position is not a non-negative integer.
+   *
+   * @example
+   * ```javascript
+   * // Select the placeholder "name" in `hello(name)` on the first line.
+   * Rsvim.buf.select(0, 6, 0, 9);
+   * ```
+   */
+  select(
+    startLine: number,
+    startChar: number,
+    endLine: number,
+    endChar: number,
+  ): void {
+    for (const p of [startLine, startChar, endLine, endChar]) {
+      if (!Number.isInteger(p) || p < 0) {
+        throw new Error(
+          `"Rsvim.buf.select" position must be a non-negative integer, but found ${p} (${typeof p})`,
+        );
+      }
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.buf_select(
+      startLine,
+      startChar,
+      endLine,
+      endChar,
+    );
+  }
+}
+
+/**
+ * The parsed command-line passed to the callback of a user ex command.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimCmdArgs {
+  /** The full command name. */
+  name: string;
+  /** The arguments as a string, without the count. */
+  args: string;
+  /** The arguments split by whitespaces, or the whole arguments if `nargs` is `"1"` or `"?"`. */
+  fargs: string[];
+  /** The 1-based `[start, end]` line numbers of the range, both are inclusive, i.e. `:3,5Foo` is `[3, 5]`. It is `null` if the command doesn't accept a range. */
+  range: [number, number] | null;
+  /** The command is executed with `!`, i.e. `:Name!`. */
+  bang: boolean;
+  /** The count, it is `null` if the command doesn't accept a count. */
+  count: number | null;
+}
+
+/**
+ * The callback of a user ex command. The returned value (except `undefined` and `null`) is shown
+ * in the message area.
+ *
+ * @category Editor APIs
+ */
+export type RsvimCmdCallback = (args: RsvimCmdArgs) => unknown;
+
+/**
+ * The options of a user ex command.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimCmdOptions {
+  /** The number of arguments: `"0"`, `"1"`, `"*"` (any), `"?"` (0 or 1), `"+"` (1 or more). By default `"0"`. */
+  nargs?: "0" | "1" | "*" | "?" | "+" | 0 | 1;
+  /** Accepts a range that defaults to the cursor line if `true`, or the whole file if `"%"`. Accepts a count that defaults to the number if it is a number, the count is in the line number position or the first argument. By default `false`. */
+  range?: boolean | "%" | number;
+  /** Accepts `!`, i.e. `:Name!`. By default `false`. */
+  bang?: boolean;
+  /** The completion of the arguments: `"command"`, `"option"`, `"file"`, `"dir"`, `"buffer"`, `"history"`, or a completer function. `"custom"` uses the completer set by {@link RsvimCmd.setCompleter}. */
+  complete?: string | RsvimCmdCompleter;
+  /** The description. */
+  desc?: string;
+}
+
+/**
+ * A user ex command returned by {@link RsvimCmd.list}.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimCmdInfo {
+  name: string;
+  nargs: string;
+  range: boolean | "%" | number;
+  bang: boolean;
+  /** The completion kind, it is `"custom"` for a completer function, `null` if there's no completion. */
+  complete: string | null;
+  desc: string;
+}
+
+/**
+ * The completer for the arguments of an ex command.
+ *
+ * @param {string} argLead - The leading portion of the argument currently being completed.
+ * @param {string} cmdLine - The entire command-line.
+ * @param {number} cursorPos - The cursor position (char index) in the command-line.
+ * @returns {string[]} The completion matches, they replace the `argLead`.
+ *
+ * @category Editor APIs
+ */
+export type RsvimCmdCompleter = (
+  argLead: string,
+  cmdLine: string,
+  cursorPos: number,
+) => string[];
+
+/**
+ * The repeatable action, it is called by `.` with the count given to `.`, or `undefined` if no
+ * count is given.
+ *
+ * @category Editor APIs
+ */
+export type RsvimCmdRepeatAction = (count: number | undefined) => void;
+
+/**
+ * The `Rsvim.cmd` global object for ex commands.
+ *
+ * @example
+ * ```javascript
+ * // Create a alias to 'Rsvim.cmd'.
+ * const cmd = Rsvim.cmd;
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimCmd {
+  /**
+   * Create a user ex command, or replace the existing command of the same name. The command-line
+   * is parsed and checked against the options, the errors are shown in the message area. The
+   * exception thrown by the callback is also shown in the message area.
+   *
+   * @param {string} name - The command name, it starts with an uppercase letter, and only contains letters and digits.
+   * @param {RsvimCmdCallback} callback - The callback.
+   * @param {RsvimCmdOptions} opts - The options.
+   * @throws Throws {@link !Error} if any argument is invalid.
+   *
+   * @example
+   * ```javascript
+   * // Delete the trailing whitespaces in the range, i.e. `:%Trim`.
+   * Rsvim.cmd.create("Trim", ({ range }) => { ... }, { range: true });
+   * // Print the arguments, i.e. `:Echo a b`.
+   * Rsvim.cmd.create("Echo", ({ fargs }) => fargs.join(","), { nargs: "*" });
+   * ```
+   */
+  create(
+    name: string,
+    callback: RsvimCmdCallback,
+    opts?: RsvimCmdOptions,
+  ): void {
+    if (typeof name !== "string") {
+      throw new Error(
+        `"Rsvim.cmd.create" name must be a string, but found ${name} (${typeof name})`,
+      );
+    }
+    if (typeof callback !== "function") {
+      throw new Error(
+        `"Rsvim.cmd.create" callback must be a function, but found ${callback} (${typeof callback})`,
+      );
+    }
+    const o = opts ?? {};
+    if (typeof o !== "object") {
+      throw new Error(
+        `"Rsvim.cmd.create" opts must be an object, but found ${o} (${typeof o})`,
+      );
+    }
+    const complete = o.complete ?? "";
+    if (typeof complete !== "string" && typeof complete !== "function") {
+      throw new Error(
+        `"Rsvim.cmd.create" complete must be a string or a function, but found ${complete} (${typeof complete})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.cmd_create(
+      name,
+      callback,
+      String(o.nargs ?? "0"),
+      o.range ?? false,
+      !!o.bang,
+      typeof complete === "function" ? "custom" : complete,
+      o.desc === undefined ? "" : String(o.desc),
+    );
+    if (typeof complete === "function") {
+      this.setCompleter(name, complete);
+    }
+  }
+
+  /**
+   * Delete a user ex command, and its completer.
+   *
+   * @param {string} name - The command name.
+   * @throws Throws {@link !Error} if name is not a string, or the command is not found.
+   */
+  del(name: string): void {
+    if (typeof name !== "string") {
+      throw new Error(
+        `"Rsvim.cmd.del" name must be a string, but found ${name} (${typeof name})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.cmd_del(name);
+  }
+
+  /**
+   * List the user ex commands in the order of names.
+   *
+   * @returns {RsvimCmdInfo[]} The commands.
+   *
+   * @example
+   * ```javascript
+   * const names = Rsvim.cmd.list().map((c) => c.name);
+   * ```
+   */
+  list(): RsvimCmdInfo[] {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.cmd_list();
+  }
+
+  /**
+   * Set the completer for the arguments of an ex command, it is used when pressing `<Tab>` in
+   * command-line. The matches are shown in the wildmenu, see the 'wildmode' and 'wildoptions'
+   * options.
+   *
+   * @param {string} name - The ex command name.
+   * @param {RsvimCmdCompleter | null} completer - The completer, `null` removes the completer.
+   * @throws Throws {@link !Error} if name is not a string, or completer is not a function or `null`.
+   *
+   * @example
+   * ```javascript
+   * // Complete the colors for the 'Color' command.
+   * Rsvim.cmd.setCompleter("Color", (argLead, cmdLine, cursorPos) => {
+   *   return ["red", "green", "blue"].filter((c) => c.startsWith(argLead));
+   * });
+   * ```
+   */
+  setCompleter(name: string, completer: RsvimCmdCompleter | null): void {
+    if (typeof name !== "string") {
+      throw new Error(
+        `"Rsvim.cmd.setCompleter" name must be a string, but found ${name} (${typeof name})`,
+      );
+    }
+    if (completer !== null && typeof completer !== "function") {
+      throw new Error(
+        `"Rsvim.cmd.setCompleter" completer must be a function or null, but found ${completer} (${typeof completer})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.cmd_set_completer(name, completer);
+  }
+
+  /**
+   * Set the repeatable action as the last change, i.e. the next `.` calls the action instead of
+   * repeating the last change made in normal mode. A plugin calls this after its change is done,
+   * so the change can be repeated.
+   *
+   * @param {RsvimCmdRepeatAction} action - The repeatable action.
+   * @throws Throws {@link !Error} if action is not a function.
+   *
+   * @example
+   * ```javascript
+   * // Surround the current line with quotes, and make it repeatable.
+   * function surround(count) {
+   *   // ...
+   *   Rsvim.cmd.setRepeat(surround);
+   * }
+   * ```
+   */
+  setRepeat(action: RsvimCmdRepeatAction): void {
+    if (typeof action !== "function") {
+      throw new Error(
+        `"Rsvim.cmd.setRepeat" action must be a function, but found ${action} (${typeof action})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.cmd_set_repeat(action);
+  }
+}
+
+/**
+ * The callback of a mapping. For an `expr` mapping, the returned string is fed as the keys.
+ *
+ * @category Editor APIs
+ */
+export type RsvimKeymapCallback = () => string | void;
+
+/**
+ * The options of a mapping.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimKeymapOptions {
+  /** The right-hand side keys are remapped, i.e. `:map` instead of `:noremap`. By default `false`. */
+  remap?: boolean;
+  /** The messages echoed by the mapping are not shown, i.e. `<silent>`. By default `false`. */
+  silent?: boolean;
+  /** The string returned by the callback is fed as the keys, i.e. `<expr>`. By default `false`. */
+  expr?: boolean;
+  /** Local to current buffer if `true`, or to the buffer ID, i.e. `<buffer>`. By default `false`. */
+  buffer?: boolean | number;
+  /** The description. */
+  desc?: string;
+}
+
+/**
+ * A mapping returned by {@link RsvimKeymap.list}.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimKeymapInfo {
+  /** The mode char, i.e. `n`, `x`, `s`, `o`, `i`, `c`, `t`. */
+  mode: string;
+  /** The left-hand side in key notation. */
+  lhs: string;
+  /** The right-hand side in key notation, `<Nop>` for no keys, `<Callback>` for a callback. */
+  rhs: string;
+  remap: boolean;
+  silent: boolean;
+  expr: boolean;
+  /** The buffer ID of a buffer-local mapping, `null` for a global mapping. */
+  buffer: number | null;
+  desc: string;
+  /** It is a builtin default mapping, i.e. `x` is `dl`. */
+  builtin: boolean;
+}
+
+/**
+ * The `Rsvim.keymap` global object for key mappings.
+ *
+ * The modes of a mapping is a string, each char is a mode: `n` (normal), `v` (visual and select),
+ * `x` (visual), `s` (select), `o` (operator-pending), `i` (insert), `c` (command-line), `t`
+ * (terminal), `!` (insert and command-line). The empty string is `nvo`, the same as `:map`.
+ *
+ * The builtin synonyms (i.e. `x` is `dl`, `D` is `d$`) are the default mappings, they can be
+ * remapped or deleted as well.
+ *
+ * @example
+ * ```javascript
+ * // Create a alias to 'Rsvim.keymap'.
+ * const keymap = Rsvim.keymap;
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimKeymap {
+  /**
+   * Get the leader key, i.e. `<Leader>` in the mappings. By default it is `\`.
+   *
+   * @returns {string}
+   */
+  get leader(): string {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.keymap_get_leader();
+  }
+
+  /**
+   * Set the leader key, it is used by the mappings set after it.
+   *
+   * @param {string} value - The leader key in key notation, i.e. `,` or `<Space>`.
+   * @throws Throws {@link !Error} if value is not a non-empty string.
+   *
+   * @example
+   * ```javascript
+   * Rsvim.keymap.leader = "<Space>";
+   * ```
+   */
+  set leader(value: string) {
+    if (typeof value !== "string" || value.length === 0) {
+      throw new Error(
+        `"Rsvim.keymap.leader" must be a non-empty string, but found ${value} (${typeof value})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.keymap_set_leader(value);
+  }
+
+  /**
+   * Set a mapping, it replaces the existing mapping of the same left-hand side.
+   *
+   * @param {string} modes - The modes.
+   * @param {string} lhs - The left-hand side in key notation, i.e. `<Leader>w`.
+   * @param {string | RsvimKeymapCallback} rhs - The right-hand side in key notation (the empty string is `<Nop>`), or a callback.
+   * @param {RsvimKeymapOptions} opts - The options.
+   * @throws Throws {@link !Error} if any argument is invalid, or the buffer is not found.
+   *
+   * @example
+   * ```javascript
+   * // Save the file with `<Leader>w`.
+   * Rsvim.keymap.set("n", "<Leader>w", ":w<CR>", { silent: true });
+   * // Delete the line with a callback.
+   * Rsvim.keymap.set("n", "<Leader>d", () => "dd", { expr: true });
+   * ```
+   */
+  set(
+    modes: string,
+    lhs: string,
+    rhs: string | RsvimKeymapCallback,
+    opts?: RsvimKeymapOptions,
+  ): void {
+    if (typeof modes !== "string") {
+      throw new Error(
+        `"Rsvim.keymap.set" modes must be a string, but found ${modes} (${typeof modes})`,
+      );
+    }
+    if (typeof lhs !== "string" || lhs.length === 0) {
+      throw new Error(
+        `"Rsvim.keymap.set" lhs must be a non-empty string, but found ${lhs} (${typeof lhs})`,
+      );
+    }
+    if (typeof rhs !== "string" && typeof rhs !== "function") {
+      throw new Error(
+        `"Rsvim.keymap.set" rhs must be a string or a function, but found ${rhs} (${typeof rhs})`,
+      );
+    }
+    const o = opts ?? {};
+    if (typeof o !== "object") {
+      throw new Error(
+        `"Rsvim.keymap.set" opts must be an object, but found ${o} (${typeof o})`,
+      );
+    }
+    const buffer = bufferArg("Rsvim.keymap.set", o.buffer);
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.keymap_set(
+      modes,
+      lhs,
+      rhs,
+      !!o.remap,
+      !!o.silent,
+      !!o.expr,
+      buffer,
+      o.desc === undefined ? "" : String(o.desc),
+    );
+  }
+
+  /**
+   * Delete a mapping.
+   *
+   * @param {string} modes - The modes.
+   * @param {string} lhs - The left-hand side in key notation.
+   * @param {{ buffer?: boolean | number }} opts - Delete the buffer-local mapping of current buffer if `buffer` is `true`, or of the buffer ID.
+   * @throws Throws {@link !Error} if any argument is invalid, or the mapping is not found.
+   *
+   * @example
+   * ```javascript
+   * // Make `x` not a synonym of `dl`.
+   * Rsvim.keymap.del("n", "x");
+   * ```
+   */
+  del(
+    modes: string,
+    lhs: string,
+    opts?: { buffer?: boolean | number },
+  ): void {
+    if (typeof modes !== "string") {
+      throw new Error(
+        `"Rsvim.keymap.del" modes must be a string, but found ${modes} (${typeof modes})`,
+      );
+    }
+    if (typeof lhs !== "string" || lhs.length === 0) {
+      throw new Error(
+        `"Rsvim.keymap.del" lhs must be a non-empty string, but found ${lhs} (${typeof lhs})`,
+      );
+    }
+    const buffer = bufferArg("Rsvim.keymap.del", (opts ?? {}).buffer);
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.keymap_del(modes, lhs, buffer);
+  }
+
+  /**
+   * List the mappings of the modes, including the buffer-local mappings of current buffer.
+   *
+   * @param {string} modes - The modes, by default it is the empty string (`nvo`).
+   * @returns {RsvimKeymapInfo[]}
+   * @throws Throws {@link !Error} if modes is not a string.
+   *
+   * @example
+   * ```javascript
+   * // The normal mode mappings.
+   * const mappings = Rsvim.keymap.list("n");
+   * ```
+   */
+  list(modes?: string): RsvimKeymapInfo[] {
+    const m = modes ?? "";
+    if (typeof m !== "string") {
+      throw new Error(
+        `"Rsvim.keymap.list" modes must be a string, but found ${m} (${typeof m})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.keymap_list(m);
+  }
+}
+
+/**
+ * The buffer-local options.
+ *
+ * The option names are the camel case of the Vim option names, i.e. `tabStop` for `'tabstop'`.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimBufferOptions {
+  /**
+   * The _tab-stop_ option, the number of spaces that a tab in the file counts for. It must be a
+   * positive integer.
+   *
+   * @defaultValue `8`
+   */
+  tabStop: number;
+
+  /**
+   * The _file-encoding_ option, the character encoding of the file.
+   *
+   * @defaultValue `"utf-8"`
+   */
+  fileEncoding: string;
+
+  /**
+   * The _file-format_ option, the line break of the file: `"dos"`, `"unix"` or `"mac"`.
+   *
+   * @defaultValue `"unix"` (`"dos"` on Windows)
+   */
+  fileFormat: string;
+
+  /**
+   * The _is-keyword_ option, the characters of a keyword, i.e. for the `w` motion.
+   *
+   * @defaultValue `"@,48-57,_,192-255"`
+   */
+  isKeyword: string;
+}
+
+/**
+ * The window-local options.
+ *
+ * The option names are the camel case of the Vim option names, i.e. `lineBreak` for `'linebreak'`.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimWindowOptions {
+  /**
+   * The _wrap_ option. This option is also known as [line wrap](https://en.wikipedia.org/wiki/Line_wrap_and_word_wrap).
+   *
+   * When `true` (on), lines longer than the width of the window will wrap and
+   * displaying continues on the next line. When `false` (off) lines will not wrap
+   * and only part of long lines will be displayed. When the cursor is
+   * moved to a part that is not shown, the screen will scroll horizontally.
+   *
+   * The line will be broken in the middle of a word if necessary. See {@link lineBreak}
+   * to get the break at a word boundary.
+   *
+   * @defaultValue `true`
+   */
+  wrap: boolean;
+
+  /**
+   * The _line-break_ option. This options is also known as [word wrap](https://en.wikipedia.org/wiki/Line_wrap_and_word_wrap).
+   *
+   * If `true` (on), Vim will wrap long lines by a word boundary rather than at the last character that fits on the screen.
+   * It only affects the way the file is displayed, not its contents.
+   *
+   * This option is not used when the {@link wrap} option is `false`.
+   *
+   * @defaultValue `false`
+   */
+  lineBreak: boolean;
+
+  /**
+   * The _scroll-off_ option, the minimal number of lines to keep above and below the cursor.
+   *
+   * @defaultValue `0`
+   */
+  scrollOff: number;
+}
+
+/**
+ * The global options.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimGlobalOptions {
+  /** The _history_ option, the number of remembered command-line history entries. */
+  history: number;
+  /** The _msg-history_ option, the number of remembered messages. */
+  msgHistory: number;
+  /** The _wild-mode_ option, the completion mode of the command-line. */
+  wildMode: string;
+  /** The _wild-ignore_ option, the file patterns ignored by the completion. */
+  wildIgnore: string;
+  /** The _wild-options_ option, the options of the command-line completion. */
+  wildOptions: string;
+  /** The _timeout_ option, whether mappings time out. */
+  timeout: boolean;
+  /** The _timeout-len_ option, the milliseconds to wait for a mapped key sequence. */
+  timeoutLen: number;
+  /** The _show-cmd_ option, whether to show the pending command in the last line. */
+  showCmd: boolean;
+  /** The _update-time_ option, the milliseconds to wait before the `CursorHold` event. */
+  updateTime: number;
+  /** The _key-model_ option, whether the shifted special keys start selection. */
+  keyModel: string;
+  /** The _select-mode_ option, when to start select mode instead of visual mode. */
+  selectMode: string;
+  /** The _mouse_ option, the modes that enable the mouse. */
+  mouse: string;
+}
+
+/**
+ * All the editor options.
+ *
+ * @category Editor APIs
+ */
+export type RsvimOptions = RsvimBufferOptions &
+  RsvimWindowOptions &
+  RsvimGlobalOptions;
+
+/**
+ * The buffer-local options of current buffer, and the buffer-local options of other buffers by
+ * buffer ID.
+ *
+ * @category Editor APIs
+ */
+export type RsvimBufOptions = RsvimBufferOptions & {
+  [id: number]: RsvimBufferOptions;
+};
+
+/**
+ * The window-local options of current window, and the window-local options of other windows by
+ * window ID.
+ *
+ * @category Editor APIs
+ */
+export type RsvimWinOptions = RsvimWindowOptions & {
+  [id: number]: RsvimWindowOptions;
+};
+
+// Create the options object, the property names are forwarded to the `get` and `set` functions.
+// The numeric property names are forwarded to the `byId` function if it exists.
+function optionsProxy(
+  get: (name: string) => unknown,
+  set: (name: string, value: unknown) => void,
+  byId?: (id: number) => object,
+): any {
+  return new Proxy(
+    {},
+    {
+      get(_target, name) {
+        if (typeof name !== "string") {
+          return undefined;
+        }
+        if (byId !== undefined && /^\d+$/.test(name)) {
+          return byId(Number(name));
+        }
+        return get(name);
+      },
+      set(_target, name, value) {
+        if (typeof name !== "string") {
+          return false;
+        }
+        set(name, value);
+        return true;
+      },
+    },
+  );
+}
+
+// Create the buffer-local options object of the buffer `id`, `0` is current buffer, which also
+// indexes other buffers by ID.
+function bufOptions(id: number): RsvimBufOptions {
+  return optionsProxy(
+    // @ts-ignore Ignore warning
+    (name) => __InternalRsvimGlobalObject.buf_get_option(id, name),
+    (name, value) => {
+      // @ts-ignore Ignore warning
+      __InternalRsvimGlobalObject.buf_set_option(id, name, value);
+    },
+    id === 0 ? bufOptions : undefined,
+  );
+}
+
+// Create the window-local options object of the window `id`, `0` is current window, which also
+// indexes other windows by ID.
+function winOptions(id: number): RsvimWinOptions {
+  return optionsProxy(
+    // @ts-ignore Ignore warning
+    (name) => __InternalRsvimGlobalObject.win_get_option(id, name),
+    (name, value) => {
+      // @ts-ignore Ignore warning
+      __InternalRsvimGlobalObject.win_set_option(id, name, value);
+    },
+    id === 0 ? winOptions : undefined,
+  );
+}
+
+// Create the options object of the `target`, `""` is `:set`, `"global"` is `:setglobal`.
+function options(target: string): RsvimOptions {
+  return optionsProxy(
+    // @ts-ignore Ignore warning
+    (name) => __InternalRsvimGlobalObject.opt_get(name, target),
+    (name, value) => {
+      // @ts-ignore Ignore warning
+      __InternalRsvimGlobalObject.opt_set(name, target, value);
+    },
+  );
+}
+
+// Convert the window `id` argument of the `api`: `0` (or `undefined`) is current window.
+function winIdArg(api: string, id: number | undefined): number {
+  if (id === undefined) {
+    return 0;
+  }
+  if (!Number.isInteger(id) || id < 0) {
+    throw new Error(
+      `"${api}" id must be a non-negative integer, but found ${id} (${typeof id})`,
+    );
+  }
+  return id;
+}
+
+/**
+ * The size of a window, i.e. the text area.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimWinSize {
+  width: number;
+  height: number;
+}
+
+/**
+ * The position of a window on the terminal, i.e. the top-left corner. Both are 0-based.
+ *
+ * @category Editor APIs
+ */
+export interface RsvimWinPosition {
+  row: number;
+  column: number;
+}
+
+/**
+ * The `Rsvim.win` global object for windows.
+ *
+ * A window is identified by its ID, the ID `0` (or omitting it) is current window, i.e. the
+ * window that has the cursor. The lines, chars and columns are 0-based.
+ *
+ * @example
+ * ```javascript
+ * // Create a alias to 'Rsvim.win'.
+ * const win = Rsvim.win;
+ * ```
+ *
+ * @category Editor APIs
+ * @hideconstructor
+ */
+export class RsvimWin {
+  /**
+   * Get current window ID, i.e. the window that has the cursor.
+   *
+   * @returns {number | null} The window ID, `null` if there is no window.
+   */
+  current(): number | null {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.win_current();
+  }
+
+  /**
+   * List all the window IDs.
+   *
+   * @returns {number[]}
+   */
+  list(): number[] {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.win_list();
+  }
+
+  /**
+   * Get the cursor position of the window.
+   *
+   * @param {number} id - The window ID, by default it is current window.
+   * @returns {RsvimBufPosition} The position `[line, char]` in the buffer of the window.
+   * @throws Throws {@link !Error} if id is invalid, or the window is not found.
+   */
+  getCursor(id?: number): RsvimBufPosition {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.win_get_cursor(
+      winIdArg("Rsvim.win.getCursor", id),
+    );
+  }
+
+  /**
+   * Move the cursor of the window, the char is clamped into the line. The window scrolls to keep
+   * the cursor visible.
+   *
+   * @param {number} id - The window ID, `0` is current window.
+   * @param {number} line - The line.
+   * @param {number} char - The char on the line.
+   * @throws Throws {@link !Error} if any argument is invalid, the window is not found, or the line is out of range.
+   *
+   * @example
+   * ```javascript
+   * // Move the cursor to the first line.
+   * Rsvim.win.setCursor(0, 0, 0);
+   * ```
+   */
+  setCursor(id: number, line: number, char: number): void {
+    positionsArg("Rsvim.win.setCursor", [line, char]);
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.win_set_cursor(
+      winIdArg("Rsvim.win.setCursor", id),
+      line,
+      char,
+    );
+  }
+
+  /**
+   * Get the scroll position of the window, i.e. the first line and the first column shown in the
+   * window.
+   *
+   * @param {number} id - The window ID, by default it is current window.
+   * @returns {[number, number]} The position `[line, column]`.
+   * @throws Throws {@link !Error} if id is invalid, or the window is not found.
+   */
+  getScroll(id?: number): [number, number] {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.win_get_scroll(
+      winIdArg("Rsvim.win.getScroll", id),
+    );
+  }
+
+  /**
+   * Scroll the window, the line is shown at the top of the window and the column is shown at the
+   * left. The cursor moves into the window if it is not shown, the same as scrolling with the
+   * mouse wheel.
+   *
+   * @param {number} id - The window ID, `0` is current window.
+   * @param {number} line - The line.
+   * @param {number} column - The column, it is only used when the 'wrap' option is `false`.
+   * @throws Throws {@link !Error} if any argument is invalid, the window is not found, or the line is out of range.
+   *
+   * @example
+   * ```javascript
+   * // Show the line 100 at the top of current window.
+   * Rsvim.win.setScroll(0, 100, 0);
+   * ```
+   */
+  setScroll(id: number, line: number, column: number): void {
+    positionsArg("Rsvim.win.setScroll", [line, column]);
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.win_set_scroll(
+      winIdArg("Rsvim.win.setScroll", id),
+      line,
+      column,
+    );
+  }
+
+  /**
+   * Get the size of the window, i.e. the text area.
+   *
+   * @param {number} id - The window ID, by default it is current window.
+   * @returns {RsvimWinSize}
+   * @throws Throws {@link !Error} if id is invalid, or the window is not found.
+   */
+  getSize(id?: number): RsvimWinSize {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.win_get_size(
+      winIdArg("Rsvim.win.getSize", id),
+    );
+  }
+
+  /**
+   * Get the position of the window on the terminal.
+   *
+   * @param {number} id - The window ID, by default it is current window.
+   * @returns {RsvimWinPosition}
+   * @throws Throws {@link !Error} if id is invalid, or the window is not found.
+   */
+  getPosition(id?: number): RsvimWinPosition {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.win_get_position(
+      winIdArg("Rsvim.win.getPosition", id),
+    );
+  }
+
+  /**
+   * Get the window-local option of the window, i.e. `wrap`. The option name can be the short
+   * alias.
+   *
+   * @param {number} id - The window ID, `0` is current window.
+   * @param {string} name - The option name.
+   * @returns {boolean | number | string} The option value.
+   * @throws Throws {@link !Error} if any argument is invalid, the window is not found, or the option is not a window-local option.
+   *
+   * @example
+   * ```javascript
+   * const wrap = Rsvim.win.getOption(0, "wrap");
+   * ```
+   */
+  getOption(id: number, name: string): boolean | number | string {
+    if (typeof name !== "string") {
+      throw new Error(
+        `"Rsvim.win.getOption" name must be a string, but found ${name} (${typeof name})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.win_get_option(
+      winIdArg("Rsvim.win.getOption", id),
+      name,
+    );
+  }
+
+  /**
+   * Set the window-local option of the window, i.e. `:setlocal` in the window.
+   *
+   * @param {number} id - The window ID, `0` is current window.
+   * @param {string} name - The option name.
+   * @param {boolean | number | string} value - The option value.
+   * @throws Throws {@link !Error} if any argument is invalid, the window is not found, the option is not a window-local option, or the option doesn't accept the value.
+   *
+   * @example
+   * ```javascript
+   * Rsvim.win.setOption(0, "wrap", false);
+   * ```
+   */
+  setOption(
+    id: number,
+    name: string,
+    value: boolean | number | string,
+  ): void {
+    if (typeof name !== "string") {
+      throw new Error(
+        `"Rsvim.win.setOption" name must be a string, but found ${name} (${typeof name})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.win_set_option(
+      winIdArg("Rsvim.win.setOption", id),
+      name,
+      value,
+    );
+  }
+
+  /**
+   * Get the buffer ID shown in the window.
+   *
+   * @param {number} id - The window ID, by default it is current window.
+   * @returns {number}
+   * @throws Throws {@link !Error} if id is invalid, or the window is not found.
+   */
+  getBuffer(id?: number): number {
+    // @ts-ignore Ignore warning
+    return __InternalRsvimGlobalObject.win_get_buffer(
+      winIdArg("Rsvim.win.getBuffer", id),
+    );
+  }
+
+  /**
+   * Show the buffer in the window, the cursor moves to the start of the buffer.
+   *
+   * @param {number} id - The window ID, `0` is current window.
+   * @param {number} buffer - The buffer ID, see {@link RsvimBuf.list}.
+   * @throws Throws {@link !Error} if any argument is invalid, or the window or the buffer is not found.
+   *
+   * @example
+   * ```javascript
+   * // Open the file in current window.
+   * Rsvim.win.setBuffer(0, Rsvim.buf.create("README.md"));
+   * ```
+   */
+  setBuffer(id: number, buffer: number): void {
+    if (!Number.isInteger(buffer) || buffer <= 0) {
+      throw new Error(
+        `"Rsvim.win.setBuffer" buffer must be a positive integer, but found ${buffer} (${typeof buffer})`,
+      );
+    }
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.win_set_buffer(
+      winIdArg("Rsvim.win.setBuffer", id),
+      buffer,
+    );
+  }
+
+  /**
+   * Focus the window, i.e. move the cursor into the window, it becomes current window.
+   *
+   * @param {number} id - The window ID.
+   * @throws Throws {@link !Error} if id is invalid, or the window is not found.
+   */
+  focus(id: number): void {
+    // @ts-ignore Ignore warning
+    __InternalRsvimGlobalObject.win_focus(winIdArg("Rsvim.win.focus", id));
   }
 }
 
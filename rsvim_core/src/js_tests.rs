@@ -1,6 +1,524 @@
 use super::js::*;
 
+use crate::buf::BuffersManagerArc;
+use crate::buf::opt::{BufferLocalOptionsBuilder, FileFormatOption};
+use crate::content::message::MessageLevel;
+use crate::defaults;
+use crate::excommand::ExCommandArgs;
+use crate::js::binding::global_rsvim::cmd::ExCommandFuture;
+use crate::js::msg::ExCommandReq;
+use crate::prelude::*;
+use crate::state::autocmd::AutoCmdEvent;
+use crate::test::buf::{make_buffer_from_lines, make_buffers_manager};
+use crate::test::constant::acquire_sequential_guard;
+use crate::test::js::make_js_runtime_with_tree;
+use crate::test::log::init as test_log_init;
+use crate::test::tree::make_tree_with_buffers;
+use crate::ui::tree::{TreeArc, TreeNode};
+use crate::ui::widget::window::{Window, WindowLocalOptionsBuilder};
+
+use assert_fs::TempDir;
+
 #[test]
 fn next_future_id1() {
   assert!(next_future_id() > 0);
 }
+
+// The assertion helper used by the js sources.
+const ASSERT_JS: &str = r#"
+function assertEq(actual, expected, msg) {
+  const a = JSON.stringify(actual);
+  const e = JSON.stringify(expected);
+  if (a !== e) {
+    throw new Error(`${msg}: expected ${e}, but found ${a}`);
+  }
+}
+function assertThrows(f, type, msg) {
+  try {
+    f();
+  } catch (e) {
+    if (!(e instanceof type)) {
+      throw new Error(`${msg}: expected ${type.name}, but found ${e}`);
+    }
+    return;
+  }
+  throw new Error(`${msg}: expected ${type.name}, but nothing is thrown`);
+}
+"#;
+
+// Make a js runtime with 1 window of `canvas_size`, and the buffers of `lines`. The first buffer
+// is shown in the window.
+fn make_js_runtime_with_buffers(
+  canvas_size: U16Size,
+  lines: Vec<Vec<&str>>,
+) -> (JsRuntime, TreeArc, BuffersManagerArc) {
+  let buf_opts = BufferLocalOptionsBuilder::default().build().unwrap();
+  let bufs = lines
+    .into_iter()
+    .map(|lines| make_buffer_from_lines(canvas_size, buf_opts, lines))
+    .collect();
+  let buffers = make_buffers_manager(buf_opts, bufs);
+  let tree = make_tree_with_buffers(
+    canvas_size,
+    WindowLocalOptionsBuilder::default().build().unwrap(),
+    buffers.clone(),
+  );
+  let jsrt =
+    make_js_runtime_with_tree(canvas_size, tree.clone(), buffers.clone());
+  (jsrt, tree, buffers)
+}
+
+// Execute the js source as a module, it fails if the source throws.
+fn execute(jsrt: &mut JsRuntime, src: &str) -> Result<(), AnyErr> {
+  let tmpdir = TempDir::new().unwrap();
+  let path = tmpdir.join("test.js");
+  std::fs::write(&path, format!("{ASSERT_JS}{src}")).unwrap();
+  jsrt.execute_module(path.to_str().unwrap(), None)
+}
+
+#[test]
+fn win_cursor_scroll1() {
+  let _guard = acquire_sequential_guard();
+  test_log_init();
+
+  let lines: Vec<String> = (0..20).map(|i| format!("line{i}\n")).collect();
+  let lines: Vec<&str> = lines.iter().map(|l| l.as_str()).collect();
+  let (mut jsrt, tree, _buffers) =
+    make_js_runtime_with_buffers(U16Size::new(10, 5), vec![lines]);
+
+  let src = r#"
+  const win = Rsvim.win;
+  const id = win.current();
+  assertEq(win.list(), [id], "list");
+  assertEq(win.getCursor(), [0, 0], "getCursor");
+  assertEq(win.getScroll(), [0, 0], "getScroll");
+
+  win.setCursor(0, 10, 3);
+  assertEq(win.getCursor(id), [10, 3], "setCursor");
+  assertEq(win.getScroll(id)[0] > 0, true, "setCursor scrolls");
+
+  // The char is clamped into the line.
+  win.setCursor(id, 2, 100);
+  assertEq(win.getCursor(), [2, 4], "setCursor clamped");
+
+  win.setScroll(0, 12, 0);
+  assertEq(win.getScroll(), [12, 0], "setScroll");
+  assertEq(win.getCursor(), [12, 4], "setScroll moves cursor");
+
+  assertThrows(() => win.setCursor(0, 20, 0), RangeError, "setCursor out of range");
+  assertThrows(() => win.setScroll(0, 20, 0), RangeError, "setScroll out of range");
+  assertThrows(() => win.getCursor(-1), Error, "getCursor invalid id");
+  assertThrows(() => win.getCursor(987654), TypeError, "getCursor not found");
+  "#;
+  execute(&mut jsrt, src).unwrap();
+
+  let tree = lock!(tree);
+  let cursor_viewport = tree.current_window().unwrap().cursor_viewport();
+  assert_eq!(cursor_viewport.line_idx(), 12);
+  assert_eq!(cursor_viewport.char_idx(), 4);
+}
+
+#[test]
+fn win_info_option1() {
+  let _guard = acquire_sequential_guard();
+  test_log_init();
+
+  let (mut jsrt, tree, _buffers) =
+    make_js_runtime_with_buffers(U16Size::new(10, 5), vec![vec!["hello\n"]]);
+
+  let src = r#"
+  const win = Rsvim.win;
+  assertEq(win.getSize(), { width: 10, height: 5 }, "getSize");
+  assertEq(win.getPosition(), { row: 0, column: 0 }, "getPosition");
+  assertEq(win.getBuffer(), Rsvim.buf.current(), "getBuffer");
+
+  assertEq(win.getOption(0, "wrap"), true, "getOption");
+  win.setOption(0, "wrap", false);
+  assertEq(win.getOption(0, "wrap"), false, "setOption");
+  win.setOption(0, "so", 2);
+  assertEq(win.getOption(0, "scrolloff"), 2, "setOption alias");
+
+  assertThrows(() => win.getOption(0, "tabstop"), TypeError, "buffer option");
+  assertThrows(() => win.setOption(0, "wrap", 1), TypeError, "option type");
+  "#;
+  execute(&mut jsrt, src).unwrap();
+
+  let tree = lock!(tree);
+  let options = tree.current_window().unwrap().options();
+  assert!(!options.wrap());
+  assert_eq!(options.scroll_off(), 2);
+}
+
+#[test]
+fn win_buffer_focus1() {
+  let _guard = acquire_sequential_guard();
+  test_log_init();
+
+  let (mut jsrt, tree, buffers) = make_js_runtime_with_buffers(
+    U16Size::new(10, 5),
+    vec![vec!["hello\n"], vec!["world\n", "bye\n"]],
+  );
+
+  // The second window shows the same buffer.
+  let window_id = {
+    let mut tree = lock!(tree);
+    let buf = tree.current_window().unwrap().buffer();
+    let window = Window::new(
+      tree.global_local_options(),
+      IRect::new((0, 3), (10, 5)),
+      buf,
+    );
+    let window_id = window.id();
+    let root_id = tree.root_id();
+    tree.bounded_insert(root_id, TreeNode::Window(window));
+    window_id
+  };
+
+  let src = format!(
+    r#"
+  const win = Rsvim.win;
+  const other = {window_id};
+  assertEq(win.list().includes(other), true, "list");
+  assertEq(win.getPosition(other), {{ row: 3, column: 0 }}, "getPosition");
+
+  const buffers = Rsvim.buf.list();
+  const buffer = buffers.find((b) => b !== win.getBuffer());
+  win.setCursor(0, 0, 3);
+  win.setBuffer(0, buffer);
+  assertEq(win.getBuffer(), buffer, "setBuffer");
+  assertEq(win.getCursor(), [0, 0], "setBuffer resets cursor");
+  assertThrows(() => win.setBuffer(0, 987654), TypeError, "buffer not found");
+
+  win.focus(other);
+  assertEq(win.current(), other, "focus");
+  "#
+  );
+  execute(&mut jsrt, &src).unwrap();
+
+  let tree = lock!(tree);
+  assert_eq!(tree.current_window_id(), Some(window_id));
+  assert!(tree.current_window().unwrap().cursor_id().is_some());
+  let buffer_ids: Vec<_> = lock!(buffers).keys().copied().collect();
+  let first_window_id = *tree
+    .window_ids()
+    .iter()
+    .find(|id| **id != window_id)
+    .unwrap();
+  let buffer = tree.window(first_window_id).unwrap().buffer().upgrade();
+  assert_eq!(lock!(buffer.unwrap()).id(), buffer_ids[1]);
+}
+
+#[test]
+fn buf_integer_args1() {
+  let _guard = acquire_sequential_guard();
+  test_log_init();
+
+  let (mut jsrt, _tree, buffers) =
+    make_js_runtime_with_buffers(U16Size::new(10, 5), vec![vec!["hello\n"]]);
+
+  // The internal APIs throw instead of clamping the invalid integers.
+  let src = r#"
+  const internal = __InternalRsvimGlobalObject;
+  assertEq(internal.buf_get_lines(0, 0, -1), ["hello"], "getLines");
+  assertThrows(() => internal.buf_get_lines(-1, 0, -1), RangeError, "negative id");
+  assertThrows(() => internal.buf_get_lines(2 ** 40, 0, -1), RangeError, "id out of range");
+  assertThrows(() => internal.buf_get_lines(0, -1, -1), RangeError, "negative start");
+  assertThrows(() => internal.buf_insert(0, 0, 1.5, "x"), RangeError, "fractional char");
+  assertThrows(() => internal.buf_select(0, 0, -1, 0), RangeError, "negative select");
+  "#;
+  execute(&mut jsrt, src).unwrap();
+
+  let buffer = lock!(buffers).first_key_value().unwrap().1.clone();
+  assert_eq!(lock!(buffer).text().rope().to_string(), "hello\n");
+}
+
+#[test]
+fn opt_global_local1() {
+  let _guard = acquire_sequential_guard();
+  test_log_init();
+
+  let (mut jsrt, tree, buffers) =
+    make_js_runtime_with_buffers(U16Size::new(10, 5), vec![vec!["hello\n"]]);
+
+  let src = r#"
+  // `Rsvim.opt` sets both current window and the global value.
+  assertEq(Rsvim.opt.wrap, true, "opt.wrap");
+  Rsvim.opt.wrap = false;
+  assertEq(Rsvim.wo.wrap, false, "opt.wrap local");
+  assertEq(Rsvim.o.wrap, false, "opt.wrap global");
+
+  // `Rsvim.o` only sets the global value.
+  Rsvim.o.lineBreak = true;
+  assertEq(Rsvim.o.lineBreak, true, "o.lineBreak");
+  assertEq(Rsvim.opt.lineBreak, false, "o.lineBreak local");
+
+  // The camel case names, the Vim names and the aliases.
+  Rsvim.opt.tabStop = 4;
+  assertEq(Rsvim.opt.tabstop, 4, "opt.tabstop");
+  assertEq(Rsvim.opt.ts, 4, "opt.ts");
+  Rsvim.opt.history = 50;
+  assertEq(Rsvim.o.history, 50, "opt.history");
+
+  assertThrows(() => Rsvim.opt.noSuchOption, TypeError, "not found");
+  assertThrows(() => { Rsvim.opt.wrap = 1; }, TypeError, "wrap type");
+  assertThrows(() => { Rsvim.opt.tabStop = 1.5; }, TypeError, "tabStop type");
+  assertThrows(() => { Rsvim.opt.tabStop = 0; }, RangeError, "tabStop range");
+  assertThrows(() => { Rsvim.o.fileFormat = "abc"; }, RangeError, "fileFormat range");
+  "#;
+  execute(&mut jsrt, src).unwrap();
+
+  let tree = lock!(tree);
+  assert!(!tree.current_window().unwrap().options().wrap());
+  assert!(!tree.global_local_options().wrap());
+  assert!(tree.global_local_options().line_break());
+  assert!(!tree.current_window().unwrap().options().line_break());
+  let buffers = lock!(buffers);
+  assert_eq!(buffers.global_local_options().tab_stop(), 4);
+  let buffer = tree.current_window().unwrap().buffer().upgrade().unwrap();
+  assert_eq!(lock!(buffer).options().tab_stop(), 4);
+  let editing_state = jsrt.get_state().borrow().editing_state.clone();
+  assert_eq!(lock!(editing_state).global_options().history(), 50);
+}
+
+#[test]
+fn opt_bo_wo1() {
+  let _guard = acquire_sequential_guard();
+  test_log_init();
+
+  let (mut jsrt, tree, buffers) = make_js_runtime_with_buffers(
+    U16Size::new(10, 5),
+    vec![vec!["hello\n"], vec!["world\n"]],
+  );
+
+  let src = r#"
+  const current = Rsvim.buf.current();
+  const other = Rsvim.buf.list().find((b) => b !== current);
+
+  Rsvim.bo.tabStop = 2;
+  assertEq(Rsvim.bo.tabStop, 2, "bo.tabStop");
+  assertEq(Rsvim.bo[current].tabStop, 2, "bo[current].tabStop");
+  assertEq(Rsvim.bo[other].tabStop, 8, "bo[other].tabStop");
+  Rsvim.bo[other].fileFormat = "dos";
+  assertEq(Rsvim.bo[other].ff, "dos", "bo[other].ff");
+  assertEq(Rsvim.o.tabStop, 8, "bo doesn't set global");
+
+  const win = Rsvim.win.current();
+  Rsvim.wo.scrollOff = 2;
+  assertEq(Rsvim.wo[win].scrollOff, 2, "wo[win].scrollOff");
+  Rsvim.wo[win].wrap = false;
+  assertEq(Rsvim.wo.wrap, false, "wo.wrap");
+  assertEq(Rsvim.o.wrap, true, "wo doesn't set global");
+
+  assertThrows(() => Rsvim.bo.wrap, TypeError, "bo window option");
+  assertThrows(() => { Rsvim.wo.tabStop = 4; }, TypeError, "wo buffer option");
+  assertThrows(() => Rsvim.bo[987654].tabStop, TypeError, "bo not found");
+  assertThrows(() => Rsvim.wo[987654].wrap, TypeError, "wo not found");
+  assertThrows(() => { Rsvim.wo.scrollOff = -1; }, RangeError, "scrollOff range");
+  "#;
+  execute(&mut jsrt, src).unwrap();
+
+  let tree = lock!(tree);
+  let options = tree.current_window().unwrap().options();
+  assert!(!options.wrap());
+  assert_eq!(options.scroll_off(), 2);
+  assert!(tree.global_local_options().wrap());
+  let buffers = lock!(buffers);
+  let formats: Vec<_> = buffers
+    .values()
+    .map(|b| {
+      let b = lock!(b);
+      (b.options().tab_stop(), b.options().file_format())
+    })
+    .collect();
+  assert_eq!(
+    formats,
+    vec![(2, defaults::buf::FILE_FORMAT), (8, FileFormatOption::Dos)]
+  );
+}
+
+#[test]
+fn opt_option_set1() {
+  let _guard = acquire_sequential_guard();
+  test_log_init();
+
+  let (mut jsrt, _tree, _buffers) =
+    make_js_runtime_with_buffers(U16Size::new(10, 5), vec![vec!["hello\n"]]);
+
+  let src = r#"
+  Rsvim.autocmd.create("OptionSet", () => {});
+  Rsvim.opt.wrap = false;
+  // The same value fires nothing.
+  Rsvim.opt.wrap = false;
+  Rsvim.bo.tabStop = 4;
+  Rsvim.wo.scrollOff = 1;
+  Rsvim.o.mouse = "a";
+  // The local value is the same, but the global value is changed.
+  Rsvim.wo.scrollOff = 7;
+  Rsvim.opt.scrollOff = 7;
+  Rsvim.opt.scrollOff = 7;
+  "#;
+  execute(&mut jsrt, src).unwrap();
+
+  let editing_state = jsrt.get_state().borrow().editing_state.clone();
+  let fired: Vec<_> = lock!(editing_state)
+    .autocmds_mut()
+    .take_fired()
+    .into_iter()
+    .map(|fired| (fired.event, fired.data.to_string()))
+    .collect();
+  assert_eq!(
+    fired,
+    vec![
+      (AutoCmdEvent::OptionSet, "wrap".to_string()),
+      (AutoCmdEvent::OptionSet, "tabstop".to_string()),
+      (AutoCmdEvent::OptionSet, "scrolloff".to_string()),
+      (AutoCmdEvent::OptionSet, "mouse".to_string()),
+      (AutoCmdEvent::OptionSet, "scrolloff".to_string()),
+      (AutoCmdEvent::OptionSet, "scrolloff".to_string()),
+    ]
+  );
+}
+
+#[test]
+fn cmd_create_del_list1() {
+  let _guard = acquire_sequential_guard();
+  test_log_init();
+
+  let (mut jsrt, _tree, _buffers) =
+    make_js_runtime_with_buffers(U16Size::new(10, 5), vec![vec!["hello\n"]]);
+
+  let src = r#"
+  const cmd = Rsvim.cmd;
+  cmd.create("Foo", () => {});
+  cmd.create("Bar", () => {}, {
+    nargs: "*",
+    range: "%",
+    bang: true,
+    complete: () => [],
+    desc: "bar",
+  });
+  cmd.create("Baz", () => {}, { nargs: 1, range: 3, complete: "file" });
+  assertEq(
+    cmd.list(),
+    [
+      { name: "Bar", nargs: "*", range: "%", bang: true, complete: "custom", desc: "bar" },
+      { name: "Baz", nargs: "1", range: 3, bang: false, complete: "file", desc: "" },
+      { name: "Foo", nargs: "0", range: false, bang: false, complete: null, desc: "" },
+    ],
+    "list",
+  );
+
+  // Replace the existing command.
+  cmd.create("Foo", () => {}, { range: true });
+  assertEq(cmd.list()[2].range, true, "replace");
+
+  assertThrows(() => cmd.create("foo", () => {}), TypeError, "name");
+  assertThrows(() => cmd.create("Foo-x", () => {}), TypeError, "name chars");
+  assertThrows(() => cmd.create("Foo", () => {}, { nargs: "2" }), TypeError, "nargs");
+  assertThrows(() => cmd.create("Foo", () => {}, { range: "x" }), TypeError, "range");
+  assertThrows(() => cmd.create("Foo", () => {}, { complete: "x" }), TypeError, "complete");
+  assertThrows(() => cmd.create("Foo", "x"), Error, "callback");
+
+  // The internal APIs throw instead of panic.
+  const internal = __InternalRsvimGlobalObject;
+  assertThrows(() => internal.cmd_create("Foo", "x", "0", false, false, "", ""), TypeError, "internal callback");
+  assertThrows(() => internal.cmd_create("Foo"), TypeError, "internal args");
+  assertThrows(() => internal.cmd_set_repeat(null), TypeError, "internal action");
+
+  cmd.del("Baz");
+  assertEq(cmd.list().map((c) => c.name), ["Bar", "Foo"], "del");
+  assertThrows(() => cmd.del("Baz"), TypeError, "del not found");
+  "#;
+  execute(&mut jsrt, src).unwrap();
+
+  let state = jsrt.get_state();
+  let state = state.borrow();
+  assert_eq!(
+    lock!(state.editing_state).ex_commands().names(),
+    vec!["Bar", "Foo"]
+  );
+  // The callbacks of the replaced and deleted commands are removed.
+  assert_eq!(state.ex_command_callbacks.len(), 2);
+  assert!(state.completers.contains_key("Bar"));
+}
+
+#[test]
+fn cmd_callback1() {
+  let _guard = acquire_sequential_guard();
+  test_log_init();
+
+  let (mut jsrt, _tree, _buffers) =
+    make_js_runtime_with_buffers(U16Size::new(10, 5), vec![vec!["hello\n"]]);
+
+  let src = r#"
+  Rsvim.cmd.create("Echo", (a) => JSON.stringify(a), { nargs: "*", range: true, bang: true });
+  Rsvim.cmd.create("Fail", () => { throw new Error("failed"); });
+  Rsvim.cmd.create("Quiet", () => {});
+  "#;
+  execute(&mut jsrt, src).unwrap();
+
+  let run = |jsrt: &mut JsRuntime, name: &str, args: ExCommandArgs| {
+    let (callback_id, cb, contents) = {
+      let state = jsrt.get_state();
+      let state = state.borrow();
+      let callback_id = lock!(state.editing_state)
+        .ex_commands()
+        .get(name)
+        .unwrap()
+        .callback_id();
+      let cb = state.ex_command_callbacks.get(&callback_id).cloned();
+      (callback_id, cb, state.contents.clone())
+    };
+    let req = ExCommandReq::new(next_future_id(), callback_id, args);
+    let scope = &mut jsrt.handle_scope();
+    ExCommandFuture { req, cb }.run(scope);
+    if let Some(error) = check_exceptions(scope) {
+      report_error(scope, &format!("{error:?}"));
+    }
+    let mut contents = lock!(contents);
+    let shown: Vec<_> = contents
+      .messages()
+      .shown()
+      .iter()
+      .map(|m| (m.level(), m.text().to_string()))
+      .collect();
+    contents.messages_mut().clear_shown();
+    shown
+  };
+
+  let args = ExCommandArgs {
+    name: "Echo".into(),
+    args: r"a\ b c".into(),
+    fargs: vec!["a b".into(), "c".into()],
+    range: Some((2, 4)),
+    bang: true,
+    count: None,
+  };
+  assert_eq!(
+    run(&mut jsrt, "Echo", args.clone()),
+    vec![(
+      MessageLevel::Info,
+      r#"{"name":"Echo","args":"a\\ b c","fargs":["a b","c"],"range":[3,5],"bang":true,"count":null}"#
+        .to_string()
+    )]
+  );
+
+  let args = ExCommandArgs {
+    name: "Fail".into(),
+    args: "".into(),
+    fargs: vec![],
+    range: None,
+    bang: false,
+    count: None,
+  };
+  let shown = run(&mut jsrt, "Fail", args.clone());
+  assert_eq!(shown.len(), 1);
+  assert_eq!(shown[0].0, MessageLevel::Error);
+  assert!(shown[0].1.contains("failed"));
+
+  let args = ExCommandArgs {
+    name: "Quiet".into(),
+    ..args
+  };
+  assert!(run(&mut jsrt, "Quiet", args).is_empty());
+}
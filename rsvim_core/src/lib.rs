@@ -11,9 +11,11 @@ pub mod excommand;
 pub mod js;
 pub mod lock;
 pub mod log;
+pub mod opt;
 pub mod prelude;
 pub mod results;
 pub mod state;
+pub mod terminal;
 pub mod ui;
 
 // Only for unit test.
@@ -26,4 +28,10 @@ mod cli_tests;
 #[cfg(test)]
 mod constant_tests;
 #[cfg(test)]
+mod excommand_tests;
+#[cfg(test)]
 mod js_tests;
+#[cfg(test)]
+mod opt_tests;
+#[cfg(test)]
+mod terminal_tests;
@@ -0,0 +1,709 @@
+//! Editor options registry.
+//!
+//! All the editor options, i.e. [`BufferLocalOptions`], [`WindowLocalOptions`] and
+//! [`GlobalOptions`], are described in a single registry (name, short alias, type, scope and
+//! validator). The `:set` ex command family looks up options by their names here, then parses,
+//! validates and updates option values through the registry.
+//!
+//! NOTE: The option names follow Vim, i.e. `tabstop` (short alias `ts`), not `tab_stop`.
+
+use crate::buf::opt::{
+  BufferLocalOptions, BufferLocalOptionsBuilder, FileEncodingOption,
+  FileFormatOption, IsKeywordOption,
+};
+use crate::buf::{BufferArc, BuffersManager};
+use crate::excommand::complete;
+use crate::prelude::*;
+use crate::state::ops::cursor_ops;
+use crate::state::opt::{GlobalOptions, GlobalOptionsBuilder};
+use crate::ui::tree::*;
+use crate::ui::widget::window::{
+  WindowLocalOptions, WindowLocalOptionsBuilder,
+};
+
+use compact_str::{CompactString, ToCompactString};
+use std::fmt::Display;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// Option value type.
+pub enum OptionKind {
+  Boolean,
+  Number,
+  String,
+}
+
+impl Display for OptionKind {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      OptionKind::Boolean => write!(f, "boolean"),
+      OptionKind::Number => write!(f, "number"),
+      OptionKind::String => write!(f, "string"),
+    }
+  }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// Option scope.
+pub enum OptionScope {
+  /// Local to buffer, i.e. [`BufferLocalOptions`].
+  Buffer,
+  /// Local to window, i.e. [`WindowLocalOptions`].
+  Window,
+  /// Global, i.e. [`GlobalOptions`].
+  Global,
+}
+
+impl Display for OptionScope {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      OptionScope::Buffer => write!(f, "buffer"),
+      OptionScope::Window => write!(f, "window"),
+      OptionScope::Global => write!(f, "global"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// Option value.
+pub enum OptionValue {
+  Boolean(bool),
+  Number(i64),
+  String(CompactString),
+}
+
+impl OptionValue {
+  pub fn kind(&self) -> OptionKind {
+    match self {
+      OptionValue::Boolean(_) => OptionKind::Boolean,
+      OptionValue::Number(_) => OptionKind::Number,
+      OptionValue::String(_) => OptionKind::String,
+    }
+  }
+
+  /// Get boolean value.
+  ///
+  /// # Panics
+  ///
+  /// If the value is not a boolean.
+  pub fn as_bool(&self) -> bool {
+    match self {
+      OptionValue::Boolean(b) => *b,
+      _ => unreachable!(),
+    }
+  }
+
+  /// Get number value.
+  ///
+  /// # Panics
+  ///
+  /// If the value is not a number.
+  pub fn as_number(&self) -> i64 {
+    match self {
+      OptionValue::Number(n) => *n,
+      _ => unreachable!(),
+    }
+  }
+
+  /// Get string value.
+  ///
+  /// # Panics
+  ///
+  /// If the value is not a string.
+  pub fn as_str(&self) -> &str {
+    match self {
+      OptionValue::String(s) => s.as_str(),
+      _ => unreachable!(),
+    }
+  }
+}
+
+impl Display for OptionValue {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      OptionValue::Boolean(b) => write!(f, "{b}"),
+      OptionValue::Number(n) => write!(f, "{n}"),
+      OptionValue::String(s) => write!(f, "{s}"),
+    }
+  }
+}
+
+#[derive(Debug, Copy, Clone)]
+/// Getter and setter of an option, they are bound to the options struct of the option scope.
+///
+/// NOTE: The setter is only called with a validated value, i.e. the value type matches the option
+/// type and the validator accepts it.
+pub enum OptionAccessor {
+  Buffer {
+    get: fn(&BufferLocalOptions) -> OptionValue,
+    set: fn(&mut BufferLocalOptions, &OptionValue),
+  },
+  Window {
+    get: fn(&WindowLocalOptions) -> OptionValue,
+    set: fn(&mut WindowLocalOptions, &OptionValue),
+  },
+  Global {
+    get: fn(&GlobalOptions) -> OptionValue,
+    set: fn(&mut GlobalOptions, &OptionValue),
+  },
+}
+
+#[derive(Debug, Copy, Clone)]
+/// Option definition.
+pub struct OptionDef {
+  name: &'static str,
+  alias: Option<&'static str>,
+  kind: OptionKind,
+  accessor: OptionAccessor,
+  validator: fn(&OptionValue) -> bool,
+  list: bool,
+  values: &'static [&'static str],
+}
+
+impl OptionDef {
+  /// Option name.
+  pub fn name(&self) -> &'static str {
+    self.name
+  }
+
+  /// Option short alias, for example `ts` for `tabstop`.
+  pub fn alias(&self) -> Option<&'static str> {
+    self.alias
+  }
+
+  /// Option value type.
+  pub fn kind(&self) -> OptionKind {
+    self.kind
+  }
+
+  /// Option scope.
+  pub fn scope(&self) -> OptionScope {
+    match self.accessor {
+      OptionAccessor::Buffer { .. } => OptionScope::Buffer,
+      OptionAccessor::Window { .. } => OptionScope::Window,
+      OptionAccessor::Global { .. } => OptionScope::Global,
+    }
+  }
+
+  /// Option getter/setter.
+  pub fn accessor(&self) -> &OptionAccessor {
+    &self.accessor
+  }
+
+  /// Whether it is a comma-separated list option, for example `iskeyword`. The `+=`, `^=` and `-=`
+  /// add and remove the items, instead of the chars.
+  pub fn is_list(&self) -> bool {
+    self.list
+  }
+
+  /// All the possible values of a string option that only accepts a set of values, for example
+  /// `dos`, `unix` and `mac` for `fileformat`. It is empty for other options.
+  pub fn values(&self) -> &'static [&'static str] {
+    self.values
+  }
+
+  /// Whether the `name` is either the option name or its short alias.
+  pub fn is_named(&self, name: &str) -> bool {
+    self.name == name || self.alias == Some(name)
+  }
+
+  /// Whether the value is valid for this option, i.e. the value type matches the option type and
+  /// the validator accepts it.
+  pub fn validate(&self, value: &OptionValue) -> bool {
+    value.kind() == self.kind && (self.validator)(value)
+  }
+
+  /// Default value of this option.
+  pub fn default_value(&self) -> OptionValue {
+    match self.accessor {
+      OptionAccessor::Buffer { get, .. } => {
+        get(&BufferLocalOptionsBuilder::default().build().unwrap())
+      }
+      OptionAccessor::Window { get, .. } => {
+        get(&WindowLocalOptionsBuilder::default().build().unwrap())
+      }
+      OptionAccessor::Global { get, .. } => {
+        get(&GlobalOptionsBuilder::default().build().unwrap())
+      }
+    }
+  }
+
+  /// Parse the raw `value` string (i.e. from `:set {option}={value}`) by option type.
+  ///
+  /// NOTE: This method doesn't validate the parsed value, see [`OptionDef::validate`].
+  pub fn parse(&self, value: &str) -> OptionResult<OptionValue> {
+    match self.kind {
+      OptionKind::Boolean => Err(OptionErr::InvalidArgument(value.to_string())),
+      OptionKind::Number => match value.parse::<i64>() {
+        Ok(n) => Ok(OptionValue::Number(n)),
+        Err(_) => Err(OptionErr::NumberRequired(value.to_string())),
+      },
+      OptionKind::String => Ok(OptionValue::String(value.to_compact_string())),
+    }
+  }
+
+  /// Format the option as `{name}={value}` for number/string options, or `{name}`/`no{name}` for
+  /// boolean options, the same as `:set {option}?`.
+  pub fn format(&self, value: &OptionValue) -> String {
+    match value {
+      OptionValue::Boolean(true) => self.name.to_string(),
+      OptionValue::Boolean(false) => format!("no{}", self.name),
+      _ => format!("{}={}", self.name, value),
+    }
+  }
+}
+
+fn _u16_validator(value: &OptionValue) -> bool {
+  let n = value.as_number();
+  n >= 0 && n <= u16::MAX as i64
+}
+
+fn _positive_u16_validator(value: &OptionValue) -> bool {
+  let n = value.as_number();
+  n > 0 && n <= u16::MAX as i64
+}
+
+fn _any_validator(_value: &OptionValue) -> bool {
+  true
+}
+
+/// All the editor options.
+pub static OPTIONS: [OptionDef; 19] = [
+  // Buffer {
+  OptionDef {
+    name: "tabstop",
+    alias: Some("ts"),
+    kind: OptionKind::Number,
+    accessor: OptionAccessor::Buffer {
+      get: |opts| OptionValue::Number(opts.tab_stop() as i64),
+      set: |opts, value| opts.set_tab_stop(value.as_number() as u16),
+    },
+    validator: _positive_u16_validator,
+    list: false,
+    values: &[],
+  },
+  OptionDef {
+    name: "fileencoding",
+    alias: Some("fenc"),
+    kind: OptionKind::String,
+    accessor: OptionAccessor::Buffer {
+      get: |opts| OptionValue::String(opts.file_encoding().to_compact_string()),
+      set: |opts, value| {
+        opts.set_file_encoding(
+          FileEncodingOption::try_from(value.as_str()).unwrap(),
+        )
+      },
+    },
+    validator: |value| FileEncodingOption::try_from(value.as_str()).is_ok(),
+    list: false,
+    values: &["utf-8"],
+  },
+  OptionDef {
+    name: "fileformat",
+    alias: Some("ff"),
+    kind: OptionKind::String,
+    accessor: OptionAccessor::Buffer {
+      get: |opts| OptionValue::String(opts.file_format().to_compact_string()),
+      set: |opts, value| {
+        opts
+          .set_file_format(FileFormatOption::try_from(value.as_str()).unwrap())
+      },
+    },
+    validator: |value| FileFormatOption::try_from(value.as_str()).is_ok(),
+    list: false,
+    values: &["dos", "unix", "mac"],
+  },
+  OptionDef {
+    name: "iskeyword",
+    alias: Some("isk"),
+    kind: OptionKind::String,
+    accessor: OptionAccessor::Buffer {
+      get: |opts| OptionValue::String(opts.is_keyword().to_compact_string()),
+      set: |opts, value| {
+        opts.set_is_keyword(IsKeywordOption::try_from(value.as_str()).unwrap())
+      },
+    },
+    validator: |value| IsKeywordOption::try_from(value.as_str()).is_ok(),
+    list: true,
+    values: &[],
+  },
+  // Buffer }
+  // Window {
+  OptionDef {
+    name: "wrap",
+    alias: None,
+    kind: OptionKind::Boolean,
+    accessor: OptionAccessor::Window {
+      get: |opts| OptionValue::Boolean(opts.wrap()),
+      set: |opts, value| opts.set_wrap(value.as_bool()),
+    },
+    validator: _any_validator,
+    list: false,
+    values: &[],
+  },
+  OptionDef {
+    name: "linebreak",
+    alias: Some("lbr"),
+    kind: OptionKind::Boolean,
+    accessor: OptionAccessor::Window {
+      get: |opts| OptionValue::Boolean(opts.line_break()),
+      set: |opts, value| opts.set_line_break(value.as_bool()),
+    },
+    validator: _any_validator,
+    list: false,
+    values: &[],
+  },
+  OptionDef {
+    name: "scrolloff",
+    alias: Some("so"),
+    kind: OptionKind::Number,
+    accessor: OptionAccessor::Window {
+      get: |opts| OptionValue::Number(opts.scroll_off() as i64),
+      set: |opts, value| opts.set_scroll_off(value.as_number() as u16),
+    },
+    validator: _u16_validator,
+    list: false,
+    values: &[],
+  },
+  // Window }
+  // Global {
+  OptionDef {
+    name: "history",
+    alias: Some("hi"),
+    kind: OptionKind::Number,
+    accessor: OptionAccessor::Global {
+      get: |opts| OptionValue::Number(opts.history() as i64),
+      set: |opts, value| opts.set_history(value.as_number() as u16),
+    },
+    validator: |value| (0..=10000).contains(&value.as_number()),
+    list: false,
+    values: &[],
+  },
+  OptionDef {
+    name: "msghistory",
+    alias: Some("mhi"),
+    kind: OptionKind::Number,
+    accessor: OptionAccessor::Global {
+      get: |opts| OptionValue::Number(opts.message_history() as i64),
+      set: |opts, value| opts.set_message_history(value.as_number() as u16),
+    },
+    validator: |value| (0..=10000).contains(&value.as_number()),
+    list: false,
+    values: &[],
+  },
+  OptionDef {
+    name: "wildmode",
+    alias: Some("wim"),
+    kind: OptionKind::String,
+    accessor: OptionAccessor::Global {
+      get: |opts| OptionValue::String(opts.wild_mode().to_compact_string()),
+      set: |opts, value| opts.set_wild_mode(value.as_str()),
+    },
+    validator: |value| complete::parse_wild_mode(value.as_str()).is_some(),
+    list: false,
+    values: &[
+      "full",
+      "longest",
+      "longest:full",
+      "list",
+      "list:full",
+      "list:longest",
+    ],
+  },
+  OptionDef {
+    name: "wildignore",
+    alias: Some("wig"),
+    kind: OptionKind::String,
+    accessor: OptionAccessor::Global {
+      get: |opts| OptionValue::String(opts.wild_ignore().to_compact_string()),
+      set: |opts, value| opts.set_wild_ignore(value.as_str()),
+    },
+    validator: _any_validator,
+    list: true,
+    values: &[],
+  },
+  OptionDef {
+    name: "wildoptions",
+    alias: Some("wop"),
+    kind: OptionKind::String,
+    accessor: OptionAccessor::Global {
+      get: |opts| OptionValue::String(opts.wild_options().to_compact_string()),
+      set: |opts, value| opts.set_wild_options(value.as_str()),
+    },
+    validator: |value| {
+      value
+        .as_str()
+        .split(',')
+        .all(|item| item.is_empty() || item == "pum")
+    },
+    list: true,
+    values: &["pum"],
+  },
+  OptionDef {
+    name: "timeout",
+    alias: Some("to"),
+    kind: OptionKind::Boolean,
+    accessor: OptionAccessor::Global {
+      get: |opts| OptionValue::Boolean(opts.timeout()),
+      set: |opts, value| opts.set_timeout(value.as_bool()),
+    },
+    validator: _any_validator,
+    list: false,
+    values: &[],
+  },
+  OptionDef {
+    name: "timeoutlen",
+    alias: Some("tm"),
+    kind: OptionKind::Number,
+    accessor: OptionAccessor::Global {
+      get: |opts| OptionValue::Number(opts.timeout_len() as i64),
+      set: |opts, value| opts.set_timeout_len(value.as_number() as u32),
+    },
+    validator: |value| (0..=u32::MAX as i64).contains(&value.as_number()),
+    list: false,
+    values: &[],
+  },
+  OptionDef {
+    name: "showcmd",
+    alias: Some("sc"),
+    kind: OptionKind::Boolean,
+    accessor: OptionAccessor::Global {
+      get: |opts| OptionValue::Boolean(opts.show_cmd()),
+      set: |opts, value| opts.set_show_cmd(value.as_bool()),
+    },
+    validator: _any_validator,
+    list: false,
+    values: &[],
+  },
+  OptionDef {
+    name: "updatetime",
+    alias: Some("ut"),
+    kind: OptionKind::Number,
+    accessor: OptionAccessor::Global {
+      get: |opts| OptionValue::Number(opts.update_time() as i64),
+      set: |opts, value| opts.set_update_time(value.as_number() as u32),
+    },
+    validator: |value| (0..=u32::MAX as i64).contains(&value.as_number()),
+    list: false,
+    values: &[],
+  },
+  OptionDef {
+    name: "keymodel",
+    alias: Some("km"),
+    kind: OptionKind::String,
+    accessor: OptionAccessor::Global {
+      get: |opts| OptionValue::String(opts.key_model().to_compact_string()),
+      set: |opts, value| opts.set_key_model(value.as_str()),
+    },
+    validator: |value| {
+      value
+        .as_str()
+        .split(',')
+        .all(|item| matches!(item, "" | "startsel" | "stopsel"))
+    },
+    list: true,
+    values: &["startsel", "stopsel"],
+  },
+  OptionDef {
+    name: "selectmode",
+    alias: Some("slm"),
+    kind: OptionKind::String,
+    accessor: OptionAccessor::Global {
+      get: |opts| OptionValue::String(opts.select_mode().to_compact_string()),
+      set: |opts, value| opts.set_select_mode(value.as_str()),
+    },
+    validator: |value| {
+      value
+        .as_str()
+        .split(',')
+        .all(|item| matches!(item, "" | "mouse" | "key" | "cmd"))
+    },
+    list: true,
+    values: &["mouse", "key", "cmd"],
+  },
+  OptionDef {
+    name: "mouse",
+    alias: None,
+    kind: OptionKind::String,
+    accessor: OptionAccessor::Global {
+      get: |opts| OptionValue::String(opts.mouse().to_compact_string()),
+      set: |opts, value| opts.set_mouse(value.as_str()),
+    },
+    validator: |value| value.as_str().chars().all(|c| "anvicr".contains(c)),
+    list: false,
+    values: &["a", "n", "v", "i", "c", "r"],
+  },
+  // Global }
+];
+
+/// Find option definition by its name or short alias.
+pub fn find_option(name: &str) -> Option<&'static OptionDef> {
+  OPTIONS.iter().find(|def| def.is_named(name))
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// Which option value to read/write, follows Vim's `:set`, `:setlocal` and `:setglobal`.
+///
+/// For local options, the "local" value is the value of current window (or the buffer of current
+/// window), the "global" value is the global-local default value that newly created
+/// windows/buffers are initialized with. For global options, there's only one global value.
+pub enum OptionTarget {
+  /// Read the local value, write both local and global values (`:set`).
+  LocalAndGlobal,
+  /// Read/write the local value only (`:setlocal`).
+  Local,
+  /// Read/write the global value only (`:setglobal`).
+  Global,
+}
+
+/// Get option value from the editor.
+///
+/// NOTE: When there's no current window, the local value falls back to the global value.
+pub fn get_option_value(
+  tree: &Tree,
+  buffers: &BuffersManager,
+  global_options: &GlobalOptions,
+  def: &OptionDef,
+  target: OptionTarget,
+) -> OptionValue {
+  let local = !matches!(target, OptionTarget::Global);
+  match def.accessor() {
+    OptionAccessor::Buffer { get, .. } => {
+      if local {
+        if let Some(buffer) =
+          tree.current_window().and_then(|w| w.buffer().upgrade())
+        {
+          return get(lock!(buffer).options());
+        }
+      }
+      get(buffers.global_local_options())
+    }
+    OptionAccessor::Window { get, .. } => {
+      if local {
+        if let Some(window) = tree.current_window() {
+          return get(window.options());
+        }
+      }
+      get(tree.global_local_options())
+    }
+    OptionAccessor::Global { get, .. } => get(global_options),
+  }
+}
+
+/// Get the values of all the [`OPTIONS`] from the editor, in the same order.
+pub fn get_option_values(
+  tree: &Tree,
+  buffers: &BuffersManager,
+  global_options: &GlobalOptions,
+  target: OptionTarget,
+) -> Vec<OptionValue> {
+  OPTIONS
+    .iter()
+    .map(|def| get_option_value(tree, buffers, global_options, def, target))
+    .collect()
+}
+
+/// Set option value to the editor, the value must be validated by [`OptionDef::validate`].
+///
+/// The viewports of affected windows are re-synced after local values are changed, i.e. the
+/// current window for window options, all the windows bound to the buffer for buffer options.
+pub fn set_option_value(
+  tree: &mut Tree,
+  buffers: &mut BuffersManager,
+  global_options: &mut GlobalOptions,
+  def: &OptionDef,
+  target: OptionTarget,
+  value: &OptionValue,
+) {
+  debug_assert!(def.validate(value));
+  let (local, global) = match target {
+    OptionTarget::LocalAndGlobal => (true, true),
+    OptionTarget::Local => (true, false),
+    OptionTarget::Global => (false, true),
+  };
+
+  match def.accessor() {
+    OptionAccessor::Buffer { set, .. } => {
+      if global {
+        set(buffers.global_local_options_mut(), value);
+      }
+      if local {
+        let buffer = tree.current_window().and_then(|w| w.buffer().upgrade());
+        if let Some(buffer) = buffer {
+          set_buffer_option_value(tree, &buffer, def, value);
+        }
+      }
+    }
+    OptionAccessor::Window { set, .. } => {
+      if global {
+        set(tree.global_local_options_mut(), value);
+      }
+      if local {
+        if let Some(window_id) = tree.current_window_id() {
+          set_window_option_value(tree, window_id, def, value);
+        }
+      }
+    }
+    OptionAccessor::Global { set, .. } => {
+      set(global_options, value);
+    }
+  }
+}
+
+/// Set the value of a buffer option to the `buffer`, the value must be validated by
+/// [`OptionDef::validate`]. The viewports of all the windows bound to the buffer are re-synced.
+///
+/// # Panics
+///
+/// If the option is not a buffer option.
+pub fn set_buffer_option_value(
+  tree: &mut Tree,
+  buffer: &BufferArc,
+  def: &OptionDef,
+  value: &OptionValue,
+) {
+  debug_assert!(def.validate(value));
+  let OptionAccessor::Buffer { set, .. } = def.accessor() else {
+    unreachable!()
+  };
+  let window_ids = tree.buffer_window_ids(buffer);
+  let mut buffer = lock!(buffer);
+  let mut opts = *buffer.options();
+  set(&mut opts, value);
+  buffer.set_options(&opts);
+  for window_id in window_ids {
+    cursor_ops::_update_viewport_after_text_changed(
+      tree,
+      window_id,
+      buffer.text(),
+    );
+  }
+}
+
+/// Set the value of a window option to the window `window_id`, the value must be validated by
+/// [`OptionDef::validate`]. The viewport of the window is re-synced.
+///
+/// # Panics
+///
+/// If the option is not a window option, or the window doesn't exist.
+pub fn set_window_option_value(
+  tree: &mut Tree,
+  window_id: TreeNodeId,
+  def: &OptionDef,
+  value: &OptionValue,
+) {
+  debug_assert!(def.validate(value));
+  let OptionAccessor::Window { set, .. } = def.accessor() else {
+    unreachable!()
+  };
+  let window = tree.window_mut(window_id).unwrap();
+  let mut opts = *window.options();
+  set(&mut opts, value);
+  window.set_options(&opts);
+  let buffer = window.buffer().upgrade().unwrap();
+  let buffer = lock!(buffer);
+  cursor_ops::_update_viewport_after_text_changed(
+    tree,
+    window_id,
+    buffer.text(),
+  );
+}
@@ -0,0 +1,218 @@
+use super::opt::*;
+
+use crate::buf::opt::{BufferLocalOptionsBuilder, FileFormatOption};
+use crate::defaults;
+use crate::prelude::*;
+use crate::state::opt::GlobalOptionsBuilder;
+use crate::test::buf::{make_buffer_from_lines, make_buffers_manager};
+use crate::test::log::init as test_log_init;
+use crate::test::tree::make_tree_with_buffers;
+use crate::ui::widget::window::WindowLocalOptionsBuilder;
+
+use compact_str::CompactString;
+
+#[test]
+fn find_option1() {
+  let ts1 = find_option("tabstop").unwrap();
+  let ts2 = find_option("ts").unwrap();
+  assert_eq!(ts1.name(), "tabstop");
+  assert_eq!(ts2.name(), "tabstop");
+  assert_eq!(ts1.alias(), Some("ts"));
+  assert_eq!(ts1.kind(), OptionKind::Number);
+  assert_eq!(ts1.scope(), OptionScope::Buffer);
+
+  let wrap = find_option("wrap").unwrap();
+  assert_eq!(wrap.kind(), OptionKind::Boolean);
+  assert_eq!(wrap.scope(), OptionScope::Window);
+
+  assert!(find_option("tab_stop").is_none());
+  assert!(find_option("").is_none());
+}
+
+#[test]
+fn default_value1() {
+  assert_eq!(
+    find_option("tabstop").unwrap().default_value(),
+    OptionValue::Number(defaults::buf::TAB_STOP as i64)
+  );
+  assert_eq!(
+    find_option("fileformat").unwrap().default_value(),
+    OptionValue::String(CompactString::new(format!(
+      "{}",
+      defaults::buf::FILE_FORMAT
+    )))
+  );
+  assert_eq!(
+    find_option("iskeyword").unwrap().default_value(),
+    OptionValue::String(CompactString::new("@,48-57,_,192-255"))
+  );
+  assert_eq!(
+    find_option("wrap").unwrap().default_value(),
+    OptionValue::Boolean(defaults::win::WRAP)
+  );
+  assert_eq!(
+    find_option("scrolloff").unwrap().default_value(),
+    OptionValue::Number(defaults::win::SCROLL_OFF as i64)
+  );
+  assert_eq!(
+    find_option("tm").unwrap().default_value(),
+    OptionValue::Number(defaults::global::TIMEOUT_LEN as i64)
+  );
+  assert_eq!(
+    find_option("showcmd").unwrap().default_value(),
+    OptionValue::Boolean(defaults::global::SHOW_CMD)
+  );
+}
+
+#[test]
+fn parse_and_validate1() {
+  let ts = find_option("ts").unwrap();
+  assert_eq!(ts.parse("4"), Ok(OptionValue::Number(4)));
+  assert_eq!(
+    ts.parse("abc"),
+    Err(OptionErr::NumberRequired("abc".to_string()))
+  );
+  assert!(ts.validate(&OptionValue::Number(4)));
+  assert!(!ts.validate(&OptionValue::Number(0)));
+  assert!(!ts.validate(&OptionValue::Number(-1)));
+  assert!(!ts.validate(&OptionValue::Number(u16::MAX as i64 + 1)));
+  assert!(!ts.validate(&OptionValue::Boolean(true)));
+
+  let so = find_option("so").unwrap();
+  assert!(so.validate(&OptionValue::Number(0)));
+
+  let ff = find_option("ff").unwrap();
+  assert!(ff.validate(&ff.parse("dos").unwrap()));
+  assert!(!ff.validate(&ff.parse("windows").unwrap()));
+
+  let isk = find_option("isk").unwrap();
+  assert!(isk.validate(&isk.parse("@,48-57,_,-").unwrap()));
+  assert!(!isk.validate(&isk.parse("z-a").unwrap()));
+
+  let tm = find_option("tm").unwrap();
+  assert!(tm.validate(&OptionValue::Number(0)));
+  assert!(!tm.validate(&OptionValue::Number(-1)));
+
+  let km = find_option("km").unwrap();
+  assert!(km.validate(&km.parse("startsel,stopsel").unwrap()));
+  assert!(!km.validate(&km.parse("startsel,popup").unwrap()));
+
+  let slm = find_option("selectmode").unwrap();
+  assert!(slm.validate(&slm.parse("key,cmd").unwrap()));
+  assert!(!slm.validate(&slm.parse("keys").unwrap()));
+
+  let mouse = find_option("mouse").unwrap();
+  assert!(mouse.validate(&mouse.parse("nvi").unwrap()));
+  assert!(mouse.validate(&mouse.parse("").unwrap()));
+  assert!(!mouse.validate(&mouse.parse("nx").unwrap()));
+
+  let wrap = find_option("wrap").unwrap();
+  assert!(wrap.parse("true").is_err());
+}
+
+#[test]
+fn format1() {
+  let ts = find_option("ts").unwrap();
+  assert_eq!(ts.format(&OptionValue::Number(4)), "tabstop=4");
+  let wrap = find_option("wrap").unwrap();
+  assert_eq!(wrap.format(&OptionValue::Boolean(true)), "wrap");
+  assert_eq!(wrap.format(&OptionValue::Boolean(false)), "nowrap");
+}
+
+#[test]
+fn get_set_option_value1() {
+  test_log_init();
+
+  let terminal_size = U16Size::new(10, 10);
+  let buf_opts = BufferLocalOptionsBuilder::default().build().unwrap();
+  let win_opts = WindowLocalOptionsBuilder::default().build().unwrap();
+  let buf = make_buffer_from_lines(terminal_size, buf_opts, vec!["a\tb\n"]);
+  let bufs = make_buffers_manager(buf_opts, vec![buf.clone()]);
+  let tree = make_tree_with_buffers(terminal_size, win_opts, bufs.clone());
+
+  let mut tree = lock!(tree);
+  let mut bufs = lock!(bufs);
+  let mut options = GlobalOptionsBuilder::default().build().unwrap();
+
+  // Buffer option
+  let ts = find_option("ts").unwrap();
+  set_option_value(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    ts,
+    OptionTarget::Local,
+    &OptionValue::Number(4),
+  );
+  assert_eq!(lock!(buf).options().tab_stop(), 4);
+  assert_eq!(
+    bufs.global_local_options().tab_stop(),
+    defaults::buf::TAB_STOP
+  );
+  assert_eq!(
+    get_option_value(&tree, &bufs, &options, ts, OptionTarget::LocalAndGlobal),
+    OptionValue::Number(4)
+  );
+  assert_eq!(
+    get_option_value(&tree, &bufs, &options, ts, OptionTarget::Global),
+    OptionValue::Number(defaults::buf::TAB_STOP as i64)
+  );
+
+  let ff = find_option("ff").unwrap();
+  set_option_value(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    ff,
+    OptionTarget::LocalAndGlobal,
+    &OptionValue::String(CompactString::new("mac")),
+  );
+  assert_eq!(lock!(buf).options().file_format(), FileFormatOption::Mac);
+  assert_eq!(
+    bufs.global_local_options().file_format(),
+    FileFormatOption::Mac
+  );
+
+  // Window option
+  let wrap = find_option("wrap").unwrap();
+  set_option_value(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    wrap,
+    OptionTarget::Global,
+    &OptionValue::Boolean(false),
+  );
+  assert!(tree.current_window().unwrap().options().wrap());
+  assert!(!tree.global_local_options().wrap());
+
+  set_option_value(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    wrap,
+    OptionTarget::LocalAndGlobal,
+    &OptionValue::Boolean(false),
+  );
+  assert!(!tree.current_window().unwrap().options().wrap());
+  assert_eq!(
+    get_option_value(&tree, &bufs, &options, wrap, OptionTarget::Local),
+    OptionValue::Boolean(false)
+  );
+
+  // Global option
+  let hi = find_option("hi").unwrap();
+  set_option_value(
+    &mut tree,
+    &mut bufs,
+    &mut options,
+    hi,
+    OptionTarget::Local,
+    &OptionValue::Number(50),
+  );
+  assert_eq!(options.history(), 50);
+  assert_eq!(
+    get_option_value(&tree, &bufs, &options, hi, OptionTarget::Global),
+    OptionValue::Number(50)
+  );
+}
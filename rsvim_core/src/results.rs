@@ -1,5 +1,7 @@
 //! Results and errors.
 
+use crate::buf::BufferId;
+
 use thiserror::Error as ThisError;
 
 // anyhow {
@@ -38,3 +40,85 @@ pub enum JsRuntimeErr {
 pub type JsRuntimeResult<T> = std::result::Result<T, JsRuntimeErr>;
 
 // Js Runtime }
+
+// Options {
+
+#[derive(Debug, Clone, PartialEq, Eq, ThisError)]
+/// Error code for editor options, follows Vim's error messages.
+pub enum OptionErr {
+  #[error("E518: Unknown option: {0}")]
+  UnknownOption(String),
+
+  #[error("E474: Invalid argument: {0}")]
+  InvalidArgument(String),
+
+  #[error("E521: Number required after =: {0}")]
+  NumberRequired(String),
+}
+
+/// [`std::result::Result`] with `T` if ok, [`OptionErr`] if error.
+pub type OptionResult<T> = std::result::Result<T, OptionErr>;
+
+// Options }
+
+// Ex commands {
+
+#[derive(Debug, Clone, PartialEq, Eq, ThisError)]
+/// Error code for builtin ex commands, follows Vim's error messages.
+pub enum ExCommandErr {
+  #[error("E488: Trailing characters: {0}")]
+  TrailingCharacters(String),
+
+  #[error("E16: Invalid range")]
+  InvalidRange,
+
+  #[error("E20: Mark not set")]
+  MarkNotSet,
+
+  #[error("E903: Process failed to start: {0}")]
+  ProcessFailedToStart(String),
+
+  #[error("E31: No such mapping")]
+  NoSuchMapping,
+
+  #[error("E227: Mapping already exists for {0}")]
+  MappingAlreadyExists(String),
+
+  #[error("E475: Invalid argument: {0}")]
+  InvalidArgument(String),
+
+  #[error("E37: No write since last change (add ! to override)")]
+  NoWriteSinceLastChange(BufferId),
+
+  #[error("E162: No write since last change for buffer \"{1}\"")]
+  NoWriteSinceLastChangeForBuffer(BufferId, String),
+
+  #[error("E32: No file name")]
+  NoFileName,
+
+  #[error("E141: No file name for buffer {0}")]
+  NoFileNameForBuffer(BufferId),
+
+  #[error("E212: Can't open file for writing: {0}")]
+  CannotWrite(String),
+
+  #[error("E492: Not an editor command: {0}")]
+  NotAnEditorCommand(String),
+
+  #[error("E464: Ambiguous use of user-defined command: {0}")]
+  AmbiguousCommand(String),
+
+  #[error("E477: No ! allowed")]
+  NoBangAllowed,
+
+  #[error("E481: No range allowed")]
+  NoRangeAllowed,
+
+  #[error("E471: Argument required")]
+  ArgumentRequired,
+}
+
+/// [`std::result::Result`] with `T` if ok, [`ExCommandErr`] if error.
+pub type ExCommandResult<T> = std::result::Result<T, ExCommandErr>;
+
+// Ex commands }
@@ -1,15 +1,46 @@
 //! Vim editing mode.
 
+use crate::evloop::msg::WorkerToMasterMessage;
+use crate::excommand::ExCommandsManager;
 use crate::js::msg::EventLoopToJsRuntimeMessage;
 use crate::prelude::*;
+use crate::state::autocmd::{AutoCmdEvent, AutoCmds, mode_name};
 use crate::state::fsm::StatefulValue;
+use crate::state::fsm::mouse::MouseClick;
+use crate::state::keymap::{Keymap, Keymaps};
+use crate::state::macros::Macros;
 use crate::state::mode::Mode;
+use crate::state::ops::CharFind;
+use crate::state::ops::replace_ops::Overwritten;
+use crate::state::opt::{GlobalOptions, GlobalOptionsBuilder};
+use crate::state::repeat::DotRepeat;
 
+use compact_str::CompactString;
+use crossterm::event::KeyEvent;
 use tokio::sync::mpsc::Sender;
 
+pub mod autocmd;
 pub mod fsm;
+pub mod keymap;
+pub mod keys;
+pub mod macros;
 pub mod mode;
 pub mod ops;
+pub mod opt;
+pub mod repeat;
+
+#[cfg(test)]
+mod autocmd_tests;
+#[cfg(test)]
+mod keymap_tests;
+#[cfg(test)]
+mod keys_tests;
+#[cfg(test)]
+mod macros_tests;
+#[cfg(test)]
+mod opt_tests;
+#[cfg(test)]
+mod repeat_tests;
 
 #[derive(Debug, Clone)]
 pub struct State {
@@ -18,8 +49,48 @@ pub struct State {
   // Last editing mode.
   last_mode: Mode,
 
+  // Global options, i.e. the options that are not local to any buffer or window.
+  global_options: GlobalOptions,
+
+  // Last char find motion, i.e. `fx`, it is repeated by `;` and `,`.
+  last_char_find: Option<CharFind>,
+
+  // Last repeatable change, it is repeated by `.`.
+  dot_repeat: DotRepeat,
+
+  // Recording and playing macros, i.e. `qa` and `@a`.
+  macros: Macros,
+
+  // The text overwritten in replace mode, it is restored by `<BS>`.
+  overwritten: Vec<Overwritten>,
+
+  // Key mappings, and the typed keys that are a prefix of some mappings.
+  keymaps: Keymaps,
+  keymap_pending: Vec<KeyEvent>,
+
+  // The mapping whose js callback waits to run before the pending keys, and the keys returned by
+  // the callback of an `<expr>` mapping.
+  keymap_callback: Option<Keymap>,
+  keymap_expr_keys: Option<CompactString>,
+
+  // The last mouse click, it counts the double/triple clicks.
+  mouse_click: Option<MouseClick>,
+
+  // Autocommands, and the fired events that wait to be dispatched to them.
+  autocmds: AutoCmds,
+
+  // User ex commands, they're backed by js callbacks.
+  ex_commands: ExCommandsManager,
+
+  // The stateful machine requested outside of the state machine (i.e. by plugins), it is switched
+  // to by the event loop.
+  requested_stateful: Option<StatefulValue>,
+
   // Js runtime tick dispatcher
   jsrt_tick_dispatcher: Sender<EventLoopToJsRuntimeMessage>,
+
+  // Worker to master sender, i.e. the output of terminal buffers is sent to the event loop.
+  worker_send_to_master: Option<Sender<WorkerToMasterMessage>>,
 }
 
 arc_mutex_ptr!(State);
@@ -31,7 +102,21 @@ impl State {
     State {
       mode: Mode::Normal,
       last_mode: Mode::Normal,
+      global_options: GlobalOptionsBuilder::default().build().unwrap(),
+      last_char_find: None,
+      dot_repeat: DotRepeat::default(),
+      macros: Macros::default(),
+      overwritten: vec![],
+      keymaps: Keymaps::new(),
+      keymap_pending: vec![],
+      keymap_callback: None,
+      keymap_expr_keys: None,
+      mouse_click: None,
+      autocmds: AutoCmds::new(),
+      ex_commands: ExCommandsManager::new(),
+      requested_stateful: None,
       jsrt_tick_dispatcher,
+      worker_send_to_master: None,
     }
   }
 
@@ -43,9 +128,142 @@ impl State {
     self.last_mode
   }
 
+  pub fn global_options(&self) -> &GlobalOptions {
+    &self.global_options
+  }
+
+  pub fn global_options_mut(&mut self) -> &mut GlobalOptions {
+    &mut self.global_options
+  }
+
+  pub fn set_global_options(&mut self, options: &GlobalOptions) {
+    self.global_options = options.clone();
+  }
+
+  pub fn last_char_find(&self) -> Option<CharFind> {
+    self.last_char_find
+  }
+
+  pub fn set_last_char_find(&mut self, value: Option<CharFind>) {
+    self.last_char_find = value;
+  }
+
+  pub fn dot_repeat(&self) -> &DotRepeat {
+    &self.dot_repeat
+  }
+
+  pub fn dot_repeat_mut(&mut self) -> &mut DotRepeat {
+    &mut self.dot_repeat
+  }
+
+  pub fn macros(&self) -> &Macros {
+    &self.macros
+  }
+
+  pub fn macros_mut(&mut self) -> &mut Macros {
+    &mut self.macros
+  }
+
+  /// The text overwritten in replace mode, the last one is restored first by `<BS>`.
+  pub fn overwritten(&self) -> &Vec<Overwritten> {
+    &self.overwritten
+  }
+
+  pub fn overwritten_mut(&mut self) -> &mut Vec<Overwritten> {
+    &mut self.overwritten
+  }
+
+  pub fn keymaps(&self) -> &Keymaps {
+    &self.keymaps
+  }
+
+  pub fn keymaps_mut(&mut self) -> &mut Keymaps {
+    &mut self.keymaps
+  }
+
+  /// The typed keys that are a prefix of some mappings, they wait for the next key.
+  pub fn keymap_pending(&self) -> &Vec<KeyEvent> {
+    &self.keymap_pending
+  }
+
+  pub fn keymap_pending_mut(&mut self) -> &mut Vec<KeyEvent> {
+    &mut self.keymap_pending
+  }
+
+  /// The mapping whose js callback runs before the pending keys are resolved.
+  pub fn keymap_callback(&self) -> Option<&Keymap> {
+    self.keymap_callback.as_ref()
+  }
+
+  pub fn set_keymap_callback(&mut self, keymap: Option<Keymap>) {
+    self.keymap_callback = keymap;
+  }
+
+  /// Take the mapping whose js callback waits to run, if any.
+  pub fn take_keymap_callback(&mut self) -> Option<Keymap> {
+    self.keymap_callback.take()
+  }
+
+  /// Set the keys returned by the js callback of an `<expr>` mapping.
+  pub fn set_keymap_expr_keys(&mut self, keys: Option<CompactString>) {
+    self.keymap_expr_keys = keys;
+  }
+
+  /// Take the keys returned by the js callback of an `<expr>` mapping, if any.
+  pub fn take_keymap_expr_keys(&mut self) -> Option<CompactString> {
+    self.keymap_expr_keys.take()
+  }
+
+  pub fn mouse_click(&self) -> Option<MouseClick> {
+    self.mouse_click
+  }
+
+  pub fn set_mouse_click(&mut self, value: Option<MouseClick>) {
+    self.mouse_click = value;
+  }
+
+  pub fn autocmds(&self) -> &AutoCmds {
+    &self.autocmds
+  }
+
+  pub fn autocmds_mut(&mut self) -> &mut AutoCmds {
+    &mut self.autocmds
+  }
+
+  pub fn ex_commands(&self) -> &ExCommandsManager {
+    &self.ex_commands
+  }
+
+  pub fn ex_commands_mut(&mut self) -> &mut ExCommandsManager {
+    &mut self.ex_commands
+  }
+
+  /// Request to switch the stateful machine, i.e. a plugin selects the text in select mode.
+  pub fn request_stateful(&mut self, stateful: StatefulValue) {
+    self.requested_stateful = Some(stateful);
+  }
+
+  /// Take the requested stateful machine, if any.
+  pub fn take_requested_stateful(&mut self) -> Option<StatefulValue> {
+    self.requested_stateful.take()
+  }
+
   pub fn jsrt_tick_dispatcher(&self) -> &Sender<EventLoopToJsRuntimeMessage> {
     &self.jsrt_tick_dispatcher
   }
+
+  pub fn worker_send_to_master(
+    &self,
+  ) -> &Option<Sender<WorkerToMasterMessage>> {
+    &self.worker_send_to_master
+  }
+
+  pub fn set_worker_send_to_master(
+    &mut self,
+    sender: Option<Sender<WorkerToMasterMessage>>,
+  ) {
+    self.worker_send_to_master = sender;
+  }
 }
 
 impl State {
@@ -62,6 +280,11 @@ impl State {
       StatefulValue::SelectMode(_) => Some(Mode::Select),
       StatefulValue::OperatorPendingMode(_) => Some(Mode::OperatorPending),
       StatefulValue::InsertMode(_) => Some(Mode::Insert),
+      StatefulValue::ReplaceMode(replace) => Some(if replace.is_virtual() {
+        Mode::VirtualReplace
+      } else {
+        Mode::Replace
+      }),
       StatefulValue::CommandLineExMode(_) => Some(Mode::CommandLineEx),
       StatefulValue::CommandLineSearchForwardMode(_) => {
         Some(Mode::CommandLineSearchForward)
@@ -72,12 +295,47 @@ impl State {
       StatefulValue::TerminalMode(_) => Some(Mode::Terminal),
       // Internal states.
       StatefulValue::QuitState(_) => None,
+      StatefulValue::MessagePagerState(_) => None,
+      StatefulValue::ConfirmState(_) => None,
     };
 
     if let Some(mode) = next_mode {
+      if mode != self.mode {
+        self.fire_mode_events(self.mode, mode);
+      }
       self.mode = mode;
     }
   }
+
+  // Fire the events of leaving the `from` mode and entering the `to` mode.
+  fn fire_mode_events(&mut self, from: Mode, to: Mode) {
+    let cmdline_type = |mode: Mode| match mode {
+      Mode::CommandLineEx => Some(":"),
+      Mode::CommandLineSearchForward => Some("/"),
+      Mode::CommandLineSearchBackward => Some("?"),
+      _ => None,
+    };
+    let is_insert = |mode: Mode| {
+      matches!(mode, Mode::Insert | Mode::Replace | Mode::VirtualReplace)
+    };
+
+    let autocmds = &mut self.autocmds;
+    autocmds.mode_changed(to);
+    if let Some(cmdline_type) = cmdline_type(from) {
+      autocmds.fire(AutoCmdEvent::CmdlineLeave, None, cmdline_type);
+    }
+    if is_insert(from) && !is_insert(to) {
+      autocmds.fire(AutoCmdEvent::InsertLeave, None, "");
+    }
+    let data = format!("{}:{}", mode_name(from), mode_name(to));
+    autocmds.fire(AutoCmdEvent::ModeChanged, None, &data);
+    if is_insert(to) && !is_insert(from) {
+      autocmds.fire(AutoCmdEvent::InsertEnter, None, "");
+    }
+    if let Some(cmdline_type) = cmdline_type(to) {
+      autocmds.fire(AutoCmdEvent::CmdlineEnter, None, cmdline_type);
+    }
+  }
 }
 
 #[cfg(test)]
@@ -98,4 +356,68 @@ mod tests {
     assert_eq!(state.last_mode(), Mode::Normal);
     assert_eq!(state.mode(), Mode::Insert);
   }
+
+  #[test]
+  fn update_state_machine2() {
+    use crate::state::autocmd::{AutoCmd, AutoCmdOptions};
+
+    let (jsrt_tick_dispatcher, _jsrt_tick_queue) = channel(1);
+    let mut state = State::new(jsrt_tick_dispatcher);
+    state.autocmds_mut().add(AutoCmd::new(
+      1,
+      vec![
+        AutoCmdEvent::ModeChanged,
+        AutoCmdEvent::InsertEnter,
+        AutoCmdEvent::InsertLeave,
+        AutoCmdEvent::CmdlineEnter,
+        AutoCmdEvent::CmdlineLeave,
+      ],
+      AutoCmdOptions::default(),
+    ));
+    let fired = |state: &mut State| -> Vec<(AutoCmdEvent, String)> {
+      state
+        .autocmds_mut()
+        .take_fired()
+        .into_iter()
+        .map(|fired| (fired.event, fired.data.to_string()))
+        .collect()
+    };
+
+    state.update_state_machine(&StatefulValue::InsertMode(
+      fsm::InsertStateful::default(),
+    ));
+    assert_eq!(
+      fired(&mut state),
+      vec![
+        (AutoCmdEvent::ModeChanged, "n:i".to_string()),
+        (AutoCmdEvent::InsertEnter, "".to_string()),
+      ]
+    );
+    state.update_state_machine(&StatefulValue::CommandLineExMode(
+      fsm::CommandLineExStateful::default(),
+    ));
+    assert_eq!(
+      fired(&mut state),
+      vec![
+        (AutoCmdEvent::InsertLeave, "".to_string()),
+        (AutoCmdEvent::ModeChanged, "i:c".to_string()),
+        (AutoCmdEvent::CmdlineEnter, ":".to_string()),
+      ]
+    );
+    state.update_state_machine(&StatefulValue::NormalMode(
+      fsm::NormalStateful::default(),
+    ));
+    assert_eq!(
+      fired(&mut state),
+      vec![
+        (AutoCmdEvent::CmdlineLeave, ":".to_string()),
+        (AutoCmdEvent::ModeChanged, "c:n".to_string()),
+      ]
+    );
+    // The same mode fires nothing.
+    state.update_state_machine(&StatefulValue::NormalMode(
+      fsm::NormalStateful::default(),
+    ));
+    assert!(fired(&mut state).is_empty());
+  }
 }
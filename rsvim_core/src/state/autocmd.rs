@@ -0,0 +1,542 @@
+//! Autocommands, i.e. the js listeners of the editor events set by `Rsvim.autocmd.create`.
+//!
+//! The editor fires the events into a queue (i.e. the mode changes, a buffer is written), then the
+//! event loop dispatches the queued events to the listeners after handling each input. The events
+//! of the editor status are fired where the status changes: the widget tree fires the cursor moves
+//! and the window switches, the text fires its changes. They're collected by the event loop, see
+//! [`AutoCmds::fire_changes`]. Each event has a sequence number, so the events are dispatched in
+//! the order they happen. The events that no listener listens to are dropped.
+//!
+//! A listener is filtered by the patterns (file globs), the file types and the buffer, and it can
+//! be in a group, so a plugin can clear all its listeners at once.
+//!
+//! See: <https://vimhelp.org/autocmd.txt.html>.
+
+use crate::buf::BufferId;
+use crate::excommand::complete::glob_matches;
+use crate::state::mode::Mode;
+
+use compact_str::{CompactString, ToCompactString};
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// Editor event.
+pub enum AutoCmdEvent {
+  /// Before reading the file into the buffer.
+  BufReadPre,
+  /// After reading the file into the buffer.
+  BufReadPost,
+  /// Before writing the buffer to the file.
+  BufWritePre,
+  /// After writing the buffer to the file.
+  BufWritePost,
+  /// After entering a buffer, i.e. current window shows another buffer.
+  BufEnter,
+  /// Before leaving a buffer.
+  BufLeave,
+  /// After entering a window.
+  WinEnter,
+  /// Before leaving a window.
+  WinLeave,
+  /// After the mode is changed, the pattern matches `old:new`, i.e. `n:i`.
+  ModeChanged,
+  /// When starting insert mode (or replace mode).
+  InsertEnter,
+  /// When leaving insert mode (or replace mode).
+  InsertLeave,
+  /// After the text of a buffer is changed in normal mode.
+  TextChanged,
+  /// After the text of a buffer is changed in insert mode.
+  TextChangedI,
+  /// After the cursor is moved in normal mode.
+  CursorMoved,
+  /// After the cursor is moved in insert mode.
+  CursorMovedI,
+  /// When no key is typed for 'updatetime' milliseconds in normal mode.
+  CursorHold,
+  /// After the terminal is resized.
+  VimResized,
+  /// When the terminal gets the focus.
+  FocusGained,
+  /// When the terminal loses the focus.
+  FocusLost,
+  /// After starting command-line mode, the pattern matches the command-line type, i.e. `:`.
+  CmdlineEnter,
+  /// Before leaving command-line mode.
+  CmdlineLeave,
+  /// After an option value is changed, the pattern matches the option name, i.e. `tabstop`.
+  OptionSet,
+  /// Before quitting the editor.
+  VimLeavePre,
+}
+
+// All the events.
+const EVENTS: [AutoCmdEvent; 23] = [
+  AutoCmdEvent::BufReadPre,
+  AutoCmdEvent::BufReadPost,
+  AutoCmdEvent::BufWritePre,
+  AutoCmdEvent::BufWritePost,
+  AutoCmdEvent::BufEnter,
+  AutoCmdEvent::BufLeave,
+  AutoCmdEvent::WinEnter,
+  AutoCmdEvent::WinLeave,
+  AutoCmdEvent::ModeChanged,
+  AutoCmdEvent::InsertEnter,
+  AutoCmdEvent::InsertLeave,
+  AutoCmdEvent::TextChanged,
+  AutoCmdEvent::TextChangedI,
+  AutoCmdEvent::CursorMoved,
+  AutoCmdEvent::CursorMovedI,
+  AutoCmdEvent::CursorHold,
+  AutoCmdEvent::VimResized,
+  AutoCmdEvent::FocusGained,
+  AutoCmdEvent::FocusLost,
+  AutoCmdEvent::CmdlineEnter,
+  AutoCmdEvent::CmdlineLeave,
+  AutoCmdEvent::OptionSet,
+  AutoCmdEvent::VimLeavePre,
+];
+
+impl AutoCmdEvent {
+  pub fn name(&self) -> &'static str {
+    match self {
+      AutoCmdEvent::BufReadPre => "BufReadPre",
+      AutoCmdEvent::BufReadPost => "BufReadPost",
+      AutoCmdEvent::BufWritePre => "BufWritePre",
+      AutoCmdEvent::BufWritePost => "BufWritePost",
+      AutoCmdEvent::BufEnter => "BufEnter",
+      AutoCmdEvent::BufLeave => "BufLeave",
+      AutoCmdEvent::WinEnter => "WinEnter",
+      AutoCmdEvent::WinLeave => "WinLeave",
+      AutoCmdEvent::ModeChanged => "ModeChanged",
+      AutoCmdEvent::InsertEnter => "InsertEnter",
+      AutoCmdEvent::InsertLeave => "InsertLeave",
+      AutoCmdEvent::TextChanged => "TextChanged",
+      AutoCmdEvent::TextChangedI => "TextChangedI",
+      AutoCmdEvent::CursorMoved => "CursorMoved",
+      AutoCmdEvent::CursorMovedI => "CursorMovedI",
+      AutoCmdEvent::CursorHold => "CursorHold",
+      AutoCmdEvent::VimResized => "VimResized",
+      AutoCmdEvent::FocusGained => "FocusGained",
+      AutoCmdEvent::FocusLost => "FocusLost",
+      AutoCmdEvent::CmdlineEnter => "CmdlineEnter",
+      AutoCmdEvent::CmdlineLeave => "CmdlineLeave",
+      AutoCmdEvent::OptionSet => "OptionSet",
+      AutoCmdEvent::VimLeavePre => "VimLeavePre",
+    }
+  }
+
+  /// Parse the event name, it is case-insensitive.
+  pub fn parse(name: &str) -> Option<AutoCmdEvent> {
+    EVENTS
+      .iter()
+      .find(|event| event.name().eq_ignore_ascii_case(name))
+      .copied()
+  }
+
+  /// Whether the patterns match the event data instead of the file name, i.e. `ModeChanged`.
+  pub fn matches_data(&self) -> bool {
+    matches!(
+      self,
+      AutoCmdEvent::ModeChanged
+        | AutoCmdEvent::CmdlineEnter
+        | AutoCmdEvent::CmdlineLeave
+        | AutoCmdEvent::OptionSet
+    )
+  }
+}
+
+impl Display for AutoCmdEvent {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+/// The mode name in the data of `ModeChanged`, the same as Vim's `mode()`.
+pub fn mode_name(mode: Mode) -> &'static str {
+  match mode {
+    Mode::Normal => "n",
+    Mode::Visual => "v",
+    Mode::Select => "s",
+    Mode::OperatorPending => "no",
+    Mode::Insert => "i",
+    Mode::Replace => "R",
+    Mode::VirtualReplace => "Rv",
+    Mode::CommandLineEx
+    | Mode::CommandLineSearchForward
+    | Mode::CommandLineSearchBackward => "c",
+    Mode::Terminal => "t",
+  }
+}
+
+/// The ID of an autocommand, it is also the ID of its js callback.
+pub type AutoCmdId = i32;
+
+/// Next sequence number of the fired events, it orders the events fired by different parts of
+/// the editor.
+pub fn next_event_seq() -> u64 {
+  static VALUE: AtomicU64 = AtomicU64::new(1);
+  VALUE.fetch_add(1, Ordering::Relaxed)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An event fired by the editor, it waits to be dispatched.
+pub struct FiredEvent {
+  /// The sequence number, see [`next_event_seq`].
+  pub seq: u64,
+  pub event: AutoCmdEvent,
+  /// The buffer of the event, `None` is the buffer of current window.
+  pub buffer_id: Option<BufferId>,
+  /// The event data, i.e. `n:i` for `ModeChanged`, `:` for `CmdlineEnter`.
+  pub data: CompactString,
+}
+
+impl FiredEvent {
+  pub fn new(
+    event: AutoCmdEvent,
+    buffer_id: Option<BufferId>,
+    data: &str,
+  ) -> Self {
+    FiredEvent {
+      seq: next_event_seq(),
+      event,
+      buffer_id,
+      data: data.to_compact_string(),
+    }
+  }
+
+  /// The string matched by the patterns, it is the event data or the `file` name.
+  pub fn target<'a>(&'a self, file: &'a str) -> &'a str {
+    if self.event.matches_data() {
+      &self.data
+    } else {
+      file
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// The options of an autocommand.
+pub struct AutoCmdOptions {
+  /// The file globs, a glob without `/` matches the file name tail. Empty matches everything.
+  pub patterns: Vec<CompactString>,
+  /// The file types of the buffer, empty matches everything.
+  pub filetypes: Vec<CompactString>,
+  /// Local to the buffer.
+  pub buffer: Option<BufferId>,
+  /// The group name.
+  pub group: Option<CompactString>,
+  /// It is removed after it runs once.
+  pub once: bool,
+  /// The description, shown when listing the autocommands.
+  pub desc: CompactString,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// An autocommand, the js callback listens to the events.
+pub struct AutoCmd {
+  id: AutoCmdId,
+  events: Vec<AutoCmdEvent>,
+  options: AutoCmdOptions,
+}
+
+impl AutoCmd {
+  pub fn new(
+    id: AutoCmdId,
+    events: Vec<AutoCmdEvent>,
+    options: AutoCmdOptions,
+  ) -> Self {
+    AutoCmd {
+      id,
+      events,
+      options,
+    }
+  }
+
+  pub fn id(&self) -> AutoCmdId {
+    self.id
+  }
+
+  pub fn events(&self) -> &Vec<AutoCmdEvent> {
+    &self.events
+  }
+
+  pub fn options(&self) -> &AutoCmdOptions {
+    &self.options
+  }
+
+  pub fn group(&self) -> Option<&CompactString> {
+    self.options.group.as_ref()
+  }
+
+  pub fn once(&self) -> bool {
+    self.options.once
+  }
+
+  /// Whether it listens to the `fired` event of the buffer, `file` is the file name of the buffer
+  /// and `filetype` is its file type.
+  pub fn matches(
+    &self,
+    fired: &FiredEvent,
+    buffer_id: Option<BufferId>,
+    file: &str,
+    filetype: &str,
+  ) -> bool {
+    if !self.events.contains(&fired.event) {
+      return false;
+    }
+    if self.options.buffer.is_some() && self.options.buffer != buffer_id {
+      return false;
+    }
+    if !self.options.filetypes.is_empty()
+      && !self.options.filetypes.iter().any(|ft| ft == filetype)
+    {
+      return false;
+    }
+    let target = fired.target(file);
+    let tail = target.rsplit('/').next().unwrap_or(target);
+    self.options.patterns.is_empty()
+      || self.options.patterns.iter().any(|pattern| {
+        if pattern.contains('/') {
+          glob_matches(pattern, target)
+        } else {
+          glob_matches(pattern, tail)
+        }
+      })
+  }
+}
+
+#[derive(Debug, Clone)]
+/// The autocommands, and the fired events that wait to be dispatched.
+pub struct AutoCmds {
+  autocmds: Vec<AutoCmd>,
+  groups: Vec<CompactString>,
+  fired: Vec<FiredEvent>,
+  // The mode before the mode changes that are not collected by `fire_changes` yet.
+  mode: Mode,
+  // The mode changes with their sequence numbers, that are not collected by `fire_changes` yet.
+  mode_changes: Vec<(u64, Mode)>,
+}
+
+impl Default for AutoCmds {
+  fn default() -> Self {
+    AutoCmds {
+      autocmds: vec![],
+      groups: vec![],
+      fired: vec![],
+      mode: Mode::Normal,
+      mode_changes: vec![],
+    }
+  }
+}
+
+impl AutoCmds {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Create the group if it doesn't exist. With `clear`, the autocommands in the existing group
+  /// are deleted, so a plugin can be reloaded without duplicating its autocommands.
+  pub fn create_group(&mut self, name: &str, clear: bool) {
+    if self.has_group(name) {
+      if clear {
+        self.autocmds.retain(|autocmd| {
+          autocmd.group().map(|group| group.as_str()) != Some(name)
+        });
+      }
+    } else {
+      self.groups.push(name.to_compact_string());
+    }
+  }
+
+  pub fn has_group(&self, name: &str) -> bool {
+    self.groups.iter().any(|group| group == name)
+  }
+
+  pub fn groups(&self) -> &Vec<CompactString> {
+    &self.groups
+  }
+
+  /// Delete the group and its autocommands, returns `false` if it is not found.
+  pub fn del_group(&mut self, name: &str) -> bool {
+    if !self.has_group(name) {
+      return false;
+    }
+    self.groups.retain(|group| group != name);
+    self.autocmds.retain(|autocmd| {
+      autocmd.group().map(|group| group.as_str()) != Some(name)
+    });
+    true
+  }
+
+  /// Add the autocommand.
+  ///
+  /// # Panics
+  ///
+  /// If the group doesn't exist.
+  pub fn add(&mut self, autocmd: AutoCmd) {
+    debug_assert!(
+      autocmd
+        .group()
+        .map(|group| self.has_group(group))
+        .unwrap_or(true)
+    );
+    self.autocmds.push(autocmd);
+  }
+
+  /// Delete the autocommand, returns `false` if it is not found.
+  pub fn del(&mut self, id: AutoCmdId) -> bool {
+    let len = self.autocmds.len();
+    self.autocmds.retain(|autocmd| autocmd.id() != id);
+    self.autocmds.len() != len
+  }
+
+  /// Delete the `event` from the autocommands in the `group`, `None` is any event or any group.
+  /// The autocommand is deleted if it listens to no event.
+  pub fn clear(&mut self, event: Option<AutoCmdEvent>, group: Option<&str>) {
+    for autocmd in self.autocmds.iter_mut() {
+      if group.is_some() && autocmd.group().map(|g| g.as_str()) != group {
+        continue;
+      }
+      match event {
+        Some(event) => autocmd.events.retain(|e| *e != event),
+        None => autocmd.events.clear(),
+      }
+    }
+    self.autocmds.retain(|autocmd| !autocmd.events.is_empty());
+  }
+
+  /// List the autocommands of the `event` in the `group`, `None` is any event or any group.
+  pub fn list(
+    &self,
+    event: Option<AutoCmdEvent>,
+    group: Option<&str>,
+  ) -> Vec<&AutoCmd> {
+    self
+      .autocmds
+      .iter()
+      .filter(|autocmd| {
+        event.map(|e| autocmd.events.contains(&e)).unwrap_or(true)
+      })
+      .filter(|autocmd| {
+        group.is_none() || autocmd.group().map(|g| g.as_str()) == group
+      })
+      .collect()
+  }
+
+  /// Whether any autocommand listens to the `event`.
+  pub fn has_listener(&self, event: AutoCmdEvent) -> bool {
+    self
+      .autocmds
+      .iter()
+      .any(|autocmd| autocmd.events.contains(&event))
+  }
+
+  /// Whether the js callback is used by any autocommand.
+  pub fn has_callback(&self, id: AutoCmdId) -> bool {
+    self.autocmds.iter().any(|autocmd| autocmd.id() == id)
+  }
+
+  /// Fire the event, it is dropped if no autocommand listens to it.
+  pub fn fire(
+    &mut self,
+    event: AutoCmdEvent,
+    buffer_id: Option<BufferId>,
+    data: &str,
+  ) {
+    if self.has_listener(event) {
+      self.fired.push(FiredEvent::new(event, buffer_id, data));
+    }
+  }
+
+  /// Record the mode is changed to `mode`, the cursor and text changes after it are fired in the
+  /// mode, see [`AutoCmds::fire_changes`].
+  pub fn mode_changed(&mut self, mode: Mode) {
+    self.mode_changes.push((next_event_seq(), mode));
+  }
+
+  /// The fired events that wait to be dispatched.
+  pub fn fired(&self) -> &Vec<FiredEvent> {
+    &self.fired
+  }
+
+  /// Take the fired events in the firing order.
+  pub fn take_fired(&mut self) -> Vec<FiredEvent> {
+    std::mem::take(&mut self.fired)
+  }
+
+  /// The autocommands that listen to the `fired` event of the buffer, the `once` autocommands are
+  /// deleted.
+  pub fn matched(
+    &mut self,
+    fired: &FiredEvent,
+    buffer_id: Option<BufferId>,
+    file: &str,
+    filetype: &str,
+  ) -> Vec<AutoCmd> {
+    let matched: Vec<AutoCmd> = self
+      .autocmds
+      .iter()
+      .filter(|autocmd| autocmd.matches(fired, buffer_id, file, filetype))
+      .cloned()
+      .collect();
+    self.autocmds.retain(|autocmd| {
+      !(autocmd.once() && matched.iter().any(|m| m.id() == autocmd.id()))
+    });
+    matched
+  }
+
+  /// Fire the `changes` fired by other parts of the editor (i.e. the buffers, the widget tree and
+  /// the texts), they're merged with the fired events by the sequence numbers.
+  ///
+  /// The `CursorMoved` and `TextChanged` changes are fired as `CursorMovedI` and `TextChangedI` in
+  /// insert mode, and dropped in terminal mode. The repeated cursor and text changes of the same
+  /// buffer are fired once.
+  pub fn fire_changes(&mut self, mut changes: Vec<FiredEvent>) {
+    changes.sort_by_key(|change| change.seq);
+    let mut mode_changes = std::mem::take(&mut self.mode_changes)
+      .into_iter()
+      .peekable();
+    for mut change in changes {
+      while let Some((_, mode)) =
+        mode_changes.next_if(|(seq, _)| *seq < change.seq)
+      {
+        self.mode = mode;
+      }
+      let insert = matches!(
+        self.mode,
+        Mode::Insert | Mode::Replace | Mode::VirtualReplace
+      );
+      change.event = match change.event {
+        AutoCmdEvent::CursorMoved | AutoCmdEvent::TextChanged
+          if self.mode == Mode::Terminal =>
+        {
+          continue;
+        }
+        AutoCmdEvent::CursorMoved if insert => AutoCmdEvent::CursorMovedI,
+        AutoCmdEvent::TextChanged if insert => AutoCmdEvent::TextChangedI,
+        event => event,
+      };
+      if self.has_listener(change.event) {
+        self.fired.push(change);
+      }
+    }
+    if let Some((_, mode)) = mode_changes.last() {
+      self.mode = mode;
+    }
+
+    self.fired.sort_by_key(|fired| fired.seq);
+    self.fired.dedup_by(|next, prev| {
+      next.event == prev.event
+        && next.buffer_id == prev.buffer_id
+        && matches!(
+          next.event,
+          AutoCmdEvent::CursorMoved
+            | AutoCmdEvent::CursorMovedI
+            | AutoCmdEvent::TextChanged
+            | AutoCmdEvent::TextChangedI
+        )
+    });
+  }
+}
@@ -0,0 +1,259 @@
+use super::autocmd::*;
+
+use crate::state::mode::Mode;
+use crate::test::log::init as test_log_init;
+
+use compact_str::CompactString;
+
+fn autocmd(
+  id: AutoCmdId,
+  events: &[AutoCmdEvent],
+  patterns: &[&str],
+) -> AutoCmd {
+  AutoCmd::new(
+    id,
+    events.to_vec(),
+    AutoCmdOptions {
+      patterns: patterns.iter().map(|p| CompactString::from(*p)).collect(),
+      ..Default::default()
+    },
+  )
+}
+
+fn fired(event: AutoCmdEvent, data: &str) -> FiredEvent {
+  FiredEvent::new(event, None, data)
+}
+
+fn fired_events(autocmds: &mut AutoCmds) -> Vec<AutoCmdEvent> {
+  autocmds
+    .take_fired()
+    .iter()
+    .map(|fired| fired.event)
+    .collect()
+}
+
+fn change(event: AutoCmdEvent, buffer_id: i32) -> FiredEvent {
+  FiredEvent::new(event, Some(buffer_id), "")
+}
+
+#[test]
+fn parse1() {
+  test_log_init();
+  assert_eq!(
+    AutoCmdEvent::parse("BufEnter"),
+    Some(AutoCmdEvent::BufEnter)
+  );
+  assert_eq!(
+    AutoCmdEvent::parse("bufenter"),
+    Some(AutoCmdEvent::BufEnter)
+  );
+  assert_eq!(
+    AutoCmdEvent::parse("TEXTCHANGEDI"),
+    Some(AutoCmdEvent::TextChangedI)
+  );
+  assert_eq!(AutoCmdEvent::parse("BufEnterX"), None);
+  assert_eq!(AutoCmdEvent::parse(""), None);
+  assert_eq!(AutoCmdEvent::VimLeavePre.to_string(), "VimLeavePre");
+  assert_eq!(mode_name(Mode::Normal), "n");
+  assert_eq!(mode_name(Mode::CommandLineSearchForward), "c");
+}
+
+#[test]
+fn matches1() {
+  test_log_init();
+  let a = autocmd(1, &[AutoCmdEvent::BufEnter], &["*.rs"]);
+  let enter = fired(AutoCmdEvent::BufEnter, "");
+  assert!(a.matches(&enter, Some(1), "/home/a/main.rs", ""));
+  assert!(!a.matches(&enter, Some(1), "/home/a/main.ts", ""));
+  assert!(!a.matches(
+    &fired(AutoCmdEvent::BufLeave, ""),
+    Some(1),
+    "main.rs",
+    ""
+  ));
+
+  let b = autocmd(2, &[AutoCmdEvent::BufEnter], &["/home/*/main.rs"]);
+  assert!(b.matches(&enter, Some(1), "/home/a/main.rs", ""));
+  assert!(!b.matches(&enter, Some(1), "/tmp/main.rs", ""));
+
+  let c = autocmd(3, &[AutoCmdEvent::ModeChanged], &["i:*"]);
+  assert!(c.matches(
+    &fired(AutoCmdEvent::ModeChanged, "i:n"),
+    None,
+    "a.rs",
+    ""
+  ));
+  assert!(!c.matches(
+    &fired(AutoCmdEvent::ModeChanged, "n:i"),
+    None,
+    "a.rs",
+    ""
+  ));
+
+  let d = AutoCmd::new(
+    4,
+    vec![AutoCmdEvent::BufEnter, AutoCmdEvent::BufLeave],
+    AutoCmdOptions {
+      filetypes: vec![CompactString::from("rust")],
+      buffer: Some(2),
+      ..Default::default()
+    },
+  );
+  assert!(d.matches(&enter, Some(2), "a.rs", "rust"));
+  assert!(!d.matches(&enter, Some(1), "a.rs", "rust"));
+  assert!(!d.matches(&enter, Some(2), "a.ts", "typescript"));
+}
+
+#[test]
+fn matched1() {
+  test_log_init();
+  let mut autocmds = AutoCmds::new();
+  autocmds.add(autocmd(1, &[AutoCmdEvent::BufEnter], &[]));
+  autocmds.add(AutoCmd::new(
+    2,
+    vec![AutoCmdEvent::BufEnter],
+    AutoCmdOptions {
+      once: true,
+      ..Default::default()
+    },
+  ));
+  autocmds.add(autocmd(3, &[AutoCmdEvent::BufLeave], &[]));
+
+  let enter = fired(AutoCmdEvent::BufEnter, "");
+  let ids = |matched: Vec<AutoCmd>| -> Vec<AutoCmdId> {
+    matched.iter().map(|a| a.id()).collect()
+  };
+  assert_eq!(ids(autocmds.matched(&enter, None, "", "")), vec![1, 2]);
+  assert_eq!(ids(autocmds.matched(&enter, None, "", "")), vec![1]);
+  assert!(!autocmds.has_callback(2));
+  assert!(autocmds.has_callback(3));
+}
+
+#[test]
+fn group1() {
+  test_log_init();
+  let mut autocmds = AutoCmds::new();
+  autocmds.create_group("g", true);
+  let grouped = |id: AutoCmdId| {
+    AutoCmd::new(
+      id,
+      vec![AutoCmdEvent::BufEnter, AutoCmdEvent::BufLeave],
+      AutoCmdOptions {
+        group: Some(CompactString::from("g")),
+        ..Default::default()
+      },
+    )
+  };
+  autocmds.add(grouped(1));
+  autocmds.add(grouped(2));
+  autocmds.add(autocmd(3, &[AutoCmdEvent::BufEnter], &[]));
+  assert_eq!(autocmds.list(None, Some("g")).len(), 2);
+  assert_eq!(autocmds.list(Some(AutoCmdEvent::BufEnter), None).len(), 3);
+
+  // Clear an event in the group.
+  autocmds.clear(Some(AutoCmdEvent::BufEnter), Some("g"));
+  assert_eq!(autocmds.list(Some(AutoCmdEvent::BufEnter), None).len(), 1);
+  assert_eq!(autocmds.list(Some(AutoCmdEvent::BufLeave), None).len(), 2);
+
+  // Create the existing group without `clear`.
+  autocmds.create_group("g", false);
+  assert_eq!(autocmds.list(None, Some("g")).len(), 2);
+  // Create the existing group with `clear`.
+  autocmds.create_group("g", true);
+  assert!(autocmds.list(None, Some("g")).is_empty());
+  assert!(autocmds.has_group("g"));
+
+  autocmds.add(grouped(4));
+  assert!(autocmds.del_group("g"));
+  assert!(!autocmds.del_group("g"));
+  assert!(!autocmds.has_callback(4));
+  assert!(autocmds.del(3));
+  assert!(!autocmds.del(3));
+}
+
+#[test]
+fn fire1() {
+  test_log_init();
+  let mut autocmds = AutoCmds::new();
+  autocmds.fire(AutoCmdEvent::BufEnter, None, "");
+  assert!(autocmds.fired().is_empty());
+
+  autocmds.add(autocmd(1, &[AutoCmdEvent::BufEnter], &[]));
+  autocmds.fire(AutoCmdEvent::BufEnter, Some(1), "");
+  autocmds.fire(AutoCmdEvent::BufLeave, Some(1), "");
+  assert_eq!(fired_events(&mut autocmds), vec![AutoCmdEvent::BufEnter]);
+  assert!(autocmds.fired().is_empty());
+}
+
+#[test]
+fn fire_changes1() {
+  test_log_init();
+  use AutoCmdEvent::*;
+
+  let mut autocmds = AutoCmds::new();
+  autocmds.add(autocmd(
+    1,
+    &[
+      BufEnter,
+      BufLeave,
+      WinEnter,
+      WinLeave,
+      TextChanged,
+      TextChangedI,
+      CursorMoved,
+      CursorMovedI,
+    ],
+    &[],
+  ));
+
+  // Nothing changes.
+  autocmds.fire_changes(vec![]);
+  assert!(fired_events(&mut autocmds).is_empty());
+
+  // The changes are fired in the order they happen.
+  let cursor = change(CursorMoved, 1);
+  let text = change(TextChanged, 1);
+  autocmds.fire_changes(vec![text, cursor]);
+  assert_eq!(fired_events(&mut autocmds), vec![CursorMoved, TextChanged]);
+
+  // The changes in insert mode, the repeated changes are fired once.
+  autocmds.mode_changed(Mode::Insert);
+  let changes = vec![
+    change(TextChanged, 1),
+    change(TextChanged, 1),
+    change(CursorMoved, 1),
+  ];
+  autocmds.mode_changed(Mode::Normal);
+  let cursor = change(CursorMoved, 1);
+  autocmds.fire_changes(changes.into_iter().chain([cursor]).collect());
+  assert_eq!(
+    fired_events(&mut autocmds),
+    vec![TextChangedI, CursorMovedI, CursorMoved]
+  );
+
+  // The changes in terminal mode.
+  autocmds.mode_changed(Mode::Terminal);
+  let changes = vec![change(TextChanged, 1), change(CursorMoved, 1)];
+  autocmds.fire_changes(changes);
+  assert!(fired_events(&mut autocmds).is_empty());
+
+  // The mode is kept.
+  autocmds.fire_changes(vec![change(CursorMoved, 1)]);
+  assert!(fired_events(&mut autocmds).is_empty());
+  autocmds.mode_changed(Mode::Normal);
+
+  // The changes are merged with the fired events.
+  let leave = change(BufLeave, 1);
+  autocmds.fire(WinLeave, Some(1), "");
+  let enter = change(BufEnter, 2);
+  autocmds.fire_changes(vec![enter, leave]);
+  let fired = autocmds.take_fired();
+  assert_eq!(
+    fired.iter().map(|f| f.event).collect::<Vec<_>>(),
+    vec![BufLeave, WinLeave, BufEnter]
+  );
+  assert_eq!(
+    fired.iter().map(|f| f.buffer_id).collect::<Vec<_>>(),
+    vec![Some(1), Some(1), Some(2)]
+  );
+}
@@ -11,34 +11,50 @@
 //! user, but help maintaining the internal state of the editor:
 //!
 //! * Quit state: The editor should quit on this state.
+//! * Message pager state: The shown messages are taller than one line, the pager scrolls them
+//!   until it is closed.
+//! * Confirm state: The quit command asks to save the modified buffers, i.e. `:confirm quit`.
 
 use crate::buf::BuffersManagerArc;
 use crate::content::TextContentsArc;
+use crate::content::message::MessageLevel;
+use crate::prelude::*;
 use crate::state::StateArc;
 use crate::state::ops::Operation;
 use crate::ui::tree::TreeArc;
 
-use crossterm::event::Event;
+use crossterm::event::{Event, KeyEvent};
 
 // Re-export
 pub use command_line_ex::CommandLineExStateful;
 pub use command_line_search_backward::CommandLineSearchBackwardStateful;
 pub use command_line_search_forward::CommandLineSearchForwardStateful;
+pub use confirm::ConfirmStateful;
 pub use insert::InsertStateful;
+pub use message_pager::MessagePagerStateful;
 pub use normal::NormalStateful;
 pub use operator_pending::OperatorPendingStateful;
 pub use quit::QuitStateful;
+pub use replace::ReplaceStateful;
 pub use select::SelectStateful;
 pub use terminal::TerminalStateful;
 pub use visual::VisualStateful;
 
+pub mod command_line_edit;
 pub mod command_line_ex;
 pub mod command_line_search_backward;
 pub mod command_line_search_forward;
+pub mod confirm;
 pub mod insert;
+pub mod mapping;
+pub mod message_pager;
+pub mod motion;
+pub mod mouse;
 pub mod normal;
 pub mod operator_pending;
+pub mod pending;
 pub mod quit;
+pub mod replace;
 pub mod select;
 pub mod terminal;
 pub mod visual;
@@ -46,9 +62,29 @@ pub mod visual;
 #[cfg(test)]
 mod command_line_ex_tests;
 #[cfg(test)]
+mod command_line_search_tests;
+#[cfg(test)]
+mod confirm_tests;
+#[cfg(test)]
 mod insert_tests;
 #[cfg(test)]
+mod mapping_tests;
+#[cfg(test)]
+mod message_pager_tests;
+#[cfg(test)]
+mod mouse_tests;
+#[cfg(test)]
 mod normal_tests;
+#[cfg(test)]
+mod operator_pending_tests;
+#[cfg(test)]
+mod pending_tests;
+#[cfg(test)]
+mod replace_tests;
+#[cfg(test)]
+mod select_tests;
+#[cfg(test)]
+mod visual_tests;
 
 #[derive(Debug)]
 /// The mutable data passed to each state handler, and allow them access the editor.
@@ -76,6 +112,17 @@ impl StatefulDataAccess {
       event,
     }
   }
+
+  /// The same data with another event.
+  pub fn with_event(&self, event: Event) -> Self {
+    StatefulDataAccess::new(
+      self.state.clone(),
+      self.tree.clone(),
+      self.buffers.clone(),
+      self.contents.clone(),
+      event,
+    )
+  }
 }
 
 /// The FSM trait.
@@ -93,6 +140,25 @@ pub trait Stateful {
     data_access: StatefulDataAccess,
     op: Operation,
   ) -> StatefulValue;
+
+  /// Handle the timeout of the pending keys, i.e. the next key of an incomplete command doesn't
+  /// arrive in `timeoutlen` milliseconds.
+  ///
+  /// Returns next state, or `None` if there's no pending keys.
+  fn handle_timeout(&self) -> Option<StatefulValue> {
+    None
+  }
+
+  /// The pending keys of an incomplete command, shown in command-line with the `showcmd` option.
+  fn showcmd(&self) -> String {
+    String::new()
+  }
+
+  /// Whether the next key is taken literally, i.e. the char of `fx`, the register of `"a`. The key
+  /// mappings are not applied to it.
+  fn is_literal_pending(&self) -> bool {
+    false
+  }
 }
 
 /// Generate enum dispatcher for `Stateful`.
@@ -115,6 +181,30 @@ macro_rules! stateful_enum_dispatcher {
           )*
         }
       }
+
+      fn handle_timeout(&self) -> Option<StatefulValue> {
+        match self {
+          $(
+            $enum::$variant(e) => e.handle_timeout(),
+          )*
+        }
+      }
+
+      fn showcmd(&self) -> String {
+        match self {
+          $(
+            $enum::$variant(e) => e.showcmd(),
+          )*
+        }
+      }
+
+      fn is_literal_pending(&self) -> bool {
+        match self {
+          $(
+            $enum::$variant(e) => e.is_literal_pending(),
+          )*
+        }
+      }
     }
   }
 }
@@ -128,12 +218,15 @@ pub enum StatefulValue {
   SelectMode(SelectStateful),
   OperatorPendingMode(OperatorPendingStateful),
   InsertMode(InsertStateful),
+  ReplaceMode(ReplaceStateful),
   CommandLineExMode(CommandLineExStateful),
   CommandLineSearchForwardMode(CommandLineSearchForwardStateful),
   CommandLineSearchBackwardMode(CommandLineSearchBackwardStateful),
   TerminalMode(TerminalStateful),
   // Internal states.
   QuitState(QuitStateful),
+  MessagePagerState(MessagePagerStateful),
+  ConfirmState(ConfirmStateful),
 }
 
 stateful_enum_dispatcher!(
@@ -143,11 +236,14 @@ stateful_enum_dispatcher!(
   SelectMode,
   OperatorPendingMode,
   InsertMode,
+  ReplaceMode,
   CommandLineExMode,
   CommandLineSearchForwardMode,
   CommandLineSearchBackwardMode,
   TerminalMode,
-  QuitState
+  QuitState,
+  MessagePagerState,
+  ConfirmState
 );
 
 impl Default for StatefulValue {
@@ -157,3 +253,65 @@ impl Default for StatefulValue {
     StatefulValue::NormalMode(NormalStateful::default())
   }
 }
+
+/// Feed the keys one by one to the state machine starting from `stateful`, i.e. replay the keys of
+/// the last change.
+///
+/// Returns the state after the last key.
+pub fn feed_keys(
+  data_access: &StatefulDataAccess,
+  stateful: StatefulValue,
+  keys: &[KeyEvent],
+) -> StatefulValue {
+  let mut stateful = stateful;
+  for key_event in keys {
+    stateful = stateful.handle(data_access.with_event(Event::Key(*key_event)));
+    let mut state = lock!(data_access.state);
+    state.update_state_machine(&stateful);
+    // The rest keys are dropped if a command fails when playing a macro.
+    if state.macros().is_failed() {
+      break;
+    }
+    if let StatefulValue::QuitState(_) = stateful {
+      break;
+    }
+  }
+  stateful
+}
+
+/// Play the keys of a macro or `:normal` `count` times, i.e. feed the keys to the state machine
+/// starting from `stateful`, through the key mappings if `remap`. It stops on the first failed
+/// command, or if the playing keys are deeper than
+/// [`MAX_MACRO_DEPTH`](crate::state::macros::MAX_MACRO_DEPTH).
+///
+/// Returns the state after the last key.
+pub fn play_keys(
+  data_access: &StatefulDataAccess,
+  stateful: StatefulValue,
+  keys: &[KeyEvent],
+  count: usize,
+  remap: bool,
+) -> StatefulValue {
+  if !lock!(data_access.state).macros_mut().enter() {
+    let contents = data_access.contents.clone();
+    lock!(contents)
+      .messages_mut()
+      .add(MessageLevel::Error, "E223: Recursive mapping");
+    return stateful;
+  }
+
+  let mut stateful = stateful;
+  for _ in 0..count {
+    stateful = if remap {
+      mapping::feed_mapped_keys(data_access, stateful, keys)
+    } else {
+      feed_keys(data_access, stateful, keys)
+    };
+    if lock!(data_access.state).macros().is_failed() {
+      break;
+    }
+  }
+
+  lock!(data_access.state).macros_mut().leave();
+  stateful
+}
@@ -0,0 +1,417 @@
+//! The editing keys shared by all the command-line modes, i.e. the command-line ex mode, search
+//! forward mode and search backward mode.
+//!
+//! See: <https://vimhelp.org/cmdline.txt.html#cmdline-editing>.
+
+use crate::content::history::HistoryKind;
+use crate::prelude::*;
+use crate::state::fsm::StatefulDataAccess;
+use crate::state::ops::{Operation, cmdline_ops, cursor_ops};
+use crate::ui::canvas::CursorStyle;
+use crate::ui::tree::*;
+use crate::ui::widget::command_line::{
+  CommandLineIndicatorSymbol, CommandLineNode,
+};
+use crate::ui::widget::window::WindowNode;
+
+use compact_str::{CompactString, ToCompactString};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use tracing::trace;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// The key that waits for the next key.
+pub enum CommandLinePendingKey {
+  /// `<C-R>`, insert the register named by the next key.
+  InsertRegister,
+  /// `<C-V>`, insert the next key literally.
+  InsertLiteral,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The editing key.
+pub enum CommandLineEditKey {
+  /// The key is done, and it is an operation.
+  Operation(Operation),
+  /// The key waits for the next key.
+  Pending(CommandLinePendingKey),
+}
+
+fn _is_ctrl(key_event: &KeyEvent) -> bool {
+  key_event.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+/// Get the editing key from the event.
+pub fn get_edit_key(event: &Event) -> Option<CommandLineEditKey> {
+  let op = match event {
+    Event::Key(key_event) => match key_event.kind {
+      KeyEventKind::Press => {
+        trace!("Event::key:{:?}", key_event);
+        let ctrl = _is_ctrl(key_event);
+        let word = ctrl || key_event.modifiers.contains(KeyModifiers::SHIFT);
+        match key_event.code {
+          KeyCode::Left if word => Operation::CommandLineCursorMoveWordLeft,
+          KeyCode::Right if word => Operation::CommandLineCursorMoveWordRight,
+          KeyCode::Left => Operation::CursorMoveLeftBy(1),
+          KeyCode::Right => Operation::CursorMoveRightBy(1),
+          KeyCode::Up => Operation::CommandLineHistoryPrevious,
+          KeyCode::Down => Operation::CommandLineHistoryNext,
+          KeyCode::Home => Operation::CursorMoveLeftBy(usize::MAX),
+          KeyCode::End => Operation::CursorMoveRightBy(usize::MAX),
+          KeyCode::Backspace => Operation::CursorDelete(-1),
+          KeyCode::Delete => Operation::CursorDelete(1),
+          KeyCode::Char(c) if ctrl => match c {
+            'a' | 'b' => Operation::CursorMoveLeftBy(usize::MAX),
+            'e' => Operation::CursorMoveRightBy(usize::MAX),
+            'h' => Operation::CursorDelete(-1),
+            'w' => Operation::CommandLineDeleteWordBeforeCursor,
+            'u' => Operation::CommandLineDeleteToStart,
+            'r' => {
+              return Some(CommandLineEditKey::Pending(
+                CommandLinePendingKey::InsertRegister,
+              ));
+            }
+            'v' | 'q' => {
+              return Some(CommandLineEditKey::Pending(
+                CommandLinePendingKey::InsertLiteral,
+              ));
+            }
+            _ => return None,
+          },
+          KeyCode::Char(c) => Operation::CursorInsert(c.to_compact_string()),
+          _ => return None,
+        }
+      }
+      KeyEventKind::Repeat => return None,
+      KeyEventKind::Release => return None,
+    },
+    Event::Paste(paste_string) => {
+      let payload = cmdline_ops::single_line(paste_string);
+      if payload.is_empty() {
+        return None;
+      }
+      Operation::CursorInsert(payload)
+    }
+    _ => return None,
+  };
+  Some(CommandLineEditKey::Operation(op))
+}
+
+/// Get the operation for the next key of the pending key.
+///
+/// Returns `None` if the pending key is cancelled, for example by `<Esc>`.
+pub fn get_pending_operation(
+  pending: CommandLinePendingKey,
+  event: &Event,
+) -> Option<Operation> {
+  let key_event = match event {
+    Event::Key(key_event) if key_event.kind == KeyEventKind::Press => key_event,
+    _ => return None,
+  };
+  trace!("Pending:{:?}, Event::key:{:?}", pending, key_event);
+  let ctrl = _is_ctrl(key_event);
+
+  match pending {
+    CommandLinePendingKey::InsertRegister => match key_event.code {
+      KeyCode::Char('w') if ctrl => {
+        Some(Operation::CommandLineInsertWordUnderCursor)
+      }
+      KeyCode::Char(c) if !ctrl => {
+        Some(Operation::CommandLineInsertRegister(c))
+      }
+      _ => None,
+    },
+    CommandLinePendingKey::InsertLiteral => {
+      let c = match key_event.code {
+        // Control chars, i.e. `<C-A>` is `0x01`.
+        KeyCode::Char(c) if ctrl && c.is_ascii_alphabetic() => {
+          ((c.to_ascii_lowercase() as u8) & 0x1f) as char
+        }
+        KeyCode::Char(c) => c,
+        KeyCode::Tab => '\t',
+        KeyCode::Enter => '\r',
+        KeyCode::Esc => '\x1b',
+        KeyCode::Backspace => '\x08',
+        _ => return None,
+      };
+      Some(Operation::CursorInsert(c.to_compact_string()))
+    }
+  }
+}
+
+/// Move cursor in command-line.
+pub fn cursor_move(data_access: &StatefulDataAccess, op: Operation) {
+  let tree = data_access.tree.clone();
+  let mut tree = lock!(tree);
+  debug_assert!(tree.command_line_id().is_some());
+  let cmdline_id = tree.command_line_id().unwrap();
+  let contents = data_access.contents.clone();
+  let contents = lock!(contents);
+
+  cursor_ops::cursor_move(
+    &mut tree,
+    cmdline_id,
+    contents.command_line_content(),
+    op,
+    true,
+  );
+}
+
+/// Insert text at cursor in command-line.
+pub fn cursor_insert(data_access: &StatefulDataAccess, payload: CompactString) {
+  let tree = data_access.tree.clone();
+  let mut tree = lock!(tree);
+  debug_assert!(tree.command_line_id().is_some());
+  let cmdline_id = tree.command_line_id().unwrap();
+  let contents = data_access.contents.clone();
+  let mut contents = lock!(contents);
+
+  if !payload.is_empty() {
+    cursor_ops::cursor_insert(
+      &mut tree,
+      cmdline_id,
+      contents.command_line_content_mut(),
+      payload,
+    );
+    // The edited content is the new prefix of history recall.
+    contents.command_line_history_mut().reset_navigation();
+  }
+}
+
+/// Delete N-chars at cursor in command-line.
+pub fn cursor_delete(data_access: &StatefulDataAccess, n: isize) {
+  let tree = data_access.tree.clone();
+  let mut tree = lock!(tree);
+  let contents = data_access.contents.clone();
+  let mut contents = lock!(contents);
+  let text = contents.command_line_content_mut();
+
+  debug_assert!(tree.command_line_id().is_some());
+  let cmdline_id = tree.command_line_id().unwrap();
+  let cmdline = tree.command_line().unwrap();
+  debug_assert_eq!(cmdline.cursor_viewport().line_idx(), 0);
+  debug_assert!(
+    text
+      .rope()
+      .get_line(cmdline.cursor_viewport().line_idx())
+      .is_some()
+  );
+
+  cursor_ops::cursor_delete(&mut tree, cmdline_id, text, n);
+  contents.command_line_history_mut().reset_navigation();
+}
+
+/// Replace the command-line content with the older (or newer if `newer` is `true`) entry of the
+/// `kind` history, only the entries start with the typed text are recalled.
+pub fn history_recall(
+  data_access: &StatefulDataAccess,
+  kind: HistoryKind,
+  newer: bool,
+) {
+  let tree = data_access.tree.clone();
+  let mut tree = lock!(tree);
+  debug_assert!(tree.command_line_id().is_some());
+  let cmdline_id = tree.command_line_id().unwrap();
+  let contents = data_access.contents.clone();
+  let mut contents = lock!(contents);
+
+  let typed = contents.command_line_content().rope().to_compact_string();
+  let history = contents.command_line_history_mut();
+  let recalled = if newer {
+    history.next(kind)
+  } else {
+    history.previous(kind, &typed)
+  };
+
+  if let Some(recalled) = recalled {
+    let text = contents.command_line_content_mut();
+    cursor_ops::cursor_clear(&mut tree, cmdline_id, text);
+    cursor_ops::cursor_insert(&mut tree, cmdline_id, text, recalled);
+  }
+}
+
+/// Add the confirmed command-line content to the `kind` history, the history size is the
+/// 'history' option.
+pub fn history_add(
+  data_access: &StatefulDataAccess,
+  kind: HistoryKind,
+  text: &str,
+) {
+  let history_size = lock!(data_access.state).global_options().history();
+  let mut contents = lock!(data_access.contents);
+  let history = contents.command_line_history_mut();
+  history.set_max_size(history_size as usize);
+  history.add(kind, text);
+}
+
+/// Move the cursor from current window to the command-line, and show the indicator `symbol`.
+pub fn enter_command_line(
+  data_access: &StatefulDataAccess,
+  symbol: CommandLineIndicatorSymbol,
+) {
+  let tree = data_access.tree.clone();
+  let mut tree = lock!(tree);
+
+  // Remove cursor from current window
+  let current_window = tree.current_window_mut().unwrap();
+  debug_assert!(current_window.cursor_id().is_some());
+  let cursor = match current_window.remove_cursor().unwrap() {
+    WindowNode::Cursor(mut cursor) => {
+      cursor.set_style(&CursorStyle::SteadyBar);
+      cursor
+    }
+    _ => unreachable!(),
+  };
+  debug_assert!(current_window.cursor_id().is_none());
+
+  // Clear previous command-line output.
+  debug_assert!(tree.command_line_id().is_some());
+  let cmdline_id = tree.command_line_id().unwrap();
+  {
+    let contents = data_access.contents.clone();
+    let mut contents = lock!(contents);
+    cursor_ops::cursor_clear(
+      &mut tree,
+      cmdline_id,
+      contents.command_line_content_mut(),
+    );
+    contents.command_line_history_mut().reset_navigation();
+    contents.messages_mut().clear_shown();
+  }
+
+  // Insert to command-line
+  debug_assert!(tree.command_line_mut().is_some());
+  let cmdline = tree.command_line_mut().unwrap();
+  let _previous_cursor = cmdline.insert_cursor(cursor);
+  debug_assert!(_previous_cursor.is_none());
+  cmdline.move_cursor_to(0, 0);
+  cmdline.indicator_mut().set_symbol(symbol);
+}
+
+/// Move the cursor from the command-line back to current window, clear the command-line and
+/// returns its (trimmed) content.
+pub fn leave_command_line(data_access: &StatefulDataAccess) -> CompactString {
+  let tree = data_access.tree.clone();
+  let mut tree = lock!(tree);
+
+  debug_assert!(tree.command_line_id().is_some());
+  let cmdline_id = tree.command_line_id().unwrap();
+  let cmdline = tree.command_line_mut().unwrap();
+  debug_assert!(cmdline.cursor_id().is_some());
+
+  // Remove from current parent
+  let cursor = match cmdline.remove_cursor().unwrap() {
+    CommandLineNode::Cursor(mut cursor) => {
+      cursor.set_style(&CursorStyle::SteadyBlock);
+      cursor
+    }
+    _ => unreachable!(),
+  };
+  debug_assert!(cmdline.cursor_id().is_none());
+
+  // Insert to new parent
+  let current_window = tree.current_window_mut().unwrap();
+  let cursor_viewport = current_window.cursor_viewport();
+  trace!("before viewport:{:?}", current_window.viewport());
+  trace!("before cursor_viewport:{:?}", cursor_viewport);
+  let _previous_cursor = current_window.insert_cursor(cursor);
+  debug_assert!(_previous_cursor.is_none());
+  current_window.move_cursor_to(
+    cursor_viewport.column_idx() as isize,
+    cursor_viewport.row_idx() as isize,
+  );
+
+  // Clear command-line contents.
+  let contents = data_access.contents.clone();
+  let mut contents = lock!(contents);
+  let cmdline_content =
+    contents.command_line_content().rope().to_compact_string();
+
+  cursor_ops::cursor_clear(
+    &mut tree,
+    cmdline_id,
+    contents.command_line_content_mut(),
+  );
+  contents.command_line_history_mut().reset_navigation();
+  contents.command_line_wildmenu_mut().clear();
+
+  tree
+    .command_line_mut()
+    .unwrap()
+    .indicator_mut()
+    .set_symbol(CommandLineIndicatorSymbol::Empty);
+
+  CompactString::new(cmdline_content.trim())
+}
+
+/// Handle the editing operations, i.e. the operations from [`get_edit_key`] and
+/// [`get_pending_operation`]. The history operations browse the `history_kind` history.
+pub fn handle_edit_op(
+  data_access: &StatefulDataAccess,
+  history_kind: HistoryKind,
+  op: Operation,
+) {
+  match op {
+    Operation::CursorMoveBy((_, _))
+    | Operation::CursorMoveUpBy(_)
+    | Operation::CursorMoveDownBy(_)
+    | Operation::CursorMoveLeftBy(_)
+    | Operation::CursorMoveRightBy(_)
+    | Operation::CursorMoveTo((_, _)) => cursor_move(data_access, op),
+    Operation::CursorInsert(text) => cursor_insert(data_access, text),
+    Operation::CursorDelete(n) => cursor_delete(data_access, n),
+    Operation::CommandLineHistoryPrevious => {
+      history_recall(data_access, history_kind, false)
+    }
+    Operation::CommandLineHistoryNext => {
+      history_recall(data_access, history_kind, true)
+    }
+    Operation::CommandLineInsertRegister(_)
+    | Operation::CommandLineInsertWordUnderCursor => {
+      let payload = {
+        let tree = lock!(data_access.tree);
+        let contents = lock!(data_access.contents);
+        match op {
+          Operation::CommandLineInsertRegister(name) => {
+            cmdline_ops::register_text(&tree, &contents, name)
+          }
+          _ => cmdline_ops::word_under_window_cursor(&tree),
+        }
+      };
+      if let Some(payload) = payload {
+        cursor_insert(data_access, cmdline_ops::single_line(&payload));
+      }
+    }
+    Operation::CommandLineDeleteWordBeforeCursor
+    | Operation::CommandLineDeleteToStart
+    | Operation::CommandLineCursorMoveWordLeft
+    | Operation::CommandLineCursorMoveWordRight => {
+      let tree = data_access.tree.clone();
+      let mut tree = lock!(tree);
+      debug_assert!(tree.command_line_id().is_some());
+      let cmdline_id = tree.command_line_id().unwrap();
+      let contents = data_access.contents.clone();
+      let mut contents = lock!(contents);
+      let text = contents.command_line_content_mut();
+      match op {
+        Operation::CommandLineDeleteWordBeforeCursor => {
+          cmdline_ops::cmdline_delete_word_before_cursor(
+            &mut tree, cmdline_id, text,
+          );
+          contents.command_line_history_mut().reset_navigation();
+        }
+        Operation::CommandLineDeleteToStart => {
+          cmdline_ops::cmdline_delete_to_start(&mut tree, cmdline_id, text);
+          contents.command_line_history_mut().reset_navigation();
+        }
+        Operation::CommandLineCursorMoveWordLeft => {
+          cmdline_ops::cmdline_move_word(&mut tree, cmdline_id, text, false)
+        }
+        Operation::CommandLineCursorMoveWordRight => {
+          cmdline_ops::cmdline_move_word(&mut tree, cmdline_id, text, true)
+        }
+        _ => unreachable!(),
+      }
+    }
+    _ => unreachable!(),
+  }
+}
@@ -1,15 +1,33 @@
 //! The command-line ex mode.
 
-use crate::js::msg::{EventLoopToJsRuntimeMessage, ExCommandReq};
+use crate::buf::BuffersManager;
+use crate::buf::text::Text;
+use crate::content::history::HistoryKind;
+use crate::content::message::{Message, MessageLevel};
+use crate::excommand::complete;
+use crate::excommand::map::MapKind;
+use crate::excommand::range::ExRange;
+use crate::excommand::{
+  BuiltinExCommand, ExCommandLine, history, map, messages, quit, set, terminal,
+};
+use crate::js::msg::{
+  CompletionReq, EventLoopToJsRuntimeMessage, ExCommandReq,
+};
 use crate::js::next_future_id;
+use crate::opt::{OPTIONS, OptionTarget, get_option_values};
 use crate::prelude::*;
-use crate::state::fsm::{Stateful, StatefulDataAccess, StatefulValue};
-use crate::state::ops::{Operation, cursor_ops};
-use crate::ui::canvas::CursorStyle;
-use crate::ui::tree::*;
-use crate::ui::widget::command_line::{
-  CommandLineIndicatorSymbol, CommandLineNode,
+use crate::state::autocmd::AutoCmdEvent;
+use crate::state::fsm::command_line_edit::{
+  self, CommandLineEditKey, CommandLinePendingKey,
 };
+use crate::state::fsm::{
+  QuitStateful, Stateful, StatefulDataAccess, StatefulValue, TerminalStateful,
+  feed_keys, play_keys,
+};
+use crate::state::keys;
+use crate::state::ops::{Operation, cmdline_ops, cursor_ops};
+use crate::state::opt::GlobalOptions;
+use crate::ui::tree::*;
 
 use compact_str::{CompactString, ToCompactString};
 use crossterm::event::{Event, KeyCode, KeyEventKind};
@@ -17,28 +35,20 @@ use tracing::trace;
 
 #[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
 /// The command-line ex mode.
-pub struct CommandLineExStateful {}
+pub struct CommandLineExStateful {
+  /// The key waits for the next key, i.e. `<C-R>` and `<C-V>`.
+  pending: Option<CommandLinePendingKey>,
+}
 
 impl CommandLineExStateful {
-  fn get_operation(&self, event: Event) -> Option<Operation> {
+  fn get_operation(&self, event: &Event) -> Option<Operation> {
     match event {
-      Event::FocusGained => None,
-      Event::FocusLost => None,
       Event::Key(key_event) => match key_event.kind {
         KeyEventKind::Press => {
           trace!("Event::key:{:?}", key_event);
           match key_event.code {
-            // KeyCode::Up | KeyCode::Char('k') => Some(Operation::CursorMoveUpBy(1)),
-            // KeyCode::Down | KeyCode::Char('j') => Some(Operation::CursorMoveDownBy(1)),
-            KeyCode::Left => Some(Operation::CursorMoveLeftBy(1)),
-            KeyCode::Right => Some(Operation::CursorMoveRightBy(1)),
-            KeyCode::Home => Some(Operation::CursorMoveLeftBy(usize::MAX)),
-            KeyCode::End => Some(Operation::CursorMoveRightBy(usize::MAX)),
-            KeyCode::Char(c) => {
-              Some(Operation::CursorInsert(c.to_compact_string()))
-            }
-            KeyCode::Backspace => Some(Operation::CursorDelete(-1)),
-            KeyCode::Delete => Some(Operation::CursorDelete(1)),
+            KeyCode::Tab => Some(Operation::CommandLineCompleteNext),
+            KeyCode::BackTab => Some(Operation::CommandLineCompletePrevious),
             KeyCode::Esc => Some(Operation::GotoNormalMode),
             KeyCode::Enter => {
               Some(Operation::ConfirmExCommandAndGotoNormalMode)
@@ -49,9 +59,7 @@ impl CommandLineExStateful {
         KeyEventKind::Repeat => None,
         KeyEventKind::Release => None,
       },
-      Event::Mouse(_mouse_event) => None,
-      Event::Paste(ref _paste_string) => None,
-      Event::Resize(_columns, _rows) => None,
+      _ => None,
     }
   }
 }
@@ -60,11 +68,32 @@ impl Stateful for CommandLineExStateful {
   fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
     let event = data_access.event.clone();
 
-    if let Some(op) = self.get_operation(event) {
+    if let Some(pending) = self.pending {
+      if let Some(op) =
+        command_line_edit::get_pending_operation(pending, &event)
+      {
+        return self.handle_op(data_access, op);
+      }
+      return StatefulValue::CommandLineExMode(CommandLineExStateful::default());
+    }
+
+    if let Some(op) = self.get_operation(&event) {
       return self.handle_op(data_access, op);
     }
 
-    StatefulValue::CommandLineExMode(CommandLineExStateful::default())
+    match command_line_edit::get_edit_key(&event) {
+      Some(CommandLineEditKey::Operation(op)) => {
+        self.handle_op(data_access, op)
+      }
+      Some(CommandLineEditKey::Pending(pending)) => {
+        StatefulValue::CommandLineExMode(CommandLineExStateful {
+          pending: Some(pending),
+        })
+      }
+      None => {
+        StatefulValue::CommandLineExMode(CommandLineExStateful::default())
+      }
+    }
   }
 
   fn handle_op(
@@ -72,6 +101,17 @@ impl Stateful for CommandLineExStateful {
     data_access: StatefulDataAccess,
     op: Operation,
   ) -> StatefulValue {
+    // Any other operations finish the completion.
+    if !matches!(
+      op,
+      Operation::CommandLineCompleteNext
+        | Operation::CommandLineCompletePrevious
+    ) {
+      lock!(data_access.contents)
+        .command_line_wildmenu_mut()
+        .clear();
+    }
+
     match op {
       Operation::CursorMoveBy((_, _))
       | Operation::CursorMoveUpBy(_)
@@ -85,9 +125,32 @@ impl Stateful for CommandLineExStateful {
       }
       Operation::CursorInsert(text) => self.cursor_insert(&data_access, text),
       Operation::CursorDelete(n) => self.cursor_delete(&data_access, n),
+      Operation::CommandLineHistoryPrevious => {
+        self.history_recall(&data_access, false)
+      }
+      Operation::CommandLineHistoryNext => {
+        self.history_recall(&data_access, true)
+      }
+      Operation::CommandLineCompleteNext => self.complete(&data_access, true),
+      Operation::CommandLineCompletePrevious => {
+        self.complete(&data_access, false)
+      }
+      Operation::CommandLineDeleteWordBeforeCursor
+      | Operation::CommandLineDeleteToStart
+      | Operation::CommandLineCursorMoveWordLeft
+      | Operation::CommandLineCursorMoveWordRight
+      | Operation::CommandLineInsertRegister(_)
+      | Operation::CommandLineInsertWordUnderCursor => {
+        command_line_edit::handle_edit_op(&data_access, HistoryKind::Cmd, op);
+        StatefulValue::CommandLineExMode(CommandLineExStateful::default())
+      }
       _ => unreachable!(),
     }
   }
+
+  fn is_literal_pending(&self) -> bool {
+    self.pending.is_some()
+  }
 }
 
 impl CommandLineExStateful {
@@ -96,78 +159,364 @@ impl CommandLineExStateful {
     data_access: &StatefulDataAccess,
   ) -> StatefulValue {
     let cmdline_content = self._goto_normal_mode_impl(data_access);
-    let state = data_access.state.clone();
-    let jsrt_tick_dispatcher = lock!(state).jsrt_tick_dispatcher().clone();
 
+    // Save to history, before executing it.
+    command_line_edit::history_add(
+      data_access,
+      HistoryKind::Cmd,
+      &cmdline_content,
+    );
+
+    // Builtin ex commands are executed directly, without js runtime.
+    match BuiltinExCommand::parse(&cmdline_content) {
+      Some(BuiltinExCommand::Normal(range, bang, keys)) => {
+        self.run_normal(data_access, range, bang, &keys);
+        return StatefulValue::NormalMode(super::NormalStateful::default());
+      }
+      Some(BuiltinExCommand::Terminal(command)) => {
+        return self.run_terminal(data_access, &command);
+      }
+      Some(BuiltinExCommand::Map(modes, kind, args)) => {
+        self.run_map(data_access, &modes, kind, &args);
+        return StatefulValue::NormalMode(super::NormalStateful::default());
+      }
+      Some(BuiltinExCommand::Quit(kind, bang, confirm)) => {
+        return QuitStateful::quit(data_access, kind, bang, confirm);
+      }
+      Some(builtin) => {
+        self.run_builtin_ex_command(data_access, builtin);
+        return StatefulValue::NormalMode(super::NormalStateful::default());
+      }
+      None => {}
+    }
+
+    self.run_user_ex_command(data_access, &cmdline_content);
+    StatefulValue::NormalMode(super::NormalStateful::default())
+  }
+}
+
+impl CommandLineExStateful {
+  /// Execute a user ex command created by `Rsvim.cmd.create`, the parsed arguments are sent to
+  /// its js callback. The errors (i.e. the command is not found, or the arguments are invalid)
+  /// are shown in the message area.
+  pub fn run_user_ex_command(
+    &self,
+    data_access: &StatefulDataAccess,
+    cmdline_content: &str,
+  ) {
+    let source = cmdline_content
+      .trim_start_matches(|c: char| c == ':' || c.is_whitespace());
+    // Nothing to run, i.e. the empty command-line.
+    if ExRange::parse(source).1.trim().is_empty() {
+      return;
+    }
+    let found = match ExCommandLine::parse(source) {
+      Some(cmdline) => lock!(data_access.state)
+        .ex_commands()
+        .find(&cmdline.name)
+        .map(|cmd| (cmdline, cmd.clone())),
+      None => Err(ExCommandErr::NotAnEditorCommand(source.to_string())),
+    };
+    let prepared = found.and_then(|(cmdline, cmd)| {
+      let tree = lock!(data_access.tree);
+      let window = tree.current_window();
+      let current = window.map(|w| w.cursor_viewport().line_idx());
+      let args = match window.and_then(|w| w.buffer().upgrade()) {
+        Some(buffer) => {
+          let buffer = lock!(buffer);
+          let last = _last_line_idx(buffer.text());
+          cmd.prepare(&cmdline, current.unwrap_or(0), last, |c| {
+            buffer.mark(c).map(|m| m.0)
+          })
+        }
+        None => cmd.prepare(&cmdline, 0, 0, |_| None),
+      };
+      args.map(|args| (cmd.callback_id(), args))
+    });
+    trace!("user ex command:{:?}", prepared);
+
+    let (callback_id, args) = match prepared {
+      Ok(prepared) => prepared,
+      Err(e) => {
+        lock!(data_access.contents)
+          .messages_mut()
+          .add(MessageLevel::Error, &e.to_string());
+        return;
+      }
+    };
+    let jsrt_tick_dispatcher =
+      lock!(data_access.state).jsrt_tick_dispatcher().clone();
     let current_handle = tokio::runtime::Handle::current();
     current_handle.spawn_blocking(move || {
       jsrt_tick_dispatcher
         .blocking_send(EventLoopToJsRuntimeMessage::ExCommandReq(
-          ExCommandReq::new(next_future_id(), cmdline_content),
+          ExCommandReq::new(next_future_id(), callback_id, args),
         ))
         .unwrap();
     });
+  }
+}
 
-    StatefulValue::NormalMode(super::NormalStateful::default())
+impl CommandLineExStateful {
+  /// Execute `:normal`, plays the keys in normal mode on each line of the range (the cursor moves
+  /// to the start of the line first), or at the cursor if there's no range. An incomplete command
+  /// is aborted like `<Esc>` is typed. The key mappings are not applied with `bang`, i.e.
+  /// `:normal!`.
+  pub fn run_normal(
+    &self,
+    data_access: &StatefulDataAccess,
+    range: Option<ExRange>,
+    bang: bool,
+    keys: &str,
+  ) {
+    let lines = match range {
+      Some(range) => {
+        let tree = data_access.tree.clone();
+        let tree = lock!(tree);
+        let current_window = tree.current_window().unwrap();
+        let current = current_window.cursor_viewport().line_idx();
+        let buffer = current_window.buffer().upgrade().unwrap();
+        let buffer = lock!(buffer);
+        let last = _last_line_idx(buffer.text());
+        match range.resolve(current, last, |c| buffer.mark(c).map(|m| m.0)) {
+          Ok(lines) => Some(lines),
+          Err(e) => {
+            let contents = data_access.contents.clone();
+            lock!(contents)
+              .messages_mut()
+              .add(MessageLevel::Error, &e.to_string());
+            return;
+          }
+        }
+      }
+      None => None,
+    };
+
+    let keys = keys::parse(keys);
+    let play = || {
+      let stateful = play_keys(
+        data_access,
+        StatefulValue::NormalMode(super::NormalStateful::default()),
+        &keys,
+        1,
+        !bang,
+      );
+      if !matches!(stateful, StatefulValue::NormalMode(_)) {
+        feed_keys(data_access, stateful, &keys::parse("<Esc>"));
+      }
+    };
+
+    match lines {
+      Some(lines) => {
+        for line_idx in lines {
+          // The keys may delete lines.
+          {
+            let tree = data_access.tree.clone();
+            let mut tree = lock!(tree);
+            let current_window = tree.current_window().unwrap();
+            let current_window_id = current_window.id();
+            let buffer = current_window.buffer().upgrade().unwrap();
+            let buffer = lock!(buffer);
+            if line_idx > _last_line_idx(buffer.text()) {
+              break;
+            }
+            cursor_ops::cursor_move(
+              &mut tree,
+              current_window_id,
+              buffer.text(),
+              Operation::CursorMoveTo((0, line_idx)),
+              false,
+            );
+          }
+          play();
+        }
+      }
+      None => play(),
+    }
   }
 }
 
 impl CommandLineExStateful {
-  pub fn _goto_normal_mode_impl(
+  /// Execute `:terminal`, runs the command in a new terminal buffer in current window and goes to
+  /// terminal mode. The error is shown in the message area, i.e. the PTY is not supported.
+  pub fn run_terminal(
     &self,
     data_access: &StatefulDataAccess,
-  ) -> CompactString {
+    command: &str,
+  ) -> StatefulValue {
+    let worker_send_to_master =
+      lock!(data_access.state).worker_send_to_master().clone();
+
     let tree = data_access.tree.clone();
     let mut tree = lock!(tree);
-
-    debug_assert!(tree.command_line_id().is_some());
-    let cmdline_id = tree.command_line_id().unwrap();
-    let cmdline = tree.command_line_mut().unwrap();
-    debug_assert!(cmdline.cursor_id().is_some());
-
-    // Remove from current parent
-    let cursor = match cmdline.remove_cursor().unwrap() {
-      CommandLineNode::Cursor(mut cursor) => {
-        cursor.set_style(&CursorStyle::SteadyBlock);
-        cursor
+    let buffers = data_access.buffers.clone();
+    let mut buffers = lock!(buffers);
+    match terminal::execute(
+      &mut tree,
+      &mut buffers,
+      worker_send_to_master,
+      command,
+    ) {
+      Ok(buffer_id) => {
+        trace!("terminal buffer:{:?}", buffer_id);
+        StatefulValue::TerminalMode(TerminalStateful::default())
       }
-      _ => unreachable!(),
+      Err(e) => {
+        let contents = data_access.contents.clone();
+        lock!(contents)
+          .messages_mut()
+          .add(MessageLevel::Error, &e.to_string());
+        StatefulValue::NormalMode(super::NormalStateful::default())
+      }
+    }
+  }
+}
+
+impl CommandLineExStateful {
+  /// Execute `:map`, `:noremap`, `:unmap` and their mode variants, the `<buffer>` mappings are
+  /// local to current buffer.
+  pub fn run_map(
+    &self,
+    data_access: &StatefulDataAccess,
+    modes: &str,
+    kind: MapKind,
+    args: &str,
+  ) {
+    let buffer_id = {
+      let tree = data_access.tree.clone();
+      let tree = lock!(tree);
+      let buffer = tree.current_window().unwrap().buffer().upgrade().unwrap();
+      lock!(buffer).id()
     };
-    debug_assert!(cmdline.cursor_id().is_none());
-
-    // Insert to new parent
-    let current_window = tree.current_window_mut().unwrap();
-    let cursor_viewport = current_window.cursor_viewport();
-    trace!("before viewport:{:?}", current_window.viewport());
-    trace!("before cursor_viewport:{:?}", cursor_viewport);
-    let _current_window_id = current_window.id();
-    let _previous_cursor = current_window.insert_cursor(cursor);
-    debug_assert!(_previous_cursor.is_none());
-    current_window.move_cursor_to(
-      cursor_viewport.column_idx() as isize,
-      cursor_viewport.row_idx() as isize,
-    );
+    let output = {
+      let mut state = lock!(data_access.state);
+      map::execute(state.keymaps_mut(), buffer_id, modes, kind, args)
+    };
+    trace!("map output:{:?}", output);
 
-    // Clear command-line contents.
     let contents = data_access.contents.clone();
     let mut contents = lock!(contents);
-    let cmdline_content =
-      contents.command_line_content().rope().to_compact_string();
+    let messages = contents.messages_mut();
+    match output {
+      Ok(lines) => messages.echo(MessageLevel::Info, &lines.join("\n")),
+      Err(e) => messages.add(MessageLevel::Error, &e.to_string()),
+    }
+  }
+}
 
-    cursor_ops::cursor_clear(
-      &mut tree,
-      cmdline_id,
-      contents.command_line_content_mut(),
-    );
+// The index of the last line, the empty line after the last line break is not a line.
+fn _last_line_idx(text: &Text) -> usize {
+  let rope = text.rope();
+  let len_lines = rope.len_lines();
+  if len_lines > 1 && rope.line(len_lines - 1).len_chars() == 0 {
+    len_lines - 2
+  } else {
+    len_lines.saturating_sub(1)
+  }
+}
+
+impl CommandLineExStateful {
+  /// Execute a builtin ex command, and show its output (or error) in the message area.
+  pub fn run_builtin_ex_command(
+    &self,
+    data_access: &StatefulDataAccess,
+    cmd: BuiltinExCommand,
+  ) {
+    let tree = data_access.tree.clone();
+    let mut tree = lock!(tree);
+    let buffers = data_access.buffers.clone();
+    let mut buffers = lock!(buffers);
+    let contents = data_access.contents.clone();
+    let mut contents = lock!(contents);
 
-    let cmdline_content = cmdline_content.trim();
-    tree
-      .command_line_mut()
-      .unwrap()
-      .indicator_mut()
-      .set_symbol(CommandLineIndicatorSymbol::Empty);
+    let output = match cmd {
+      BuiltinExCommand::Set(target, args) => {
+        // The `:set` writes both the local and global values, either of them can be changed.
+        let values =
+          |tree: &Tree, buffers: &BuffersManager, options: &GlobalOptions| {
+            [target, OptionTarget::Global]
+              .map(|target| get_option_values(tree, buffers, options, target))
+          };
+        let mut state = lock!(data_access.state);
+        let [old_values, old_global_values] =
+          values(&tree, &buffers, state.global_options());
+        let result = set::execute(
+          &mut tree,
+          &mut buffers,
+          state.global_options_mut(),
+          target,
+          &args,
+        );
+        let [new_values, new_global_values] =
+          values(&tree, &buffers, state.global_options());
+        for (i, def) in OPTIONS.iter().enumerate() {
+          if old_values[i] != new_values[i]
+            || old_global_values[i] != new_global_values[i]
+          {
+            state.autocmds_mut().fire(
+              AutoCmdEvent::OptionSet,
+              None,
+              def.name(),
+            );
+          }
+        }
+        result
+          .map(|lines| {
+            // Listing options shows one option per line, otherwise the queried options are shown
+            // in a single line.
+            let separator = if lines.len() > 1 && lines[0].starts_with("---") {
+              "\n"
+            } else {
+              "  "
+            };
+            vec![Message::new(MessageLevel::Info, &lines.join(separator))]
+          })
+          .map_err(|e| e.to_string())
+      }
+      BuiltinExCommand::History(args) => {
+        history::execute(contents.command_line_history(), &args)
+          .map(|lines| {
+            vec![Message::new(MessageLevel::Info, &lines.join("\n"))]
+          })
+          .map_err(|e| e.to_string())
+      }
+      BuiltinExCommand::Normal(..)
+      | BuiltinExCommand::Terminal(_)
+      | BuiltinExCommand::Map(..)
+      | BuiltinExCommand::Quit(..) => {
+        unreachable!()
+      }
+      BuiltinExCommand::Write => quit::write(&tree, &mut buffers)
+        .map(|line| vec![Message::new(MessageLevel::Info, &line)])
+        .map_err(|e| e.to_string()),
+      BuiltinExCommand::Messages(args) => {
+        let max_size =
+          lock!(data_access.state).global_options().message_history();
+        let messages = contents.messages_mut();
+        messages.set_max_size(max_size as usize);
+        messages::execute(messages, &args).map_err(|e| e.to_string())
+      }
+    };
+    trace!("builtin ex command output:{:?}", output);
+
+    let messages = contents.messages_mut();
+    match output {
+      Ok(output) => {
+        for message in output {
+          messages.echo(message.level(), message.text());
+        }
+      }
+      Err(e) => messages.add(MessageLevel::Error, &e),
+    }
+  }
+}
 
-    CompactString::new(cmdline_content)
+impl CommandLineExStateful {
+  pub fn _goto_normal_mode_impl(
+    &self,
+    data_access: &StatefulDataAccess,
+  ) -> CompactString {
+    command_line_edit::leave_command_line(data_access)
   }
 
   pub fn goto_normal_mode(
@@ -186,21 +535,7 @@ impl CommandLineExStateful {
     data_access: &StatefulDataAccess,
     op: Operation,
   ) -> StatefulValue {
-    let tree = data_access.tree.clone();
-    let mut tree = lock!(tree);
-    debug_assert!(tree.command_line_id().is_some());
-    let cmdline_id = tree.command_line_id().unwrap();
-    let contents = data_access.contents.clone();
-    let contents = lock!(contents);
-
-    cursor_ops::cursor_move(
-      &mut tree,
-      cmdline_id,
-      contents.command_line_content(),
-      op,
-      true,
-    );
-
+    command_line_edit::cursor_move(data_access, op);
     StatefulValue::CommandLineExMode(CommandLineExStateful::default())
   }
 }
@@ -210,20 +545,115 @@ impl CommandLineExStateful {
     &self,
     data_access: &StatefulDataAccess,
     payload: CompactString,
+  ) -> StatefulValue {
+    command_line_edit::cursor_insert(data_access, payload);
+    StatefulValue::CommandLineExMode(CommandLineExStateful::default())
+  }
+}
+
+impl CommandLineExStateful {
+  /// Replace the command-line content with the older (or newer if `newer` is `true`) history.
+  pub fn history_recall(
+    &self,
+    data_access: &StatefulDataAccess,
+    newer: bool,
+  ) -> StatefulValue {
+    command_line_edit::history_recall(data_access, HistoryKind::Cmd, newer);
+    StatefulValue::CommandLineExMode(CommandLineExStateful::default())
+  }
+}
+
+impl CommandLineExStateful {
+  /// Complete the command-line content before cursor, or select the next (or previous if
+  /// `forward` is `false`) match if the completion is already started.
+  pub fn complete(
+    &self,
+    data_access: &StatefulDataAccess,
+    forward: bool,
   ) -> StatefulValue {
     let tree = data_access.tree.clone();
     let mut tree = lock!(tree);
     debug_assert!(tree.command_line_id().is_some());
     let cmdline_id = tree.command_line_id().unwrap();
+    let buffers = data_access.buffers.clone();
+    let buffers = lock!(buffers);
     let contents = data_access.contents.clone();
     let mut contents = lock!(contents);
+    let global_options = lock!(data_access.state).global_options().clone();
+
+    let wildmenu = contents.command_line_wildmenu();
+    if wildmenu.pending().is_some() {
+      // Still waiting for the js completer.
+      return StatefulValue::CommandLineExMode(CommandLineExStateful::default());
+    }
+
+    if wildmenu.is_active() {
+      contents.command_line_wildmenu_mut().increase_tab_count();
+    } else {
+      let before_cursor =
+        cmdline_ops::cmdline_text_before_cursor(&tree, &contents);
+      let context = complete::parse_context(&before_cursor);
+      trace!("complete context:{:?}", context);
+      let ex_commands = lock!(data_access.state).ex_commands().clone();
+      match cmdline_ops::collect_completion_matches(
+        &tree,
+        &buffers,
+        &global_options,
+        &ex_commands,
+        &context,
+      ) {
+        Some(items) => {
+          contents.command_line_wildmenu_mut().start(
+            items,
+            context.start_char_idx,
+            context.lead,
+          );
+        }
+        None => {
+          // Ask js runtime for the matches, they're filled when the completer returns.
+          let future_id = next_future_id();
+          contents.command_line_wildmenu_mut().start_pending(
+            future_id,
+            context.start_char_idx,
+            context.lead.clone(),
+          );
+          // The completer is set by the full name of the user ex command.
+          let name = ex_commands
+            .find(&context.command)
+            .map(|cmd| cmd.name().clone())
+            .unwrap_or(context.command);
+          let req = CompletionReq::new(
+            future_id,
+            name,
+            context.lead,
+            contents.command_line_content().rope().to_compact_string(),
+            before_cursor.chars().count(),
+          );
+
+          let state = data_access.state.clone();
+          let jsrt_tick_dispatcher =
+            lock!(state).jsrt_tick_dispatcher().clone();
+          let current_handle = tokio::runtime::Handle::current();
+          current_handle.spawn_blocking(move || {
+            jsrt_tick_dispatcher
+              .blocking_send(EventLoopToJsRuntimeMessage::CompletionReq(req))
+              .unwrap();
+          });
+          return StatefulValue::CommandLineExMode(
+            CommandLineExStateful::default(),
+          );
+        }
+      }
+    }
 
-    cursor_ops::cursor_insert(
+    cmdline_ops::cmdline_complete_apply(
       &mut tree,
       cmdline_id,
-      contents.command_line_content_mut(),
-      payload,
+      &mut contents,
+      &global_options,
+      forward,
     );
+    contents.command_line_history_mut().reset_navigation();
 
     StatefulValue::CommandLineExMode(CommandLineExStateful::default())
   }
@@ -235,24 +665,7 @@ impl CommandLineExStateful {
     data_access: &StatefulDataAccess,
     n: isize,
   ) -> StatefulValue {
-    let tree = data_access.tree.clone();
-    let mut tree = lock!(tree);
-    let contents = data_access.contents.clone();
-    let mut contents = lock!(contents);
-    let text = contents.command_line_content_mut();
-
-    let cmdline = tree.command_line_mut().unwrap();
-    let cmdline_id = cmdline.id();
-    debug_assert_eq!(cmdline.cursor_viewport().line_idx(), 0);
-    debug_assert!(
-      text
-        .rope()
-        .get_line(cmdline.cursor_viewport().line_idx())
-        .is_some()
-    );
-
-    cursor_ops::cursor_delete(&mut tree, cmdline_id, text, n);
-
+    command_line_edit::cursor_delete(data_access, n);
     StatefulValue::CommandLineExMode(CommandLineExStateful::default())
   }
 }
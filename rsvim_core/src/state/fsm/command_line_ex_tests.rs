@@ -785,3 +785,598 @@ mod tests_confirm_ex_command_and_goto_normal_mode {
     }
   }
 }
+
+#[cfg(test)]
+mod tests_history_recall {
+  use super::*;
+
+  use crate::content::history::HistoryKind;
+  use crate::state::fsm::NormalStateful;
+
+  fn cmdline_content(contents: &TextContentsArc) -> String {
+    lock!(contents).command_line_content().rope().to_string()
+  }
+
+  #[test]
+  fn recall1() {
+    test_log_init();
+
+    let terminal_size = U16Size::new(20, 5);
+    let window_options = WindowLocalOptionsBuilder::default().build().unwrap();
+    let (tree, state, bufs, _buf, contents) =
+      make_tree_with_cmdline(terminal_size, window_options, vec![]);
+    {
+      let mut contents = lock!(contents);
+      let history = contents.command_line_history_mut();
+      history.add(HistoryKind::Cmd, "set ts=4");
+      history.add(HistoryKind::Cmd, "js 1");
+      history.add(HistoryKind::Cmd, "set nowrap");
+    }
+
+    let key_event = KeyEvent::new_with_kind(
+      KeyCode::Up,
+      KeyModifiers::empty(),
+      KeyEventKind::Press,
+    );
+    let data_access = StatefulDataAccess::new(
+      state,
+      tree.clone(),
+      bufs,
+      contents.clone(),
+      Event::Key(key_event),
+    );
+    NormalStateful::default().goto_command_line_ex_mode(&data_access);
+
+    let stateful = CommandLineExStateful::default();
+    stateful.cursor_insert(&data_access, CompactString::new("se"));
+
+    stateful.handle_op(
+      data_access_clone(&data_access),
+      Operation::CommandLineHistoryPrevious,
+    );
+    assert_eq!(cmdline_content(&contents), "set nowrap");
+    let cursor = lock!(tree).command_line().unwrap().cursor_viewport();
+    assert_eq!(cursor.char_idx(), 10);
+
+    stateful.handle_op(
+      data_access_clone(&data_access),
+      Operation::CommandLineHistoryPrevious,
+    );
+    assert_eq!(cmdline_content(&contents), "set ts=4");
+
+    // No more older entries.
+    stateful.handle_op(
+      data_access_clone(&data_access),
+      Operation::CommandLineHistoryPrevious,
+    );
+    assert_eq!(cmdline_content(&contents), "set ts=4");
+
+    stateful.handle_op(
+      data_access_clone(&data_access),
+      Operation::CommandLineHistoryNext,
+    );
+    assert_eq!(cmdline_content(&contents), "set nowrap");
+
+    // Back to the typed text.
+    stateful.handle_op(
+      data_access_clone(&data_access),
+      Operation::CommandLineHistoryNext,
+    );
+    assert_eq!(cmdline_content(&contents), "se");
+
+    // Confirm adds the command to history.
+    stateful.cursor_insert(&data_access, CompactString::new("t so=1"));
+    stateful.confirm_ex_command_and_goto_normal_mode(&data_access);
+    let contents = lock!(contents);
+    let entries: Vec<&str> = contents
+      .command_line_history()
+      .entries(HistoryKind::Cmd)
+      .iter()
+      .map(|e| e.text().as_str())
+      .collect();
+    assert_eq!(entries, vec!["set ts=4", "js 1", "set nowrap", "set so=1"]);
+  }
+
+  #[test]
+  fn recall_after_edit1() {
+    test_log_init();
+
+    let terminal_size = U16Size::new(20, 5);
+    let window_options = WindowLocalOptionsBuilder::default().build().unwrap();
+    let (tree, state, bufs, _buf, contents) =
+      make_tree_with_cmdline(terminal_size, window_options, vec![]);
+    {
+      let mut contents = lock!(contents);
+      let history = contents.command_line_history_mut();
+      history.add(HistoryKind::Cmd, "js 1");
+      history.add(HistoryKind::Cmd, "set ts=4");
+      history.add(HistoryKind::Cmd, "set nowrap");
+    }
+
+    let key_event = KeyEvent::new_with_kind(
+      KeyCode::Up,
+      KeyModifiers::empty(),
+      KeyEventKind::Press,
+    );
+    let data_access = StatefulDataAccess::new(
+      state,
+      tree.clone(),
+      bufs,
+      contents.clone(),
+      Event::Key(key_event),
+    );
+    NormalStateful::default().goto_command_line_ex_mode(&data_access);
+
+    let stateful = CommandLineExStateful::default();
+    stateful.cursor_insert(&data_access, CompactString::new("se"));
+    stateful.handle_op(
+      data_access_clone(&data_access),
+      Operation::CommandLineHistoryPrevious,
+    );
+    assert_eq!(cmdline_content(&contents), "set nowrap");
+
+    // The edited content is the new prefix.
+    stateful.handle_op(
+      data_access_clone(&data_access),
+      Operation::CommandLineDeleteToStart,
+    );
+    stateful.cursor_insert(&data_access, CompactString::new("j"));
+    stateful.handle_op(
+      data_access_clone(&data_access),
+      Operation::CommandLineHistoryPrevious,
+    );
+    assert_eq!(cmdline_content(&contents), "js 1");
+
+    stateful.cursor_delete(&data_access, -3);
+    assert_eq!(cmdline_content(&contents), "j");
+    stateful.handle_op(
+      data_access_clone(&data_access),
+      Operation::CommandLineHistoryNext,
+    );
+    assert_eq!(cmdline_content(&contents), "j");
+    stateful.cursor_delete(&data_access, -1);
+    stateful.handle_op(
+      data_access_clone(&data_access),
+      Operation::CommandLineHistoryPrevious,
+    );
+    assert_eq!(cmdline_content(&contents), "set nowrap");
+  }
+
+  fn data_access_clone(data_access: &StatefulDataAccess) -> StatefulDataAccess {
+    StatefulDataAccess::new(
+      data_access.state.clone(),
+      data_access.tree.clone(),
+      data_access.buffers.clone(),
+      data_access.contents.clone(),
+      data_access.event.clone(),
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests_complete {
+  use super::*;
+
+  use crate::state::fsm::NormalStateful;
+
+  fn cmdline_content(contents: &TextContentsArc) -> String {
+    lock!(contents).command_line_content().rope().to_string()
+  }
+
+  fn make_cmdline(
+    typed: &str,
+  ) -> (TreeArc, TextContentsArc, StatefulDataAccess) {
+    let terminal_size = U16Size::new(30, 5);
+    let window_options = WindowLocalOptionsBuilder::default().build().unwrap();
+    let (tree, state, bufs, _buf, contents) =
+      make_tree_with_cmdline(terminal_size, window_options, vec![]);
+
+    let key_event = KeyEvent::new_with_kind(
+      KeyCode::Tab,
+      KeyModifiers::empty(),
+      KeyEventKind::Press,
+    );
+    let data_access = StatefulDataAccess::new(
+      state,
+      tree.clone(),
+      bufs,
+      contents.clone(),
+      Event::Key(key_event),
+    );
+    NormalStateful::default().goto_command_line_ex_mode(&data_access);
+    CommandLineExStateful::default()
+      .cursor_insert(&data_access, CompactString::new(typed));
+    (tree, contents, data_access)
+  }
+
+  fn tab(data_access: &StatefulDataAccess, forward: bool) {
+    let op = if forward {
+      Operation::CommandLineCompleteNext
+    } else {
+      Operation::CommandLineCompletePrevious
+    };
+    CommandLineExStateful::default()
+      .handle_op(data_access_clone(data_access), op);
+  }
+
+  #[test]
+  fn full1() {
+    test_log_init();
+    let (tree, contents, data_access) = make_cmdline("se");
+
+    tab(&data_access, true);
+    assert_eq!(cmdline_content(&contents), "set");
+    assert!(lock!(contents).command_line_wildmenu().visible());
+    assert_eq!(lock!(contents).command_line_wildmenu().items().len(), 3);
+    tab(&data_access, true);
+    assert_eq!(cmdline_content(&contents), "setglobal");
+    let cursor = lock!(tree).command_line().unwrap().cursor_viewport();
+    assert_eq!(cursor.char_idx(), 9);
+    tab(&data_access, true);
+    assert_eq!(cmdline_content(&contents), "setlocal");
+    // Back to the typed text.
+    tab(&data_access, true);
+    assert_eq!(cmdline_content(&contents), "se");
+    tab(&data_access, false);
+    assert_eq!(cmdline_content(&contents), "setlocal");
+
+    // Other keys finish the completion.
+    CommandLineExStateful::default().handle_op(
+      data_access_clone(&data_access),
+      Operation::CursorInsert(CompactString::new(" ")),
+    );
+    assert_eq!(cmdline_content(&contents), "setlocal ");
+    assert!(!lock!(contents).command_line_wildmenu().is_active());
+  }
+
+  #[test]
+  fn single1() {
+    test_log_init();
+    let (_tree, contents, data_access) = make_cmdline("set nowr");
+
+    tab(&data_access, true);
+    assert_eq!(cmdline_content(&contents), "set nowrap");
+    assert!(!lock!(contents).command_line_wildmenu().is_active());
+
+    // No matches.
+    CommandLineExStateful::default()
+      .cursor_insert(&data_access, CompactString::new(" xyz"));
+    tab(&data_access, true);
+    assert_eq!(cmdline_content(&contents), "set nowrap xyz");
+    assert!(!lock!(contents).command_line_wildmenu().is_active());
+  }
+
+  #[test]
+  fn option_value1() {
+    test_log_init();
+    let (_tree, contents, data_access) = make_cmdline("set ff=");
+
+    // The current value is the first match.
+    tab(&data_access, true);
+    assert_eq!(cmdline_content(&contents), "set ff=unix");
+    tab(&data_access, true);
+    assert_eq!(cmdline_content(&contents), "set ff=dos");
+  }
+
+  #[test]
+  fn longest1() {
+    test_log_init();
+    let (_tree, contents, data_access) = make_cmdline("set wi");
+    {
+      let mut state = lock!(data_access.state);
+      let mut options = state.global_options().clone();
+      options.set_wild_mode("longest:full,full");
+      options.set_wild_options("pum");
+      state.set_global_options(&options);
+    }
+
+    // Complete the longest common string and show the wildmenu.
+    tab(&data_access, true);
+    assert_eq!(cmdline_content(&contents), "set wild");
+    {
+      let contents = lock!(contents);
+      let wildmenu = contents.command_line_wildmenu();
+      assert!(wildmenu.visible());
+      assert!(wildmenu.popup());
+      assert_eq!(wildmenu.selected(), None);
+    }
+
+    tab(&data_access, true);
+    assert_eq!(cmdline_content(&contents), "set wildignore");
+    tab(&data_access, true);
+    assert_eq!(cmdline_content(&contents), "set wildmode");
+  }
+
+  fn data_access_clone(data_access: &StatefulDataAccess) -> StatefulDataAccess {
+    StatefulDataAccess::new(
+      data_access.state.clone(),
+      data_access.tree.clone(),
+      data_access.buffers.clone(),
+      data_access.contents.clone(),
+      data_access.event.clone(),
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests_edit_keys {
+  use super::*;
+
+  use crate::content::history::HistoryKind;
+  use crate::state::fsm::NormalStateful;
+
+  fn cmdline_content(contents: &TextContentsArc) -> String {
+    lock!(contents).command_line_content().rope().to_string()
+  }
+
+  fn cursor_char_idx(tree: &TreeArc) -> usize {
+    lock!(tree)
+      .command_line()
+      .unwrap()
+      .cursor_viewport()
+      .char_idx()
+  }
+
+  fn make_cmdline(
+    lines: Vec<&str>,
+    typed: &str,
+  ) -> (TreeArc, TextContentsArc, StatefulDataAccess) {
+    let terminal_size = U16Size::new(30, 5);
+    let window_options = WindowLocalOptionsBuilder::default().build().unwrap();
+    let (tree, state, bufs, _buf, contents) =
+      make_tree_with_cmdline(terminal_size, window_options, lines);
+
+    let data_access = StatefulDataAccess::new(
+      state,
+      tree.clone(),
+      bufs,
+      contents.clone(),
+      Event::FocusGained,
+    );
+    NormalStateful::default().goto_command_line_ex_mode(&data_access);
+    CommandLineExStateful::default()
+      .cursor_insert(&data_access, CompactString::new(typed));
+    (tree, contents, data_access)
+  }
+
+  /// Send the events to the stateful, returns the last stateful.
+  fn press(
+    data_access: &StatefulDataAccess,
+    events: Vec<Event>,
+  ) -> CommandLineExStateful {
+    let mut stateful = CommandLineExStateful::default();
+    for event in events {
+      let data_access = StatefulDataAccess::new(
+        data_access.state.clone(),
+        data_access.tree.clone(),
+        data_access.buffers.clone(),
+        data_access.contents.clone(),
+        event,
+      );
+      stateful = match stateful.handle(data_access) {
+        StatefulValue::CommandLineExMode(s) => s,
+        _ => unreachable!(),
+      };
+    }
+    stateful
+  }
+
+  fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new_with_kind(
+      code,
+      KeyModifiers::empty(),
+      KeyEventKind::Press,
+    ))
+  }
+
+  fn ctrl(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new_with_kind(
+      code,
+      KeyModifiers::CONTROL,
+      KeyEventKind::Press,
+    ))
+  }
+
+  #[test]
+  fn delete_word1() {
+    test_log_init();
+    let (tree, contents, data_access) =
+      make_cmdline(vec![], "set ts=4  foo.bar  ");
+
+    press(&data_access, vec![ctrl(KeyCode::Char('w'))]);
+    assert_eq!(cmdline_content(&contents), "set ts=4  foo.");
+    press(&data_access, vec![ctrl(KeyCode::Char('w'))]);
+    assert_eq!(cmdline_content(&contents), "set ts=4  foo");
+    press(&data_access, vec![ctrl(KeyCode::Char('w'))]);
+    assert_eq!(cmdline_content(&contents), "set ts=4  ");
+    press(&data_access, vec![ctrl(KeyCode::Char('w'))]);
+    assert_eq!(cmdline_content(&contents), "set ts=");
+    assert_eq!(cursor_char_idx(&tree), 7);
+
+    // Delete to start, keep the text after cursor.
+    press(
+      &data_access,
+      vec![
+        key(KeyCode::Left),
+        key(KeyCode::Left),
+        ctrl(KeyCode::Char('u')),
+      ],
+    );
+    assert_eq!(cmdline_content(&contents), "s=");
+    assert_eq!(cursor_char_idx(&tree), 0);
+  }
+
+  #[test]
+  fn move1() {
+    test_log_init();
+    let (tree, _contents, data_access) = make_cmdline(vec![], "set ts=4 sw=4");
+
+    press(&data_access, vec![ctrl(KeyCode::Char('a'))]);
+    assert_eq!(cursor_char_idx(&tree), 0);
+    press(&data_access, vec![ctrl(KeyCode::Char('e'))]);
+    assert_eq!(cursor_char_idx(&tree), 13);
+    press(&data_access, vec![ctrl(KeyCode::Char('b'))]);
+    assert_eq!(cursor_char_idx(&tree), 0);
+
+    press(&data_access, vec![ctrl(KeyCode::Right)]);
+    assert_eq!(cursor_char_idx(&tree), 4);
+    press(&data_access, vec![ctrl(KeyCode::Right)]);
+    assert_eq!(cursor_char_idx(&tree), 9);
+    press(&data_access, vec![ctrl(KeyCode::Right)]);
+    assert_eq!(cursor_char_idx(&tree), 13);
+    press(&data_access, vec![ctrl(KeyCode::Left)]);
+    assert_eq!(cursor_char_idx(&tree), 9);
+    press(&data_access, vec![ctrl(KeyCode::Left), ctrl(KeyCode::Left)]);
+    assert_eq!(cursor_char_idx(&tree), 0);
+  }
+
+  #[test]
+  fn insert_register1() {
+    test_log_init();
+    let (_tree, contents, data_access) =
+      make_cmdline(vec!["hello world\n"], "echo ");
+    {
+      let mut contents = lock!(contents);
+      contents.registers_mut().set('a', "abc\n");
+      contents
+        .command_line_history_mut()
+        .add(HistoryKind::Cmd, "set nowrap");
+    }
+
+    let stateful = press(&data_access, vec![ctrl(KeyCode::Char('r'))]);
+    assert_ne!(stateful, CommandLineExStateful::default());
+    press(
+      &data_access,
+      vec![ctrl(KeyCode::Char('r')), key(KeyCode::Char('a'))],
+    );
+    assert_eq!(cmdline_content(&contents), "echo abc");
+
+    press(
+      &data_access,
+      vec![ctrl(KeyCode::Char('r')), key(KeyCode::Char(':'))],
+    );
+    assert_eq!(cmdline_content(&contents), "echo abcset nowrap");
+
+    // Empty register inserts nothing.
+    press(
+      &data_access,
+      vec![ctrl(KeyCode::Char('r')), key(KeyCode::Char('z'))],
+    );
+    assert_eq!(cmdline_content(&contents), "echo abcset nowrap");
+
+    // Word under cursor of current window.
+    press(&data_access, vec![ctrl(KeyCode::Char('u'))]);
+    press(
+      &data_access,
+      vec![ctrl(KeyCode::Char('r')), ctrl(KeyCode::Char('w'))],
+    );
+    assert_eq!(cmdline_content(&contents), "hello");
+
+    // Cancelled by Esc, stay in command-line.
+    let stateful = press(
+      &data_access,
+      vec![ctrl(KeyCode::Char('r')), key(KeyCode::Esc)],
+    );
+    assert_eq!(stateful, CommandLineExStateful::default());
+    assert_eq!(cmdline_content(&contents), "hello");
+  }
+
+  #[test]
+  fn insert_literal1() {
+    test_log_init();
+    let (_tree, contents, data_access) = make_cmdline(vec![], "a");
+
+    press(
+      &data_access,
+      vec![ctrl(KeyCode::Char('v')), key(KeyCode::Tab)],
+    );
+    assert_eq!(cmdline_content(&contents), "a\t");
+    press(
+      &data_access,
+      vec![ctrl(KeyCode::Char('v')), ctrl(KeyCode::Char('a'))],
+    );
+    assert_eq!(cmdline_content(&contents), "a\t\u{1}");
+    press(
+      &data_access,
+      vec![ctrl(KeyCode::Char('v')), key(KeyCode::Char('b'))],
+    );
+    assert_eq!(cmdline_content(&contents), "a\t\u{1}b");
+  }
+
+  #[test]
+  fn paste1() {
+    test_log_init();
+    let (tree, contents, data_access) = make_cmdline(vec![], "e ");
+
+    press(&data_access, vec![Event::Paste("foo\nbar\n".to_string())]);
+    assert_eq!(cmdline_content(&contents), "e foo bar");
+    assert_eq!(cursor_char_idx(&tree), 9);
+  }
+}
+
+#[cfg(test)]
+mod tests_option_set {
+  use super::*;
+
+  use crate::excommand::BuiltinExCommand;
+  use crate::opt::OptionTarget;
+  use crate::state::autocmd::{AutoCmd, AutoCmdEvent, AutoCmdOptions};
+
+  #[test]
+  fn set1() {
+    test_log_init();
+    let terminal_size = U16Size::new(30, 5);
+    let window_options = WindowLocalOptionsBuilder::default().build().unwrap();
+    let (tree, state, bufs, _buf, contents) =
+      make_tree_with_cmdline(terminal_size, window_options, vec![]);
+    lock!(state).autocmds_mut().add(AutoCmd::new(
+      1,
+      vec![AutoCmdEvent::OptionSet],
+      AutoCmdOptions::default(),
+    ));
+    let data_access = StatefulDataAccess::new(
+      state.clone(),
+      tree,
+      bufs,
+      contents,
+      Event::FocusGained,
+    );
+
+    let stateful = CommandLineExStateful::default();
+    for args in ["nowrap ts=4", "nowrap ts=4 so?", "so=2"] {
+      stateful.run_builtin_ex_command(
+        &data_access,
+        BuiltinExCommand::Set(OptionTarget::LocalAndGlobal, args.into()),
+      );
+    }
+    // The local value is the same, but the global value is changed.
+    for (target, args) in [
+      (OptionTarget::Local, "so=7"),
+      (OptionTarget::LocalAndGlobal, "so=7"),
+      (OptionTarget::LocalAndGlobal, "so=7"),
+    ] {
+      stateful.run_builtin_ex_command(
+        &data_access,
+        BuiltinExCommand::Set(target, args.into()),
+      );
+    }
+
+    // The unchanged options fire nothing.
+    let fired: Vec<_> = lock!(state)
+      .autocmds_mut()
+      .take_fired()
+      .into_iter()
+      .map(|fired| (fired.event, fired.data.to_string()))
+      .collect();
+    assert_eq!(
+      fired,
+      vec![
+        (AutoCmdEvent::OptionSet, "tabstop".to_string()),
+        (AutoCmdEvent::OptionSet, "wrap".to_string()),
+        (AutoCmdEvent::OptionSet, "scrolloff".to_string()),
+        (AutoCmdEvent::OptionSet, "scrolloff".to_string()),
+        (AutoCmdEvent::OptionSet, "scrolloff".to_string()),
+      ]
+    );
+  }
+}
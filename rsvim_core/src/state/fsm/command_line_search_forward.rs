@@ -1,25 +1,105 @@
 //! The command-line search forward mode.
 
+use crate::content::history::HistoryKind;
+use crate::state::fsm::command_line_edit::{
+  self, CommandLineEditKey, CommandLinePendingKey,
+};
 use crate::state::fsm::{Stateful, StatefulDataAccess, StatefulValue};
 use crate::state::ops::Operation;
 
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+
 #[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
 /// The command-line search forward mode.
-pub struct CommandLineSearchForwardStateful {}
+pub struct CommandLineSearchForwardStateful {
+  /// The key waits for the next key, i.e. `<C-R>` and `<C-V>`.
+  pending: Option<CommandLinePendingKey>,
+}
+
+impl CommandLineSearchForwardStateful {
+  fn get_operation(&self, event: &Event) -> Option<Operation> {
+    match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+        match key_event.code {
+          KeyCode::Esc => Some(Operation::GotoNormalMode),
+          KeyCode::Enter => Some(Operation::ConfirmSearchAndGotoNormalMode),
+          _ => None,
+        }
+      }
+      _ => None,
+    }
+  }
+}
 
 impl Stateful for CommandLineSearchForwardStateful {
-  fn handle(&self, _data_access: StatefulDataAccess) -> StatefulValue {
-    StatefulValue::CommandLineSearchForwardMode(
-      CommandLineSearchForwardStateful::default(),
-    )
+  fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    let event = data_access.event.clone();
+
+    if let Some(pending) = self.pending {
+      if let Some(op) =
+        command_line_edit::get_pending_operation(pending, &event)
+      {
+        return self.handle_op(data_access, op);
+      }
+      return StatefulValue::CommandLineSearchForwardMode(
+        CommandLineSearchForwardStateful::default(),
+      );
+    }
+
+    if let Some(op) = self.get_operation(&event) {
+      return self.handle_op(data_access, op);
+    }
+
+    match command_line_edit::get_edit_key(&event) {
+      Some(CommandLineEditKey::Operation(op)) => {
+        self.handle_op(data_access, op)
+      }
+      Some(CommandLineEditKey::Pending(pending)) => {
+        StatefulValue::CommandLineSearchForwardMode(
+          CommandLineSearchForwardStateful {
+            pending: Some(pending),
+          },
+        )
+      }
+      None => StatefulValue::CommandLineSearchForwardMode(
+        CommandLineSearchForwardStateful::default(),
+      ),
+    }
   }
+
   fn handle_op(
     &self,
-    _data_access: StatefulDataAccess,
-    _op: Operation,
+    data_access: StatefulDataAccess,
+    op: Operation,
   ) -> StatefulValue {
-    StatefulValue::CommandLineSearchForwardMode(
-      CommandLineSearchForwardStateful::default(),
-    )
+    match op {
+      Operation::GotoNormalMode => {
+        command_line_edit::leave_command_line(&data_access);
+        StatefulValue::NormalMode(super::NormalStateful::default())
+      }
+      Operation::ConfirmSearchAndGotoNormalMode => {
+        let pattern = command_line_edit::leave_command_line(&data_access);
+        command_line_edit::history_add(
+          &data_access,
+          HistoryKind::SearchForward,
+          &pattern,
+        );
+        StatefulValue::NormalMode(super::NormalStateful::default())
+      }
+      _ => {
+        command_line_edit::handle_edit_op(
+          &data_access,
+          HistoryKind::SearchForward,
+          op,
+        );
+        StatefulValue::CommandLineSearchForwardMode(
+          CommandLineSearchForwardStateful::default(),
+        )
+      }
+    }
+  }
+
+  fn is_literal_pending(&self) -> bool {
+    self.pending.is_some()
   }
 }
@@ -0,0 +1,144 @@
+use super::command_line_edit;
+use super::command_line_search_backward::*;
+use super::command_line_search_forward::*;
+
+use crate::content::TextContentsArc;
+use crate::content::history::HistoryKind;
+use crate::prelude::*;
+use crate::state::fsm::{StatefulDataAccess, StatefulValue};
+use crate::test::fsm::{chars, ctrl, feed, key, make_data_access, press};
+use crate::test::log::init as test_log_init;
+use crate::ui::widget::command_line::CommandLineIndicatorSymbol;
+
+use crossterm::event::KeyCode;
+
+fn cmdline_content(contents: &TextContentsArc) -> String {
+  lock!(contents).command_line_content().rope().to_string()
+}
+
+fn history(contents: &TextContentsArc, kind: HistoryKind) -> Vec<String> {
+  lock!(contents)
+    .command_line_history()
+    .entries(kind)
+    .iter()
+    .map(|e| e.text().to_string())
+    .collect()
+}
+
+fn search_forward(data_access: &StatefulDataAccess) -> StatefulValue {
+  command_line_edit::enter_command_line(
+    data_access,
+    CommandLineIndicatorSymbol::SearchForward,
+  );
+  StatefulValue::CommandLineSearchForwardMode(
+    CommandLineSearchForwardStateful::default(),
+  )
+}
+
+fn search_backward(data_access: &StatefulDataAccess) -> StatefulValue {
+  command_line_edit::enter_command_line(
+    data_access,
+    CommandLineIndicatorSymbol::SearchBackard,
+  );
+  StatefulValue::CommandLineSearchBackwardMode(
+    CommandLineSearchBackwardStateful::default(),
+  )
+}
+
+#[test]
+fn confirm_history1() {
+  test_log_init();
+
+  let (_buf, contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello\n"]);
+
+  let mut events = chars("foo");
+  events.push(key(KeyCode::Enter));
+  let stateful = feed(&data_access, search_forward(&data_access), events);
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(cmdline_content(&contents), "");
+
+  let mut events = chars("bar");
+  events.push(key(KeyCode::Enter));
+  feed(&data_access, search_backward(&data_access), events);
+
+  // Cancelled search is not saved.
+  let mut events = chars("baz");
+  events.push(key(KeyCode::Esc));
+  let stateful = feed(&data_access, search_forward(&data_access), events);
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+
+  assert_eq!(history(&contents, HistoryKind::SearchForward), vec!["foo"]);
+  assert_eq!(history(&contents, HistoryKind::SearchBackward), vec!["bar"]);
+  assert!(history(&contents, HistoryKind::Cmd).is_empty());
+}
+
+#[test]
+fn goto1() {
+  test_log_init();
+
+  let (_buf, contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello\n"]);
+
+  let stateful = press(&data_access, chars("/"));
+  assert!(matches!(
+    stateful,
+    StatefulValue::CommandLineSearchForwardMode(_)
+  ));
+  let mut events = chars("foo");
+  events.push(key(KeyCode::Enter));
+  let stateful = feed(&data_access, stateful, events);
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+
+  let stateful = press(&data_access, chars("?"));
+  assert!(matches!(
+    stateful,
+    StatefulValue::CommandLineSearchBackwardMode(_)
+  ));
+  let stateful = feed(&data_access, stateful, vec![key(KeyCode::Esc)]);
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+
+  assert_eq!(history(&contents, HistoryKind::SearchForward), vec!["foo"]);
+  assert!(history(&contents, HistoryKind::SearchBackward).is_empty());
+}
+
+#[test]
+fn recall1() {
+  test_log_init();
+
+  let (_buf, contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello\n"]);
+  {
+    let mut contents = lock!(contents);
+    let history = contents.command_line_history_mut();
+    history.add(HistoryKind::SearchForward, "foo");
+    history.add(HistoryKind::SearchForward, "bar");
+    history.add(HistoryKind::SearchForward, "foobar");
+    history.add(HistoryKind::SearchBackward, "fox");
+  }
+
+  let stateful = search_forward(&data_access);
+  let stateful = feed(&data_access, stateful, chars("f"));
+  let stateful = feed(&data_access, stateful, vec![key(KeyCode::Up)]);
+  assert_eq!(cmdline_content(&contents), "foobar");
+  let stateful = feed(&data_access, stateful, vec![key(KeyCode::Up)]);
+  assert_eq!(cmdline_content(&contents), "foo");
+  let stateful = feed(&data_access, stateful, vec![key(KeyCode::Down)]);
+  assert_eq!(cmdline_content(&contents), "foobar");
+
+  // The edited content is the new prefix.
+  let stateful = feed(&data_access, stateful, vec![ctrl('u')]);
+  let stateful = feed(&data_access, stateful, chars("b"));
+  let stateful = feed(&data_access, stateful, vec![key(KeyCode::Up)]);
+  assert_eq!(cmdline_content(&contents), "bar");
+  let stateful = feed(&data_access, stateful, vec![key(KeyCode::Esc)]);
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+
+  // Search backward browses its own history.
+  let stateful = search_backward(&data_access);
+  let stateful = feed(&data_access, stateful, vec![key(KeyCode::Up)]);
+  assert_eq!(cmdline_content(&contents), "fox");
+  let stateful = feed(&data_access, stateful, vec![key(KeyCode::Up)]);
+  assert_eq!(cmdline_content(&contents), "fox");
+  feed(&data_access, stateful, vec![key(KeyCode::Esc)]);
+}
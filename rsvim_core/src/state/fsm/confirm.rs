@@ -0,0 +1,205 @@
+//! The confirmation prompt of the quit commands.
+//!
+//! See: <https://vimhelp.org/editing.txt.html#%3Aconfirm>.
+
+use crate::buf::BufferId;
+use crate::content::message::MessageLevel;
+use crate::excommand::quit::{self, QuitKind};
+use crate::prelude::*;
+use crate::results::ExCommandResult;
+use crate::state::fsm::{
+  NormalStateful, QuitStateful, Stateful, StatefulDataAccess, StatefulValue,
+};
+use crate::state::ops::{ConfirmChoice, Operation};
+
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use tracing::trace;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// The confirmation state.
+///
+/// NOTE: This is an internal state, it asks to save the modified buffer before the quit command
+/// (i.e. `:confirm quit`), and takes over the keys until it is answered. The modified buffers are
+/// asked one by one, in the order of [`quit::modified_buffers`].
+pub struct ConfirmStateful {
+  kind: QuitKind,
+  buffer_id: BufferId,
+}
+
+impl ConfirmStateful {
+  pub fn new(kind: QuitKind, buffer_id: BufferId) -> Self {
+    Self { kind, buffer_id }
+  }
+
+  /// The quit command being confirmed.
+  pub fn kind(&self) -> QuitKind {
+    self.kind
+  }
+
+  /// The modified buffer being asked.
+  pub fn buffer_id(&self) -> BufferId {
+    self.buffer_id
+  }
+
+  /// Ask to save the modified buffer before the quit command, the prompt is shown in the message
+  /// area.
+  pub fn start(
+    data_access: &StatefulDataAccess,
+    kind: QuitKind,
+    buffer_id: BufferId,
+  ) -> StatefulValue {
+    let (name, all) = {
+      let tree = lock!(data_access.tree);
+      let buffers = lock!(data_access.buffers);
+      let name = buffers
+        .get(&buffer_id)
+        .map(|buffer| lock!(buffer).name())
+        .unwrap_or_default();
+      (name, !quit::closes_window(&tree, kind))
+    };
+    let choices = if all {
+      "[Y]es, (N)o, Save (A)ll, (D)iscard All, (C)ancel: "
+    } else {
+      "[Y]es, (N)o, (C)ancel: "
+    };
+
+    let mut contents = lock!(data_access.contents);
+    let messages = contents.messages_mut();
+    messages.clear_shown();
+    messages.echo(
+      MessageLevel::Info,
+      &format!("Save changes to \"{name}\"? {choices}"),
+    );
+    StatefulValue::ConfirmState(ConfirmStateful::new(kind, buffer_id))
+  }
+
+  fn get_operation(&self, event: &Event, all: bool) -> Option<Operation> {
+    let key_event = match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+        key_event
+      }
+      _ => return None,
+    };
+    trace!("Event::key:{:?}", key_event);
+
+    let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+    let choice = match key_event.code {
+      KeyCode::Char('c') if ctrl => ConfirmChoice::Cancel,
+      KeyCode::Char(_) if ctrl => return None,
+      KeyCode::Enter | KeyCode::Char('y') | KeyCode::Char('Y') => {
+        ConfirmChoice::Yes
+      }
+      KeyCode::Char('n') | KeyCode::Char('N') => ConfirmChoice::No,
+      KeyCode::Char('a') | KeyCode::Char('A') if all => ConfirmChoice::SaveAll,
+      KeyCode::Char('d') | KeyCode::Char('D') if all => {
+        ConfirmChoice::DiscardAll
+      }
+      KeyCode::Esc | KeyCode::Char('c') | KeyCode::Char('C') => {
+        ConfirmChoice::Cancel
+      }
+      _ => return None,
+    };
+    Some(Operation::ConfirmChoose(choice))
+  }
+
+  // Save the buffers of the choice, returns the next modified buffer to ask.
+  fn save(
+    &self,
+    data_access: &StatefulDataAccess,
+    choice: ConfirmChoice,
+  ) -> ExCommandResult<Option<BufferId>> {
+    let tree = lock!(data_access.tree);
+    let mut buffers = lock!(data_access.buffers);
+    let modified = quit::modified_buffers(&tree, &buffers, self.kind);
+    let rest = match modified.iter().position(|id| *id == self.buffer_id) {
+      Some(i) => modified[i + 1..].to_vec(),
+      None => modified,
+    };
+
+    let saved = match choice {
+      ConfirmChoice::Yes => vec![self.buffer_id],
+      ConfirmChoice::SaveAll => {
+        let mut saved = vec![self.buffer_id];
+        saved.extend(rest.iter().copied());
+        saved
+      }
+      _ => vec![],
+    };
+    for buffer_id in saved {
+      let modified = buffers
+        .get(&buffer_id)
+        .map(|buffer| lock!(buffer).is_modified())
+        .unwrap_or(false);
+      if modified {
+        quit::write_buffer(&mut buffers, buffer_id)?;
+      }
+    }
+
+    Ok(match choice {
+      ConfirmChoice::SaveAll => None,
+      _ => rest.first().copied(),
+    })
+  }
+}
+
+impl Stateful for ConfirmStateful {
+  fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    let event = data_access.event.clone();
+    let all = {
+      let tree = lock!(data_access.tree);
+      !quit::closes_window(&tree, self.kind)
+    };
+
+    if let Some(op) = self.get_operation(&event, all) {
+      return self.handle_op(data_access, op);
+    }
+
+    StatefulValue::ConfirmState(*self)
+  }
+
+  fn handle_op(
+    &self,
+    data_access: StatefulDataAccess,
+    op: Operation,
+  ) -> StatefulValue {
+    let choice = match op {
+      Operation::ConfirmChoose(choice) => choice,
+      _ => unreachable!(),
+    };
+    lock!(data_access.contents).messages_mut().clear_shown();
+
+    match choice {
+      ConfirmChoice::Cancel => {
+        StatefulValue::NormalMode(NormalStateful::default())
+      }
+      // The buffers are already written by the command, i.e. current buffer of `:wq`.
+      ConfirmChoice::DiscardAll => {
+        QuitStateful::quit(&data_access, self.kind.without_write(), true, false)
+      }
+      ConfirmChoice::Yes | ConfirmChoice::No | ConfirmChoice::SaveAll => {
+        match self.save(&data_access, choice) {
+          Ok(Some(next_buffer_id)) => {
+            Self::start(&data_access, self.kind, next_buffer_id)
+          }
+          // The rest modified buffers are not saved.
+          Ok(None) => QuitStateful::quit(
+            &data_access,
+            self.kind.without_write(),
+            true,
+            false,
+          ),
+          Err(e) => {
+            lock!(data_access.contents)
+              .messages_mut()
+              .add(MessageLevel::Error, &e.to_string());
+            StatefulValue::NormalMode(NormalStateful::default())
+          }
+        }
+      }
+    }
+  }
+
+  fn is_literal_pending(&self) -> bool {
+    true
+  }
+}
@@ -0,0 +1,210 @@
+use super::confirm::*;
+
+use crate::buf::{Buffer, BufferArc};
+use crate::excommand::quit::QuitKind;
+use crate::prelude::*;
+use crate::state::fsm::{StatefulDataAccess, StatefulValue};
+use crate::test::fsm::{chars, feed, key, make_data_access, text};
+use crate::test::log::init as test_log_init;
+
+use crossterm::event::KeyCode;
+use ropey::Rope;
+use std::path::Path;
+
+// Type the ex command in normal mode.
+fn ex_command(data_access: &StatefulDataAccess, cmd: &str) -> StatefulValue {
+  let mut events = chars(&format!(":{cmd}"));
+  events.push(key(KeyCode::Enter));
+  feed(data_access, StatefulValue::default(), events)
+}
+
+fn set_filename(buf: &BufferArc, path: &Path) {
+  let mut buf = lock!(buf);
+  buf.set_filename(Some(path.to_path_buf()));
+  buf.set_absolute_filename(Some(path.to_path_buf()));
+}
+
+// Add a modified buffer with the file name, it is not shown in any window.
+fn add_modified_buffer(
+  data_access: &StatefulDataAccess,
+  path: &Path,
+) -> BufferArc {
+  let buf_opts = *lock!(data_access.buffers).global_local_options();
+  let mut buf = Buffer::_new(
+    buf_opts,
+    U16Size::new(10, 5),
+    Rope::from_str("world\n"),
+    Some(path.to_path_buf()),
+    Some(path.to_path_buf()),
+    None,
+    None,
+  );
+  buf.text_mut().insert_at(0, 0, "x".into());
+  let buf = Buffer::to_arc(buf);
+  lock!(data_access.buffers)._add_buffer(buf.clone());
+  buf
+}
+
+fn prompt(data_access: &StatefulDataAccess) -> String {
+  let contents = lock!(data_access.contents);
+  let shown_lines = contents.messages().shown_lines();
+  shown_lines
+    .last()
+    .map(|(_, line)| line.to_string())
+    .unwrap_or_default()
+}
+
+#[test]
+fn refuse1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(30, 5), vec!["hello\n"]);
+  feed(&data_access, StatefulValue::default(), chars("dl"));
+  assert!(lock!(buf).is_modified());
+
+  let stateful = ex_command(&data_access, "q");
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert!(prompt(&data_access).starts_with("E37:"));
+
+  let stateful = ex_command(&data_access, "q!");
+  assert!(matches!(stateful, StatefulValue::QuitState(_)));
+}
+
+#[test]
+fn confirm1() {
+  test_log_init();
+  let dir = assert_fs::TempDir::new().unwrap();
+  let path = dir.path().join("a.txt");
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(80, 5), vec!["hello\n"]);
+  set_filename(&buf, &path);
+  feed(&data_access, StatefulValue::default(), chars("dl"));
+
+  let stateful = ex_command(&data_access, "conf q");
+  let buf_id = lock!(buf).id();
+  assert_eq!(
+    stateful,
+    StatefulValue::ConfirmState(ConfirmStateful::new(QuitKind::Quit, buf_id))
+  );
+  assert!(prompt(&data_access).starts_with("Save changes to"));
+
+  // Other keys are ignored.
+  let stateful = feed(&data_access, stateful, chars("z"));
+  assert!(matches!(stateful, StatefulValue::ConfirmState(_)));
+
+  // Cancel.
+  let stateful = feed(&data_access, stateful, vec![key(KeyCode::Esc)]);
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert!(lock!(buf).is_modified());
+  assert!(!path.exists());
+
+  // Save.
+  let stateful = ex_command(&data_access, "confirm q");
+  let stateful = feed(&data_access, stateful, chars("y"));
+  assert!(matches!(stateful, StatefulValue::QuitState(_)));
+  assert!(!lock!(buf).is_modified());
+  assert_eq!(std::fs::read_to_string(&path).unwrap(), text(&buf));
+}
+
+#[test]
+fn confirm_all1() {
+  test_log_init();
+  let dir = assert_fs::TempDir::new().unwrap();
+  let path1 = dir.path().join("a.txt");
+  let path2 = dir.path().join("b.txt");
+  let (buf1, _contents, data_access) =
+    make_data_access(U16Size::new(80, 5), vec!["hello\n"]);
+  set_filename(&buf1, &path1);
+  feed(&data_access, StatefulValue::default(), chars("dl"));
+  let buf2 = add_modified_buffer(&data_access, &path2);
+  let buf2_id = lock!(buf2).id();
+
+  // Don't save current buffer, then save the other buffer.
+  let stateful = ex_command(&data_access, "confirm qa");
+  assert!(prompt(&data_access).contains("Save (A)ll"));
+  let stateful = feed(&data_access, stateful, chars("n"));
+  assert_eq!(
+    stateful,
+    StatefulValue::ConfirmState(ConfirmStateful::new(
+      QuitKind::QuitAll,
+      buf2_id
+    ))
+  );
+  assert!(prompt(&data_access).contains("b.txt"));
+  let stateful = feed(&data_access, stateful, chars("y"));
+  assert!(matches!(stateful, StatefulValue::QuitState(_)));
+  assert!(lock!(buf1).is_modified());
+  assert!(!path1.exists());
+  assert_eq!(std::fs::read_to_string(&path2).unwrap(), "xworld\n");
+
+  // Save all.
+  let stateful = ex_command(&data_access, "confirm qa");
+  let stateful = feed(&data_access, stateful, chars("a"));
+  assert!(matches!(stateful, StatefulValue::QuitState(_)));
+  assert!(!lock!(buf1).is_modified());
+  assert!(path1.exists());
+}
+
+#[test]
+fn confirm_write_quit1() {
+  test_log_init();
+  let dir = assert_fs::TempDir::new().unwrap();
+  let path1 = dir.path().join("a.txt");
+  let path2 = dir.path().join("b.txt");
+  let (buf1, _contents, data_access) =
+    make_data_access(U16Size::new(80, 5), vec!["hello\n"]);
+  set_filename(&buf1, &path1);
+  let buf2 = add_modified_buffer(&data_access, &path2);
+  let buf2_id = lock!(buf2).id();
+
+  // The `:wq` writes current buffer even if it is not modified, but the other modified buffer
+  // stops it.
+  let stateful = ex_command(&data_access, "wq");
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert!(prompt(&data_access).starts_with("E162:"));
+  assert_eq!(std::fs::read_to_string(&path1).unwrap(), "hello\n");
+
+  // Don't save the other buffer.
+  let stateful = ex_command(&data_access, "confirm wq");
+  assert_eq!(
+    stateful,
+    StatefulValue::ConfirmState(ConfirmStateful::new(
+      QuitKind::WriteQuit,
+      buf2_id
+    ))
+  );
+  let stateful = feed(&data_access, stateful, chars("n"));
+  assert!(matches!(stateful, StatefulValue::QuitState(_)));
+  assert!(lock!(buf2).is_modified());
+
+  // Save the other buffer.
+  let stateful = ex_command(&data_access, "confirm x");
+  assert_eq!(
+    stateful,
+    StatefulValue::ConfirmState(ConfirmStateful::new(QuitKind::Exit, buf2_id))
+  );
+  let stateful = feed(&data_access, stateful, chars("y"));
+  assert!(matches!(stateful, StatefulValue::QuitState(_)));
+  assert_eq!(std::fs::read_to_string(&path2).unwrap(), "xworld\n");
+}
+
+#[test]
+fn write1() {
+  test_log_init();
+  let dir = assert_fs::TempDir::new().unwrap();
+  let path = dir.path().join("a.txt");
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(80, 5), vec!["hello\n"]);
+
+  // The unnamed buffer cannot be written.
+  let stateful = ex_command(&data_access, "w");
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert!(prompt(&data_access).starts_with("E32:"));
+
+  set_filename(&buf, &path);
+  feed(&data_access, StatefulValue::default(), chars("dl"));
+  ex_command(&data_access, "write");
+  assert!(prompt(&data_access).ends_with("written"));
+  assert!(!lock!(buf).is_modified());
+  assert_eq!(std::fs::read_to_string(&path).unwrap(), "ello\n");
+}
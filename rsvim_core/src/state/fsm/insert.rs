@@ -1,9 +1,11 @@
 //! The insert mode.
 
 use crate::prelude::*;
+use crate::state::fsm::select;
 use crate::state::fsm::{Stateful, StatefulDataAccess, StatefulValue};
 use crate::state::ops::Operation;
 use crate::state::ops::cursor_ops;
+use crate::state::repeat;
 use crate::ui::canvas::CursorStyle;
 use crate::ui::tree::*;
 
@@ -38,6 +40,7 @@ impl InsertStateful {
             KeyCode::Char(c) => {
               Some(Operation::CursorInsert(c.to_compact_string()))
             }
+            KeyCode::Tab => Some(Operation::CursorInsert("\t".into())),
             KeyCode::Enter => {
               let eol = {
                 let tree = data_access.tree.clone();
@@ -62,7 +65,15 @@ impl InsertStateful {
         KeyEventKind::Release => None,
       },
       Event::Mouse(_mouse_event) => None,
-      Event::Paste(_paste_string) => None,
+      // The pasted text is inserted at once, it bypasses the mappings.
+      Event::Paste(paste_string) => {
+        let payload = pasted_text(data_access, paste_string);
+        if payload.is_empty() {
+          None
+        } else {
+          Some(Operation::CursorInsert(payload))
+        }
+      }
       Event::Resize(_columns, _rows) => None,
     }
   }
@@ -70,6 +81,35 @@ impl InsertStateful {
 
 impl Stateful for InsertStateful {
   fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    // The shifted special keys stop inserting and start the selection, see the 'keymodel' option.
+    if let Event::Key(key_event) = data_access.event {
+      if select::is_start_selection_key(&data_access, &key_event) {
+        self.goto_normal_mode(&data_access);
+        if let Some(stateful) =
+          select::start_selection_by_key(&data_access, &key_event)
+        {
+          return stateful;
+        }
+      }
+    }
+
+    // The typed keys (and the pasted text) are appended to the change repeated by `.`.
+    match &data_access.event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+        lock!(data_access.state)
+          .dot_repeat_mut()
+          .push_key(*key_event);
+      }
+      Event::Paste(paste_string) => {
+        let payload = pasted_text(&data_access, paste_string);
+        let mut state = lock!(data_access.state);
+        for key_event in repeat::char_keys(&payload) {
+          state.dot_repeat_mut().push_key(key_event);
+        }
+      }
+      _ => {}
+    }
+
     if let Some(op) = self.get_operation(&data_access) {
       return self.handle_op(data_access, op);
     }
@@ -97,6 +137,22 @@ impl Stateful for InsertStateful {
   }
 }
 
+/// The pasted text with the line breaks of current buffer, i.e. the 'fileformat' option.
+///
+/// NOTE: There's no undo yet, the pasted text cannot be undone.
+pub fn pasted_text(
+  data_access: &StatefulDataAccess,
+  paste_string: &str,
+) -> CompactString {
+  let tree = data_access.tree.clone();
+  let tree = lock!(tree);
+  debug_assert!(tree.current_window_id().is_some());
+  let current_window = tree.current_window().unwrap();
+  let buffer = current_window.buffer().upgrade().unwrap();
+  let eol = lock!(buffer).options().end_of_line();
+  eol.normalize(paste_string).to_compact_string()
+}
+
 impl InsertStateful {
   pub fn cursor_delete(
     &self,
@@ -174,6 +230,8 @@ impl InsertStateful {
     debug_assert_eq!(_cursor_id, cursor.id());
     cursor.set_style(&CursorStyle::SteadyBlock);
 
+    lock!(data_access.state).dot_repeat_mut().finish();
+
     StatefulValue::NormalMode(super::NormalStateful::default())
   }
 }
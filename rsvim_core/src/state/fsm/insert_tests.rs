@@ -8501,3 +8501,33 @@ mod tests_delete_text {
     }
   }
 }
+
+#[cfg(test)]
+mod tests_paste {
+  use super::*;
+
+  use crate::test::fsm::{chars, cursor, key, make_data_access, press, text};
+
+  #[test]
+  fn paste1() {
+    test_log_init();
+    let (buf, _contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["hello\n"]);
+
+    // The pasted text is inserted at once, with the line breaks of the buffer.
+    let mut events = chars("i");
+    events.push(Event::Paste("foo\r\nbar".to_string()));
+    let stateful = press(&data_access, events.clone());
+    assert!(matches!(stateful, StatefulValue::InsertMode(_)));
+    assert_eq!(text(&buf), "foo\nbarhello\n");
+    assert_eq!(cursor(&data_access), (1, 3));
+
+    // The pasted text is repeated with the change.
+    let (buf, _contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["hello\n"]);
+    events.push(key(KeyCode::Esc));
+    press(&data_access, events);
+    press(&data_access, chars("gg."));
+    assert_eq!(text(&buf), "foo\nbarfoo\nbarhello\n");
+  }
+}
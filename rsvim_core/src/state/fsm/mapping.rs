@@ -0,0 +1,273 @@
+//! The key mappings layer in front of the state machine.
+//!
+//! The typed keys are matched against the mappings of current mode before they're handled by the
+//! state machine. The keys that are a prefix of some mappings are kept in
+//! [`State::keymap_pending`](crate::state::State::keymap_pending) until the next key arrives, or
+//! the `timeoutlen` expires.
+//!
+//! The js callback of a mapping runs before the next key: the keys after the mapping are kept
+//! pending, and the event loop runs the callback then [`resume`]s them, so the keys returned by an
+//! `<expr>` mapping are handled before the keys typed after it.
+//!
+//! See: <https://vimhelp.org/map.txt.html#map-typing>.
+
+use crate::content::message::MessageLevel;
+use crate::prelude::*;
+use crate::state::fsm::{
+  Stateful, StatefulDataAccess, StatefulValue, feed_keys,
+};
+use crate::state::keymap::{
+  Keymap, KeymapMatch, KeymapTarget, MAX_MAP_DEPTH, normalize_key,
+};
+
+use crossterm::event::{Event, KeyEvent};
+use std::collections::VecDeque;
+
+/// Feed the typed key to the state machine starting from `stateful`, through the key mappings.
+///
+/// Returns the state after the key, it is not changed if the key waits for the next key.
+pub fn feed_key(
+  data_access: &StatefulDataAccess,
+  stateful: StatefulValue,
+  key_event: KeyEvent,
+) -> StatefulValue {
+  let keys = {
+    let mut state = lock!(data_access.state);
+    let mut keys: VecDeque<KeyEvent> =
+      state.keymap_pending_mut().drain(..).collect();
+    keys.push_back(normalize_key(&key_event));
+    keys
+  };
+  let (stateful, pending) = _resolve(data_access, stateful, keys, false);
+  *lock!(data_access.state).keymap_pending_mut() = pending;
+  stateful
+}
+
+/// Resolve the pending keys without waiting for the next key, i.e. the `timeoutlen` expires.
+///
+/// Returns the state after the keys.
+pub fn flush(
+  data_access: &StatefulDataAccess,
+  stateful: StatefulValue,
+) -> StatefulValue {
+  let keys = lock!(data_access.state)
+    .keymap_pending_mut()
+    .drain(..)
+    .collect();
+  let (stateful, pending) = _resolve(data_access, stateful, keys, true);
+  *lock!(data_access.state).keymap_pending_mut() = pending;
+  stateful
+}
+
+/// Feed the keys to the state machine starting from `stateful` through the key mappings, i.e. the
+/// keys of a macro or `:normal`. The keys never wait for more keys.
+///
+/// Returns the state after the last key.
+pub fn feed_mapped_keys(
+  data_access: &StatefulDataAccess,
+  stateful: StatefulValue,
+  keys: &[KeyEvent],
+) -> StatefulValue {
+  let keys = keys.iter().map(normalize_key).collect();
+  let (stateful, pending) = _resolve(data_access, stateful, keys, true);
+  // The keys after a js callback wait for it, before the typed keys.
+  lock!(data_access.state)
+    .keymap_pending_mut()
+    .splice(0..0, pending);
+  stateful
+}
+
+/// Resolve the pending keys after the js callback of the `keymap` runs, the `expr_keys` returned
+/// by an `<expr>` mapping go first.
+///
+/// Returns the state after the keys.
+pub fn resume(
+  data_access: &StatefulDataAccess,
+  stateful: StatefulValue,
+  keymap: &Keymap,
+  expr_keys: Vec<KeyEvent>,
+) -> StatefulValue {
+  let mut keys: VecDeque<KeyEvent> = lock!(data_access.state)
+    .keymap_pending_mut()
+    .drain(..)
+    .collect();
+  let expanded = Keymap::new(
+    keymap.lhs().clone(),
+    KeymapTarget::Keys(expr_keys),
+    keymap.options().clone(),
+  );
+  let stateful = _execute(data_access, stateful, &expanded, &mut keys);
+  let (stateful, pending) = _resolve(data_access, stateful, keys, false);
+  *lock!(data_access.state).keymap_pending_mut() = pending;
+  stateful
+}
+
+// The keys are taken as they are in the states, i.e. the char after `f` or the register name after
+// `"`, and the internal states.
+fn _bypass(stateful: &StatefulValue) -> bool {
+  stateful.is_literal_pending()
+    || matches!(
+      stateful,
+      StatefulValue::QuitState(_) | StatefulValue::MessagePagerState(_)
+    )
+}
+
+// Whether the rest keys wait for the js callback of a mapping.
+fn _waiting(data_access: &StatefulDataAccess) -> bool {
+  lock!(data_access.state).keymap_callback().is_some()
+}
+
+// Whether the rest keys should be dropped.
+fn _stopped(
+  data_access: &StatefulDataAccess,
+  stateful: &StatefulValue,
+) -> bool {
+  matches!(stateful, StatefulValue::QuitState(_))
+    || lock!(data_access.state).macros().is_failed()
+}
+
+fn _lookup(data_access: &StatefulDataAccess, keys: &[KeyEvent]) -> KeymapMatch {
+  let buffer_id = {
+    let tree = data_access.tree.clone();
+    let tree = lock!(tree);
+    tree
+      .current_window()
+      .and_then(|window| window.buffer().upgrade())
+      .map(|buffer| lock!(buffer).id())
+  };
+  let state = lock!(data_access.state);
+  state.keymaps().lookup(state.mode(), buffer_id, keys)
+}
+
+fn _handle_key(
+  data_access: &StatefulDataAccess,
+  stateful: StatefulValue,
+  key_event: KeyEvent,
+) -> StatefulValue {
+  let stateful = stateful.handle(data_access.with_event(Event::Key(key_event)));
+  lock!(data_access.state).update_state_machine(&stateful);
+  stateful
+}
+
+// Match the `keys` against the mappings and handle them. The keys that are a prefix of some
+// mappings are returned if it can wait for the next key, i.e. `flush` is `false`, and the keys
+// after a js callback are returned to wait for the callback.
+//
+// The right-hand side of a recursive mapping is put back in front of the rest keys, so it is
+// remapped, the `depth` counts the mappings in a row without handling any key.
+fn _resolve(
+  data_access: &StatefulDataAccess,
+  stateful: StatefulValue,
+  keys: VecDeque<KeyEvent>,
+  flush: bool,
+) -> (StatefulValue, Vec<KeyEvent>) {
+  let mut stateful = stateful;
+  let mut keys = keys;
+  let mut depth = 0_usize;
+  // The shown messages before the first `<silent>` mapping.
+  let mut silent_since: Option<usize> = None;
+
+  while !keys.is_empty() {
+    if _stopped(data_access, &stateful) {
+      keys.clear();
+      break;
+    }
+    if _waiting(data_access) {
+      break;
+    }
+    if _bypass(&stateful) {
+      let key_event = keys.pop_front().unwrap();
+      stateful = _handle_key(data_access, stateful, key_event);
+      depth = 0;
+      continue;
+    }
+
+    keys.make_contiguous();
+    let matched = match _lookup(data_access, keys.as_slices().0) {
+      KeymapMatch::Prefix | KeymapMatch::ExactPrefix(_) if !flush => {
+        break;
+      }
+      KeymapMatch::Exact(keymap) | KeymapMatch::ExactPrefix(keymap) => {
+        Some((keys.len(), keymap))
+      }
+      // The longest keys that complete a mapping, otherwise the first key is not mapped.
+      KeymapMatch::None | KeymapMatch::Prefix => (1..keys.len())
+        .rev()
+        .find_map(|n| match _lookup(data_access, &keys.as_slices().0[..n]) {
+          KeymapMatch::Exact(keymap) | KeymapMatch::ExactPrefix(keymap) => {
+            Some((n, keymap))
+          }
+          _ => None,
+        }),
+    };
+
+    let Some((n, keymap)) = matched else {
+      let key_event = keys.pop_front().unwrap();
+      stateful = _handle_key(data_access, stateful, key_event);
+      depth = 0;
+      continue;
+    };
+
+    depth += 1;
+    if depth > MAX_MAP_DEPTH {
+      let contents = data_access.contents.clone();
+      lock!(contents)
+        .messages_mut()
+        .add(MessageLevel::Error, "E223: Recursive mapping");
+      lock!(data_access.state).macros_mut().fail();
+      keys.clear();
+      break;
+    }
+    if keymap.silent() && silent_since.is_none() {
+      let contents = data_access.contents.clone();
+      silent_since = Some(lock!(contents).messages().shown().len());
+    }
+    keys.drain(..n);
+    stateful = _execute(data_access, stateful, &keymap, &mut keys);
+  }
+
+  if let Some(shown) = silent_since {
+    let contents = data_access.contents.clone();
+    lock!(contents).messages_mut().hide_shown_since(shown);
+  }
+  (stateful, keys.into())
+}
+
+// Execute the right-hand side of the mapping, the keys of a recursive mapping are put in front of
+// the rest `keys`.
+fn _execute(
+  data_access: &StatefulDataAccess,
+  stateful: StatefulValue,
+  keymap: &Keymap,
+  keys: &mut VecDeque<KeyEvent>,
+) -> StatefulValue {
+  match keymap.target() {
+    KeymapTarget::Keys(rhs) if keymap.noremap() => {
+      feed_keys(data_access, stateful, rhs)
+    }
+    KeymapTarget::Keys(rhs) => {
+      // The left-hand side at the start of the right-hand side is not remapped, i.e. `:map ab abc`.
+      let mut stateful = stateful;
+      let lhs = keymap.lhs();
+      let rest = if rhs.starts_with(lhs) {
+        for key_event in lhs.iter() {
+          stateful = _handle_key(data_access, stateful, *key_event);
+        }
+        &rhs[lhs.len()..]
+      } else {
+        &rhs[..]
+      };
+      for key_event in rest.iter().rev() {
+        keys.push_front(*key_event);
+      }
+      stateful
+    }
+    // The builtin commands are not in the mappings, the keys are handled as they are.
+    KeymapTarget::Command(_) => feed_keys(data_access, stateful, keymap.lhs()),
+    // The callback runs in the js runtime before the rest keys, see `resume`.
+    KeymapTarget::Callback(_) => {
+      lock!(data_access.state).set_keymap_callback(Some(keymap.clone()));
+      stateful
+    }
+  }
+}
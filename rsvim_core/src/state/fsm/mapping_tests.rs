@@ -0,0 +1,229 @@
+use super::mapping::*;
+
+use crate::prelude::*;
+use crate::state::fsm::{NormalStateful, StatefulDataAccess, StatefulValue};
+use crate::state::keymap::{Keymap, KeymapOptions, KeymapTarget, parse_modes};
+use crate::state::keys;
+use crate::test::fsm::{chars, cursor, make_data_access, text};
+use crate::test::log::init as test_log_init;
+
+use crossterm::event::Event;
+
+// Type the events through the key mappings, starts from normal mode.
+fn typed(
+  data_access: &StatefulDataAccess,
+  stateful: StatefulValue,
+  events: Vec<Event>,
+) -> StatefulValue {
+  let mut stateful = stateful;
+  for event in events {
+    if let Event::Key(key_event) = event {
+      stateful = feed_key(data_access, stateful, key_event);
+    }
+  }
+  stateful
+}
+
+fn normal() -> StatefulValue {
+  StatefulValue::NormalMode(NormalStateful::default())
+}
+
+fn map(data_access: &StatefulDataAccess, modes: &str, lhs: &str, rhs: &str) {
+  let keymap = Keymap::new(
+    keys::parse(lhs),
+    KeymapTarget::Keys(keys::parse(rhs)),
+    KeymapOptions::default(),
+  );
+  lock!(data_access.state)
+    .keymaps_mut()
+    .set(&parse_modes(modes).unwrap(), keymap);
+}
+
+fn noremap(
+  data_access: &StatefulDataAccess,
+  modes: &str,
+  lhs: &str,
+  rhs: &str,
+) {
+  let keymap = Keymap::new(
+    keys::parse(lhs),
+    KeymapTarget::Keys(keys::parse(rhs)),
+    KeymapOptions {
+      noremap: true,
+      ..Default::default()
+    },
+  );
+  lock!(data_access.state)
+    .keymaps_mut()
+    .set(&parse_modes(modes).unwrap(), keymap);
+}
+
+#[test]
+fn defaults1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["abcdef\n"]);
+
+  typed(&data_access, normal(), chars("x"));
+  assert_eq!(text(&buf), "bcdef\n");
+  typed(&data_access, normal(), chars("2x"));
+  assert_eq!(text(&buf), "def\n");
+  typed(&data_access, normal(), chars("$X"));
+  assert_eq!(text(&buf), "df\n");
+  typed(&data_access, normal(), chars("0D"));
+  assert_eq!(text(&buf), "\n");
+
+  // Visual mode.
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["abcdef\n"]);
+  let stateful = typed(&data_access, normal(), chars("vlx"));
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(text(&buf), "cdef\n");
+}
+
+#[test]
+fn literal1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["abxcx\n"]);
+
+  // The char after `f` and `r` is not mapped.
+  typed(&data_access, normal(), chars("fx"));
+  assert_eq!(cursor(&data_access), (0, 2));
+  typed(&data_access, normal(), chars("rs"));
+  assert_eq!(text(&buf), "abscx\n");
+}
+
+#[test]
+fn remap1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["abc\n"]);
+
+  // The `x` is not remapped, and it does nothing in normal mode.
+  noremap(&data_access, "n", "Q", "x");
+  typed(&data_access, normal(), chars("Q"));
+  assert_eq!(text(&buf), "abc\n");
+
+  map(&data_access, "n", "Q", "x");
+  typed(&data_access, normal(), chars("Q"));
+  assert_eq!(text(&buf), "bc\n");
+
+  // The left-hand side at the start of the right-hand side is not remapped.
+  map(&data_access, "n", "l", "lx");
+  typed(&data_access, normal(), chars("l"));
+  assert_eq!(text(&buf), "b\n");
+}
+
+#[test]
+fn builtin1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["abc\n"]);
+
+  // The keys of a non-recursive mapping are the builtin commands, even if they're mapped.
+  map(&data_access, "n", "i", "x");
+  noremap(&data_access, "n", "Q", "iZ<Esc>");
+  typed(&data_access, normal(), chars("i"));
+  assert_eq!(text(&buf), "bc\n");
+  let stateful = typed(&data_access, normal(), chars("Q"));
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(text(&buf), "Zbc\n");
+}
+
+#[test]
+fn prefix1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["a\n", "b\n", "c\n"]);
+  noremap(&data_access, "n", "gx", "dd");
+
+  let stateful = typed(&data_access, normal(), chars("g"));
+  assert_eq!(*lock!(data_access.state).keymap_pending(), keys::parse("g"));
+  typed(&data_access, stateful, chars("x"));
+  assert!(lock!(data_access.state).keymap_pending().is_empty());
+  assert_eq!(text(&buf), "b\nc\n");
+
+  // The `gg` is not mapped.
+  typed(&data_access, normal(), chars("jgg"));
+  assert_eq!(cursor(&data_access), (0, 0));
+}
+
+#[test]
+fn flush1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["abc\n", "def\n"]);
+  noremap(&data_access, "n", "xx", "dd");
+
+  // The `x` waits for the next key, then it is the default mapping on timeout.
+  let stateful = typed(&data_access, normal(), chars("x"));
+  assert_eq!(text(&buf), "abc\ndef\n");
+  flush(&data_access, stateful);
+  assert_eq!(text(&buf), "bc\ndef\n");
+
+  typed(&data_access, normal(), chars("xx"));
+  assert_eq!(text(&buf), "def\n");
+
+  // The keys are not a mapping, the longest mapping is used.
+  typed(&data_access, normal(), chars("xl"));
+  assert_eq!(text(&buf), "ef\n");
+  assert_eq!(cursor(&data_access), (0, 1));
+}
+
+#[test]
+fn recursive1() {
+  test_log_init();
+  let (buf, contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["abc\n"]);
+  map(&data_access, "n", "Q", "W");
+  map(&data_access, "n", "W", "Q");
+
+  typed(&data_access, normal(), chars("Q"));
+  assert_eq!(text(&buf), "abc\n");
+  let contents = lock!(contents);
+  let shown_lines = contents.messages().shown_lines();
+  assert!(shown_lines.last().unwrap().1.contains("E223"));
+}
+
+#[test]
+fn feed_mapped_keys1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["abc\n"]);
+  noremap(&data_access, "n", "xx", "dd");
+
+  // The keys never wait for more keys.
+  feed_mapped_keys(&data_access, normal(), &keys::parse("x"));
+  assert_eq!(text(&buf), "bc\n");
+  assert!(lock!(data_access.state).keymap_pending().is_empty());
+}
+
+#[test]
+fn callback1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["abc\n"]);
+  let keymap = Keymap::new(
+    keys::parse("Q"),
+    KeymapTarget::Callback(1),
+    KeymapOptions {
+      expr: true,
+      ..Default::default()
+    },
+  );
+  lock!(data_access.state)
+    .keymaps_mut()
+    .set(&parse_modes("n").unwrap(), keymap);
+
+  // The keys after the mapping wait for the callback.
+  let stateful = typed(&data_access, normal(), chars("Qx"));
+  assert_eq!(text(&buf), "abc\n");
+  assert_eq!(*lock!(data_access.state).keymap_pending(), keys::parse("x"));
+
+  // The keys returned by the callback go first.
+  let keymap = lock!(data_access.state).take_keymap_callback().unwrap();
+  resume(&data_access, stateful, &keymap, keys::parse("l"));
+  assert_eq!(text(&buf), "ac\n");
+  assert!(lock!(data_access.state).keymap_pending().is_empty());
+}
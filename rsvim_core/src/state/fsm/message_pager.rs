@@ -0,0 +1,173 @@
+//! The message pager state.
+//!
+//! See: <https://vimhelp.org/message.txt.html#more-prompt> and
+//! <https://vimhelp.org/message.txt.html#hit-enter>.
+
+use crate::prelude::*;
+use crate::state::fsm::{
+  CommandLineExStateful, CommandLineSearchBackwardStateful,
+  CommandLineSearchForwardStateful, InsertStateful, NormalStateful,
+  ReplaceStateful, SelectStateful, Stateful, StatefulDataAccess, StatefulValue,
+  TerminalStateful, VisualStateful,
+};
+use crate::state::mode::Mode;
+use crate::state::ops::Operation;
+use crate::ui::tree::*;
+
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyModifiers};
+use tracing::trace;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// The message pager state.
+///
+/// NOTE: This is an internal state, it takes over the keys when the shown messages are taller than
+/// one line, and scrolls them like `more`. When the pager is closed, it goes back to the
+/// `previous` editing mode.
+pub struct MessagePagerStateful {
+  previous: Mode,
+}
+
+impl MessagePagerStateful {
+  pub fn new(previous: Mode) -> Self {
+    Self { previous }
+  }
+
+  /// The editing mode before the pager starts.
+  pub fn previous(&self) -> Mode {
+    self.previous
+  }
+
+  fn previous_stateful(&self) -> StatefulValue {
+    match self.previous {
+      Mode::Normal => StatefulValue::NormalMode(NormalStateful::default()),
+      Mode::Visual => StatefulValue::VisualMode(VisualStateful::default()),
+      Mode::Select => StatefulValue::SelectMode(SelectStateful::default()),
+      // The pending operator cannot be restored, the pager is never started from operator-pending
+      // mode anyway.
+      Mode::OperatorPending => {
+        StatefulValue::NormalMode(NormalStateful::default())
+      }
+      Mode::Insert => StatefulValue::InsertMode(InsertStateful::default()),
+      Mode::Replace => StatefulValue::ReplaceMode(ReplaceStateful::new(false)),
+      Mode::VirtualReplace => {
+        StatefulValue::ReplaceMode(ReplaceStateful::new(true))
+      }
+      Mode::CommandLineEx => {
+        StatefulValue::CommandLineExMode(CommandLineExStateful::default())
+      }
+      Mode::CommandLineSearchForward => {
+        StatefulValue::CommandLineSearchForwardMode(
+          CommandLineSearchForwardStateful::default(),
+        )
+      }
+      Mode::CommandLineSearchBackward => {
+        StatefulValue::CommandLineSearchBackwardMode(
+          CommandLineSearchBackwardStateful::default(),
+        )
+      }
+      Mode::Terminal => {
+        StatefulValue::TerminalMode(TerminalStateful::default())
+      }
+    }
+  }
+
+  /// Lines shown in the pager, i.e. all the rows above the command-line.
+  fn height(data_access: &StatefulDataAccess) -> usize {
+    let tree = lock!(data_access.tree);
+    debug_assert!(tree.command_line().is_some());
+    let cmdline = tree.command_line().unwrap();
+    (cmdline.actual_shape().min().y as usize).max(1)
+  }
+
+  fn get_operation(
+    &self,
+    event: &Event,
+    height: usize,
+    at_end: bool,
+  ) -> Option<Operation> {
+    let key_event = match event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+        key_event
+      }
+      _ => return None,
+    };
+    trace!("Event::key:{:?}", key_event);
+
+    let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+    let page = height as isize;
+    // Scroll down, or close the pager if it already shows the last line.
+    let down = |n: isize| {
+      if at_end {
+        Operation::MessagePagerClose
+      } else {
+        Operation::MessagePagerScrollBy(n)
+      }
+    };
+
+    let op = match key_event.code {
+      KeyCode::Char('c') if ctrl => Operation::MessagePagerClose,
+      KeyCode::Char('f') if ctrl => down(page),
+      KeyCode::Char('b') if ctrl => Operation::MessagePagerScrollBy(-page),
+      KeyCode::Char(_) if ctrl => return None,
+      KeyCode::Enter | KeyCode::Down | KeyCode::Char('j') => down(1),
+      KeyCode::Char(' ') | KeyCode::PageDown | KeyCode::Char('f') => down(page),
+      KeyCode::Char('d') => down((page / 2).max(1)),
+      KeyCode::Up | KeyCode::Char('k') => Operation::MessagePagerScrollBy(-1),
+      KeyCode::PageUp | KeyCode::Char('b') => {
+        Operation::MessagePagerScrollBy(-page)
+      }
+      KeyCode::Char('u') => Operation::MessagePagerScrollBy(-(page / 2).max(1)),
+      KeyCode::Char('G') => Operation::MessagePagerScrollBy(isize::MAX),
+      KeyCode::Char('g') => Operation::MessagePagerScrollBy(isize::MIN),
+      KeyCode::Esc | KeyCode::Char('q') => Operation::MessagePagerClose,
+      _ => return None,
+    };
+    Some(op)
+  }
+}
+
+impl Stateful for MessagePagerStateful {
+  fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    let event = data_access.event.clone();
+    let height = Self::height(&data_access);
+    let at_end = lock!(data_access.contents).messages().pager_at_end(height);
+
+    if let Some(op) = self.get_operation(&event, height, at_end) {
+      return self.handle_op(data_access, op);
+    }
+
+    // On the last page, other keys close the pager and they're handled by the previous mode,
+    // i.e. "Press ENTER or type command to continue".
+    let is_key_press = matches!(
+      &event,
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press
+    );
+    if at_end && is_key_press {
+      lock!(data_access.contents).messages_mut().clear_shown();
+      return self.previous_stateful().handle(data_access);
+    }
+
+    StatefulValue::MessagePagerState(*self)
+  }
+
+  fn handle_op(
+    &self,
+    data_access: StatefulDataAccess,
+    op: Operation,
+  ) -> StatefulValue {
+    match op {
+      Operation::MessagePagerScrollBy(n) => {
+        let height = Self::height(&data_access);
+        lock!(data_access.contents)
+          .messages_mut()
+          .scroll_pager(n, height);
+        StatefulValue::MessagePagerState(*self)
+      }
+      Operation::MessagePagerClose => {
+        lock!(data_access.contents).messages_mut().clear_shown();
+        self.previous_stateful()
+      }
+      _ => unreachable!(),
+    }
+  }
+}
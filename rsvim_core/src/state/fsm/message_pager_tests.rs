@@ -0,0 +1,185 @@
+use super::message_pager::*;
+
+use crate::content::TextContentsArc;
+use crate::content::message::MessageLevel;
+use crate::excommand::BuiltinExCommand;
+use crate::opt::OptionTarget;
+use crate::prelude::*;
+use crate::state::fsm::command_line_ex_tests::make_tree_with_cmdline;
+use crate::state::fsm::{
+  CommandLineExStateful, Stateful, StatefulDataAccess, StatefulValue,
+};
+use crate::state::mode::Mode;
+use crate::test::log::init as test_log_init;
+use crate::ui::widget::window::WindowLocalOptionsBuilder;
+
+use compact_str::CompactString;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+fn make_data_access() -> (TextContentsArc, StatefulDataAccess) {
+  // The command-line is the 5th row, the pager shows 4 lines.
+  let terminal_size = U16Size::new(30, 5);
+  let window_options = WindowLocalOptionsBuilder::default().build().unwrap();
+  let (tree, state, bufs, _buf, contents) =
+    make_tree_with_cmdline(terminal_size, window_options, vec![]);
+  let data_access = StatefulDataAccess::new(
+    state,
+    tree,
+    bufs,
+    contents.clone(),
+    Event::FocusGained,
+  );
+  (contents, data_access)
+}
+
+fn start_pager(contents: &TextContentsArc, n: usize) {
+  let mut contents = lock!(contents);
+  let messages = contents.messages_mut();
+  for i in 0..n {
+    messages.add(MessageLevel::Info, &format!("{i}"));
+  }
+  assert!(messages.needs_pager());
+  messages.start_pager();
+}
+
+fn key(code: KeyCode) -> Event {
+  Event::Key(KeyEvent::new_with_kind(
+    code,
+    KeyModifiers::empty(),
+    KeyEventKind::Press,
+  ))
+}
+
+/// Send the key to the stateful, returns the next stateful.
+fn press(
+  data_access: &StatefulDataAccess,
+  stateful: &StatefulValue,
+  code: KeyCode,
+) -> StatefulValue {
+  let data_access = StatefulDataAccess::new(
+    data_access.state.clone(),
+    data_access.tree.clone(),
+    data_access.buffers.clone(),
+    data_access.contents.clone(),
+    key(code),
+  );
+  stateful.handle(data_access)
+}
+
+fn pager_top(contents: &TextContentsArc) -> Option<usize> {
+  lock!(contents).messages().pager_top()
+}
+
+#[test]
+fn scroll1() {
+  test_log_init();
+  let (contents, data_access) = make_data_access();
+  start_pager(&contents, 10);
+  let pager =
+    StatefulValue::MessagePagerState(MessagePagerStateful::new(Mode::Normal));
+
+  let stateful = press(&data_access, &pager, KeyCode::Char('j'));
+  assert_eq!(stateful, pager);
+  assert_eq!(pager_top(&contents), Some(1));
+  let stateful = press(&data_access, &stateful, KeyCode::Char(' '));
+  assert_eq!(pager_top(&contents), Some(5));
+  let stateful = press(&data_access, &stateful, KeyCode::Char('k'));
+  assert_eq!(pager_top(&contents), Some(4));
+  let stateful = press(&data_access, &stateful, KeyCode::Char('u'));
+  assert_eq!(pager_top(&contents), Some(2));
+  let stateful = press(&data_access, &stateful, KeyCode::Char('G'));
+  assert_eq!(pager_top(&contents), Some(6));
+  let stateful = press(&data_access, &stateful, KeyCode::Char('g'));
+  assert_eq!(pager_top(&contents), Some(0));
+  let stateful = press(&data_access, &stateful, KeyCode::Char('d'));
+  assert_eq!(pager_top(&contents), Some(2));
+
+  // Unknown keys are ignored before the last page.
+  let stateful = press(&data_access, &stateful, KeyCode::Char('x'));
+  assert_eq!(stateful, pager);
+  assert_eq!(pager_top(&contents), Some(2));
+
+  // Enter on the last page closes the pager.
+  let stateful = press(&data_access, &stateful, KeyCode::Char('G'));
+  let stateful = press(&data_access, &stateful, KeyCode::Enter);
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(pager_top(&contents), None);
+  assert!(lock!(contents).messages().shown().is_empty());
+  assert_eq!(lock!(contents).messages().history().len(), 10);
+}
+
+#[test]
+fn close1() {
+  test_log_init();
+  let (contents, data_access) = make_data_access();
+  start_pager(&contents, 10);
+  let pager =
+    StatefulValue::MessagePagerState(MessagePagerStateful::new(Mode::Insert));
+
+  let stateful = press(&data_access, &pager, KeyCode::Char('q'));
+  assert!(matches!(stateful, StatefulValue::InsertMode(_)));
+  assert_eq!(pager_top(&contents), None);
+}
+
+#[test]
+fn type_command1() {
+  test_log_init();
+  let (contents, data_access) = make_data_access();
+  start_pager(&contents, 3);
+  let pager =
+    StatefulValue::MessagePagerState(MessagePagerStateful::new(Mode::Normal));
+
+  // All lines are shown, other keys close the pager and start the command.
+  let stateful = press(&data_access, &pager, KeyCode::Char(':'));
+  assert!(matches!(stateful, StatefulValue::CommandLineExMode(_)));
+  assert_eq!(pager_top(&contents), None);
+  assert!(lock!(contents).messages().shown().is_empty());
+}
+
+#[test]
+fn builtin_output1() {
+  test_log_init();
+  let (contents, data_access) = make_data_access();
+  let stateful = CommandLineExStateful::default();
+
+  // Errors are saved in history.
+  stateful.run_builtin_ex_command(
+    &data_access,
+    BuiltinExCommand::Set(OptionTarget::LocalAndGlobal, "foo".into()),
+  );
+  {
+    let contents = lock!(contents);
+    let messages = contents.messages();
+    assert_eq!(messages.shown().len(), 1);
+    assert_eq!(messages.shown()[0].level(), MessageLevel::Error);
+    assert_eq!(messages.history().len(), 1);
+    assert!(!messages.needs_pager());
+  }
+  lock!(contents).messages_mut().clear_shown();
+
+  // Outputs are not saved in history.
+  stateful.run_builtin_ex_command(
+    &data_access,
+    BuiltinExCommand::Messages(CompactString::new("")),
+  );
+  {
+    let contents = lock!(contents);
+    let messages = contents.messages();
+    assert_eq!(messages.shown().len(), 1);
+    assert_eq!(messages.shown()[0].level(), MessageLevel::Error);
+    assert_eq!(messages.history().len(), 1);
+  }
+  lock!(contents).messages_mut().clear_shown();
+
+  stateful.run_builtin_ex_command(
+    &data_access,
+    BuiltinExCommand::Set(OptionTarget::LocalAndGlobal, "all".into()),
+  );
+  {
+    let contents = lock!(contents);
+    let messages = contents.messages();
+    assert_eq!(messages.shown_lines()[0].1, "--- Options ---");
+    assert!(messages.needs_pager());
+    assert_eq!(messages.history().len(), 1);
+  }
+}
@@ -0,0 +1,197 @@
+//! The motion keys shared by the normal mode, visual mode and operator-pending mode.
+//!
+//! The keys of the motions are the builtin commands in the key mappings table, see
+//! [`Command::Motion`](crate::state::keymap::Command::Motion).
+//!
+//! See: <https://vimhelp.org/motion.txt.html>.
+
+use crate::prelude::*;
+use crate::state::fsm::StatefulDataAccess;
+use crate::state::keymap::Command;
+use crate::state::ops::{CharFind, CharFindKind, Operation, WordMotion};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// The motions, they move the cursor `count` times.
+pub enum Motion {
+  /// Left, i.e. `h`.
+  Left,
+  /// Right, i.e. `l`.
+  Right,
+  /// Up, i.e. `k`.
+  Up,
+  /// Down, i.e. `j`.
+  Down,
+  /// The first char of the line, i.e. `0`.
+  LineStart,
+  /// The first non-blank char of the line, i.e. `^`.
+  FirstNonBlank,
+  /// The last char of the line, i.e. `$`.
+  LineEnd,
+  /// The last non-blank char of the line, i.e. `g_`.
+  LastNonBlank,
+  /// The words, i.e. `w`, `b`, `e`, `ge` and the WORD motions `W`, `B`, `E`, `gE`.
+  Word(WordMotion, /* big_word */ bool),
+  /// The first line, or the line of the count, i.e. `gg`.
+  FirstLine,
+  /// The last line, or the line of the count, i.e. `G`.
+  LastLine,
+  /// The matching bracket, i.e. `%`.
+  MatchingBracket,
+  /// The sentences forward (`)`) or backward (`(`).
+  Sentence(/* forward */ bool),
+  /// The paragraphs forward (`}`) or backward (`{`).
+  Paragraph(/* forward */ bool),
+  /// The top of window, i.e. `H`.
+  WindowTop,
+  /// The middle of window, i.e. `M`.
+  WindowMiddle,
+  /// The bottom of window, i.e. `L`.
+  WindowBottom,
+  /// Repeat the last char find motion (`;`), or in the opposite direction (`,`).
+  RepeatCharFind(/* reverse */ bool),
+}
+
+/// Get the motion of the key with the `count`, i.e. `w`, `$`, `G`, `;`.
+///
+/// The `;` and `,` repeat the last char find motion, it returns `None` if there's no char find
+/// motion yet.
+pub fn get_motion(
+  data_access: &StatefulDataAccess,
+  code: KeyCode,
+  count: Option<usize>,
+) -> Option<Operation> {
+  _lookup(data_access, &[_key(code)], count)
+}
+
+/// Get the motion of the key after `g` with the `count`, i.e. `ge`, `g_`, `gg`.
+pub fn get_g_motion(
+  data_access: &StatefulDataAccess,
+  code: KeyCode,
+  count: Option<usize>,
+) -> Option<Operation> {
+  _lookup(data_access, &[_key(KeyCode::Char('g')), _key(code)], count)
+}
+
+fn _key(code: KeyCode) -> KeyEvent {
+  KeyEvent::new_with_kind(code, KeyModifiers::empty(), KeyEventKind::Press)
+}
+
+// The motion of the keys in the builtin commands of current mode.
+fn _lookup(
+  data_access: &StatefulDataAccess,
+  keys: &[KeyEvent],
+  count: Option<usize>,
+) -> Option<Operation> {
+  let motion = {
+    let state = lock!(data_access.state);
+    match state.keymaps().command(state.mode(), keys) {
+      Some(Command::Motion(motion)) => motion,
+      _ => return None,
+    }
+  };
+  motion_operation(data_access, motion, count)
+}
+
+/// The operation of the `motion` with the `count`.
+///
+/// The `;` and `,` repeat the last char find motion, it returns `None` if there's no char find
+/// motion yet.
+pub fn motion_operation(
+  data_access: &StatefulDataAccess,
+  motion: Motion,
+  count: Option<usize>,
+) -> Option<Operation> {
+  let n = count.unwrap_or(1);
+  match motion {
+    Motion::Left => Some(Operation::CursorMoveLeftBy(n)),
+    Motion::Right => Some(Operation::CursorMoveRightBy(n)),
+    Motion::Up => Some(Operation::CursorMoveUpBy(n)),
+    Motion::Down => Some(Operation::CursorMoveDownBy(n)),
+    Motion::LineStart => Some(Operation::CursorMoveToLineStart),
+    Motion::FirstNonBlank => Some(Operation::CursorMoveToFirstNonBlank),
+    Motion::LineEnd => Some(Operation::CursorMoveToLineEnd(n)),
+    Motion::LastNonBlank => Some(Operation::CursorMoveToLastNonBlank(n)),
+    Motion::Word(word, big_word) => {
+      Some(Operation::CursorMoveByWord((word, big_word, n)))
+    }
+    // `gg` goes to the first line without count.
+    Motion::FirstLine => Some(Operation::CursorMoveToLine(
+      count.map(|n| n.saturating_sub(1)).unwrap_or(0),
+    )),
+    // `G` goes to the last line without count.
+    Motion::LastLine => Some(Operation::CursorMoveToLine(
+      count.map(|n| n.saturating_sub(1)).unwrap_or(usize::MAX),
+    )),
+    Motion::MatchingBracket => Some(Operation::CursorMoveToMatchingBracket),
+    Motion::Sentence(forward) => {
+      Some(Operation::CursorMoveBySentence((forward, n)))
+    }
+    Motion::Paragraph(forward) => {
+      Some(Operation::CursorMoveByParagraph((forward, n)))
+    }
+    Motion::WindowTop => Some(Operation::CursorMoveToWindowTop(n)),
+    Motion::WindowMiddle => Some(Operation::CursorMoveToWindowMiddle),
+    Motion::WindowBottom => Some(Operation::CursorMoveToWindowBottom(n)),
+    Motion::RepeatCharFind(reverse) => {
+      let find = lock!(data_access.state).last_char_find()?;
+      let find = if reverse {
+        CharFind {
+          kind: find.kind.reverse(),
+          c: find.c,
+        }
+      } else {
+        find
+      };
+      Some(Operation::CursorMoveToChar((find, true, n)))
+    }
+  }
+}
+
+/// Get the char find motion that waits for the char, i.e. `f`, `F`, `t`, `T`.
+pub fn get_char_find_kind(code: KeyCode) -> Option<CharFindKind> {
+  match code {
+    KeyCode::Char('f') => Some(CharFindKind::Forward),
+    KeyCode::Char('F') => Some(CharFindKind::Backward),
+    KeyCode::Char('t') => Some(CharFindKind::TillForward),
+    KeyCode::Char('T') => Some(CharFindKind::TillBackward),
+    _ => None,
+  }
+}
+
+/// Get the char find motion of the char `c`, and save it for `;` and `,`.
+pub fn char_find_motion(
+  data_access: &StatefulDataAccess,
+  kind: CharFindKind,
+  c: char,
+  count: Option<usize>,
+) -> Operation {
+  let find = CharFind { kind, c };
+  lock!(data_access.state).set_last_char_find(Some(find));
+  Operation::CursorMoveToChar((find, false, count.unwrap_or(1)))
+}
+
+/// Whether the motion fails, i.e. the relative motion doesn't move the cursor from `from`, for
+/// example `j` at the last line, `fx` when `x` is not found. The absolute motions (i.e. `0`, `gg`)
+/// don't fail.
+pub fn is_failed_motion(
+  op: &Operation,
+  from: (usize, usize),
+  to: (usize, usize),
+) -> bool {
+  let relative = matches!(
+    op,
+    Operation::CursorMoveBy(_)
+      | Operation::CursorMoveLeftBy(_)
+      | Operation::CursorMoveRightBy(_)
+      | Operation::CursorMoveUpBy(_)
+      | Operation::CursorMoveDownBy(_)
+      | Operation::CursorMoveByWord(_)
+      | Operation::CursorMoveToChar(_)
+      | Operation::CursorMoveToMatchingBracket
+      | Operation::CursorMoveBySentence(_)
+      | Operation::CursorMoveByParagraph(_)
+  );
+  relative && from == to
+}
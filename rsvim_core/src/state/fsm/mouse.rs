@@ -0,0 +1,475 @@
+//! The mouse support.
+//!
+//! The mouse events are handled outside of the editing modes, they're enabled by the 'mouse'
+//! option for the current mode:
+//!
+//! - Left click moves the cursor to the clicked position, or focuses the clicked window. It stops
+//!   the selection in visual/select mode.
+//! - Double click selects a word, triple click selects a line, in visual mode (or select mode if
+//!   the 'selectmode' option contains `mouse`).
+//! - Left drag starts the selection, or extends it.
+//! - The wheel scrolls the window under the mouse, the cursor stays inside the window.
+//! - Left click in command-line modes moves the cursor in command-line, and the wheel scrolls the
+//!   message pager.
+//!
+//! See: <https://vimhelp.org/term.txt.html#mouse-using>.
+
+use crate::buf::selection::SelectionKind;
+use crate::buf::text::Text;
+use crate::prelude::*;
+use crate::state::fsm::{
+  NormalStateful, SelectStateful, Stateful, StatefulDataAccess, StatefulValue,
+  VisualStateful,
+};
+use crate::state::ops::{Operation, cursor_ops};
+use crate::state::opt::GlobalOptions;
+use crate::ui::tree::*;
+use crate::ui::viewport::Viewport;
+use crate::ui::widget::window::WindowNode;
+
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use std::time::{Duration, Instant};
+use tracing::trace;
+
+/// The max interval between the clicks of a double/triple click, it is the default value of Vim's
+/// 'mousetime' option.
+pub const MULTI_CLICK_TIME: Duration = Duration::from_millis(500);
+
+/// The lines scrolled by the wheel.
+pub const SCROLL_LINES: usize = 3;
+
+/// The columns scrolled by the horizontal wheel.
+pub const SCROLL_COLUMNS: usize = 6;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// The last mouse click, the clicks at the same position within [`MULTI_CLICK_TIME`] are counted
+/// as a double/triple click.
+pub struct MouseClick {
+  time: Instant,
+  column: u16,
+  row: u16,
+  count: usize,
+}
+
+impl MouseClick {
+  /// The click at `(column, row)` after the `last` click.
+  pub fn next(
+    last: Option<MouseClick>,
+    column: u16,
+    row: u16,
+    time: Instant,
+  ) -> Self {
+    let count = match last {
+      Some(last)
+        if last.column == column
+          && last.row == row
+          && time.saturating_duration_since(last.time) <= MULTI_CLICK_TIME =>
+      {
+        last.count + 1
+      }
+      _ => 1,
+    };
+    Self {
+      time,
+      column,
+      row,
+      count,
+    }
+  }
+
+  /// The clicks count, i.e. `2` is a double click.
+  pub fn count(&self) -> usize {
+    self.count
+  }
+}
+
+/// The flag of the 'mouse' option that enables the mouse in the `stateful`, or `None` if the mouse
+/// is never enabled in it.
+pub fn mouse_flag(stateful: &StatefulValue) -> Option<char> {
+  match stateful {
+    StatefulValue::NormalMode(_) => Some('n'),
+    StatefulValue::VisualMode(_) | StatefulValue::SelectMode(_) => Some('v'),
+    StatefulValue::InsertMode(_) | StatefulValue::ReplaceMode(_) => Some('i'),
+    StatefulValue::CommandLineExMode(_)
+    | StatefulValue::CommandLineSearchForwardMode(_)
+    | StatefulValue::CommandLineSearchBackwardMode(_) => Some('c'),
+    StatefulValue::MessagePagerState(_) => Some('r'),
+    // The keys are sent to the terminal program in terminal mode, the mouse is left to the
+    // terminal.
+    StatefulValue::OperatorPendingMode(_)
+    | StatefulValue::TerminalMode(_)
+    | StatefulValue::QuitState(_)
+    | StatefulValue::ConfirmState(_) => None,
+  }
+}
+
+/// Whether the 'mouse' option enables the mouse in the `stateful`, i.e. the mouse events are
+/// captured.
+pub fn is_enabled(options: &GlobalOptions, stateful: &StatefulValue) -> bool {
+  mouse_flag(stateful)
+    .map(|flag| options.mouse_has(flag))
+    .unwrap_or(false)
+}
+
+fn _contains(shape: &U16Rect, column: u16, row: u16) -> bool {
+  column >= shape.min().x
+    && column < shape.max().x
+    && row >= shape.min().y
+    && row < shape.max().y
+}
+
+/// The window at the terminal position `(column, row)`.
+pub fn window_at(tree: &Tree, column: u16, row: u16) -> Option<TreeNodeId> {
+  tree.window_ids().iter().copied().find(|window_id| {
+    tree
+      .window(*window_id)
+      .map(|window| _contains(window.content().actual_shape(), column, row))
+      .unwrap_or(false)
+  })
+}
+
+/// Map the position `(column, row)` in the viewport back to the buffer position
+/// `(line_idx, char_idx)`, through the rows of each line in the viewport.
+///
+/// The position below the last line maps to the last row, the position after the end of a row maps
+/// to its last char, or the end of line if it is the last row of the line.
+///
+/// It returns `None` if the viewport is empty.
+pub fn buffer_position(
+  viewport: &Viewport,
+  text: &Text,
+  column: u16,
+  row: u16,
+) -> Option<(usize, usize)> {
+  let mut found = None;
+  for (line_idx, line_viewport) in viewport.lines().iter() {
+    for (row_idx, row_viewport) in line_viewport.rows().iter() {
+      if *row_idx > row {
+        break;
+      }
+      found = Some((*line_idx, line_viewport, *row_idx, *row_viewport));
+    }
+  }
+  let (line_idx, line_viewport, row_idx, row_viewport) = found?;
+
+  let start_char_idx = row_viewport.start_char_idx();
+  let end_char_idx = row_viewport.end_char_idx();
+  let mut row_start_width = text.width_before(line_idx, start_char_idx);
+  let first_row_idx = line_viewport.rows().first().map(|(r, _)| *r);
+  if first_row_idx == Some(row_idx) {
+    row_start_width =
+      row_start_width.saturating_sub(line_viewport.start_filled_cols());
+  }
+  let width = row_start_width + column as usize;
+
+  for char_idx in start_char_idx..end_char_idx {
+    if text.width_until(line_idx, char_idx) > width {
+      return Some((line_idx, char_idx));
+    }
+  }
+  if end_char_idx >= text.rope().line(line_idx).len_chars() {
+    Some((line_idx, end_char_idx))
+  } else {
+    Some((
+      line_idx,
+      std::cmp::max(end_char_idx.saturating_sub(1), start_char_idx),
+    ))
+  }
+}
+
+/// Map the terminal position `(column, row)` in the window back to its buffer position
+/// `(line_idx, char_idx)`, the position outside of the window is clamped into it.
+pub fn window_position(
+  tree: &Tree,
+  window_id: TreeNodeId,
+  column: u16,
+  row: u16,
+) -> Option<(usize, usize)> {
+  let window = tree.window(window_id)?;
+  let shape = window.content().actual_shape();
+  let min = shape.min();
+  let max = shape.max();
+  let column = column.clamp(min.x, std::cmp::max(max.x, min.x + 1) - 1) - min.x;
+  let row = row.clamp(min.y, std::cmp::max(max.y, min.y + 1) - 1) - min.y;
+  let buffer = window.buffer().upgrade().unwrap();
+  let buffer = lock!(buffer);
+  buffer_position(&window.viewport(), buffer.text(), column, row)
+}
+
+/// Move the cursor into the window, it becomes the current window.
+pub fn focus_window(tree: &mut Tree, window_id: TreeNodeId) {
+  if tree.current_window_id() == Some(window_id) {
+    return;
+  }
+  let cursor = match tree.current_window_mut().unwrap().remove_cursor() {
+    Some(WindowNode::Cursor(cursor)) => Some(cursor),
+    _ => None,
+  };
+  tree.set_current_window_id(Some(window_id));
+
+  let current_window = tree.current_window_mut().unwrap();
+  if let Some(cursor) = cursor {
+    let cursor_viewport = current_window.cursor_viewport();
+    current_window.insert_cursor(cursor);
+    current_window.move_cursor_to(
+      cursor_viewport.column_idx() as isize,
+      cursor_viewport.row_idx() as isize,
+    );
+  }
+}
+
+fn _handle_op(
+  data_access: &StatefulDataAccess,
+  stateful: StatefulValue,
+  op: Operation,
+) -> StatefulValue {
+  stateful.handle_op(data_access.with_event(data_access.event.clone()), op)
+}
+
+// Start the selection at cursor, the `word` is selected if it is a double click.
+fn _start_selection(
+  data_access: &StatefulDataAccess,
+  kind: SelectionKind,
+  word: bool,
+) -> StatefulValue {
+  NormalStateful::default().goto_visual_mode(data_access, kind);
+  if word {
+    VisualStateful::default().select_text_object(data_access, 'w', true);
+  }
+  let select = lock!(data_access.state)
+    .global_options()
+    .select_mode_has("mouse");
+  if select {
+    StatefulValue::SelectMode(SelectStateful::default())
+  } else {
+    StatefulValue::VisualMode(VisualStateful::default())
+  }
+}
+
+fn _click(
+  data_access: &StatefulDataAccess,
+  stateful: StatefulValue,
+  column: u16,
+  row: u16,
+) -> StatefulValue {
+  let count = {
+    let mut state = lock!(data_access.state);
+    let click =
+      MouseClick::next(state.mouse_click(), column, row, Instant::now());
+    state.set_mouse_click(Some(click));
+    click.count()
+  };
+
+  let (window_id, (line_idx, char_idx)) = {
+    let tree = lock!(data_access.tree);
+    let window_id = match window_at(&tree, column, row) {
+      Some(window_id) => window_id,
+      None => return stateful,
+    };
+    match window_position(&tree, window_id, column, row) {
+      Some(position) => (window_id, position),
+      None => return stateful,
+    }
+  };
+
+  let stateful = match stateful {
+    StatefulValue::VisualMode(_) | StatefulValue::SelectMode(_) => {
+      VisualStateful::default().goto_normal_mode(data_access)
+    }
+    stateful => stateful,
+  };
+  focus_window(&mut lock!(data_access.tree), window_id);
+  let stateful = _handle_op(
+    data_access,
+    stateful,
+    Operation::CursorMoveTo((char_idx, line_idx)),
+  );
+
+  // The double/triple click only selects in normal mode.
+  match stateful {
+    StatefulValue::NormalMode(_) => match (count - 1) % 3 {
+      1 => _start_selection(data_access, SelectionKind::Char, true),
+      2 => _start_selection(data_access, SelectionKind::Line, false),
+      _ => stateful,
+    },
+    stateful => stateful,
+  }
+}
+
+fn _drag(
+  data_access: &StatefulDataAccess,
+  stateful: StatefulValue,
+  column: u16,
+  row: u16,
+) -> StatefulValue {
+  // The drag stays in current window.
+  let (line_idx, char_idx) = {
+    let tree = lock!(data_access.tree);
+    let window_id = tree.current_window_id().unwrap();
+    match window_position(&tree, window_id, column, row) {
+      Some(position) => position,
+      None => return stateful,
+    }
+  };
+
+  let stateful = match stateful {
+    StatefulValue::NormalMode(_) => {
+      _start_selection(data_access, SelectionKind::Char, false)
+    }
+    stateful => stateful,
+  };
+  _handle_op(
+    data_access,
+    stateful,
+    Operation::CursorMoveTo((char_idx, line_idx)),
+  )
+}
+
+fn _scroll(
+  data_access: &StatefulDataAccess,
+  stateful: StatefulValue,
+  op: Operation,
+  column: u16,
+  row: u16,
+) -> StatefulValue {
+  let include_eol = matches!(
+    stateful,
+    StatefulValue::InsertMode(_) | StatefulValue::ReplaceMode(_)
+  );
+  let (line_idx, char_idx) = {
+    let mut tree = lock!(data_access.tree);
+    let current_window_id = tree.current_window_id().unwrap();
+    let window_id = window_at(&tree, column, row).unwrap_or(current_window_id);
+    let buffer = tree.window(window_id).unwrap().buffer().upgrade().unwrap();
+    let buffer = lock!(buffer);
+    cursor_ops::window_scroll(
+      &mut tree,
+      window_id,
+      buffer.text(),
+      op,
+      include_eol,
+    );
+    if window_id != current_window_id {
+      return stateful;
+    }
+    let cursor_viewport = tree.current_window().unwrap().cursor_viewport();
+    (cursor_viewport.line_idx(), cursor_viewport.char_idx())
+  };
+
+  // The selection follows the cursor.
+  match stateful {
+    StatefulValue::VisualMode(_) | StatefulValue::SelectMode(_) => _handle_op(
+      data_access,
+      stateful,
+      Operation::CursorMoveTo((char_idx, line_idx)),
+    ),
+    stateful => stateful,
+  }
+}
+
+fn _command_line_click(
+  data_access: &StatefulDataAccess,
+  column: u16,
+  row: u16,
+) {
+  let mut tree = lock!(data_access.tree);
+  let cmdline_id = match tree.command_line_id() {
+    Some(cmdline_id) => cmdline_id,
+    None => return,
+  };
+  let cmdline = tree.command_line().unwrap();
+  let shape = *cmdline.content().actual_shape();
+  if !_contains(&shape, column, row) {
+    return;
+  }
+  let contents = lock!(data_access.contents);
+  let text = contents.command_line_content();
+  let position = buffer_position(
+    &cmdline.viewport(),
+    text,
+    column - shape.min().x,
+    row - shape.min().y,
+  );
+  if let Some((line_idx, char_idx)) = position {
+    cursor_ops::cursor_move(
+      &mut tree,
+      cmdline_id,
+      text,
+      Operation::CursorMoveTo((char_idx, line_idx)),
+      true,
+    );
+  }
+}
+
+/// Handle the mouse event in the `stateful`, if the 'mouse' option enables it.
+///
+/// Returns next state.
+pub fn handle(
+  data_access: &StatefulDataAccess,
+  stateful: StatefulValue,
+  mouse_event: MouseEvent,
+) -> StatefulValue {
+  let enabled =
+    is_enabled(lock!(data_access.state).global_options(), &stateful);
+  if !enabled {
+    return stateful;
+  }
+  trace!("Event::mouse:{:?}", mouse_event);
+
+  let column = mouse_event.column;
+  let row = mouse_event.row;
+  let scroll = match mouse_event.kind {
+    MouseEventKind::ScrollUp => {
+      Some(Operation::WindowScrollBy((0, -(SCROLL_LINES as isize))))
+    }
+    MouseEventKind::ScrollDown => {
+      Some(Operation::WindowScrollBy((0, SCROLL_LINES as isize)))
+    }
+    MouseEventKind::ScrollLeft => {
+      Some(Operation::WindowScrollBy((-(SCROLL_COLUMNS as isize), 0)))
+    }
+    MouseEventKind::ScrollRight => {
+      Some(Operation::WindowScrollBy((SCROLL_COLUMNS as isize, 0)))
+    }
+    _ => None,
+  };
+
+  match stateful {
+    StatefulValue::NormalMode(_)
+    | StatefulValue::VisualMode(_)
+    | StatefulValue::SelectMode(_)
+    | StatefulValue::InsertMode(_)
+    | StatefulValue::ReplaceMode(_) => match (mouse_event.kind, scroll) {
+      (MouseEventKind::Down(MouseButton::Left), _) => {
+        _click(data_access, stateful, column, row)
+      }
+      (MouseEventKind::Drag(MouseButton::Left), _) => {
+        _drag(data_access, stateful, column, row)
+      }
+      (_, Some(op)) => _scroll(data_access, stateful, op, column, row),
+      _ => stateful,
+    },
+    StatefulValue::CommandLineExMode(_)
+    | StatefulValue::CommandLineSearchForwardMode(_)
+    | StatefulValue::CommandLineSearchBackwardMode(_) => {
+      if mouse_event.kind == MouseEventKind::Down(MouseButton::Left) {
+        _command_line_click(data_access, column, row);
+      }
+      stateful
+    }
+    StatefulValue::MessagePagerState(_) => match mouse_event.kind {
+      MouseEventKind::ScrollUp => _handle_op(
+        data_access,
+        stateful,
+        Operation::MessagePagerScrollBy(-(SCROLL_LINES as isize)),
+      ),
+      MouseEventKind::ScrollDown => _handle_op(
+        data_access,
+        stateful,
+        Operation::MessagePagerScrollBy(SCROLL_LINES as isize),
+      ),
+      _ => stateful,
+    },
+    _ => stateful,
+  }
+}
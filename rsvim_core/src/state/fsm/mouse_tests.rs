@@ -0,0 +1,261 @@
+use super::mouse::*;
+
+use crate::buf::selection::{Selection, SelectionKind};
+use crate::prelude::*;
+use crate::state::autocmd::AutoCmdEvent;
+use crate::state::fsm::{StatefulDataAccess, StatefulValue, VisualStateful};
+use crate::test::fsm::{chars, cursor, make_data_access, press, selection};
+use crate::test::log::init as test_log_init;
+use crate::ui::tree::*;
+use crate::ui::widget::window::Window;
+
+use crossterm::event::{
+  Event, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+fn set_mouse(data_access: &StatefulDataAccess, value: &str) {
+  lock!(data_access.state)
+    .global_options_mut()
+    .set_mouse(value);
+}
+
+fn mouse(
+  data_access: &StatefulDataAccess,
+  stateful: StatefulValue,
+  kind: MouseEventKind,
+  column: u16,
+  row: u16,
+) -> StatefulValue {
+  let mouse_event = MouseEvent {
+    kind,
+    column,
+    row,
+    modifiers: KeyModifiers::NONE,
+  };
+  handle(
+    &data_access.with_event(Event::Mouse(mouse_event)),
+    stateful,
+    mouse_event,
+  )
+}
+
+fn click(
+  data_access: &StatefulDataAccess,
+  stateful: StatefulValue,
+  column: u16,
+  row: u16,
+) -> StatefulValue {
+  let kind = MouseEventKind::Down(MouseButton::Left);
+  mouse(data_access, stateful, kind, column, row)
+}
+
+fn start_line(data_access: &StatefulDataAccess) -> usize {
+  let tree = lock!(data_access.tree);
+  tree.current_window().unwrap().viewport().start_line_idx()
+}
+
+#[test]
+fn click_count1() {
+  let now = Instant::now();
+  let click1 = MouseClick::next(None, 1, 1, now);
+  assert_eq!(click1.count(), 1);
+  let click2 = MouseClick::next(Some(click1), 1, 1, now);
+  assert_eq!(click2.count(), 2);
+  let click3 = MouseClick::next(Some(click2), 1, 1, now);
+  assert_eq!(click3.count(), 3);
+
+  // Another position, or too late.
+  assert_eq!(MouseClick::next(Some(click3), 2, 1, now).count(), 1);
+  let later = now + MULTI_CLICK_TIME + Duration::from_millis(1);
+  assert_eq!(MouseClick::next(Some(click3), 1, 1, later).count(), 1);
+}
+
+#[test]
+fn enabled1() {
+  test_log_init();
+  let (_buf, _contents, data_access) =
+    make_data_access(U16Size::new(10, 5), vec!["hello\n"]);
+  let normal = StatefulValue::default();
+  let visual = StatefulValue::VisualMode(VisualStateful::default());
+  let state = data_access.state.clone();
+
+  assert!(!is_enabled(lock!(state).global_options(), &normal));
+  set_mouse(&data_access, "n");
+  assert!(is_enabled(lock!(state).global_options(), &normal));
+  assert!(!is_enabled(lock!(state).global_options(), &visual));
+  set_mouse(&data_access, "a");
+  assert!(is_enabled(lock!(state).global_options(), &visual));
+
+  // The mouse events are ignored when disabled.
+  set_mouse(&data_access, "v");
+  let stateful = click(&data_access, normal, 3, 0);
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(cursor(&data_access), (0, 0));
+}
+
+#[test]
+fn click1() {
+  test_log_init();
+  let (_buf, _contents, data_access) =
+    make_data_access(U16Size::new(10, 5), vec!["hello\n", "\tab\n", "world"]);
+  set_mouse(&data_access, "a");
+
+  let stateful = click(&data_access, StatefulValue::default(), 3, 2);
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(cursor(&data_access), (2, 3));
+
+  // The tab uses 8 columns.
+  let stateful = click(&data_access, stateful, 3, 1);
+  assert_eq!(cursor(&data_access), (1, 0));
+  let stateful = click(&data_access, stateful, 8, 1);
+  assert_eq!(cursor(&data_access), (1, 1));
+
+  // After the end of line, below the last line.
+  let stateful = click(&data_access, stateful, 8, 0);
+  assert_eq!(cursor(&data_access), (0, 4));
+  click(&data_access, stateful, 1, 3);
+  assert_eq!(cursor(&data_access), (2, 1));
+}
+
+#[test]
+fn click_insert1() {
+  test_log_init();
+  let (_buf, _contents, data_access) =
+    make_data_access(U16Size::new(10, 5), vec!["hello\n", "world\n"]);
+  set_mouse(&data_access, "a");
+
+  let stateful = press(&data_access, chars("i"));
+  let stateful = click(&data_access, stateful, 8, 1);
+  assert!(matches!(stateful, StatefulValue::InsertMode(_)));
+  assert_eq!(cursor(&data_access), (1, 5));
+}
+
+#[test]
+fn multi_click1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello world\n"]);
+  set_mouse(&data_access, "a");
+
+  let stateful = click(&data_access, StatefulValue::default(), 7, 0);
+  let stateful = click(&data_access, stateful, 7, 0);
+  assert!(matches!(stateful, StatefulValue::VisualMode(_)));
+  assert_eq!(
+    selection(&buf),
+    Some(Selection::new(SelectionKind::Char, (0, 6), (0, 10)))
+  );
+
+  let stateful = click(&data_access, stateful, 7, 0);
+  assert!(matches!(stateful, StatefulValue::VisualMode(_)));
+  assert_eq!(
+    selection(&buf),
+    Some(Selection::new(SelectionKind::Line, (0, 7), (0, 7)))
+  );
+
+  // Another click stops the selection.
+  let stateful = click(&data_access, stateful, 1, 0);
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(selection(&buf), None);
+  assert_eq!(cursor(&data_access), (0, 1));
+
+  // Select mode.
+  lock!(data_access.state)
+    .global_options_mut()
+    .set_select_mode("mouse");
+  let stateful = click(&data_access, stateful, 1, 0);
+  assert!(matches!(stateful, StatefulValue::SelectMode(_)));
+  assert_eq!(
+    selection(&buf),
+    Some(Selection::new(SelectionKind::Char, (0, 0), (0, 4)))
+  );
+}
+
+#[test]
+fn drag1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(10, 5), vec!["hello\n", "world"]);
+  set_mouse(&data_access, "a");
+
+  let stateful = click(&data_access, StatefulValue::default(), 1, 0);
+  let drag = MouseEventKind::Drag(MouseButton::Left);
+  let stateful = mouse(&data_access, stateful, drag, 2, 0);
+  assert!(matches!(stateful, StatefulValue::VisualMode(_)));
+  let stateful = mouse(&data_access, stateful, drag, 3, 1);
+  assert!(matches!(stateful, StatefulValue::VisualMode(_)));
+  assert_eq!(
+    selection(&buf),
+    Some(Selection::new(SelectionKind::Char, (0, 1), (1, 3)))
+  );
+
+  // Outside of the window.
+  mouse(&data_access, stateful, drag, 3, 4);
+  assert_eq!(cursor(&data_access), (1, 3));
+}
+
+#[test]
+fn scroll1() {
+  test_log_init();
+  let lines: Vec<String> = (0..20).map(|i| format!("line{i}\n")).collect();
+  let (_buf, _contents, data_access) = make_data_access(
+    U16Size::new(10, 5),
+    lines.iter().map(|line| line.as_str()).collect(),
+  );
+  set_mouse(&data_access, "a");
+
+  let stateful = press(&data_access, chars("ll"));
+  mouse(&data_access, stateful, MouseEventKind::ScrollDown, 0, 0);
+  assert_eq!(start_line(&data_access), SCROLL_LINES);
+  assert_eq!(cursor(&data_access), (SCROLL_LINES, 2));
+
+  // The cursor is still shown.
+  let stateful = press(&data_access, chars("j"));
+  assert_eq!(cursor(&data_access), (SCROLL_LINES + 1, 2));
+  mouse(&data_access, stateful, MouseEventKind::ScrollUp, 0, 0);
+  assert_eq!(start_line(&data_access), 0);
+  assert_eq!(cursor(&data_access), (3, 2));
+}
+
+#[test]
+fn focus_window1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(10, 5), vec!["hello\n"]);
+  let mut tree = lock!(data_access.tree);
+  let window = Window::new(
+    tree.global_local_options(),
+    IRect::new((0, 0), (10, 2)),
+    Arc::downgrade(&buf),
+  );
+  let window_id = window.id();
+  let root_id = tree.root_id();
+  tree.bounded_insert(root_id, TreeNode::Window(window));
+  let current_window_id = tree.current_window_id().unwrap();
+  tree.take_fired();
+
+  focus_window(&mut tree, window_id);
+  assert_eq!(tree.current_window_id(), Some(window_id));
+  // Both windows show the same buffer.
+  let buf_id = lock!(buf).id();
+  assert_eq!(
+    tree
+      .take_fired()
+      .iter()
+      .map(|fired| (fired.event, fired.buffer_id))
+      .collect::<Vec<_>>(),
+    vec![
+      (AutoCmdEvent::WinLeave, Some(buf_id)),
+      (AutoCmdEvent::WinEnter, Some(buf_id)),
+    ]
+  );
+  assert!(tree.current_window().unwrap().cursor_id().is_some());
+  assert!(
+    tree
+      .window(current_window_id)
+      .unwrap()
+      .cursor_id()
+      .is_none()
+  );
+}
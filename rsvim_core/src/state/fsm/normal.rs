@@ -1,77 +1,237 @@
 //! The normal mode.
 
+use crate::buf::selection::{Selection, SelectionKind};
+use crate::content::message::MessageLevel;
+use crate::excommand::quit::QuitKind;
+use crate::js::msg::{EventLoopToJsRuntimeMessage, RepeatReq};
+use crate::js::next_future_id;
 use crate::prelude::*;
+use crate::state::fsm::command_line_edit;
+use crate::state::fsm::insert;
+use crate::state::fsm::motion;
+use crate::state::fsm::pending::{Feed, PendingKeys, PrefixKey};
 use crate::state::fsm::quit::QuitStateful;
-use crate::state::fsm::{Stateful, StatefulDataAccess, StatefulValue};
-use crate::state::ops::cursor_ops;
+use crate::state::fsm::select;
+use crate::state::fsm::{
+  OperatorPendingStateful, ReplaceStateful, SelectStateful, Stateful,
+  StatefulDataAccess, StatefulValue, TerminalStateful, VisualStateful,
+  feed_keys, play_keys,
+};
+use crate::state::keymap::{Command, KeymapMatch, KeymapTarget};
+use crate::state::mode::Mode;
 use crate::state::ops::{GotoInsertModeVariant, Operation};
+use crate::state::ops::{cursor_ops, motion_ops, replace_ops};
+use crate::state::repeat::{RepeatKind, RepeatableChange};
+use crate::state::{keys, macros};
 use crate::ui::canvas::CursorStyle;
 use crate::ui::tree::*;
 use crate::ui::widget::command_line::CommandLineIndicatorSymbol;
-use crate::ui::widget::window::WindowNode;
 
-use compact_str::CompactString;
-use crossterm::event::{Event, KeyCode, KeyEventKind};
+use compact_str::{CompactString, ToCompactString};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use tracing::trace;
 
 #[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
 /// The finite-state-machine for normal mode.
-pub struct NormalStateful {}
+pub struct NormalStateful {
+  // The register, count and prefix key typed before the command, i.e. `"a3` in `"a3yy`.
+  pending: PendingKeys,
+}
+
+// The prefix key of the pressed key in normal mode, i.e. `g` of `gg`, or the builtin command that
+// waits for the argument, i.e. `f` of `fx`.
+fn _get_prefix(
+  data_access: &StatefulDataAccess,
+  key_event: &KeyEvent,
+) -> Option<PrefixKey> {
+  let keys = [*key_event];
+  let state = lock!(data_access.state);
+  match state.keymaps().lookup_command(Mode::Normal, &keys) {
+    KeymapMatch::Exact(keymap) => match keymap.target() {
+      KeymapTarget::Command(Command::Argument(prefix)) => Some(*prefix),
+      _ => None,
+    },
+    KeymapMatch::Prefix | KeymapMatch::ExactPrefix(_) => {
+      PrefixKey::from_key_event(key_event)
+    }
+    KeymapMatch::None => None,
+  }
+}
 
 impl NormalStateful {
-  fn get_operation(&self, event: Event) -> Option<Operation> {
+  /// The pending keys, i.e. the register and count passed to operator-pending mode.
+  pub fn pending(&self) -> PendingKeys {
+    self.pending
+  }
+
+  fn get_operation(
+    &self,
+    data_access: &StatefulDataAccess,
+    event: Event,
+  ) -> Option<Operation> {
     match event {
       Event::FocusGained => None,
       Event::FocusLost => None,
       Event::Key(key_event) => match key_event.kind {
         KeyEventKind::Press => {
           trace!("Event::key:{:?}", key_event);
-          match key_event.code {
-            KeyCode::Up | KeyCode::Char('k') => {
-              Some(Operation::CursorMoveUpBy(1))
-            }
-            KeyCode::Down | KeyCode::Char('j') => {
-              Some(Operation::CursorMoveDownBy(1))
+          let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+          let count = self.pending.count();
+          // The argument of the builtin command, i.e. the char of `fx`.
+          match self.pending.prefix() {
+            Some(PrefixKey::G | PrefixKey::Z | PrefixKey::UpperZ) | None => {}
+            // The ctrl key is the same, i.e. `<C-w><C-v>` is `<C-w>v`.
+            Some(PrefixKey::CtrlW) => {
+              return match key_event.code {
+                KeyCode::Char(c) => Some(Operation::WindowCommand(c)),
+                _ => None,
+              };
             }
-            KeyCode::Left | KeyCode::Char('h') => {
-              Some(Operation::CursorMoveLeftBy(1))
+            Some(_) if ctrl => return None,
+            Some(PrefixKey::CharFind(kind)) => {
+              return match key_event.code {
+                KeyCode::Char(c) => {
+                  Some(motion::char_find_motion(data_access, kind, c, count))
+                }
+                _ => None,
+              };
             }
-            KeyCode::Right | KeyCode::Char('l') => {
-              Some(Operation::CursorMoveRightBy(1))
+            Some(PrefixKey::Q) => {
+              return match key_event.code {
+                KeyCode::Char(c) if macros::is_macro_register(c) => {
+                  Some(Operation::MacroStartRecording(c))
+                }
+                _ => None,
+              };
             }
-            KeyCode::Home => Some(Operation::CursorMoveLeftBy(usize::MAX)),
-            KeyCode::End => Some(Operation::CursorMoveRightBy(usize::MAX)),
-            KeyCode::Char('i') => {
-              Some(Operation::GotoInsertMode(GotoInsertModeVariant::Keep))
+            Some(PrefixKey::At) => {
+              return match key_event.code {
+                KeyCode::Char(c)
+                  if c == '@' || macros::is_macro_register(c) =>
+                {
+                  Some(Operation::MacroPlay(c))
+                }
+                _ => None,
+              };
             }
-            KeyCode::Char('a') => {
-              Some(Operation::GotoInsertMode(GotoInsertModeVariant::Append))
+            Some(PrefixKey::R) => {
+              return match key_event.code {
+                KeyCode::Char(c) => {
+                  Some(Operation::ReplaceChar(c.to_compact_string()))
+                }
+                KeyCode::Tab => Some(Operation::ReplaceChar("\t".into())),
+                KeyCode::Enter => {
+                  let tree = data_access.tree.clone();
+                  let tree = lock!(tree);
+                  let current_window = tree.current_window().unwrap();
+                  let buffer = current_window.buffer().upgrade().unwrap();
+                  let eol =
+                    format!("{}", lock!(buffer).options().end_of_line());
+                  Some(Operation::ReplaceChar(eol.to_compact_string()))
+                }
+                _ => None,
+              };
             }
-            KeyCode::Char('o') => {
-              Some(Operation::GotoInsertMode(GotoInsertModeVariant::NewLine))
-            }
-            KeyCode::Char(':') => Some(Operation::GotoCommandLineExMode),
-            // KeyCode::Char('/') => Some(Operation::GotoCommandLineSearchForwardMode),
-            // KeyCode::Char('?') => Some(Operation::GotoCommandLineSearchBackwardMode),
-            KeyCode::Esc => Some(Operation::EditorQuit),
-            _ => None,
+            Some(PrefixKey::Object(_)) => unreachable!(),
           }
+
+          // The builtin command of the keys, i.e. `i`, `gg`, `zz`.
+          let mut keys: Vec<KeyEvent> = self
+            .pending
+            .prefix()
+            .map(|p| p.key_event())
+            .into_iter()
+            .collect();
+          keys.push(key_event);
+          let command = lock!(data_access.state)
+            .keymaps()
+            .command(Mode::Normal, &keys)?;
+          self.get_command_operation(data_access, command)
         }
         KeyEventKind::Repeat => None,
         KeyEventKind::Release => None,
       },
       Event::Mouse(_mouse_event) => None,
-      Event::Paste(ref _paste_string) => None,
+      // The pasted text is put after cursor like `p`, it bypasses the mappings.
+      Event::Paste(ref paste_string) => {
+        let payload = insert::pasted_text(data_access, paste_string);
+        if payload.is_empty() {
+          None
+        } else {
+          Some(Operation::CursorPutAfter(payload))
+        }
+      }
       Event::Resize(_columns, _rows) => None,
     }
   }
+
+  // The operation of the builtin command with the typed count.
+  fn get_command_operation(
+    &self,
+    data_access: &StatefulDataAccess,
+    command: Command,
+  ) -> Option<Operation> {
+    let count = self.pending.count();
+    match command {
+      Command::Operation(op) => Some(op),
+      Command::Motion(motion) => {
+        motion::motion_operation(data_access, motion, count)
+      }
+      // The count is the line number.
+      Command::ScrollCursorLine(position, first_non_blank) => {
+        let line_idx = count.map(|n| n.saturating_sub(1));
+        Some(Operation::WindowScrollCursorLineTo((
+          position,
+          first_non_blank,
+          line_idx,
+        )))
+      }
+      // The argument is not typed yet.
+      Command::Argument(_) => None,
+    }
+  }
 }
 
 impl Stateful for NormalStateful {
   fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
     let event = data_access.event.clone();
 
-    if let Some(op) = self.get_operation(event) {
+    if let Event::Key(key_event) = &event {
+      if key_event.kind == KeyEventKind::Press {
+        // The `q` stops recording the macro.
+        let recording = lock!(data_access.state).macros().recording().is_some();
+        if recording
+          && self.pending.is_empty()
+          && key_event.code == KeyCode::Char('q')
+          && key_event.modifiers.is_empty()
+        {
+          return self.handle_op(data_access, Operation::MacroStopRecording);
+        }
+
+        // The shifted special keys start the selection, see the 'keymodel' option.
+        if self.pending.is_empty() {
+          if let Some(stateful) =
+            select::start_selection_by_key(&data_access, key_event)
+          {
+            return stateful;
+          }
+        }
+
+        match self.pending.feed(key_event, true, |key_event| {
+          _get_prefix(&data_access, key_event)
+        }) {
+          Feed::Pending(pending) => {
+            return StatefulValue::NormalMode(NormalStateful { pending });
+          }
+          Feed::Cancel => {
+            return StatefulValue::NormalMode(NormalStateful::default());
+          }
+          Feed::Complete => {}
+        }
+      }
+    }
+
+    if let Some(op) = self.get_operation(&data_access, event) {
       return self.handle_op(data_access, op);
     }
 
@@ -85,68 +245,219 @@ impl Stateful for NormalStateful {
   ) -> StatefulValue {
     match op {
       Operation::GotoInsertMode(insert_motion) => {
+        // The terminal buffer is not editable, the keys are sent to the program instead.
+        if let Some(stateful) = self.goto_terminal_mode(&data_access) {
+          return stateful;
+        }
+        // The inserted text is recorded until going back to normal mode.
+        if let Event::Key(key_event) = data_access.event {
+          lock!(data_access.state).dot_repeat_mut().start(
+            RepeatableChange::new(
+              RepeatKind::Normal,
+              None,
+              None,
+              vec![key_event],
+            ),
+          );
+        }
         self.goto_insert_mode(&data_access, insert_motion)
       }
+      Operation::GotoReplaceMode(virtual_replace) => {
+        // The typed text is recorded until going back to normal mode.
+        if let Event::Key(key_event) = data_access.event {
+          let mut keys = vec![key_event];
+          if virtual_replace {
+            keys.insert(0, PrefixKey::G.key_event());
+          }
+          lock!(data_access.state)
+            .dot_repeat_mut()
+            .start(RepeatableChange::new(RepeatKind::Normal, None, None, keys));
+        }
+        ReplaceStateful::goto_replace_mode(&data_access, virtual_replace)
+      }
+      Operation::CursorPutAfter(payload) => {
+        self.cursor_put_after(&data_access, payload)
+      }
+      Operation::ReplaceChar(payload) => {
+        self.replace_char(&data_access, payload)
+      }
       Operation::GotoCommandLineExMode => {
         self.goto_command_line_ex_mode(&data_access)
       }
-      // Operation::GotoCommandLineSearchForwardMode => {
-      //   self.goto_command_line_search_forward_mode(&data_access)
-      // }
-      // Operation::GotoCommandLineSearchBackwardMode => {
-      //   self.goto_command_line_search_backward_mode(&data_access)
-      // }
-      Operation::EditorQuit => self.editor_quit(&data_access),
+      Operation::GotoOperatorPendingMode(operator) => {
+        StatefulValue::OperatorPendingMode(OperatorPendingStateful::new(
+          operator,
+          PendingKeys::new(self.pending.register(), self.pending.count()),
+        ))
+      }
+      Operation::GotoVisualMode(kind) => {
+        // The `v`, `V` and `<C-v>` start select mode instead, see the 'selectmode' option.
+        let select = {
+          let state = data_access.state.clone();
+          let state = lock!(state);
+          state.global_options().select_mode_has("cmd")
+        };
+        if select {
+          SelectStateful::goto_select_mode(&data_access, kind)
+        } else {
+          self.goto_visual_mode(&data_access, kind)
+        }
+      }
+      Operation::GotoSelectMode(kind) => {
+        SelectStateful::goto_select_mode(&data_access, kind)
+      }
+      Operation::VisualReselect => self.visual_reselect(&data_access),
+      Operation::RepeatLastChange => self.repeat_last_change(&data_access),
+      Operation::MacroStartRecording(name) => {
+        lock!(data_access.state).macros_mut().start_recording(name);
+        StatefulValue::NormalMode(NormalStateful::default())
+      }
+      Operation::MacroStopRecording => self.stop_recording(&data_access),
+      Operation::MacroPlay(name) => self.play_macro(&data_access, name),
+      Operation::GotoCommandLineSearchForwardMode => {
+        self.goto_command_line_search_forward_mode(&data_access)
+      }
+      Operation::GotoCommandLineSearchBackwardMode => {
+        self.goto_command_line_search_backward_mode(&data_access)
+      }
+      Operation::WindowCommand(c) => self.window_command(&data_access, c),
+      Operation::EditorQuit(kind, bang) => {
+        QuitStateful::quit(&data_access, kind, bang, false)
+      }
       Operation::CursorMoveBy((_, _))
       | Operation::CursorMoveUpBy(_)
       | Operation::CursorMoveDownBy(_)
       | Operation::CursorMoveLeftBy(_)
       | Operation::CursorMoveRightBy(_)
       | Operation::CursorMoveTo((_, _)) => self.cursor_move(&data_access, op),
+      op if motion_ops::is_motion(&op) => self.cursor_move(&data_access, op),
+      Operation::WindowScrollCursorLineTo(_) => {
+        self.scroll_cursor_line(&data_access, op)
+      }
       _ => unreachable!(),
     }
   }
+
+  fn handle_timeout(&self) -> Option<StatefulValue> {
+    if self.pending.is_empty() {
+      None
+    } else {
+      Some(StatefulValue::NormalMode(NormalStateful::default()))
+    }
+  }
+
+  fn showcmd(&self) -> String {
+    self.pending.showcmd()
+  }
+
+  fn is_literal_pending(&self) -> bool {
+    self.pending.is_literal_pending()
+  }
 }
 
 impl NormalStateful {
   pub fn goto_command_line_ex_mode(
     &self,
     data_access: &StatefulDataAccess,
+  ) -> StatefulValue {
+    command_line_edit::enter_command_line(
+      data_access,
+      CommandLineIndicatorSymbol::Ex,
+    );
+    StatefulValue::CommandLineExMode(super::CommandLineExStateful::default())
+  }
+}
+
+impl NormalStateful {
+  /// Start the selection at cursor and goto visual mode.
+  pub fn goto_visual_mode(
+    &self,
+    data_access: &StatefulDataAccess,
+    kind: SelectionKind,
   ) -> StatefulValue {
     let tree = data_access.tree.clone();
-    let mut tree = lock!(tree);
+    let tree = lock!(tree);
+    let current_window = tree.current_window().unwrap();
+    let cursor_viewport = current_window.cursor_viewport();
+    let buffer = current_window.buffer().upgrade().unwrap();
+    let position = (cursor_viewport.line_idx(), cursor_viewport.char_idx());
+    lock!(buffer).set_selection(Some(Selection::new(kind, position, position)));
+
+    StatefulValue::VisualMode(super::VisualStateful::default())
+  }
 
-    // Remove cursor from current window
+  /// Restore the last selection and goto visual mode, i.e. `gv`.
+  pub fn visual_reselect(
+    &self,
+    data_access: &StatefulDataAccess,
+  ) -> StatefulValue {
+    let tree = data_access.tree.clone();
+    let mut tree = lock!(tree);
     let current_window = tree.current_window_mut().unwrap();
-    debug_assert!(current_window.cursor_id().is_some());
-    let cursor = match current_window.remove_cursor().unwrap() {
-      WindowNode::Cursor(mut cursor) => {
-        cursor.set_style(&CursorStyle::SteadyBar);
-        cursor
-      }
-      _ => unreachable!(),
+    let current_window_id = current_window.id();
+    let buffer = current_window.buffer().upgrade().unwrap();
+    let mut buffer = lock!(buffer);
+
+    let selection = match buffer.last_selection() {
+      Some(selection) => selection.clamp(buffer.text()),
+      None => return StatefulValue::NormalMode(NormalStateful::default()),
     };
-    debug_assert!(current_window.cursor_id().is_none());
-
-    // Insert to command-line
-    debug_assert!(tree.command_line_mut().is_some());
-    let cmdline = tree.command_line_mut().unwrap();
-    let _previous_cursor = cmdline.insert_cursor(cursor);
-    debug_assert!(_previous_cursor.is_none());
-    cmdline.move_cursor_to(0, 0);
-    cmdline
-      .indicator_mut()
-      .set_symbol(CommandLineIndicatorSymbol::Ex);
+    buffer.set_selection(Some(selection));
+    let (line_idx, char_idx) = selection.cursor();
+    cursor_ops::cursor_move(
+      &mut tree,
+      current_window_id,
+      buffer.text(),
+      Operation::CursorMoveTo((char_idx, line_idx)),
+      false,
+    );
 
-    StatefulValue::CommandLineExMode(super::CommandLineExStateful::default())
+    StatefulValue::VisualMode(super::VisualStateful::default())
   }
 }
 
 impl NormalStateful {
-  fn _goto_command_line_search_forward_mode(
+  /// Run the window command, i.e. `<C-w>v`. There's always only one window since the window split
+  /// is not supported yet, so the commands that move between the windows do nothing, and the
+  /// commands that create a new window report an error.
+  fn window_command(
     &self,
-    _data_access: &StatefulDataAccess,
+    data_access: &StatefulDataAccess,
+    c: char,
   ) -> StatefulValue {
+    let message = match c {
+      'q' => {
+        return QuitStateful::quit(data_access, QuitKind::Quit, false, false);
+      }
+      'w' | 'W' | 'p' | 'h' | 'j' | 'k' | 'l' | 't' | 'b' | 'x' | 'r' | 'R'
+      | '=' => None,
+      'c' => Some((
+        MessageLevel::Error,
+        "E444: Cannot close last window".to_string(),
+      )),
+      'o' => Some((MessageLevel::Info, "Already only one window".to_string())),
+      _ => Some((
+        MessageLevel::Error,
+        format!("Window command \"<C-w>{c}\" is not supported yet"),
+      )),
+    };
+    if let Some((level, text)) = message {
+      let contents = data_access.contents.clone();
+      lock!(contents).messages_mut().add(level, &text);
+    }
+    StatefulValue::NormalMode(NormalStateful::default())
+  }
+}
+
+impl NormalStateful {
+  fn goto_command_line_search_forward_mode(
+    &self,
+    data_access: &StatefulDataAccess,
+  ) -> StatefulValue {
+    command_line_edit::enter_command_line(
+      data_access,
+      CommandLineIndicatorSymbol::SearchForward,
+    );
     StatefulValue::CommandLineSearchForwardMode(
       super::CommandLineSearchForwardStateful::default(),
     )
@@ -154,10 +465,14 @@ impl NormalStateful {
 }
 
 impl NormalStateful {
-  fn _goto_command_line_search_backward_mode(
+  fn goto_command_line_search_backward_mode(
     &self,
-    _data_access: &StatefulDataAccess,
+    data_access: &StatefulDataAccess,
   ) -> StatefulValue {
+    command_line_edit::enter_command_line(
+      data_access,
+      CommandLineIndicatorSymbol::SearchBackard,
+    );
     StatefulValue::CommandLineSearchBackwardMode(
       super::CommandLineSearchBackwardStateful::default(),
     )
@@ -165,6 +480,252 @@ impl NormalStateful {
 }
 
 impl NormalStateful {
+  /// Repeat the last change, i.e. `.`. The count replaces the count of the last change, and it is
+  /// used by the following `.`.
+  pub fn repeat_last_change(
+    &self,
+    data_access: &StatefulDataAccess,
+  ) -> StatefulValue {
+    let change = lock!(data_access.state).dot_repeat().last_change().cloned();
+    let mut change = match change {
+      Some(change) => change,
+      None => return StatefulValue::NormalMode(NormalStateful::default()),
+    };
+    if let Some(count) = self.pending.count() {
+      change.count = Some(count);
+    }
+
+    let stateful = match change.kind {
+      RepeatKind::Normal => {
+        StatefulValue::NormalMode(NormalStateful::default())
+      }
+      RepeatKind::Visual(region) => {
+        // Select the region with the same size from the cursor.
+        let tree = data_access.tree.clone();
+        let tree = lock!(tree);
+        let current_window = tree.current_window().unwrap();
+        let cursor_viewport = current_window.cursor_viewport();
+        let buffer = current_window.buffer().upgrade().unwrap();
+        let mut buffer = lock!(buffer);
+        let selection = region
+          .to_selection((
+            cursor_viewport.line_idx(),
+            cursor_viewport.char_idx(),
+          ))
+          .clamp(buffer.text());
+        buffer.set_selection(Some(selection));
+        StatefulValue::VisualMode(VisualStateful::default())
+      }
+      RepeatKind::Js => {
+        let count = change.count;
+        let jsrt_tick_dispatcher =
+          lock!(data_access.state).jsrt_tick_dispatcher().clone();
+        let current_handle = tokio::runtime::Handle::current();
+        current_handle.spawn_blocking(move || {
+          jsrt_tick_dispatcher
+            .blocking_send(EventLoopToJsRuntimeMessage::RepeatReq(
+              RepeatReq::new(next_future_id(), count),
+            ))
+            .unwrap();
+        });
+        lock!(data_access.state)
+          .dot_repeat_mut()
+          .set_last_change(change);
+        return StatefulValue::NormalMode(NormalStateful::default());
+      }
+    };
+
+    lock!(data_access.state)
+      .dot_repeat_mut()
+      .set_replaying(true);
+    let next = feed_keys(data_access, stateful, &change.replay_keys());
+    let mut state = lock!(data_access.state);
+    let dot_repeat = state.dot_repeat_mut();
+    dot_repeat.set_replaying(false);
+    dot_repeat.set_last_change(change);
+    next
+  }
+}
+
+impl NormalStateful {
+  /// Replace the chars at cursor with the payload, i.e. `3rx`, the count is the number of chars.
+  /// It fails if there're not enough chars after the cursor.
+  pub fn replace_char(
+    &self,
+    data_access: &StatefulDataAccess,
+    payload: CompactString,
+  ) -> StatefulValue {
+    let count = self.pending.count();
+    let replaced = {
+      let tree = data_access.tree.clone();
+      let mut tree = lock!(tree);
+      let current_window = tree.current_window_mut().unwrap();
+      let current_window_id = current_window.id();
+      let cursor_viewport = current_window.cursor_viewport();
+      let buffer = current_window.buffer().upgrade().unwrap();
+      let mut buffer = lock!(buffer);
+
+      let replaced = replace_ops::replace_chars(
+        buffer.text_mut(),
+        (cursor_viewport.line_idx(), cursor_viewport.char_idx()),
+        count.unwrap_or(1),
+        &payload,
+      );
+      if let Some((line_idx, char_idx)) = replaced {
+        cursor_ops::_update_viewport_after_text_changed(
+          &mut tree,
+          current_window_id,
+          buffer.text(),
+        );
+        cursor_ops::cursor_move(
+          &mut tree,
+          current_window_id,
+          buffer.text(),
+          Operation::CursorMoveTo((char_idx, line_idx)),
+          false,
+        );
+      }
+      replaced.is_some()
+    };
+
+    let mut state = lock!(data_access.state);
+    if !replaced {
+      state.macros_mut().fail();
+    } else if let Event::Key(key_event) = data_access.event {
+      state
+        .dot_repeat_mut()
+        .set_last_change(RepeatableChange::new(
+          RepeatKind::Normal,
+          None,
+          count,
+          vec![PrefixKey::R.key_event(), key_event],
+        ));
+    }
+    StatefulValue::NormalMode(NormalStateful::default())
+  }
+}
+
+impl NormalStateful {
+  /// Put the text after cursor as a single change, the cursor moves to the last put char.
+  pub fn cursor_put_after(
+    &self,
+    data_access: &StatefulDataAccess,
+    payload: CompactString,
+  ) -> StatefulValue {
+    let tree = data_access.tree.clone();
+    let mut tree = lock!(tree);
+    let current_window = tree.current_window_mut().unwrap();
+    let current_window_id = current_window.id();
+    let cursor_viewport = current_window.cursor_viewport();
+    let buffer = current_window.buffer().upgrade().unwrap();
+    let mut buffer = lock!(buffer);
+
+    // Put after the cursor char, or at the start of an empty line.
+    let line_idx = cursor_viewport.line_idx();
+    let char_idx = match buffer.text().last_char_on_line_no_eol(line_idx) {
+      Some(_) => cursor_viewport.char_idx() + 1,
+      None => 0,
+    };
+    let (line_idx, char_idx) =
+      buffer.text_mut().insert_at(line_idx, char_idx, payload);
+
+    cursor_ops::_update_viewport_after_text_changed(
+      &mut tree,
+      current_window_id,
+      buffer.text(),
+    );
+    cursor_ops::cursor_move(
+      &mut tree,
+      current_window_id,
+      buffer.text(),
+      Operation::CursorMoveTo((char_idx.saturating_sub(1), line_idx)),
+      false,
+    );
+    StatefulValue::NormalMode(NormalStateful::default())
+  }
+}
+
+impl NormalStateful {
+  /// Stop recording the macro, and save the recorded keys to the register as text.
+  pub fn stop_recording(
+    &self,
+    data_access: &StatefulDataAccess,
+  ) -> StatefulValue {
+    let recorded = lock!(data_access.state).macros_mut().stop_recording();
+    if let Some((name, recorded_keys)) = recorded {
+      let contents = data_access.contents.clone();
+      lock!(contents)
+        .registers_mut()
+        .set(name, &keys::to_notations(&recorded_keys));
+    }
+    StatefulValue::NormalMode(NormalStateful::default())
+  }
+
+  /// Play the keys in the register `name` count times, the `@` is the last played register.
+  pub fn play_macro(
+    &self,
+    data_access: &StatefulDataAccess,
+    name: char,
+  ) -> StatefulValue {
+    let name = if name == '@' {
+      lock!(data_access.state).macros().last_played()
+    } else {
+      Some(name)
+    };
+    let text = name.and_then(|name| {
+      let contents = data_access.contents.clone();
+      let contents = lock!(contents);
+      contents.registers().get(name).cloned()
+    });
+    let (name, text) = match (name, text) {
+      (Some(name), Some(text)) => (name, text),
+      _ => {
+        lock!(data_access.state).macros_mut().fail();
+        return StatefulValue::NormalMode(NormalStateful::default());
+      }
+    };
+
+    lock!(data_access.state).macros_mut().set_last_played(name);
+    play_keys(
+      data_access,
+      StatefulValue::NormalMode(NormalStateful::default()),
+      &keys::parse(&text),
+      self.pending.count().unwrap_or(1),
+      true,
+    )
+  }
+}
+
+impl NormalStateful {
+  /// Goto terminal mode if current window shows a terminal buffer whose program is still
+  /// running, i.e. `i` and `a` in the terminal buffer. The cursor moves to the terminal cursor.
+  ///
+  /// Returns `None` if it is not a running terminal.
+  pub fn goto_terminal_mode(
+    &self,
+    data_access: &StatefulDataAccess,
+  ) -> Option<StatefulValue> {
+    let tree = data_access.tree.clone();
+    let mut tree = lock!(tree);
+    let current_window = tree.current_window().unwrap();
+    let current_window_id = current_window.id();
+    let buffer = current_window.buffer().upgrade().unwrap();
+    let buffer = lock!(buffer);
+    if !buffer.is_terminal_running() {
+      return None;
+    }
+
+    let (line_idx, char_idx) = buffer.terminal().as_ref().unwrap().cursor();
+    cursor_ops::cursor_move(
+      &mut tree,
+      current_window_id,
+      buffer.text(),
+      Operation::CursorMoveTo((char_idx, line_idx)),
+      true,
+    );
+    Some(StatefulValue::TerminalMode(TerminalStateful::default()))
+  }
+
   pub fn goto_insert_mode(
     &self,
     data_access: &StatefulDataAccess,
@@ -234,14 +795,44 @@ impl NormalStateful {
     let current_window_id = current_window.id();
     let buffer = current_window.buffer().upgrade().unwrap();
     let buffer = lock!(buffer);
+    let cursor_viewport = current_window.cursor_viewport();
+    let from = (cursor_viewport.line_idx(), cursor_viewport.char_idx());
 
     cursor_ops::cursor_move(
       &mut tree,
       current_window_id,
       buffer.text(),
-      op,
+      op.clone(),
       false,
     );
+
+    let cursor_viewport = tree.current_window().unwrap().cursor_viewport();
+    let to = (cursor_viewport.line_idx(), cursor_viewport.char_idx());
+    if motion::is_failed_motion(&op, from, to) {
+      lock!(data_access.state).macros_mut().fail();
+    }
+    StatefulValue::NormalMode(NormalStateful::default())
+  }
+
+  /// Scroll the cursor line to the top, middle or bottom of current window, i.e. `zt`, `zz`, `zb`.
+  pub fn scroll_cursor_line(
+    &self,
+    data_access: &StatefulDataAccess,
+    op: Operation,
+  ) -> StatefulValue {
+    let tree = data_access.tree.clone();
+    let mut tree = lock!(tree);
+    let current_window = tree.current_window_mut().unwrap();
+    let current_window_id = current_window.id();
+    let buffer = current_window.buffer().upgrade().unwrap();
+    let buffer = lock!(buffer);
+
+    cursor_ops::window_scroll_cursor_line_to(
+      &mut tree,
+      current_window_id,
+      buffer.text(),
+      op,
+    );
     StatefulValue::NormalMode(NormalStateful::default())
   }
 }
@@ -341,11 +932,4 @@ impl NormalStateful {
       current_window.set_viewport(new_viewport_arc.clone());
     }
   }
-
-  pub fn editor_quit(
-    &self,
-    _data_access: &StatefulDataAccess,
-  ) -> StatefulValue {
-    StatefulValue::QuitState(QuitStateful::default())
-  }
 }
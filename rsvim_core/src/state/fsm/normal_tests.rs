@@ -9149,3 +9149,505 @@ mod tests_goto_insert_mode {
     }
   }
 }
+
+#[cfg(test)]
+mod tests_motion {
+  use super::*;
+
+  use crate::test::fsm::{assert_cursor_after, make_data_access};
+
+  #[test]
+  fn word_line1() {
+    test_log_init();
+    let (_buf, _contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["foo bar\n", "\n", "  baz\n"]);
+
+    assert_cursor_after(&data_access, "w", (0, 4));
+    // The empty line is a word.
+    assert_cursor_after(&data_access, "w", (1, 0));
+    assert_cursor_after(&data_access, "w", (2, 2));
+    assert_cursor_after(&data_access, "b", (1, 0));
+    assert_cursor_after(&data_access, "G", (2, 2));
+    assert_cursor_after(&data_access, "gg", (0, 0));
+    assert_cursor_after(&data_access, "$", (0, 6));
+    assert_cursor_after(&data_access, "0", (0, 0));
+    assert_cursor_after(&data_access, "ll2w", (1, 0));
+    assert_cursor_after(&data_access, "3G", (2, 2));
+    assert_cursor_after(&data_access, "{", (1, 0));
+  }
+
+  #[test]
+  fn char_find1() {
+    test_log_init();
+    let (_buf, _contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["foo bar\n"]);
+
+    assert_cursor_after(&data_access, "fr", (0, 6));
+    assert_cursor_after(&data_access, "Fo", (0, 2));
+    assert_cursor_after(&data_access, ";", (0, 1));
+    assert_cursor_after(&data_access, ",", (0, 2));
+    // Not found.
+    assert_cursor_after(&data_access, "fz", (0, 2));
+  }
+}
+
+#[cfg(test)]
+mod tests_pending {
+  use super::*;
+
+  use crate::test::fsm::{
+    assert_cursor_after, chars, cursor, key, make_data_access, press, register,
+    text,
+  };
+
+  fn start_line_idx(data_access: &StatefulDataAccess) -> usize {
+    let tree = lock!(data_access.tree);
+    tree.current_window().unwrap().viewport().start_line_idx()
+  }
+
+  #[test]
+  fn register1() {
+    test_log_init();
+    let (buf, contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["foo\n", "bar\n", "baz\n"]);
+
+    press(&data_access, chars("\"ayy"));
+    assert_eq!(register(&contents, 'a').unwrap(), "foo\n");
+    assert_eq!(register(&contents, '"').unwrap(), "foo\n");
+    assert!(register(&contents, '0').is_none());
+
+    press(&data_access, chars("j\"Ayy"));
+    assert_eq!(register(&contents, 'a').unwrap(), "foo\nbar\n");
+
+    press(&data_access, chars("\"bdd"));
+    assert_eq!(text(&buf), "foo\nbaz\n");
+    assert_eq!(register(&contents, 'b').unwrap(), "bar\n");
+    assert!(register(&contents, '1').is_none());
+
+    // The black hole register.
+    press(&data_access, chars("\"_dd"));
+    assert_eq!(text(&buf), "foo\n");
+    assert_eq!(register(&contents, '"').unwrap(), "bar\n");
+
+    // The count after the register.
+    press(&data_access, chars("\"c2yl"));
+    assert_eq!(register(&contents, 'c').unwrap(), "fo");
+  }
+
+  #[test]
+  fn scroll1() {
+    test_log_init();
+    let lines = vec![
+      "0\n", "1\n", "2\n", "  3\n", "4\n", "5\n", "6\n", "7\n", "8\n", "9\n",
+    ];
+    let (_buf, _contents, data_access) =
+      make_data_access(U16Size::new(20, 5), lines);
+
+    assert_cursor_after(&data_access, "6G", (5, 0));
+    press(&data_access, chars("zt"));
+    assert_eq!(start_line_idx(&data_access), 5);
+    press(&data_access, chars("zz"));
+    assert_eq!(start_line_idx(&data_access), 4);
+    press(&data_access, chars("zb"));
+    assert_eq!(start_line_idx(&data_access), 2);
+    assert_eq!(cursor(&data_access), (5, 0));
+
+    // The count is the line number, `z<CR>` goes to the first non-blank char.
+    let mut events = chars("4z");
+    events.push(key(KeyCode::Enter));
+    press(&data_access, events);
+    assert_eq!(start_line_idx(&data_access), 3);
+    assert_eq!(cursor(&data_access), (3, 2));
+  }
+
+  #[test]
+  fn showcmd1() {
+    test_log_init();
+    let (_buf, _contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["foo\n"]);
+
+    assert_eq!(press(&data_access, chars("\"a3g")).showcmd(), "\"a3g");
+    assert_eq!(press(&data_access, chars("2d3")).showcmd(), "2d3");
+    assert_eq!(press(&data_access, chars("gUi")).showcmd(), "gUi");
+    assert_eq!(press(&data_access, chars("dvf")).showcmd(), "dvf");
+    assert_eq!(press(&data_access, chars("vg")).showcmd(), "g");
+    assert_eq!(press(&data_access, chars("3j")).showcmd(), "");
+
+    // Timeout drops the pending keys.
+    let stateful = press(&data_access, chars("2g"));
+    let stateful = stateful.handle_timeout().unwrap();
+    assert_eq!(
+      stateful,
+      StatefulValue::NormalMode(NormalStateful::default())
+    );
+    assert!(stateful.handle_timeout().is_none());
+    let stateful = press(&data_access, chars("d"));
+    assert!(matches!(
+      stateful.handle_timeout(),
+      Some(StatefulValue::NormalMode(_))
+    ));
+  }
+}
+
+#[cfg(test)]
+mod tests_repeat {
+  use super::*;
+
+  use crate::test::fsm::{
+    chars, cursor, make_data_access, press, register, text, with_keys,
+  };
+
+  #[test]
+  fn operator1() {
+    test_log_init();
+    let (buf, _contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["a b c d e f g\n"]);
+
+    press(&data_access, chars("dw"));
+    assert_eq!(text(&buf), "b c d e f g\n");
+    press(&data_access, chars("."));
+    assert_eq!(text(&buf), "c d e f g\n");
+
+    // The new count replaces the count, and it is used by the following `.`.
+    press(&data_access, chars("2."));
+    assert_eq!(text(&buf), "e f g\n");
+
+    // Yank is not a change.
+    press(&data_access, chars("yw."));
+    assert_eq!(text(&buf), "g\n");
+  }
+
+  #[test]
+  fn register1() {
+    test_log_init();
+    let (buf, contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["x\n", "y\n", "z\n"]);
+
+    press(&data_access, chars("\"add"));
+    assert_eq!(register(&contents, 'a').unwrap(), "x\n");
+    press(&data_access, chars("yy."));
+    assert_eq!(text(&buf), "z\n");
+    assert_eq!(register(&contents, 'a').unwrap(), "y\n");
+  }
+
+  #[test]
+  fn insert1() {
+    test_log_init();
+    let (buf, _contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["foo bar\n", "foo baz\n"]);
+
+    // The inserted text is repeated with the change.
+    press(&data_access, with_keys("ciwxy", &[KeyCode::Esc]));
+    assert_eq!(text(&buf), "xy bar\nfoo baz\n");
+    press(&data_access, chars("j0."));
+    assert_eq!(text(&buf), "xy bar\nxy baz\n");
+
+    let (buf, _contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["ab\n"]);
+    press(&data_access, with_keys("ix", &[KeyCode::Esc]));
+    assert_eq!(text(&buf), "xab\n");
+    assert_eq!(cursor(&data_access), (0, 1));
+    press(&data_access, chars("."));
+    assert_eq!(text(&buf), "xxab\n");
+
+    press(&data_access, with_keys("onew", &[KeyCode::Esc]));
+    press(&data_access, chars("."));
+    assert_eq!(text(&buf), "xxab\nnew\nnew\n");
+  }
+
+  #[test]
+  fn visual1() {
+    test_log_init();
+    let (buf, _contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["abcdef\n"]);
+
+    // The region with the same size is changed.
+    press(&data_access, chars("vld"));
+    assert_eq!(text(&buf), "cdef\n");
+    press(&data_access, chars("."));
+    assert_eq!(text(&buf), "ef\n");
+
+    let (buf, _contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["a\n", "b\n", "c\n", "d\n"]);
+    press(&data_access, chars("VjJ"));
+    assert_eq!(text(&buf), "a b\nc\nd\n");
+    press(&data_access, chars("."));
+    assert_eq!(text(&buf), "a b c\nd\n");
+  }
+}
+
+#[cfg(test)]
+mod tests_macro {
+  use super::*;
+
+  use crate::excommand::range::ExRange;
+  use crate::state::fsm::CommandLineExStateful;
+  use crate::test::fsm::{
+    chars, cursor, key, make_data_access, register, text,
+  };
+
+  // Send the events like the event loop, the typed keys are recorded into the macro.
+  fn record(data_access: &StatefulDataAccess, events: Vec<Event>) {
+    let mut stateful = StatefulValue::NormalMode(NormalStateful::default());
+    for event in events {
+      if let Event::Key(key_event) = &event {
+        lock!(data_access.state).macros_mut().push_key(*key_event);
+      }
+      stateful = stateful.handle(data_access.with_event(event));
+      lock!(data_access.state).update_state_machine(&stateful);
+    }
+  }
+
+  fn set_register(contents: &TextContentsArc, name: char, value: &str) {
+    lock!(contents).registers_mut().set(name, value);
+  }
+
+  #[test]
+  fn record1() {
+    test_log_init();
+    let (buf, contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["a b c d e f\n"]);
+
+    record(&data_access, chars("qa"));
+    assert_eq!(lock!(data_access.state).macros().recording(), Some('a'));
+    record(&data_access, chars("dwq"));
+    assert_eq!(lock!(data_access.state).macros().recording(), None);
+    assert_eq!(register(&contents, 'a').unwrap(), "dw");
+    assert_eq!(text(&buf), "b c d e f\n");
+
+    // The special keys are saved in key notation.
+    let mut events = chars("qbix");
+    events.push(key(KeyCode::Esc));
+    events.extend(chars("q"));
+    record(&data_access, events);
+    assert_eq!(register(&contents, 'b').unwrap(), "ix<Esc>");
+    assert_eq!(text(&buf), "xb c d e f\n");
+  }
+
+  #[test]
+  fn play1() {
+    test_log_init();
+    let (buf, contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["a b c d e f\n"]);
+    set_register(&contents, 'a', "dw");
+
+    record(&data_access, chars("@a"));
+    assert_eq!(text(&buf), "b c d e f\n");
+    record(&data_access, chars("2@a"));
+    assert_eq!(text(&buf), "d e f\n");
+    record(&data_access, chars("@@"));
+    assert_eq!(text(&buf), "e f\n");
+
+    // The unknown register fails.
+    record(&data_access, chars("@z"));
+    assert_eq!(text(&buf), "e f\n");
+  }
+
+  #[test]
+  fn play2() {
+    test_log_init();
+    let (buf, _contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["dw\n", "a b c\n"]);
+
+    // The register text is edited as text, and played.
+    record(&data_access, chars("\"ay$j0@a"));
+    assert_eq!(text(&buf), "dw\nb c\n");
+  }
+
+  #[test]
+  fn recursive1() {
+    test_log_init();
+    let (buf, contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["a1\n", "a2\n", "a3\n", "a4"]);
+    set_register(&contents, 'a', "x0j@a");
+
+    // The macro stops at the last line, when `j` fails.
+    record(&data_access, chars("j@a"));
+    assert_eq!(text(&buf), "a1\n2\n3\n4");
+    assert_eq!(cursor(&data_access), (3, 0));
+    assert_eq!(lock!(data_access.state).macros().depth(), 0);
+
+    // The endless macro stops at the max depth.
+    set_register(&contents, 'b', "@b");
+    record(&data_access, chars("@b"));
+    assert_eq!(lock!(data_access.state).macros().depth(), 0);
+    let contents = lock!(contents);
+    let shown_lines = contents.messages().shown_lines();
+    assert!(shown_lines.last().unwrap().1.contains("E223"));
+  }
+
+  #[test]
+  fn normal1() {
+    test_log_init();
+    let (buf, _contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["a\n", "b\n", "c\n", "d\n"]);
+    let cmdline = CommandLineExStateful::default();
+
+    // The incomplete insert is finished like `<Esc>`.
+    cmdline.run_normal(&data_access, ExRange::parse("2,3").0, false, "A;");
+    assert_eq!(text(&buf), "a\nb;\nc;\nd\n");
+    assert_eq!(lock!(data_access.state).macros().depth(), 0);
+
+    cmdline.run_normal(&data_access, None, false, "x");
+    assert_eq!(text(&buf), "a\nb;\nc\nd\n");
+
+    // The range stops when the lines are deleted.
+    cmdline.run_normal(&data_access, ExRange::parse("%").0, false, "dd");
+    assert_eq!(text(&buf), "b;\nd\n");
+  }
+}
+
+#[cfg(test)]
+mod tests_quit {
+  use super::*;
+
+  use crate::test::fsm::{chars, key, make_data_access, press};
+
+  #[test]
+  fn esc1() {
+    test_log_init();
+    let (_buf, _contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["hello\n"]);
+
+    // The `<Esc>` doesn't quit.
+    let stateful = press(&data_access, vec![key(KeyCode::Esc)]);
+    assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+    let stateful = press(
+      &data_access,
+      chars("2g").into_iter().chain([key(KeyCode::Esc)]).collect(),
+    );
+    assert_eq!(
+      stateful,
+      StatefulValue::NormalMode(NormalStateful::default())
+    );
+  }
+
+  #[test]
+  fn zz1() {
+    test_log_init();
+    let (buf, contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["hello\n"]);
+
+    let stateful = press(&data_access, chars("ZZ"));
+    assert!(matches!(stateful, StatefulValue::QuitState(_)));
+
+    // The unnamed buffer cannot be written.
+    press(&data_access, chars("dl"));
+    assert!(lock!(buf).is_modified());
+    let stateful = press(&data_access, chars("ZZ"));
+    assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+    {
+      let contents = lock!(contents);
+      let shown_lines = contents.messages().shown_lines();
+      assert!(shown_lines.last().unwrap().1.starts_with("E32:"));
+    }
+
+    // The changes are discarded.
+    let stateful = press(&data_access, chars("ZQ"));
+    assert!(matches!(stateful, StatefulValue::QuitState(_)));
+  }
+}
+
+#[cfg(test)]
+mod tests_window {
+  use super::*;
+
+  use crate::content::message::MessageLevel;
+  use crate::test::fsm::{chars, ctrl, make_data_access, press};
+
+  #[test]
+  fn window1() {
+    test_log_init();
+    let (_buf, contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["hello\n"]);
+
+    // There's only one window, moving between the windows does nothing.
+    let stateful = press(&data_access, vec![ctrl('w'), ctrl('w')]);
+    assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+    assert!(lock!(contents).messages().shown().is_empty());
+
+    // The window split is not supported.
+    let mut events = vec![ctrl('w')];
+    events.extend(chars("v"));
+    let stateful = press(&data_access, events);
+    assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+    {
+      let contents = lock!(contents);
+      let shown_lines = contents.messages().shown_lines();
+      assert_eq!(
+        shown_lines.last().unwrap(),
+        &(
+          MessageLevel::Error,
+          "Window command \"<C-w>v\" is not supported yet"
+        )
+      );
+    }
+
+    let mut events = vec![ctrl('w')];
+    events.extend(chars("c"));
+    press(&data_access, events);
+    {
+      let contents = lock!(contents);
+      let shown_lines = contents.messages().shown_lines();
+      assert!(shown_lines.last().unwrap().1.starts_with("E444:"));
+    }
+
+    // Close the last window quits.
+    let stateful = press(&data_access, vec![ctrl('w'), ctrl('q')]);
+    assert!(matches!(stateful, StatefulValue::QuitState(_)));
+  }
+}
+
+#[cfg(test)]
+mod tests_paste {
+  use super::*;
+
+  use crate::buf::opt::FileFormatOption;
+  use crate::test::fsm::{chars, cursor, make_data_access, press, text};
+
+  fn paste(s: &str) -> Event {
+    Event::Paste(s.to_string())
+  }
+
+  #[test]
+  fn paste1() {
+    test_log_init();
+    let (buf, _contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["hello\n", "\n"]);
+
+    // Put after the cursor char, the cursor moves to the last put char.
+    let stateful = press(&data_access, vec![paste("ab")]);
+    assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+    assert_eq!(text(&buf), "habello\n\n");
+    assert_eq!(cursor(&data_access), (0, 2));
+
+    // The line breaks are normalized.
+    press(&data_access, vec![paste("x\r\ny\rz")]);
+    assert_eq!(text(&buf), "habx\ny\nzello\n\n");
+    assert_eq!(cursor(&data_access), (2, 0));
+
+    // Empty line.
+    press(&data_access, chars("j"));
+    press(&data_access, vec![paste("cd")]);
+    assert_eq!(text(&buf), "habx\ny\nzello\ncd\n");
+    assert_eq!(cursor(&data_access), (3, 1));
+
+    // Nothing to put.
+    press(&data_access, vec![paste("")]);
+    assert_eq!(text(&buf), "habx\ny\nzello\ncd\n");
+  }
+
+  #[test]
+  fn paste_crlf1() {
+    test_log_init();
+    let (buf, _contents, data_access) =
+      make_data_access(U16Size::new(20, 5), vec!["ab\r\n"]);
+    let mut buf_opts = *lock!(buf).options();
+    buf_opts.set_file_format(FileFormatOption::Dos);
+    lock!(buf).set_options(&buf_opts);
+
+    press(&data_access, vec![paste("x\ny")]);
+    assert_eq!(text(&buf), "ax\r\nyb\r\n");
+    assert_eq!(cursor(&data_access), (1, 0));
+  }
+}
@@ -1,21 +1,566 @@
 //! The operator-pending mode.
+//!
+//! After an operator (`d`, `y`, `c`, `>`, `<`, `g~`, `gu`, `gU`) is typed in normal mode, the
+//! editor waits for a motion, then the operator acts on the text moved over by the motion. The
+//! grammar is `[count]{operator}[count]{motion}`, i.e. the two counts multiply, and the motion
+//! type can be forced with `v`, `V` and `<C-V>` before the motion. Typing the operator twice
+//! (i.e. `dd`, `yy`, `cc`, `>>`, `g~~`) operates on `count` lines. The text objects can be used
+//! instead of a motion, i.e. `diw`, `ca(`, see [`text_object`](crate::buf::text_object).
+//!
+//! NOTE: There's no undo yet, the changes made by an operator cannot be undone.
+//!
+//! See: <https://vimhelp.org/motion.txt.html#operator>.
 
+use crate::buf::selection::{Selection, SelectionKind};
+use crate::buf::text::Text;
+use crate::buf::unicode::{BLANK_CLASS, char_class};
+use crate::prelude::*;
+use crate::state::fsm::motion;
+use crate::state::fsm::pending::{Feed, PendingKeys, PrefixKey};
 use crate::state::fsm::{Stateful, StatefulDataAccess, StatefulValue};
-use crate::state::ops::Operation;
+use crate::state::ops::cursor_ops::{self, CursorMoveDirection};
+use crate::state::ops::{
+  CaseChange, GotoInsertModeVariant, MotionType, Operation, Operator,
+  WordMotion,
+};
+use crate::state::ops::{motion_ops, visual_ops};
+use crate::state::repeat::{self, RepeatKind, RepeatableChange};
+use crate::ui::tree::*;
+use crate::ui::viewport::Viewport;
 
-#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use tracing::trace;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 /// The operator-pending editing mode.
-pub struct OperatorPendingStateful {}
+pub struct OperatorPendingStateful {
+  operator: Operator,
+
+  // The register and count before the operator, i.e. `"a2` in `"a2d3w`.
+  pending: PendingKeys,
+
+  // The count and prefix key after the operator, i.e. `3` in `2d3w`, `i` in `diw`.
+  motion_pending: PendingKeys,
+
+  // The forced motion type, i.e. `v`, `V` and `<C-V>`.
+  force: Option<SelectionKind>,
+}
+
+// The prefix keys in operator-pending mode.
+fn _get_prefix(key_event: &KeyEvent) -> Option<PrefixKey> {
+  if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+    return None;
+  }
+  match key_event.code {
+    KeyCode::Char('g') => Some(PrefixKey::G),
+    KeyCode::Char('i') => Some(PrefixKey::Object(true)),
+    KeyCode::Char('a') => Some(PrefixKey::Object(false)),
+    code => motion::get_char_find_kind(code).map(PrefixKey::CharFind),
+  }
+}
+
+impl OperatorPendingStateful {
+  /// Make the operator-pending mode with the register and count typed before the operator.
+  pub fn new(operator: Operator, pending: PendingKeys) -> Self {
+    Self {
+      operator,
+      pending,
+      motion_pending: PendingKeys::default(),
+      force: None,
+    }
+  }
+
+  pub fn operator(&self) -> Operator {
+    self.operator
+  }
+
+  /// The register given by `"x` before the operator.
+  pub fn register(&self) -> Option<char> {
+    self.pending.register()
+  }
+
+  /// The total count, i.e. the count before the operator multiplies the count after it.
+  pub fn count(&self) -> usize {
+    self
+      .pending
+      .count()
+      .unwrap_or(1)
+      .saturating_mul(self.motion_pending.count().unwrap_or(1))
+  }
+
+  /// The keys of the operator, i.e. `d`, `g~`.
+  fn operator_keys(&self) -> &'static str {
+    match self.operator {
+      Operator::Delete => "d",
+      Operator::Yank => "y",
+      Operator::Change => "c",
+      Operator::ShiftRight => ">",
+      Operator::ShiftLeft => "<",
+      Operator::ChangeCase(CaseChange::Toggle) => "g~",
+      Operator::ChangeCase(CaseChange::Lower) => "gu",
+      Operator::ChangeCase(CaseChange::Upper) => "gU",
+    }
+  }
+
+  /// The key that doubles the operator to operate on lines, i.e. the last key of the operator.
+  fn double_key(&self) -> char {
+    self.operator_keys().chars().last().unwrap()
+  }
+
+  /// The total count if any count is typed, i.e. `dG` goes to the last line but `d3G` goes to the
+  /// 3rd line.
+  fn typed_count(&self) -> Option<usize> {
+    if self.pending.count().is_none() && self.motion_pending.count().is_none() {
+      None
+    } else {
+      Some(self.count())
+    }
+  }
+
+  /// The change repeated by `.`, i.e. the keys of the operator, the forced motion type and the
+  /// motion, the last key is the key of `data_access` that completes the motion.
+  fn repeatable_change(
+    &self,
+    data_access: &StatefulDataAccess,
+  ) -> Option<RepeatableChange> {
+    let key_event = match data_access.event {
+      Event::Key(key_event) => key_event,
+      _ => return None,
+    };
+    let mut keys = repeat::char_keys(self.operator_keys());
+    match self.force {
+      Some(SelectionKind::Char) => keys.extend(repeat::char_keys("v")),
+      Some(SelectionKind::Line) => keys.extend(repeat::char_keys("V")),
+      Some(SelectionKind::Block) => keys.push(KeyEvent::new_with_kind(
+        KeyCode::Char('v'),
+        KeyModifiers::CONTROL,
+        KeyEventKind::Press,
+      )),
+      None => {}
+    }
+    if let Some(prefix) = self.motion_pending.prefix() {
+      keys.push(prefix.key_event());
+    }
+    keys.push(key_event);
+    Some(RepeatableChange::new(
+      RepeatKind::Normal,
+      self.register(),
+      self.typed_count(),
+      keys,
+    ))
+  }
+}
+
+/// The motion and its type.
+fn with_motion_type(op: Operation) -> (Operation, MotionType) {
+  let motion_type = motion_ops::motion_type(&op);
+  (op, motion_type)
+}
 
 impl Stateful for OperatorPendingStateful {
-  fn handle(&self, _data_access: StatefulDataAccess) -> StatefulValue {
-    StatefulValue::OperatorPendingMode(OperatorPendingStateful::default())
+  fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    let key_event = match data_access.event {
+      Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+        key_event
+      }
+      _ => return StatefulValue::OperatorPendingMode(*self),
+    };
+    trace!("Event::key:{:?}", key_event);
+    let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+
+    match key_event.code {
+      KeyCode::Esc => {
+        return StatefulValue::NormalMode(super::NormalStateful::default());
+      }
+      KeyCode::Char('c') if ctrl => {
+        return StatefulValue::NormalMode(super::NormalStateful::default());
+      }
+      KeyCode::Char('v') if ctrl && self.motion_pending.prefix().is_none() => {
+        return StatefulValue::OperatorPendingMode(Self {
+          force: Some(SelectionKind::Block),
+          ..*self
+        });
+      }
+      _ => {}
+    }
+
+    match self.motion_pending.feed(&key_event, false, _get_prefix) {
+      Feed::Pending(motion_pending) => {
+        return StatefulValue::OperatorPendingMode(Self {
+          motion_pending,
+          ..*self
+        });
+      }
+      Feed::Cancel => {
+        return StatefulValue::NormalMode(super::NormalStateful::default());
+      }
+      Feed::Complete => {}
+    }
+    if ctrl {
+      return StatefulValue::NormalMode(super::NormalStateful::default());
+    }
+
+    match (self.motion_pending.prefix(), key_event.code) {
+      // `g~g~`, `gugu`, `gUgU`.
+      (Some(PrefixKey::G), KeyCode::Char(c)) if c == self.double_key() => {
+        self.operate(&data_access, None)
+      }
+      (Some(PrefixKey::G), code) => {
+        match motion::get_g_motion(&data_access, code, self.typed_count()) {
+          Some(op) => self.operate(&data_access, Some(with_motion_type(op))),
+          None => StatefulValue::NormalMode(super::NormalStateful::default()),
+        }
+      }
+      (Some(PrefixKey::CharFind(kind)), KeyCode::Char(c)) => {
+        let op =
+          motion::char_find_motion(&data_access, kind, c, self.typed_count());
+        self.operate(&data_access, Some(with_motion_type(op)))
+      }
+      (Some(PrefixKey::Object(inner)), KeyCode::Char(key)) => {
+        self.operate_text_object(&data_access, key, inner)
+      }
+      (Some(_), _) => {
+        StatefulValue::NormalMode(super::NormalStateful::default())
+      }
+      (None, KeyCode::Char('v')) => StatefulValue::OperatorPendingMode(Self {
+        force: Some(SelectionKind::Char),
+        ..*self
+      }),
+      (None, KeyCode::Char('V')) => StatefulValue::OperatorPendingMode(Self {
+        force: Some(SelectionKind::Line),
+        ..*self
+      }),
+      // Double the operator, i.e. `dd`.
+      (None, KeyCode::Char(c)) if c == self.double_key() => {
+        self.operate(&data_access, None)
+      }
+      (None, code) => {
+        match motion::get_motion(&data_access, code, self.typed_count()) {
+          Some(op) => self.operate(&data_access, Some(with_motion_type(op))),
+          None => StatefulValue::NormalMode(super::NormalStateful::default()),
+        }
+      }
+    }
   }
+
   fn handle_op(
     &self,
-    _data_access: StatefulDataAccess,
-    _op: Operation,
+    data_access: StatefulDataAccess,
+    op: Operation,
+  ) -> StatefulValue {
+    match op {
+      Operation::CursorMoveBy((_, _))
+      | Operation::CursorMoveUpBy(_)
+      | Operation::CursorMoveDownBy(_)
+      | Operation::CursorMoveLeftBy(_)
+      | Operation::CursorMoveRightBy(_)
+      | Operation::CursorMoveTo((_, _)) => {
+        self.operate(&data_access, Some(with_motion_type(op)))
+      }
+      op if motion_ops::is_motion(&op) => {
+        self.operate(&data_access, Some(with_motion_type(op)))
+      }
+      _ => unreachable!(),
+    }
+  }
+
+  fn handle_timeout(&self) -> Option<StatefulValue> {
+    Some(StatefulValue::NormalMode(super::NormalStateful::default()))
+  }
+
+  fn showcmd(&self) -> String {
+    let force = match self.force {
+      Some(SelectionKind::Char) => "v",
+      Some(SelectionKind::Line) => "V",
+      Some(SelectionKind::Block) => "^V",
+      None => "",
+    };
+    format!(
+      "{}{}{}{}",
+      self.pending.showcmd(),
+      self.operator_keys(),
+      force,
+      self.motion_pending.showcmd()
+    )
+  }
+
+  fn is_literal_pending(&self) -> bool {
+    self.pending.is_literal_pending()
+      || self.motion_pending.is_literal_pending()
+  }
+}
+
+impl OperatorPendingStateful {
+  /// Apply the operator on the text moved over by the `motion`, or `count` lines if the operator
+  /// is doubled (i.e. `dd`) and `motion` is `None`.
+  fn operate(
+    &self,
+    data_access: &StatefulDataAccess,
+    motion: Option<(Operation, MotionType)>,
+  ) -> StatefulValue {
+    let selection = {
+      let tree = data_access.tree.clone();
+      let tree = lock!(tree);
+      let current_window = tree.current_window().unwrap();
+      let cursor_viewport = current_window.cursor_viewport();
+      let buffer = current_window.buffer().upgrade().unwrap();
+      let buffer = lock!(buffer);
+      let text = buffer.text();
+
+      let from = (cursor_viewport.line_idx(), cursor_viewport.char_idx());
+      let doubled = motion.is_none();
+      let (op, motion_type) = match motion {
+        Some(motion) => motion,
+        None => (
+          Operation::CursorMoveDownBy(self.count().saturating_sub(1)),
+          MotionType::Linewise,
+        ),
+      };
+      if motion_ops::is_motion(&op) {
+        self.text_motion_selection(
+          text,
+          &current_window.viewport(),
+          op,
+          motion_type,
+          from,
+        )
+      } else {
+        // The exclusive motion can move to the eol, i.e. `dl` at the last char deletes it.
+        let (char_idx, line_idx, direction) =
+          if motion_type == MotionType::Exclusive {
+            cursor_ops::normalize_to_cursor_move_to_include_eol(
+              text, op, from.1, from.0,
+            )
+          } else {
+            cursor_ops::normalize_to_cursor_move_to_exclude_eol(
+              text, op, from.1, from.0,
+            )
+          };
+        let to = (line_idx, char_idx);
+
+        // The vertical motion fails if it cannot move, i.e. `dj` at the last line.
+        let vertical = matches!(
+          direction,
+          CursorMoveDirection::Up | CursorMoveDirection::Down
+        );
+        if !doubled && vertical && to.0 == from.0 {
+          None
+        } else {
+          visual_ops::motion_selection(text, from, to, motion_type, self.force)
+        }
+      }
+    };
+
+    match selection {
+      Some(selection) => apply_operator(
+        data_access,
+        &selection,
+        self.operator,
+        self.register(),
+        self.repeatable_change(data_access),
+      ),
+      None => _fail(data_access),
+    }
+  }
+
+  /// The selection of the text motion, i.e. `w`, `$`, `fx`.
+  fn text_motion_selection(
+    &self,
+    text: &Text,
+    viewport: &Viewport,
+    op: Operation,
+    motion_type: MotionType,
+    from: (usize, usize),
+  ) -> Option<Selection> {
+    let (to, motion_type) = match op {
+      // `cw` on a non-blank char is `ce`, and it doesn't move to the next word at the end of word.
+      Operation::CursorMoveByWord((WordMotion::Forward, big_word, n))
+        if self.operator == Operator::Change
+          && !_is_blank(text, from, big_word) =>
+      {
+        let end_of_word =
+          !_is_same_word(text, from, (from.0, from.1 + 1), big_word);
+        let to = if end_of_word && n <= 1 {
+          from
+        } else {
+          let n = if end_of_word { n - 1 } else { n };
+          let op =
+            Operation::CursorMoveByWord((WordMotion::ForwardEnd, big_word, n));
+          motion_ops::motion_target(text, viewport, &op, from)?
+        };
+        (to, MotionType::Inclusive)
+      }
+      // The `w` motion doesn't cross the line, i.e. `dw` at the last word of line.
+      Operation::CursorMoveByWord((WordMotion::Forward, _, _)) => {
+        let to = motion_ops::motion_target(text, viewport, &op, from)?;
+        if to.0 > from.0 {
+          let line_idx = to.0 - 1;
+          let len_chars = text
+            .last_char_on_line_no_eol(line_idx)
+            .map(|c| c + 1)
+            .unwrap_or(0);
+          ((line_idx, len_chars), motion_type)
+        } else {
+          (to, motion_type)
+        }
+      }
+      _ => (
+        motion_ops::motion_target(text, viewport, &op, from)?,
+        motion_type,
+      ),
+    };
+    visual_ops::motion_selection(text, from, to, motion_type, self.force)
+  }
+
+  /// Apply the operator on the text object registered with `key`, the `inner` is `i` (`true`) or
+  /// `a` (`false`).
+  fn operate_text_object(
+    &self,
+    data_access: &StatefulDataAccess,
+    key: char,
+    inner: bool,
   ) -> StatefulValue {
-    StatefulValue::OperatorPendingMode(OperatorPendingStateful::default())
+    let object = {
+      let contents = data_access.contents.clone();
+      lock!(contents).text_objects().get(key)
+    };
+    let selection = object.and_then(|object| {
+      let tree = data_access.tree.clone();
+      let tree = lock!(tree);
+      let current_window = tree.current_window().unwrap();
+      let cursor_viewport = current_window.cursor_viewport();
+      let buffer = current_window.buffer().upgrade().unwrap();
+      let buffer = lock!(buffer);
+      object.select(
+        buffer.text(),
+        (cursor_viewport.line_idx(), cursor_viewport.char_idx()),
+        self.count(),
+        inner,
+      )
+    });
+
+    match selection {
+      Some(mut selection) => {
+        if let Some(kind) = self.force {
+          selection.set_kind(kind);
+        }
+        apply_operator(
+          data_access,
+          &selection,
+          self.operator,
+          self.register(),
+          self.repeatable_change(data_access),
+        )
+      }
+      None => _fail(data_access),
+    }
+  }
+}
+
+// Whether the char at `(line_idx, char_idx)` is blank, the eol is also blank.
+fn _is_blank(
+  text: &Text,
+  (line_idx, char_idx): (usize, usize),
+  big_word: bool,
+) -> bool {
+  if text.is_eol(line_idx, char_idx) {
+    return true;
+  }
+  match text
+    .rope()
+    .get_line(line_idx)
+    .and_then(|line| line.get_char(char_idx))
+  {
+    Some(c) => char_class(text.options(), c, big_word) == BLANK_CLASS,
+    None => true,
+  }
+}
+
+// Whether the two chars are in the same word.
+fn _is_same_word(
+  text: &Text,
+  a: (usize, usize),
+  b: (usize, usize),
+  big_word: bool,
+) -> bool {
+  let class = |(line_idx, char_idx): (usize, usize)| {
+    if text.is_eol(line_idx, char_idx) {
+      return None;
+    }
+    text
+      .rope()
+      .get_line(line_idx)
+      .and_then(|line| line.get_char(char_idx))
+      .map(|c| char_class(text.options(), c, big_word))
+  };
+  matches!((class(a), class(b)), (Some(x), Some(y)) if x == y)
+}
+
+// The motion or text object fails, i.e. the rest keys of the playing macro are dropped.
+fn _fail(data_access: &StatefulDataAccess) -> StatefulValue {
+  lock!(data_access.state).macros_mut().fail();
+  StatefulValue::NormalMode(super::NormalStateful::default())
+}
+
+/// Apply the operator on the selection in current window, then goto normal mode, or insert mode
+/// for [`Operator::Change`].
+///
+/// The deleted or yanked text is saved to the registers, or the `register` given by `"x`. The
+/// `change` is recorded as the last change repeated by `.` (except yank), the text typed in insert
+/// mode is appended to it for [`Operator::Change`].
+pub fn apply_operator(
+  data_access: &StatefulDataAccess,
+  selection: &Selection,
+  operator: Operator,
+  register: Option<char>,
+  change: Option<RepeatableChange>,
+) -> StatefulValue {
+  {
+    let tree = data_access.tree.clone();
+    let mut tree = lock!(tree);
+    let current_window = tree.current_window_mut().unwrap();
+    let current_window_id = current_window.id();
+    let buffer = current_window.buffer().upgrade().unwrap();
+    let mut buffer = lock!(buffer);
+    let contents = data_access.contents.clone();
+    let mut contents = lock!(contents);
+
+    let (line_idx, char_idx) = visual_ops::apply_operator(
+      buffer.text_mut(),
+      contents.registers_mut(),
+      register,
+      selection,
+      operator,
+    );
+
+    if operator != Operator::Yank {
+      cursor_ops::_update_viewport_after_text_changed(
+        &mut tree,
+        current_window_id,
+        buffer.text(),
+      );
+    }
+    cursor_ops::cursor_move(
+      &mut tree,
+      current_window_id,
+      buffer.text(),
+      Operation::CursorMoveTo((char_idx, line_idx)),
+      operator == Operator::Change,
+    );
+  }
+
+  if let (Some(change), true) = (change, operator != Operator::Yank) {
+    let mut state = lock!(data_access.state);
+    let dot_repeat = state.dot_repeat_mut();
+    dot_repeat.start(change);
+    if operator != Operator::Change {
+      dot_repeat.finish();
+    }
+  }
+
+  if operator == Operator::Change {
+    return super::NormalStateful::default()
+      .goto_insert_mode(data_access, GotoInsertModeVariant::Keep);
   }
+  StatefulValue::NormalMode(super::NormalStateful::default())
 }
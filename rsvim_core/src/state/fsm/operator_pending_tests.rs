@@ -0,0 +1,235 @@
+use crate::content::register::{SMALL_DELETE_REGISTER, UNNAMED_REGISTER};
+use crate::prelude::*;
+use crate::state::fsm::StatefulValue;
+use crate::test::fsm::{
+  chars, ctrl, cursor, key, make_data_access, press, register, text,
+};
+use crate::test::log::init as test_log_init;
+
+use crossterm::event::KeyCode;
+
+#[test]
+fn delete1() {
+  test_log_init();
+  let (buf, contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello world\n", "foo\n"]);
+
+  let stateful = press(&data_access, chars("d"));
+  assert!(matches!(stateful, StatefulValue::OperatorPendingMode(_)));
+
+  let stateful = press(&data_access, chars("dl"));
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(text(&buf), "ello world\nfoo\n");
+  assert_eq!(register(&contents, SMALL_DELETE_REGISTER).unwrap(), "h");
+
+  // Counts multiply.
+  press(&data_access, chars("2d3l"));
+  assert_eq!(text(&buf), "orld\nfoo\n");
+  assert_eq!(register(&contents, UNNAMED_REGISTER).unwrap(), "ello w");
+
+  // Inclusive motion.
+  let mut events = chars("d");
+  events.push(key(KeyCode::End));
+  press(&data_access, events);
+  assert_eq!(text(&buf), "\nfoo\n");
+  assert_eq!(cursor(&data_access), (0, 0));
+}
+
+#[test]
+fn linewise1() {
+  test_log_init();
+  let (buf, contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["a\n", "b\n", "c\n", "d\n"]);
+
+  press(&data_access, chars("dd"));
+  assert_eq!(text(&buf), "b\nc\nd\n");
+  assert_eq!(register(&contents, '1').unwrap(), "a\n");
+
+  press(&data_access, chars("2dd"));
+  assert_eq!(text(&buf), "d\n");
+  assert_eq!(register(&contents, '1').unwrap(), "b\nc\n");
+  assert_eq!(register(&contents, '2').unwrap(), "a\n");
+
+  press(&data_access, chars("yy"));
+  assert_eq!(text(&buf), "d\n");
+  assert_eq!(register(&contents, '0').unwrap(), "d\n");
+
+  // The motion fails at the first line.
+  press(&data_access, chars("dk"));
+  assert_eq!(text(&buf), "d\n");
+}
+
+#[test]
+fn linewise2() {
+  test_log_init();
+  let (buf, _contents, data_access) = make_data_access(
+    U16Size::new(20, 8),
+    vec!["1\n", "2\n", "3\n", "4\n", "5\n", "6\n"],
+  );
+
+  press(&data_access, chars("jj2dk"));
+  assert_eq!(text(&buf), "4\n5\n6\n");
+  assert_eq!(cursor(&data_access), (0, 0));
+
+  press(&data_access, chars("2y2j"));
+  assert_eq!(text(&buf), "4\n5\n6\n");
+}
+
+#[test]
+fn change1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello\n", "world\n"]);
+
+  let stateful = press(&data_access, chars("cl"));
+  assert!(matches!(stateful, StatefulValue::InsertMode(_)));
+  assert_eq!(text(&buf), "ello\nworld\n");
+
+  let stateful = press(&data_access, chars("jcc"));
+  assert!(matches!(stateful, StatefulValue::InsertMode(_)));
+  assert_eq!(text(&buf), "ello\n\n");
+  assert_eq!(cursor(&data_access), (1, 0));
+}
+
+#[test]
+fn shift_case1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello\n", "world\n"]);
+
+  press(&data_access, chars(">>"));
+  assert_eq!(text(&buf), "\thello\nworld\n");
+  assert_eq!(cursor(&data_access), (0, 1));
+
+  press(&data_access, chars("g~~"));
+  assert_eq!(text(&buf), "\tHELLO\nworld\n");
+
+  press(&data_access, chars("gugu"));
+  assert_eq!(text(&buf), "\thello\nworld\n");
+
+  press(&data_access, chars("gUl"));
+  assert_eq!(text(&buf), "\tHello\nworld\n");
+
+  press(&data_access, chars("2>>"));
+  assert_eq!(text(&buf), "\t\tHello\n\tworld\n");
+
+  press(&data_access, chars("<j"));
+  assert_eq!(text(&buf), "\tHello\nworld\n");
+}
+
+#[test]
+fn force1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello\n", "world\n", "foo\n"]);
+
+  // Linewise motion becomes exclusive charwise.
+  press(&data_access, chars("ldvj"));
+  assert_eq!(text(&buf), "horld\nfoo\n");
+
+  // Charwise motion becomes linewise.
+  press(&data_access, chars("dVl"));
+  assert_eq!(text(&buf), "foo\n");
+}
+
+#[test]
+fn force2() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello\n", "world\n"]);
+
+  let mut events = chars("ld");
+  events.push(ctrl('v'));
+  events.extend(chars("j"));
+  press(&data_access, events);
+  assert_eq!(text(&buf), "hllo\nwrld\n");
+}
+
+#[test]
+fn cancel1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello\n", "world\n"]);
+
+  let mut events = chars("2d");
+  events.push(key(KeyCode::Esc));
+  let stateful = press(&data_access, events);
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(text(&buf), "hello\nworld\n");
+
+  // Unknown motion.
+  let stateful = press(&data_access, chars("dz"));
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(text(&buf), "hello\nworld\n");
+}
+
+#[test]
+fn text_object1() {
+  test_log_init();
+  let (buf, contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["foo bar baz\n", "f(a, b)\n"]);
+
+  press(&data_access, chars("llllldaw"));
+  assert_eq!(text(&buf), "foo baz\nf(a, b)\n");
+  assert_eq!(register(&contents, UNNAMED_REGISTER).unwrap(), "bar ");
+  assert_eq!(cursor(&data_access), (0, 4));
+
+  press(&data_access, chars("y2iw"));
+  assert_eq!(register(&contents, '0').unwrap(), "baz");
+
+  let stateful = press(&data_access, chars("jhhci("));
+  assert!(matches!(stateful, StatefulValue::InsertMode(_)));
+  assert_eq!(text(&buf), "foo baz\nf()\n");
+  assert_eq!(cursor(&data_access), (1, 2));
+
+  // Unknown text object.
+  let stateful = press(&data_access, chars("diz"));
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(text(&buf), "foo baz\nf()\n");
+}
+
+#[test]
+fn motion1() {
+  test_log_init();
+  let (buf, contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["foo bar baz\n", "qux\n"]);
+
+  press(&data_access, chars("dw"));
+  assert_eq!(text(&buf), "bar baz\nqux\n");
+
+  // `cw` is `ce` on a word.
+  let stateful = press(&data_access, chars("cw"));
+  assert!(matches!(stateful, StatefulValue::InsertMode(_)));
+  assert_eq!(text(&buf), " baz\nqux\n");
+  assert_eq!(register(&contents, UNNAMED_REGISTER).unwrap(), "bar");
+
+  // `dw` doesn't delete the eol at the last word of line.
+  press(&data_access, chars("wdw"));
+  assert_eq!(text(&buf), " \nqux\n");
+
+  press(&data_access, chars("d$"));
+  assert_eq!(text(&buf), "\nqux\n");
+  // Nothing to delete.
+  press(&data_access, chars("d$"));
+  assert_eq!(text(&buf), "\nqux\n");
+}
+
+#[test]
+fn motion2() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["a,b,c,d\n"]);
+
+  press(&data_access, chars("df,"));
+  assert_eq!(text(&buf), "b,c,d\n");
+  press(&data_access, chars("d;"));
+  assert_eq!(text(&buf), "c,d\n");
+  // The `t` motion fails before the adjacent char.
+  press(&data_access, chars("dt,"));
+  assert_eq!(text(&buf), "c,d\n");
+
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["1\n", "2\n", "3\n", "4\n"]);
+  press(&data_access, chars("jjdgg"));
+  assert_eq!(text(&buf), "4\n");
+}
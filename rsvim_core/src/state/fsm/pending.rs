@@ -0,0 +1,235 @@
+//! The pending keys of an incomplete command in normal mode, visual mode and operator-pending
+//! mode.
+//!
+//! A command is `["x][count]{command}`, where the command can be a prefix key followed by the
+//! next key, i.e. `"ayy`, `3j`, `gg`, `zz`, `<C-w>v`, `fx`. The keys are collected one by one
+//! until they complete a command, and are shown in command-line with the `showcmd` option. If the
+//! next key doesn't arrive in `timeoutlen` milliseconds (when the `timeout` option is on), the
+//! pending keys are dropped.
+//!
+//! See: <https://vimhelp.org/intro.txt.html#notation> and
+//! <https://vimhelp.org/options.txt.html#%27timeoutlen%27>.
+
+use crate::content::register::Registers;
+use crate::state::keymap::normalize_key;
+use crate::state::ops::CharFindKind;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+/// Append a digit to the count, i.e. `1` then `2` is `12`.
+pub fn push_count_digit(count: Option<usize>, digit: char) -> usize {
+  debug_assert!(digit.is_ascii_digit());
+  count
+    .unwrap_or(0)
+    .saturating_mul(10)
+    .saturating_add(digit.to_digit(10).unwrap() as usize)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// The prefix key that waits for the next key.
+pub enum PrefixKey {
+  /// `g`, i.e. `gg`, `gv`, `g~`.
+  G,
+  /// `z`, i.e. `zz`, `zt`, `zb`.
+  Z,
+  /// `<C-w>`, i.e. `<C-w>v`, `<C-w>s`.
+  CtrlW,
+  /// `f`, `F`, `t`, `T`, i.e. waiting for the char of `fx`.
+  CharFind(CharFindKind),
+  /// `i` (`true`) or `a` (`false`), i.e. waiting for the text object of `iw`.
+  Object(bool),
+  /// `q`, i.e. waiting for the register of `qa`.
+  Q,
+  /// `@`, i.e. waiting for the register of `@a`.
+  At,
+  /// `r`, i.e. waiting for the char of `rx`.
+  R,
+  /// `Z`, i.e. `ZZ`, `ZQ`.
+  UpperZ,
+}
+
+impl PrefixKey {
+  /// The key shown in command-line.
+  pub fn showcmd(&self) -> &'static str {
+    match self {
+      PrefixKey::G => "g",
+      PrefixKey::Z => "z",
+      PrefixKey::CtrlW => "^W",
+      PrefixKey::CharFind(CharFindKind::Forward) => "f",
+      PrefixKey::CharFind(CharFindKind::Backward) => "F",
+      PrefixKey::CharFind(CharFindKind::TillForward) => "t",
+      PrefixKey::CharFind(CharFindKind::TillBackward) => "T",
+      PrefixKey::Object(true) => "i",
+      PrefixKey::Object(false) => "a",
+      PrefixKey::Q => "q",
+      PrefixKey::At => "@",
+      PrefixKey::R => "r",
+      PrefixKey::UpperZ => "Z",
+    }
+  }
+
+  /// The pressed key, i.e. the prefix key of the last change repeated by `.`.
+  pub fn key_event(&self) -> KeyEvent {
+    let (c, modifiers) = match self {
+      PrefixKey::CtrlW => ('w', KeyModifiers::CONTROL),
+      _ => (
+        self.showcmd().chars().next().unwrap(),
+        KeyModifiers::empty(),
+      ),
+    };
+    KeyEvent::new_with_kind(KeyCode::Char(c), modifiers, KeyEventKind::Press)
+  }
+
+  /// The prefix key of the pressed key that is followed by the next key of the command, i.e. `g`
+  /// of `gg`. It is the reverse of [`key_event`](PrefixKey::key_event).
+  pub fn from_key_event(key_event: &KeyEvent) -> Option<PrefixKey> {
+    let key_event = normalize_key(key_event);
+    [
+      PrefixKey::G,
+      PrefixKey::Z,
+      PrefixKey::CtrlW,
+      PrefixKey::UpperZ,
+    ]
+    .into_iter()
+    .find(|prefix| prefix.key_event() == key_event)
+  }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// The result of feeding a key to the pending keys.
+pub enum Feed {
+  /// The key is collected, and the command is still incomplete.
+  Pending(PendingKeys),
+  /// The key completes the command, the caller resolves the command with the pending keys and
+  /// the key.
+  Complete,
+  /// The key is invalid, the pending keys are dropped.
+  Cancel,
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+/// The pending keys, i.e. the register, the count and the prefix key.
+pub struct PendingKeys {
+  // The `"` key is pressed, i.e. waiting for the register name.
+  register_key: bool,
+
+  // The register given by `"x`.
+  register: Option<char>,
+
+  // The count, i.e. `3` in `3j`.
+  count: Option<usize>,
+
+  // The prefix key, i.e. `g` in `gg`.
+  prefix: Option<PrefixKey>,
+}
+
+impl PendingKeys {
+  /// Pending keys with the register and count, i.e. passed from normal mode to operator-pending
+  /// mode.
+  pub fn new(register: Option<char>, count: Option<usize>) -> Self {
+    Self {
+      register_key: false,
+      register,
+      count,
+      prefix: None,
+    }
+  }
+
+  /// Whether there's no pending keys.
+  pub fn is_empty(&self) -> bool {
+    *self == Self::default()
+  }
+
+  pub fn register(&self) -> Option<char> {
+    self.register
+  }
+
+  pub fn count(&self) -> Option<usize> {
+    self.count
+  }
+
+  pub fn prefix(&self) -> Option<PrefixKey> {
+    self.prefix
+  }
+
+  /// Feed the pressed key. The `"x` register is only collected if `with_register` is `true`, and
+  /// `get_prefix` tells whether the key is a prefix key when there's no prefix key yet.
+  ///
+  /// NOTE: The `0` is not a count if it is the first digit, i.e. it is the `0` motion.
+  pub fn feed(
+    &self,
+    key_event: &KeyEvent,
+    with_register: bool,
+    get_prefix: impl Fn(&KeyEvent) -> Option<PrefixKey>,
+  ) -> Feed {
+    let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+
+    if self.register_key {
+      return match key_event.code {
+        KeyCode::Char(c) if !ctrl && Registers::is_writable(c) => {
+          Feed::Pending(Self {
+            register_key: false,
+            register: Some(c),
+            ..*self
+          })
+        }
+        _ => Feed::Cancel,
+      };
+    }
+
+    if self.prefix.is_some() {
+      return match key_event.code {
+        KeyCode::Esc => Feed::Cancel,
+        _ => Feed::Complete,
+      };
+    }
+
+    match key_event.code {
+      KeyCode::Esc if !self.is_empty() => Feed::Cancel,
+      KeyCode::Char('"') if with_register && !ctrl => Feed::Pending(Self {
+        register_key: true,
+        ..*self
+      }),
+      KeyCode::Char(c @ '0'..='9')
+        if !ctrl && (c != '0' || self.count.is_some()) =>
+      {
+        Feed::Pending(Self {
+          count: Some(push_count_digit(self.count, c)),
+          ..*self
+        })
+      }
+      _ => match get_prefix(key_event) {
+        Some(prefix) => Feed::Pending(Self {
+          prefix: Some(prefix),
+          ..*self
+        }),
+        None => Feed::Complete,
+      },
+    }
+  }
+
+  /// Whether the next key is taken literally, i.e. the register name after `"`, or the key after
+  /// the prefix key (`gg`, `fx`). The key mappings are not applied to it.
+  pub fn is_literal_pending(&self) -> bool {
+    self.register_key || self.prefix.is_some()
+  }
+
+  /// The pending keys shown in command-line, i.e. `"a3g`.
+  pub fn showcmd(&self) -> String {
+    let mut s = String::new();
+    if let Some(register) = self.register {
+      s.push('"');
+      s.push(register);
+    }
+    if self.register_key {
+      s.push('"');
+    }
+    if let Some(count) = self.count {
+      s.push_str(&count.to_string());
+    }
+    if let Some(prefix) = self.prefix {
+      s.push_str(prefix.showcmd());
+    }
+    s
+  }
+}
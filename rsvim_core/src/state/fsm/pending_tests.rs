@@ -0,0 +1,93 @@
+use super::pending::*;
+
+use crate::state::ops::CharFindKind;
+use crate::test::log::init as test_log_init;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+fn key(code: KeyCode) -> KeyEvent {
+  KeyEvent::new_with_kind(code, KeyModifiers::empty(), KeyEventKind::Press)
+}
+
+fn ctrl(c: char) -> KeyEvent {
+  KeyEvent::new_with_kind(
+    KeyCode::Char(c),
+    KeyModifiers::CONTROL,
+    KeyEventKind::Press,
+  )
+}
+
+fn get_prefix(key_event: &KeyEvent) -> Option<PrefixKey> {
+  match key_event.code {
+    KeyCode::Char('w')
+      if key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+    {
+      Some(PrefixKey::CtrlW)
+    }
+    KeyCode::Char('g') => Some(PrefixKey::G),
+    KeyCode::Char('f') => Some(PrefixKey::CharFind(CharFindKind::Forward)),
+    _ => None,
+  }
+}
+
+// Feed the keys, returns the last pending keys, or the last feed result if it is not pending.
+fn feed(keys: Vec<KeyEvent>) -> Result<PendingKeys, Feed> {
+  let mut pending = PendingKeys::default();
+  for key_event in keys {
+    match pending.feed(&key_event, true, get_prefix) {
+      Feed::Pending(next) => pending = next,
+      feed => return Err(feed),
+    }
+  }
+  Ok(pending)
+}
+
+fn chars(s: &str) -> Vec<KeyEvent> {
+  s.chars().map(|c| key(KeyCode::Char(c))).collect()
+}
+
+#[test]
+fn feed1() {
+  test_log_init();
+  let pending = feed(chars("\"a12g")).unwrap();
+  assert_eq!(pending.register(), Some('a'));
+  assert_eq!(pending.count(), Some(12));
+  assert_eq!(pending.prefix(), Some(PrefixKey::G));
+  assert_eq!(pending.showcmd(), "\"a12g");
+
+  // The key after prefix completes the command.
+  assert_eq!(feed(chars("gg")), Err(Feed::Complete));
+  assert_eq!(feed(chars("fx")), Err(Feed::Complete));
+  assert_eq!(feed(chars("3j")), Err(Feed::Complete));
+
+  // The `0` is not a count if it is the first digit.
+  assert_eq!(feed(chars("0")), Err(Feed::Complete));
+  assert_eq!(feed(chars("10")).unwrap().count(), Some(10));
+}
+
+#[test]
+fn feed2() {
+  test_log_init();
+  let pending = feed(vec![key(KeyCode::Char('2')), ctrl('w')]).unwrap();
+  assert_eq!(pending.prefix(), Some(PrefixKey::CtrlW));
+  assert_eq!(pending.showcmd(), "2^W");
+
+  assert_eq!(feed(chars("\"")).unwrap().showcmd(), "\"");
+  // Invalid register.
+  assert_eq!(feed(chars("\"%")), Err(Feed::Cancel));
+  // The `Esc` cancels the pending keys.
+  assert_eq!(
+    feed(vec![key(KeyCode::Char('g')), key(KeyCode::Esc)]),
+    Err(Feed::Cancel)
+  );
+  assert_eq!(feed(vec![key(KeyCode::Esc)]), Err(Feed::Complete));
+
+  // No register.
+  let pending = PendingKeys::default();
+  assert_eq!(
+    pending.feed(&key(KeyCode::Char('"')), false, get_prefix),
+    Feed::Complete
+  );
+  assert!(pending.is_empty());
+  assert!(!PendingKeys::new(None, Some(1)).is_empty());
+}
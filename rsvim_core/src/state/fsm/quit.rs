@@ -1,14 +1,65 @@
 //! The quit state.
 
-use crate::state::fsm::{Stateful, StatefulDataAccess, StatefulValue};
+use crate::content::message::MessageLevel;
+use crate::excommand::quit::{self, QuitAction, QuitKind};
+use crate::prelude::*;
+use crate::results::ExCommandErr;
+use crate::state::fsm::{
+  ConfirmStateful, NormalStateful, Stateful, StatefulDataAccess, StatefulValue,
+};
 use crate::state::ops::Operation;
 
+use tracing::trace;
+
 #[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
 /// The quit state.
 ///
 /// NOTE: This is an internal state to tell the editor to quit.
 pub struct QuitStateful {}
 
+impl QuitStateful {
+  /// Quit the editor or close current window, i.e. `:quit`, `ZZ`. It is refused with an error
+  /// message if there's any modified buffer, unless `bang` is given. With `confirm`, it asks to
+  /// save the modified buffers instead, i.e. `:confirm quit`.
+  ///
+  /// Returns the quit state if the editor should quit, otherwise the normal mode.
+  pub fn quit(
+    data_access: &StatefulDataAccess,
+    kind: QuitKind,
+    bang: bool,
+    confirm: bool,
+  ) -> StatefulValue {
+    let result = {
+      let tree = data_access.tree.clone();
+      let mut tree = lock!(tree);
+      let buffers = data_access.buffers.clone();
+      let mut buffers = lock!(buffers);
+      quit::execute(&mut tree, &mut buffers, kind, bang)
+    };
+    trace!("quit:{:?}", result);
+
+    match result {
+      Ok(QuitAction::QuitEditor) => {
+        StatefulValue::QuitState(QuitStateful::default())
+      }
+      Ok(QuitAction::CloseWindow) => {
+        StatefulValue::NormalMode(NormalStateful::default())
+      }
+      Err(
+        ExCommandErr::NoWriteSinceLastChange(buffer_id)
+        | ExCommandErr::NoWriteSinceLastChangeForBuffer(buffer_id, _),
+      ) if confirm => ConfirmStateful::start(data_access, kind, buffer_id),
+      Err(e) => {
+        let contents = data_access.contents.clone();
+        lock!(contents)
+          .messages_mut()
+          .add(MessageLevel::Error, &e.to_string());
+        StatefulValue::NormalMode(NormalStateful::default())
+      }
+    }
+  }
+}
+
 impl Stateful for QuitStateful {
   fn handle(&self, _data_access: StatefulDataAccess) -> StatefulValue {
     // unreachable!("Never handle QuitStateful");
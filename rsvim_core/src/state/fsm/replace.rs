@@ -0,0 +1,243 @@
+//! The replace mode, and the virtual replace mode.
+//!
+//! The typed chars overwrite the existing chars (or the screen cells in virtual replace mode), and
+//! `<BS>` restores the overwritten text, until leaving the mode or moving the cursor.
+//!
+//! See: <https://vimhelp.org/insert.txt.html#Replace-mode>.
+
+use crate::prelude::*;
+use crate::state::fsm::{
+  InsertStateful, Stateful, StatefulDataAccess, StatefulValue,
+};
+use crate::state::ops::Operation;
+use crate::state::ops::{cursor_ops, replace_ops};
+use crate::ui::canvas::CursorStyle;
+use crate::ui::tree::*;
+
+use compact_str::{CompactString, ToCompactString};
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use tracing::trace;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+/// The finite-state-machine for replace mode.
+pub struct ReplaceStateful {
+  // Virtual replace mode, i.e. `gR`.
+  virtual_replace: bool,
+
+  // The cells of the tab at cursor that are already overwritten in virtual replace mode.
+  covered: usize,
+}
+
+impl ReplaceStateful {
+  pub fn new(virtual_replace: bool) -> Self {
+    Self {
+      virtual_replace,
+      covered: 0,
+    }
+  }
+
+  /// Whether it is the virtual replace mode, i.e. `gR`.
+  pub fn is_virtual(&self) -> bool {
+    self.virtual_replace
+  }
+
+  fn get_operation(
+    &self,
+    data_access: &StatefulDataAccess,
+  ) -> Option<Operation> {
+    let event = &data_access.event;
+
+    match event {
+      Event::Key(key_event) => match key_event.kind {
+        KeyEventKind::Press => {
+          trace!("Event::key:{:?}", key_event);
+          match key_event.code {
+            KeyCode::Up => Some(Operation::CursorMoveUpBy(1)),
+            KeyCode::Down => Some(Operation::CursorMoveDownBy(1)),
+            KeyCode::Left => Some(Operation::CursorMoveLeftBy(1)),
+            KeyCode::Right => Some(Operation::CursorMoveRightBy(1)),
+            KeyCode::Home => Some(Operation::CursorMoveLeftBy(usize::MAX)),
+            KeyCode::End => Some(Operation::CursorMoveRightBy(usize::MAX)),
+            KeyCode::Char(c) => {
+              Some(Operation::CursorInsert(c.to_compact_string()))
+            }
+            KeyCode::Tab => Some(Operation::CursorInsert("\t".into())),
+            KeyCode::Enter => {
+              let tree = data_access.tree.clone();
+              let tree = lock!(tree);
+              let current_window = tree.current_window().unwrap();
+              let buffer = current_window.buffer().upgrade().unwrap();
+              let eol = format!("{}", lock!(buffer).options().end_of_line());
+              Some(Operation::CursorInsert(eol.to_compact_string()))
+            }
+            KeyCode::Backspace => Some(Operation::CursorDelete(-1)),
+            KeyCode::Esc => Some(Operation::GotoNormalMode),
+            _ => None,
+          }
+        }
+        KeyEventKind::Repeat => None,
+        KeyEventKind::Release => None,
+      },
+      _ => None,
+    }
+  }
+}
+
+impl Stateful for ReplaceStateful {
+  fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    // The typed keys are appended to the change repeated by `.`.
+    if let Event::Key(key_event) = data_access.event {
+      if key_event.kind == KeyEventKind::Press {
+        lock!(data_access.state)
+          .dot_repeat_mut()
+          .push_key(key_event);
+      }
+    }
+
+    if let Some(op) = self.get_operation(&data_access) {
+      return self.handle_op(data_access, op);
+    }
+
+    StatefulValue::ReplaceMode(*self)
+  }
+
+  fn handle_op(
+    &self,
+    data_access: StatefulDataAccess,
+    op: Operation,
+  ) -> StatefulValue {
+    match op {
+      Operation::GotoNormalMode => {
+        lock!(data_access.state).overwritten_mut().clear();
+        InsertStateful::default().goto_normal_mode(&data_access)
+      }
+      Operation::CursorMoveBy((_, _))
+      | Operation::CursorMoveUpBy(_)
+      | Operation::CursorMoveDownBy(_)
+      | Operation::CursorMoveLeftBy(_)
+      | Operation::CursorMoveRightBy(_)
+      | Operation::CursorMoveTo((_, _)) => {
+        // The overwritten text cannot be restored after moving the cursor.
+        lock!(data_access.state).overwritten_mut().clear();
+        InsertStateful::default().cursor_move(&data_access, op);
+        StatefulValue::ReplaceMode(ReplaceStateful::new(self.virtual_replace))
+      }
+      Operation::CursorInsert(payload) => {
+        self.cursor_overwrite(&data_access, payload)
+      }
+      Operation::CursorDelete(_) => self.cursor_restore(&data_access),
+      _ => unreachable!(),
+    }
+  }
+}
+
+impl ReplaceStateful {
+  /// Overwrite the chars at cursor with the typed payload.
+  pub fn cursor_overwrite(
+    &self,
+    data_access: &StatefulDataAccess,
+    payload: CompactString,
+  ) -> StatefulValue {
+    let (overwritten, covered) = {
+      let tree = data_access.tree.clone();
+      let mut tree = lock!(tree);
+      let current_window = tree.current_window_mut().unwrap();
+      let current_window_id = current_window.id();
+      let cursor_viewport = current_window.cursor_viewport();
+      let buffer = current_window.buffer().upgrade().unwrap();
+      let mut buffer = lock!(buffer);
+
+      let (overwritten, covered, (line_idx, char_idx)) = replace_ops::overwrite(
+        buffer.text_mut(),
+        (cursor_viewport.line_idx(), cursor_viewport.char_idx()),
+        &payload,
+        self.virtual_replace,
+        self.covered,
+      );
+
+      cursor_ops::_update_viewport_after_text_changed(
+        &mut tree,
+        current_window_id,
+        buffer.text(),
+      );
+      cursor_ops::cursor_move(
+        &mut tree,
+        current_window_id,
+        buffer.text(),
+        Operation::CursorMoveTo((char_idx, line_idx)),
+        true,
+      );
+      (overwritten, covered)
+    };
+    lock!(data_access.state).overwritten_mut().push(overwritten);
+
+    StatefulValue::ReplaceMode(ReplaceStateful {
+      virtual_replace: self.virtual_replace,
+      covered,
+    })
+  }
+}
+
+impl ReplaceStateful {
+  /// Restore the last overwritten text before cursor, or move cursor left if there's nothing to
+  /// restore.
+  pub fn cursor_restore(
+    &self,
+    data_access: &StatefulDataAccess,
+  ) -> StatefulValue {
+    let overwritten = lock!(data_access.state).overwritten_mut().pop();
+
+    let tree = data_access.tree.clone();
+    let mut tree = lock!(tree);
+    let current_window = tree.current_window_mut().unwrap();
+    let current_window_id = current_window.id();
+    let cursor_viewport = current_window.cursor_viewport();
+    let buffer = current_window.buffer().upgrade().unwrap();
+    let mut buffer = lock!(buffer);
+
+    let op = match overwritten {
+      Some(overwritten) => {
+        let (line_idx, char_idx) = replace_ops::restore(
+          buffer.text_mut(),
+          (cursor_viewport.line_idx(), cursor_viewport.char_idx()),
+          &overwritten,
+        );
+        cursor_ops::_update_viewport_after_text_changed(
+          &mut tree,
+          current_window_id,
+          buffer.text(),
+        );
+        Operation::CursorMoveTo((char_idx, line_idx))
+      }
+      None => Operation::CursorMoveLeftBy(1),
+    };
+    cursor_ops::cursor_move(
+      &mut tree,
+      current_window_id,
+      buffer.text(),
+      op,
+      true,
+    );
+
+    StatefulValue::ReplaceMode(ReplaceStateful::new(self.virtual_replace))
+  }
+}
+
+impl ReplaceStateful {
+  /// Start replace mode from normal mode.
+  pub fn goto_replace_mode(
+    data_access: &StatefulDataAccess,
+    virtual_replace: bool,
+  ) -> StatefulValue {
+    lock!(data_access.state).overwritten_mut().clear();
+
+    let tree = data_access.tree.clone();
+    let mut tree = lock!(tree);
+    let current_window = tree.current_window_mut().unwrap();
+    debug_assert!(current_window.cursor_mut().is_some());
+    let cursor = current_window.cursor_mut().unwrap();
+    cursor.set_style(&CursorStyle::SteadyUnderScore);
+
+    StatefulValue::ReplaceMode(ReplaceStateful::new(virtual_replace))
+  }
+}
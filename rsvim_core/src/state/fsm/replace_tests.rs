@@ -0,0 +1,105 @@
+use crate::prelude::*;
+use crate::state::fsm::StatefulValue;
+use crate::test::fsm::{
+  chars, cursor, key, make_data_access, press, text, with_keys,
+};
+use crate::test::log::init as test_log_init;
+
+use crossterm::event::KeyCode;
+
+#[test]
+fn replace1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["abc\n"]);
+
+  let stateful = press(&data_access, chars("Rxyzw"));
+  assert!(matches!(stateful, StatefulValue::ReplaceMode(_)));
+  assert_eq!(text(&buf), "xyzw\n");
+
+  // The overwritten chars are restored.
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["abc\n"]);
+  let stateful = press(
+    &data_access,
+    with_keys(
+      "Rxyzw",
+      &[KeyCode::Backspace, KeyCode::Backspace, KeyCode::Esc],
+    ),
+  );
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(text(&buf), "xyc\n");
+  assert_eq!(cursor(&data_access), (0, 2));
+}
+
+#[test]
+fn replace2() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["abc\n"]);
+
+  // The line break is inserted, and it is restored too.
+  let mut events = with_keys("Rx", &[KeyCode::Enter]);
+  events.extend(chars("y"));
+  press(&data_access, events.clone());
+  assert_eq!(text(&buf), "x\nyc\n");
+
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["abc\n"]);
+  events.extend([KeyCode::Backspace; 2].map(key));
+  press(&data_access, events);
+  assert_eq!(text(&buf), "xbc\n");
+  assert_eq!(cursor(&data_access), (0, 1));
+}
+
+#[test]
+fn replace_repeat1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["abcd\n"]);
+
+  press(&data_access, with_keys("Rxy", &[KeyCode::Esc]));
+  assert_eq!(text(&buf), "xycd\n");
+  press(&data_access, chars("."));
+  assert_eq!(text(&buf), "xyxy\n");
+}
+
+#[test]
+fn virtual_replace1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["\tb\n"]);
+
+  // The typed chars are inserted before the tab, until they fill its cells.
+  let stateful = press(&data_access, chars("gRxy"));
+  assert!(matches!(stateful, StatefulValue::ReplaceMode(r) if r.is_virtual()));
+  assert_eq!(text(&buf), "xy\tb\n");
+}
+
+#[test]
+fn replace_char1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello world\n"]);
+
+  press(&data_access, chars("3rx"));
+  assert_eq!(text(&buf), "xxxlo world\n");
+  assert_eq!(cursor(&data_access), (0, 2));
+  press(&data_access, chars("w."));
+  assert_eq!(text(&buf), "xxxlo xxxld\n");
+
+  // Not enough chars.
+  press(&data_access, chars("$9ry"));
+  assert_eq!(text(&buf), "xxxlo xxxld\n");
+}
+
+#[test]
+fn replace_char2() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["ab cd\n"]);
+
+  press(&data_access, with_keys("f r", &[KeyCode::Enter]));
+  assert_eq!(text(&buf), "ab\ncd\n");
+  assert_eq!(cursor(&data_access), (1, 0));
+}
@@ -1,21 +1,320 @@
 //! The select mode.
+//!
+//! It shares the selection with visual mode (see [`Buffer::selection`](crate::buf::Buffer)), but
+//! typing a printable char replaces the selected text and goes to insert mode, like the selection
+//! of most GUI editors. The selection is started by `gh`, `gH`, or the shifted special keys (see
+//! the 'keymodel' and 'selectmode' options), or set by plugins, i.e. the snippet placeholders.
+//! The `<C-g>` toggles between visual mode and select mode.
+//!
+//! See: <https://vimhelp.org/visual.txt.html#Select-mode>.
 
-use crate::state::fsm::{Stateful, StatefulDataAccess, StatefulValue};
-use crate::state::ops::Operation;
+use crate::buf::selection::{Selection, SelectionKind};
+use crate::content::register::BLACK_HOLE_REGISTER;
+use crate::prelude::*;
+use crate::state::fsm::operator_pending;
+use crate::state::fsm::{
+  InsertStateful, NormalStateful, Stateful, StatefulDataAccess, StatefulValue,
+  VisualStateful,
+};
+use crate::state::ops::cursor_ops;
+use crate::state::ops::{Operation, Operator};
+use crate::state::repeat::{RepeatKind, RepeatRegion, RepeatableChange};
+use crate::ui::canvas::CursorStyle;
+use crate::ui::tree::*;
+
+use compact_str::{CompactString, ToCompactString};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use tracing::trace;
 
 #[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
 /// The select editing mode.
 pub struct SelectStateful {}
 
+/// The motion of the special keys used by the 'keymodel' option, i.e. the arrow keys, `<Home>`
+/// and `<End>`.
+pub fn get_special_key_motion(code: KeyCode) -> Option<Operation> {
+  match code {
+    KeyCode::Up => Some(Operation::CursorMoveUpBy(1)),
+    KeyCode::Down => Some(Operation::CursorMoveDownBy(1)),
+    KeyCode::Left => Some(Operation::CursorMoveLeftBy(1)),
+    KeyCode::Right => Some(Operation::CursorMoveRightBy(1)),
+    KeyCode::Home => Some(Operation::CursorMoveLeftBy(usize::MAX)),
+    KeyCode::End => Some(Operation::CursorMoveRightBy(usize::MAX)),
+    _ => None,
+  }
+}
+
+/// Whether the key starts the selection, i.e. the shifted special keys (`<S-Right>`) if the
+/// 'keymodel' option contains `startsel`.
+pub fn is_start_selection_key(
+  data_access: &StatefulDataAccess,
+  key_event: &KeyEvent,
+) -> bool {
+  key_event.kind == KeyEventKind::Press
+    && key_event.modifiers.contains(KeyModifiers::SHIFT)
+    && get_special_key_motion(key_event.code).is_some()
+    && {
+      let state = data_access.state.clone();
+      let state = lock!(state);
+      state.global_options().key_model_has("startsel")
+    }
+}
+
+/// Start the selection with the shifted special key in normal mode, i.e. `<S-Right>`. It goes to
+/// select mode if the 'selectmode' option contains `key`, otherwise visual mode.
+///
+/// Returns `None` if the key doesn't start the selection, see [`is_start_selection_key`].
+pub fn start_selection_by_key(
+  data_access: &StatefulDataAccess,
+  key_event: &KeyEvent,
+) -> Option<StatefulValue> {
+  if !is_start_selection_key(data_access, key_event) {
+    return None;
+  }
+  let op = get_special_key_motion(key_event.code)?;
+  let select = {
+    let state = data_access.state.clone();
+    let state = lock!(state);
+    state.global_options().select_mode_has("key")
+  };
+
+  let stateful = if select {
+    SelectStateful::goto_select_mode(data_access, SelectionKind::Char)
+  } else {
+    NormalStateful::default().goto_visual_mode(data_access, SelectionKind::Char)
+  };
+  VisualStateful::default().cursor_move(data_access, op);
+  Some(stateful)
+}
+
+/// Whether the key stops the selection, i.e. the unshifted special keys (`<Right>`) if the
+/// 'keymodel' option contains `stopsel`.
+pub fn is_stop_selection_key(
+  data_access: &StatefulDataAccess,
+  key_event: &KeyEvent,
+) -> bool {
+  key_event.kind == KeyEventKind::Press
+    && !key_event.modifiers.contains(KeyModifiers::SHIFT)
+    && get_special_key_motion(key_event.code).is_some()
+    && {
+      let state = data_access.state.clone();
+      let state = lock!(state);
+      state.global_options().key_model_has("stopsel")
+    }
+}
+
+impl SelectStateful {
+  fn get_operation(
+    &self,
+    data_access: &StatefulDataAccess,
+  ) -> Option<Operation> {
+    match &data_access.event {
+      Event::Key(key_event) => match key_event.kind {
+        KeyEventKind::Press => {
+          trace!("Event::key:{:?}", key_event);
+          let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+          match key_event.code {
+            KeyCode::Char('g') if ctrl => Some(Operation::SelectToggle),
+            KeyCode::Char('h') if ctrl => Some(Operation::CursorDelete(-1)),
+            KeyCode::Char('c') if ctrl => Some(Operation::GotoNormalMode),
+            KeyCode::Char(_) if ctrl => None,
+            KeyCode::Char(c) => {
+              Some(Operation::CursorInsert(c.to_compact_string()))
+            }
+            KeyCode::Tab => Some(Operation::CursorInsert("\t".into())),
+            KeyCode::Enter => {
+              let tree = data_access.tree.clone();
+              let tree = lock!(tree);
+              let current_window = tree.current_window().unwrap();
+              let buffer = current_window.buffer().upgrade().unwrap();
+              let eol = format!("{}", lock!(buffer).options().end_of_line());
+              Some(Operation::CursorInsert(eol.to_compact_string()))
+            }
+            KeyCode::Backspace => Some(Operation::CursorDelete(-1)),
+            KeyCode::Delete => Some(Operation::CursorDelete(1)),
+            KeyCode::Esc => Some(Operation::GotoNormalMode),
+            code => get_special_key_motion(code),
+          }
+        }
+        KeyEventKind::Repeat => None,
+        KeyEventKind::Release => None,
+      },
+      _ => None,
+    }
+  }
+}
+
 impl Stateful for SelectStateful {
-  fn handle(&self, _data_access: StatefulDataAccess) -> StatefulValue {
+  fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    if let Some(op) = self.get_operation(&data_access) {
+      return self.handle_op(data_access, op);
+    }
+
     StatefulValue::SelectMode(SelectStateful::default())
   }
+
   fn handle_op(
     &self,
-    _data_access: StatefulDataAccess,
-    _op: Operation,
+    data_access: StatefulDataAccess,
+    op: Operation,
+  ) -> StatefulValue {
+    match op {
+      Operation::CursorMoveBy((_, _))
+      | Operation::CursorMoveUpBy(_)
+      | Operation::CursorMoveDownBy(_)
+      | Operation::CursorMoveLeftBy(_)
+      | Operation::CursorMoveRightBy(_)
+      | Operation::CursorMoveTo((_, _)) => self.cursor_move(&data_access, op),
+      Operation::SelectToggle => {
+        StatefulValue::VisualMode(VisualStateful::default())
+      }
+      Operation::GotoNormalMode => {
+        VisualStateful::default().goto_normal_mode(&data_access)
+      }
+      Operation::CursorInsert(payload) => {
+        self.replace_selection(data_access, payload)
+      }
+      Operation::CursorDelete(_) => self.delete_selection(&data_access),
+      _ => unreachable!(),
+    }
+  }
+}
+
+impl SelectStateful {
+  /// Start the selection at cursor and goto select mode, i.e. `gh`, `gH`.
+  pub fn goto_select_mode(
+    data_access: &StatefulDataAccess,
+    kind: SelectionKind,
+  ) -> StatefulValue {
+    NormalStateful::default().goto_visual_mode(data_access, kind);
+    StatefulValue::SelectMode(SelectStateful::default())
+  }
+
+  /// Cursor move in current window, the selection follows the cursor. The unshifted special keys
+  /// stop the selection and goto normal mode if the 'keymodel' option contains `stopsel`.
+  pub fn cursor_move(
+    &self,
+    data_access: &StatefulDataAccess,
+    op: Operation,
   ) -> StatefulValue {
+    if let Event::Key(key_event) = &data_access.event {
+      if is_stop_selection_key(data_access, key_event) {
+        VisualStateful::default().goto_normal_mode(data_access);
+        return NormalStateful::default().cursor_move(data_access, op);
+      }
+    }
+    VisualStateful::default().cursor_move(data_access, op);
     StatefulValue::SelectMode(SelectStateful::default())
   }
+
+  // The change repeated by `.`, i.e. the `keys` in visual mode on the region with the same size as
+  // the `selection`. The deleted text is not saved in registers.
+  fn _repeatable_change(
+    selection: &Selection,
+    keys: Vec<KeyEvent>,
+  ) -> RepeatableChange {
+    RepeatableChange::new(
+      RepeatKind::Visual(RepeatRegion::from_selection(selection)),
+      Some(BLACK_HOLE_REGISTER),
+      None,
+      keys,
+    )
+  }
+
+  /// Delete the selection and goto normal mode, i.e. `<BS>` in select mode. The deleted text
+  /// doesn't overwrite the registers.
+  pub fn delete_selection(
+    &self,
+    data_access: &StatefulDataAccess,
+  ) -> StatefulValue {
+    let selection = {
+      let tree = data_access.tree.clone();
+      let tree = lock!(tree);
+      let buffer = tree.current_window().unwrap().buffer().upgrade().unwrap();
+      lock!(buffer).take_selection()
+    };
+    match selection {
+      Some(selection) => {
+        let keys = vec![KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE)];
+        operator_pending::apply_operator(
+          data_access,
+          &selection,
+          Operator::Delete,
+          Some(BLACK_HOLE_REGISTER),
+          Some(Self::_repeatable_change(&selection, keys)),
+        )
+      }
+      None => StatefulValue::NormalMode(NormalStateful::default()),
+    }
+  }
+
+  /// Replace the selection with the typed char and goto insert mode, i.e. typing a printable char
+  /// in select mode. The deleted text doesn't overwrite the registers, so the yanked text can
+  /// still be put into the selection.
+  pub fn replace_selection(
+    &self,
+    data_access: StatefulDataAccess,
+    payload: CompactString,
+  ) -> StatefulValue {
+    let selection = {
+      let tree = data_access.tree.clone();
+      let tree = lock!(tree);
+      let buffer = tree.current_window().unwrap().buffer().upgrade().unwrap();
+      lock!(buffer).take_selection()
+    };
+    let selection = match selection {
+      Some(selection) => selection,
+      None => return StatefulValue::NormalMode(NormalStateful::default()),
+    };
+
+    let keys = vec![KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE)];
+    operator_pending::apply_operator(
+      &data_access,
+      &selection,
+      Operator::Change,
+      Some(BLACK_HOLE_REGISTER),
+      Some(Self::_repeatable_change(&selection, keys)),
+    );
+
+    // The typed char is the first key in insert mode.
+    if let Event::Key(key_event) = data_access.event {
+      lock!(data_access.state)
+        .dot_repeat_mut()
+        .push_key(key_event);
+    }
+    InsertStateful::default().cursor_insert(&data_access, payload)
+  }
+}
+
+impl SelectStateful {
+  /// Select the text from `anchor` to `cursor` in current window, the positions are
+  /// `(line_idx, char_idx)` and they're clamped into the buffer text. It is used by plugins, i.e.
+  /// the snippet placeholders, the caller switches to select mode after that.
+  pub fn select_text(
+    tree: &mut Tree,
+    anchor: (usize, usize),
+    cursor: (usize, usize),
+  ) {
+    let current_window = tree.current_window_mut().unwrap();
+    let current_window_id = current_window.id();
+    let buffer = current_window.buffer().upgrade().unwrap();
+    let mut buffer = lock!(buffer);
+
+    let selection =
+      Selection::new(SelectionKind::Char, anchor, cursor).clamp(buffer.text());
+    buffer.set_selection(Some(selection));
+    let (line_idx, char_idx) = selection.cursor();
+    cursor_ops::cursor_move(
+      tree,
+      current_window_id,
+      buffer.text(),
+      Operation::CursorMoveTo((char_idx, line_idx)),
+      false,
+    );
+
+    let current_window = tree.current_window_mut().unwrap();
+    if let Some(cursor) = current_window.cursor_mut() {
+      cursor.set_style(&CursorStyle::SteadyBlock);
+    }
+  }
 }
@@ -0,0 +1,199 @@
+use super::select::*;
+
+use crate::buf::selection::{Selection, SelectionKind};
+use crate::prelude::*;
+use crate::state::fsm::StatefulValue;
+use crate::test::fsm::{
+  chars, ctrl, cursor, key, make_data_access, press, register, selection,
+  shift, text,
+};
+use crate::test::log::init as test_log_init;
+
+use crossterm::event::KeyCode;
+
+#[test]
+fn select1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello\n"]);
+
+  let stateful = press(&data_access, chars("ghl"));
+  assert!(matches!(stateful, StatefulValue::SelectMode(_)));
+  assert_eq!(
+    selection(&buf),
+    Some(Selection::new(SelectionKind::Char, (0, 0), (0, 1)))
+  );
+
+  // The typed chars replace the selection.
+  let (buf, contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello\n"]);
+  let stateful = press(&data_access, chars("ghlxy"));
+  assert!(matches!(stateful, StatefulValue::InsertMode(_)));
+  assert_eq!(text(&buf), "xyllo\n");
+  assert_eq!(cursor(&data_access), (0, 2));
+  assert_eq!(selection(&buf), None);
+  assert_eq!(register(&contents, '"'), None);
+}
+
+#[test]
+fn select2() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello\n", "world\n"]);
+
+  let stateful = press(&data_access, chars("gH"));
+  assert!(matches!(stateful, StatefulValue::SelectMode(_)));
+  assert_eq!(selection(&buf).unwrap().kind(), SelectionKind::Line);
+
+  // The `<C-c>` stops the selection.
+  let mut events = chars("gH");
+  events.push(ctrl('c'));
+  let stateful = press(&data_access, events);
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(selection(&buf), None);
+}
+
+#[test]
+fn toggle1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello\n"]);
+
+  let mut events = chars("vl");
+  events.push(ctrl('g'));
+  let stateful = press(&data_access, events.clone());
+  assert!(matches!(stateful, StatefulValue::SelectMode(_)));
+
+  events.push(ctrl('g'));
+  let stateful = press(&data_access, events);
+  assert!(matches!(stateful, StatefulValue::VisualMode(_)));
+  assert!(selection(&buf).is_some());
+}
+
+#[test]
+fn delete1() {
+  test_log_init();
+  let (buf, contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello world\n"]);
+
+  // The deleted text doesn't overwrite the registers.
+  let mut events = chars("yiwwghl");
+  events.push(key(KeyCode::Backspace));
+  let stateful = press(&data_access, events);
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(text(&buf), "hello rld\n");
+  assert_eq!(register(&contents, '"'), Some("hello".to_string()));
+}
+
+#[test]
+fn repeat1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["abc abc\n"]);
+
+  let mut events = chars("ghlx");
+  events.push(key(KeyCode::Esc));
+  press(&data_access, events);
+  assert_eq!(text(&buf), "xc abc\n");
+
+  press(&data_access, chars("w."));
+  assert_eq!(text(&buf), "xc xc\n");
+}
+
+#[test]
+fn key_model1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello\n"]);
+
+  // The shifted keys are only motions by default.
+  let stateful = press(&data_access, vec![shift(KeyCode::Right)]);
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(selection(&buf), None);
+
+  lock!(data_access.state)
+    .global_options_mut()
+    .set_key_model("startsel");
+  let stateful = press(&data_access, vec![shift(KeyCode::Right)]);
+  assert!(matches!(stateful, StatefulValue::VisualMode(_)));
+  assert_eq!(
+    selection(&buf),
+    Some(Selection::new(SelectionKind::Char, (0, 1), (0, 2)))
+  );
+}
+
+#[test]
+fn key_model2() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello\n"]);
+  {
+    let mut state = lock!(data_access.state);
+    state.global_options_mut().set_key_model("startsel,stopsel");
+    state.global_options_mut().set_select_mode("key");
+  }
+
+  let stateful = press(
+    &data_access,
+    vec![shift(KeyCode::Right), shift(KeyCode::Right)],
+  );
+  assert!(matches!(stateful, StatefulValue::SelectMode(_)));
+  assert_eq!(
+    selection(&buf),
+    Some(Selection::new(SelectionKind::Char, (0, 0), (0, 2)))
+  );
+
+  // The unshifted keys stop the selection.
+  let stateful = press(
+    &data_access,
+    vec![shift(KeyCode::Left), key(KeyCode::Right)],
+  );
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(selection(&buf), None);
+  assert_eq!(cursor(&data_access), (0, 2));
+
+  // In insert mode.
+  let stateful = press(
+    &data_access,
+    vec![key(KeyCode::Char('i')), shift(KeyCode::End)],
+  );
+  assert!(matches!(stateful, StatefulValue::SelectMode(_)));
+  assert_eq!(
+    selection(&buf),
+    Some(Selection::new(SelectionKind::Char, (0, 2), (0, 4)))
+  );
+}
+
+#[test]
+fn select_mode1() {
+  test_log_init();
+  let (_buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello\n"]);
+  lock!(data_access.state)
+    .global_options_mut()
+    .set_select_mode("cmd");
+
+  let stateful = press(&data_access, chars("V"));
+  assert!(matches!(stateful, StatefulValue::SelectMode(_)));
+}
+
+#[test]
+fn select_text1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello(name)\n"]);
+
+  SelectStateful::select_text(&mut lock!(data_access.tree), (0, 6), (0, 9));
+  assert_eq!(
+    selection(&buf),
+    Some(Selection::new(SelectionKind::Char, (0, 6), (0, 9)))
+  );
+  assert_eq!(cursor(&data_access), (0, 9));
+
+  // Clamped into the text.
+  SelectStateful::select_text(&mut lock!(data_access.tree), (0, 6), (9, 99));
+  assert_eq!(
+    selection(&buf),
+    Some(Selection::new(SelectionKind::Char, (0, 6), (1, 0)))
+  );
+}
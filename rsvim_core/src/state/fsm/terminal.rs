@@ -1,21 +1,171 @@
 //! The terminal mode.
+//!
+//! The keys (and the pasted text) are sent to the program running in the terminal buffer, except
+//! `<C-\><C-n>` that goes to normal mode, then the terminal output can be scrolled, searched and
+//! copied like any other buffers. The `i` and `a` in normal mode go back to terminal mode.
+//!
+//! See: <https://vimhelp.org/terminal.txt.html#Terminal-mode>.
 
-use crate::state::fsm::{Stateful, StatefulDataAccess, StatefulValue};
+use crate::prelude::*;
+use crate::state::fsm::{
+  NormalStateful, Stateful, StatefulDataAccess, StatefulValue,
+};
 use crate::state::ops::Operation;
+use crate::state::ops::cursor_ops;
+use crate::terminal::{Terminal, encode_key};
+use crate::ui::tree::*;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use tracing::trace;
 
 #[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
 /// The terminal editing mode.
-pub struct TerminalStateful {}
+pub struct TerminalStateful {
+  /// The `<C-\>` waits for the next key, i.e. `<C-\><C-n>`.
+  ctrl_backslash: bool,
+}
+
+// Whether the key is `<C-{c}>`.
+fn _is_ctrl(key_event: &KeyEvent, c: char) -> bool {
+  key_event.modifiers.contains(KeyModifiers::CONTROL)
+    && key_event.code == KeyCode::Char(c)
+}
 
 impl Stateful for TerminalStateful {
-  fn handle(&self, _data_access: StatefulDataAccess) -> StatefulValue {
+  fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    let key_event = match &data_access.event {
+      Event::Key(key_event) if key_event.kind != KeyEventKind::Release => {
+        *key_event
+      }
+      Event::Paste(text) => {
+        // The pending `<C-\>` is sent before the pasted text.
+        if self.ctrl_backslash {
+          self._write(&data_access, &[0x1c]);
+        }
+        self._paste(&data_access, text);
+        return StatefulValue::TerminalMode(TerminalStateful::default());
+      }
+      _ => return StatefulValue::TerminalMode(*self),
+    };
+    trace!("Event::key:{:?}", key_event);
+
+    if self.ctrl_backslash {
+      if _is_ctrl(&key_event, 'n') {
+        return self.handle_op(data_access, Operation::GotoNormalMode);
+      }
+      // The `<C-\>` is sent along with the next key.
+      let mut bytes = vec![0x1c];
+      if !_is_ctrl(&key_event, '\\') {
+        bytes.extend(self._encode(&data_access, &key_event));
+      }
+      self._write(&data_access, &bytes);
+      return StatefulValue::TerminalMode(TerminalStateful::default());
+    }
+
+    if _is_ctrl(&key_event, '\\') {
+      return StatefulValue::TerminalMode(TerminalStateful {
+        ctrl_backslash: true,
+      });
+    }
+
+    let bytes = self._encode(&data_access, &key_event);
+    self._write(&data_access, &bytes);
     StatefulValue::TerminalMode(TerminalStateful::default())
   }
+
   fn handle_op(
     &self,
-    _data_access: StatefulDataAccess,
-    _op: Operation,
+    data_access: StatefulDataAccess,
+    op: Operation,
   ) -> StatefulValue {
-    StatefulValue::TerminalMode(TerminalStateful::default())
+    match op {
+      Operation::GotoNormalMode => self.goto_normal_mode(&data_access),
+      _ => StatefulValue::TerminalMode(TerminalStateful::default()),
+    }
+  }
+
+  fn is_literal_pending(&self) -> bool {
+    self.ctrl_backslash
+  }
+}
+
+impl TerminalStateful {
+  // Encode the key with the cursor keys mode of the terminal in current window.
+  fn _encode(
+    &self,
+    data_access: &StatefulDataAccess,
+    key_event: &KeyEvent,
+  ) -> Vec<u8> {
+    let tree = data_access.tree.clone();
+    let tree = lock!(tree);
+    let application_cursor_keys = tree
+      .current_window()
+      .and_then(|window| window.buffer().upgrade())
+      .map(|buffer| {
+        lock!(buffer)
+          .terminal()
+          .as_ref()
+          .map(|terminal| terminal.grid().application_cursor_keys())
+          .unwrap_or(false)
+      })
+      .unwrap_or(false);
+    encode_key(key_event, application_cursor_keys)
+  }
+
+  // Run `f` on the terminal of the buffer in current window.
+  fn _with_terminal(
+    &self,
+    data_access: &StatefulDataAccess,
+    f: impl FnOnce(&mut Terminal),
+  ) {
+    let tree = data_access.tree.clone();
+    let tree = lock!(tree);
+    if let Some(buffer) = tree
+      .current_window()
+      .and_then(|window| window.buffer().upgrade())
+    {
+      if let Some(terminal) = lock!(buffer).terminal_mut() {
+        f(terminal);
+      }
+    }
+  }
+
+  // Write the bytes to the program of the terminal buffer in current window.
+  fn _write(&self, data_access: &StatefulDataAccess, bytes: &[u8]) {
+    if bytes.is_empty() {
+      return;
+    }
+    self._with_terminal(data_access, |terminal| terminal.write(bytes));
+  }
+
+  // Write the pasted text to the program of the terminal buffer in current window.
+  fn _paste(&self, data_access: &StatefulDataAccess, text: &str) {
+    if text.is_empty() {
+      return;
+    }
+    self._with_terminal(data_access, |terminal| terminal.paste(text));
+  }
+
+  /// Goto normal mode, i.e. `<C-\><C-n>`. The cursor stays at the terminal cursor, but it cannot
+  /// stay after the end of line.
+  pub fn goto_normal_mode(
+    &self,
+    data_access: &StatefulDataAccess,
+  ) -> StatefulValue {
+    let tree = data_access.tree.clone();
+    let mut tree = lock!(tree);
+    let current_window = tree.current_window().unwrap();
+    let current_window_id = current_window.id();
+    let buffer = current_window.buffer().upgrade().unwrap();
+    let buffer = lock!(buffer);
+    cursor_ops::cursor_move(
+      &mut tree,
+      current_window_id,
+      buffer.text(),
+      Operation::CursorMoveBy((0, 0)),
+      false,
+    );
+
+    StatefulValue::NormalMode(NormalStateful::default())
   }
 }
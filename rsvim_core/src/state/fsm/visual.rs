@@ -1,21 +1,476 @@
 //! The visual mode.
+//!
+//! The selection is anchored on the buffer (see [`Buffer::selection`](crate::buf::Buffer)), it
+//! starts from the cursor position when entering visual mode, and its other end moves with the
+//! cursor. When leaving visual mode, the selection is saved as the last selection, i.e. `gv` and
+//! the `'<`, `'>` marks.
+//!
+//! The text objects (i.e. `iw`, `a(`) select the object around the cursor, or extend the
+//! selection if it is not a single char.
 
+use crate::buf::selection::{Selection, SelectionKind};
+use crate::prelude::*;
+use crate::state::fsm::command_line_edit;
+use crate::state::fsm::motion;
+use crate::state::fsm::operator_pending;
+use crate::state::fsm::pending::{Feed, PendingKeys, PrefixKey};
+use crate::state::fsm::select;
 use crate::state::fsm::{Stateful, StatefulDataAccess, StatefulValue};
-use crate::state::ops::Operation;
+use crate::state::ops::{CaseChange, Operation, Operator};
+use crate::state::ops::{cursor_ops, motion_ops, visual_ops};
+use crate::state::repeat::{RepeatKind, RepeatRegion, RepeatableChange};
+use crate::ui::tree::*;
+
+use compact_str::CompactString;
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use tracing::trace;
+
+/// The range of the selection pre-filled in command-line, i.e. `:` in visual mode.
+pub const SELECTION_RANGE: &str = "'<,'>";
 
 #[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
 /// The visual editing mode.
-pub struct VisualStateful {}
+pub struct VisualStateful {
+  // The register, count and prefix key typed before the command, i.e. `"a` in `"ay`, `g` in
+  // `vge`, `i` in `viw`.
+  pending: PendingKeys,
+}
+
+// The prefix keys in visual mode.
+fn _get_prefix(key_event: &KeyEvent) -> Option<PrefixKey> {
+  if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+    return None;
+  }
+  match key_event.code {
+    KeyCode::Char('g') => Some(PrefixKey::G),
+    KeyCode::Char('i') => Some(PrefixKey::Object(true)),
+    KeyCode::Char('a') => Some(PrefixKey::Object(false)),
+    code => motion::get_char_find_kind(code).map(PrefixKey::CharFind),
+  }
+}
+
+impl VisualStateful {
+  fn get_operation(
+    &self,
+    data_access: &StatefulDataAccess,
+    event: Event,
+  ) -> Option<Operation> {
+    match event {
+      Event::Key(key_event) => match key_event.kind {
+        KeyEventKind::Press => {
+          trace!("Event::key:{:?}", key_event);
+          let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+          let count = self.pending.count();
+          let pending_g = self.pending.prefix() == Some(PrefixKey::G);
+          let motion = if ctrl {
+            None
+          } else if pending_g {
+            motion::get_g_motion(data_access, key_event.code, count)
+          } else {
+            motion::get_motion(data_access, key_event.code, count)
+          };
+          if motion.is_some() || pending_g {
+            return motion;
+          }
+          match key_event.code {
+            KeyCode::Char('v') if ctrl => {
+              Some(Operation::GotoVisualMode(SelectionKind::Block))
+            }
+            KeyCode::Char('c') if ctrl => Some(Operation::GotoNormalMode),
+            KeyCode::Char('g') if ctrl => Some(Operation::SelectToggle),
+            KeyCode::Char(_) if ctrl => None,
+            KeyCode::Char('v') => {
+              Some(Operation::GotoVisualMode(SelectionKind::Char))
+            }
+            KeyCode::Char('V') => {
+              Some(Operation::GotoVisualMode(SelectionKind::Line))
+            }
+            KeyCode::Char('o') => Some(Operation::VisualSwapEnds),
+            KeyCode::Char(':') => Some(Operation::GotoCommandLineExMode),
+            // The `x`, `<Del>` and `s` are the default mappings.
+            KeyCode::Char('d') => {
+              Some(Operation::VisualOperator(Operator::Delete))
+            }
+            KeyCode::Char('y') => {
+              Some(Operation::VisualOperator(Operator::Yank))
+            }
+            KeyCode::Char('c') => {
+              Some(Operation::VisualOperator(Operator::Change))
+            }
+            KeyCode::Char('>') => {
+              Some(Operation::VisualOperator(Operator::ShiftRight))
+            }
+            KeyCode::Char('<') => {
+              Some(Operation::VisualOperator(Operator::ShiftLeft))
+            }
+            KeyCode::Char('~') => Some(Operation::VisualOperator(
+              Operator::ChangeCase(CaseChange::Toggle),
+            )),
+            KeyCode::Char('u') => Some(Operation::VisualOperator(
+              Operator::ChangeCase(CaseChange::Lower),
+            )),
+            KeyCode::Char('U') => Some(Operation::VisualOperator(
+              Operator::ChangeCase(CaseChange::Upper),
+            )),
+            KeyCode::Char('J') => Some(Operation::VisualJoinLines),
+            KeyCode::Esc => Some(Operation::GotoNormalMode),
+            _ => None,
+          }
+        }
+        KeyEventKind::Repeat => None,
+        KeyEventKind::Release => None,
+      },
+      _ => None,
+    }
+  }
+}
 
 impl Stateful for VisualStateful {
-  fn handle(&self, _data_access: StatefulDataAccess) -> StatefulValue {
+  fn handle(&self, data_access: StatefulDataAccess) -> StatefulValue {
+    let event = data_access.event.clone();
+
+    if let Event::Key(key_event) = &event {
+      if key_event.kind == KeyEventKind::Press {
+        // The unshifted special keys stop the selection, see the 'keymodel' option.
+        if self.pending.is_empty()
+          && select::is_stop_selection_key(&data_access, key_event)
+        {
+          if let Some(op) = select::get_special_key_motion(key_event.code) {
+            self.goto_normal_mode(&data_access);
+            return super::NormalStateful::default()
+              .cursor_move(&data_access, op);
+          }
+        }
+
+        match self.pending.feed(key_event, true, _get_prefix) {
+          Feed::Pending(pending) => {
+            return StatefulValue::VisualMode(VisualStateful { pending });
+          }
+          Feed::Cancel => {
+            return StatefulValue::VisualMode(VisualStateful::default());
+          }
+          Feed::Complete => {}
+        }
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        match (self.pending.prefix(), key_event.code) {
+          (Some(_), _) if ctrl => {
+            return StatefulValue::VisualMode(VisualStateful::default());
+          }
+          (Some(PrefixKey::Object(inner)), KeyCode::Char(key)) => {
+            return self.select_text_object(&data_access, key, inner);
+          }
+          (Some(PrefixKey::CharFind(kind)), KeyCode::Char(c)) => {
+            let op = motion::char_find_motion(
+              &data_access,
+              kind,
+              c,
+              self.pending.count(),
+            );
+            return self.handle_op(data_access, op);
+          }
+          (Some(PrefixKey::Object(_) | PrefixKey::CharFind(_)), _) => {
+            return StatefulValue::VisualMode(VisualStateful::default());
+          }
+          _ => {}
+        }
+      }
+    }
+
+    if let Some(op) = self.get_operation(&data_access, event) {
+      return self.handle_op(data_access, op);
+    }
+
     StatefulValue::VisualMode(VisualStateful::default())
   }
+
   fn handle_op(
     &self,
-    _data_access: StatefulDataAccess,
-    _op: Operation,
+    data_access: StatefulDataAccess,
+    op: Operation,
+  ) -> StatefulValue {
+    match op {
+      Operation::CursorMoveBy((_, _))
+      | Operation::CursorMoveUpBy(_)
+      | Operation::CursorMoveDownBy(_)
+      | Operation::CursorMoveLeftBy(_)
+      | Operation::CursorMoveRightBy(_)
+      | Operation::CursorMoveTo((_, _)) => self.cursor_move(&data_access, op),
+      op if motion_ops::is_motion(&op) => self.cursor_move(&data_access, op),
+      Operation::GotoVisualMode(kind) => self.switch_kind(&data_access, kind),
+      Operation::VisualSwapEnds => self.swap_ends(&data_access),
+      Operation::SelectToggle => {
+        StatefulValue::SelectMode(super::SelectStateful::default())
+      }
+      Operation::GotoNormalMode => self.goto_normal_mode(&data_access),
+      Operation::GotoCommandLineExMode => {
+        self.goto_command_line_ex_mode(&data_access)
+      }
+      Operation::VisualOperator(operator) => {
+        self.operate(&data_access, operator)
+      }
+      Operation::VisualJoinLines => self.join_lines(&data_access),
+      _ => unreachable!(),
+    }
+  }
+
+  fn handle_timeout(&self) -> Option<StatefulValue> {
+    if self.pending.is_empty() {
+      None
+    } else {
+      Some(StatefulValue::VisualMode(VisualStateful::default()))
+    }
+  }
+
+  fn showcmd(&self) -> String {
+    self.pending.showcmd()
+  }
+
+  fn is_literal_pending(&self) -> bool {
+    self.pending.is_literal_pending()
+  }
+}
+
+impl VisualStateful {
+  /// Cursor move in current window, the selection follows the cursor.
+  pub fn cursor_move(
+    &self,
+    data_access: &StatefulDataAccess,
+    op: Operation,
+  ) -> StatefulValue {
+    let tree = data_access.tree.clone();
+    let mut tree = lock!(tree);
+    let current_window = tree.current_window_mut().unwrap();
+    let current_window_id = current_window.id();
+    let buffer = current_window.buffer().upgrade().unwrap();
+    let mut buffer = lock!(buffer);
+    let cursor_viewport = current_window.cursor_viewport();
+    let from = (cursor_viewport.line_idx(), cursor_viewport.char_idx());
+
+    cursor_ops::cursor_move(
+      &mut tree,
+      current_window_id,
+      buffer.text(),
+      op.clone(),
+      false,
+    );
+
+    let cursor_viewport = tree.current_window().unwrap().cursor_viewport();
+    let to = (cursor_viewport.line_idx(), cursor_viewport.char_idx());
+    if motion::is_failed_motion(&op, from, to) {
+      lock!(data_access.state).macros_mut().fail();
+    }
+
+    if let Some(selection) = buffer.selection_mut() {
+      selection
+        .set_cursor((cursor_viewport.line_idx(), cursor_viewport.char_idx()));
+    }
+    StatefulValue::VisualMode(VisualStateful::default())
+  }
+
+  /// Switch the selection type, or goto normal mode if it is the same type, i.e. `v`, `V` and
+  /// `<C-V>` in visual mode.
+  pub fn switch_kind(
+    &self,
+    data_access: &StatefulDataAccess,
+    kind: SelectionKind,
+  ) -> StatefulValue {
+    {
+      let tree = data_access.tree.clone();
+      let tree = lock!(tree);
+      let buffer = tree.current_window().unwrap().buffer().upgrade().unwrap();
+      let mut buffer = lock!(buffer);
+      if let Some(selection) = buffer.selection_mut() {
+        if selection.kind() != kind {
+          selection.set_kind(kind);
+          return StatefulValue::VisualMode(VisualStateful::default());
+        }
+      }
+    }
+    self.goto_normal_mode(data_access)
+  }
+
+  /// Swap the two ends of the selection, and move cursor to the other end, i.e. `o` in visual
+  /// mode.
+  pub fn swap_ends(&self, data_access: &StatefulDataAccess) -> StatefulValue {
+    let tree = data_access.tree.clone();
+    let mut tree = lock!(tree);
+    let current_window = tree.current_window_mut().unwrap();
+    let current_window_id = current_window.id();
+    let buffer = current_window.buffer().upgrade().unwrap();
+    let mut buffer = lock!(buffer);
+
+    if let Some(selection) = buffer.selection_mut() {
+      selection.swap();
+      let (line_idx, char_idx) = selection.cursor();
+      cursor_ops::cursor_move(
+        &mut tree,
+        current_window_id,
+        buffer.text(),
+        Operation::CursorMoveTo((char_idx, line_idx)),
+        false,
+      );
+    }
+    StatefulValue::VisualMode(VisualStateful::default())
+  }
+
+  /// Stop the selection and goto normal mode.
+  pub fn goto_normal_mode(
+    &self,
+    data_access: &StatefulDataAccess,
+  ) -> StatefulValue {
+    let tree = data_access.tree.clone();
+    let tree = lock!(tree);
+    let buffer = tree.current_window().unwrap().buffer().upgrade().unwrap();
+    lock!(buffer).take_selection();
+    StatefulValue::NormalMode(super::NormalStateful::default())
+  }
+
+  /// Stop the selection and goto command-line ex mode, with the selection range `'<,'>`
+  /// pre-filled.
+  pub fn goto_command_line_ex_mode(
+    &self,
+    data_access: &StatefulDataAccess,
+  ) -> StatefulValue {
+    self.goto_normal_mode(data_access);
+    let stateful =
+      super::NormalStateful::default().goto_command_line_ex_mode(data_access);
+    command_line_edit::cursor_insert(
+      data_access,
+      CompactString::const_new(SELECTION_RANGE),
+    );
+    stateful
+  }
+}
+
+impl VisualStateful {
+  /// Select the text object registered with `key` around the cursor, the `inner` is `i` (`true`)
+  /// or `a` (`false`). If the selection is not a single char, it is extended to include the text
+  /// object.
+  pub fn select_text_object(
+    &self,
+    data_access: &StatefulDataAccess,
+    key: char,
+    inner: bool,
   ) -> StatefulValue {
+    let object = {
+      let contents = data_access.contents.clone();
+      lock!(contents).text_objects().get(key)
+    };
+    let object = match object {
+      Some(object) => object,
+      None => return StatefulValue::VisualMode(VisualStateful::default()),
+    };
+
+    let tree = data_access.tree.clone();
+    let mut tree = lock!(tree);
+    let current_window = tree.current_window_mut().unwrap();
+    let current_window_id = current_window.id();
+    let buffer = current_window.buffer().upgrade().unwrap();
+    let mut buffer = lock!(buffer);
+
+    let selection = match *buffer.selection() {
+      Some(selection) => selection,
+      None => return StatefulValue::VisualMode(VisualStateful::default()),
+    };
+    let count = self.pending.count().unwrap_or(1);
+    let found = object.select(buffer.text(), selection.cursor(), count, inner);
+    if let Some(found) = found {
+      let kind = match (found.kind(), selection.kind()) {
+        (SelectionKind::Line, _) => SelectionKind::Line,
+        (_, kind) => kind,
+      };
+      let (anchor, cursor) = if selection.anchor() == selection.cursor() {
+        (found.start(), found.end())
+      } else {
+        (
+          std::cmp::min(selection.start(), found.start()),
+          std::cmp::max(selection.end(), found.end()),
+        )
+      };
+      buffer.set_selection(Some(Selection::new(kind, anchor, cursor)));
+      cursor_ops::cursor_move(
+        &mut tree,
+        current_window_id,
+        buffer.text(),
+        Operation::CursorMoveTo((cursor.1, cursor.0)),
+        false,
+      );
+    }
     StatefulValue::VisualMode(VisualStateful::default())
   }
+
+  /// The change repeated by `.`, i.e. the key of `data_access` operates on the region with the
+  /// same size as the `selection`.
+  fn repeatable_change(
+    &self,
+    data_access: &StatefulDataAccess,
+    selection: &Selection,
+  ) -> Option<RepeatableChange> {
+    match data_access.event {
+      Event::Key(key_event) => Some(RepeatableChange::new(
+        RepeatKind::Visual(RepeatRegion::from_selection(selection)),
+        self.pending.register(),
+        None,
+        vec![key_event],
+      )),
+      _ => None,
+    }
+  }
+
+  /// Apply the operator on the selection, then stop the selection.
+  pub fn operate(
+    &self,
+    data_access: &StatefulDataAccess,
+    operator: Operator,
+  ) -> StatefulValue {
+    let selection = {
+      let tree = data_access.tree.clone();
+      let tree = lock!(tree);
+      let buffer = tree.current_window().unwrap().buffer().upgrade().unwrap();
+      lock!(buffer).take_selection()
+    };
+    match selection {
+      Some(selection) => operator_pending::apply_operator(
+        data_access,
+        &selection,
+        operator,
+        self.pending.register(),
+        self.repeatable_change(data_access, &selection),
+      ),
+      None => StatefulValue::NormalMode(super::NormalStateful::default()),
+    }
+  }
+
+  /// Join the selected lines, then stop the selection, i.e. `J` in visual mode.
+  pub fn join_lines(&self, data_access: &StatefulDataAccess) -> StatefulValue {
+    let tree = data_access.tree.clone();
+    let mut tree = lock!(tree);
+    let current_window = tree.current_window_mut().unwrap();
+    let current_window_id = current_window.id();
+    let buffer = current_window.buffer().upgrade().unwrap();
+    let mut buffer = lock!(buffer);
+
+    if let Some(selection) = buffer.take_selection() {
+      let (line_idx, char_idx) =
+        visual_ops::join_lines(buffer.text_mut(), selection.lines());
+      cursor_ops::_update_viewport_after_text_changed(
+        &mut tree,
+        current_window_id,
+        buffer.text(),
+      );
+      cursor_ops::cursor_move(
+        &mut tree,
+        current_window_id,
+        buffer.text(),
+        Operation::CursorMoveTo((char_idx, line_idx)),
+        false,
+      );
+      if let Some(change) = self.repeatable_change(data_access, &selection) {
+        lock!(data_access.state)
+          .dot_repeat_mut()
+          .set_last_change(change);
+      }
+    }
+    StatefulValue::NormalMode(super::NormalStateful::default())
+  }
 }
@@ -0,0 +1,280 @@
+use super::visual::*;
+
+use crate::buf::selection::{Selection, SelectionKind};
+use crate::content::register::{SMALL_DELETE_REGISTER, UNNAMED_REGISTER};
+use crate::prelude::*;
+use crate::state::fsm::StatefulValue;
+use crate::state::fsm::command_line_ex_tests::make_canvas;
+use crate::test::fsm::{
+  chars, ctrl, cursor, key, make_data_access, press, register, selection, text,
+};
+use crate::test::log::init as test_log_init;
+
+use crossterm::event::KeyCode;
+use crossterm::style::{Attribute, Attributes};
+use geo::point;
+
+#[test]
+fn select1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(10, 5), vec!["hello\n", "world\n"]);
+
+  let stateful = press(&data_access, chars("vll"));
+  assert!(matches!(stateful, StatefulValue::VisualMode(_)));
+  assert_eq!(
+    selection(&buf),
+    Some(Selection::new(SelectionKind::Char, (0, 0), (0, 2)))
+  );
+
+  // Switch selection type.
+  let stateful = press(&data_access, chars("vlljV"));
+  assert!(matches!(stateful, StatefulValue::VisualMode(_)));
+  assert_eq!(selection(&buf).unwrap().kind(), SelectionKind::Line);
+  assert_eq!(selection(&buf).unwrap().end(), (1, 4));
+
+  // Same selection type exits.
+  let stateful = press(&data_access, chars("vv"));
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(selection(&buf), None);
+}
+
+#[test]
+fn swap_ends1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(10, 5), vec!["hello\n", "world\n"]);
+
+  press(&data_access, chars("lvljo"));
+  let selection = selection(&buf).unwrap();
+  assert_eq!(selection.anchor(), (1, 2));
+  assert_eq!(selection.cursor(), (0, 1));
+  assert_eq!(cursor(&data_access), (0, 1));
+}
+
+#[test]
+fn marks1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(10, 5), vec!["hello\n", "world\n"]);
+
+  let mut events = chars("lvlj");
+  events.push(key(KeyCode::Esc));
+  let stateful = press(&data_access, events);
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(selection(&buf), None);
+  assert_eq!(lock!(buf).mark('<'), Some((0, 1)));
+  assert_eq!(lock!(buf).mark('>'), Some((1, 2)));
+
+  // Reselect.
+  let stateful = press(&data_access, chars("hhgv"));
+  assert!(matches!(stateful, StatefulValue::VisualMode(_)));
+  assert_eq!(
+    selection(&buf),
+    Some(Selection::new(SelectionKind::Char, (0, 1), (1, 2)))
+  );
+  assert_eq!(cursor(&data_access), (1, 2));
+}
+
+#[test]
+fn delete1() {
+  test_log_init();
+  let (buf, contents, data_access) =
+    make_data_access(U16Size::new(10, 5), vec!["hello\n", "world\n", "foo\n"]);
+
+  let stateful = press(&data_access, chars("vlld"));
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(text(&buf), "lo\nworld\nfoo\n");
+  assert_eq!(register(&contents, UNNAMED_REGISTER).unwrap(), "hel");
+  assert_eq!(register(&contents, SMALL_DELETE_REGISTER).unwrap(), "hel");
+  assert_eq!(cursor(&data_access), (0, 0));
+
+  press(&data_access, chars("Vjd"));
+  assert_eq!(text(&buf), "foo\n");
+  assert_eq!(register(&contents, '1').unwrap(), "lo\nworld\n");
+}
+
+#[test]
+fn yank1() {
+  test_log_init();
+  let (buf, contents, data_access) =
+    make_data_access(U16Size::new(10, 5), vec!["hello\n", "world\n"]);
+
+  let mut events = chars("l");
+  events.push(ctrl('v'));
+  events.extend(chars("jly"));
+  let stateful = press(&data_access, events);
+  assert!(matches!(stateful, StatefulValue::NormalMode(_)));
+  assert_eq!(text(&buf), "hello\nworld\n");
+  assert_eq!(register(&contents, UNNAMED_REGISTER).unwrap(), "el\nor");
+  assert_eq!(register(&contents, '0').unwrap(), "el\nor");
+  assert_eq!(cursor(&data_access), (0, 1));
+}
+
+#[test]
+fn change1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(10, 5), vec!["hello\n", "world\n"]);
+
+  let stateful = press(&data_access, chars("Vc"));
+  assert!(matches!(stateful, StatefulValue::InsertMode(_)));
+  assert_eq!(text(&buf), "\nworld\n");
+  assert_eq!(cursor(&data_access), (0, 0));
+}
+
+#[test]
+fn change_case1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(10, 5), vec!["Hello\n", "World\n"]);
+
+  let mut events = chars("v");
+  events.push(key(KeyCode::End));
+  events.extend(chars("~"));
+  press(&data_access, events);
+  assert_eq!(text(&buf), "hELLO\nWorld\n");
+
+  press(&data_access, chars("VjU"));
+  assert_eq!(text(&buf), "HELLO\nWORLD\n");
+
+  press(&data_access, chars("jvlu"));
+  assert_eq!(text(&buf), "HELLO\nwoRLD\n");
+}
+
+#[test]
+fn shift1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["hello\n", "\n", "  world\n"]);
+
+  press(&data_access, chars("Vjj>"));
+  assert_eq!(text(&buf), "\thello\n\n\t  world\n");
+  assert_eq!(cursor(&data_access), (0, 1));
+
+  press(&data_access, chars("Vjj<"));
+  assert_eq!(text(&buf), "hello\n\n  world\n");
+
+  press(&data_access, chars("Vjj<"));
+  assert_eq!(text(&buf), "hello\n\nworld\n");
+}
+
+#[test]
+fn join1() {
+  test_log_init();
+  let (buf, _contents, data_access) = make_data_access(
+    U16Size::new(20, 5),
+    vec!["hello\n", "  world\n", ")\n", "foo\n"],
+  );
+
+  press(&data_access, chars("VjjJ"));
+  assert_eq!(text(&buf), "hello world)\nfoo\n");
+  assert_eq!(cursor(&data_access), (0, 11));
+
+  // Single line joins with the next line.
+  press(&data_access, chars("vJ"));
+  assert_eq!(text(&buf), "hello world) foo\n");
+
+  // No next line.
+  press(&data_access, chars("vJ"));
+  assert_eq!(text(&buf), "hello world) foo\n");
+}
+
+#[test]
+fn command_line1() {
+  test_log_init();
+  let (buf, contents, data_access) =
+    make_data_access(U16Size::new(10, 5), vec!["hello\n", "world\n"]);
+
+  let stateful = press(&data_access, chars("vj:"));
+  assert!(matches!(stateful, StatefulValue::CommandLineExMode(_)));
+  assert_eq!(selection(&buf), None);
+  assert_eq!(lock!(buf).mark('>'), Some((1, 0)));
+  assert_eq!(
+    lock!(contents).command_line_content().rope().to_string(),
+    SELECTION_RANGE
+  );
+}
+
+#[test]
+fn draw1() {
+  test_log_init();
+  let terminal_size = U16Size::new(10, 5);
+  let (_buf, _contents, data_access) =
+    make_data_access(terminal_size, vec!["hello\n", "world\n"]);
+
+  press(&data_access, chars("lvlj"));
+  let canvas = make_canvas(data_access.tree.clone(), terminal_size);
+  let canvas = lock!(canvas);
+  let reversed = |x: u16, y: u16| {
+    canvas.frame().get_cell(point!(x: x, y: y)).attrs()
+      == Attributes::from(Attribute::Reverse)
+  };
+  assert!(!reversed(0, 0));
+  assert!(reversed(1, 0));
+  assert!(reversed(4, 0));
+  assert!(reversed(0, 1));
+  assert!(reversed(2, 1));
+  assert!(!reversed(3, 1));
+}
+
+#[test]
+fn text_object1() {
+  test_log_init();
+  let (buf, _contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["f(foo bar)\n", "\n", "x\n"]);
+
+  let stateful = press(&data_access, chars("llviw"));
+  assert!(matches!(stateful, StatefulValue::VisualMode(_)));
+  assert_eq!(
+    selection(&buf),
+    Some(Selection::new(SelectionKind::Char, (0, 2), (0, 4)))
+  );
+  assert_eq!(cursor(&data_access), (0, 4));
+
+  // Extend the selection.
+  press(&data_access, chars("viwha("));
+  assert_eq!(
+    selection(&buf),
+    Some(Selection::new(SelectionKind::Char, (0, 1), (0, 9)))
+  );
+
+  // Linewise object.
+  press(&data_access, chars("vip"));
+  assert_eq!(
+    selection(&buf),
+    Some(Selection::new(SelectionKind::Line, (0, 0), (0, 0)))
+  );
+}
+
+#[test]
+fn motion1() {
+  test_log_init();
+  let (_buf, contents, data_access) =
+    make_data_access(U16Size::new(20, 5), vec!["foo bar\n"]);
+
+  press(&data_access, chars("vey"));
+  assert_eq!(register(&contents, '0').unwrap(), "foo");
+
+  press(&data_access, chars("vfry"));
+  assert_eq!(register(&contents, '0').unwrap(), "foo bar");
+
+  press(&data_access, chars("$vgey"));
+  assert_eq!(register(&contents, '0').unwrap(), "o bar");
+}
+
+#[test]
+fn register1() {
+  test_log_init();
+  let (buf, contents, data_access) =
+    make_data_access(U16Size::new(10, 5), vec!["hello\n", "world\n"]);
+
+  press(&data_access, chars("vl\"ay"));
+  assert_eq!(register(&contents, 'a').unwrap(), "he");
+  assert!(register(&contents, '0').is_none());
+
+  // The count of motion.
+  press(&data_access, chars("v2l\"bd"));
+  assert_eq!(text(&buf), "lo\nworld\n");
+  assert_eq!(register(&contents, 'b').unwrap(), "hel");
+}
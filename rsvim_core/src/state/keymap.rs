@@ -0,0 +1,760 @@
+//! Key mappings, i.e. `:map`, `:noremap` and `Rsvim.keymap.set`.
+//!
+//! The mappings of each mode are saved in a trie of the key sequences (the left-hand side), so the
+//! typed keys are matched one by one: they either complete a mapping, or are a prefix of some
+//! mappings (and wait for the next key), or don't match any mapping (and are handled by the
+//! editing mode as they are). The buffer-local mappings take precedence over the global mappings.
+//!
+//! The builtin synonyms (i.e. `x` is `dl`, `D` is `d$`) are the default mappings in the same
+//! table, so they can be remapped or deleted like the user mappings.
+//!
+//! The builtin commands (i.e. `i`, `d`, `gg`, `zz`, the motions) are in a table of their own, the
+//! editing modes dispatch the keys through it. They're never remapped, so the right-hand side of
+//! a non-recursive mapping is always the builtin command, i.e. `:nnoremap a i`.
+//!
+//! See: <https://vimhelp.org/map.txt.html>.
+
+use crate::buf::BufferId;
+use crate::buf::selection::SelectionKind;
+use crate::excommand::quit::QuitKind;
+use crate::prelude::*;
+use crate::state::fsm::motion::Motion;
+use crate::state::fsm::pending::PrefixKey;
+use crate::state::keys;
+use crate::state::mode::{Mode, Modes};
+use crate::state::ops::{
+  CaseChange, CharFindKind, GotoInsertModeVariant, Operation, Operator,
+  ScrollPosition, WordMotion,
+};
+
+use compact_str::CompactString;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+/// The default leader key, i.e. `<leader>` in the left-hand side of mappings.
+pub const DEFAULT_LEADER: &str = "\\";
+
+/// The max depth of the recursive mappings, i.e. the `maxmapdepth` option in Vim.
+pub const MAX_MAP_DEPTH: usize = 1000;
+
+/// The ID of the js callback of a mapping, the function itself lives in the js runtime.
+pub type KeymapCallbackId = i32;
+
+// The builtin default mappings, `(modes, lhs, rhs)`. They're non-recursive.
+const DEFAULT_KEYMAPS: [(&str, &str, &str); 11] = [
+  ("n", "x", "dl"),
+  ("n", "<Del>", "dl"),
+  ("n", "X", "dh"),
+  ("n", "D", "d$"),
+  ("n", "C", "c$"),
+  ("n", "s", "cl"),
+  ("n", "S", "cc"),
+  ("n", "Y", "yy"),
+  ("x", "x", "d"),
+  ("x", "<Del>", "d"),
+  ("x", "s", "c"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// The builtin command of the keys, the editing mode resolves it with the typed count.
+pub enum Command {
+  /// The operation, i.e. `i`, `v`, `d`. The count is passed to the next mode, if any.
+  Operation(Operation),
+  /// The motion, it moves `count` times.
+  Motion(Motion),
+  /// Scroll the cursor line (or the line of the count) in window, and move cursor to the first
+  /// non-blank char if `true`, i.e. `zz`, `z.`.
+  ScrollCursorLine(ScrollPosition, /* first_non_blank */ bool),
+  /// The command waits for the next key as its argument, i.e. the char of `fx`, the register of
+  /// `qa`.
+  Argument(PrefixKey),
+}
+
+// The builtin motions in normal, visual and operator-pending mode, `(lhs, motion)`.
+const BUILTIN_MOTIONS: [(&str, Motion); 36] = [
+  ("h", Motion::Left),
+  ("<Left>", Motion::Left),
+  ("<BS>", Motion::Left),
+  ("l", Motion::Right),
+  ("<Right>", Motion::Right),
+  ("<Space>", Motion::Right),
+  ("k", Motion::Up),
+  ("<Up>", Motion::Up),
+  ("j", Motion::Down),
+  ("<Down>", Motion::Down),
+  ("0", Motion::LineStart),
+  ("<Home>", Motion::LineStart),
+  ("^", Motion::FirstNonBlank),
+  ("$", Motion::LineEnd),
+  ("<End>", Motion::LineEnd),
+  ("g_", Motion::LastNonBlank),
+  ("w", Motion::Word(WordMotion::Forward, false)),
+  ("W", Motion::Word(WordMotion::Forward, true)),
+  ("b", Motion::Word(WordMotion::Backward, false)),
+  ("B", Motion::Word(WordMotion::Backward, true)),
+  ("e", Motion::Word(WordMotion::ForwardEnd, false)),
+  ("E", Motion::Word(WordMotion::ForwardEnd, true)),
+  ("ge", Motion::Word(WordMotion::BackwardEnd, false)),
+  ("gE", Motion::Word(WordMotion::BackwardEnd, true)),
+  ("gg", Motion::FirstLine),
+  ("G", Motion::LastLine),
+  ("%", Motion::MatchingBracket),
+  ("(", Motion::Sentence(false)),
+  (")", Motion::Sentence(true)),
+  ("{", Motion::Paragraph(false)),
+  ("}", Motion::Paragraph(true)),
+  ("H", Motion::WindowTop),
+  ("M", Motion::WindowMiddle),
+  ("L", Motion::WindowBottom),
+  (";", Motion::RepeatCharFind(false)),
+  (",", Motion::RepeatCharFind(true)),
+];
+
+// The builtin operations in normal mode, `(lhs, operation)`.
+const BUILTIN_NORMAL_OPERATIONS: [(&str, Operation); 26] = [
+  ("i", Operation::GotoInsertMode(GotoInsertModeVariant::Keep)),
+  (
+    "a",
+    Operation::GotoInsertMode(GotoInsertModeVariant::Append),
+  ),
+  (
+    "o",
+    Operation::GotoInsertMode(GotoInsertModeVariant::NewLine),
+  ),
+  ("R", Operation::GotoReplaceMode(false)),
+  ("gR", Operation::GotoReplaceMode(true)),
+  ("v", Operation::GotoVisualMode(SelectionKind::Char)),
+  ("V", Operation::GotoVisualMode(SelectionKind::Line)),
+  ("<C-v>", Operation::GotoVisualMode(SelectionKind::Block)),
+  ("gv", Operation::VisualReselect),
+  ("gh", Operation::GotoSelectMode(SelectionKind::Char)),
+  ("gH", Operation::GotoSelectMode(SelectionKind::Line)),
+  ("g<C-h>", Operation::GotoSelectMode(SelectionKind::Block)),
+  ("d", Operation::GotoOperatorPendingMode(Operator::Delete)),
+  ("y", Operation::GotoOperatorPendingMode(Operator::Yank)),
+  ("c", Operation::GotoOperatorPendingMode(Operator::Change)),
+  (
+    ">",
+    Operation::GotoOperatorPendingMode(Operator::ShiftRight),
+  ),
+  (
+    "<lt>",
+    Operation::GotoOperatorPendingMode(Operator::ShiftLeft),
+  ),
+  (
+    "g~",
+    Operation::GotoOperatorPendingMode(Operator::ChangeCase(
+      CaseChange::Toggle,
+    )),
+  ),
+  (
+    "gu",
+    Operation::GotoOperatorPendingMode(Operator::ChangeCase(CaseChange::Lower)),
+  ),
+  (
+    "gU",
+    Operation::GotoOperatorPendingMode(Operator::ChangeCase(CaseChange::Upper)),
+  ),
+  (".", Operation::RepeatLastChange),
+  (":", Operation::GotoCommandLineExMode),
+  ("/", Operation::GotoCommandLineSearchForwardMode),
+  ("?", Operation::GotoCommandLineSearchBackwardMode),
+  ("ZZ", Operation::EditorQuit(QuitKind::Exit, false)),
+  ("ZQ", Operation::EditorQuit(QuitKind::Quit, true)),
+];
+
+// The builtin commands that wait for the argument in normal mode, `(lhs, prefix)`.
+const BUILTIN_NORMAL_ARGUMENTS: [(&str, PrefixKey); 8] = [
+  ("f", PrefixKey::CharFind(CharFindKind::Forward)),
+  ("F", PrefixKey::CharFind(CharFindKind::Backward)),
+  ("t", PrefixKey::CharFind(CharFindKind::TillForward)),
+  ("T", PrefixKey::CharFind(CharFindKind::TillBackward)),
+  ("r", PrefixKey::R),
+  ("q", PrefixKey::Q),
+  ("@", PrefixKey::At),
+  ("<C-w>", PrefixKey::CtrlW),
+];
+
+// The builtin scrolling commands in normal mode, `(lhs, position, first_non_blank)`.
+const BUILTIN_NORMAL_SCROLLS: [(&str, ScrollPosition, bool); 6] = [
+  ("zt", ScrollPosition::Top, false),
+  ("z<CR>", ScrollPosition::Top, true),
+  ("zz", ScrollPosition::Middle, false),
+  ("z.", ScrollPosition::Middle, true),
+  ("zb", ScrollPosition::Bottom, false),
+  ("z-", ScrollPosition::Bottom, true),
+];
+
+// The builtin commands, `(modes, lhs, command)`.
+fn _builtin_commands() -> Vec<(&'static str, &'static str, Command)> {
+  let motions = BUILTIN_MOTIONS
+    .iter()
+    .map(|(lhs, motion)| ("nxo", *lhs, Command::Motion(*motion)));
+  let operations = BUILTIN_NORMAL_OPERATIONS
+    .iter()
+    .map(|(lhs, op)| ("n", *lhs, Command::Operation(op.clone())));
+  let arguments = BUILTIN_NORMAL_ARGUMENTS
+    .iter()
+    .map(|(lhs, prefix)| ("n", *lhs, Command::Argument(*prefix)));
+  let scrolls =
+    BUILTIN_NORMAL_SCROLLS
+      .iter()
+      .map(|(lhs, position, first_non_blank)| {
+        (
+          "n",
+          *lhs,
+          Command::ScrollCursorLine(*position, *first_non_blank),
+        )
+      });
+  motions
+    .chain(operations)
+    .chain(arguments)
+    .chain(scrolls)
+    .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The right-hand side of a mapping.
+pub enum KeymapTarget {
+  /// The keys fed as if they were typed, no keys is `<Nop>`.
+  Keys(Vec<KeyEvent>),
+  /// The js callback. For an `<expr>` mapping, the returned string is fed as the keys.
+  Callback(KeymapCallbackId),
+  /// The builtin command, see [`Command`].
+  Command(Command),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// The options of a mapping.
+pub struct KeymapOptions {
+  /// Non-recursive, i.e. `:noremap`. The keys are not remapped.
+  pub noremap: bool,
+  /// `<silent>`, the messages echoed by the mapping are not shown, the errors are still shown.
+  pub silent: bool,
+  /// `<expr>`, the string returned by the js callback is fed as the keys.
+  pub expr: bool,
+  /// `<buffer>`, local to the buffer.
+  pub buffer: Option<BufferId>,
+  /// The description, shown when listing the mappings.
+  pub desc: CompactString,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A key mapping.
+pub struct Keymap {
+  lhs: Vec<KeyEvent>,
+  target: KeymapTarget,
+  options: KeymapOptions,
+  builtin: bool,
+}
+
+impl Keymap {
+  pub fn new(
+    lhs: Vec<KeyEvent>,
+    target: KeymapTarget,
+    options: KeymapOptions,
+  ) -> Self {
+    Self {
+      lhs: lhs.iter().map(normalize_key).collect(),
+      target,
+      options,
+      builtin: false,
+    }
+  }
+
+  /// The left-hand side keys.
+  pub fn lhs(&self) -> &Vec<KeyEvent> {
+    &self.lhs
+  }
+
+  /// The right-hand side.
+  pub fn target(&self) -> &KeymapTarget {
+    &self.target
+  }
+
+  pub fn options(&self) -> &KeymapOptions {
+    &self.options
+  }
+
+  pub fn noremap(&self) -> bool {
+    self.options.noremap
+  }
+
+  pub fn silent(&self) -> bool {
+    self.options.silent
+  }
+
+  pub fn expr(&self) -> bool {
+    self.options.expr
+  }
+
+  pub fn buffer(&self) -> Option<BufferId> {
+    self.options.buffer
+  }
+
+  pub fn desc(&self) -> &str {
+    &self.options.desc
+  }
+
+  /// Whether it is a builtin default mapping.
+  pub fn is_builtin(&self) -> bool {
+    self.builtin
+  }
+
+  /// The right-hand side in key notation, i.e. `d$`, `<Nop>`, `<Callback>`.
+  pub fn rhs_notation(&self) -> String {
+    match &self.target {
+      KeymapTarget::Keys(keys) if keys.is_empty() => "<Nop>".to_string(),
+      KeymapTarget::Keys(keys) => keys::to_notations(keys),
+      KeymapTarget::Callback(_) => "<Callback>".to_string(),
+      KeymapTarget::Command(_) => keys::to_notations(&self.lhs),
+    }
+  }
+}
+
+/// Normalize the key for matching, i.e. the kind is always press, and the shift is already in the
+/// char (`A` instead of `<S-a>`).
+pub fn normalize_key(key_event: &KeyEvent) -> KeyEvent {
+  let mut modifiers = key_event.modifiers
+    & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+  let code = match key_event.code {
+    KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+      modifiers.remove(KeyModifiers::SHIFT);
+      KeyCode::Char(c.to_uppercase().next().unwrap_or(c))
+    }
+    code => code,
+  };
+  KeyEvent::new_with_kind(code, modifiers, KeyEventKind::Press)
+}
+
+/// The mode whose mappings are used in the editing `mode`, i.e. the insert mode mappings are also
+/// used in replace mode.
+pub fn keymap_mode(mode: Mode) -> Mode {
+  match mode {
+    Mode::Replace | Mode::VirtualReplace => Mode::Insert,
+    Mode::CommandLineSearchForward | Mode::CommandLineSearchBackward => {
+      Mode::CommandLineEx
+    }
+    mode => mode,
+  }
+}
+
+/// Parse the modes of the mapping commands, each char is a mode (or several modes):
+///
+/// - `n`: Normal.
+/// - `v`: Visual and select.
+/// - `x`: Visual.
+/// - `s`: Select.
+/// - `o`: Operator-pending.
+/// - `i`: Insert (and replace).
+/// - `c`: Command-line.
+/// - `t`: Terminal.
+/// - `!`: Insert and command-line, i.e. `:map!`.
+///
+/// The empty string is normal, visual, select and operator-pending, i.e. `:map`.
+///
+/// Returns `None` if there's an unknown mode char.
+pub fn parse_modes(s: &str) -> Option<Modes> {
+  if s.is_empty() {
+    return Some(Modes::from(vec![
+      Mode::Normal,
+      Mode::Visual,
+      Mode::Select,
+      Mode::OperatorPending,
+    ]));
+  }
+  let mut modes = Modes::new();
+  for c in s.chars() {
+    let values: &[Mode] = match c {
+      'n' => &[Mode::Normal],
+      'v' => &[Mode::Visual, Mode::Select],
+      'x' => &[Mode::Visual],
+      's' => &[Mode::Select],
+      'o' => &[Mode::OperatorPending],
+      'i' => &[Mode::Insert],
+      'c' => &[Mode::CommandLineEx],
+      't' => &[Mode::Terminal],
+      '!' => &[Mode::Insert, Mode::CommandLineEx],
+      _ => return None,
+    };
+    for mode in values {
+      modes.set(*mode);
+    }
+  }
+  Some(modes)
+}
+
+/// The mode char of the mapping table, i.e. `n` for normal mode. It's the reverse of
+/// [`parse_modes`].
+pub fn mode_char(mode: Mode) -> char {
+  match keymap_mode(mode) {
+    Mode::Normal => 'n',
+    Mode::Visual => 'x',
+    Mode::Select => 's',
+    Mode::OperatorPending => 'o',
+    Mode::Insert => 'i',
+    Mode::Terminal => 't',
+    _ => 'c',
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The result of matching the typed keys.
+pub enum KeymapMatch {
+  /// The keys don't match any mapping.
+  None,
+  /// The keys are a prefix of some mappings, wait for the next key.
+  Prefix,
+  /// The keys complete a mapping.
+  Exact(Keymap),
+  /// The keys complete a mapping, and they're also a prefix of some longer mappings. The mapping
+  /// is used if the next key doesn't arrive in time.
+  ExactPrefix(Keymap),
+}
+
+#[derive(Debug, Clone, Default)]
+struct KeymapNode {
+  keymap: Option<Keymap>,
+  children: HashMap<KeyEvent, KeymapNode>,
+}
+
+impl KeymapNode {
+  fn is_empty(&self) -> bool {
+    self.keymap.is_none() && self.children.is_empty()
+  }
+
+  fn collect<'a>(&'a self, result: &mut Vec<&'a Keymap>) {
+    if let Some(keymap) = &self.keymap {
+      result.push(keymap);
+    }
+    for child in self.children.values() {
+      child.collect(result);
+    }
+  }
+
+  fn remove(&mut self, lhs: &[KeyEvent]) -> Option<Keymap> {
+    match lhs.split_first() {
+      None => self.keymap.take(),
+      Some((key, rest)) => {
+        let child = self.children.get_mut(key)?;
+        let removed = child.remove(rest);
+        if child.is_empty() {
+          self.children.remove(key);
+        }
+        removed
+      }
+    }
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+/// The trie of the mappings in a mode, keyed by the left-hand side keys.
+pub struct KeymapTrie {
+  root: KeymapNode,
+}
+
+impl KeymapTrie {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.root.is_empty()
+  }
+
+  /// Insert the mapping.
+  ///
+  /// Returns the replaced mapping with the same left-hand side, if any.
+  pub fn insert(&mut self, keymap: Keymap) -> Option<Keymap> {
+    let mut node = &mut self.root;
+    for key in keymap.lhs.iter() {
+      node = node.children.entry(*key).or_default();
+    }
+    node.keymap.replace(keymap)
+  }
+
+  /// Remove the mapping with the left-hand side `lhs`.
+  pub fn remove(&mut self, lhs: &[KeyEvent]) -> Option<Keymap> {
+    let lhs: Vec<KeyEvent> = lhs.iter().map(normalize_key).collect();
+    self.root.remove(&lhs)
+  }
+
+  fn node(&self, keys: &[KeyEvent]) -> Option<&KeymapNode> {
+    let mut node = &self.root;
+    for key in keys.iter() {
+      node = node.children.get(&normalize_key(key))?;
+    }
+    Some(node)
+  }
+
+  /// Get the mapping with the left-hand side `lhs`.
+  pub fn get(&self, lhs: &[KeyEvent]) -> Option<&Keymap> {
+    self.node(lhs).and_then(|node| node.keymap.as_ref())
+  }
+
+  /// Match the typed keys.
+  pub fn lookup(&self, keys: &[KeyEvent]) -> KeymapMatch {
+    match self.node(keys) {
+      None => KeymapMatch::None,
+      Some(node) => match (&node.keymap, node.children.is_empty()) {
+        (Some(keymap), true) => KeymapMatch::Exact(keymap.clone()),
+        (Some(keymap), false) => KeymapMatch::ExactPrefix(keymap.clone()),
+        (None, false) => KeymapMatch::Prefix,
+        (None, true) => KeymapMatch::None,
+      },
+    }
+  }
+
+  /// All the mappings, sorted by the left-hand side notation.
+  pub fn list(&self) -> Vec<&Keymap> {
+    let mut result = vec![];
+    self.root.collect(&mut result);
+    result.sort_by_cached_key(|keymap| keys::to_notations(&keymap.lhs));
+    result
+  }
+}
+
+#[derive(Debug, Clone)]
+/// The mappings of all modes, the global mappings and the buffer-local mappings.
+pub struct Keymaps {
+  global: HashMap<Mode, KeymapTrie>,
+  buffer_local: HashMap<(BufferId, Mode), KeymapTrie>,
+  // The builtin commands, they can't be changed.
+  commands: HashMap<Mode, KeymapTrie>,
+  leader: CompactString,
+}
+
+impl Default for Keymaps {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Keymaps {
+  /// Make the mappings with the builtin default mappings and the builtin commands.
+  pub fn new() -> Self {
+    let mut keymaps = Self {
+      global: HashMap::new(),
+      buffer_local: HashMap::new(),
+      commands: HashMap::new(),
+      leader: DEFAULT_LEADER.into(),
+    };
+    for (modes, lhs, command) in _builtin_commands() {
+      let mut keymap = Keymap::new(
+        keys::parse(lhs),
+        KeymapTarget::Command(command),
+        KeymapOptions {
+          noremap: true,
+          ..Default::default()
+        },
+      );
+      keymap.builtin = true;
+      for mode in parse_modes(modes).unwrap().iter() {
+        keymaps
+          .commands
+          .entry(*mode)
+          .or_default()
+          .insert(keymap.clone());
+      }
+    }
+    for (modes, lhs, rhs) in DEFAULT_KEYMAPS.iter() {
+      let mut keymap = Keymap::new(
+        keys::parse(lhs),
+        KeymapTarget::Keys(keys::parse(rhs)),
+        KeymapOptions {
+          noremap: true,
+          ..Default::default()
+        },
+      );
+      keymap.builtin = true;
+      keymaps.set(&parse_modes(modes).unwrap(), keymap);
+    }
+    keymaps
+  }
+
+  /// The leader key, i.e. `<leader>` in the left-hand side of mappings.
+  pub fn leader(&self) -> &str {
+    &self.leader
+  }
+
+  /// Set the leader key, it only affects the mappings defined after it.
+  pub fn set_leader(&mut self, leader: &str) {
+    self.leader = leader.into();
+  }
+
+  /// Parse the left-hand side in key notation, the `<leader>` is replaced with the leader key.
+  pub fn parse_lhs(&self, lhs: &str) -> Vec<KeyEvent> {
+    let mut result = String::with_capacity(lhs.len());
+    let mut rest = lhs;
+    while let Some(start) = rest.find('<') {
+      result.push_str(&rest[..start]);
+      let tail = &rest[start..];
+      match tail.get(..8) {
+        Some(name) if name.eq_ignore_ascii_case("<leader>") => {
+          result.push_str(&self.leader);
+          rest = &tail[8..];
+        }
+        _ => {
+          result.push('<');
+          rest = &tail[1..];
+        }
+      }
+    }
+    result.push_str(rest);
+    keys::parse(&result)
+  }
+
+  /// Parse the right-hand side in key notation like [`parse_lhs`](Keymaps::parse_lhs), the `<Nop>`
+  /// is no keys.
+  pub fn parse_rhs(&self, rhs: &str) -> Vec<KeyEvent> {
+    if rhs.eq_ignore_ascii_case("<nop>") {
+      vec![]
+    } else {
+      self.parse_lhs(rhs)
+    }
+  }
+
+  fn _trie(&self, mode: Mode, buffer: Option<BufferId>) -> Option<&KeymapTrie> {
+    match buffer {
+      Some(buffer) => self.buffer_local.get(&(buffer, mode)),
+      None => self.global.get(&mode),
+    }
+  }
+
+  fn _trie_mut(
+    &mut self,
+    mode: Mode,
+    buffer: Option<BufferId>,
+  ) -> &mut KeymapTrie {
+    match buffer {
+      Some(buffer) => self.buffer_local.entry((buffer, mode)).or_default(),
+      None => self.global.entry(mode).or_default(),
+    }
+  }
+
+  /// Set the mapping in the `modes`, it is buffer-local if its `buffer` option is set.
+  ///
+  /// Returns the replaced mappings.
+  pub fn set(&mut self, modes: &Modes, keymap: Keymap) -> Vec<Keymap> {
+    let mut replaced = vec![];
+    for mode in modes.iter() {
+      let trie = self._trie_mut(*mode, keymap.buffer());
+      if let Some(old) = trie.insert(keymap.clone()) {
+        replaced.push(old);
+      }
+    }
+    replaced
+  }
+
+  /// Delete the mapping with the left-hand side `lhs` in the `modes`, the buffer-local mapping if
+  /// `buffer` is set.
+  ///
+  /// Returns the deleted mappings.
+  pub fn del(
+    &mut self,
+    modes: &Modes,
+    lhs: &[KeyEvent],
+    buffer: Option<BufferId>,
+  ) -> Vec<Keymap> {
+    let mut deleted = vec![];
+    for mode in modes.iter() {
+      let trie = self._trie_mut(*mode, buffer);
+      if let Some(old) = trie.remove(lhs) {
+        deleted.push(old);
+      }
+    }
+    deleted
+  }
+
+  /// Get the mapping with the left-hand side `lhs` in the `mode`, the buffer-local mapping if
+  /// `buffer` is set.
+  pub fn get(
+    &self,
+    mode: Mode,
+    lhs: &[KeyEvent],
+    buffer: Option<BufferId>,
+  ) -> Option<&Keymap> {
+    self._trie(mode, buffer).and_then(|trie| trie.get(lhs))
+  }
+
+  /// The mappings in the `mode`, the buffer-local mappings of `buffer` are listed first.
+  pub fn list(&self, mode: Mode, buffer: Option<BufferId>) -> Vec<&Keymap> {
+    let mut result = vec![];
+    if let Some(trie) = buffer.and_then(|buffer| self._trie(mode, Some(buffer)))
+    {
+      result.extend(trie.list());
+    }
+    if let Some(trie) = self._trie(mode, None) {
+      result.extend(trie.list());
+    }
+    result
+  }
+
+  /// Match the typed keys in the `mode`, the buffer-local mappings of `buffer` take precedence.
+  pub fn lookup(
+    &self,
+    mode: Mode,
+    buffer: Option<BufferId>,
+    keys: &[KeyEvent],
+  ) -> KeymapMatch {
+    let mode = keymap_mode(mode);
+    let local = buffer
+      .and_then(|buffer| self._trie(mode, Some(buffer)))
+      .map(|trie| trie.lookup(keys))
+      .unwrap_or(KeymapMatch::None);
+    let global = self
+      ._trie(mode, None)
+      .map(|trie| trie.lookup(keys))
+      .unwrap_or(KeymapMatch::None);
+
+    let exact = |m: &KeymapMatch| match m {
+      KeymapMatch::Exact(keymap) | KeymapMatch::ExactPrefix(keymap) => {
+        Some(keymap.clone())
+      }
+      _ => None,
+    };
+    let prefix = |m: &KeymapMatch| {
+      matches!(m, KeymapMatch::Prefix | KeymapMatch::ExactPrefix(_))
+    };
+    match (
+      exact(&local).or_else(|| exact(&global)),
+      prefix(&local) || prefix(&global),
+    ) {
+      (Some(keymap), false) => KeymapMatch::Exact(keymap),
+      (Some(keymap), true) => KeymapMatch::ExactPrefix(keymap),
+      (None, true) => KeymapMatch::Prefix,
+      (None, false) => KeymapMatch::None,
+    }
+  }
+
+  /// Match the typed keys against the builtin commands in the `mode`, i.e. `g` is a prefix of `gg`.
+  pub fn lookup_command(&self, mode: Mode, keys: &[KeyEvent]) -> KeymapMatch {
+    self
+      .commands
+      .get(&keymap_mode(mode))
+      .map(|trie| trie.lookup(keys))
+      .unwrap_or(KeymapMatch::None)
+  }
+
+  /// The builtin command of the keys in the `mode`.
+  pub fn command(&self, mode: Mode, keys: &[KeyEvent]) -> Option<Command> {
+    match self.commands.get(&keymap_mode(mode))?.get(keys)?.target() {
+      KeymapTarget::Command(command) => Some(command.clone()),
+      _ => None,
+    }
+  }
+
+  /// Remove all the buffer-local mappings of the `buffer`, i.e. the buffer is deleted.
+  pub fn clear_buffer(&mut self, buffer: BufferId) {
+    self.buffer_local.retain(|(id, _), _| *id != buffer);
+  }
+
+  /// Whether the js callback is still used by any mapping.
+  pub fn has_callback(&self, id: KeymapCallbackId) -> bool {
+    self
+      .global
+      .values()
+      .chain(self.buffer_local.values())
+      .flat_map(|trie| trie.list())
+      .any(|keymap| keymap.target == KeymapTarget::Callback(id))
+  }
+}
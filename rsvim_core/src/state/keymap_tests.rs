@@ -0,0 +1,246 @@
+use super::keymap::*;
+
+use crate::state::fsm::motion::Motion;
+use crate::state::fsm::pending::PrefixKey;
+use crate::state::keys;
+use crate::state::mode::{Mode, Modes};
+use crate::state::ops::{CharFindKind, WordMotion};
+use crate::test::log::init as test_log_init;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+fn keymap(lhs: &str, rhs: &str, noremap: bool) -> Keymap {
+  Keymap::new(
+    keys::parse(lhs),
+    KeymapTarget::Keys(keys::parse(rhs)),
+    KeymapOptions {
+      noremap,
+      ..Default::default()
+    },
+  )
+}
+
+fn rhs(m: &KeymapMatch) -> Option<String> {
+  match m {
+    KeymapMatch::Exact(keymap) | KeymapMatch::ExactPrefix(keymap) => {
+      Some(keymap.rhs_notation())
+    }
+    _ => None,
+  }
+}
+
+#[test]
+fn normalize_key1() {
+  test_log_init();
+  let shifted = KeyEvent::new_with_kind(
+    KeyCode::Char('a'),
+    KeyModifiers::SHIFT,
+    KeyEventKind::Repeat,
+  );
+  assert_eq!(normalize_key(&shifted), keys::parse("A")[0]);
+  let ctrl = KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL);
+  assert_eq!(normalize_key(&ctrl), keys::parse("<C-w>")[0]);
+}
+
+#[test]
+fn parse_modes1() {
+  test_log_init();
+  let modes = parse_modes("").unwrap();
+  assert_eq!(modes.len(), 4);
+  assert!(modes.contains(&Mode::Normal));
+  assert!(modes.contains(&Mode::Select));
+  assert!(!modes.contains(&Mode::Insert));
+
+  let modes = parse_modes("v").unwrap();
+  assert!(modes.contains(&Mode::Visual));
+  assert!(modes.contains(&Mode::Select));
+  let modes = parse_modes("!").unwrap();
+  assert!(modes.contains(&Mode::Insert));
+  assert!(modes.contains(&Mode::CommandLineEx));
+  assert_eq!(parse_modes("nt").unwrap().len(), 2);
+  assert!(parse_modes("nz").is_none());
+
+  assert_eq!(mode_char(Mode::Replace), 'i');
+  assert_eq!(mode_char(Mode::CommandLineSearchForward), 'c');
+}
+
+#[test]
+fn trie1() {
+  test_log_init();
+  let mut trie = KeymapTrie::new();
+  assert!(trie.is_empty());
+  assert!(trie.insert(keymap("gx", "dd", true)).is_none());
+  assert!(trie.insert(keymap("g", "x", true)).is_none());
+  let replaced = trie.insert(keymap("gx", "yy", true)).unwrap();
+  assert_eq!(replaced.rhs_notation(), "dd");
+
+  assert_eq!(trie.lookup(&keys::parse("q")), KeymapMatch::None);
+  assert_eq!(rhs(&trie.lookup(&keys::parse("gx"))), Some("yy".into()));
+  assert!(matches!(
+    trie.lookup(&keys::parse("g")),
+    KeymapMatch::ExactPrefix(_)
+  ));
+  assert_eq!(trie.lookup(&keys::parse("gxx")), KeymapMatch::None);
+
+  let lhs: Vec<String> = trie
+    .list()
+    .iter()
+    .map(|keymap| keys::to_notations(keymap.lhs()))
+    .collect();
+  assert_eq!(lhs, vec!["g", "gx"]);
+
+  // The empty nodes are removed.
+  assert!(trie.remove(&keys::parse("g")).is_some());
+  assert_eq!(trie.lookup(&keys::parse("g")), KeymapMatch::Prefix);
+  assert!(trie.remove(&keys::parse("gx")).is_some());
+  assert!(trie.remove(&keys::parse("gx")).is_none());
+  assert!(trie.is_empty());
+}
+
+#[test]
+fn defaults1() {
+  test_log_init();
+  let keymaps = Keymaps::new();
+  let x = keymaps.get(Mode::Normal, &keys::parse("x"), None).unwrap();
+  assert!(x.is_builtin());
+  assert!(x.noremap());
+  assert_eq!(x.rhs_notation(), "dl");
+  assert_eq!(
+    rhs(&keymaps.lookup(Mode::Visual, None, &keys::parse("<Del>"))),
+    Some("d".into())
+  );
+  assert_eq!(
+    keymaps.lookup(Mode::Select, None, &keys::parse("x")),
+    KeymapMatch::None
+  );
+}
+
+#[test]
+fn commands1() {
+  test_log_init();
+  let mut keymaps = Keymaps::new();
+  assert_eq!(
+    keymaps.command(Mode::Normal, &keys::parse("gg")),
+    Some(Command::Motion(Motion::FirstLine))
+  );
+  assert_eq!(
+    keymaps.command(Mode::OperatorPending, &keys::parse("w")),
+    Some(Command::Motion(Motion::Word(WordMotion::Forward, false)))
+  );
+  assert_eq!(
+    keymaps.command(Mode::Normal, &keys::parse("f")),
+    Some(Command::Argument(PrefixKey::CharFind(
+      CharFindKind::Forward
+    )))
+  );
+  assert_eq!(
+    keymaps.lookup_command(Mode::Normal, &keys::parse("g")),
+    KeymapMatch::Prefix
+  );
+  assert_eq!(keymaps.command(Mode::Visual, &keys::parse("i")), None);
+
+  // The builtin commands are not changed by the mappings, and they're not listed.
+  let modes = parse_modes("n").unwrap();
+  keymaps.set(&modes, keymap("i", "a", true));
+  keymaps.del(&modes, &keys::parse("i"), None);
+  assert!(matches!(
+    keymaps.command(Mode::Normal, &keys::parse("i")),
+    Some(Command::Operation(_))
+  ));
+  assert!(
+    keymaps
+      .list(Mode::Normal, None)
+      .iter()
+      .all(|keymap| !matches!(keymap.target(), KeymapTarget::Command(_)))
+  );
+}
+
+#[test]
+fn set_del1() {
+  test_log_init();
+  let mut keymaps = Keymaps::new();
+  let modes = parse_modes("n").unwrap();
+
+  // Remap the default mapping.
+  let replaced = keymaps.set(&modes, keymap("x", "dd", false));
+  assert_eq!(replaced.len(), 1);
+  assert!(replaced[0].is_builtin());
+  let x = keymaps.get(Mode::Normal, &keys::parse("x"), None).unwrap();
+  assert!(!x.is_builtin());
+  assert!(!x.noremap());
+
+  assert_eq!(keymaps.del(&modes, &keys::parse("x"), None).len(), 1);
+  assert!(keymaps.del(&modes, &keys::parse("x"), None).is_empty());
+  assert_eq!(
+    keymaps.lookup(Mode::Normal, None, &keys::parse("x")),
+    KeymapMatch::None
+  );
+}
+
+#[test]
+fn buffer_local1() {
+  test_log_init();
+  let mut keymaps = Keymaps::new();
+  let modes = Modes::from(Mode::Normal);
+  keymaps.set(&modes, keymap("gx", "dd", true));
+  let local = Keymap::new(
+    keys::parse("g"),
+    KeymapTarget::Keys(keys::parse("yy")),
+    KeymapOptions {
+      noremap: true,
+      buffer: Some(1),
+      ..Default::default()
+    },
+  );
+  keymaps.set(&modes, local);
+
+  // The local mapping is also a prefix of the global mapping.
+  assert_eq!(
+    rhs(&keymaps.lookup(Mode::Normal, Some(1), &keys::parse("g"))),
+    Some("yy".into())
+  );
+  assert!(matches!(
+    keymaps.lookup(Mode::Normal, Some(1), &keys::parse("g")),
+    KeymapMatch::ExactPrefix(_)
+  ));
+  assert_eq!(
+    keymaps.lookup(Mode::Normal, Some(2), &keys::parse("g")),
+    KeymapMatch::Prefix
+  );
+  assert_eq!(keymaps.list(Mode::Normal, Some(1))[0].buffer(), Some(1));
+
+  keymaps.clear_buffer(1);
+  assert_eq!(
+    keymaps.lookup(Mode::Normal, Some(1), &keys::parse("g")),
+    KeymapMatch::Prefix
+  );
+}
+
+#[test]
+fn leader1() {
+  test_log_init();
+  let mut keymaps = Keymaps::new();
+  assert_eq!(keymaps.leader(), DEFAULT_LEADER);
+  assert_eq!(keymaps.parse_lhs("<Leader>w"), keys::parse("\\w"));
+  keymaps.set_leader("<Space>");
+  assert_eq!(keymaps.parse_lhs("<leader>w<lt>"), keys::parse("<Space>w<"));
+  assert!(keymaps.parse_rhs("<Nop>").is_empty());
+}
+
+#[test]
+fn callback1() {
+  test_log_init();
+  let mut keymaps = Keymaps::new();
+  let keymap = Keymap::new(
+    keys::parse("Q"),
+    KeymapTarget::Callback(7),
+    KeymapOptions::default(),
+  );
+  assert_eq!(keymap.rhs_notation(), "<Callback>");
+  keymaps.set(&parse_modes("nx").unwrap(), keymap);
+  assert!(keymaps.has_callback(7));
+  keymaps.del(&parse_modes("n").unwrap(), &keys::parse("Q"), None);
+  assert!(keymaps.has_callback(7));
+  keymaps.del(&parse_modes("x").unwrap(), &keys::parse("Q"), None);
+  assert!(!keymaps.has_callback(7));
+}
@@ -0,0 +1,161 @@
+//! Key notation, i.e. the keys are written as text like `dw`, `<Esc>`, `<C-w>`, `<lt>`.
+//!
+//! The recorded macros are saved to registers in this notation, so they can be edited as text,
+//! and the keys of `:normal` are parsed from it.
+//!
+//! See: <https://vimhelp.org/intro.txt.html#key-notation>.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+// The named keys, the names are case-insensitive when parsing.
+const NAMED_KEYS: [(&str, KeyCode); 19] = [
+  ("Esc", KeyCode::Esc),
+  ("CR", KeyCode::Enter),
+  ("Tab", KeyCode::Tab),
+  ("BS", KeyCode::Backspace),
+  ("Del", KeyCode::Delete),
+  ("Up", KeyCode::Up),
+  ("Down", KeyCode::Down),
+  ("Left", KeyCode::Left),
+  ("Right", KeyCode::Right),
+  ("Home", KeyCode::Home),
+  ("End", KeyCode::End),
+  ("PageUp", KeyCode::PageUp),
+  ("PageDown", KeyCode::PageDown),
+  ("Insert", KeyCode::Insert),
+  ("Space", KeyCode::Char(' ')),
+  ("lt", KeyCode::Char('<')),
+  ("Bslash", KeyCode::Char('\\')),
+  ("Bar", KeyCode::Char('|')),
+  ("Enter", KeyCode::Enter),
+];
+
+fn _key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+  KeyEvent::new_with_kind(code, modifiers, KeyEventKind::Press)
+}
+
+// The name of a non-char key, i.e. `Esc`, `F1`.
+fn _key_name(code: KeyCode) -> Option<String> {
+  match code {
+    KeyCode::F(n) => Some(format!("F{n}")),
+    KeyCode::BackTab => Some("Tab".to_string()),
+    KeyCode::Char(_) => None,
+    code => NAMED_KEYS
+      .iter()
+      .find(|(_, c)| *c == code)
+      .map(|(name, _)| name.to_string()),
+  }
+}
+
+/// The notation of the key, i.e. `d`, `<Esc>`, `<C-w>`, `<lt>`.
+///
+/// Returns `None` if the key cannot be written, i.e. the media keys.
+pub fn to_notation(key_event: &KeyEvent) -> Option<String> {
+  let mut modifiers = String::new();
+  if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+    modifiers.push_str("C-");
+  }
+  if key_event.modifiers.contains(KeyModifiers::ALT) {
+    modifiers.push_str("M-");
+  }
+  // The shift is already in the char, i.e. `A`.
+  let shift = key_event.code == KeyCode::BackTab
+    || (key_event.modifiers.contains(KeyModifiers::SHIFT)
+      && !matches!(key_event.code, KeyCode::Char(_)));
+  if shift {
+    modifiers.push_str("S-");
+  }
+
+  match key_event.code {
+    KeyCode::Char('<') if modifiers.is_empty() => Some("<lt>".to_string()),
+    KeyCode::Char(c) if modifiers.is_empty() => Some(c.to_string()),
+    KeyCode::Char(c) => Some(format!("<{modifiers}{c}>")),
+    code => _key_name(code).map(|name| format!("<{modifiers}{name}>")),
+  }
+}
+
+/// The notation of the keys, the keys that cannot be written are skipped.
+pub fn to_notations(keys: &[KeyEvent]) -> String {
+  keys.iter().filter_map(to_notation).collect()
+}
+
+// Parse the `<...>` notation without the angle brackets, i.e. `C-w`, `Esc`.
+fn _parse_special(s: &str) -> Option<KeyEvent> {
+  let mut modifiers = KeyModifiers::empty();
+  let mut rest = s;
+  while rest.len() > 2 && rest.as_bytes()[1] == b'-' {
+    match rest.as_bytes()[0].to_ascii_uppercase() {
+      b'C' => modifiers |= KeyModifiers::CONTROL,
+      b'M' | b'A' => modifiers |= KeyModifiers::ALT,
+      b'S' => modifiers |= KeyModifiers::SHIFT,
+      _ => return None,
+    }
+    rest = &rest[2..];
+  }
+
+  let mut chars = rest.chars();
+  if let (Some(c), None) = (chars.next(), chars.next()) {
+    // The char without modifiers is not a key notation, i.e. `<a>`.
+    if modifiers.is_empty() {
+      return None;
+    }
+    // The control chars are lowercase, i.e. `<C-W>` is the same as `<C-w>`.
+    let c = if modifiers.contains(KeyModifiers::CONTROL) {
+      c.to_ascii_lowercase()
+    } else {
+      c
+    };
+    return Some(_key(KeyCode::Char(c), modifiers));
+  }
+  if let Some(n) = rest
+    .strip_prefix(['F', 'f'])
+    .and_then(|n| n.parse::<u8>().ok())
+  {
+    return Some(_key(KeyCode::F(n), modifiers));
+  }
+  NAMED_KEYS
+    .iter()
+    .find(|(name, _)| name.eq_ignore_ascii_case(rest))
+    .map(|(_, code)| match code {
+      KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => {
+        _key(KeyCode::BackTab, modifiers)
+      }
+      code => _key(*code, modifiers),
+    })
+}
+
+// The key of a raw char, i.e. the control chars in the text yanked into a register.
+fn _parse_char(c: char) -> KeyEvent {
+  match c {
+    '\n' | '\r' => _key(KeyCode::Enter, KeyModifiers::empty()),
+    '\t' => _key(KeyCode::Tab, KeyModifiers::empty()),
+    '\x1b' => _key(KeyCode::Esc, KeyModifiers::empty()),
+    '\x08' | '\x7f' => _key(KeyCode::Backspace, KeyModifiers::empty()),
+    '\x01'..='\x1a' => _key(
+      KeyCode::Char((b'a' + c as u8 - 1) as char),
+      KeyModifiers::CONTROL,
+    ),
+    c => _key(KeyCode::Char(c), KeyModifiers::empty()),
+  }
+}
+
+/// Parse the keys from the notation. The `<...>` that is not a key notation is the literal chars,
+/// i.e. `<foo>` is the 5 keys `<`, `f`, `o`, `o`, `>`.
+pub fn parse(s: &str) -> Vec<KeyEvent> {
+  let mut keys = vec![];
+  let mut rest = s;
+  while let Some(c) = rest.chars().next() {
+    if c == '<' {
+      if let Some(end) = rest.find('>') {
+        if let Some(key_event) = _parse_special(&rest[1..end]) {
+          keys.push(key_event);
+          rest = &rest[end + 1..];
+          continue;
+        }
+      }
+    }
+    keys.push(_parse_char(c));
+    rest = &rest[c.len_utf8()..];
+  }
+  keys
+}
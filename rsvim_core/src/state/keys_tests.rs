@@ -0,0 +1,69 @@
+use super::keys::*;
+
+use crate::test::log::init as test_log_init;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+  KeyEvent::new_with_kind(code, modifiers, KeyEventKind::Press)
+}
+
+#[test]
+fn to_notation1() {
+  test_log_init();
+  let keys = vec![
+    key(KeyCode::Char('d'), KeyModifiers::empty()),
+    key(KeyCode::Char('A'), KeyModifiers::SHIFT),
+    key(KeyCode::Char('<'), KeyModifiers::empty()),
+    key(KeyCode::Char('w'), KeyModifiers::CONTROL),
+    key(KeyCode::Esc, KeyModifiers::empty()),
+    key(KeyCode::Enter, KeyModifiers::empty()),
+    key(KeyCode::Up, KeyModifiers::SHIFT),
+    key(KeyCode::BackTab, KeyModifiers::SHIFT),
+    key(KeyCode::F(5), KeyModifiers::empty()),
+  ];
+  assert_eq!(to_notations(&keys), "dA<lt><C-w><Esc><CR><S-Up><S-Tab><F5>");
+  assert!(to_notation(&key(KeyCode::Null, KeyModifiers::empty())).is_none());
+}
+
+#[test]
+fn parse1() {
+  test_log_init();
+  let keys = parse("dA<lt><C-W><esc><CR><S-Up><S-Tab><F5>");
+  assert_eq!(
+    keys,
+    vec![
+      key(KeyCode::Char('d'), KeyModifiers::empty()),
+      key(KeyCode::Char('A'), KeyModifiers::empty()),
+      key(KeyCode::Char('<'), KeyModifiers::empty()),
+      key(KeyCode::Char('w'), KeyModifiers::CONTROL),
+      key(KeyCode::Esc, KeyModifiers::empty()),
+      key(KeyCode::Enter, KeyModifiers::empty()),
+      key(KeyCode::Up, KeyModifiers::SHIFT),
+      key(KeyCode::BackTab, KeyModifiers::SHIFT),
+      key(KeyCode::F(5), KeyModifiers::empty()),
+    ]
+  );
+
+  // Not a key notation.
+  assert_eq!(to_notations(&parse("<a><foo")), "<lt>a><lt>foo");
+  assert_eq!(parse("<foo>").len(), 5);
+
+  // The raw control chars, i.e. the yanked line.
+  assert_eq!(
+    parse("x\x1b\n"),
+    vec![
+      key(KeyCode::Char('x'), KeyModifiers::empty()),
+      key(KeyCode::Esc, KeyModifiers::empty()),
+      key(KeyCode::Enter, KeyModifiers::empty()),
+    ]
+  );
+  assert_eq!(
+    parse("\x17"),
+    vec![key(KeyCode::Char('w'), KeyModifiers::CONTROL)]
+  );
+
+  // Round trip.
+  let s = "ciwfoo<Esc>j0<C-v>2<lt>";
+  assert_eq!(to_notations(&parse(s)), s);
+}
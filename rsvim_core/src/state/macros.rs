@@ -0,0 +1,114 @@
+//! Macros, i.e. `q{register}` records the typed keys into the register, and `@{register}` plays
+//! them back.
+//!
+//! The keys are recorded in all modes, and saved to the register in the
+//! [key notation](crate::state::keys) when the recording stops, so they can be edited as register
+//! text. The playing keys (of macros and `:normal`) are fed to the state machine the same as the
+//! typed keys, they stop on the first failed command (i.e. the motion fails), and the recursive
+//! macros stop at [`MAX_MACRO_DEPTH`].
+//!
+//! See: <https://vimhelp.org/repeat.txt.html#recording>.
+
+use crossterm::event::{KeyCode, KeyEvent};
+
+/// The max depth of the playing macros and `:normal`, i.e. the recursive macro `qaq` `qa...@aq`
+/// stops at this depth.
+pub const MAX_MACRO_DEPTH: usize = 100;
+
+/// Whether the macro can be recorded into the register `name`, i.e. `a`-`z`, `A`-`Z` (append),
+/// `0`-`9` and `"`.
+pub fn is_macro_register(name: char) -> bool {
+  name.is_ascii_alphanumeric() || name == '"'
+}
+
+#[derive(Debug, Clone, Default)]
+/// The recording and playing state of macros.
+pub struct Macros {
+  // The register and the keys being recorded.
+  recording: Option<(char, Vec<KeyEvent>)>,
+
+  // The last played register, i.e. `@@`.
+  last_played: Option<char>,
+
+  // The depth of the playing macros and `:normal`.
+  depth: usize,
+
+  // A command failed when playing keys, the rest keys are dropped.
+  failed: bool,
+}
+
+impl Macros {
+  /// The register being recorded.
+  pub fn recording(&self) -> Option<char> {
+    self.recording.as_ref().map(|(name, _)| *name)
+  }
+
+  /// Start recording the typed keys into the register `name`.
+  pub fn start_recording(&mut self, name: char) {
+    self.recording = Some((name, vec![]));
+  }
+
+  /// Record the typed key if recording.
+  pub fn push_key(&mut self, key_event: KeyEvent) {
+    if let Some((_, keys)) = &mut self.recording {
+      keys.push(key_event);
+    }
+  }
+
+  /// Stop recording, returns the register and the recorded keys. The last `q` that stops the
+  /// recording is not recorded.
+  pub fn stop_recording(&mut self) -> Option<(char, Vec<KeyEvent>)> {
+    let (name, mut keys) = self.recording.take()?;
+    if keys.last().map(|k| k.code) == Some(KeyCode::Char('q')) {
+      keys.pop();
+    }
+    Some((name, keys))
+  }
+
+  pub fn last_played(&self) -> Option<char> {
+    self.last_played
+  }
+
+  pub fn set_last_played(&mut self, name: char) {
+    self.last_played = Some(name);
+  }
+
+  /// The depth of the playing keys, `0` if no keys are being played.
+  pub fn depth(&self) -> usize {
+    self.depth
+  }
+
+  /// Start playing keys, returns `false` if it is deeper than [`MAX_MACRO_DEPTH`].
+  pub fn enter(&mut self) -> bool {
+    if self.depth >= MAX_MACRO_DEPTH {
+      self.failed = true;
+      return false;
+    }
+    if self.depth == 0 {
+      self.failed = false;
+    }
+    self.depth += 1;
+    true
+  }
+
+  /// Finish playing keys.
+  pub fn leave(&mut self) {
+    self.depth = self.depth.saturating_sub(1);
+    if self.depth == 0 {
+      self.failed = false;
+    }
+  }
+
+  /// A command failed, i.e. the motion fails. It stops the playing keys, and does nothing if no
+  /// keys are being played.
+  pub fn fail(&mut self) {
+    if self.depth > 0 {
+      self.failed = true;
+    }
+  }
+
+  /// Whether a command failed when playing keys.
+  pub fn is_failed(&self) -> bool {
+    self.failed
+  }
+}
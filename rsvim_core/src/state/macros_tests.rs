@@ -0,0 +1,68 @@
+use super::macros::*;
+
+use crate::test::log::init as test_log_init;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+fn key(c: char) -> KeyEvent {
+  KeyEvent::new_with_kind(
+    KeyCode::Char(c),
+    KeyModifiers::empty(),
+    KeyEventKind::Press,
+  )
+}
+
+#[test]
+fn recording1() {
+  test_log_init();
+  let mut macros = Macros::default();
+  macros.push_key(key('x'));
+  assert!(macros.stop_recording().is_none());
+
+  macros.start_recording('a');
+  assert_eq!(macros.recording(), Some('a'));
+  for c in "dwq".chars() {
+    macros.push_key(key(c));
+  }
+  // The last `q` is not recorded.
+  assert_eq!(
+    macros.stop_recording(),
+    Some(('a', vec![key('d'), key('w')]))
+  );
+  assert!(macros.recording().is_none());
+
+  assert!(is_macro_register('a'));
+  assert!(is_macro_register('Z'));
+  assert!(is_macro_register('1'));
+  assert!(is_macro_register('"'));
+  assert!(!is_macro_register('-'));
+  assert!(!is_macro_register(':'));
+}
+
+#[test]
+fn playing1() {
+  test_log_init();
+  let mut macros = Macros::default();
+
+  // Nothing fails when not playing.
+  macros.fail();
+  assert!(!macros.is_failed());
+
+  assert!(macros.enter());
+  assert!(macros.enter());
+  assert_eq!(macros.depth(), 2);
+  macros.fail();
+  assert!(macros.is_failed());
+  macros.leave();
+  assert!(macros.is_failed());
+  macros.leave();
+  assert!(!macros.is_failed());
+
+  // The depth guard.
+  for _ in 0..MAX_MACRO_DEPTH {
+    assert!(macros.enter());
+  }
+  assert!(!macros.enter());
+  assert!(macros.is_failed());
+  assert_eq!(macros.depth(), MAX_MACRO_DEPTH);
+}
@@ -17,6 +17,10 @@ pub enum Mode {
   OperatorPending,
   /// Insert mode.
   Insert,
+  /// Replace mode.
+  Replace,
+  /// Virtual replace mode.
+  VirtualReplace,
   /// Command-line mode, ex-command variant.
   CommandLineEx,
   /// Command-line mode, search forward variant.
@@ -35,6 +39,8 @@ impl Display for Mode {
       Mode::Select => write!(f, "Select"),
       Mode::OperatorPending => write!(f, "Operator-pending"),
       Mode::Insert => write!(f, "Insert"),
+      Mode::Replace => write!(f, "Replace"),
+      Mode::VirtualReplace => write!(f, "Virtual replace"),
       Mode::CommandLineEx => write!(f, "Command-line (ex)"),
       Mode::CommandLineSearchForward => {
         write!(f, "Command-line (search forward)")
@@ -56,6 +62,8 @@ impl Mode {
       Mode::Select,
       Mode::OperatorPending,
       Mode::Insert,
+      Mode::Replace,
+      Mode::VirtualReplace,
       Mode::CommandLineEx,
       Mode::Terminal,
     ]
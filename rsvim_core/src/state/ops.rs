@@ -1,8 +1,20 @@
 //! The low-level editor operations.
 
+use crate::buf::selection::SelectionKind;
+use crate::excommand::quit::QuitKind;
+
 use compact_str::CompactString;
 
+pub mod cmdline_ops;
 pub mod cursor_ops;
+pub mod motion_ops;
+pub mod replace_ops;
+pub mod visual_ops;
+
+#[cfg(test)]
+mod motion_ops_tests;
+#[cfg(test)]
+mod replace_ops_tests;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 /// A set of low-level editor operations between terminal keyboard/mouse events and editor
@@ -32,6 +44,47 @@ pub enum Operation {
   /// `(char_idx,line_idx)`, based on current buffer.
   CursorMoveTo((/* char_idx */ usize, /* lines_idx */ usize)),
 
+  /// Move cursor by `n` words, i.e. `w`, `b`, `e`, `ge` and the WORD motions `W`, `B`, `E`, `gE`.
+  CursorMoveByWord((WordMotion, /* big_word */ bool, /* n */ usize)),
+
+  /// Move cursor to the first char of current line, i.e. `0`.
+  CursorMoveToLineStart,
+
+  /// Move cursor to the first non-blank char of current line, i.e. `^`.
+  CursorMoveToFirstNonBlank,
+
+  /// Move cursor to the last char of the line `n-1` lines below, i.e. `$`.
+  CursorMoveToLineEnd(/* n */ usize),
+
+  /// Move cursor to the last non-blank char of the line `n-1` lines below, i.e. `g_`.
+  CursorMoveToLastNonBlank(/* n */ usize),
+
+  /// Move cursor to the `n`-th occurrence of the char in current line, i.e. `f`, `F`, `t`, `T`.
+  /// The `repeat` is `;` and `,`, it doesn't stop before the adjacent char for `t` and `T`.
+  CursorMoveToChar((CharFind, /* repeat */ bool, /* n */ usize)),
+
+  /// Move cursor to the first non-blank char of the line, i.e. `gg` and `G`. The line index is
+  /// clamped to the last line.
+  CursorMoveToLine(/* line_idx */ usize),
+
+  /// Move cursor to the matching bracket of the next bracket in current line, i.e. `%`.
+  CursorMoveToMatchingBracket,
+
+  /// Move cursor by `n` sentences forward (`)`) or backward (`(`).
+  CursorMoveBySentence((/* forward */ bool, /* n */ usize)),
+
+  /// Move cursor by `n` paragraphs forward (`}`) or backward (`{`).
+  CursorMoveByParagraph((/* forward */ bool, /* n */ usize)),
+
+  /// Move cursor to the `n`-th line from the top of window, i.e. `H`.
+  CursorMoveToWindowTop(/* n */ usize),
+
+  /// Move cursor to the middle line of window, i.e. `M`.
+  CursorMoveToWindowMiddle,
+
+  /// Move cursor to the `n`-th line from the bottom of window, i.e. `L`.
+  CursorMoveToWindowBottom(/* n */ usize),
+
   /// Scroll buffer by offset `(columns,lines)` relatively, based on current window.
   ///
   /// - For `columns` (not chars!), when negative it moves to left, when positive it moves to right.
@@ -58,21 +111,80 @@ pub enum Operation {
   /// `(column_idx,line_idx)` based on current buffer.
   WindowScrollTo((/* column_idx */ usize, /* line_idx */ usize)),
 
+  /// Scroll window to put the cursor line, or the line `line_idx` if it is given, at the top,
+  /// middle or bottom of window, and move cursor to the line, i.e. `zt`, `zz`, `zb`. The cursor
+  /// moves to the first non-blank char if `first_non_blank`, i.e. `z<CR>`, `z.`, `z-`.
+  WindowScrollCursorLineTo(
+    (
+      ScrollPosition,
+      /* first_non_blank */ bool,
+      /* line_idx */ Option<usize>,
+    ),
+  ),
+
   /// Goto insert mode. Allows to append or to go to new line.
   GotoInsertMode(GotoInsertModeVariant),
 
   /// Goto normal mode.
   GotoNormalMode,
 
+  /// Goto replace mode, i.e. `R`, or virtual replace mode if `true`, i.e. `gR`.
+  GotoReplaceMode(/* virtual replace */ bool),
+
+  /// Replace N-chars (the count) at cursor with the text, i.e. `r{char}`.
+  ReplaceChar(/* text */ CompactString),
+
   /// Insert text at cursor.
   CursorInsert(/* text */ CompactString),
 
+  /// Put text after cursor, i.e. the bracketed paste in normal mode.
+  CursorPutAfter(/* text */ CompactString),
+
   /// Delete N-chars text, to the left of cursor if negative, to the right of cursor if positive.
   CursorDelete(/* N-chars */ isize),
 
   /// Goto command-line ex mode.
   GotoCommandLineExMode,
 
+  /// Goto operator-pending mode, i.e. wait for the motion of the operator.
+  GotoOperatorPendingMode(Operator),
+
+  /// Goto visual mode with the selection type, or switch the selection type in visual mode.
+  GotoVisualMode(SelectionKind),
+
+  /// Reselect the last visual selection, i.e. `gv`.
+  VisualReselect,
+
+  /// Swap the two ends of the visual selection, i.e. `o` in visual mode.
+  VisualSwapEnds,
+
+  /// Goto select mode with the selection type, i.e. `gh`, `gH`.
+  GotoSelectMode(SelectionKind),
+
+  /// Toggle between visual mode and select mode, i.e. `<C-g>`.
+  SelectToggle,
+
+  /// Apply the operator on the visual selection.
+  VisualOperator(Operator),
+
+  /// Join the selected lines.
+  VisualJoinLines,
+
+  /// Repeat the last change, i.e. `.`, the count replaces the count of the last change.
+  RepeatLastChange,
+
+  /// Start recording the typed keys into the register, i.e. `qa`.
+  MacroStartRecording(/* register name */ char),
+
+  /// Stop recording the typed keys, i.e. `q`.
+  MacroStopRecording,
+
+  /// Play the keys in the register, i.e. `@a`, and `@@` plays the last played register.
+  MacroPlay(/* register name */ char),
+
+  /// The window command after `<C-w>`, i.e. `<C-w>v`.
+  WindowCommand(/* key */ char),
+
   /// Goto command-line search forward mode.
   GotoCommandLineSearchForwardMode,
 
@@ -82,8 +194,50 @@ pub enum Operation {
   /// Confirm ex command and goto normal mode.
   ConfirmExCommandAndGotoNormalMode,
 
-  /// Quit editor
-  EditorQuit,
+  /// Confirm search pattern and goto normal mode.
+  ConfirmSearchAndGotoNormalMode,
+
+  /// Recall the older command-line history that starts with the typed text.
+  CommandLineHistoryPrevious,
+
+  /// Recall the newer command-line history that starts with the typed text.
+  CommandLineHistoryNext,
+
+  /// Complete the command-line, or select the next completion match.
+  CommandLineCompleteNext,
+
+  /// Complete the command-line, or select the previous completion match.
+  CommandLineCompletePrevious,
+
+  /// Delete the word before cursor in command-line.
+  CommandLineDeleteWordBeforeCursor,
+
+  /// Delete all the text before cursor in command-line.
+  CommandLineDeleteToStart,
+
+  /// Move cursor one WORD left in command-line.
+  CommandLineCursorMoveWordLeft,
+
+  /// Move cursor one WORD right in command-line.
+  CommandLineCursorMoveWordRight,
+
+  /// Insert the text of a register at cursor in command-line.
+  CommandLineInsertRegister(/* register name */ char),
+
+  /// Insert the word under the cursor of current window at cursor in command-line.
+  CommandLineInsertWordUnderCursor,
+
+  /// Scroll the message pager by N lines, negative scrolls up.
+  MessagePagerScrollBy(/* lines */ isize),
+
+  /// Close the message pager.
+  MessagePagerClose,
+
+  /// Answer the confirmation prompt, i.e. `:confirm quit`.
+  ConfirmChoose(ConfirmChoice),
+
+  /// Quit editor (or close current window), i.e. `ZZ` and `ZQ`.
+  EditorQuit(QuitKind, /* bang */ bool),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -98,3 +252,150 @@ pub enum GotoInsertModeVariant {
   /// Create a new line and move cursor to next line
   NewLine,
 }
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// Where the cursor line is scrolled to in window.
+///
+/// See: <https://vimhelp.org/scroll.txt.html#scroll-cursor>.
+pub enum ScrollPosition {
+  /// The top of window, i.e. `zt`.
+  Top,
+
+  /// The middle of window, i.e. `zz`.
+  Middle,
+
+  /// The bottom of window, i.e. `zb`.
+  Bottom,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// The choices of the confirmation prompt to save the modified buffers.
+///
+/// See: <https://vimhelp.org/editing.txt.html#%3Aconfirm>.
+pub enum ConfirmChoice {
+  /// Save the buffer, i.e. `[Y]es`.
+  Yes,
+
+  /// Don't save the buffer, i.e. `(N)o`.
+  No,
+
+  /// Save all the modified buffers, i.e. `Save (A)ll`.
+  SaveAll,
+
+  /// Don't save any buffer, i.e. `(D)iscard All`.
+  DiscardAll,
+
+  /// Cancel the command, i.e. `(C)ancel`.
+  Cancel,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// A set of word motions.
+///
+/// See: <https://vimhelp.org/motion.txt.html#word-motions>.
+pub enum WordMotion {
+  /// To the start of next word, i.e. `w`.
+  Forward,
+
+  /// To the start of previous word, i.e. `b`.
+  Backward,
+
+  /// To the end of next word, i.e. `e`.
+  ForwardEnd,
+
+  /// To the end of previous word, i.e. `ge`.
+  BackwardEnd,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// A set of char find motions in current line.
+///
+/// See: <https://vimhelp.org/motion.txt.html#f>.
+pub enum CharFindKind {
+  /// To the char, i.e. `f`.
+  Forward,
+
+  /// Backward to the char, i.e. `F`.
+  Backward,
+
+  /// Till before the char, i.e. `t`.
+  TillForward,
+
+  /// Backward till after the char, i.e. `T`.
+  TillBackward,
+}
+
+impl CharFindKind {
+  /// The opposite direction, i.e. `,`.
+  pub fn reverse(&self) -> Self {
+    match self {
+      CharFindKind::Forward => CharFindKind::Backward,
+      CharFindKind::Backward => CharFindKind::Forward,
+      CharFindKind::TillForward => CharFindKind::TillBackward,
+      CharFindKind::TillBackward => CharFindKind::TillForward,
+    }
+  }
+
+  pub fn is_forward(&self) -> bool {
+    matches!(self, CharFindKind::Forward | CharFindKind::TillForward)
+  }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// The char find motion and the char, i.e. `fx`.
+pub struct CharFind {
+  pub kind: CharFindKind,
+  pub c: char,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// A set of possible case changes.
+pub enum CaseChange {
+  /// Switch case, i.e. `~`.
+  Toggle,
+
+  /// Make lowercase, i.e. `u`.
+  Lower,
+
+  /// Make uppercase, i.e. `U`.
+  Upper,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// A set of operators, they act on the text moved over by a motion, or the visual selection.
+///
+/// See: <https://vimhelp.org/motion.txt.html#operator>.
+pub enum Operator {
+  /// Delete, i.e. `d`.
+  Delete,
+
+  /// Yank, i.e. `y`.
+  Yank,
+
+  /// Delete and goto insert mode, i.e. `c`.
+  Change,
+
+  /// Shift lines right by one 'tab-stop', i.e. `>`.
+  ShiftRight,
+
+  /// Shift lines left by one 'tab-stop', i.e. `<`.
+  ShiftLeft,
+
+  /// Change case, i.e. `g~`, `gu` and `gU`.
+  ChangeCase(CaseChange),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// The type of motion, it decides which text is operated by an operator.
+///
+/// See: <https://vimhelp.org/motion.txt.html#exclusive>.
+pub enum MotionType {
+  /// The last char of the motion is not included.
+  Exclusive,
+
+  /// The last char of the motion is included.
+  Inclusive,
+
+  /// All the lines of the motion are included.
+  Linewise,
+}
@@ -0,0 +1,400 @@
+//! Command-line operations.
+
+use crate::buf::BuffersManager;
+use crate::buf::text::Text;
+use crate::content::TextContents;
+use crate::content::history::HistoryKind;
+use crate::excommand::complete::{
+  self, CompletionContext, CompletionItem, CompletionKind, WildModePart,
+};
+use crate::excommand::{ExCommandsManager, is_abbrev_of};
+use crate::js::JsFutureId;
+use crate::opt::{self, OptionTarget};
+use crate::prelude::*;
+use crate::state::ops::{Operation, cursor_ops};
+use crate::state::opt::GlobalOptions;
+use crate::ui::tree::*;
+
+use compact_str::{CompactString, ToCompactString};
+use tracing::trace;
+
+fn _cursor_char_idx(tree: &Tree) -> usize {
+  tree.command_line().unwrap().cursor_viewport().char_idx()
+}
+
+fn _line_chars(text: &Text) -> Vec<char> {
+  let rope = text.rope();
+  if rope.len_lines() == 0 {
+    return vec![];
+  }
+  rope.line(0).chars().filter(|c| *c != '\n').collect()
+}
+
+fn _is_word_char(c: char) -> bool {
+  c.is_alphanumeric() || c == '_'
+}
+
+/// Join multiple lines into a single line for command-line, i.e. line breaks are replaced with
+/// whitespaces and the trailing line break is removed.
+pub fn single_line(text: &str) -> CompactString {
+  let text = text
+    .strip_suffix("\r\n")
+    .or_else(|| text.strip_suffix('\n'))
+    .unwrap_or(text);
+  text
+    .replace("\r\n", " ")
+    .replace(['\n', '\r'], " ")
+    .to_compact_string()
+}
+
+/// Delete the word before cursor, i.e. `<C-W>`.
+///
+/// The whitespaces before cursor are deleted together with the word, and if the char before them
+/// is not a word char, the sequence of other non-blank chars is deleted instead.
+pub fn cmdline_delete_word_before_cursor(
+  tree: &mut Tree,
+  id: TreeNodeId,
+  text: &mut Text,
+) {
+  let chars = _line_chars(text);
+  let cursor_char_idx = _cursor_char_idx(tree).min(chars.len());
+  let mut i = cursor_char_idx;
+  while i > 0 && chars[i - 1].is_whitespace() {
+    i -= 1;
+  }
+  if i > 0 {
+    let word = _is_word_char(chars[i - 1]);
+    while i > 0
+      && !chars[i - 1].is_whitespace()
+      && _is_word_char(chars[i - 1]) == word
+    {
+      i -= 1;
+    }
+  }
+  let n = cursor_char_idx - i;
+  if n > 0 {
+    cursor_ops::cursor_delete(tree, id, text, -(n as isize));
+  }
+}
+
+/// Delete all the text before cursor, i.e. `<C-U>`.
+pub fn cmdline_delete_to_start(
+  tree: &mut Tree,
+  id: TreeNodeId,
+  text: &mut Text,
+) {
+  let n = _cursor_char_idx(tree);
+  if n > 0 {
+    cursor_ops::cursor_delete(tree, id, text, -(n as isize));
+  }
+}
+
+/// Move cursor one WORD left (or right if `forward` is `true`), i.e. `<C-Left>` and `<C-Right>`.
+///
+/// A WORD is a sequence of non-blank chars, moving left stops at the start of the WORD, moving
+/// right stops at the start of next WORD (or the end of line).
+pub fn cmdline_move_word(
+  tree: &mut Tree,
+  id: TreeNodeId,
+  text: &Text,
+  forward: bool,
+) {
+  let chars = _line_chars(text);
+  let cursor_char_idx = _cursor_char_idx(tree).min(chars.len());
+  let mut i = cursor_char_idx;
+  if forward {
+    while i < chars.len() && !chars[i].is_whitespace() {
+      i += 1;
+    }
+    while i < chars.len() && chars[i].is_whitespace() {
+      i += 1;
+    }
+  } else {
+    while i > 0 && chars[i - 1].is_whitespace() {
+      i -= 1;
+    }
+    while i > 0 && !chars[i - 1].is_whitespace() {
+      i -= 1;
+    }
+  }
+  cursor_ops::cursor_move(
+    tree,
+    id,
+    text,
+    Operation::CursorMoveTo((i, 0)),
+    true,
+  );
+}
+
+/// Get the text of register `name` for command-line, the read-only registers are resolved here:
+///
+/// - `:`: The last command-line.
+/// - `/`: The last search pattern.
+/// - `%`: The file name of current buffer.
+pub fn register_text(
+  tree: &Tree,
+  contents: &TextContents,
+  name: char,
+) -> Option<CompactString> {
+  let history = contents.command_line_history();
+  match name {
+    ':' => history
+      .entries(HistoryKind::Cmd)
+      .back()
+      .map(|e| e.text().clone()),
+    '/' => [HistoryKind::SearchForward, HistoryKind::SearchBackward]
+      .iter()
+      .filter_map(|kind| history.entries(*kind).back())
+      .max_by_key(|e| e.timestamp())
+      .map(|e| e.text().clone()),
+    '%' => tree
+      .current_window()
+      .and_then(|w| w.buffer().upgrade())
+      .and_then(|buf| {
+        lock!(buf)
+          .filename()
+          .as_ref()
+          .map(|f| f.to_string_lossy().to_compact_string())
+      }),
+    _ => contents.registers().get(name).cloned(),
+  }
+}
+
+/// Get the word under the cursor of current window, i.e. for `<C-R><C-W>`.
+///
+/// If the cursor is not on a word, the first word after cursor in the line is used.
+pub fn word_under_window_cursor(tree: &Tree) -> Option<CompactString> {
+  let window = tree.current_window()?;
+  let cursor_viewport = window.cursor_viewport();
+  let buffer = window.buffer().upgrade()?;
+  let buffer = lock!(buffer);
+  let line = buffer.text().rope().get_line(cursor_viewport.line_idx())?;
+  let chars: Vec<char> = line.chars().collect();
+  let mut start = cursor_viewport.char_idx().min(chars.len());
+  while start < chars.len() && !_is_word_char(chars[start]) {
+    start += 1;
+  }
+  if start >= chars.len() {
+    return None;
+  }
+  while start > 0 && _is_word_char(chars[start - 1]) {
+    start -= 1;
+  }
+  let mut end = start;
+  while end < chars.len() && _is_word_char(chars[end]) {
+    end += 1;
+  }
+  Some(chars[start..end].iter().collect())
+}
+
+/// Get the command-line content before cursor.
+pub fn cmdline_text_before_cursor(
+  tree: &Tree,
+  contents: &TextContents,
+) -> CompactString {
+  let cursor_char_idx =
+    tree.command_line().unwrap().cursor_viewport().char_idx();
+  let rope = contents.command_line_content().rope();
+  if rope.len_lines() == 0 {
+    return CompactString::new("");
+  }
+  rope
+    .line(0)
+    .chars()
+    .take(cursor_char_idx)
+    .filter(|c| *c != '\n')
+    .collect()
+}
+
+/// Replace the command-line content between `start_char_idx` and cursor with `payload`, the
+/// cursor is moved to the end of `payload`.
+pub fn cmdline_replace_before_cursor(
+  tree: &mut Tree,
+  id: TreeNodeId,
+  contents: &mut TextContents,
+  start_char_idx: usize,
+  payload: CompactString,
+) {
+  let cursor_char_idx =
+    tree.command_line().unwrap().cursor_viewport().char_idx();
+  let n = cursor_char_idx.saturating_sub(start_char_idx);
+  let text = contents.command_line_content_mut();
+  if n > 0 {
+    cursor_ops::cursor_delete(tree, id, text, -(n as isize));
+  }
+  if !payload.is_empty() {
+    cursor_ops::cursor_insert(tree, id, text, payload);
+  }
+}
+
+/// Collect the completion matches for the context.
+///
+/// Returns `None` if the matches are provided by js completer, i.e. the arguments of user ex
+/// commands that don't have a builtin `complete` kind.
+pub fn collect_completion_matches(
+  tree: &Tree,
+  buffers: &BuffersManager,
+  global_options: &GlobalOptions,
+  ex_commands: &ExCommandsManager,
+  context: &CompletionContext,
+) -> Option<Vec<CompletionItem>> {
+  let lead = context.lead.as_str();
+  let items = match context.kind {
+    CompletionKind::Command => {
+      complete::complete_commands(lead, &ex_commands.names())
+    }
+    CompletionKind::Option => complete::complete_options(lead),
+    CompletionKind::OptionValue(name) => {
+      let def = opt::find_option(name).unwrap();
+      let target = if is_abbrev_of(&context.command, "setlocal", 4) {
+        OptionTarget::Local
+      } else if is_abbrev_of(&context.command, "setglobal", 4) {
+        OptionTarget::Global
+      } else {
+        OptionTarget::LocalAndGlobal
+      };
+      let current =
+        opt::get_option_value(tree, buffers, global_options, def, target);
+      complete::complete_option_values(def, lead, &current)
+    }
+    CompletionKind::File | CompletionKind::Directory => {
+      let cwd = std::env::current_dir().unwrap_or_default();
+      let home = dirs::home_dir();
+      complete::complete_files(
+        lead,
+        &cwd,
+        home.as_deref(),
+        global_options.wild_ignore(),
+        context.kind == CompletionKind::Directory,
+      )
+    }
+    CompletionKind::Buffer => {
+      let names: Vec<CompactString> = buffers
+        .iter()
+        .filter_map(|(_, buf)| {
+          lock!(buf)
+            .filename()
+            .as_ref()
+            .map(|f| f.to_string_lossy().to_compact_string())
+        })
+        .collect();
+      complete::complete_buffers(lead, &names)
+    }
+    CompletionKind::History => complete::complete_history_names(lead),
+    CompletionKind::Custom => {
+      let kind = ex_commands
+        .find(&context.command)
+        .ok()
+        .and_then(|cmd| cmd.options().complete)
+        .filter(|kind| *kind != CompletionKind::Custom)?;
+      let context = CompletionContext {
+        kind,
+        ..context.clone()
+      };
+      return collect_completion_matches(
+        tree,
+        buffers,
+        global_options,
+        ex_commands,
+        &context,
+      );
+    }
+  };
+  Some(items)
+}
+
+fn _wild_mode_part(
+  global_options: &GlobalOptions,
+  tab_count: usize,
+) -> WildModePart {
+  let parts = complete::parse_wild_mode(global_options.wild_mode())
+    .unwrap_or_else(|| {
+      vec![WildModePart {
+        longest: false,
+        full: true,
+        list: true,
+      }]
+    });
+  parts[tab_count.min(parts.len() - 1)]
+}
+
+/// Apply a `<Tab>` (or `<S-Tab>` if `forward` is `false`) to the started completion, by the
+/// 'wildmode' option:
+///
+/// - If there's only one match, it is inserted and the completion is finished.
+/// - Otherwise, it completes the longest common string, shows the wildmenu and selects the next
+///   match, by the part of 'wildmode' for this `<Tab>`.
+pub fn cmdline_complete_apply(
+  tree: &mut Tree,
+  id: TreeNodeId,
+  contents: &mut TextContents,
+  global_options: &GlobalOptions,
+  forward: bool,
+) {
+  let popup = global_options
+    .wild_options()
+    .split(',')
+    .any(|item| item == "pum");
+  let part = _wild_mode_part(
+    global_options,
+    contents.command_line_wildmenu().tab_count(),
+  );
+  trace!("cmdline complete part:{:?}, forward:{:?}", part, forward);
+
+  let wildmenu = contents.command_line_wildmenu_mut();
+  let start_char_idx = wildmenu.start_char_idx();
+  let payload = match wildmenu.items().len() {
+    0 => {
+      wildmenu.clear();
+      None
+    }
+    1 => {
+      let word = wildmenu.items()[0].word().clone();
+      wildmenu.clear();
+      Some(word)
+    }
+    _ => {
+      let mut payload = None;
+      if part.longest {
+        let lcp = complete::longest_common_prefix(wildmenu.items());
+        if lcp.chars().count() > wildmenu.original().chars().count() {
+          wildmenu.set_original(lcp.clone());
+          payload = Some(lcp);
+        }
+      }
+      wildmenu.set_popup(popup);
+      if part.list {
+        wildmenu.set_visible(true);
+      }
+      if part.full || !forward {
+        payload = Some(wildmenu.select_next(forward));
+      }
+      payload
+    }
+  };
+
+  if let Some(payload) = payload {
+    cmdline_replace_before_cursor(tree, id, contents, start_char_idx, payload);
+  }
+}
+
+/// Fill the matches from js completer, for the pending completion request `future_id`.
+///
+/// NOTE: The matches are dropped if the completion is already cancelled, i.e. user typed other
+/// keys or left the command-line mode.
+pub fn cmdline_complete_resolve(
+  tree: &mut Tree,
+  id: TreeNodeId,
+  contents: &mut TextContents,
+  global_options: &GlobalOptions,
+  future_id: JsFutureId,
+  items: Vec<CompletionItem>,
+) {
+  let wildmenu = contents.command_line_wildmenu_mut();
+  if wildmenu.pending() != Some(future_id) {
+    trace!("cmdline complete resolve dropped:{:?}", future_id);
+    return;
+  }
+  wildmenu.resolve_pending(items);
+  cmdline_complete_apply(tree, id, contents, global_options, true);
+}
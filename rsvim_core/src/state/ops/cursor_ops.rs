@@ -2,7 +2,8 @@
 
 use crate::buf::text::Text;
 use crate::coord::U16Rect;
-use crate::state::ops::Operation;
+use crate::state::ops::motion_ops;
+use crate::state::ops::{Operation, ScrollPosition};
 use crate::ui::tree::*;
 use crate::ui::viewport::{
   CursorViewport, CursorViewportArc, Viewport, ViewportArc,
@@ -431,6 +432,7 @@ pub fn _update_viewport_after_text_changed(
       "after updated_cursor_viewport:{:?}",
       updated_cursor_viewport
     );
+    let moved = _cursor_moved(&cursor_viewport, &updated_cursor_viewport);
     match node {
       TreeNode::Window(window) => {
         window.set_cursor_viewport(updated_cursor_viewport)
@@ -440,9 +442,18 @@ pub fn _update_viewport_after_text_changed(
       }
       _ => unreachable!(),
     }
+    if moved {
+      tree.fire_cursor_moved(id);
+    }
   }
 }
 
+// Whether the cursor is moved to another char, the cursor events are not fired when only the
+// viewport scrolls.
+fn _cursor_moved(old: &CursorViewport, new: &CursorViewport) -> bool {
+  (old.line_idx(), old.char_idx()) != (new.line_idx(), new.char_idx())
+}
+
 /// High-level cursor move operation.
 ///
 /// This API will move the cursor (and possibly scroll the widget/window it belongs to), as if the
@@ -479,6 +490,20 @@ pub fn cursor_move(
     _ => unreachable!(),
   };
 
+  // Resolve the motions to the absolute target position.
+  let op = if motion_ops::is_motion(&op) {
+    let cursor = (cursor_viewport.line_idx(), cursor_viewport.char_idx());
+    match motion_ops::motion_target(text, &viewport, &op, cursor) {
+      Some(target) => {
+        let (line_idx, char_idx) = motion_ops::clamp_target(text, target);
+        Operation::CursorMoveTo((char_idx, line_idx))
+      }
+      None => return,
+    }
+  } else {
+    op
+  };
+
   // Only move cursor when it is different from current cursor.
   let (target_cursor_char, target_cursor_line, move_direction) = if include_eol
   {
@@ -552,6 +577,7 @@ pub fn cursor_move(
     );
 
     if let Some(new_cursor_viewport) = new_cursor_viewport {
+      let moved = _cursor_moved(&cursor_viewport, &new_cursor_viewport);
       match node {
         TreeNode::Window(window) => {
           window.set_cursor_viewport(new_cursor_viewport.clone());
@@ -573,10 +599,183 @@ pub fn cursor_move(
         }
         _ => unreachable!(),
       }
+      if moved {
+        tree.fire_cursor_moved(id);
+      }
     }
   }
 }
 
+/// Update the viewport after the window (or command-line) specified by node `id` is resized, the
+/// cursor stays on the same position and the viewport scrolls to keep it visible.
+pub fn update_viewport_after_resized(
+  tree: &mut Tree,
+  id: TreeNodeId,
+  text: &Text,
+  include_eol: bool,
+) {
+  debug_assert!(tree.node(id).is_some());
+  let cursor_viewport = match tree.node(id).unwrap() {
+    TreeNode::Window(window) => window.cursor_viewport(),
+    TreeNode::CommandLine(cmdline) => cmdline.cursor_viewport(),
+    _ => unreachable!(),
+  };
+  let (line_idx, char_idx) = motion_ops::clamp_target(
+    text,
+    (cursor_viewport.line_idx(), cursor_viewport.char_idx()),
+  );
+
+  _update_viewport_after_text_changed(tree, id, text);
+  let op = Operation::CursorMoveTo((char_idx, line_idx));
+  cursor_move(tree, id, text, op, include_eol);
+}
+
+/// Update the viewports of the windows after the `text` is changed outside of them, i.e. by the
+/// js plugins. The cursors stay on the same positions if they are still inside the text.
+pub fn update_viewports_after_text_changed(
+  tree: &mut Tree,
+  window_ids: &[TreeNodeId],
+  text: &Text,
+  include_eol: bool,
+) {
+  for window_id in window_ids {
+    update_viewport_after_resized(tree, *window_id, text, include_eol);
+  }
+}
+
+/// High-level window scroll operation.
+///
+/// This API scrolls the window specified by node `id`, to put the cursor line (or the line given
+/// by the operation) at the top, middle or bottom of the window, then moves the cursor to the line.
+///
+/// NOTE: The position is calculated by buffer lines, i.e. the wrapped rows of a long line are not
+/// counted.
+///
+/// # Panics
+///
+/// It panics if the operation is not `Operation::WindowScrollCursorLineTo`, or the node is not a
+/// window.
+pub fn window_scroll_cursor_line_to(
+  tree: &mut Tree,
+  id: TreeNodeId,
+  text: &Text,
+  op: Operation,
+) {
+  let (position, first_non_blank, line_idx) = match op {
+    Operation::WindowScrollCursorLineTo((p, f, l)) => (p, f, l),
+    _ => unreachable!(),
+  };
+  debug_assert!(tree.node_mut(id).is_some());
+  let window = match tree.node_mut(id).unwrap() {
+    TreeNode::Window(window) => window,
+    _ => unreachable!(),
+  };
+  let actual_shape = *window.content().actual_shape();
+  let local_options = *window.options();
+  let viewport = window.viewport();
+  let cursor_viewport = window.cursor_viewport();
+
+  let line_idx = line_idx.unwrap_or(cursor_viewport.line_idx());
+  let (line_idx, _) = motion_ops::clamp_target(text, (line_idx, 0));
+  let height = std::cmp::max(actual_shape.height() as usize, 1);
+  let start_line = match position {
+    ScrollPosition::Top => line_idx,
+    ScrollPosition::Middle => line_idx.saturating_sub((height - 1) / 2),
+    ScrollPosition::Bottom => line_idx.saturating_sub(height - 1),
+  };
+  if let Some(new_viewport) = raw_viewport_scroll_to(
+    &viewport,
+    &actual_shape,
+    &local_options,
+    text,
+    Operation::WindowScrollTo((viewport.start_column_idx(), start_line)),
+  ) {
+    window.set_viewport(new_viewport);
+  }
+
+  let op = if first_non_blank {
+    Operation::CursorMoveToLine(line_idx)
+  } else {
+    Operation::CursorMoveTo((cursor_viewport.char_idx(), line_idx))
+  };
+  cursor_move(tree, id, text, op, false);
+}
+
+/// High-level window scroll operation.
+///
+/// This API scrolls the window specified by node `id` by `Operation::WindowScroll*`, as if the
+/// user is scrolling with the mouse wheel. The cursor stays at the same position if it is still
+/// shown in the window, otherwise it moves to the nearest shown line/char.
+///
+/// # Panics
+///
+/// It panics if the operation is not `Operation::WindowScroll*`, or the node is not a window.
+pub fn window_scroll(
+  tree: &mut Tree,
+  id: TreeNodeId,
+  text: &Text,
+  op: Operation,
+  include_eol: bool,
+) {
+  debug_assert!(tree.node_mut(id).is_some());
+  let window = match tree.node_mut(id).unwrap() {
+    TreeNode::Window(window) => window,
+    _ => unreachable!(),
+  };
+  let actual_shape = *window.content().actual_shape();
+  let local_options = *window.options();
+  let viewport = window.viewport();
+  let cursor_viewport = window.cursor_viewport();
+
+  let (start_column, start_line) = normalize_to_window_scroll_to(
+    op,
+    viewport.start_column_idx(),
+    viewport.start_line_idx(),
+  );
+  let new_viewport = match raw_viewport_scroll_to(
+    &viewport,
+    &actual_shape,
+    &local_options,
+    text,
+    Operation::WindowScrollTo((start_column, start_line)),
+  ) {
+    Some(new_viewport) => new_viewport,
+    None => return,
+  };
+  window.set_viewport(new_viewport.clone());
+
+  // Keep the cursor inside the new viewport.
+  let lines = new_viewport.lines();
+  let (first_line_idx, last_line_idx) = match (lines.first(), lines.last()) {
+    (Some((first, _)), Some((last, _))) => (*first, *last),
+    _ => return,
+  };
+  let line_idx = cursor_viewport
+    .line_idx()
+    .clamp(first_line_idx, last_line_idx);
+  let rows = lines.get(&line_idx).unwrap().rows();
+  let char_idx = match (rows.first(), rows.last()) {
+    (Some((_, first_row)), Some((_, last_row))) => {
+      let start_char_idx = first_row.start_char_idx();
+      let end_char_idx = std::cmp::max(
+        last_row.end_char_idx().saturating_sub(1),
+        start_char_idx,
+      );
+      cursor_viewport
+        .char_idx()
+        .clamp(start_char_idx, end_char_idx)
+    }
+    _ => 0,
+  };
+  cursor_move(
+    tree,
+    id,
+    text,
+    Operation::CursorMoveTo((char_idx, line_idx)),
+    include_eol,
+  );
+}
+
 /// High-level cursor insert operation.
 ///
 /// This API will insert text at the cursor (and possibly scroll the widget/window it belongs to),
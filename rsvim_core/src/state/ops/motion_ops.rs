@@ -0,0 +1,529 @@
+//! Motion operations, i.e. the cursor motions by words, chars, lines, brackets, sentences and
+//! paragraphs.
+//!
+//! They calculate the target cursor position `(line_idx, char_idx)` of the motion, the cursor
+//! operations then move the cursor to it. The operators use the raw target, i.e. the `w` motion at
+//! the last word goes to the end of text.
+
+use crate::buf::text::Text;
+use crate::buf::text_object::sentences;
+use crate::buf::unicode::{BLANK_CLASS, char_class};
+use crate::state::ops::{
+  CharFind, CharFindKind, MotionType, Operation, WordMotion,
+};
+use crate::ui::viewport::Viewport;
+
+/// Whether the operation is a motion handled by [`motion_target`].
+pub fn is_motion(op: &Operation) -> bool {
+  matches!(
+    op,
+    Operation::CursorMoveByWord(_)
+      | Operation::CursorMoveToLineStart
+      | Operation::CursorMoveToFirstNonBlank
+      | Operation::CursorMoveToLineEnd(_)
+      | Operation::CursorMoveToLastNonBlank(_)
+      | Operation::CursorMoveToChar(_)
+      | Operation::CursorMoveToLine(_)
+      | Operation::CursorMoveToMatchingBracket
+      | Operation::CursorMoveBySentence(_)
+      | Operation::CursorMoveByParagraph(_)
+      | Operation::CursorMoveToWindowTop(_)
+      | Operation::CursorMoveToWindowMiddle
+      | Operation::CursorMoveToWindowBottom(_)
+  )
+}
+
+/// The motion type of the operation when it is used after an operator.
+///
+/// See: <https://vimhelp.org/motion.txt.html#exclusive>.
+pub fn motion_type(op: &Operation) -> MotionType {
+  match op {
+    Operation::CursorMoveUpBy(_)
+    | Operation::CursorMoveDownBy(_)
+    | Operation::CursorMoveToLine(_)
+    | Operation::CursorMoveToWindowTop(_)
+    | Operation::CursorMoveToWindowMiddle
+    | Operation::CursorMoveToWindowBottom(_) => MotionType::Linewise,
+    Operation::CursorMoveByWord((WordMotion::ForwardEnd, _, _))
+    | Operation::CursorMoveByWord((WordMotion::BackwardEnd, _, _))
+    | Operation::CursorMoveToLineEnd(_)
+    | Operation::CursorMoveToLastNonBlank(_)
+    | Operation::CursorMoveToMatchingBracket => MotionType::Inclusive,
+    Operation::CursorMoveToChar((find, _, _)) if find.kind.is_forward() => {
+      MotionType::Inclusive
+    }
+    _ => MotionType::Exclusive,
+  }
+}
+
+// Position {
+
+fn _absolute(text: &Text, (line_idx, char_idx): (usize, usize)) -> usize {
+  text.rope().line_to_char(line_idx) + char_idx
+}
+
+fn _position(text: &Text, absolute: usize) -> (usize, usize) {
+  let line_idx = text.rope().char_to_line(absolute);
+  (line_idx, absolute - text.rope().line_to_char(line_idx))
+}
+
+// The last line in text, the empty last line after the eol at the end of text is not included.
+fn _last_line(text: &Text) -> usize {
+  let rope = text.rope();
+  let len_lines = rope.len_lines();
+  if len_lines > 1 && rope.line_to_char(len_lines - 1) >= rope.len_chars() {
+    len_lines - 2
+  } else {
+    len_lines.saturating_sub(1)
+  }
+}
+
+// The chars on the line, without the eol.
+fn _line_chars(text: &Text, line_idx: usize) -> Vec<char> {
+  let len_chars = text
+    .last_char_on_line_no_eol(line_idx)
+    .map(|c| c + 1)
+    .unwrap_or(0);
+  text.rope().line(line_idx).chars().take(len_chars).collect()
+}
+
+fn _is_eol_char(c: char) -> bool {
+  c == '\n' || c == '\r'
+}
+
+// Whether the absolute char is the eol of an empty line.
+fn _is_empty_line_at(text: &Text, i: usize) -> bool {
+  let rope = text.rope();
+  _is_eol_char(rope.char(i)) && (i == 0 || rope.char(i - 1) == '\n')
+}
+
+// The first non-blank char on the line, or the last char if the line is blank.
+fn _first_non_blank(text: &Text, line_idx: usize) -> usize {
+  let chars = _line_chars(text, line_idx);
+  chars
+    .iter()
+    .position(|c| !c.is_whitespace())
+    .unwrap_or(chars.len().saturating_sub(1))
+}
+
+// Position }
+
+/// Clamp the motion target to the last char of text, i.e. the cursor in normal mode cannot stay on
+/// the empty last line after the eol at the end of text.
+pub fn clamp_target(
+  text: &Text,
+  (line_idx, char_idx): (usize, usize),
+) -> (usize, usize) {
+  let last_line = _last_line(text);
+  if line_idx > last_line {
+    let last_char = text.last_char_on_line_no_eol(last_line).unwrap_or(0);
+    (last_line, last_char)
+  } else {
+    (line_idx, char_idx)
+  }
+}
+
+/// Calculate the target cursor position `(line_idx, char_idx)` of the motion operation from the
+/// `cursor` `(line_idx, char_idx)`, the `viewport` is used by the `H`, `M`, `L` motions.
+///
+/// It returns `None` if the motion fails, i.e. the char is not found.
+pub fn motion_target(
+  text: &Text,
+  viewport: &Viewport,
+  op: &Operation,
+  cursor: (usize, usize),
+) -> Option<(usize, usize)> {
+  let line_idx = cursor.0;
+  let target = match *op {
+    Operation::CursorMoveByWord((motion, big_word, n)) => {
+      let at = _absolute(text, cursor);
+      let mut pos = at;
+      for _ in 0..std::cmp::max(n, 1) {
+        let next = match motion {
+          WordMotion::Forward => _word_forward(text, pos, big_word),
+          WordMotion::Backward => _word_backward(text, pos, big_word),
+          WordMotion::ForwardEnd => _word_end_forward(text, pos, big_word),
+          WordMotion::BackwardEnd => _word_end_backward(text, pos, big_word),
+        };
+        if next == pos {
+          break;
+        }
+        pos = next;
+      }
+      _position(text, pos)
+    }
+    Operation::CursorMoveToLineStart => (line_idx, 0),
+    Operation::CursorMoveToFirstNonBlank => {
+      (line_idx, _first_non_blank(text, line_idx))
+    }
+    Operation::CursorMoveToLineEnd(n) => {
+      let line_idx =
+        std::cmp::min(line_idx + std::cmp::max(n, 1) - 1, _last_line(text));
+      let last_char = text.last_char_on_line_no_eol(line_idx).unwrap_or(0);
+      (line_idx, last_char)
+    }
+    Operation::CursorMoveToLastNonBlank(n) => {
+      let line_idx =
+        std::cmp::min(line_idx + std::cmp::max(n, 1) - 1, _last_line(text));
+      let chars = _line_chars(text, line_idx);
+      let last_char =
+        chars.iter().rposition(|c| !c.is_whitespace()).unwrap_or(0);
+      (line_idx, last_char)
+    }
+    Operation::CursorMoveToChar((find, repeat, n)) => {
+      (line_idx, _char_find(text, cursor, find, repeat, n)?)
+    }
+    Operation::CursorMoveToLine(target_line) => {
+      let target_line = std::cmp::min(target_line, _last_line(text));
+      (target_line, _first_non_blank(text, target_line))
+    }
+    Operation::CursorMoveToMatchingBracket => _matching_bracket(text, cursor)?,
+    Operation::CursorMoveBySentence((forward, n)) => {
+      _sentence(text, cursor, forward, n)
+    }
+    Operation::CursorMoveByParagraph((forward, n)) => {
+      _paragraph(text, line_idx, forward, n)
+    }
+    Operation::CursorMoveToWindowTop(_)
+    | Operation::CursorMoveToWindowMiddle
+    | Operation::CursorMoveToWindowBottom(_) => {
+      let top = viewport.start_line_idx();
+      let bottom = std::cmp::min(
+        viewport.end_line_idx().saturating_sub(1),
+        _last_line(text),
+      );
+      let bottom = std::cmp::max(top, bottom);
+      let target_line = match *op {
+        Operation::CursorMoveToWindowTop(n) => {
+          std::cmp::min(top + std::cmp::max(n, 1) - 1, bottom)
+        }
+        Operation::CursorMoveToWindowBottom(n) => {
+          std::cmp::max(bottom.saturating_sub(std::cmp::max(n, 1) - 1), top)
+        }
+        _ => top + (bottom - top) / 2,
+      };
+      (target_line, _first_non_blank(text, target_line))
+    }
+    _ => return None,
+  };
+
+  Some(target)
+}
+
+// Word {
+
+// The class of the absolute char, the eol is blank.
+fn _class(text: &Text, i: usize, big_word: bool) -> usize {
+  let c = text.rope().char(i);
+  if _is_eol_char(c) {
+    BLANK_CLASS
+  } else {
+    char_class(text.options(), c, big_word)
+  }
+}
+
+fn _is_eol_at(text: &Text, i: usize) -> bool {
+  _is_eol_char(text.rope().char(i))
+}
+
+// The start of next word, an empty line is also a word. It returns the end of text if there's no
+// more words.
+fn _word_forward(text: &Text, at: usize, big_word: bool) -> usize {
+  let n = text.rope().len_chars();
+  let mut i = at;
+  if i < n && _class(text, i, big_word) != BLANK_CLASS {
+    let class = _class(text, i, big_word);
+    while i < n && !_is_eol_at(text, i) && _class(text, i, big_word) == class {
+      i += 1;
+    }
+  }
+  while i < n {
+    if _is_eol_at(text, i) {
+      if i != at && _is_empty_line_at(text, i) {
+        break;
+      }
+    } else if _class(text, i, big_word) != BLANK_CLASS {
+      break;
+    }
+    i += 1;
+  }
+  i
+}
+
+// The start of previous word, an empty line is also a word.
+fn _word_backward(text: &Text, at: usize, big_word: bool) -> usize {
+  if at == 0 {
+    return 0;
+  }
+  let mut i = at - 1;
+  loop {
+    if _is_eol_at(text, i) {
+      if _is_empty_line_at(text, i) {
+        return i;
+      }
+    } else if _class(text, i, big_word) != BLANK_CLASS {
+      break;
+    }
+    if i == 0 {
+      return 0;
+    }
+    i -= 1;
+  }
+  let class = _class(text, i, big_word);
+  while i > 0
+    && !_is_eol_at(text, i - 1)
+    && _class(text, i - 1, big_word) == class
+  {
+    i -= 1;
+  }
+  i
+}
+
+// The end of next word, the empty lines are skipped.
+fn _word_end_forward(text: &Text, at: usize, big_word: bool) -> usize {
+  let n = text.rope().len_chars();
+  let mut i = at + 1;
+  while i < n && _class(text, i, big_word) == BLANK_CLASS {
+    i += 1;
+  }
+  if i >= n {
+    return at;
+  }
+  let class = _class(text, i, big_word);
+  while i + 1 < n
+    && !_is_eol_at(text, i + 1)
+    && _class(text, i + 1, big_word) == class
+  {
+    i += 1;
+  }
+  i
+}
+
+// The end of previous word, an empty line is also a word.
+fn _word_end_backward(text: &Text, at: usize, big_word: bool) -> usize {
+  let n = text.rope().len_chars();
+  let mut i = at;
+  if i < n && _class(text, i, big_word) != BLANK_CLASS {
+    let class = _class(text, i, big_word);
+    while i > 0
+      && !_is_eol_at(text, i - 1)
+      && _class(text, i - 1, big_word) == class
+    {
+      i -= 1;
+    }
+  }
+  if i == 0 {
+    return 0;
+  }
+  i -= 1;
+  loop {
+    if _is_eol_at(text, i) {
+      if _is_empty_line_at(text, i) {
+        return i;
+      }
+    } else if _class(text, i, big_word) != BLANK_CLASS {
+      return i;
+    }
+    if i == 0 {
+      return 0;
+    }
+    i -= 1;
+  }
+}
+
+// Word }
+
+// Char {
+
+// Find the `n`-th char in current line, the `repeat` skips the adjacent char for `t` and `T`.
+fn _char_find(
+  text: &Text,
+  (line_idx, char_idx): (usize, usize),
+  find: CharFind,
+  repeat: bool,
+  n: usize,
+) -> Option<usize> {
+  let chars = _line_chars(text, line_idx);
+  let till = matches!(
+    find.kind,
+    CharFindKind::TillForward | CharFindKind::TillBackward
+  );
+  let skip = if till && repeat { 1 } else { 0 };
+
+  let mut idx = char_idx;
+  if find.kind.is_forward() {
+    idx += skip;
+    for _ in 0..std::cmp::max(n, 1) {
+      idx = idx + 1 + chars.iter().skip(idx + 1).position(|c| *c == find.c)?;
+    }
+    let idx = if till { idx - 1 } else { idx };
+    if idx == char_idx { None } else { Some(idx) }
+  } else {
+    idx = idx.checked_sub(skip)?;
+    for _ in 0..std::cmp::max(n, 1) {
+      idx = chars.iter().take(idx).rposition(|c| *c == find.c)?;
+    }
+    let idx = if till { idx + 1 } else { idx };
+    if idx == char_idx { None } else { Some(idx) }
+  }
+}
+
+// Char }
+
+// Bracket {
+
+// The matching bracket of the first bracket at or after the cursor in current line.
+fn _matching_bracket(
+  text: &Text,
+  (line_idx, char_idx): (usize, usize),
+) -> Option<(usize, usize)> {
+  let brackets = [('(', ')'), ('[', ']'), ('{', '}')];
+  let chars = _line_chars(text, line_idx);
+  let (offset, c) = chars
+    .iter()
+    .enumerate()
+    .skip(char_idx)
+    .find(|(_, c)| brackets.iter().any(|(o, e)| *c == o || *c == e))?;
+  let (open, close) = *brackets.iter().find(|(o, e)| o == c || e == c)?;
+  let at = _absolute(text, (line_idx, offset));
+
+  let rope = text.rope();
+  let mut depth = 0_usize;
+  if *c == open {
+    for i in at + 1..rope.len_chars() {
+      let c = rope.char(i);
+      if c == open {
+        depth += 1;
+      } else if c == close {
+        if depth == 0 {
+          return Some(_position(text, i));
+        }
+        depth -= 1;
+      }
+    }
+  } else {
+    let mut i = at;
+    while i > 0 {
+      i -= 1;
+      let c = rope.char(i);
+      if c == close {
+        depth += 1;
+      } else if c == open {
+        if depth == 0 {
+          return Some(_position(text, i));
+        }
+        depth -= 1;
+      }
+    }
+  }
+  None
+}
+
+// Bracket }
+
+// Sentence {
+
+// The starts of all the sentences in absolute char indexes, the first empty line after a
+// paragraph is also a sentence.
+fn _sentence_starts(text: &Text) -> Vec<usize> {
+  let last_line = _last_line(text);
+  let mut starts = vec![];
+  let mut line_idx = 0;
+  while line_idx <= last_line {
+    if _line_chars(text, line_idx).is_empty() {
+      if line_idx == 0 || !_line_chars(text, line_idx - 1).is_empty() {
+        starts.push(_absolute(text, (line_idx, 0)));
+      }
+      line_idx += 1;
+      continue;
+    }
+    let first_line = line_idx;
+    while line_idx <= last_line && !_line_chars(text, line_idx).is_empty() {
+      line_idx += 1;
+    }
+    let paragraph_start = _absolute(text, (first_line, 0));
+    let paragraph_end =
+      _absolute(text, (line_idx - 1, _line_chars(text, line_idx - 1).len()));
+    let chars: Vec<char> = text
+      .rope()
+      .slice(paragraph_start..paragraph_end)
+      .chars()
+      .collect();
+    starts.extend(
+      sentences(&chars)
+        .into_iter()
+        .map(|sentence| paragraph_start + sentence.start),
+    );
+  }
+  starts
+}
+
+fn _sentence(
+  text: &Text,
+  cursor: (usize, usize),
+  forward: bool,
+  n: usize,
+) -> (usize, usize) {
+  let starts = _sentence_starts(text);
+  let mut pos = _absolute(text, cursor);
+  for _ in 0..std::cmp::max(n, 1) {
+    let next = if forward {
+      starts.iter().find(|start| **start > pos).copied()
+    } else {
+      starts.iter().rev().find(|start| **start < pos).copied()
+    };
+    match next {
+      Some(next) => pos = next,
+      None if forward => {
+        let last_line = _last_line(text);
+        let last_char = text.last_char_on_line_no_eol(last_line).unwrap_or(0);
+        return (last_line, last_char);
+      }
+      None => return (0, 0),
+    }
+  }
+  _position(text, pos)
+}
+
+// Sentence }
+
+// Paragraph {
+
+// The next (or previous) empty line after (or before) the paragraph.
+fn _paragraph(
+  text: &Text,
+  line_idx: usize,
+  forward: bool,
+  n: usize,
+) -> (usize, usize) {
+  let last_line = _last_line(text);
+  let is_empty = |line_idx: usize| _line_chars(text, line_idx).is_empty();
+  let mut line_idx = line_idx;
+  for _ in 0..std::cmp::max(n, 1) {
+    if forward {
+      while line_idx <= last_line && is_empty(line_idx) {
+        line_idx += 1;
+      }
+      while line_idx <= last_line && !is_empty(line_idx) {
+        line_idx += 1;
+      }
+      if line_idx > last_line {
+        let last_char = text.last_char_on_line_no_eol(last_line).unwrap_or(0);
+        return (last_line, last_char);
+      }
+    } else {
+      while line_idx > 0 && is_empty(line_idx) {
+        line_idx -= 1;
+      }
+      while line_idx > 0 && !is_empty(line_idx) {
+        line_idx -= 1;
+      }
+      if line_idx == 0 {
+        return (0, 0);
+      }
+    }
+  }
+  (line_idx, 0)
+}
+
+// Paragraph }
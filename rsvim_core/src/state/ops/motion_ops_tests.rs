@@ -0,0 +1,302 @@
+use super::motion_ops::*;
+
+use crate::buf::opt::{BufferLocalOptionsBuilder, IsKeywordOption};
+use crate::buf::text::Text;
+use crate::coord::{U16Rect, U16Size};
+use crate::state::ops::{
+  CharFind, CharFindKind, MotionType, Operation, WordMotion,
+};
+use crate::test::log::init as test_log_init;
+use crate::ui::viewport::Viewport;
+use crate::ui::widget::window::WindowLocalOptionsBuilder;
+
+use ropey::Rope;
+
+fn make_text(payload: &str) -> Text {
+  let opt = BufferLocalOptionsBuilder::default().build().unwrap();
+  Text::new(opt, U16Size::new(10, 4), Rope::from_str(payload))
+}
+
+fn target(
+  text: &Text,
+  op: Operation,
+  cursor: (usize, usize),
+) -> Option<(usize, usize)> {
+  let opts = WindowLocalOptionsBuilder::default()
+    .wrap(false)
+    .build()
+    .unwrap();
+  let viewport =
+    Viewport::view(&opts, text, &U16Rect::new((0, 0), (10, 4)), 0, 0);
+  motion_target(text, &viewport, &op, cursor)
+}
+
+fn word(motion: WordMotion, big_word: bool, n: usize) -> Operation {
+  Operation::CursorMoveByWord((motion, big_word, n))
+}
+
+fn find(kind: CharFindKind, c: char, repeat: bool, n: usize) -> Operation {
+  Operation::CursorMoveToChar((CharFind { kind, c }, repeat, n))
+}
+
+#[test]
+fn word1() {
+  test_log_init();
+  let text = make_text("foo bar.baz  qux\n\nend\n");
+
+  let w = word(WordMotion::Forward, false, 1);
+  assert_eq!(target(&text, w.clone(), (0, 0)), Some((0, 4)));
+  assert_eq!(target(&text, w.clone(), (0, 4)), Some((0, 7)));
+  assert_eq!(
+    target(&text, word(WordMotion::Forward, true, 1), (0, 4)),
+    Some((0, 13))
+  );
+  // The empty line is a word.
+  assert_eq!(target(&text, w.clone(), (0, 13)), Some((1, 0)));
+  assert_eq!(target(&text, w.clone(), (1, 0)), Some((2, 0)));
+  assert_eq!(
+    target(&text, word(WordMotion::Forward, false, 3), (0, 0)),
+    Some((0, 8))
+  );
+  // The end of text.
+  assert_eq!(target(&text, w.clone(), (2, 0)), Some((3, 0)));
+  assert_eq!(clamp_target(&text, (3, 0)), (2, 2));
+
+  let b = word(WordMotion::Backward, false, 1);
+  assert_eq!(target(&text, b.clone(), (0, 13)), Some((0, 8)));
+  assert_eq!(target(&text, b.clone(), (2, 0)), Some((1, 0)));
+  assert_eq!(target(&text, b.clone(), (1, 0)), Some((0, 13)));
+  assert_eq!(target(&text, b.clone(), (0, 0)), Some((0, 0)));
+}
+
+#[test]
+fn word2() {
+  test_log_init();
+  let text = make_text("foo bar.baz  qux\n\nend\n");
+
+  let e = word(WordMotion::ForwardEnd, false, 1);
+  assert_eq!(target(&text, e.clone(), (0, 0)), Some((0, 2)));
+  assert_eq!(target(&text, e.clone(), (0, 2)), Some((0, 6)));
+  assert_eq!(
+    target(&text, word(WordMotion::ForwardEnd, true, 1), (0, 2)),
+    Some((0, 10))
+  );
+  // The empty line is skipped.
+  assert_eq!(target(&text, e.clone(), (0, 15)), Some((2, 2)));
+
+  let ge = word(WordMotion::BackwardEnd, false, 1);
+  assert_eq!(target(&text, ge.clone(), (0, 8)), Some((0, 7)));
+  assert_eq!(target(&text, ge.clone(), (2, 0)), Some((1, 0)));
+  assert_eq!(
+    target(&text, word(WordMotion::BackwardEnd, true, 1), (0, 13)),
+    Some((0, 10))
+  );
+}
+
+#[test]
+fn word3() {
+  test_log_init();
+  let payload = "foo-bar baz\n";
+  let text = make_text(payload);
+  let w = word(WordMotion::Forward, false, 1);
+  assert_eq!(target(&text, w.clone(), (0, 0)), Some((0, 3)));
+
+  // The `-` is a keyword char.
+  let opt = BufferLocalOptionsBuilder::default()
+    .is_keyword(IsKeywordOption::try_from("@,48-57,_,-").unwrap())
+    .build()
+    .unwrap();
+  let text = Text::new(opt, U16Size::new(10, 4), Rope::from_str(payload));
+  assert_eq!(target(&text, w.clone(), (0, 0)), Some((0, 8)));
+}
+
+#[test]
+fn line1() {
+  test_log_init();
+  let text = make_text("  foo bar  \n\tx\n");
+
+  assert_eq!(
+    target(&text, Operation::CursorMoveToLineStart, (0, 5)),
+    Some((0, 0))
+  );
+  assert_eq!(
+    target(&text, Operation::CursorMoveToFirstNonBlank, (0, 5)),
+    Some((0, 2))
+  );
+  assert_eq!(
+    target(&text, Operation::CursorMoveToFirstNonBlank, (1, 1)),
+    Some((1, 1))
+  );
+  assert_eq!(
+    target(&text, Operation::CursorMoveToLineEnd(1), (0, 0)),
+    Some((0, 10))
+  );
+  assert_eq!(
+    target(&text, Operation::CursorMoveToLineEnd(5), (0, 0)),
+    Some((1, 1))
+  );
+  assert_eq!(
+    target(&text, Operation::CursorMoveToLastNonBlank(1), (0, 0)),
+    Some((0, 8))
+  );
+  assert_eq!(
+    target(&text, Operation::CursorMoveToLine(usize::MAX), (0, 5)),
+    Some((1, 1))
+  );
+  assert_eq!(
+    target(&text, Operation::CursorMoveToLine(0), (1, 0)),
+    Some((0, 2))
+  );
+}
+
+#[test]
+fn char_find1() {
+  test_log_init();
+  let text = make_text("a,b,c,d\n");
+
+  assert_eq!(
+    target(&text, find(CharFindKind::Forward, ',', false, 1), (0, 0)),
+    Some((0, 1))
+  );
+  assert_eq!(
+    target(&text, find(CharFindKind::Forward, ',', false, 2), (0, 0)),
+    Some((0, 3))
+  );
+  // The `t` before the adjacent char doesn't move, the repeat skips it.
+  assert_eq!(
+    target(
+      &text,
+      find(CharFindKind::TillForward, ',', false, 1),
+      (0, 0)
+    ),
+    None
+  );
+  assert_eq!(
+    target(&text, find(CharFindKind::TillForward, ',', true, 1), (0, 0)),
+    Some((0, 2))
+  );
+  assert_eq!(
+    target(&text, find(CharFindKind::Backward, ',', false, 1), (0, 6)),
+    Some((0, 5))
+  );
+  assert_eq!(
+    target(
+      &text,
+      find(CharFindKind::TillBackward, ',', true, 1),
+      (0, 6)
+    ),
+    Some((0, 4))
+  );
+  assert_eq!(
+    target(&text, find(CharFindKind::Forward, 'z', false, 1), (0, 0)),
+    None
+  );
+  assert_eq!(
+    target(&text, find(CharFindKind::Forward, ',', false, 5), (0, 0)),
+    None
+  );
+}
+
+#[test]
+fn bracket1() {
+  test_log_init();
+  let text = make_text("if (a[1] {x}) y\n");
+  let op = Operation::CursorMoveToMatchingBracket;
+
+  assert_eq!(target(&text, op.clone(), (0, 0)), Some((0, 12)));
+  assert_eq!(target(&text, op.clone(), (0, 12)), Some((0, 3)));
+  assert_eq!(target(&text, op.clone(), (0, 5)), Some((0, 7)));
+  assert_eq!(target(&text, op.clone(), (0, 13)), None);
+
+  let text = make_text("{\n  a\n}\n");
+  assert_eq!(target(&text, op.clone(), (0, 0)), Some((2, 0)));
+}
+
+#[test]
+fn sentence1() {
+  test_log_init();
+  let text = make_text("Hello world. This is.  End\n\nNext.\n");
+  let forward = |n| Operation::CursorMoveBySentence((true, n));
+  let backward = |n| Operation::CursorMoveBySentence((false, n));
+
+  assert_eq!(target(&text, forward(1), (0, 0)), Some((0, 13)));
+  assert_eq!(target(&text, forward(2), (0, 0)), Some((0, 23)));
+  // The empty line is a sentence.
+  assert_eq!(target(&text, forward(1), (0, 23)), Some((1, 0)));
+  assert_eq!(target(&text, forward(1), (2, 0)), Some((2, 4)));
+
+  assert_eq!(target(&text, backward(1), (0, 15)), Some((0, 13)));
+  assert_eq!(target(&text, backward(1), (0, 13)), Some((0, 0)));
+  assert_eq!(target(&text, backward(1), (2, 0)), Some((1, 0)));
+  assert_eq!(target(&text, backward(1), (0, 0)), Some((0, 0)));
+}
+
+#[test]
+fn paragraph1() {
+  test_log_init();
+  let text = make_text("a\nb\n\n\nc\nd\n");
+  let forward = |n| Operation::CursorMoveByParagraph((true, n));
+  let backward = |n| Operation::CursorMoveByParagraph((false, n));
+
+  assert_eq!(target(&text, forward(1), (0, 0)), Some((2, 0)));
+  assert_eq!(target(&text, forward(1), (2, 0)), Some((5, 0)));
+  assert_eq!(target(&text, forward(2), (0, 0)), Some((5, 0)));
+
+  assert_eq!(target(&text, backward(1), (5, 0)), Some((3, 0)));
+  assert_eq!(target(&text, backward(1), (3, 0)), Some((0, 0)));
+}
+
+#[test]
+fn window1() {
+  test_log_init();
+  let text = make_text("0\n1\n2\n3\n4\n5\n6\n7\n8\n9\n");
+
+  assert_eq!(
+    target(&text, Operation::CursorMoveToWindowTop(1), (2, 0)),
+    Some((0, 0))
+  );
+  assert_eq!(
+    target(&text, Operation::CursorMoveToWindowTop(2), (0, 0)),
+    Some((1, 0))
+  );
+  assert_eq!(
+    target(&text, Operation::CursorMoveToWindowBottom(1), (0, 0)),
+    Some((3, 0))
+  );
+  assert_eq!(
+    target(&text, Operation::CursorMoveToWindowBottom(2), (0, 0)),
+    Some((2, 0))
+  );
+  assert_eq!(
+    target(&text, Operation::CursorMoveToWindowMiddle, (0, 0)),
+    Some((1, 0))
+  );
+}
+
+#[test]
+fn motion_type1() {
+  assert_eq!(
+    motion_type(&word(WordMotion::Forward, false, 1)),
+    MotionType::Exclusive
+  );
+  assert_eq!(
+    motion_type(&word(WordMotion::ForwardEnd, false, 1)),
+    MotionType::Inclusive
+  );
+  assert_eq!(
+    motion_type(&find(CharFindKind::TillForward, 'x', false, 1)),
+    MotionType::Inclusive
+  );
+  assert_eq!(
+    motion_type(&find(CharFindKind::Backward, 'x', false, 1)),
+    MotionType::Exclusive
+  );
+  assert_eq!(
+    motion_type(&Operation::CursorMoveToLine(0)),
+    MotionType::Linewise
+  );
+  assert_eq!(
+    motion_type(&Operation::CursorMoveDownBy(1)),
+    MotionType::Linewise
+  );
+}
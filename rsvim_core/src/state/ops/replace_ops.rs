@@ -0,0 +1,130 @@
+//! Replace operations, i.e. `r{char}` in normal mode, and the typed chars overwrite the existing
+//! chars in replace mode (`R`) and virtual replace mode (`gR`).
+//!
+//! All the operations only change the text, and return the new cursor position
+//! `(line_idx, char_idx)`. The caller should update the viewport and move the cursor.
+
+use crate::buf::text::Text;
+
+// The count of chars on the line, excluding the eol.
+fn _line_len_chars(text: &Text, line_idx: usize) -> usize {
+  text
+    .last_char_on_line_no_eol(line_idx)
+    .map(|last_char| last_char + 1)
+    .unwrap_or(0)
+}
+
+fn _is_eol(payload: &str) -> bool {
+  payload.chars().all(|c| c == '\n' || c == '\r')
+}
+
+/// Replace `count` chars at the cursor with the `payload`, i.e. `3rx`. The eol payload replaces
+/// all the chars with only one line break, i.e. `r<CR>`.
+///
+/// # Returns
+/// It returns the new cursor position, i.e. on the last replaced char, or at the start of the new
+/// line for the eol. It returns `None` if there're not enough chars after the cursor, and nothing
+/// is changed.
+pub fn replace_chars(
+  text: &mut Text,
+  (line_idx, char_idx): (usize, usize),
+  count: usize,
+  payload: &str,
+) -> Option<(usize, usize)> {
+  let count = count.max(1);
+  if char_idx + count > _line_len_chars(text, line_idx) {
+    return None;
+  }
+
+  let start = text.rope().line_to_char(line_idx) + char_idx;
+  if _is_eol(payload) {
+    text.replace_range(start..start + count, payload);
+    Some((line_idx + 1, 0))
+  } else {
+    text.replace_range(start..start + count, &payload.repeat(count));
+    Some((line_idx, char_idx + count - 1))
+  }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// The text overwritten by a typed char in replace mode, it is restored by `<BS>`.
+pub struct Overwritten {
+  /// The count of the inserted chars.
+  pub inserted: usize,
+
+  /// The removed text, it is empty if the char is appended after the end of line.
+  pub removed: String,
+}
+
+/// Overwrite the chars at the cursor with the typed `payload` in replace mode. The eol payload is
+/// inserted without removing any char, i.e. `<CR>` in replace mode.
+///
+/// In virtual replace mode (`virtual_replace`) the payload overwrites screen cells instead of
+/// chars: a wide char overwrites two narrow chars, and a tab is only overwritten when the typed
+/// chars fill all its cells, i.e. the chars are inserted before it until then. The `covered` is
+/// the cells of the tab at the cursor that are already overwritten.
+///
+/// # Returns
+/// It returns the overwritten text, the covered cells of the tab at the new cursor, and the new
+/// cursor position, i.e. after the inserted payload.
+pub fn overwrite(
+  text: &mut Text,
+  (line_idx, char_idx): (usize, usize),
+  payload: &str,
+  virtual_replace: bool,
+  covered: usize,
+) -> (Overwritten, usize, (usize, usize)) {
+  let len_chars = _line_len_chars(text, line_idx);
+  let line_start = text.rope().line_to_char(line_idx);
+  let mut removed = 0_usize;
+  let mut covered = covered;
+
+  if !_is_eol(payload) {
+    if virtual_replace {
+      let mut cells: usize = payload.chars().map(|c| text.char_width(c)).sum();
+      while cells > 0 && char_idx + removed < len_chars {
+        let c = text.rope().char(line_start + char_idx + removed);
+        let width = text.char_width(c).saturating_sub(covered);
+        if c == '\t' && width > cells {
+          covered += cells;
+          break;
+        }
+        cells = cells.saturating_sub(width);
+        covered = 0;
+        removed += 1;
+      }
+    } else if char_idx < len_chars {
+      removed = 1;
+    }
+  }
+
+  let start = line_start + char_idx;
+  let removed_text = text.rope().slice(start..start + removed).to_string();
+  let end = text.replace_range(start..start + removed, payload);
+  let new_line_idx = text.rope().char_to_line(end);
+  let new_char_idx = end - text.rope().line_to_char(new_line_idx);
+  (
+    Overwritten {
+      inserted: payload.chars().count(),
+      removed: removed_text,
+    },
+    covered,
+    (new_line_idx, new_char_idx),
+  )
+}
+
+/// Restore the text overwritten before the cursor, i.e. `<BS>` in replace mode.
+///
+/// # Returns
+/// It returns the new cursor position, i.e. before the restored text.
+pub fn restore(
+  text: &mut Text,
+  (line_idx, char_idx): (usize, usize),
+  overwritten: &Overwritten,
+) -> (usize, usize) {
+  let end = text.rope().line_to_char(line_idx) + char_idx;
+  let start = end.saturating_sub(overwritten.inserted);
+  text.replace_range(start..end, &overwritten.removed);
+  let new_line_idx = text.rope().char_to_line(start);
+  (new_line_idx, start - text.rope().line_to_char(new_line_idx))
+}
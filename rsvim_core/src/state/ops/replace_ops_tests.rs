@@ -0,0 +1,89 @@
+use super::replace_ops::*;
+
+use crate::buf::opt::BufferLocalOptionsBuilder;
+use crate::buf::text::Text;
+use crate::coord::U16Size;
+use crate::test::log::init as test_log_init;
+
+use ropey::Rope;
+
+fn make_text(payload: &str) -> Text {
+  let opt = BufferLocalOptionsBuilder::default()
+    .tab_stop(4)
+    .build()
+    .unwrap();
+  Text::new(opt, U16Size::new(10, 4), Rope::from_str(payload))
+}
+
+#[test]
+fn replace_chars1() {
+  test_log_init();
+  let mut text = make_text("hello\n");
+  assert_eq!(replace_chars(&mut text, (0, 1), 3, "x"), Some((0, 3)));
+  assert_eq!(text.rope().to_string(), "hxxxo\n");
+
+  // Not enough chars.
+  assert_eq!(replace_chars(&mut text, (0, 1), 5, "y"), None);
+  assert_eq!(text.rope().to_string(), "hxxxo\n");
+
+  // All chars are replaced with only one line break.
+  assert_eq!(replace_chars(&mut text, (0, 2), 2, "\n"), Some((1, 0)));
+  assert_eq!(text.rope().to_string(), "hx\no\n");
+}
+
+#[test]
+fn overwrite1() {
+  test_log_init();
+  let mut text = make_text("ab\n");
+  let (overwritten, _, position) = overwrite(&mut text, (0, 0), "x", false, 0);
+  assert_eq!(text.rope().to_string(), "xb\n");
+  assert_eq!(overwritten.removed, "a");
+  assert_eq!(position, (0, 1));
+
+  // Appended after the end of line.
+  let (appended, _, position) = overwrite(&mut text, (0, 2), "y", false, 0);
+  assert_eq!(text.rope().to_string(), "xby\n");
+  assert_eq!(appended.removed, "");
+  assert_eq!(position, (0, 3));
+
+  assert_eq!(restore(&mut text, (0, 3), &appended), (0, 2));
+  assert_eq!(text.rope().to_string(), "xb\n");
+  assert_eq!(restore(&mut text, (0, 1), &overwritten), (0, 0));
+  assert_eq!(text.rope().to_string(), "ab\n");
+
+  // The line break is inserted.
+  let (overwritten, _, position) = overwrite(&mut text, (0, 1), "\n", false, 0);
+  assert_eq!(text.rope().to_string(), "a\nb\n");
+  assert_eq!(position, (1, 0));
+  assert_eq!(restore(&mut text, (1, 0), &overwritten), (0, 1));
+  assert_eq!(text.rope().to_string(), "ab\n");
+}
+
+#[test]
+fn overwrite_virtual1() {
+  test_log_init();
+  // The tab is 4 cells, it is overwritten by the 4th char.
+  let mut text = make_text("\tb\n");
+  let mut covered = 0;
+  let mut position = (0, 0);
+  for c in ["x", "y", "z"] {
+    let (overwritten, next_covered, next_position) =
+      overwrite(&mut text, position, c, true, covered);
+    assert_eq!(overwritten.removed, "");
+    (covered, position) = (next_covered, next_position);
+  }
+  assert_eq!(text.rope().to_string(), "xyz\tb\n");
+  assert_eq!(covered, 3);
+  let (overwritten, covered, _) =
+    overwrite(&mut text, position, "w", true, covered);
+  assert_eq!(text.rope().to_string(), "xyzwb\n");
+  assert_eq!(overwritten.removed, "\t");
+  assert_eq!(covered, 0);
+
+  // The typed tab overwrites 4 cells.
+  let mut text = make_text("abcdef\n");
+  let (overwritten, _, position) = overwrite(&mut text, (0, 1), "\t", true, 0);
+  assert_eq!(text.rope().to_string(), "a\tf\n");
+  assert_eq!(overwritten.removed, "bcde");
+  assert_eq!(position, (0, 2));
+}
@@ -0,0 +1,318 @@
+//! Visual mode operations, i.e. the operators that act on the visual selection.
+//!
+//! The text moved over by a motion in operator-pending mode is also converted into a selection,
+//! see [`motion_selection`].
+//!
+//! All the operations only change the text, and return the new cursor position
+//! `(line_idx, char_idx)`. The caller should update the viewport and move the cursor.
+
+use crate::buf::selection::{Selection, SelectionKind};
+use crate::buf::text::Text;
+use crate::content::register::Registers;
+use crate::state::ops::{CaseChange, MotionType, Operator};
+
+use std::ops::RangeInclusive;
+
+/// The top-left position of the selection, i.e. the cursor position after the operator.
+pub fn top_left(text: &Text, selection: &Selection) -> (usize, usize) {
+  match selection.kind() {
+    SelectionKind::Block => {
+      let line_idx = selection.start().0;
+      let char_idx = selection
+        .char_ranges(text)
+        .first()
+        .map(|range| range.start - text.rope().line_to_char(line_idx))
+        .unwrap_or(0);
+      (line_idx, char_idx)
+    }
+    _ => selection.start(),
+  }
+}
+
+/// Delete the selection, i.e. `d` in visual mode.
+///
+/// # Returns
+/// It returns the deleted text and the new cursor position.
+pub fn delete(
+  text: &mut Text,
+  selection: &Selection,
+) -> (String, (usize, usize)) {
+  let deleted = selection.text(text);
+  let position = top_left(text, selection);
+  for range in selection.char_ranges(text).into_iter().rev() {
+    text.replace_range(range, "");
+  }
+  let position = match selection.kind() {
+    SelectionKind::Line => (position.0, 0),
+    _ => position,
+  };
+  (deleted, position)
+}
+
+/// Delete the selection for insert, i.e. `c` in visual mode. Different from [`delete`], the
+/// linewise selection leaves an empty line for insert.
+///
+/// # Returns
+/// It returns the deleted text and the new cursor position.
+pub fn change(
+  text: &mut Text,
+  selection: &Selection,
+) -> (String, (usize, usize)) {
+  match selection.kind() {
+    SelectionKind::Line => {
+      let deleted = selection.text(text);
+      let eol = format!("{}", text.options().end_of_line());
+      for range in selection.char_ranges(text).into_iter().rev() {
+        text.replace_range(range, &eol);
+      }
+      (deleted, (selection.start().0, 0))
+    }
+    _ => delete(text, selection),
+  }
+}
+
+fn _change_case(s: &str, case: CaseChange) -> String {
+  match case {
+    CaseChange::Toggle => s
+      .chars()
+      .map(|c| {
+        if c.is_uppercase() {
+          c.to_lowercase().collect::<String>()
+        } else {
+          c.to_uppercase().collect::<String>()
+        }
+      })
+      .collect(),
+    CaseChange::Lower => s.to_lowercase(),
+    CaseChange::Upper => s.to_uppercase(),
+  }
+}
+
+/// Change case of the selection, i.e. `~`, `u` and `U` in visual mode.
+///
+/// # Returns
+/// It returns the new cursor position.
+pub fn change_case(
+  text: &mut Text,
+  selection: &Selection,
+  case: CaseChange,
+) -> (usize, usize) {
+  let position = top_left(text, selection);
+  for range in selection.char_ranges(text).into_iter().rev() {
+    let old = text.rope().slice(range.clone()).to_string();
+    let new = _change_case(&old, case);
+    if new != old {
+      text.replace_range(range, &new);
+    }
+  }
+  position
+}
+
+// The `(chars, width)` of the leading whitespaces on the line.
+fn _indent(text: &Text, line_idx: usize) -> (usize, usize) {
+  let chars = text
+    .rope()
+    .line(line_idx)
+    .chars()
+    .take_while(|c| *c == ' ' || *c == '\t')
+    .count();
+  (chars, text.width_before(line_idx, chars))
+}
+
+// Whether the line is empty, i.e. it has no chars except the eol.
+fn _is_empty_line(text: &Text, line_idx: usize) -> bool {
+  text.last_char_on_line_no_eol(line_idx).is_none()
+}
+
+/// Shift lines right (`>`) or left (`<`) by one 'tab-stop', the indent is made of tabs then
+/// spaces. Empty lines are not shifted.
+///
+/// # Returns
+/// It returns the new cursor position, i.e. the first non-blank char on the first line.
+pub fn shift_lines(
+  text: &mut Text,
+  lines: RangeInclusive<usize>,
+  right: bool,
+) -> (usize, usize) {
+  let tab_stop = std::cmp::max(text.options().tab_stop() as usize, 1);
+  let first_line_idx = *lines.start();
+
+  for line_idx in lines {
+    if text.rope().get_line(line_idx).is_none()
+      || _is_empty_line(text, line_idx)
+    {
+      continue;
+    }
+    let (indent_chars, indent_width) = _indent(text, line_idx);
+    let new_width = if right {
+      indent_width + tab_stop
+    } else {
+      indent_width.saturating_sub(tab_stop)
+    };
+    if new_width == indent_width {
+      continue;
+    }
+    let new_indent = format!(
+      "{}{}",
+      "\t".repeat(new_width / tab_stop),
+      " ".repeat(new_width % tab_stop)
+    );
+    let line_start = text.rope().line_to_char(line_idx);
+    text.replace_range(line_start..line_start + indent_chars, &new_indent);
+  }
+
+  match text.rope().get_line(first_line_idx) {
+    Some(_) => (first_line_idx, _indent(text, first_line_idx).0),
+    None => (first_line_idx, 0),
+  }
+}
+
+/// Join lines, i.e. `J` in visual mode. The eol and the leading whitespaces of the next line are
+/// replaced with one space, no space is inserted if the line is empty or ends with whitespace, or
+/// the next line is empty or starts with `)`. A single line is joined with its next line.
+///
+/// # Returns
+/// It returns the new cursor position, i.e. where the last two lines are joined.
+pub fn join_lines(
+  text: &mut Text,
+  lines: RangeInclusive<usize>,
+) -> (usize, usize) {
+  let line_idx = *lines.start();
+  let n = std::cmp::max(lines.end().saturating_sub(line_idx), 1);
+  let mut position = (line_idx, 0);
+
+  for _ in 0..n {
+    // The next line doesn't exist, NOTE: The last line after the eol at the end of text is empty.
+    if line_idx + 1 >= text.rope().len_lines()
+      || text.rope().line_to_char(line_idx + 1) >= text.rope().len_chars()
+    {
+      break;
+    }
+
+    let line_len = text
+      .last_char_on_line_no_eol(line_idx)
+      .map(|c| c + 1)
+      .unwrap_or(0);
+    let line_start = text.rope().line_to_char(line_idx);
+    let next_line_start = text.rope().line_to_char(line_idx + 1);
+    let (next_indent_chars, _) = _indent(text, line_idx + 1);
+
+    let last_char = if line_len > 0 {
+      Some(text.rope().char(line_start + line_len - 1))
+    } else {
+      None
+    };
+    let next_char = if text.is_eol(line_idx + 1, next_indent_chars) {
+      None
+    } else {
+      text.rope().line(line_idx + 1).get_char(next_indent_chars)
+    };
+    let separator = match (last_char, next_char) {
+      (Some(c), Some(n)) if !c.is_whitespace() && n != ')' => " ",
+      _ => "",
+    };
+
+    text.replace_range(
+      line_start + line_len..next_line_start + next_indent_chars,
+      separator,
+    );
+    position = (line_idx, line_len);
+  }
+
+  position
+}
+
+/// Convert the text moved over by a motion, from `from` to `to`, into a selection. The motion type
+/// can be forced with `force`, i.e. `v`, `V` and `<C-V>` in operator-pending mode.
+///
+/// The exclusive motion excludes its last char, and if it ends at the first column of a line
+/// below, it ends at the end of the previous line instead.
+///
+/// See: <https://vimhelp.org/motion.txt.html#exclusive>.
+///
+/// # Returns
+/// It returns `None` if the selection is empty.
+pub fn motion_selection(
+  text: &Text,
+  from: (usize, usize),
+  to: (usize, usize),
+  motion_type: MotionType,
+  force: Option<SelectionKind>,
+) -> Option<Selection> {
+  let motion_type = match (force, motion_type) {
+    (Some(SelectionKind::Line), _) => {
+      return Some(Selection::new(SelectionKind::Line, from, to));
+    }
+    (Some(SelectionKind::Block), _) => {
+      return Some(Selection::new(SelectionKind::Block, from, to));
+    }
+    (Some(SelectionKind::Char), MotionType::Exclusive) => MotionType::Inclusive,
+    (Some(SelectionKind::Char), _) => MotionType::Exclusive,
+    (None, motion_type) => motion_type,
+  };
+
+  match motion_type {
+    MotionType::Linewise => Some(Selection::new(SelectionKind::Line, from, to)),
+    MotionType::Inclusive => {
+      // Nothing to operate on the eol of an empty line, i.e. `d$`.
+      if from == to && text.is_eol(to.0, to.1) {
+        return None;
+      }
+      Some(Selection::new(SelectionKind::Char, from, to))
+    }
+    MotionType::Exclusive => {
+      let (start, end) = (std::cmp::min(from, to), std::cmp::max(from, to));
+      if start == end {
+        return None;
+      }
+      let end = if end.1 > 0 {
+        (end.0, end.1 - 1)
+      } else {
+        let line_idx = end.0 - 1;
+        (
+          line_idx,
+          text.last_char_on_line_no_eol(line_idx).unwrap_or(0),
+        )
+      };
+      if end < start {
+        return None;
+      }
+      Some(Selection::new(SelectionKind::Char, start, end))
+    }
+  }
+}
+
+/// Apply the operator on the selection, the deleted or yanked text is saved to `registers`, or
+/// the `register` given by `"x`.
+///
+/// # Returns
+/// It returns the new cursor position.
+pub fn apply_operator(
+  text: &mut Text,
+  registers: &mut Registers,
+  register: Option<char>,
+  selection: &Selection,
+  operator: Operator,
+) -> (usize, usize) {
+  match operator {
+    Operator::Yank => {
+      registers.yank_to(register, &selection.text(text));
+      top_left(text, selection)
+    }
+    Operator::Delete | Operator::Change => {
+      let (deleted, position) = if operator == Operator::Change {
+        change(text, selection)
+      } else {
+        delete(text, selection)
+      };
+      let small = selection.kind() == SelectionKind::Char
+        && selection.start().0 == selection.end().0;
+      registers.delete_to(register, &deleted, small);
+      position
+    }
+    Operator::ShiftRight | Operator::ShiftLeft => {
+      shift_lines(text, selection.lines(), operator == Operator::ShiftRight)
+    }
+    Operator::ChangeCase(case) => change_case(text, selection, case),
+  }
+}
@@ -0,0 +1,210 @@
+//! Editor global options, i.e. the options that are not local to any buffer or window.
+
+use crate::defaults;
+
+use compact_str::CompactString;
+use derive_builder::Builder;
+
+#[derive(Debug, Clone, Builder)]
+/// Global options.
+pub struct GlobalOptions {
+  #[builder(default = defaults::global::HISTORY)]
+  history: u16,
+
+  #[builder(default = defaults::global::MESSAGE_HISTORY)]
+  message_history: u16,
+
+  #[builder(default = CompactString::const_new(defaults::global::WILD_MODE))]
+  wild_mode: CompactString,
+
+  #[builder(default = CompactString::const_new(defaults::global::WILD_IGNORE))]
+  wild_ignore: CompactString,
+
+  #[builder(default = CompactString::const_new(defaults::global::WILD_OPTIONS))]
+  wild_options: CompactString,
+
+  #[builder(default = defaults::global::TIMEOUT)]
+  timeout: bool,
+
+  #[builder(default = defaults::global::TIMEOUT_LEN)]
+  timeout_len: u32,
+
+  #[builder(default = defaults::global::SHOW_CMD)]
+  show_cmd: bool,
+
+  #[builder(default = defaults::global::UPDATE_TIME)]
+  update_time: u32,
+
+  #[builder(default = CompactString::const_new(defaults::global::KEY_MODEL))]
+  key_model: CompactString,
+
+  #[builder(default = CompactString::const_new(defaults::global::SELECT_MODE))]
+  select_mode: CompactString,
+
+  #[builder(default = CompactString::const_new(defaults::global::MOUSE))]
+  mouse: CompactString,
+}
+
+impl GlobalOptions {
+  /// The 'history' option, max entries of each command-line history, default to `10000`.
+  ///
+  /// See: <https://vimhelp.org/options.txt.html#%27history%27>.
+  pub fn history(&self) -> u16 {
+    self.history
+  }
+
+  pub fn set_history(&mut self, value: u16) {
+    self.history = value;
+  }
+
+  /// The 'message-history' option, max entries of the `:messages` history, default to `500`.
+  ///
+  /// See: <https://neovim.io/doc/user/options.html#'msghistory'>.
+  pub fn message_history(&self) -> u16 {
+    self.message_history
+  }
+
+  pub fn set_message_history(&mut self, value: u16) {
+    self.message_history = value;
+  }
+
+  /// The 'wild-mode' option, the completion mode for each `<Tab>` in command-line, default to
+  /// `full`.
+  ///
+  /// See: <https://vimhelp.org/options.txt.html#%27wildmode%27>.
+  pub fn wild_mode(&self) -> &str {
+    &self.wild_mode
+  }
+
+  pub fn set_wild_mode(&mut self, value: &str) {
+    self.wild_mode = CompactString::new(value);
+  }
+
+  /// The 'wild-ignore' option, the comma-separated file patterns that are ignored when completing
+  /// file names, default to empty.
+  ///
+  /// See: <https://vimhelp.org/options.txt.html#%27wildignore%27>.
+  pub fn wild_ignore(&self) -> &str {
+    &self.wild_ignore
+  }
+
+  pub fn set_wild_ignore(&mut self, value: &str) {
+    self.wild_ignore = CompactString::new(value);
+  }
+
+  /// The 'wild-options' option, default to empty. When it contains `pum`, the completion matches
+  /// are shown in a popup menu, instead of the horizontal wildmenu.
+  ///
+  /// See: <https://vimhelp.org/options.txt.html#%27wildoptions%27>.
+  pub fn wild_options(&self) -> &str {
+    &self.wild_options
+  }
+
+  pub fn set_wild_options(&mut self, value: &str) {
+    self.wild_options = CompactString::new(value);
+  }
+
+  /// The 'timeout' option, whether the pending keys of an incomplete command time out after
+  /// 'timeout-len' milliseconds, default to `true`.
+  ///
+  /// See: <https://vimhelp.org/options.txt.html#%27timeout%27>.
+  pub fn timeout(&self) -> bool {
+    self.timeout
+  }
+
+  pub fn set_timeout(&mut self, value: bool) {
+    self.timeout = value;
+  }
+
+  /// The 'timeout-len' option, the milliseconds to wait for the next key of an incomplete command,
+  /// default to `1000`.
+  ///
+  /// See: <https://vimhelp.org/options.txt.html#%27timeoutlen%27>.
+  pub fn timeout_len(&self) -> u32 {
+    self.timeout_len
+  }
+
+  pub fn set_timeout_len(&mut self, value: u32) {
+    self.timeout_len = value;
+  }
+
+  /// The 'show-cmd' option, whether the pending keys of an incomplete command are shown in the
+  /// last line of the screen, default to `true`.
+  ///
+  /// See: <https://vimhelp.org/options.txt.html#%27showcmd%27>.
+  pub fn show_cmd(&self) -> bool {
+    self.show_cmd
+  }
+
+  pub fn set_show_cmd(&mut self, value: bool) {
+    self.show_cmd = value;
+  }
+
+  /// The 'update-time' option, the `CursorHold` event is fired when no key is typed for these
+  /// milliseconds, default to `4000`.
+  ///
+  /// See: <https://vimhelp.org/options.txt.html#%27updatetime%27>.
+  pub fn update_time(&self) -> u32 {
+    self.update_time
+  }
+
+  pub fn set_update_time(&mut self, value: u32) {
+    self.update_time = value;
+  }
+
+  /// The 'key-model' option, default to empty. When it contains `startsel`, the shifted special
+  /// keys (i.e. `<S-Right>`) start a selection, when it contains `stopsel`, the unshifted special
+  /// keys stop the selection.
+  ///
+  /// See: <https://vimhelp.org/options.txt.html#%27keymodel%27>.
+  pub fn key_model(&self) -> &str {
+    &self.key_model
+  }
+
+  pub fn set_key_model(&mut self, value: &str) {
+    self.key_model = CompactString::new(value);
+  }
+
+  /// The 'select-mode' option, default to empty. It decides when to start select mode instead of
+  /// visual mode: `mouse` when using the mouse, `key` when using shifted special keys, `cmd` when
+  /// using `v`, `V` or `<C-v>`.
+  ///
+  /// See: <https://vimhelp.org/options.txt.html#%27selectmode%27>.
+  pub fn select_mode(&self) -> &str {
+    &self.select_mode
+  }
+
+  pub fn set_select_mode(&mut self, value: &str) {
+    self.select_mode = CompactString::new(value);
+  }
+
+  /// The 'mouse' option, default to empty. It enables the mouse in the modes of its flags: `n`
+  /// normal mode, `v` visual and select mode, `i` insert and replace mode, `c` command-line mode,
+  /// `r` the message pager, and `a` all the modes except the message pager.
+  ///
+  /// See: <https://vimhelp.org/options.txt.html#%27mouse%27>.
+  pub fn mouse(&self) -> &str {
+    &self.mouse
+  }
+
+  pub fn set_mouse(&mut self, value: &str) {
+    self.mouse = CompactString::new(value);
+  }
+
+  /// Whether the 'mouse' option enables the mouse with the `flag`, the `a` flag includes `n`, `v`,
+  /// `i` and `c`.
+  pub fn mouse_has(&self, flag: char) -> bool {
+    self.mouse.contains(flag)
+      || (matches!(flag, 'n' | 'v' | 'i' | 'c') && self.mouse.contains('a'))
+  }
+
+  /// Whether the comma-separated 'key-model' option contains the `item`.
+  pub fn key_model_has(&self, item: &str) -> bool {
+    self.key_model.split(',').any(|i| i == item)
+  }
+
+  /// Whether the comma-separated 'select-mode' option contains the `item`.
+  pub fn select_mode_has(&self, item: &str) -> bool {
+    self.select_mode.split(',').any(|i| i == item)
+  }
+}
@@ -0,0 +1,25 @@
+use super::opt::*;
+
+use crate::defaults;
+
+#[test]
+pub fn options1() {
+  let opt1 = GlobalOptionsBuilder::default()
+    .history(10)
+    .wild_mode("longest,full".into())
+    .build()
+    .unwrap();
+  assert_eq!(opt1.history(), 10);
+  assert_eq!(opt1.wild_mode(), "longest,full");
+  assert_eq!(opt1.wild_ignore(), defaults::global::WILD_IGNORE);
+
+  let opt2 = GlobalOptionsBuilder::default().build().unwrap();
+  assert_eq!(opt2.history(), defaults::global::HISTORY);
+  assert_eq!(opt2.message_history(), defaults::global::MESSAGE_HISTORY);
+  assert_eq!(opt2.wild_mode(), defaults::global::WILD_MODE);
+  assert_eq!(opt2.wild_options(), defaults::global::WILD_OPTIONS);
+  assert_eq!(opt2.timeout(), defaults::global::TIMEOUT);
+  assert_eq!(opt2.timeout_len(), defaults::global::TIMEOUT_LEN);
+  assert_eq!(opt2.show_cmd(), defaults::global::SHOW_CMD);
+  assert_eq!(opt2.update_time(), defaults::global::UPDATE_TIME);
+}
@@ -0,0 +1,194 @@
+//! Dot-repeat, i.e. `.` repeats the last change.
+//!
+//! The last change is recorded as the keys of the command, without the register and counts, so
+//! it can be repeated with a new count. The keys typed in insert mode (i.e. the inserted text of
+//! `cw`, `i`, `o`) are appended to the change until going back to normal mode.
+//!
+//! The visual mode changes are repeated on the region with the same size from the cursor, and the
+//! repeatable action registered by `Rsvim.cmd.setRepeat` is repeated by calling the js callback.
+//!
+//! See: <https://vimhelp.org/repeat.txt.html#.>.
+
+use crate::buf::selection::{Selection, SelectionKind};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+/// The pressed keys of the chars, i.e. the register and count of the last change.
+pub fn char_keys(s: &str) -> Vec<KeyEvent> {
+  s.chars()
+    .map(|c| {
+      KeyEvent::new_with_kind(
+        KeyCode::Char(c),
+        KeyModifiers::empty(),
+        KeyEventKind::Press,
+      )
+    })
+    .collect()
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// The size of the visual region, it is repeated from the cursor.
+pub struct RepeatRegion {
+  pub kind: SelectionKind,
+
+  /// The lines below the first line.
+  pub lines: usize,
+
+  /// For linewise region, it is not used. For charwise region, it is the chars after the start
+  /// char if the region is in one line, or the char index on the last line. For blockwise
+  /// region, it is the chars after the start char on each line.
+  pub chars: usize,
+}
+
+impl RepeatRegion {
+  /// The size of the selection.
+  pub fn from_selection(selection: &Selection) -> Self {
+    let (start, end) = (selection.start(), selection.end());
+    let lines = end.0 - start.0;
+    let chars = match selection.kind() {
+      SelectionKind::Line => 0,
+      SelectionKind::Char if lines > 0 => end.1,
+      SelectionKind::Char | SelectionKind::Block => {
+        std::cmp::max(start.1, end.1) - std::cmp::min(start.1, end.1)
+      }
+    };
+    Self {
+      kind: selection.kind(),
+      lines,
+      chars,
+    }
+  }
+
+  /// The selection with the same size from the `cursor` position `(line_idx, char_idx)`.
+  ///
+  /// NOTE: The selection can be out of text, it should be clamped.
+  pub fn to_selection(&self, cursor: (usize, usize)) -> Selection {
+    let end_line = cursor.0 + self.lines;
+    let end_char = match self.kind {
+      SelectionKind::Line => cursor.1,
+      SelectionKind::Char if self.lines > 0 => self.chars,
+      SelectionKind::Char | SelectionKind::Block => cursor.1 + self.chars,
+    };
+    Selection::new(self.kind, cursor, (end_line, end_char))
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The kind of change.
+pub enum RepeatKind {
+  /// The normal mode command, i.e. `dw`, `ciw`, `o`.
+  Normal,
+
+  /// The visual mode operator, it operates on the region with the same size.
+  Visual(RepeatRegion),
+
+  /// The repeatable action registered by js.
+  Js,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The last repeatable change.
+pub struct RepeatableChange {
+  pub kind: RepeatKind,
+
+  /// The register given by `"x`.
+  pub register: Option<char>,
+
+  /// The count, a new count given to `.` replaces it.
+  pub count: Option<usize>,
+
+  /// The keys of the command without the register and counts, and the keys typed in insert mode.
+  pub keys: Vec<KeyEvent>,
+}
+
+impl RepeatableChange {
+  pub fn new(
+    kind: RepeatKind,
+    register: Option<char>,
+    count: Option<usize>,
+    keys: Vec<KeyEvent>,
+  ) -> Self {
+    Self {
+      kind,
+      register,
+      count,
+      keys,
+    }
+  }
+
+  /// The repeatable action registered by js.
+  pub fn js() -> Self {
+    Self::new(RepeatKind::Js, None, None, vec![])
+  }
+
+  /// The keys fed to the state machine to repeat the change, i.e. `"x`, the count and the keys of
+  /// the command. The count is not used by the visual mode change.
+  pub fn replay_keys(&self) -> Vec<KeyEvent> {
+    let mut prefix = String::new();
+    if let Some(register) = self.register {
+      prefix.push('"');
+      prefix.push(register);
+    }
+    if let (Some(count), RepeatKind::Normal) = (self.count, &self.kind) {
+      prefix.push_str(&count.to_string());
+    }
+    let mut keys = char_keys(&prefix);
+    keys.extend(self.keys.iter().copied());
+    keys
+  }
+}
+
+#[derive(Debug, Clone, Default)]
+/// The recorder of the last repeatable change.
+pub struct DotRepeat {
+  last_change: Option<RepeatableChange>,
+
+  // The change is still being recorded, i.e. the keys typed in insert mode.
+  recording: Option<RepeatableChange>,
+
+  // The last change is being repeated, nothing is recorded.
+  replaying: bool,
+}
+
+impl DotRepeat {
+  pub fn last_change(&self) -> Option<&RepeatableChange> {
+    self.last_change.as_ref()
+  }
+
+  /// Set the last change directly, i.e. the js action, or the last change with a new count.
+  pub fn set_last_change(&mut self, change: RepeatableChange) {
+    self.recording = None;
+    self.last_change = Some(change);
+  }
+
+  /// Start recording the change, the keys typed in insert mode are appended until
+  /// [`finish`](Self::finish).
+  pub fn start(&mut self, change: RepeatableChange) {
+    if !self.replaying {
+      self.recording = Some(change);
+    }
+  }
+
+  /// Append the key typed in insert mode to the recording change.
+  pub fn push_key(&mut self, key: KeyEvent) {
+    if let Some(change) = &mut self.recording {
+      change.keys.push(key);
+    }
+  }
+
+  /// Finish recording the change, it becomes the last change.
+  pub fn finish(&mut self) {
+    if let Some(change) = self.recording.take() {
+      self.last_change = Some(change);
+    }
+  }
+
+  pub fn is_replaying(&self) -> bool {
+    self.replaying
+  }
+
+  pub fn set_replaying(&mut self, value: bool) {
+    self.replaying = value;
+    self.recording = None;
+  }
+}
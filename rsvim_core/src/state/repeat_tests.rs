@@ -0,0 +1,107 @@
+use super::repeat::*;
+
+use crate::buf::selection::{Selection, SelectionKind};
+use crate::test::log::init as test_log_init;
+
+#[test]
+fn dot_repeat1() {
+  test_log_init();
+  let mut dot_repeat = DotRepeat::default();
+  assert!(dot_repeat.last_change().is_none());
+
+  // The keys typed in insert mode are appended until finish.
+  dot_repeat.start(RepeatableChange::new(
+    RepeatKind::Normal,
+    None,
+    None,
+    char_keys("cw"),
+  ));
+  assert!(dot_repeat.last_change().is_none());
+  for key in char_keys("ab") {
+    dot_repeat.push_key(key);
+  }
+  dot_repeat.finish();
+  assert_eq!(dot_repeat.last_change().unwrap().keys, char_keys("cwab"));
+
+  // Nothing is recorded when there's no recording change, or the last change is being replayed.
+  dot_repeat.push_key(char_keys("x")[0]);
+  dot_repeat.set_replaying(true);
+  assert!(dot_repeat.is_replaying());
+  dot_repeat.start(RepeatableChange::new(
+    RepeatKind::Normal,
+    None,
+    None,
+    char_keys("dd"),
+  ));
+  dot_repeat.finish();
+  dot_repeat.set_replaying(false);
+  assert_eq!(dot_repeat.last_change().unwrap().keys, char_keys("cwab"));
+
+  dot_repeat.set_last_change(RepeatableChange::js());
+  assert_eq!(dot_repeat.last_change().unwrap().kind, RepeatKind::Js);
+}
+
+#[test]
+fn replay_keys1() {
+  test_log_init();
+  let change = RepeatableChange::new(
+    RepeatKind::Normal,
+    Some('a'),
+    Some(12),
+    char_keys("dw"),
+  );
+  assert_eq!(change.replay_keys(), char_keys("\"a12dw"));
+
+  // The count is not used by the visual mode change.
+  let region = RepeatRegion::from_selection(&Selection::new(
+    SelectionKind::Char,
+    (0, 2),
+    (0, 4),
+  ));
+  let change = RepeatableChange::new(
+    RepeatKind::Visual(region),
+    None,
+    Some(3),
+    char_keys("d"),
+  );
+  assert_eq!(change.replay_keys(), char_keys("d"));
+}
+
+#[test]
+fn region1() {
+  test_log_init();
+  let region = RepeatRegion::from_selection(&Selection::new(
+    SelectionKind::Char,
+    (0, 4),
+    (0, 2),
+  ));
+  assert_eq!(region.lines, 0);
+  assert_eq!(region.chars, 2);
+  let selection = region.to_selection((3, 1));
+  assert_eq!((selection.start(), selection.end()), ((3, 1), (3, 3)));
+
+  let region = RepeatRegion::from_selection(&Selection::new(
+    SelectionKind::Char,
+    (1, 4),
+    (2, 2),
+  ));
+  let selection = region.to_selection((3, 1));
+  assert_eq!((selection.start(), selection.end()), ((3, 1), (4, 2)));
+
+  let region = RepeatRegion::from_selection(&Selection::new(
+    SelectionKind::Block,
+    (1, 4),
+    (3, 2),
+  ));
+  let selection = region.to_selection((0, 0));
+  assert_eq!(selection.kind(), SelectionKind::Block);
+  assert_eq!((selection.start(), selection.end()), ((0, 0), (2, 2)));
+
+  let region = RepeatRegion::from_selection(&Selection::new(
+    SelectionKind::Line,
+    (2, 4),
+    (1, 0),
+  ));
+  let selection = region.to_selection((5, 3));
+  assert_eq!(selection.lines(), 5..=6);
+}
@@ -0,0 +1,295 @@
+//! The embedded terminal emulator, i.e. `:terminal`.
+//!
+//! The program runs in a [`Pty`], its output is parsed by the [`Parser`] and applied on the
+//! [`Grid`]. The scrollback and the screen rows are copied into the text of the terminal buffer,
+//! so the terminal is rendered in the window (and can be copied and searched in normal mode) like
+//! any other buffers.
+
+use crate::buf::BufferId;
+use crate::buf::text::Text;
+use crate::evloop::msg::{
+  TerminalExited, TerminalOutput, WorkerToMasterMessage,
+};
+use crate::prelude::*;
+
+use grid::Grid;
+use parser::Parser;
+use pty::Pty;
+use sync::TextSync;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::io::Read;
+use tokio::sync::mpsc::Sender;
+use tracing::trace;
+
+pub mod grid;
+pub mod parser;
+pub mod pty;
+pub mod sync;
+
+#[cfg(test)]
+mod grid_tests;
+#[cfg(test)]
+mod parser_tests;
+#[cfg(test)]
+mod sync_tests;
+
+/// The max count of lines in the scrollback.
+pub const SCROLLBACK: usize = 10000;
+
+#[derive(Debug)]
+/// The terminal emulator of a terminal buffer.
+pub struct Terminal {
+  command: String,
+  parser: Parser,
+  grid: Grid,
+  pty: Pty,
+  sync: TextSync,
+  // The program has exited, with the exit code.
+  exited: Option<Option<i32>>,
+}
+
+impl Terminal {
+  /// Spawn the `command` in the shell, or the shell itself if the `command` is empty. The `size`
+  /// is the columns and rows of the window.
+  pub fn spawn(command: &str, size: U16Size) -> IoResult<Self> {
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".into());
+    let pty = if command.is_empty() {
+      Pty::spawn(&shell, &[], size)?
+    } else {
+      Pty::spawn(&shell, &["-c", command], size)?
+    };
+    let command = if command.is_empty() {
+      shell
+    } else {
+      command.into()
+    };
+    trace!("spawn terminal:{:?}, pid:{:?}", command, pty.pid());
+
+    Ok(Self {
+      command,
+      parser: Parser::new(),
+      grid: Grid::new(
+        size.width() as usize,
+        size.height() as usize,
+        SCROLLBACK,
+      ),
+      pty,
+      sync: TextSync::default(),
+      exited: None,
+    })
+  }
+
+  /// The command running in the terminal.
+  pub fn command(&self) -> &str {
+    &self.command
+  }
+
+  /// The name of the terminal buffer, i.e. `term://1234:bash`.
+  pub fn name(&self) -> String {
+    format!("term://{}:{}", self.pty.pid(), self.command)
+  }
+
+  pub fn grid(&self) -> &Grid {
+    &self.grid
+  }
+
+  /// Whether the program is still running.
+  pub fn is_running(&self) -> bool {
+    self.exited.is_none()
+  }
+
+  /// Start reading the program output in a background thread, the output is sent to the event
+  /// loop with the terminal buffer ID. When the output is closed, the thread waits for the program
+  /// to exit, and sends its exit code.
+  pub fn start_reader(
+    &self,
+    buffer_id: BufferId,
+    worker_send_to_master: Sender<WorkerToMasterMessage>,
+  ) -> IoResult<()> {
+    let mut reader = self.pty.reader()?;
+    let waiter = self.pty.waiter();
+    std::thread::spawn(move || {
+      let mut buf = [0_u8; 4096];
+      loop {
+        match reader.read(&mut buf) {
+          Ok(n) if n > 0 => {
+            let msg = WorkerToMasterMessage::TerminalOutput(
+              TerminalOutput::new(buffer_id, buf[..n].to_vec()),
+            );
+            if worker_send_to_master.blocking_send(msg).is_err() {
+              break;
+            }
+          }
+          _ => {
+            let exit_code = waiter.wait();
+            let msg = WorkerToMasterMessage::TerminalExited(
+              TerminalExited::new(buffer_id, exit_code),
+            );
+            let _ = worker_send_to_master.blocking_send(msg);
+            break;
+          }
+        }
+      }
+    });
+    Ok(())
+  }
+
+  /// Write the bytes to the program, i.e. the typed keys.
+  pub fn write(&mut self, bytes: &[u8]) {
+    if self.is_running() {
+      if let Err(e) = self.pty.write(bytes) {
+        trace!("failed to write terminal:{:?}", e);
+      }
+    }
+  }
+
+  /// Write the pasted text to the program, see [`encode_paste`].
+  pub fn paste(&mut self, text: &str) {
+    let bytes = encode_paste(text, self.grid.bracketed_paste());
+    self.write(&bytes);
+  }
+
+  /// Parse the program output, and copy the lines into the `text`.
+  ///
+  /// Returns the cursor position `(line_idx, char_idx)` in the `text`.
+  pub fn feed(&mut self, bytes: &[u8], text: &mut Text) -> (usize, usize) {
+    let actions = self.parser.advance(bytes);
+    for action in actions.iter() {
+      self.grid.apply(action, |c| text.char_width(c));
+    }
+    let responses = self.grid.take_responses();
+    if !responses.is_empty() {
+      self.write(&responses);
+    }
+    self.sync_text(text)
+  }
+
+  /// Mark the program as exited with the `exit_code` (`None` if it is killed by signal), and
+  /// append the exit message to the `text`, i.e. `[Process exited 0]`.
+  pub fn exit(
+    &mut self,
+    exit_code: Option<i32>,
+    text: &mut Text,
+  ) -> (usize, usize) {
+    self.exited = Some(exit_code);
+    self.sync_text(text)
+  }
+
+  /// Copy the scrollback and the screen rows into the `text`, see [`TextSync`].
+  ///
+  /// Returns the cursor position `(line_idx, char_idx)` in the `text`.
+  pub fn sync_text(&mut self, text: &mut Text) -> (usize, usize) {
+    let exit_line = self.exited.map(|code| match code {
+      Some(code) => format!("[Process exited {code}]"),
+      None => "[Process exited]".to_string(),
+    });
+    let lines_count = self.sync.sync(&self.grid, exit_line.as_deref(), text);
+
+    match self.exited {
+      Some(_) => (lines_count - 1, 0),
+      None => self.cursor(),
+    }
+  }
+
+  /// The terminal cursor `(line_idx, char_idx)` in the text, i.e. below the scrollback.
+  pub fn cursor(&self) -> (usize, usize) {
+    let (row, _) = self.grid.cursor();
+    (
+      self.grid.scrollback().len() + row,
+      self.grid.cursor_char_idx(),
+    )
+  }
+
+  /// Resize both the screen grid and the PTY, and copy the lines into the `text`.
+  ///
+  /// Returns the cursor position `(line_idx, char_idx)` in the `text`.
+  pub fn resize(&mut self, size: U16Size, text: &mut Text) -> (usize, usize) {
+    self
+      .grid
+      .resize(size.width() as usize, size.height() as usize);
+    if let Err(e) = self.pty.resize(size) {
+      trace!("failed to resize terminal:{:?}", e);
+    }
+    self.sync_text(text)
+  }
+}
+
+/// Encode the pasted text to the bytes sent to the program, the line breaks are sent as `Enter`.
+/// The text is wrapped in `ESC [ 200 ~` and `ESC [ 201 ~` if `bracketed_paste` is set.
+///
+/// See: <https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h2-Bracketed-Paste-Mode>.
+pub fn encode_paste(text: &str, bracketed_paste: bool) -> Vec<u8> {
+  let text = text.replace("\r\n", "\r").replace('\n', "\r");
+  if bracketed_paste {
+    format!("\x1b[200~{text}\x1b[201~").into_bytes()
+  } else {
+    text.into_bytes()
+  }
+}
+
+/// Encode the key to the bytes sent to the program, the cursor keys are encoded as the
+/// application sequences (`ESC O A`) if `application_cursor_keys` is set.
+///
+/// See: <https://invisible-island.net/xterm/ctlseqs/ctlseqs.html#h2-PC-Style-Function-Keys>.
+pub fn encode_key(
+  key_event: &KeyEvent,
+  application_cursor_keys: bool,
+) -> Vec<u8> {
+  let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+  let alt = key_event.modifiers.contains(KeyModifiers::ALT);
+  let cursor_key = |c: char| {
+    if application_cursor_keys {
+      format!("\x1bO{c}").into_bytes()
+    } else {
+      format!("\x1b[{c}").into_bytes()
+    }
+  };
+
+  let mut bytes = match key_event.code {
+    KeyCode::Char(c) if ctrl => match c {
+      'a'..='z' | 'A'..='Z' => vec![(c.to_ascii_lowercase() as u8) & 0x1f],
+      '@' | ' ' | '2' => vec![0x00],
+      '[' | '3' => vec![0x1b],
+      '\\' | '4' => vec![0x1c],
+      ']' | '5' => vec![0x1d],
+      '^' | '6' => vec![0x1e],
+      '_' | '-' | '7' => vec![0x1f],
+      '?' | '8' => vec![0x7f],
+      c => c.to_string().into_bytes(),
+    },
+    KeyCode::Char(c) => c.to_string().into_bytes(),
+    KeyCode::Enter => vec![b'\r'],
+    KeyCode::Tab => vec![b'\t'],
+    KeyCode::BackTab => b"\x1b[Z".to_vec(),
+    KeyCode::Backspace => vec![0x7f],
+    KeyCode::Esc => vec![0x1b],
+    KeyCode::Up => cursor_key('A'),
+    KeyCode::Down => cursor_key('B'),
+    KeyCode::Right => cursor_key('C'),
+    KeyCode::Left => cursor_key('D'),
+    KeyCode::Home => cursor_key('H'),
+    KeyCode::End => cursor_key('F'),
+    KeyCode::Insert => b"\x1b[2~".to_vec(),
+    KeyCode::Delete => b"\x1b[3~".to_vec(),
+    KeyCode::PageUp => b"\x1b[5~".to_vec(),
+    KeyCode::PageDown => b"\x1b[6~".to_vec(),
+    KeyCode::F(n) => match n {
+      1 => b"\x1bOP".to_vec(),
+      2 => b"\x1bOQ".to_vec(),
+      3 => b"\x1bOR".to_vec(),
+      4 => b"\x1bOS".to_vec(),
+      5 => b"\x1b[15~".to_vec(),
+      6..=10 => format!("\x1b[{}~", n + 11).into_bytes(),
+      11 | 12 => format!("\x1b[{}~", n + 12).into_bytes(),
+      _ => vec![],
+    },
+    _ => vec![],
+  };
+
+  // The `Alt` (meta) key prefixes `ESC`.
+  if alt && !bytes.is_empty() {
+    bytes.insert(0, 0x1b);
+  }
+  bytes
+}
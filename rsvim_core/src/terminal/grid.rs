@@ -0,0 +1,638 @@
+//! The screen grid of the terminal emulator.
+//!
+//! The grid is the rows of cells of the terminal screen, the [`Action`]s parsed from the PTY
+//! output move the cursor and change the cells. The rows scrolled out of the top of the screen are
+//! saved in the scrollback, the alternate screen (i.e. `ESC [ ? 1049 h` used by full-screen
+//! programs) doesn't have the scrollback.
+//!
+//! NOTE: The colors and text attributes (`SGR`) are ignored, only the text is kept.
+
+use crate::terminal::parser::Action;
+
+use std::collections::VecDeque;
+
+/// The filler of the second cell of a wide char, it is not a part of the text.
+pub const WIDE_CHAR_SPACER: char = '\0';
+
+// The tab stops are at every 8 columns.
+const TAB_WIDTH: usize = 8;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+struct SavedCursor {
+  row: usize,
+  col: usize,
+}
+
+#[derive(Debug, Clone)]
+/// The screen grid, see the module document.
+pub struct Grid {
+  cols: usize,
+  rows: usize,
+  cells: Vec<Vec<char>>,
+
+  // The cursor position `(row, col)`.
+  row: usize,
+  col: usize,
+  // The cursor is after the last column, the next printed char wraps to the next line.
+  wrap_pending: bool,
+  saved_cursor: SavedCursor,
+
+  // The scrolling region, both ends are inclusive.
+  scroll_top: usize,
+  scroll_bottom: usize,
+
+  scrollback: VecDeque<String>,
+  // The absolute index of the first line in the scrollback, i.e. the count of the lines ever
+  // removed from the front of the scrollback.
+  scrollback_start: usize,
+  max_scrollback: usize,
+
+  // The cells of the primary screen, when the alternate screen is used.
+  primary_cells: Option<Vec<Vec<char>>>,
+
+  // Modes.
+  auto_wrap: bool,
+  insert: bool,
+  cursor_visible: bool,
+  application_cursor_keys: bool,
+  bracketed_paste: bool,
+
+  // The responses to the program, i.e. the cursor position report.
+  responses: Vec<u8>,
+  title: Option<String>,
+}
+
+impl Grid {
+  /// Make a grid with `cols` columns and `rows` rows, keep at most `max_scrollback` lines in the
+  /// scrollback.
+  pub fn new(cols: usize, rows: usize, max_scrollback: usize) -> Self {
+    let cols = cols.max(1);
+    let rows = rows.max(1);
+    Self {
+      cols,
+      rows,
+      cells: vec![vec![' '; cols]; rows],
+      row: 0,
+      col: 0,
+      wrap_pending: false,
+      saved_cursor: SavedCursor::default(),
+      scroll_top: 0,
+      scroll_bottom: rows - 1,
+      scrollback: VecDeque::new(),
+      scrollback_start: 0,
+      max_scrollback,
+      primary_cells: None,
+      auto_wrap: true,
+      insert: false,
+      cursor_visible: true,
+      application_cursor_keys: false,
+      bracketed_paste: false,
+      responses: vec![],
+      title: None,
+    }
+  }
+
+  pub fn cols(&self) -> usize {
+    self.cols
+  }
+
+  pub fn rows(&self) -> usize {
+    self.rows
+  }
+
+  /// The cursor position `(row, col)` on the screen.
+  pub fn cursor(&self) -> (usize, usize) {
+    (self.row, self.col)
+  }
+
+  pub fn cursor_visible(&self) -> bool {
+    self.cursor_visible
+  }
+
+  /// The cursor keys send the application sequences (`ESC O A`) instead of the normal sequences
+  /// (`ESC [ A`), i.e. `ESC [ ? 1 h`.
+  pub fn application_cursor_keys(&self) -> bool {
+    self.application_cursor_keys
+  }
+
+  /// The pasted text is surrounded by `ESC [ 200 ~` and `ESC [ 201 ~`, i.e. `ESC [ ? 2004 h`.
+  pub fn bracketed_paste(&self) -> bool {
+    self.bracketed_paste
+  }
+
+  /// Whether the alternate screen is used.
+  pub fn is_alternate_screen(&self) -> bool {
+    self.primary_cells.is_some()
+  }
+
+  /// The window title set by the program, i.e. `ESC ] 0 ; title BEL`.
+  pub fn title(&self) -> Option<&str> {
+    self.title.as_deref()
+  }
+
+  pub fn scrollback(&self) -> &VecDeque<String> {
+    &self.scrollback
+  }
+
+  /// The absolute index of the first line in the scrollback, it increases when the lines are
+  /// removed from the front of the scrollback (i.e. exceeds the max lines, or cleared). The
+  /// absolute indexes of the scrollback lines never change, so the new lines can be found.
+  pub fn scrollback_start(&self) -> usize {
+    self.scrollback_start
+  }
+
+  /// Take the responses that should be written back to the program.
+  pub fn take_responses(&mut self) -> Vec<u8> {
+    std::mem::take(&mut self.responses)
+  }
+
+  /// The text of the screen row, the trailing spaces are trimmed.
+  pub fn row_text(&self, row: usize) -> String {
+    let text: String = self.cells[row]
+      .iter()
+      .filter(|c| **c != WIDE_CHAR_SPACER)
+      .collect();
+    text.trim_end_matches(' ').to_string()
+  }
+
+  /// The char index of the cursor on its row, the wide chars occupy two columns but only one char.
+  pub fn cursor_char_idx(&self) -> usize {
+    self.cells[self.row][..self.col]
+      .iter()
+      .filter(|c| **c != WIDE_CHAR_SPACER)
+      .count()
+  }
+
+  /// All the lines, i.e. the scrollback and the screen rows. The cursor row is padded with spaces
+  /// until the cursor, so the cursor is always on the text.
+  pub fn lines(&self) -> Vec<String> {
+    let mut lines: Vec<String> = self.scrollback.iter().cloned().collect();
+    lines.extend(self.screen_lines());
+    lines
+  }
+
+  /// The screen rows, see [`Grid::lines`].
+  pub fn screen_lines(&self) -> Vec<String> {
+    let mut lines = Vec::with_capacity(self.rows);
+    for row in 0..self.rows {
+      let mut line = self.row_text(row);
+      if row == self.row {
+        let len = line.chars().count();
+        let cursor = self.cursor_char_idx();
+        if len < cursor {
+          line.push_str(&" ".repeat(cursor - len));
+        }
+      }
+      lines.push(line);
+    }
+    lines
+  }
+}
+
+impl Grid {
+  /// Resize the grid to `cols` columns and `rows` rows, the rows above the cursor are scrolled out
+  /// of the top of the screen if the cursor is outside of the new rows.
+  pub fn resize(&mut self, cols: usize, rows: usize) {
+    let cols = cols.max(1);
+    let rows = rows.max(1);
+
+    // Keep the cursor row on the screen.
+    if self.row >= rows {
+      self.scroll_top = 0;
+      self.scroll_bottom = self.rows - 1;
+      self.scroll_up(self.row + 1 - rows);
+      self.row = rows - 1;
+    }
+
+    _resize_cells(&mut self.cells, cols, rows);
+    if let Some(primary_cells) = self.primary_cells.as_mut() {
+      _resize_cells(primary_cells, cols, rows);
+    }
+    self.cols = cols;
+    self.rows = rows;
+    self.col = std::cmp::min(self.col, cols - 1);
+    self.wrap_pending = false;
+    self.saved_cursor = SavedCursor {
+      row: std::cmp::min(self.saved_cursor.row, rows - 1),
+      col: std::cmp::min(self.saved_cursor.col, cols - 1),
+    };
+    self.scroll_top = 0;
+    self.scroll_bottom = rows - 1;
+  }
+}
+
+// Truncate or pad the rows, and the wide char cut by the last column is removed.
+fn _resize_cells(cells: &mut Vec<Vec<char>>, cols: usize, rows: usize) {
+  cells.resize(rows, vec![' '; cols]);
+  for row in cells.iter_mut() {
+    if row.len() > cols && row[cols] == WIDE_CHAR_SPACER {
+      row[cols - 1] = ' ';
+    }
+    row.resize(cols, ' ');
+  }
+}
+
+impl Grid {
+  /// Apply the action.
+  pub fn apply(&mut self, action: &Action, char_width: impl Fn(char) -> usize) {
+    match action {
+      Action::Print(c) => self.print(*c, char_width(*c)),
+      Action::Execute(byte) => self.execute(*byte),
+      Action::Csi {
+        private,
+        params,
+        intermediates,
+        action,
+      } => {
+        if intermediates.is_empty() {
+          self.csi(*private, params, *action);
+        }
+      }
+      Action::Esc {
+        intermediates,
+        action,
+      } => {
+        if intermediates.is_empty() {
+          self.esc(*action);
+        }
+      }
+      Action::Osc(osc) => self.osc(osc),
+    }
+  }
+
+  /// Print the char at the cursor, the `width` is its display width.
+  pub fn print(&mut self, c: char, width: usize) {
+    let width = width.clamp(1, 2);
+    if self.wrap_pending || self.col + width > self.cols {
+      if self.auto_wrap {
+        self.col = 0;
+        self.linefeed();
+      } else {
+        self.col = self.cols.saturating_sub(width);
+      }
+      self.wrap_pending = false;
+    }
+    if self.insert {
+      let row = &mut self.cells[self.row];
+      for _ in 0..width {
+        row.insert(self.col, ' ');
+        row.pop();
+      }
+    }
+
+    self._clear_wide_char_at(self.row, self.col);
+    self.cells[self.row][self.col] = c;
+    if width == 2 {
+      self._clear_wide_char_at(self.row, self.col + 1);
+      self.cells[self.row][self.col + 1] = WIDE_CHAR_SPACER;
+    }
+
+    if self.col + width >= self.cols {
+      self.col = self.cols - 1;
+      self.wrap_pending = true;
+    } else {
+      self.col += width;
+    }
+  }
+
+  // Overwriting a half of the wide char clears the other half.
+  fn _clear_wide_char_at(&mut self, row: usize, col: usize) {
+    let cells = &mut self.cells[row];
+    if cells[col] == WIDE_CHAR_SPACER && col > 0 {
+      cells[col - 1] = ' ';
+    } else if col + 1 < self.cols && cells[col + 1] == WIDE_CHAR_SPACER {
+      cells[col + 1] = ' ';
+    }
+  }
+
+  /// Execute the C0 control code.
+  pub fn execute(&mut self, byte: u8) {
+    match byte {
+      // BS
+      0x08 => {
+        self.col = self.col.saturating_sub(1);
+        self.wrap_pending = false;
+      }
+      // HT
+      0x09 => {
+        self.col =
+          std::cmp::min((self.col / TAB_WIDTH + 1) * TAB_WIDTH, self.cols - 1);
+        self.wrap_pending = false;
+      }
+      // LF, VT, FF
+      0x0a..=0x0c => self.linefeed(),
+      // CR
+      0x0d => {
+        self.col = 0;
+        self.wrap_pending = false;
+      }
+      _ => {}
+    }
+  }
+
+  /// Move the cursor down, scroll up the scrolling region if the cursor is at its bottom.
+  pub fn linefeed(&mut self) {
+    self.wrap_pending = false;
+    if self.row == self.scroll_bottom {
+      self.scroll_up(1);
+    } else if self.row + 1 < self.rows {
+      self.row += 1;
+    }
+  }
+
+  /// Move the cursor up, scroll down the scrolling region if the cursor is at its top, i.e.
+  /// `ESC M`.
+  pub fn reverse_index(&mut self) {
+    self.wrap_pending = false;
+    if self.row == self.scroll_top {
+      self.scroll_down(1);
+    } else {
+      self.row = self.row.saturating_sub(1);
+    }
+  }
+
+  /// Scroll up `n` rows in the scrolling region, the rows scrolled out of the top of the primary
+  /// screen are saved in the scrollback.
+  pub fn scroll_up(&mut self, n: usize) {
+    let n = std::cmp::min(n, self.scroll_bottom - self.scroll_top + 1);
+    for _ in 0..n {
+      let row = self.cells.remove(self.scroll_top);
+      if self.scroll_top == 0 && !self.is_alternate_screen() {
+        let text: String =
+          row.iter().filter(|c| **c != WIDE_CHAR_SPACER).collect();
+        self
+          .scrollback
+          .push_back(text.trim_end_matches(' ').to_string());
+        while self.scrollback.len() > self.max_scrollback {
+          self.scrollback.pop_front();
+          self.scrollback_start += 1;
+        }
+      }
+      self.cells.insert(self.scroll_bottom, vec![' '; self.cols]);
+    }
+  }
+
+  /// Scroll down `n` rows in the scrolling region.
+  pub fn scroll_down(&mut self, n: usize) {
+    let n = std::cmp::min(n, self.scroll_bottom - self.scroll_top + 1);
+    for _ in 0..n {
+      self.cells.remove(self.scroll_bottom);
+      self.cells.insert(self.scroll_top, vec![' '; self.cols]);
+    }
+  }
+
+  fn _move_to(&mut self, row: usize, col: usize) {
+    self.row = std::cmp::min(row, self.rows - 1);
+    self.col = std::cmp::min(col, self.cols - 1);
+    self.wrap_pending = false;
+  }
+
+  fn _clear_cells(&mut self, row: usize, cols: std::ops::Range<usize>) {
+    let end = std::cmp::min(cols.end, self.cols);
+    for col in cols.start..end {
+      self.cells[row][col] = ' ';
+    }
+  }
+
+  fn _set_mode(&mut self, private: Option<u8>, mode: u16, value: bool) {
+    match (private, mode) {
+      (None, 4) => self.insert = value,
+      (Some(b'?'), 1) => self.application_cursor_keys = value,
+      (Some(b'?'), 7) => self.auto_wrap = value,
+      (Some(b'?'), 25) => self.cursor_visible = value,
+      (Some(b'?'), 2004) => self.bracketed_paste = value,
+      (Some(b'?'), 47 | 1047 | 1049) => {
+        if mode == 1049 {
+          if value {
+            self._save_cursor();
+          } else {
+            self._restore_cursor();
+          }
+        }
+        if value && self.primary_cells.is_none() {
+          let alternate = vec![vec![' '; self.cols]; self.rows];
+          self.primary_cells =
+            Some(std::mem::replace(&mut self.cells, alternate));
+        } else if !value {
+          if let Some(primary) = self.primary_cells.take() {
+            self.cells = primary;
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn _save_cursor(&mut self) {
+    self.saved_cursor = SavedCursor {
+      row: self.row,
+      col: self.col,
+    };
+  }
+
+  fn _restore_cursor(&mut self) {
+    let saved = self.saved_cursor;
+    self._move_to(saved.row, saved.col);
+  }
+
+  /// Execute the control sequence, see: <https://invisible-island.net/xterm/ctlseqs/ctlseqs.html>.
+  pub fn csi(&mut self, private: Option<u8>, params: &[u16], action: char) {
+    // The first parameter, the missing (or `0`) parameter is the `default`.
+    let param = |i: usize, default: usize| match params.get(i) {
+      Some(&n) if n > 0 => n as usize,
+      _ => default,
+    };
+
+    if let Some(b'?') = private {
+      match action {
+        'h' | 'l' => {
+          for &mode in params {
+            self._set_mode(private, mode, action == 'h');
+          }
+        }
+        _ => {}
+      }
+      return;
+    }
+    if private.is_some() {
+      // The secondary device attributes, i.e. `ESC [ > c`.
+      if private == Some(b'>') && action == 'c' {
+        self.responses.extend_from_slice(b"\x1b[>0;0;0c");
+      }
+      return;
+    }
+
+    match action {
+      // CUU, CUD, CUF, CUB
+      'A' => {
+        let top = if self.row >= self.scroll_top {
+          self.scroll_top
+        } else {
+          0
+        };
+        self._move_to(
+          std::cmp::max(self.row.saturating_sub(param(0, 1)), top),
+          self.col,
+        );
+      }
+      'B' | 'e' => {
+        let bottom = if self.row <= self.scroll_bottom {
+          self.scroll_bottom
+        } else {
+          self.rows - 1
+        };
+        self._move_to(std::cmp::min(self.row + param(0, 1), bottom), self.col);
+      }
+      'C' | 'a' => self._move_to(self.row, self.col + param(0, 1)),
+      'D' => self._move_to(self.row, self.col.saturating_sub(param(0, 1))),
+      // CNL, CPL
+      'E' => self._move_to(self.row + param(0, 1), 0),
+      'F' => self._move_to(self.row.saturating_sub(param(0, 1)), 0),
+      // CHA, HPA
+      'G' | '`' => self._move_to(self.row, param(0, 1) - 1),
+      // CUP, HVP
+      'H' | 'f' => self._move_to(param(0, 1) - 1, param(1, 1) - 1),
+      // VPA
+      'd' => self._move_to(param(0, 1) - 1, self.col),
+      // ED
+      'J' => match params.first().copied().unwrap_or(0) {
+        0 => {
+          self._clear_cells(self.row, self.col..self.cols);
+          for row in self.row + 1..self.rows {
+            self._clear_cells(row, 0..self.cols);
+          }
+        }
+        1 => {
+          for row in 0..self.row {
+            self._clear_cells(row, 0..self.cols);
+          }
+          self._clear_cells(self.row, 0..self.col + 1);
+        }
+        2 => {
+          for row in 0..self.rows {
+            self._clear_cells(row, 0..self.cols);
+          }
+        }
+        3 => {
+          self.scrollback_start += self.scrollback.len();
+          self.scrollback.clear();
+        }
+        _ => {}
+      },
+      // EL
+      'K' => match params.first().copied().unwrap_or(0) {
+        0 => self._clear_cells(self.row, self.col..self.cols),
+        1 => self._clear_cells(self.row, 0..self.col + 1),
+        2 => self._clear_cells(self.row, 0..self.cols),
+        _ => {}
+      },
+      // IL, DL
+      'L' | 'M'
+        if (self.scroll_top..=self.scroll_bottom).contains(&self.row) =>
+      {
+        let n = std::cmp::min(param(0, 1), self.scroll_bottom - self.row + 1);
+        for _ in 0..n {
+          if action == 'L' {
+            self.cells.remove(self.scroll_bottom);
+            self.cells.insert(self.row, vec![' '; self.cols]);
+          } else {
+            self.cells.remove(self.row);
+            self.cells.insert(self.scroll_bottom, vec![' '; self.cols]);
+          }
+        }
+        self.col = 0;
+        self.wrap_pending = false;
+      }
+      // ICH, DCH
+      '@' | 'P' => {
+        let n = std::cmp::min(param(0, 1), self.cols - self.col);
+        let row = &mut self.cells[self.row];
+        for _ in 0..n {
+          if action == '@' {
+            row.pop();
+            row.insert(self.col, ' ');
+          } else {
+            row.remove(self.col);
+            row.push(' ');
+          }
+        }
+        self.wrap_pending = false;
+      }
+      // ECH
+      'X' => {
+        let end = self.col + param(0, 1);
+        self._clear_cells(self.row, self.col..end);
+      }
+      // SU, SD
+      'S' => self.scroll_up(param(0, 1)),
+      'T' => self.scroll_down(param(0, 1)),
+      // DECSTBM
+      'r' => {
+        let top = param(0, 1) - 1;
+        let bottom = std::cmp::min(param(1, self.rows), self.rows) - 1;
+        if top < bottom {
+          self.scroll_top = top;
+          self.scroll_bottom = bottom;
+          self._move_to(0, 0);
+        }
+      }
+      // SM, RM
+      'h' | 'l' => {
+        for &mode in params {
+          self._set_mode(None, mode, action == 'h');
+        }
+      }
+      // SCOSC, SCORC
+      's' => self._save_cursor(),
+      'u' => self._restore_cursor(),
+      // DSR
+      'n' => match params.first().copied().unwrap_or(0) {
+        5 => self.responses.extend_from_slice(b"\x1b[0n"),
+        6 => {
+          let report = format!("\x1b[{};{}R", self.row + 1, self.col + 1);
+          self.responses.extend_from_slice(report.as_bytes());
+        }
+        _ => {}
+      },
+      // DA
+      'c' => self.responses.extend_from_slice(b"\x1b[?1;2c"),
+      _ => {}
+    }
+  }
+
+  /// Execute the escape sequence.
+  pub fn esc(&mut self, action: char) {
+    match action {
+      // DECSC, DECRC
+      '7' => self._save_cursor(),
+      '8' => self._restore_cursor(),
+      // IND
+      'D' => self.linefeed(),
+      // NEL
+      'E' => {
+        self.col = 0;
+        self.linefeed();
+      }
+      // RI
+      'M' => self.reverse_index(),
+      // RIS
+      'c' => {
+        let scrollback = std::mem::take(&mut self.scrollback);
+        let scrollback_start = self.scrollback_start;
+        *self = Self::new(self.cols, self.rows, self.max_scrollback);
+        self.scrollback = scrollback;
+        self.scrollback_start = scrollback_start;
+      }
+      _ => {}
+    }
+  }
+
+  /// Execute the operating system command, only the window title is supported.
+  pub fn osc(&mut self, osc: &str) {
+    if let Some((code, text)) = osc.split_once(';') {
+      if code == "0" || code == "2" {
+        self.title = Some(text.to_string());
+      }
+    }
+  }
+}
@@ -0,0 +1,150 @@
+use super::grid::*;
+use super::parser::*;
+
+use crate::test::log::init as test_log_init;
+
+fn make_grid(cols: usize, rows: usize, bytes: &[u8]) -> Grid {
+  let mut grid = Grid::new(cols, rows, 100);
+  let mut parser = Parser::new();
+  for action in parser.advance(bytes).iter() {
+    grid.apply(action, |c| if c == '你' || c == '好' { 2 } else { 1 });
+  }
+  grid
+}
+
+#[test]
+fn print1() {
+  test_log_init();
+
+  let grid = make_grid(10, 3, b"hello\r\nworld");
+  assert_eq!(grid.lines(), vec!["hello", "world", ""]);
+  assert_eq!(grid.cursor(), (1, 5));
+  assert_eq!(grid.cursor_char_idx(), 5);
+}
+
+#[test]
+fn print2() {
+  test_log_init();
+
+  // Auto wrap at the last column.
+  let grid = make_grid(4, 3, b"abcdef");
+  assert_eq!(grid.lines(), vec!["abcd", "ef", ""]);
+  assert_eq!(grid.cursor(), (1, 2));
+}
+
+#[test]
+fn print3() {
+  test_log_init();
+
+  // The wide chars occupy two columns.
+  let grid = make_grid(10, 2, "你好a".as_bytes());
+  assert_eq!(grid.row_text(0), "你好a");
+  assert_eq!(grid.cursor(), (0, 5));
+  assert_eq!(grid.cursor_char_idx(), 3);
+}
+
+#[test]
+fn scroll1() {
+  test_log_init();
+
+  // The rows scrolled out of the screen are saved in the scrollback.
+  let grid = make_grid(10, 2, b"1\r\n2\r\n3\r\n4");
+  assert_eq!(grid.scrollback().len(), 2);
+  assert_eq!(grid.lines(), vec!["1", "2", "3", "4"]);
+  assert_eq!(grid.cursor(), (1, 1));
+}
+
+#[test]
+fn cursor1() {
+  test_log_init();
+
+  let grid = make_grid(10, 5, b"\x1b[3;4Hx\x1b[Ay\x1b[Gz");
+  assert_eq!(grid.lines(), vec!["", "z   y", "   x", "", ""]);
+  assert_eq!(grid.cursor(), (1, 1));
+}
+
+#[test]
+fn cursor2() {
+  test_log_init();
+
+  // The cursor row is padded with spaces until the cursor.
+  let grid = make_grid(10, 2, b"ab\x1b[6G");
+  assert_eq!(grid.lines(), vec!["ab   ", ""]);
+  assert_eq!(grid.cursor_char_idx(), 5);
+}
+
+#[test]
+fn erase1() {
+  test_log_init();
+
+  let grid = make_grid(10, 3, b"hello\r\nworld\x1b[3D\x1b[K\x1b[1;3H\x1b[1K");
+  assert_eq!(grid.lines(), vec!["   lo", "wo", ""]);
+
+  let grid = make_grid(10, 3, b"hello\r\nworld\x1b[2J");
+  assert_eq!(grid.lines(), vec!["", "     ", ""]);
+}
+
+#[test]
+fn alternate_screen1() {
+  test_log_init();
+
+  let mut grid = make_grid(10, 2, b"shell");
+  let mut parser = Parser::new();
+  for action in parser.advance(b"\x1b[?1049h\x1b[Hvim").iter() {
+    grid.apply(action, |_| 1);
+  }
+  assert!(grid.is_alternate_screen());
+  assert_eq!(grid.lines(), vec!["vim", ""]);
+
+  for action in parser.advance(b"\x1b[?1049l").iter() {
+    grid.apply(action, |_| 1);
+  }
+  assert!(!grid.is_alternate_screen());
+  assert_eq!(grid.lines(), vec!["shell", ""]);
+  assert_eq!(grid.cursor(), (0, 5));
+}
+
+#[test]
+fn modes1() {
+  test_log_init();
+
+  let mut grid =
+    make_grid(10, 2, b"\x1b[?1h\x1b[?2004h\x1b[?25l\x1b]0;title\x07");
+  assert!(grid.application_cursor_keys());
+  assert!(grid.bracketed_paste());
+  assert!(!grid.cursor_visible());
+  assert_eq!(grid.title(), Some("title"));
+  assert!(grid.take_responses().is_empty());
+}
+
+#[test]
+fn responses1() {
+  test_log_init();
+
+  // The cursor position report.
+  let mut grid = make_grid(10, 5, b"\x1b[2;3H\x1b[6n");
+  assert_eq!(grid.take_responses(), b"\x1b[2;3R".to_vec());
+  assert!(grid.take_responses().is_empty());
+}
+
+#[test]
+fn resize1() {
+  test_log_init();
+
+  // The rows above the cursor are scrolled into the scrollback.
+  let mut grid = make_grid(10, 3, b"a\r\nb\r\nc");
+  grid.resize(4, 2);
+  assert_eq!((grid.cols(), grid.rows()), (4, 2));
+  assert_eq!(grid.lines(), vec!["a", "b", "c"]);
+  assert_eq!(grid.cursor(), (1, 1));
+
+  grid.resize(6, 4);
+  assert_eq!(grid.lines(), vec!["a", "b", "c", "", ""]);
+  assert_eq!(grid.cursor(), (1, 1));
+
+  // The wide char cut by the last column is removed.
+  let mut grid = make_grid(4, 1, "ab你".as_bytes());
+  grid.resize(3, 1);
+  assert_eq!(grid.lines(), vec!["ab"]);
+  assert_eq!(grid.cursor(), (0, 2));
+}
@@ -0,0 +1,296 @@
+//! The VT100/xterm escape sequence parser.
+//!
+//! It is a simplified version of the state machine described by Paul Williams, see:
+//! <https://vt100.net/emu/dec_ansi_parser>. The bytes from the PTY are decoded as UTF-8, and parsed
+//! into the [`Action`]s that are applied on the [`Grid`](crate::terminal::grid::Grid).
+
+use smallvec::SmallVec;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The parsed action.
+pub enum Action {
+  /// Print a char at the cursor.
+  Print(char),
+
+  /// Execute a C0 control code, i.e. `\r`, `\n`, `\x08`.
+  Execute(u8),
+
+  /// The control sequence (`CSI`), i.e. `ESC [ ? 25 h`. The missing parameters are `0`.
+  Csi {
+    /// The private marker, i.e. `?` in `ESC [ ? 25 h`.
+    private: Option<u8>,
+    params: SmallVec<[u16; 8]>,
+    intermediates: SmallVec<[u8; 2]>,
+    action: char,
+  },
+
+  /// The escape sequence, i.e. `ESC 7`, `ESC ( B`.
+  Esc {
+    intermediates: SmallVec<[u8; 2]>,
+    action: char,
+  },
+
+  /// The operating system command (`OSC`), i.e. `ESC ] 0 ; title BEL`.
+  Osc(String),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum State {
+  Ground,
+  Escape,
+  EscapeIntermediate,
+  CsiParam,
+  CsiIntermediate,
+  CsiIgnore,
+  OscString,
+  // The device control strings, and the SOS/PM/APC strings, they're ignored until `ST`.
+  IgnoreString,
+}
+
+// The max count of CSI parameters, the others are ignored.
+const MAX_PARAMS: usize = 16;
+
+// The `ESC` byte.
+const ESC: u8 = 0x1b;
+
+// The `BEL` byte, it also terminates `OSC` strings.
+const BEL: u8 = 0x07;
+
+#[derive(Debug, Clone)]
+/// The parser, it keeps the incomplete sequence (and UTF-8 char) between the calls of
+/// [`advance`](Parser::advance).
+pub struct Parser {
+  state: State,
+  private: Option<u8>,
+  params: SmallVec<[u16; 8]>,
+  param: Option<u16>,
+  intermediates: SmallVec<[u8; 2]>,
+  osc: Vec<u8>,
+  // The `ESC` inside a string, it is the string terminator `ESC \`.
+  string_esc: bool,
+  utf8: SmallVec<[u8; 4]>,
+}
+
+impl Default for Parser {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+// The byte count of the UTF-8 char by its first byte, or `None` if it is not a first byte.
+fn _utf8_len(first: u8) -> Option<usize> {
+  match first {
+    0x00..=0x7f => Some(1),
+    0xc2..=0xdf => Some(2),
+    0xe0..=0xef => Some(3),
+    0xf0..=0xf4 => Some(4),
+    _ => None,
+  }
+}
+
+impl Parser {
+  pub fn new() -> Self {
+    Self {
+      state: State::Ground,
+      private: None,
+      params: SmallVec::new(),
+      param: None,
+      intermediates: SmallVec::new(),
+      osc: vec![],
+      string_esc: false,
+      utf8: SmallVec::new(),
+    }
+  }
+
+  /// Parse the bytes, returns the actions.
+  pub fn advance(&mut self, bytes: &[u8]) -> Vec<Action> {
+    let mut actions = vec![];
+    for &byte in bytes {
+      self._advance_byte(byte, &mut actions);
+    }
+    actions
+  }
+
+  fn _clear(&mut self) {
+    self.private = None;
+    self.params.clear();
+    self.param = None;
+    self.intermediates.clear();
+  }
+
+  fn _advance_byte(&mut self, byte: u8, actions: &mut Vec<Action>) {
+    // The strings only end with `BEL` or `ST`.
+    match self.state {
+      State::OscString | State::IgnoreString => {
+        self._advance_string(byte, actions);
+        return;
+      }
+      _ => {}
+    }
+
+    // The incomplete UTF-8 char.
+    if !self.utf8.is_empty() {
+      if (0x80..=0xbf).contains(&byte) {
+        self.utf8.push(byte);
+        if Some(self.utf8.len()) == _utf8_len(self.utf8[0]) {
+          let c = std::str::from_utf8(&self.utf8)
+            .ok()
+            .and_then(|s| s.chars().next())
+            .unwrap_or(char::REPLACEMENT_CHARACTER);
+          self.utf8.clear();
+          actions.push(Action::Print(c));
+        }
+        return;
+      }
+      self.utf8.clear();
+      actions.push(Action::Print(char::REPLACEMENT_CHARACTER));
+    }
+
+    // The controls are executed in any state, except inside the strings.
+    match byte {
+      0x18 | 0x1a => {
+        // `CAN` and `SUB` cancel the sequence.
+        self.state = State::Ground;
+        return;
+      }
+      ESC => {
+        self._clear();
+        self.state = State::Escape;
+        return;
+      }
+      0x00..=0x1f => {
+        actions.push(Action::Execute(byte));
+        return;
+      }
+      _ => {}
+    }
+
+    match self.state {
+      State::Ground => {
+        if byte < 0x80 {
+          if byte != 0x7f {
+            actions.push(Action::Print(byte as char));
+          }
+        } else {
+          match _utf8_len(byte) {
+            Some(_) => self.utf8.push(byte),
+            None => actions.push(Action::Print(char::REPLACEMENT_CHARACTER)),
+          }
+        }
+      }
+      State::Escape => match byte {
+        b'[' => self.state = State::CsiParam,
+        b']' => {
+          self.osc.clear();
+          self.state = State::OscString;
+        }
+        b'P' | b'X' | b'^' | b'_' => self.state = State::IgnoreString,
+        0x20..=0x2f => {
+          self.intermediates.push(byte);
+          self.state = State::EscapeIntermediate;
+        }
+        0x30..=0x7e => {
+          actions.push(Action::Esc {
+            intermediates: self.intermediates.clone(),
+            action: byte as char,
+          });
+          self.state = State::Ground;
+        }
+        _ => {}
+      },
+      State::EscapeIntermediate => match byte {
+        0x20..=0x2f => self.intermediates.push(byte),
+        0x30..=0x7e => {
+          actions.push(Action::Esc {
+            intermediates: self.intermediates.clone(),
+            action: byte as char,
+          });
+          self.state = State::Ground;
+        }
+        _ => {}
+      },
+      State::CsiParam => match byte {
+        b'0'..=b'9' => {
+          let digit = (byte - b'0') as u16;
+          self.param = Some(
+            self
+              .param
+              .unwrap_or(0)
+              .saturating_mul(10)
+              .saturating_add(digit),
+          );
+        }
+        // The sub-parameters (i.e. `38:2:r:g:b`) are treated as parameters.
+        b';' | b':' => {
+          if self.params.len() < MAX_PARAMS {
+            self.params.push(self.param.unwrap_or(0));
+          }
+          self.param = None;
+        }
+        b'<' | b'=' | b'>' | b'?' => {
+          if self.private.is_none()
+            && self.params.is_empty()
+            && self.param.is_none()
+          {
+            self.private = Some(byte);
+          } else {
+            self.state = State::CsiIgnore;
+          }
+        }
+        0x20..=0x2f => {
+          self.intermediates.push(byte);
+          self.state = State::CsiIntermediate;
+        }
+        0x40..=0x7e => self._dispatch_csi(byte, actions),
+        _ => self.state = State::CsiIgnore,
+      },
+      State::CsiIntermediate => match byte {
+        0x20..=0x2f => self.intermediates.push(byte),
+        0x40..=0x7e => self._dispatch_csi(byte, actions),
+        _ => self.state = State::CsiIgnore,
+      },
+      State::CsiIgnore => {
+        if (0x40..=0x7e).contains(&byte) {
+          self.state = State::Ground;
+        }
+      }
+      State::OscString | State::IgnoreString => unreachable!(),
+    }
+  }
+
+  fn _dispatch_csi(&mut self, byte: u8, actions: &mut Vec<Action>) {
+    if (self.param.is_some() || !self.params.is_empty())
+      && self.params.len() < MAX_PARAMS
+    {
+      self.params.push(self.param.unwrap_or(0));
+    }
+    actions.push(Action::Csi {
+      private: self.private,
+      params: self.params.clone(),
+      intermediates: self.intermediates.clone(),
+      action: byte as char,
+    });
+    self.state = State::Ground;
+  }
+
+  fn _advance_string(&mut self, byte: u8, actions: &mut Vec<Action>) {
+    let end = match byte {
+      BEL => true,
+      b'\\' if self.string_esc => true,
+      _ => false,
+    };
+    self.string_esc = byte == ESC;
+
+    if end {
+      if self.state == State::OscString {
+        let osc = String::from_utf8_lossy(&self.osc).to_string();
+        actions.push(Action::Osc(osc));
+      }
+      self.osc.clear();
+      self.string_esc = false;
+      self.state = State::Ground;
+    } else if self.state == State::OscString && byte != ESC {
+      self.osc.push(byte);
+    }
+  }
+}
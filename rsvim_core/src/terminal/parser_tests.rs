@@ -0,0 +1,133 @@
+use super::parser::*;
+
+use crate::test::log::init as test_log_init;
+
+use smallvec::smallvec;
+
+#[test]
+fn print1() {
+  test_log_init();
+
+  let mut parser = Parser::new();
+  let actions = parser.advance("ab\r\n你".as_bytes());
+  assert_eq!(
+    actions,
+    vec![
+      Action::Print('a'),
+      Action::Print('b'),
+      Action::Execute(b'\r'),
+      Action::Execute(b'\n'),
+      Action::Print('你'),
+    ]
+  );
+}
+
+#[test]
+fn print2() {
+  test_log_init();
+
+  // The UTF-8 char is split into two reads.
+  let mut parser = Parser::new();
+  let bytes = "你".as_bytes();
+  assert!(parser.advance(&bytes[..1]).is_empty());
+  assert_eq!(parser.advance(&bytes[1..]), vec![Action::Print('你')]);
+}
+
+#[test]
+fn csi1() {
+  test_log_init();
+
+  let mut parser = Parser::new();
+  let actions = parser.advance(b"\x1b[12;5H\x1b[K\x1b[?25l");
+  assert_eq!(
+    actions,
+    vec![
+      Action::Csi {
+        private: None,
+        params: smallvec![12, 5],
+        intermediates: smallvec![],
+        action: 'H',
+      },
+      Action::Csi {
+        private: None,
+        params: smallvec![],
+        intermediates: smallvec![],
+        action: 'K',
+      },
+      Action::Csi {
+        private: Some(b'?'),
+        params: smallvec![25],
+        intermediates: smallvec![],
+        action: 'l',
+      },
+    ]
+  );
+}
+
+#[test]
+fn csi2() {
+  test_log_init();
+
+  // The sequence is split into several reads, and the missing parameters are `0`.
+  let mut parser = Parser::new();
+  assert!(parser.advance(b"\x1b[;").is_empty());
+  assert_eq!(
+    parser.advance(b"3rx"),
+    vec![
+      Action::Csi {
+        private: None,
+        params: smallvec![0, 3],
+        intermediates: smallvec![],
+        action: 'r',
+      },
+      Action::Print('x'),
+    ]
+  );
+}
+
+#[test]
+fn esc1() {
+  test_log_init();
+
+  let mut parser = Parser::new();
+  let actions = parser.advance(b"\x1b7\x1b(B");
+  assert_eq!(
+    actions,
+    vec![
+      Action::Esc {
+        intermediates: smallvec![],
+        action: '7',
+      },
+      Action::Esc {
+        intermediates: smallvec![b'('],
+        action: 'B',
+      },
+    ]
+  );
+}
+
+#[test]
+fn osc1() {
+  test_log_init();
+
+  let mut parser = Parser::new();
+  let actions = parser.advance(b"\x1b]0;hello\x07\x1b]2;world\x1b\\a");
+  assert_eq!(
+    actions,
+    vec![
+      Action::Osc("0;hello".to_string()),
+      Action::Osc("2;world".to_string()),
+      Action::Print('a'),
+    ]
+  );
+}
+
+#[test]
+fn ignore1() {
+  test_log_init();
+
+  // The device control strings are ignored, `CAN` cancels the sequence.
+  let mut parser = Parser::new();
+  let actions = parser.advance(b"\x1bPq#0\x1b\\a\x1b[1\x18b");
+  assert_eq!(actions, vec![Action::Print('a'), Action::Print('b')]);
+}
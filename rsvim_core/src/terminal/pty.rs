@@ -0,0 +1,228 @@
+//! The pseudo terminal (PTY) that runs the program of the terminal emulator.
+//!
+//! The program is spawned with the PTY slave as its controlling terminal, the editor writes the
+//! keys to the PTY master, and reads the program output from it in a background thread.
+
+use crate::prelude::*;
+
+use parking_lot::{Condvar, Mutex};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+#[derive(Debug, Default)]
+// The exit status of the program, it is set by the wait thread after the program is reaped.
+struct ExitStatus {
+  // The program is reaped, with the exit code (`None` if it is killed by signal).
+  exited: Mutex<Option<Option<i32>>>,
+  cond: Condvar,
+}
+
+#[derive(Debug)]
+/// The PTY master and the spawned program.
+pub struct Pty {
+  master: File,
+  pid: u32,
+  // The program is reaped in a dedicated thread, see [`Pty::waiter`].
+  status: Arc<ExitStatus>,
+}
+
+#[derive(Debug)]
+/// Wait for the program to exit, see [`Pty::waiter`].
+pub struct PtyWaiter {
+  status: Arc<ExitStatus>,
+}
+
+impl PtyWaiter {
+  /// Wait until the program is reaped, thus the exit code is available. It blocks the current
+  /// thread, i.e. the reader thread after the output is closed.
+  ///
+  /// Returns the exit code, or `None` if it is killed by signal.
+  pub fn wait(self) -> Option<i32> {
+    let mut exited = lock!(self.status.exited);
+    loop {
+      if let Some(exit_code) = *exited {
+        return exit_code;
+      }
+      self.status.cond.wait(&mut exited);
+    }
+  }
+}
+
+#[cfg(unix)]
+// Set the window size of the PTY, the program receives `SIGWINCH`.
+fn _set_window_size(file: &File, size: U16Size) -> IoResult<()> {
+  use std::os::fd::AsRawFd;
+
+  let ws = libc::winsize {
+    ws_row: size.height(),
+    ws_col: size.width(),
+    ws_xpixel: 0,
+    ws_ypixel: 0,
+  };
+  // SAFETY: The file descriptor is valid, and the `winsize` lives during the call.
+  let result = unsafe { libc::ioctl(file.as_raw_fd(), libc::TIOCSWINSZ, &ws) };
+  if result < 0 {
+    return Err(std::io::Error::last_os_error());
+  }
+  Ok(())
+}
+
+impl Pty {
+  #[cfg(unix)]
+  /// Spawn the `program` (with the `args`) in a new PTY with the `size` (columns and rows).
+  pub fn spawn(program: &str, args: &[&str], size: U16Size) -> IoResult<Self> {
+    use std::ffi::CStr;
+    use std::os::fd::{AsRawFd, FromRawFd};
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command, Stdio};
+
+    // SAFETY: The returned file descriptor is checked, and then owned by the `File`.
+    let master = unsafe {
+      let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+      if fd < 0 {
+        return Err(std::io::Error::last_os_error());
+      }
+      File::from_raw_fd(fd)
+    };
+    // SAFETY: The `ptsname` result is copied before any other PTY is opened.
+    let slave_name = unsafe {
+      if libc::grantpt(master.as_raw_fd()) < 0
+        || libc::unlockpt(master.as_raw_fd()) < 0
+      {
+        return Err(std::io::Error::last_os_error());
+      }
+      let name = libc::ptsname(master.as_raw_fd());
+      if name.is_null() {
+        return Err(std::io::Error::last_os_error());
+      }
+      CStr::from_ptr(name).to_string_lossy().to_string()
+    };
+    _set_window_size(&master, size)?;
+
+    let slave = std::fs::OpenOptions::new()
+      .read(true)
+      .write(true)
+      .custom_flags(libc::O_NOCTTY)
+      .open(&slave_name)?;
+
+    let mut command = Command::new(program);
+    command
+      .args(args)
+      .env("TERM", "xterm")
+      .env("COLUMNS", size.width().to_string())
+      .env("LINES", size.height().to_string())
+      .stdin(Stdio::from(slave.try_clone()?))
+      .stdout(Stdio::from(slave.try_clone()?))
+      .stderr(Stdio::from(slave));
+    // SAFETY: Only the async-signal-safe functions are called in the child process.
+    unsafe {
+      command.pre_exec(|| {
+        // Start a new session, and make the PTY slave (the stdin) the controlling terminal.
+        if libc::setsid() < 0 {
+          return Err(std::io::Error::last_os_error());
+        }
+        if libc::ioctl(0, libc::TIOCSCTTY as _, 0) < 0 {
+          return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+      });
+    }
+    let mut child = command.spawn()?;
+    let pid = child.id();
+
+    // The program is reaped by the blocking wait in a dedicated thread, so the exit code is
+    // available as soon as it exits.
+    let status = Arc::new(ExitStatus::default());
+    let wait_status = status.clone();
+    std::thread::spawn(move || {
+      let exit_code = match child.wait() {
+        Ok(status) => status.code(),
+        Err(e) => {
+          trace!("failed to wait terminal program:{:?}", e);
+          None
+        }
+      };
+      *lock!(wait_status.exited) = Some(exit_code);
+      wait_status.cond.notify_all();
+    });
+
+    Ok(Self {
+      master,
+      pid,
+      status,
+    })
+  }
+
+  #[cfg(not(unix))]
+  /// Spawn the `program` (with the `args`) in a new PTY with the `size` (columns and rows).
+  ///
+  /// NOTE: The PTY is not supported on this platform yet.
+  pub fn spawn(
+    _program: &str,
+    _args: &[&str],
+    _size: U16Size,
+  ) -> IoResult<Self> {
+    Err(std::io::Error::new(
+      std::io::ErrorKind::Unsupported,
+      "pty is not supported on this platform",
+    ))
+  }
+
+  /// The process ID of the program.
+  pub fn pid(&self) -> u32 {
+    self.pid
+  }
+
+  /// Make a waiter of the program, it waits in the reader thread after the output is closed,
+  /// since the program is not reaped yet when its output is closed.
+  pub fn waiter(&self) -> PtyWaiter {
+    PtyWaiter {
+      status: self.status.clone(),
+    }
+  }
+
+  /// Make a reader of the program output, it is read in a background thread. The reading fails
+  /// (or returns `0`) when the program exits.
+  pub fn reader(&self) -> IoResult<impl Read + Send + 'static> {
+    self.master.try_clone()
+  }
+
+  /// Write the bytes (i.e. the typed keys) to the program.
+  pub fn write(&mut self, bytes: &[u8]) -> IoResult<()> {
+    self.master.write_all(bytes)?;
+    self.master.flush()
+  }
+
+  /// Resize the PTY.
+  pub fn resize(&self, size: U16Size) -> IoResult<()> {
+    #[cfg(unix)]
+    {
+      _set_window_size(&self.master, size)
+    }
+    #[cfg(not(unix))]
+    {
+      let _ = size;
+      Ok(())
+    }
+  }
+
+  /// Whether the program is still running.
+  pub fn is_running(&self) -> bool {
+    lock!(self.status.exited).is_none()
+  }
+}
+
+impl Drop for Pty {
+  fn drop(&mut self) {
+    // The program is killed, the wait thread reaps it.
+    #[cfg(unix)]
+    if self.is_running() {
+      // SAFETY: It only sends the signal to the process.
+      unsafe {
+        libc::kill(self.pid as libc::pid_t, libc::SIGKILL);
+      }
+    }
+  }
+}
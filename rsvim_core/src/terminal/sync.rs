@@ -0,0 +1,106 @@
+//! Copy the grid lines into the text of the terminal buffer.
+//!
+//! The text is synced incrementally: the lines removed from the scrollback are removed from the
+//! top of the text, the lines newly pushed to the scrollback are appended, and only the changed
+//! screen rows are replaced. Thus each sync doesn't depend on the size of the scrollback, and the
+//! scrollback lines the user is reading (i.e. copying or searching in normal mode) are not
+//! rewritten.
+
+use crate::buf::text::Text;
+
+use super::grid::Grid;
+
+use std::ops::Range;
+
+#[derive(Debug, Clone, Default)]
+/// The grid lines that are already copied into the text.
+pub struct TextSync {
+  // The absolute indexes of the scrollback lines in the text, see [`Grid::scrollback_start`].
+  scrollback: Range<usize>,
+  // The lines after the scrollback in the text, i.e. the screen rows and the exit message.
+  rows: Vec<String>,
+}
+
+impl TextSync {
+  /// The count of lines in the text.
+  pub fn lines_count(&self) -> usize {
+    self.scrollback.len() + self.rows.len()
+  }
+
+  /// Copy the grid lines into the `text`. If the program has exited, the empty rows at the bottom
+  /// are removed and the `exit_line` is appended.
+  ///
+  /// Returns the count of lines in the text.
+  pub fn sync(
+    &mut self,
+    grid: &Grid,
+    exit_line: Option<&str>,
+    text: &mut Text,
+  ) -> usize {
+    // The text is changed by others, copy all the lines again.
+    let empty = self.lines_count() == 0;
+    if text.lines_count() != self.lines_count().max(1)
+      || (empty && text.rope().len_chars() > 0)
+    {
+      let len_chars = text.rope().len_chars();
+      text.replace_range(0..len_chars, "");
+      *self = Self::default();
+    }
+
+    let start = grid.scrollback_start();
+    let end = start + grid.scrollback().len();
+
+    // Remove the lines that are removed from the scrollback.
+    let kept_start = self.scrollback.start.max(start).min(self.scrollback.end);
+    let removed = kept_start - self.scrollback.start;
+    if removed > 0 {
+      text.set_lines(0..removed, &[]);
+    }
+    let kept = kept_start..self.scrollback.end;
+
+    // The lines after the kept scrollback, i.e. the newly pushed scrollback lines, the screen rows
+    // and the exit line.
+    let appended = kept.end.max(start);
+    let mut rows: Vec<String> = grid
+      .scrollback()
+      .range(appended - start..)
+      .cloned()
+      .collect();
+    rows.extend(grid.screen_lines());
+    if let Some(exit_line) = exit_line {
+      // The empty rows after the cursor are not needed any more.
+      while rows.last().is_some_and(|line| line.is_empty()) {
+        rows.pop();
+      }
+      rows.push(exit_line.to_string());
+    }
+
+    // Only replace the changed lines.
+    let prefix = self
+      .rows
+      .iter()
+      .zip(rows.iter())
+      .take_while(|(old, new)| old == new)
+      .count();
+    let suffix = self.rows[prefix..]
+      .iter()
+      .rev()
+      .zip(rows[prefix..].iter().rev())
+      .take_while(|(old, new)| old == new)
+      .count();
+    let old_end = self.rows.len() - suffix;
+    let new_end = rows.len() - suffix;
+    if prefix < old_end || prefix < new_end {
+      let base = kept.len();
+      text.set_lines(base + prefix..base + old_end, &rows[prefix..new_end]);
+    }
+
+    self.scrollback = if kept.is_empty() {
+      appended..end
+    } else {
+      kept.start..end
+    };
+    self.rows = rows[end - appended..].to_vec();
+    self.lines_count()
+  }
+}
@@ -0,0 +1,127 @@
+use super::grid::*;
+use super::parser::*;
+use super::sync::*;
+
+use crate::buf::opt::BufferLocalOptionsBuilder;
+use crate::buf::text::Text;
+use crate::prelude::*;
+use crate::test::log::init as test_log_init;
+
+use ropey::Rope;
+
+fn feed(grid: &mut Grid, parser: &mut Parser, bytes: &[u8]) {
+  for action in parser.advance(bytes).iter() {
+    grid.apply(action, |_| 1);
+  }
+}
+
+fn make_text() -> Text {
+  let opts = BufferLocalOptionsBuilder::default().build().unwrap();
+  Text::new(opts, U16Size::new(10, 10), Rope::new())
+}
+
+fn lines(text: &Text) -> Vec<String> {
+  text.get_lines(0..text.lines_count())
+}
+
+#[test]
+fn sync1() {
+  test_log_init();
+
+  let mut grid = Grid::new(10, 2, 100);
+  let mut parser = Parser::new();
+  let mut sync = TextSync::default();
+  let mut text = make_text();
+
+  feed(&mut grid, &mut parser, b"1\r\n2");
+  assert_eq!(sync.sync(&grid, None, &mut text), 2);
+  assert_eq!(lines(&text), grid.lines());
+
+  feed(&mut grid, &mut parser, b"\r\n3\r\n4\r\n5");
+  assert_eq!(sync.sync(&grid, None, &mut text), 5);
+  assert_eq!(lines(&text), vec!["1", "2", "3", "4", "5"]);
+  assert_eq!(lines(&text), grid.lines());
+
+  // Only the screen rows are changed.
+  feed(&mut grid, &mut parser, b"\x1b[1;1Hx");
+  assert_eq!(sync.sync(&grid, None, &mut text), 5);
+  assert_eq!(lines(&text), vec!["1", "2", "3", "x", "5"]);
+}
+
+#[test]
+fn scrollback_not_rewritten1() {
+  test_log_init();
+
+  let mut grid = Grid::new(10, 2, 100);
+  let mut parser = Parser::new();
+  let mut sync = TextSync::default();
+  let mut text = make_text();
+
+  feed(&mut grid, &mut parser, b"1\r\n2\r\n3");
+  sync.sync(&grid, None, &mut text);
+  assert_eq!(lines(&text), vec!["1", "2", "3"]);
+
+  // The scrollback lines in the text are not copied again.
+  text.set_lines(0..1, &["a".to_string()]);
+  feed(&mut grid, &mut parser, b"\r\n4");
+  sync.sync(&grid, None, &mut text);
+  assert_eq!(lines(&text), vec!["a", "2", "3", "4"]);
+}
+
+#[test]
+fn scrollback_removed1() {
+  test_log_init();
+
+  // At most 2 lines in the scrollback.
+  let mut grid = Grid::new(10, 2, 2);
+  let mut parser = Parser::new();
+  let mut sync = TextSync::default();
+  let mut text = make_text();
+
+  feed(&mut grid, &mut parser, b"1\r\n2\r\n3");
+  sync.sync(&grid, None, &mut text);
+  feed(&mut grid, &mut parser, b"\r\n4\r\n5\r\n6");
+  assert_eq!(sync.sync(&grid, None, &mut text), 4);
+  assert_eq!(lines(&text), vec!["3", "4", "5", "6"]);
+  assert_eq!(lines(&text), grid.lines());
+
+  // Clear the scrollback.
+  feed(&mut grid, &mut parser, b"\x1b[3J");
+  assert_eq!(sync.sync(&grid, None, &mut text), 2);
+  assert_eq!(lines(&text), vec!["5", "6"]);
+}
+
+#[test]
+fn exit1() {
+  test_log_init();
+
+  let mut grid = Grid::new(10, 4, 100);
+  let mut parser = Parser::new();
+  let mut sync = TextSync::default();
+  let mut text = make_text();
+
+  feed(&mut grid, &mut parser, b"1\r\n2\r\n");
+  sync.sync(&grid, None, &mut text);
+  assert_eq!(lines(&text), vec!["1", "2", "", ""]);
+
+  assert_eq!(sync.sync(&grid, Some("[Process exited 0]"), &mut text), 3);
+  assert_eq!(lines(&text), vec!["1", "2", "[Process exited 0]"]);
+}
+
+#[test]
+fn text_changed1() {
+  test_log_init();
+
+  let mut grid = Grid::new(10, 2, 100);
+  let mut parser = Parser::new();
+  let mut sync = TextSync::default();
+  let mut text = make_text();
+
+  feed(&mut grid, &mut parser, b"1\r\n2\r\n3");
+  sync.sync(&grid, None, &mut text);
+
+  // The lines are copied again if the text is changed by others.
+  text.set_lines(0..1, &[]);
+  assert_eq!(sync.sync(&grid, None, &mut text), 3);
+  assert_eq!(lines(&text), vec!["1", "2", "3"]);
+}
@@ -0,0 +1,70 @@
+use super::terminal::*;
+
+use crate::test::log::init as test_log_init;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+#[test]
+fn encode_key1() {
+  test_log_init();
+
+  let key = |code, modifiers| KeyEvent::new(code, modifiers);
+  assert_eq!(
+    encode_key(&key(KeyCode::Char('a'), KeyModifiers::NONE), false),
+    b"a".to_vec()
+  );
+  assert_eq!(
+    encode_key(&key(KeyCode::Char('你'), KeyModifiers::NONE), false),
+    "你".as_bytes().to_vec()
+  );
+  assert_eq!(
+    encode_key(&key(KeyCode::Char('c'), KeyModifiers::CONTROL), false),
+    vec![0x03]
+  );
+  assert_eq!(
+    encode_key(&key(KeyCode::Char('\\'), KeyModifiers::CONTROL), false),
+    vec![0x1c]
+  );
+  assert_eq!(
+    encode_key(&key(KeyCode::Char('b'), KeyModifiers::ALT), false),
+    b"\x1bb".to_vec()
+  );
+  assert_eq!(
+    encode_key(&key(KeyCode::Enter, KeyModifiers::NONE), false),
+    b"\r".to_vec()
+  );
+  assert_eq!(
+    encode_key(&key(KeyCode::Backspace, KeyModifiers::NONE), false),
+    vec![0x7f]
+  );
+}
+
+#[test]
+fn encode_key2() {
+  test_log_init();
+
+  let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+  assert_eq!(encode_key(&key(KeyCode::Up), false), b"\x1b[A".to_vec());
+  assert_eq!(encode_key(&key(KeyCode::Up), true), b"\x1bOA".to_vec());
+  assert_eq!(encode_key(&key(KeyCode::End), false), b"\x1b[F".to_vec());
+  assert_eq!(
+    encode_key(&key(KeyCode::Delete), false),
+    b"\x1b[3~".to_vec()
+  );
+  assert_eq!(encode_key(&key(KeyCode::F(1)), false), b"\x1bOP".to_vec());
+  assert_eq!(encode_key(&key(KeyCode::F(6)), false), b"\x1b[17~".to_vec());
+  assert_eq!(
+    encode_key(&key(KeyCode::F(12)), false),
+    b"\x1b[24~".to_vec()
+  );
+}
+
+#[test]
+fn encode_paste1() {
+  assert_eq!(encode_paste("ls\n", false), b"ls\r".to_vec());
+  assert_eq!(encode_paste("a\r\nb", false), b"a\rb".to_vec());
+  assert_eq!(
+    encode_paste("ls\n", true),
+    b"\x1b[200~ls\r\x1b[201~".to_vec()
+  );
+}
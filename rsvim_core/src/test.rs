@@ -7,6 +7,8 @@ pub mod buf;
 #[cfg(test)]
 pub mod constant;
 #[cfg(test)]
+pub mod fsm;
+#[cfg(test)]
 pub mod js;
 #[cfg(test)]
 pub mod log;
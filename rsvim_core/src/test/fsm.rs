@@ -0,0 +1,141 @@
+//! Stateful machine utils for testing.
+
+use crate::buf::BufferArc;
+use crate::buf::opt::BufferLocalOptionsBuilder;
+use crate::buf::selection::Selection;
+use crate::content::{TextContents, TextContentsArc};
+use crate::prelude::*;
+use crate::state::State;
+use crate::state::fsm::{
+  NormalStateful, Stateful, StatefulDataAccess, StatefulValue,
+};
+use crate::test::buf::{make_buffer_from_lines, make_buffers_manager};
+use crate::test::tree::make_tree_with_buffers_cmdline;
+use crate::ui::widget::window::WindowLocalOptionsBuilder;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use tokio::sync::mpsc::channel;
+
+/// Create the data access with 1 window (no wrap), 1 buffer with the `lines`, and 1
+/// command-line.
+pub fn make_data_access(
+  terminal_size: U16Size,
+  lines: Vec<&str>,
+) -> (BufferArc, TextContentsArc, StatefulDataAccess) {
+  let buf_opts = BufferLocalOptionsBuilder::default().build().unwrap();
+  let window_opts = WindowLocalOptionsBuilder::default()
+    .wrap(false)
+    .build()
+    .unwrap();
+  let buf = make_buffer_from_lines(terminal_size, buf_opts, lines);
+  let bufs = make_buffers_manager(buf_opts, vec![buf.clone()]);
+  let contents = TextContents::to_arc(TextContents::new(terminal_size));
+  let tree = make_tree_with_buffers_cmdline(
+    terminal_size,
+    window_opts,
+    bufs.clone(),
+    contents.clone(),
+  );
+  let (jsrt_tick_dispatcher, _jsrt_tick_queue) = channel(1);
+  let state = State::to_arc(State::new(jsrt_tick_dispatcher));
+  let data_access = StatefulDataAccess::new(
+    state,
+    tree,
+    bufs,
+    contents.clone(),
+    Event::FocusGained,
+  );
+  (buf, contents, data_access)
+}
+
+pub fn key(code: KeyCode) -> Event {
+  Event::Key(KeyEvent::new_with_kind(
+    code,
+    KeyModifiers::empty(),
+    KeyEventKind::Press,
+  ))
+}
+
+pub fn ctrl(c: char) -> Event {
+  Event::Key(KeyEvent::new_with_kind(
+    KeyCode::Char(c),
+    KeyModifiers::CONTROL,
+    KeyEventKind::Press,
+  ))
+}
+
+pub fn shift(code: KeyCode) -> Event {
+  Event::Key(KeyEvent::new_with_kind(
+    code,
+    KeyModifiers::SHIFT,
+    KeyEventKind::Press,
+  ))
+}
+
+pub fn chars(s: &str) -> Vec<Event> {
+  s.chars().map(|c| key(KeyCode::Char(c))).collect()
+}
+
+/// The `s` chars followed by the `codes` keys.
+pub fn with_keys(s: &str, codes: &[KeyCode]) -> Vec<Event> {
+  let mut events = chars(s);
+  events.extend(codes.iter().map(|code| key(*code)));
+  events
+}
+
+/// Send the events to the `stateful`, returns the last stateful.
+pub fn feed(
+  data_access: &StatefulDataAccess,
+  stateful: StatefulValue,
+  events: Vec<Event>,
+) -> StatefulValue {
+  let mut stateful = stateful;
+  for event in events {
+    stateful = stateful.handle(data_access.with_event(event));
+  }
+  stateful
+}
+
+/// Send the events to the stateful (starts from normal mode), returns the last stateful.
+pub fn press(
+  data_access: &StatefulDataAccess,
+  events: Vec<Event>,
+) -> StatefulValue {
+  feed(
+    data_access,
+    StatefulValue::NormalMode(NormalStateful::default()),
+    events,
+  )
+}
+
+/// Press the `s` chars (starts from normal mode), then assert the cursor position.
+#[track_caller]
+pub fn assert_cursor_after(
+  data_access: &StatefulDataAccess,
+  s: &str,
+  expect: (usize, usize),
+) {
+  press(data_access, chars(s));
+  assert_eq!(cursor(data_access), expect, "after pressing {s:?}");
+}
+
+pub fn text(buf: &BufferArc) -> String {
+  lock!(buf).text().rope().to_string()
+}
+
+pub fn selection(buf: &BufferArc) -> Option<Selection> {
+  *lock!(buf).selection()
+}
+
+pub fn register(contents: &TextContentsArc, name: char) -> Option<String> {
+  lock!(contents)
+    .registers()
+    .get(name)
+    .map(|value| value.to_string())
+}
+
+pub fn cursor(data_access: &StatefulDataAccess) -> (usize, usize) {
+  let tree = lock!(data_access.tree);
+  let cursor_viewport = tree.current_window().unwrap().cursor_viewport();
+  (cursor_viewport.line_idx(), cursor_viewport.char_idx())
+}
@@ -1,16 +1,28 @@
-use crate::buf::BuffersManager;
+use crate::buf::{BuffersManager, BuffersManagerArc};
 use crate::cli::CliOpt;
 use crate::content::TextContents;
 use crate::js::{JsRuntime, JsRuntimeOptions};
 use crate::prelude::*;
 use crate::state::State;
-use crate::ui::tree::Tree;
+use crate::ui::tree::{Tree, TreeArc};
 use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 use tokio::sync::mpsc::channel;
 
 pub fn make_js_runtime() -> JsRuntime {
   let canvas_size = U16Size::new(10, 10);
+  let tree = Tree::to_arc(Tree::new(canvas_size));
+  let buffers_manager = BuffersManager::to_arc(BuffersManager::new());
+  make_js_runtime_with_tree(canvas_size, tree, buffers_manager)
+}
+
+/// Create js runtime with the widget tree and the buffers, i.e. the tree from
+/// [`make_tree_with_buffers`](crate::test::tree::make_tree_with_buffers).
+pub fn make_js_runtime_with_tree(
+  canvas_size: U16Size,
+  tree: TreeArc,
+  buffers_manager: BuffersManagerArc,
+) -> JsRuntime {
   let (jsrt_tick_dispatcher, _jsrt_tick_queue) = channel(1);
   let (jsrt_to_mstr, _mstr_from_jsrt) = channel(*CHANNEL_BUF_SIZE);
   let (_mstr_to_jsrt, jsrt_from_mstr) = channel(*CHANNEL_BUF_SIZE);
@@ -18,8 +30,6 @@ pub fn make_js_runtime() -> JsRuntime {
   let cli_opt = CliOpt::new(false, vec![]);
   let state = State::to_arc(State::new(jsrt_tick_dispatcher.clone()));
 
-  let tree = Tree::to_arc(Tree::new(canvas_size));
-  let buffers_manager = BuffersManager::to_arc(BuffersManager::new());
   let text_contents = TextContents::to_arc(TextContents::new(canvas_size));
 
   let startup_moment = Instant::now();
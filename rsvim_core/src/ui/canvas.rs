@@ -43,6 +43,17 @@ impl Canvas {
     }
   }
 
+  /// Resize the canvas, i.e. the terminal is resized.
+  ///
+  /// NOTE: The terminal is cleared after resized, thus the previous frame is reset to empty cells
+  /// (with the previous size), and all the cells will be printed again by the brute-force
+  /// diff-algorithm.
+  pub fn resize(&mut self, size: U16Size) {
+    let prev_size = self.prev_frame.size();
+    self.frame = Frame::new(size, *self.frame.cursor());
+    self.prev_frame = Frame::new(prev_size, *self.prev_frame.cursor());
+  }
+
   // Current frame {
 
   /// Get current frame.
@@ -190,14 +201,13 @@ impl Canvas {
   /// 2. The end column index on the row if not found, i.e. the width of current frame.
   pub fn _next_same_cell_in_row(&self, row: u16, col: u16) -> u16 {
     let frame = self.frame();
-    let prev_frame = self.prev_frame();
 
     let mut col_end_at = col;
     while col_end_at < frame.size().width() {
       let pos: U16Pos = point!(x: col_end_at, y: row);
       let cell2 = frame.get_cell(pos);
-      let prev_cell2 = prev_frame.get_cell(pos);
-      if cell2 == prev_cell2 {
+      let prev_cell2 = self._prev_cell_at(pos);
+      if Some(cell2) == prev_cell2 {
         break;
       }
       col_end_at += 1;
@@ -219,20 +229,56 @@ impl Canvas {
       point!(x: start_col, y: row),
       end_col as usize - start_col as usize,
     );
-    let new_contents = new_cells
-      .iter()
-      .map(|c| c.symbol().clone())
-      .collect::<Vec<_>>()
-      .join("");
     shaders.push(ShaderCommand::CursorMoveTo(crossterm::cursor::MoveTo(
       start_col, row,
     )));
-    shaders.push(ShaderCommand::StylePrintString(crossterm::style::Print(
-      new_contents.to_string(),
-    )));
+
+    // Print the continuously cells with same styles together, the cells without styles are
+    // printed as plain strings.
+    for run in new_cells.chunk_by(|a, b| {
+      a.fg() == b.fg() && a.bg() == b.bg() && a.attrs() == b.attrs()
+    }) {
+      let contents = run
+        .iter()
+        .map(|c| c.symbol().clone())
+        .collect::<Vec<_>>()
+        .join("");
+      let first = &run[0];
+      if first.fg() == crossterm::style::Color::Reset
+        && first.bg() == crossterm::style::Color::Reset
+        && first.attrs() == crossterm::style::Attributes::default()
+      {
+        shaders.push(ShaderCommand::StylePrintString(crossterm::style::Print(
+          contents,
+        )));
+      } else {
+        let style = crossterm::style::ContentStyle {
+          foreground_color: Some(first.fg()),
+          background_color: Some(first.bg()),
+          underline_color: None,
+          attributes: first.attrs(),
+        };
+        shaders.push(ShaderCommand::StylePrintStyledContentString(
+          crossterm::style::PrintStyledContent(
+            crossterm::style::StyledContent::new(style, contents),
+          ),
+        ));
+      }
+    }
     shaders
   }
 
+  // The cell at the position on previous frame, or `None` if the position is outside of previous
+  // frame, i.e. the previous frame has a different size.
+  fn _prev_cell_at(&self, pos: U16Pos) -> Option<&Cell> {
+    let prev_size = self.prev_size();
+    if pos.x() < prev_size.width() && pos.y() < prev_size.height() {
+      Some(self.prev_frame.get_cell(pos))
+    } else {
+      None
+    }
+  }
+
   /// Brute force diff-algorithm, it iterates all cells on current frame, and compares with
   /// previous frame to find out the changed cells.
   ///
@@ -241,7 +287,6 @@ impl Canvas {
   pub fn _brute_force_diff(&mut self) -> Vec<ShaderCommand> {
     let frame = self.frame();
     let size = self.size();
-    let prev_frame = self.prev_frame();
     let _prev_size = self.prev_size();
     trace!("brute force diff, size:{:?}", size);
 
@@ -254,8 +299,8 @@ impl Canvas {
           // Skip unchanged columns
           let pos: U16Pos = point!(x: col, y: row);
           let cell = frame.get_cell(pos);
-          let prev_cell = prev_frame.get_cell(pos);
-          if cell == prev_cell {
+          let prev_cell = self._prev_cell_at(pos);
+          if Some(cell) == prev_cell {
             col += 1;
             continue;
           }
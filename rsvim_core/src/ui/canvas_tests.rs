@@ -348,3 +348,34 @@ fn diff1() {
     assert_eq!(*contents, "ABCD".to_string());
   }
 }
+
+#[test]
+fn resize1() {
+  test_log_init();
+  let mut can = Canvas::new(U16Size::new(4, 2));
+  let cells = |s: &str| s.chars().map(Cell::with_char).collect::<Vec<_>>();
+
+  can.frame_mut().set_cells_at(point!(x:0,y:0), cells("ABCD"));
+  can.shade();
+
+  // All the cells are printed again after resized, the cells outside of previous frame are always
+  // printed.
+  can.resize(U16Size::new(6, 3));
+  assert_eq!(can.size(), U16Size::new(6, 3));
+  assert_eq!(can.prev_size(), U16Size::new(4, 2));
+  can.frame_mut().set_cells_at(point!(x:0,y:0), cells("ABCD"));
+  can.frame_mut().set_cells_at(point!(x:4,y:2), cells("EF"));
+  let shader = can.shade();
+  let printed = shader
+    .iter()
+    .filter_map(|command| match command {
+      ShaderCommand::StylePrintString(crossterm::style::Print(contents)) => {
+        Some(contents.clone())
+      }
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+  info!("printed:{:?}", printed);
+  assert_eq!(printed, vec!["ABCD", "", "EF"]);
+  assert_eq!(can.prev_size(), U16Size::new(6, 3));
+}
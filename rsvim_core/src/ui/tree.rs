@@ -1,6 +1,9 @@
 //! The widget tree that manages all the widget components.
 
+use crate::buf::text::Text;
+use crate::buf::{BufferArc, BufferWk};
 use crate::prelude::*;
+use crate::state::autocmd::{AutoCmdEvent, FiredEvent, next_event_seq};
 use crate::ui::canvas::{Canvas, CanvasArc};
 use crate::ui::widget::Widgetable;
 use crate::ui::widget::command_line::CommandLine;
@@ -14,7 +17,9 @@ use crate::{inode_enum_dispatcher, widget_enum_dispatcher};
 // Re-export
 pub use internal::*;
 
+use compact_str::CompactString;
 use std::collections::BTreeSet;
+use std::sync::{Arc, Weak};
 // use tracing::trace;
 
 pub mod internal;
@@ -149,6 +154,10 @@ pub struct Tree {
 
   // Global-local options for windows.
   global_local_options: WindowLocalOptions,
+
+  // The events fired by the current window changes (i.e. the window switches, the cursor moves)
+  // that are not taken yet, with their sequence numbers and buffers.
+  fired: Vec<(u64, AutoCmdEvent, BufferWk)>,
 }
 
 arc_mutex_ptr!(Tree);
@@ -177,6 +186,7 @@ impl Tree {
       global_local_options: WindowLocalOptionsBuilder::default()
         .build()
         .unwrap(),
+      fired: vec![],
     }
   }
 
@@ -264,6 +274,7 @@ impl Tree {
       }
     }
     let old = self.current_window_id;
+    self.fire_window_switch(old, window_id);
     self.current_window_id = window_id;
     old
   }
@@ -327,6 +338,23 @@ impl Tree {
     }
   }
 
+  /// All the window widget IDs that show the `buffer`.
+  pub fn buffer_window_ids(&self, buffer: &BufferArc) -> Vec<TreeNodeId> {
+    self
+      .window_ids
+      .iter()
+      .filter(|window_id| {
+        self.window(**window_id).is_some_and(|window| {
+          window
+            .buffer()
+            .upgrade()
+            .is_some_and(|b| Arc::ptr_eq(&b, buffer))
+        })
+      })
+      .copied()
+      .collect()
+  }
+
   // Command-line widget.
   pub fn command_line(&self) -> Option<&CommandLine> {
     match self.command_line_id {
@@ -367,6 +395,95 @@ impl Tree {
 }
 // Widget }
 
+// Events {
+impl Tree {
+  fn fire(&mut self, event: AutoCmdEvent, buffer: BufferWk) {
+    self.fired.push((next_event_seq(), event, buffer));
+  }
+
+  // Fire the events of switching current window from `old` to `new`, the buffer events are fired
+  // only when the windows show different buffers.
+  fn fire_window_switch(
+    &mut self,
+    old: Option<TreeNodeId>,
+    new: Option<TreeNodeId>,
+  ) {
+    if old == new {
+      return;
+    }
+    let old_buffer = old.and_then(|id| self.window(id)).map(|w| w.buffer());
+    let new_buffer = new.and_then(|id| self.window(id)).map(|w| w.buffer());
+    let buffer_changed = match (&old_buffer, &new_buffer) {
+      (Some(old_buffer), Some(new_buffer)) => {
+        !Weak::ptr_eq(old_buffer, new_buffer)
+      }
+      (old_buffer, new_buffer) => old_buffer.is_some() || new_buffer.is_some(),
+    };
+    if let Some(old_buffer) = old_buffer {
+      if buffer_changed {
+        self.fire(AutoCmdEvent::BufLeave, old_buffer.clone());
+      }
+      self.fire(AutoCmdEvent::WinLeave, old_buffer);
+    }
+    if let Some(new_buffer) = new_buffer {
+      self.fire(AutoCmdEvent::WinEnter, new_buffer.clone());
+      if buffer_changed {
+        self.fire(AutoCmdEvent::BufEnter, new_buffer);
+      }
+    }
+  }
+
+  /// Show the `buffer` in the window, see [`Window::set_buffer`]. It fires `BufLeave` and
+  /// `BufEnter` if the window is current window.
+  pub fn set_window_buffer(
+    &mut self,
+    window_id: TreeNodeId,
+    buffer: BufferWk,
+    text: &Text,
+  ) {
+    debug_assert!(self.window(window_id).is_some());
+    let window = self.window_mut(window_id).unwrap();
+    let old_buffer = window.buffer();
+    window.set_buffer(buffer.clone(), text);
+    if self.current_window_id == Some(window_id)
+      && !Weak::ptr_eq(&old_buffer, &buffer)
+    {
+      self.fire(AutoCmdEvent::BufLeave, old_buffer);
+      self.fire(AutoCmdEvent::BufEnter, buffer);
+    }
+  }
+
+  /// Fire `CursorMoved` after the cursor inside the window is moved, it is fired only for current
+  /// window.
+  pub fn fire_cursor_moved(&mut self, window_id: TreeNodeId) {
+    if self.current_window_id != Some(window_id) {
+      return;
+    }
+    if let Some(window) = self.window(window_id) {
+      let buffer = window.buffer();
+      self.fire(AutoCmdEvent::CursorMoved, buffer);
+    }
+  }
+
+  /// Take the fired events, see
+  /// [`AutoCmds::fire_changes`](crate::state::autocmd::AutoCmds::fire_changes).
+  ///
+  /// NOTE: It locks the buffers of the events to get their IDs, so the buffers must not be locked
+  /// by the caller.
+  pub fn take_fired(&mut self) -> Vec<FiredEvent> {
+    std::mem::take(&mut self.fired)
+      .into_iter()
+      .map(|(seq, event, buffer)| FiredEvent {
+        seq,
+        event,
+        buffer_id: buffer.upgrade().map(|buffer| lock!(buffer).id()),
+        data: CompactString::default(),
+      })
+      .collect()
+  }
+}
+// Events }
+
 // Insert/Remove {
 impl Tree {
   fn insert_guard(&mut self, node: &TreeNode) {
@@ -409,8 +526,9 @@ impl Tree {
     }
     self.window_ids.remove(&id);
     if self.current_window_id == Some(id) {
-      if let Some(last_window_id) = self.window_ids.last() {
-        self.current_window_id = Some(*last_window_id);
+      if let Some(last_window_id) = self.window_ids.last().copied() {
+        self.fire_window_switch(Some(id), Some(last_window_id));
+        self.current_window_id = Some(last_window_id);
       }
     }
   }
@@ -449,6 +567,44 @@ impl Tree {
 }
 // Movement }
 
+// Resize {
+impl Tree {
+  /// Set the shape of node `id`, the windows and the command-line re-layout their children
+  /// widgets. This is a wrapper method on [`Itree::reshape`].
+  pub fn reshape(&mut self, id: TreeNodeId, shape: &IRect) -> Option<U16Rect> {
+    let actual_shape = self.base.reshape(id, shape);
+    for node_id in self.base.node_ids() {
+      match self.base.node_mut(node_id) {
+        Some(TreeNode::Window(window)) => window.update_layout(),
+        Some(TreeNode::CommandLine(cmdline)) => cmdline.update_layout(),
+        _ => { /* Skip */ }
+      }
+    }
+    actual_shape
+  }
+
+  /// Resize the tree to the canvas size, i.e. the terminal is resized. The windows cover the
+  /// canvas except the last row, the command-line is on the last row.
+  pub fn resize(&mut self, canvas_size: U16Size) {
+    let width = canvas_size.width() as isize;
+    let height = canvas_size.height() as isize;
+    let root_id = self.root_id();
+    self.reshape(root_id, &IRect::new((0, 0), (width, height)));
+
+    let window_shape =
+      IRect::new((0, 0), (width, height.saturating_sub(1).max(0)));
+    for window_id in self.window_ids.clone() {
+      self.reshape(window_id, &window_shape);
+    }
+    if let Some(cmdline_id) = self.command_line_id {
+      let cmdline_shape =
+        IRect::new((0, height.saturating_sub(1).max(0)), (width, height));
+      self.reshape(cmdline_id, &cmdline_shape);
+    }
+  }
+}
+// Resize }
+
 // Global options {
 impl Tree {
   pub fn global_options(&self) -> &WindowGlobalOptions {
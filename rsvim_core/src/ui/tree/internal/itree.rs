@@ -1,5 +1,6 @@
 //! Internal tree structure that implements the widget tree.
 
+use crate::geo_rect_as;
 use crate::prelude::*;
 use crate::ui::tree::internal::shapes;
 use crate::ui::tree::internal::{Inodeable, TreeNodeId};
@@ -784,3 +785,45 @@ where
   }
 }
 // Movement }
+
+// Resize {
+
+impl<T> Itree<T>
+where
+  T: Inodeable,
+{
+  /// Set the shape of node `id`, i.e. move and resize it.
+  ///
+  /// NOTE:
+  /// 1. The position is relatively based on the node parent. For the root node, its actual shape
+  ///    is the shape itself.
+  /// 2. This operation also updates the shape/position of all descendant nodes, similar to
+  ///    [`insert`](Itree::insert) method.
+  ///
+  /// # Returns
+  ///
+  /// 1. The new actual shape if successfully.
+  /// 2. `None` if the node `id` doesn't exist.
+  pub fn reshape(&mut self, id: TreeNodeId, shape: &IRect) -> Option<U16Rect> {
+    let parent_id = self.parent_id(id);
+    let node = self.nodes.get_mut(&id)?;
+    node.set_shape(shape);
+    match parent_id {
+      Some(parent_id) => self.update_descendant_attributes(id, parent_id),
+      None => {
+        node.set_actual_shape(&geo_rect_as!(shape, u16));
+        self.update_children_attributes(id);
+      }
+    }
+    self.nodes.get(&id).map(|node| *node.actual_shape())
+  }
+
+  /// Update the attributes of all the descendants under the `id` node, i.e. after its actual shape
+  /// is changed.
+  pub fn update_children_attributes(&mut self, id: TreeNodeId) {
+    for dnode_id in self.children_ids(id).iter() {
+      self.update_descendant_attributes(*dnode_id, id);
+    }
+  }
+}
+// Resize }
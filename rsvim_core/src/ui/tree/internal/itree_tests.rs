@@ -1063,3 +1063,55 @@ fn bounded_move_to1() {
     assert!(actual == expect);
   }
 }
+
+#[test]
+fn reshape1() {
+  test_log_init();
+
+  let s1 = IRect::new((0, 0), (20, 20));
+  let n1 = TestValue::new(1, s1);
+  let nid1 = n1.id();
+
+  let s2 = IRect::new((0, 0), (20, 20));
+  let n2 = TestValue::new(2, s2);
+  let nid2 = n2.id();
+
+  let s3 = IRect::new((5, 5), (15, 15));
+  let n3 = TestValue::new(3, s3);
+  let nid3 = n3.id();
+
+  let mut tree = Itree::new(n1);
+  tree.insert(nid1, n2);
+  tree.insert(nid2, n3);
+
+  // Shrink the root, the descendants are clipped.
+  let actual = tree.reshape(nid1, &IRect::new((0, 0), (10, 8)));
+  assert_eq!(actual, Some(U16Rect::new((0, 0), (10, 8))));
+  assert_eq!(
+    *tree.node(nid2).unwrap().actual_shape(),
+    U16Rect::new((0, 0), (10, 8))
+  );
+  assert_eq!(
+    *tree.node(nid3).unwrap().actual_shape(),
+    U16Rect::new((5, 5), (10, 8))
+  );
+
+  // Move and resize the child.
+  let actual = tree.reshape(nid2, &IRect::new((2, 1), (8, 8)));
+  assert_eq!(actual, Some(U16Rect::new((2, 1), (8, 8))));
+  assert_eq!(
+    *tree.node(nid2).unwrap().shape(),
+    IRect::new((2, 1), (8, 8))
+  );
+  assert_eq!(
+    *tree.node(nid3).unwrap().actual_shape(),
+    U16Rect::new((7, 6), (8, 8))
+  );
+
+  // Enlarge the root again.
+  tree.reshape(nid1, &IRect::new((0, 0), (30, 30)));
+  assert_eq!(
+    *tree.node(nid3).unwrap().actual_shape(),
+    U16Rect::new((7, 6), (8, 8))
+  );
+}
@@ -1,6 +1,13 @@
 use super::tree::*;
 
+use crate::buf::opt::BufferLocalOptionsBuilder;
+use crate::content::TextContents;
 use crate::prelude::*;
+use crate::state::ops::{Operation, cursor_ops};
+use crate::test::buf::{make_buffer_from_lines, make_buffers_manager};
+use crate::test::log::init as test_log_init;
+use crate::test::tree::make_tree_with_buffers_cmdline;
+use crate::ui::widget::window::WindowLocalOptionsBuilder;
 
 #[test]
 fn new() {
@@ -9,3 +16,78 @@ fn new() {
   assert!(tree.is_empty());
   assert!(tree.len() == 1);
 }
+
+#[test]
+fn resize1() {
+  test_log_init();
+  let terminal_size = U16Size::new(10, 10);
+  let lines: Vec<String> = (0..20).map(|i| format!("line{i}\n")).collect();
+  let buf_opts = BufferLocalOptionsBuilder::default().build().unwrap();
+  let buf = make_buffer_from_lines(
+    terminal_size,
+    buf_opts,
+    lines.iter().map(|line| line.as_str()).collect(),
+  );
+  let bufs = make_buffers_manager(buf_opts, vec![buf.clone()]);
+  let contents = TextContents::to_arc(TextContents::new(terminal_size));
+  let win_opts = WindowLocalOptionsBuilder::default().build().unwrap();
+  let tree = make_tree_with_buffers_cmdline(
+    terminal_size,
+    win_opts,
+    bufs.clone(),
+    contents.clone(),
+  );
+  let mut tree = lock!(tree);
+  let window_id = tree.current_window_id().unwrap();
+  let buf = lock!(buf);
+  cursor_ops::cursor_move(
+    &mut tree,
+    window_id,
+    buf.text(),
+    Operation::CursorMoveTo((2, 8)),
+    false,
+  );
+
+  // Shrink.
+  tree.resize(U16Size::new(8, 5));
+  let window = tree.current_window().unwrap();
+  assert_eq!(*window.actual_shape(), U16Rect::new((0, 0), (8, 4)));
+  assert_eq!(
+    *window.content().actual_shape(),
+    U16Rect::new((0, 0), (8, 4))
+  );
+  let cmdline = tree.command_line().unwrap();
+  assert_eq!(*cmdline.actual_shape(), U16Rect::new((0, 4), (8, 5)));
+  assert_eq!(
+    *cmdline.content().actual_shape(),
+    U16Rect::new((1, 4), (8, 5))
+  );
+
+  // The cursor is still visible.
+  cursor_ops::update_viewport_after_resized(
+    &mut tree,
+    window_id,
+    buf.text(),
+    false,
+  );
+  let window = tree.current_window().unwrap();
+  let viewport = window.viewport();
+  let cursor_viewport = window.cursor_viewport();
+  assert_eq!(cursor_viewport.line_idx(), 8);
+  assert_eq!(cursor_viewport.char_idx(), 2);
+  assert!(viewport.start_line_idx() <= 8 && 8 < viewport.end_line_idx());
+  assert_eq!(viewport.end_line_idx() - viewport.start_line_idx(), 4);
+  let cursor_shape = *window.cursor().unwrap().actual_shape();
+  assert!(cursor_shape.min().y < 4);
+  assert_eq!(cursor_shape.min().x, 2);
+
+  // Enlarge.
+  tree.resize(U16Size::new(20, 12));
+  let window = tree.current_window().unwrap();
+  assert_eq!(
+    *window.content().actual_shape(),
+    U16Rect::new((0, 0), (20, 11))
+  );
+  let cmdline = tree.command_line().unwrap();
+  assert_eq!(*cmdline.actual_shape(), U16Rect::new((0, 11), (20, 12)));
+}
@@ -1,5 +1,6 @@
 //! Fundamental viewport for all kinds of buffer typeset/rendering in UI widgets.
 
+use crate::buf::selection::Selection;
 use crate::buf::text::Text;
 use crate::prelude::*;
 use crate::ui::canvas::Canvas;
@@ -718,7 +719,13 @@ impl Viewport {
     self.lines.is_empty()
   }
 
-  pub fn draw(&self, text: &Text, actual_shape: &U16Rect, canvas: &mut Canvas) {
-    draw::draw(self, text, actual_shape, canvas);
+  pub fn draw(
+    &self,
+    text: &Text,
+    selection: Option<&Selection>,
+    actual_shape: &U16Rect,
+    canvas: &mut Canvas,
+  ) {
+    draw::draw(self, text, selection, actual_shape, canvas);
   }
 }
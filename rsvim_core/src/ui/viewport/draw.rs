@@ -1,18 +1,23 @@
 //! Draw a text (with its viewport) on a canvas (with its actual shape).
 
+use crate::buf::selection::Selection;
 use crate::buf::text::Text;
 use crate::prelude::*;
 use crate::ui::canvas::{Canvas, Cell};
 use crate::ui::viewport::Viewport;
 
+use crossterm::style::{Attribute, Attributes};
 use geo::point;
 use std::convert::From;
 use tracing::trace;
 
 /// Draw a text (with its viewport) on a canvas (with its actual shape).
+///
+/// The chars inside the visual `selection` are highlighted in reverse video.
 pub fn draw(
   viewport: &Viewport,
   text: &Text,
+  selection: Option<&Selection>,
   actual_shape: &U16Rect,
   canvas: &mut Canvas,
 ) {
@@ -129,7 +134,7 @@ pub fn draw(
             //   the `好` char, the following 1 cell is `""` empty string.
 
             if unicode_width > 0 {
-              let cells = if unicode_width > 1 {
+              let mut cells = if unicode_width > 1 {
                 let cell = Cell::with_symbol(unicode_symbol);
                 // Unicode width > 1
                 let mut v = vec![cell];
@@ -143,6 +148,13 @@ pub fn draw(
                 // Unicode width = 1
                 vec![cell]
               };
+              if selection
+                .is_some_and(|sel| sel.contains(text, line_idx, char_idx))
+              {
+                for cell in cells.iter_mut() {
+                  cell.set_attrs(Attributes::from(Attribute::Reverse));
+                }
+              }
 
               let cell_upos =
                 point!(x: col_idx + upos.x(), y: row_idx + upos.y());
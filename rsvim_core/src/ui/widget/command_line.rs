@@ -25,7 +25,11 @@ pub use indicator::CommandLineIndicatorSymbol;
 
 pub mod content;
 pub mod indicator;
+pub mod message;
+pub mod recording;
 pub mod root;
+pub mod showcmd;
+pub mod wildmenu;
 
 #[cfg(test)]
 pub mod indicator_tests;
@@ -153,6 +157,29 @@ impl Widgetable for CommandLine {
       // trace!("Draw window:{:?}", node);
       node.draw(canvas);
     }
+
+    // The wildmenu and the message pager are drawn above the command-line, over the windows.
+    let contents = self.text_contents.upgrade().unwrap();
+    let contents = lock!(contents);
+    wildmenu::draw(canvas, &contents, self.actual_shape());
+    message::draw(
+      canvas,
+      &contents,
+      self.actual_shape(),
+      self.cursor_id.is_some(),
+    );
+    recording::draw(
+      canvas,
+      &contents,
+      self.actual_shape(),
+      self.cursor_id.is_some(),
+    );
+    showcmd::draw(
+      canvas,
+      &contents,
+      self.actual_shape(),
+      self.cursor_id.is_some(),
+    );
   }
 }
 
@@ -394,3 +421,26 @@ impl CommandLine {
   }
 }
 // Cursor }
+
+// Layout {
+impl CommandLine {
+  /// Re-layout the children widgets after the command-line actual shape is changed, i.e. the
+  /// indicator is on the first column and the content fills the rest.
+  pub fn update_layout(&mut self) {
+    let actual_shape = *self.actual_shape();
+    let width = actual_shape.width() as isize;
+    let height = actual_shape.height() as isize;
+    let indicator_shape = IRect::new((0, 0), (std::cmp::min(width, 1), height));
+    let content_shape =
+      IRect::new((std::cmp::min(width, 1), 0), (width, height));
+    if let Some(indicator) = self.base.node_mut(self.indicator_id) {
+      indicator.set_shape(&indicator_shape);
+    }
+    if let Some(content) = self.base.node_mut(self.content_id) {
+      content.set_shape(&content_shape);
+    }
+    let root_id = self.base.root_id();
+    self.base.update_children_attributes(root_id);
+  }
+}
+// Layout }
@@ -0,0 +1,150 @@
+//! Command-line messages, i.e. the message shown in the command-line row, and the pager that takes
+//! over the bottom rows when the messages are taller than one line.
+
+use crate::buf::unicode;
+use crate::content::TextContents;
+use crate::content::message::MessageLevel;
+use crate::prelude::*;
+use crate::ui::canvas::{Canvas, Cell};
+
+use compact_str::{CompactString, ToCompactString};
+use crossterm::style::{Attribute, Attributes, Color};
+use geo::point;
+
+/// The prompt when the pager has more lines, i.e. the `more-prompt` of Vim.
+pub const MORE_PROMPT: &str = "-- More --";
+
+/// The prompt when the pager shows the last line, i.e. the `hit-enter` prompt of Vim.
+pub const HIT_ENTER_PROMPT: &str = "Press ENTER or type command to continue";
+
+fn _message_style(level: MessageLevel) -> (Color, Attributes) {
+  // Same with the `ErrorMsg`, `WarningMsg` highlights of Vim.
+  match level {
+    MessageLevel::Info => (Color::Reset, Attributes::default()),
+    MessageLevel::Warn => (Color::Yellow, Attributes::default()),
+    MessageLevel::Error => (Color::Red, Attributes::default()),
+  }
+}
+
+fn _prompt_style() -> (Color, Attributes) {
+  // Same with the `MoreMsg` and `Question` highlights of Vim.
+  (Color::Green, Attributes::from(Attribute::Bold))
+}
+
+/// Set a row of cells starting from `min_x`, the rest of the row is cleared. Returns the next
+/// column after the text.
+///
+/// NOTE: Wide chars are followed by empty cells, the same as window content.
+pub fn set_row(
+  canvas: &mut Canvas,
+  contents: &TextContents,
+  y: u16,
+  min_x: u16,
+  max_x: u16,
+  s: &str,
+  (fg, attrs): (Color, Attributes),
+) -> u16 {
+  let opts = contents.command_line_content().options();
+  let mut col = min_x;
+  for c in s.chars() {
+    let width = unicode::char_width(opts, c) as u16;
+    if width == 0 {
+      continue;
+    }
+    if col + width > max_x {
+      break;
+    }
+    let symbol = unicode::char_symbol(opts, c);
+    canvas.frame_mut().set_cell(
+      point!(x: col, y: y),
+      Cell::new(symbol, fg, Color::Reset, attrs),
+    );
+    for i in 1..width {
+      canvas.frame_mut().set_cell(
+        point!(x: col + i, y: y),
+        Cell::new(CompactString::const_new(""), fg, Color::Reset, attrs),
+      );
+    }
+    col += width;
+  }
+  for x in col..max_x {
+    canvas.frame_mut().set_cell(
+      point!(x: x, y: y),
+      Cell::new(' '.to_compact_string(), fg, Color::Reset, attrs),
+    );
+  }
+  col
+}
+
+/// Draw the shown messages over the command-line with `cmdline_shape`.
+///
+/// When the pager is started, the shown lines take over the rows above the command-line, and the
+/// command-line row shows the prompt. Otherwise the (single line) message is shown in the
+/// command-line row, unless the command-line is being edited, i.e. `editing` is `true`.
+pub fn draw(
+  canvas: &mut Canvas,
+  contents: &TextContents,
+  cmdline_shape: &U16Rect,
+  editing: bool,
+) {
+  let messages = contents.messages();
+  let min_x = cmdline_shape.min().x;
+  let max_x = cmdline_shape.max().x;
+  let bottom = cmdline_shape.min().y;
+
+  match messages.pager_top() {
+    Some(top) => {
+      let lines = messages.shown_lines();
+      let height = bottom as usize;
+      let rows = lines.len().saturating_sub(top).min(height);
+      let first_y = bottom - rows as u16;
+      for (i, (level, line)) in lines.iter().skip(top).take(rows).enumerate() {
+        set_row(
+          canvas,
+          contents,
+          first_y + i as u16,
+          min_x,
+          max_x,
+          line,
+          _message_style(*level),
+        );
+      }
+
+      let prompt = if messages.pager_at_end(height.max(1)) {
+        HIT_ENTER_PROMPT
+      } else {
+        MORE_PROMPT
+      };
+      let col = set_row(
+        canvas,
+        contents,
+        bottom,
+        min_x,
+        max_x,
+        prompt,
+        _prompt_style(),
+      );
+
+      // Put the cursor after the prompt.
+      let mut cursor = *canvas.frame().cursor();
+      cursor.set_pos(point!(x: col.min(max_x.saturating_sub(1)), y: bottom));
+      canvas.frame_mut().set_cursor(cursor);
+    }
+    None => {
+      if editing {
+        return;
+      }
+      if let Some((level, line)) = messages.shown_lines().last() {
+        set_row(
+          canvas,
+          contents,
+          bottom,
+          min_x,
+          max_x,
+          line,
+          _message_style(*level),
+        );
+      }
+    }
+  }
+}
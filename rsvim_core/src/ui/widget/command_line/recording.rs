@@ -0,0 +1,44 @@
+//! Command-line recording, i.e. the `recording @q` shown at the left side of the command-line row
+//! when a macro is being recorded.
+//!
+//! See: <https://vimhelp.org/repeat.txt.html#recording>.
+
+use crate::content::TextContents;
+use crate::prelude::*;
+use crate::ui::canvas::Canvas;
+use crate::ui::widget::command_line::message;
+
+use crossterm::style::{Attributes, Color};
+
+/// Draw the recording register over the command-line with `cmdline_shape`.
+///
+/// Nothing is drawn if no macro is being recorded, the command-line is being edited (i.e.
+/// `editing` is `true`), the message pager is started, or a message is shown.
+pub fn draw(
+  canvas: &mut Canvas,
+  contents: &TextContents,
+  cmdline_shape: &U16Rect,
+  editing: bool,
+) {
+  let name = match contents.recording() {
+    Some(name) => name,
+    None => return,
+  };
+  let messages = contents.messages();
+  if editing
+    || messages.pager_top().is_some()
+    || !messages.shown_lines().is_empty()
+  {
+    return;
+  }
+
+  message::set_row(
+    canvas,
+    contents,
+    cmdline_shape.min().y,
+    cmdline_shape.min().x,
+    cmdline_shape.max().x,
+    &format!("recording @{name}"),
+    (Color::Reset, Attributes::default()),
+  );
+}
@@ -0,0 +1,50 @@
+//! Command-line showcmd, i.e. the pending keys of an incomplete command shown at the right side of
+//! the command-line row.
+//!
+//! See: <https://vimhelp.org/options.txt.html#%27showcmd%27>.
+
+use crate::content::TextContents;
+use crate::prelude::*;
+use crate::ui::canvas::Canvas;
+use crate::ui::widget::command_line::message;
+
+use crossterm::style::{Attributes, Color};
+
+/// The columns of the showcmd, i.e. the last 10 chars of the pending keys are shown.
+pub const SHOWCMD_COLUMNS: u16 = 10;
+
+/// Draw the pending keys over the command-line with `cmdline_shape`, they're placed before the
+/// last column of the command-line row.
+///
+/// Nothing is drawn if there's no pending keys, the command-line is being edited (i.e. `editing`
+/// is `true`), or the message pager is started.
+pub fn draw(
+  canvas: &mut Canvas,
+  contents: &TextContents,
+  cmdline_shape: &U16Rect,
+  editing: bool,
+) {
+  let showcmd = contents.showcmd();
+  if showcmd.is_empty() || editing || contents.messages().pager_top().is_some()
+  {
+    return;
+  }
+
+  let max_x = cmdline_shape.max().x.saturating_sub(1);
+  let min_x =
+    std::cmp::max(max_x.saturating_sub(SHOWCMD_COLUMNS), cmdline_shape.min().x);
+  let skip = showcmd
+    .chars()
+    .count()
+    .saturating_sub((max_x - min_x) as usize);
+  let s: String = showcmd.chars().skip(skip).collect();
+  message::set_row(
+    canvas,
+    contents,
+    cmdline_shape.min().y,
+    min_x,
+    max_x,
+    &s,
+    (Color::Reset, Attributes::default()),
+  );
+}
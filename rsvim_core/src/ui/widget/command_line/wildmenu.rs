@@ -0,0 +1,162 @@
+//! Command-line wildmenu, i.e. the completion matches shown above the command-line.
+
+use crate::buf::unicode;
+use crate::content::TextContents;
+use crate::prelude::*;
+use crate::ui::canvas::{Canvas, Cell};
+
+use compact_str::ToCompactString;
+use crossterm::style::{Attribute, Attributes, Color};
+use geo::point;
+
+/// Max height of the popup menu.
+const POPUP_MAX_HEIGHT: usize = 10;
+
+fn _cell(c: char, selected: bool) -> Cell {
+  // The matches are shown in reverse video, the selected match is shown in normal video, same
+  // with the `StatusLine` and `WildMenu` highlights of Vim.
+  let attrs = if selected {
+    Attributes::from(Attribute::Bold)
+  } else {
+    Attributes::from(Attribute::Reverse)
+  };
+  Cell::new(c.to_compact_string(), Color::Reset, Color::Reset, attrs)
+}
+
+/// Set a line of cells starting from `(x, y)`, returns the next column after the line.
+///
+/// NOTE: Wide chars are followed by empty cells, the same as window content.
+fn _set_line(
+  canvas: &mut Canvas,
+  contents: &TextContents,
+  x: u16,
+  y: u16,
+  max_x: u16,
+  s: &str,
+  selected: bool,
+) -> u16 {
+  let opts = contents.command_line_content().options();
+  let mut col = x;
+  for c in s.chars() {
+    let width = unicode::char_width(opts, c) as u16;
+    if width == 0 {
+      continue;
+    }
+    if col + width > max_x {
+      break;
+    }
+    canvas
+      .frame_mut()
+      .set_cell(point!(x: col, y: y), _cell(c, selected));
+    for i in 1..width {
+      let mut cell = _cell(' ', selected);
+      cell.set_symbol("".to_compact_string());
+      canvas.frame_mut().set_cell(point!(x: col + i, y: y), cell);
+    }
+    col += width;
+  }
+  col
+}
+
+/// Draw the wildmenu above the command-line with `cmdline_shape`, it draws nothing if the
+/// wildmenu is not visible.
+///
+/// By default the matches are shown in a horizontal bar on the row above the command-line, if
+/// 'wildoptions' contains `pum`, they're shown in a vertical popup menu that starts at the column
+/// of the completed text.
+pub fn draw(
+  canvas: &mut Canvas,
+  contents: &TextContents,
+  cmdline_shape: &U16Rect,
+) {
+  let wildmenu = contents.command_line_wildmenu();
+  if !wildmenu.visible() || cmdline_shape.min().y == 0 {
+    return;
+  }
+  let opts = contents.command_line_content().options();
+  let items = wildmenu.items();
+  let selected = wildmenu.selected();
+  let min_x = cmdline_shape.min().x;
+  let max_x = cmdline_shape.max().x;
+  let bottom = cmdline_shape.min().y;
+
+  if wildmenu.popup() {
+    let height = items.len().min(bottom as usize).min(POPUP_MAX_HEIGHT);
+    // Scroll to make sure the selected match is shown.
+    let first = match selected {
+      Some(i) if i >= height => i + 1 - height,
+      _ => 0,
+    };
+    let width = items
+      .iter()
+      .map(|item| unicode::str_width(opts, item.abbr()))
+      .max()
+      .unwrap_or(0) as u16
+      + 2;
+    let text = contents.command_line_content();
+    let start_col = if text.rope().len_lines() > 0 {
+      text.width_before(0, wildmenu.start_char_idx()) as u16
+    } else {
+      0
+    };
+    // Skip the indicator, and move left if it overflows.
+    let left = (min_x + 1 + start_col)
+      .min(max_x.saturating_sub(width))
+      .max(min_x);
+    let right = (left + width).min(max_x);
+    let top = bottom - height as u16;
+    for (row, i) in (first..first + height).enumerate() {
+      let y = top + row as u16;
+      let is_selected = selected == Some(i);
+      let line = format!(" {} ", items[i].abbr());
+      let col = _set_line(canvas, contents, left, y, right, &line, is_selected);
+      for x in col..right {
+        canvas
+          .frame_mut()
+          .set_cell(point!(x: x, y: y), _cell(' ', is_selected));
+      }
+    }
+  } else {
+    let y = bottom - 1;
+    let width = max_x - min_x;
+    // Scroll to make sure the selected match is shown, i.e. find the first match that makes the
+    // selected match fits in the bar.
+    let widths: Vec<u16> = items
+      .iter()
+      .map(|item| unicode::str_width(opts, item.abbr()) as u16 + 2)
+      .collect();
+    let mut first = 0_usize;
+    if let Some(i) = selected {
+      while first < i && widths[first..=i].iter().sum::<u16>() > width {
+        first += 1;
+      }
+    }
+
+    let mut col = min_x;
+    if first > 0 {
+      col = _set_line(canvas, contents, col, y, max_x, "< ", false);
+    }
+    for (i, item) in items.iter().enumerate().skip(first) {
+      if col + widths[i] > max_x {
+        col = _set_line(canvas, contents, col, y, max_x, ">", false);
+        break;
+      }
+      col = _set_line(canvas, contents, col, y, max_x, " ", false);
+      col = _set_line(
+        canvas,
+        contents,
+        col,
+        y,
+        max_x,
+        item.abbr(),
+        selected == Some(i),
+      );
+      col = _set_line(canvas, contents, col, y, max_x, " ", false);
+    }
+    for x in col..max_x {
+      canvas
+        .frame_mut()
+        .set_cell(point!(x: x, y: y), _cell(' ', false));
+    }
+  }
+}
@@ -1,6 +1,7 @@
 //! Window.
 
 use crate::buf::BufferWk;
+use crate::buf::text::Text;
 use crate::prelude::*;
 use crate::ui::canvas::Canvas;
 use crate::ui::tree::*;
@@ -151,6 +152,17 @@ impl Window {
     self.buffer.clone()
   }
 
+  /// Bind another buffer, the viewport is reset to the top-left of the buffer `text`.
+  pub fn set_buffer(&mut self, buffer: BufferWk, text: &Text) {
+    let actual_shape = *self.content().actual_shape();
+    let viewport = Viewport::view(&self.options, text, &actual_shape, 0, 0);
+    let cursor_viewport = CursorViewport::from_top_left(&viewport, text);
+    self.buffer = buffer.clone();
+    self.content_mut().set_buffer(buffer);
+    self.set_viewport(Viewport::to_arc(viewport));
+    self.set_cursor_viewport(CursorViewport::to_arc(cursor_viewport));
+  }
+
   /// Cursor widget ID.
   pub fn cursor_id(&self) -> Option<TreeNodeId> {
     self.cursor_id
@@ -305,3 +317,25 @@ impl Window {
   }
 }
 // Cursor }
+
+// Layout {
+impl Window {
+  /// Re-layout the children widgets after the window actual shape is changed, i.e. the content
+  /// widget always fills the window.
+  pub fn update_layout(&mut self) {
+    let actual_shape = *self.actual_shape();
+    let content_shape = IRect::new(
+      (0, 0),
+      (
+        actual_shape.width() as isize,
+        actual_shape.height() as isize,
+      ),
+    );
+    if let Some(content) = self.base.node_mut(self.content_id) {
+      content.set_shape(&content_shape);
+    }
+    let root_id = self.base.root_id();
+    self.base.update_children_attributes(root_id);
+  }
+}
+// Layout }
@@ -34,6 +34,10 @@ impl WindowContent {
   pub fn set_viewport(&mut self, viewport: ViewportWk) {
     self.viewport = viewport;
   }
+
+  pub fn set_buffer(&mut self, buffer: BufferWk) {
+    self.buffer = buffer;
+  }
 }
 
 inode_impl!(WindowContent, base);
@@ -45,6 +49,11 @@ impl Widgetable for WindowContent {
     let buffer = lock!(buffer);
     let viewport = self.viewport.upgrade().unwrap();
 
-    viewport.draw(buffer.text(), actual_shape, canvas);
+    viewport.draw(
+      buffer.text(),
+      buffer.selection().as_ref(),
+      actual_shape,
+      canvas,
+    );
   }
 }